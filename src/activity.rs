@@ -0,0 +1,390 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+use tracing::warn;
+
+use crate::api::AppState;
+use crate::config::{ActivityStoreType, AppConfig};
+use crate::{ClassifyError, ClassifyResult, Content, Recommendation};
+
+/// Interest tags looked up for recommendations, the strongest first
+const INTEREST_TAGS: usize = 10;
+
+/// Recent text searches whose nearest content is recommended
+const INTEREST_SEARCHES: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ActivityKind {
+    /// Content queried by tags
+    Query,
+    /// A semantic search or a question
+    Search,
+    /// Content opened as text or as page snapshot
+    View,
+    /// Tags set on content
+    Tag,
+}
+
+/// Something a caller did that tells what they are interested in
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Activity {
+    pub kind: ActivityKind,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Text of a search or question
+    #[serde(default)]
+    pub text: Option<String>,
+    #[serde(default)]
+    pub content_id: Option<String>,
+    pub at: DateTime<Utc>,
+}
+
+impl Activity {
+    fn new(kind: ActivityKind) -> Self {
+        Self {
+            kind,
+            tags: Vec::new(),
+            text: None,
+            content_id: None,
+            at: Utc::now(),
+        }
+    }
+
+    pub fn query(tags: &[String]) -> Self {
+        Self {
+            tags: tags.to_vec(),
+            ..Self::new(ActivityKind::Query)
+        }
+    }
+
+    pub fn search(text: &str) -> Self {
+        Self {
+            text: Some(text.to_string()),
+            ..Self::new(ActivityKind::Search)
+        }
+    }
+
+    pub fn view(content: &Content) -> Self {
+        Self {
+            tags: content.tags.clone(),
+            content_id: Some(content.id.to_string()),
+            ..Self::new(ActivityKind::View)
+        }
+    }
+
+    pub fn tag(content_id: &str, tags: &[String]) -> Self {
+        Self {
+            tags: tags.to_vec(),
+            content_id: Some(content_id.to_string()),
+            ..Self::new(ActivityKind::Tag)
+        }
+    }
+}
+
+/// Recent activity per caller, the oldest dropped beyond the history length
+#[async_trait]
+pub trait ActivityStore: Send + Sync {
+    async fn record(&self, caller: &str, activity: &Activity) -> ClassifyResult<()>;
+
+    /// Activity of the caller, the newest first
+    async fn recent(&self, caller: &str) -> ClassifyResult<Vec<Activity>>;
+}
+
+/// Activity kept in memory, lost on restart
+pub struct MemoryActivityStore {
+    callers: Mutex<HashMap<String, VecDeque<Activity>>>,
+    history: usize,
+}
+
+impl MemoryActivityStore {
+    pub fn new(history: usize) -> Self {
+        Self {
+            callers: Mutex::new(HashMap::new()),
+            history,
+        }
+    }
+}
+
+#[async_trait]
+impl ActivityStore for MemoryActivityStore {
+    async fn record(&self, caller: &str, activity: &Activity) -> ClassifyResult<()> {
+        let mut callers = self.callers.lock().unwrap();
+        let activities = callers.entry(caller.to_string()).or_default();
+        activities.push_front(activity.clone());
+        activities.truncate(self.history);
+        Ok(())
+    }
+
+    async fn recent(&self, caller: &str) -> ClassifyResult<Vec<Activity>> {
+        let callers = self.callers.lock().unwrap();
+        Ok(callers
+            .get(caller)
+            .map(|activities| activities.iter().cloned().collect())
+            .unwrap_or_default())
+    }
+}
+
+/// Activity kept in Redis as a list per caller, shared by all instances
+#[cfg(feature = "redis")]
+pub struct RedisActivityStore {
+    connection: crate::storage::redis::SharedConnection,
+    prefix: String,
+    history: usize,
+}
+
+#[cfg(feature = "redis")]
+impl RedisActivityStore {
+    pub fn with_connection(
+        connection: crate::storage::redis::SharedConnection,
+        prefix: Option<&str>,
+        history: usize,
+    ) -> Self {
+        crate::storage::connections::register("redis", &connection);
+        Self {
+            connection,
+            prefix: prefix.unwrap_or("classify:").to_string(),
+            history,
+        }
+    }
+
+    fn caller_key(&self, caller: &str) -> String {
+        format!("{}activity:{}", self.prefix, caller)
+    }
+}
+
+#[cfg(feature = "redis")]
+#[async_trait]
+impl ActivityStore for RedisActivityStore {
+    async fn record(&self, caller: &str, activity: &Activity) -> ClassifyResult<()> {
+        let key = self.caller_key(caller);
+        let mut pipe = redis::pipe();
+        pipe.cmd("LPUSH")
+            .arg(&key)
+            .arg(serde_json::to_string(activity)?)
+            .ignore();
+        pipe.cmd("LTRIM")
+            .arg(&key)
+            .arg(0)
+            .arg(self.history.saturating_sub(1))
+            .ignore();
+
+        let mut conn = self.connection.lock().await;
+        pipe.query_async::<_, ()>(&mut *conn)
+            .await
+            .map_err(|e| ClassifyError::StorageError(format!("Failed to record activity: {}", e)))
+    }
+
+    async fn recent(&self, caller: &str) -> ClassifyResult<Vec<Activity>> {
+        let entries: Vec<String> = {
+            let mut conn = self.connection.lock().await;
+            redis::cmd("LRANGE")
+                .arg(self.caller_key(caller))
+                .arg(0)
+                .arg(-1)
+                .query_async(&mut *conn)
+                .await
+                .map_err(|e| {
+                    ClassifyError::StorageError(format!("Failed to read activity: {}", e))
+                })?
+        };
+
+        Ok(entries
+            .iter()
+            .filter_map(|entry| serde_json::from_str(entry).ok())
+            .collect())
+    }
+}
+
+/// What a caller's recent activity says about their interests
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Interests {
+    /// Weight of every tag queried, viewed or set, recent activity weighing more
+    pub tags: HashMap<String, f64>,
+    /// Recent searches with their weight, the newest first
+    pub searches: Vec<(String, f64)>,
+    /// Content the caller has opened
+    pub viewed: HashSet<String>,
+}
+
+impl Interests {
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty() && self.searches.is_empty()
+    }
+
+    /// Tags with the most weight first, ties in name order
+    pub fn top_tags(&self, limit: usize) -> Vec<(String, f64)> {
+        let mut tags: Vec<(String, f64)> = self
+            .tags
+            .iter()
+            .map(|(tag, weight)| (tag.clone(), *weight))
+            .collect();
+        tags.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        tags.truncate(limit);
+        tags
+    }
+}
+
+/// Interests from activity, the newest first. The newest activity weighs 1,
+/// the weight going down evenly to almost nothing for the oldest.
+pub fn interests(activities: &[Activity]) -> Interests {
+    let mut interests = Interests::default();
+    let count = activities.len() as f64;
+
+    for (position, activity) in activities.iter().enumerate() {
+        let weight = (count - position as f64) / count;
+        for tag in &activity.tags {
+            *interests.tags.entry(tag.clone()).or_default() += weight;
+        }
+        if let Some(text) = &activity.text {
+            if interests.searches.len() < INTEREST_SEARCHES
+                && !interests.searches.iter().any(|(seen, _)| seen == text)
+            {
+                interests.searches.push((text.clone(), weight));
+            }
+        }
+        if activity.kind == ActivityKind::View {
+            if let Some(id) = &activity.content_id {
+                interests.viewed.insert(id.clone());
+            }
+        }
+    }
+    interests
+}
+
+/// Log of what each caller queries, searches, opens and tags, behind
+/// `/recommendations`
+pub struct ActivityLog {
+    store: Arc<dyn ActivityStore>,
+}
+
+impl ActivityLog {
+    pub fn new(store: Arc<dyn ActivityStore>) -> Self {
+        Self { store }
+    }
+
+    /// Add to the caller's activity. Failures are logged, they don't fail the request.
+    pub async fn record(&self, caller: &str, activity: Activity) {
+        if let Err(e) = self.store.record(caller, &activity).await {
+            warn!("Failed to record activity of {}: {}", caller, e);
+        }
+    }
+
+    pub async fn interests(&self, caller: &str) -> ClassifyResult<Interests> {
+        Ok(interests(&self.store.recent(caller).await?))
+    }
+}
+
+/// Content the caller hasn't opened, scored by the weight of the interest
+/// tags it has and its similarity to recent searches, the best first. Without
+/// any activity the newest unopened content is recommended.
+pub async fn recommend(
+    state: &AppState,
+    interests: &Interests,
+    limit: usize,
+    can_read: impl Fn(&Content) -> bool,
+) -> ClassifyResult<Vec<Recommendation>> {
+    let unread = |content: &Content| {
+        !interests.viewed.contains(&content.id.to_string()) && can_read(content)
+    };
+
+    if interests.is_empty() {
+        let mut newest: Vec<Content> = state
+            .content_storage
+            .list()
+            .await?
+            .into_iter()
+            .filter(|content| unread(content))
+            .collect();
+        newest.sort_by_key(|content| std::cmp::Reverse(content.created_at));
+        return Ok(newest
+            .into_iter()
+            .take(limit)
+            .map(|content| Recommendation {
+                content,
+                score: 0.0,
+                reasons: Vec::new(),
+            })
+            .collect());
+    }
+
+    let mut scores: HashMap<String, (f64, Vec<String>)> = HashMap::new();
+    for (tag, weight) in interests.top_tags(INTEREST_TAGS) {
+        for id in state.tag_storage.find_by_tag(&tag).await? {
+            let (score, reasons) = scores.entry(id).or_default();
+            *score += weight;
+            reasons.push(tag.clone());
+        }
+    }
+
+    if let Some(index) = &state.semantic_index {
+        for (text, weight) in &interests.searches {
+            match index
+                .search(text, state.namespace.as_deref(), limit * 2)
+                .await
+            {
+                Ok(nearest) => {
+                    for (id, similarity) in nearest.into_iter().filter(|(_, s)| *s > 0.0) {
+                        scores.entry(id).or_default().0 += weight * f64::from(similarity);
+                    }
+                }
+                Err(e) => warn!(
+                    "Recommending without searches, semantic search failed: {}",
+                    e
+                ),
+            }
+        }
+    }
+
+    let ids: Vec<String> = scores.keys().cloned().collect();
+    let mut recommendations: Vec<Recommendation> = state
+        .content_storage
+        .get_many(&ids)
+        .await?
+        .into_iter()
+        .filter(|content| unread(content))
+        .filter_map(|content| {
+            let (score, reasons) = scores.remove(&content.id.to_string())?;
+            Some(Recommendation {
+                content,
+                score,
+                reasons,
+            })
+        })
+        .collect();
+
+    recommendations.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then(b.content.created_at.cmp(&a.content.created_at))
+    });
+    recommendations.truncate(limit);
+    Ok(recommendations)
+}
+
+/// Create the activity log configured by `ACTIVITY_STORE`
+pub async fn create_activity_log(config: &AppConfig) -> ClassifyResult<Option<ActivityLog>> {
+    let Some(activity) = &config.activity else {
+        return Ok(None);
+    };
+
+    let store: Arc<dyn ActivityStore> = match activity.store {
+        ActivityStoreType::Memory => Arc::new(MemoryActivityStore::new(activity.history)),
+        #[cfg(feature = "redis")]
+        ActivityStoreType::Redis => Arc::new(RedisActivityStore::with_connection(
+            crate::storage::redis::connect_tag_storage(&config.tag_storage).await?,
+            config.tag_storage.redis_prefix.as_deref(),
+            activity.history,
+        )),
+        #[cfg(not(feature = "redis"))]
+        ActivityStoreType::Redis => {
+            return Err(ClassifyError::ConfigError(
+                "The Redis activity store requires building with the redis feature".to_string(),
+            ))
+        }
+    };
+
+    Ok(Some(ActivityLog::new(store)))
+}
@@ -0,0 +1,61 @@
+use crate::activity::{interests, Activity, ActivityStore, MemoryActivityStore};
+use crate::Content;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(tags: &[&str]) -> Vec<String> {
+        tags.iter().map(|tag| tag.to_string()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_memory_store_keeps_newest_history() {
+        let store = MemoryActivityStore::new(2);
+        for tag in ["rust", "pasta", "travel"] {
+            store
+                .record("key:1", &Activity::query(&tags(&[tag])))
+                .await
+                .unwrap();
+        }
+        store
+            .record("key:2", &Activity::search("bread"))
+            .await
+            .unwrap();
+
+        let recent = store.recent("key:1").await.unwrap();
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].tags, tags(&["travel"]));
+        assert_eq!(recent[1].tags, tags(&["pasta"]));
+        assert!(store.recent("key:3").await.unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_interests_weigh_recent_activity_more() {
+        let content = Content::new("Fresh pasta".to_string()).with_tags(tags(&["pasta"]));
+        let activities = vec![
+            Activity::view(&content),
+            Activity::search("homemade bread"),
+            Activity::query(&tags(&["rust", "pasta"])),
+            Activity::tag("1234", &tags(&["rust"])),
+        ];
+
+        let interests = interests(&activities);
+        assert_eq!(interests.tags["pasta"], 1.0 + 0.5);
+        assert_eq!(interests.tags["rust"], 0.5 + 0.25);
+        assert_eq!(
+            interests.searches,
+            vec![("homemade bread".to_string(), 0.75)]
+        );
+        assert!(interests.viewed.contains(&content.id.to_string()));
+        assert!(!interests.viewed.contains("1234"));
+
+        let top = interests.top_tags(1);
+        assert_eq!(top, vec![("pasta".to_string(), 1.5)]);
+    }
+
+    #[test]
+    fn test_no_activity_has_no_interests() {
+        assert!(interests(&[]).is_empty());
+    }
+}
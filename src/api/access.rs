@@ -0,0 +1,54 @@
+use axum::{async_trait, extract::FromRequestParts, http::request::Parts};
+use std::convert::Infallible;
+
+use crate::api::middleware::{Caller, Tenant};
+use crate::{Content, Visibility};
+
+/// Tenant and caller of a request, to check access to content that the
+/// tenant's storage returned
+#[derive(Debug, Clone, Default)]
+pub struct Access {
+    pub namespace: Option<String>,
+    /// Unset when the routes are served without authentication
+    pub caller: Option<String>,
+}
+
+impl Access {
+    /// Whether the caller sees all content its tenant storage returns, private content included
+    pub fn sees_private(&self) -> bool {
+        matches!(self.caller.as_deref(), None | Some("admin"))
+    }
+
+    /// Private content is only seen by the caller that marked it private and the main API key
+    pub fn can_read(&self, content: &Content) -> bool {
+        if content.visibility != Visibility::Private {
+            return true;
+        }
+
+        self.sees_private() || content.owner == self.caller
+    }
+
+    /// Content shared by other tenants, or made public by them, is read-only
+    pub fn can_modify(&self, content: &Content) -> bool {
+        let owned = self.namespace.is_none() || content.namespace == self.namespace;
+        owned && self.can_read(content)
+    }
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for Access {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Self {
+            namespace: parts
+                .extensions
+                .get::<Tenant>()
+                .map(|Tenant(namespace)| namespace.clone()),
+            caller: parts
+                .extensions
+                .get::<Caller>()
+                .map(|Caller(caller)| caller.clone()),
+        })
+    }
+}
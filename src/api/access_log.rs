@@ -0,0 +1,93 @@
+use axum::{
+    body::{Body, HttpBody},
+    extract::State,
+    http::{header::CONTENT_LENGTH, Request},
+    response::Response,
+};
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::info;
+
+use crate::api::{AppState, Caller};
+
+/// Replace the values of sensitive query parameters, such as share link signatures
+pub fn redact_query(query: &str, redact_params: &[String]) -> String {
+    query
+        .split('&')
+        .map(|pair| match pair.split_once('=') {
+            Some((name, _))
+                if redact_params
+                    .iter()
+                    .any(|param| param.eq_ignore_ascii_case(name)) =>
+            {
+                format!("{}=REDACTED", name)
+            }
+            _ => pair.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// Log one line per request with its method, path, status, latency, caller and
+/// payload sizes. Bodies and headers, which carry content and API keys, are never
+/// logged.
+pub async fn access_log(
+    State(state): State<Arc<AppState>>,
+    req: Request<Body>,
+    next: axum::middleware::Next,
+) -> Response {
+    let Some(config) = state.access_log.clone() else {
+        return next.run(req).await;
+    };
+
+    let started = Instant::now();
+    let method = req.method().clone();
+    let path = match req.uri().query() {
+        Some(query) => format!(
+            "{}?{}",
+            req.uri().path(),
+            redact_query(query, &config.redact_params)
+        ),
+        None => req.uri().path().to_string(),
+    };
+    let request_bytes = req
+        .headers()
+        .get(CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<u64>().ok())
+        .unwrap_or(0);
+
+    let response = next.run(req).await;
+
+    let caller = response
+        .extensions()
+        .get::<Caller>()
+        .map(|Caller(caller)| caller.as_str())
+        .unwrap_or("-");
+    // Streamed bodies have no known size
+    let response_bytes = response.body().size_hint().exact();
+
+    info!(
+        target: "classify::access",
+        method = %method,
+        path = %path,
+        status = response.status().as_u16(),
+        latency_ms = started.elapsed().as_millis() as u64,
+        caller,
+        request_bytes,
+        response_bytes,
+        "Request finished"
+    );
+
+    response
+}
+
+/// Copy the caller identified by `validate_api_key` to the response, for the access log
+pub async fn expose_caller(req: Request<Body>, next: axum::middleware::Next) -> Response {
+    let caller = req.extensions().get::<Caller>().cloned();
+    let mut response = next.run(req).await;
+    if let Some(caller) = caller {
+        response.extensions_mut().insert(caller);
+    }
+    response
+}
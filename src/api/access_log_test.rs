@@ -0,0 +1,21 @@
+use crate::api::access_log::redact_query;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_query() {
+        let redact = vec!["signature".to_string(), "token".to_string()];
+
+        assert_eq!(
+            redact_query("expires=1700000000&signature=abc123", &redact),
+            "expires=1700000000&signature=REDACTED"
+        );
+        assert_eq!(
+            redact_query("Token=secret&tags=rust", &redact),
+            "Token=REDACTED&tags=rust"
+        );
+        assert_eq!(redact_query("tags=rust,web", &redact), "tags=rust,web");
+    }
+}
@@ -0,0 +1,90 @@
+use futures::future::BoxFuture;
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+use crate::api::AppState;
+use crate::config::AppConfig;
+use crate::shutdown;
+use crate::{ClassifyError, ClassifyResult, ComponentHealth, HealthResponse};
+
+/// Longest a single component may take to answer before it counts as down
+const CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+async fn check(name: &str, check: BoxFuture<'_, ClassifyResult<()>>) -> ComponentHealth {
+    let started = Instant::now();
+    let result = match tokio::time::timeout(CHECK_TIMEOUT, check).await {
+        Ok(result) => result,
+        Err(_) => Err(ClassifyError::StorageError(format!(
+            "No answer within {}s",
+            CHECK_TIMEOUT.as_secs()
+        ))),
+    };
+    let latency_ms = started.elapsed().as_millis() as u64;
+
+    match result {
+        Ok(()) => ComponentHealth {
+            healthy: true,
+            latency_ms,
+        },
+        Err(e) => {
+            // Details stay in the logs, the endpoint can be reached without a key
+            warn!("Health check of {} failed: {}", name, e);
+            ComponentHealth {
+                healthy: false,
+                latency_ms,
+            }
+        }
+    }
+}
+
+/// Check the storages, and the classifier when asked to, concurrently
+pub async fn check_components(state: &AppState, include_classifier: bool) -> HealthResponse {
+    let content_storage = check("content_storage", state.content_storage.health_check());
+    let tag_storage = check("tag_storage", state.tag_storage.health_check());
+    let classifier = async {
+        if include_classifier {
+            Some(check("classifier", state.classifier.health_check()).await)
+        } else {
+            None
+        }
+    };
+    let (content_storage, tag_storage, classifier) =
+        tokio::join!(content_storage, tag_storage, classifier);
+
+    let mut components = BTreeMap::new();
+    components.insert("content_storage".to_string(), content_storage);
+    components.insert("tag_storage".to_string(), tag_storage);
+    if let Some(classifier) = classifier {
+        components.insert("classifier".to_string(), classifier);
+    }
+
+    HealthResponse {
+        healthy: components.values().all(|component| component.healthy),
+        components,
+    }
+}
+
+/// Whether the instance should receive traffic: the storages can be reached, the
+/// configuration is valid and it isn't shutting down. The classifier is left out,
+/// it is checked on each request and its provider being down is no reason to stop
+/// serving queries.
+pub async fn check_readiness(state: &AppState) -> HealthResponse {
+    let mut health = check_components(state, false).await;
+    health.healthy &= !shutdown::is_shutting_down();
+
+    let config = check(
+        "config",
+        Box::pin(async {
+            match AppConfig::reload_error() {
+                Some(e) => Err(ClassifyError::ConfigError(e)),
+                None => Ok(()),
+            }
+        }),
+    )
+    .await;
+    health.healthy &= config.healthy;
+    health.components.insert("config".to_string(), config);
+
+    health
+}
@@ -0,0 +1,196 @@
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+use crate::config::JwtConfig;
+use crate::{ClassifyError, ClassifyResult};
+
+/// Minimum time between fetches triggered by tokens signed with an unknown key
+const MIN_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Caller identified by a valid JWT
+#[derive(Debug, Clone, PartialEq)]
+pub struct JwtClaims {
+    pub subject: Option<String>,
+    /// Tenant namespace from the configured namespace claim
+    pub namespace: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenIdConfiguration {
+    jwks_uri: String,
+}
+
+struct CachedKeys {
+    keys: JwkSet,
+    fetched_at: Instant,
+}
+
+/// Validates bearer tokens against the signing keys published by an OIDC issuer
+pub struct JwtValidator {
+    config: JwtConfig,
+    http_client: reqwest::Client,
+    keys: RwLock<Option<CachedKeys>>,
+}
+
+impl JwtValidator {
+    pub fn new(config: JwtConfig) -> Self {
+        Self {
+            config,
+            http_client: reqwest::Client::new(),
+            keys: RwLock::new(None),
+        }
+    }
+
+    /// Use a fixed set of signing keys until they expire from the cache
+    pub fn with_keys(config: JwtConfig, keys: JwkSet) -> Self {
+        Self {
+            keys: RwLock::new(Some(CachedKeys {
+                keys,
+                fetched_at: Instant::now(),
+            })),
+            ..Self::new(config)
+        }
+    }
+
+    /// Validate a token, returning `None` when it is not acceptable. Errors are
+    /// only returned when the signing keys can't be fetched.
+    pub async fn validate(&self, token: &str) -> ClassifyResult<Option<JwtClaims>> {
+        let header = match decode_header(token) {
+            Ok(header) => header,
+            Err(e) => {
+                warn!("Invalid JWT header: {}", e);
+                return Ok(None);
+            }
+        };
+
+        let Some(kid) = header.kid else {
+            warn!("JWT without key id");
+            return Ok(None);
+        };
+
+        let Some((key, algorithm)) = self.find_key(&kid).await? else {
+            warn!("JWT signed with unknown key {}", kid);
+            return Ok(None);
+        };
+
+        // Only accept the algorithm the key is meant for, whatever the header claims
+        let algorithm = algorithm.unwrap_or(header.alg);
+        if algorithm != header.alg {
+            warn!("JWT algorithm {:?} does not match key {}", header.alg, kid);
+            return Ok(None);
+        }
+
+        let mut validation = Validation::new(algorithm);
+        validation.set_issuer(&[&self.config.issuer]);
+        match &self.config.audience {
+            Some(audience) => validation.set_audience(&[audience]),
+            None => validation.validate_aud = false,
+        }
+
+        let claims = match decode::<HashMap<String, Value>>(token, &key, &validation) {
+            Ok(data) => data.claims,
+            Err(e) => {
+                warn!("Invalid JWT: {}", e);
+                return Ok(None);
+            }
+        };
+
+        let namespace = match &self.config.namespace_claim {
+            Some(claim) => match claims.get(claim).and_then(Value::as_str) {
+                Some(namespace) if !namespace.is_empty() => Some(namespace.to_string()),
+                // A token meant for a tenant must not fall back to unscoped access
+                _ => {
+                    warn!("JWT without {} claim", claim);
+                    return Ok(None);
+                }
+            },
+            None => None,
+        };
+
+        Ok(Some(JwtClaims {
+            subject: claims.get("sub").and_then(Value::as_str).map(String::from),
+            namespace,
+        }))
+    }
+
+    async fn find_key(
+        &self,
+        kid: &str,
+    ) -> ClassifyResult<Option<(DecodingKey, Option<Algorithm>)>> {
+        let stale = {
+            let cached = self.keys.read().await;
+            match cached.as_ref() {
+                Some(cached) => {
+                    let age = cached.fetched_at.elapsed();
+                    if age < self.config.jwks_cache_ttl {
+                        if let Some(key) = decoding_key(&cached.keys, kid) {
+                            return Ok(Some(key));
+                        }
+                    }
+                    age >= MIN_REFRESH_INTERVAL || age >= self.config.jwks_cache_ttl
+                }
+                None => true,
+            }
+        };
+
+        if !stale {
+            return Ok(None);
+        }
+
+        let keys = self.fetch_keys().await?;
+        let key = decoding_key(&keys, kid);
+        *self.keys.write().await = Some(CachedKeys {
+            keys,
+            fetched_at: Instant::now(),
+        });
+
+        Ok(key)
+    }
+
+    async fn fetch_keys(&self) -> ClassifyResult<JwkSet> {
+        let jwks_url = match &self.config.jwks_url {
+            Some(url) => url.clone(),
+            None => {
+                let url = format!("{}/.well-known/openid-configuration", self.config.issuer);
+                self.get_json::<OpenIdConfiguration>(&url).await?.jwks_uri
+            }
+        };
+
+        let keys = self.get_json::<JwkSet>(&jwks_url).await?;
+        info!(
+            "Fetched {} JWT signing keys from {}",
+            keys.keys.len(),
+            jwks_url
+        );
+        Ok(keys)
+    }
+
+    async fn get_json<T: serde::de::DeserializeOwned>(&self, url: &str) -> ClassifyResult<T> {
+        self.http_client
+            .get(url)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| ClassifyError::HttpError(format!("Failed to fetch {}: {}", url, e)))?
+            .json()
+            .await
+            .map_err(|e| ClassifyError::HttpError(format!("Failed to parse {}: {}", url, e)))
+    }
+}
+
+fn decoding_key(keys: &JwkSet, kid: &str) -> Option<(DecodingKey, Option<Algorithm>)> {
+    let jwk = keys.find(kid)?;
+    let key = DecodingKey::from_jwk(jwk).ok()?;
+    let algorithm = jwk
+        .common
+        .key_algorithm
+        .and_then(|alg| Algorithm::from_str(&alg.to_string()).ok());
+    Some((key, algorithm))
+}
@@ -0,0 +1,107 @@
+use crate::api::jwt::{JwtClaims, JwtValidator};
+use crate::config::JwtConfig;
+use crate::ClassifyResult;
+use base64::Engine;
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
+use serde_json::{json, Value};
+use std::time::Duration;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &[u8] = b"a-signing-secret-for-tests-only";
+    const ISSUER: &str = "https://sso.example.com";
+
+    fn config(namespace_claim: Option<&str>) -> JwtConfig {
+        JwtConfig {
+            issuer: ISSUER.to_string(),
+            audience: Some("classify".to_string()),
+            jwks_url: None,
+            jwks_cache_ttl: Duration::from_secs(3600),
+            namespace_claim: namespace_claim.map(String::from),
+        }
+    }
+
+    fn validator(namespace_claim: Option<&str>) -> JwtValidator {
+        let keys: JwkSet = serde_json::from_value(json!({
+            "keys": [{
+                "kty": "oct",
+                "kid": "test-key",
+                "alg": "HS256",
+                "k": base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(SECRET),
+            }]
+        }))
+        .unwrap();
+        JwtValidator::with_keys(config(namespace_claim), keys)
+    }
+
+    fn token(claims: Value) -> String {
+        let mut header = Header::new(Algorithm::HS256);
+        header.kid = Some("test-key".to_string());
+        encode(&header, &claims, &EncodingKey::from_secret(SECRET)).unwrap()
+    }
+
+    fn claims() -> Value {
+        json!({
+            "iss": ISSUER,
+            "aud": "classify",
+            "sub": "alice",
+            "tenant": "acme",
+            "exp": chrono::Utc::now().timestamp() + 600,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_valid_token() -> ClassifyResult<()> {
+        let claims = validator(Some("tenant"))
+            .validate(&token(claims()))
+            .await?
+            .expect("token is valid");
+
+        assert_eq!(
+            claims,
+            JwtClaims {
+                subject: Some("alice".to_string()),
+                namespace: Some("acme".to_string()),
+            }
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rejected_tokens() -> ClassifyResult<()> {
+        let validator = validator(None);
+
+        let mut expired = claims();
+        expired["exp"] = json!(chrono::Utc::now().timestamp() - 600);
+        assert!(validator.validate(&token(expired)).await?.is_none());
+
+        let mut other_issuer = claims();
+        other_issuer["iss"] = json!("https://evil.example.com");
+        assert!(validator.validate(&token(other_issuer)).await?.is_none());
+
+        let mut other_audience = claims();
+        other_audience["aud"] = json!("something-else");
+        assert!(validator.validate(&token(other_audience)).await?.is_none());
+
+        let mut header = Header::new(Algorithm::HS256);
+        header.kid = Some("test-key".to_string());
+        let forged = encode(&header, &claims(), &EncodingKey::from_secret(b"wrong")).unwrap();
+        assert!(validator.validate(&forged).await?.is_none());
+
+        assert!(validator.validate("not-a-jwt").await?.is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_missing_namespace_claim_is_rejected() -> ClassifyResult<()> {
+        let validator = validator(Some("tenant"));
+
+        let mut claims = claims();
+        claims.as_object_mut().unwrap().remove("tenant");
+        assert!(validator.validate(&token(claims)).await?.is_none());
+        Ok(())
+    }
+}
@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use tracing::warn;
+
+use crate::config::AuthLockoutConfig;
+
+/// Longest a response to a failed authentication is held back
+const MAX_FAILURE_DELAY: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy)]
+struct Failures {
+    count: u32,
+    first_at: Instant,
+    locked_until: Option<Instant>,
+    /// Lockouts in a row, each one lasts twice as long as the one before
+    lockouts: u32,
+}
+
+impl Failures {
+    fn new(now: Instant) -> Self {
+        Self {
+            count: 0,
+            first_at: now,
+            locked_until: None,
+            lockouts: 0,
+        }
+    }
+}
+
+/// Failed authentications per client address, kept in memory by each instance
+pub struct AuthLockout {
+    config: AuthLockoutConfig,
+    failures: Mutex<HashMap<IpAddr, Failures>>,
+}
+
+impl AuthLockout {
+    pub fn new(config: AuthLockoutConfig) -> Self {
+        Self {
+            config,
+            failures: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// How long the address is still locked out, if it is
+    pub async fn locked_for(&self, ip: IpAddr) -> Option<Duration> {
+        let now = Instant::now();
+        let failures = self.failures.lock().await;
+
+        let locked_for = failures
+            .get(&ip)
+            .and_then(|failures| failures.locked_until)
+            .filter(|locked_until| *locked_until > now)
+            .map(|locked_until| locked_until - now);
+
+        if locked_for.is_some() {
+            metrics::counter!("auth_lockout_refused_total").increment(1);
+        }
+        locked_for
+    }
+
+    /// Length of a lockout after `lockouts` earlier ones, doubling up to the maximum
+    pub fn lockout_duration(&self, lockouts: u32) -> Duration {
+        self.config
+            .duration
+            .saturating_mul(2u32.saturating_pow(lockouts))
+            .min(self.config.max_duration.max(self.config.duration))
+    }
+
+    /// How long to hold back the response to the given failure within the window,
+    /// doubling with every failure
+    pub fn failure_delay(&self, count: u32) -> Duration {
+        self.config
+            .delay
+            .saturating_mul(2u32.saturating_pow(count.saturating_sub(1)))
+            .min(MAX_FAILURE_DELAY)
+    }
+
+    /// Count a failed authentication, locking the address out when it reaches the
+    /// limit. Returns how long to hold back the response.
+    pub async fn record_failure(&self, ip: IpAddr) -> Duration {
+        let now = Instant::now();
+        let mut failures = self.failures.lock().await;
+        metrics::counter!("auth_failures_total").increment(1);
+
+        // Forget addresses whose failures have run out and that haven't been
+        // locked out for as long as the longest lockout
+        failures.retain(|_, failures| {
+            now.duration_since(failures.first_at) < self.config.window
+                || failures
+                    .locked_until
+                    .is_some_and(|until| until + self.config.max_duration > now)
+        });
+
+        let entry = failures.entry(ip).or_insert(Failures::new(now));
+        if entry.locked_until.is_some_and(|until| until > now) {
+            // Requests that slip through while the address is locked out don't count
+            return self.failure_delay(entry.count.max(1));
+        }
+        if now.duration_since(entry.first_at) >= self.config.window {
+            entry.count = 0;
+            entry.first_at = now;
+        }
+        if entry
+            .locked_until
+            .is_some_and(|until| until + self.config.max_duration <= now)
+        {
+            entry.lockouts = 0;
+        }
+
+        entry.count += 1;
+        let delay = self.failure_delay(entry.count);
+
+        if entry.count >= self.config.max_failures {
+            let duration = self.lockout_duration(entry.lockouts);
+            warn!(
+                "Locking out {} for {}s after {} failed authentications",
+                ip,
+                duration.as_secs(),
+                entry.count
+            );
+            metrics::counter!("auth_lockouts_total").increment(1);
+            entry.locked_until = Some(now + duration);
+            entry.lockouts = entry.lockouts.saturating_add(1);
+            entry.count = 0;
+            entry.first_at = now;
+        }
+
+        let locked = failures
+            .values()
+            .filter(|failures| failures.locked_until.is_some_and(|until| until > now))
+            .count();
+        metrics::gauge!("auth_locked_addresses").set(locked as f64);
+
+        delay
+    }
+}
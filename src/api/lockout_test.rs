@@ -0,0 +1,84 @@
+use crate::api::lockout::AuthLockout;
+use crate::config::AuthLockoutConfig;
+use std::net::IpAddr;
+use std::time::Duration;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lockout(window: Duration) -> AuthLockout {
+        AuthLockout::new(AuthLockoutConfig {
+            max_failures: 3,
+            window,
+            duration: Duration::from_secs(60),
+            max_duration: Duration::from_secs(300),
+            delay: Duration::from_millis(100),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_locks_out_after_max_failures() {
+        let lockout = lockout(Duration::from_secs(60));
+        let ip: IpAddr = "203.0.113.7".parse().unwrap();
+        let other: IpAddr = "203.0.113.8".parse().unwrap();
+
+        lockout.record_failure(ip).await;
+        lockout.record_failure(ip).await;
+        assert!(lockout.locked_for(ip).await.is_none());
+
+        lockout.record_failure(ip).await;
+        let locked_for = lockout.locked_for(ip).await.expect("locked out");
+        assert!(locked_for > Duration::from_secs(50));
+
+        assert!(lockout.locked_for(other).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_failures_outside_window_are_forgotten() {
+        let lockout = lockout(Duration::ZERO);
+        let ip: IpAddr = "2001:db8::1".parse().unwrap();
+
+        for _ in 0..5 {
+            lockout.record_failure(ip).await;
+        }
+        assert!(lockout.locked_for(ip).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_failure_delay_escalates() {
+        let lockout = lockout(Duration::from_secs(60));
+        let ip: IpAddr = "203.0.113.9".parse().unwrap();
+
+        assert_eq!(lockout.record_failure(ip).await, Duration::from_millis(100));
+        assert_eq!(lockout.record_failure(ip).await, Duration::from_millis(200));
+        assert_eq!(lockout.failure_delay(20), Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn test_repeated_lockouts_last_longer() {
+        let lockout = lockout(Duration::from_secs(60));
+
+        assert_eq!(lockout.lockout_duration(0), Duration::from_secs(60));
+        assert_eq!(lockout.lockout_duration(1), Duration::from_secs(120));
+        assert_eq!(lockout.lockout_duration(2), Duration::from_secs(240));
+        assert_eq!(lockout.lockout_duration(3), Duration::from_secs(300));
+        assert_eq!(lockout.lockout_duration(100), Duration::from_secs(300));
+    }
+
+    #[tokio::test]
+    async fn test_failures_while_locked_out_do_not_extend_lockout() {
+        let lockout = lockout(Duration::from_secs(60));
+        let ip: IpAddr = "203.0.113.10".parse().unwrap();
+
+        for _ in 0..3 {
+            lockout.record_failure(ip).await;
+        }
+        let locked_for = lockout.locked_for(ip).await.expect("locked out");
+
+        for _ in 0..6 {
+            lockout.record_failure(ip).await;
+        }
+        assert!(lockout.locked_for(ip).await.expect("locked out") <= locked_for);
+    }
+}
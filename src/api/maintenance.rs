@@ -0,0 +1,48 @@
+use std::sync::RwLock;
+
+/// Route switching maintenance mode, which stays reachable while it is on
+pub const PATH: &str = "/admin/maintenance";
+
+/// Shown to callers when maintenance mode is enabled without a message
+pub const DEFAULT_MESSAGE: &str = "The server is in maintenance mode, please try again later";
+
+/// Maintenance mode, in which changes are refused with `503 Service Unavailable`
+/// while reads keep working. Unlike read-only mode it can be switched at runtime,
+/// e.g. around a storage migration.
+#[derive(Debug, Default)]
+pub struct Maintenance {
+    message: RwLock<Option<String>>,
+}
+
+impl Maintenance {
+    pub fn new(enabled: bool, message: Option<String>) -> Self {
+        let maintenance = Self::default();
+        if enabled {
+            maintenance.enable(message);
+        }
+        maintenance
+    }
+
+    pub fn enable(&self, message: Option<String>) {
+        let message = message
+            .filter(|message| !message.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_MESSAGE.to_string());
+        *self.message.write().unwrap_or_else(|e| e.into_inner()) = Some(message);
+    }
+
+    pub fn disable(&self) {
+        *self.message.write().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+
+    /// Message shown to callers, `None` when maintenance mode is off
+    pub fn message(&self) -> Option<String> {
+        self.message
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.message().is_some()
+    }
+}
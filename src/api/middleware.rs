@@ -1,18 +1,83 @@
 use axum::{
+    async_trait,
     body::Body,
-    extract::State,
-    http::{Request, StatusCode},
+    extract::{ConnectInfo, FromRequestParts, State},
+    http::{header::AUTHORIZATION, request::Parts, HeaderValue, Method, Request, StatusCode},
     response::Response,
 };
+use chrono::Utc;
+use std::convert::Infallible;
+use std::net::{IpAddr, SocketAddr};
 use std::sync::Arc;
-use tracing::warn;
+use subtle::ConstantTimeEq;
+use tracing::{info_span, warn, Instrument};
+use uuid::Uuid;
 
-use crate::api::AppState;
-use crate::config::AppConfig;
+use crate::api::maintenance;
+use crate::api::rate_limit::route_class;
+use crate::api::tls::ClientCertificate;
+use crate::api::{ApiError, AppState};
+use crate::config::{ApiConfig, AppConfig, RouteClass};
+use crate::usage::{track, until_next_month};
+use crate::ApiKey;
+
+/// Namespace of the tenant whose API key authenticated the request
+#[derive(Debug, Clone)]
+pub struct Tenant(pub String);
+
+/// Identity of the credential that authenticated the request, without the secret itself
+#[derive(Debug, Clone)]
+pub struct Caller(pub String);
+
+/// Account the usage of the request is counted towards, for work that goes on
+/// after the response, like imports
+#[derive(Debug, Clone)]
+pub struct UsageAccount(pub String);
+
+/// The credential of the request may only read data
+#[derive(Debug, Clone, Copy)]
+pub struct ReadOnly;
+
+/// Identifies a request in the logs and the `X-Request-Id` response header
+#[derive(Debug, Clone)]
+pub struct RequestId(pub String);
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Request IDs taken from clients must be safe to put in logs
+fn is_valid_request_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= 128
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':'))
+}
+
+/// Take the request ID from the `X-Request-Id` header or assign a new one, and log
+/// everything done for the request within a span carrying it
+pub async fn request_id(mut req: Request<Body>, next: axum::middleware::Next) -> Response {
+    let id = req
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|id| is_valid_request_id(id))
+        .map(String::from)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+
+    req.extensions_mut().insert(RequestId(id.clone()));
+
+    let span = info_span!("request", request_id = %id);
+    let mut response = next.run(req).instrument(span).await;
+
+    if let Ok(value) = HeaderValue::from_str(&id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    response
+}
 
 pub async fn validate_api_key(
-    State(_state): State<Arc<AppState>>,
-    req: Request<Body>,
+    State(state): State<Arc<AppState>>,
+    mut req: Request<Body>,
     next: axum::middleware::Next,
 ) -> Result<Response, StatusCode> {
     let config = AppConfig::get().map_err(|_| {
@@ -20,18 +85,334 @@ pub async fn validate_api_key(
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
 
-    let expected_api_key = &config.api.api_key;
+    // Development mode, main prints a warning at startup
+    if config.api.auth_disabled {
+        req.extensions_mut().insert(Caller("admin".to_string()));
+        return Ok(next.run(req).await);
+    }
 
     let api_key = req
         .headers()
         .get("X-Api-Key")
         .and_then(|value| value.to_str().ok());
 
-    match api_key {
-        Some(key) if key == expected_api_key => Ok(next.run(req).await),
-        _ => {
-            warn!("Invalid or missing API key");
-            Err(StatusCode::UNAUTHORIZED)
+    // A client certificate verified during the TLS handshake replaces the API key
+    if api_key.is_none() {
+        if let Some(ClientCertificate(fingerprint)) = req.extensions().get::<ClientCertificate>() {
+            let caller = Caller(format!("cert:{}", fingerprint));
+            req.extensions_mut().insert(caller);
+            return Ok(next.run(req).await);
+        }
+    }
+
+    // Without an API key, fall back to a bearer token when JWTs are accepted
+    if api_key.is_none() {
+        if let Some(jwt) = &state.jwt {
+            let token = req
+                .headers()
+                .get(AUTHORIZATION)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.strip_prefix("Bearer "));
+
+            if let Some(token) = token {
+                let claims = jwt.validate(token.trim()).await.map_err(|e| {
+                    warn!("Failed to validate JWT: {}", e);
+                    StatusCode::INTERNAL_SERVER_ERROR
+                })?;
+
+                let Some(claims) = claims else {
+                    return Err(StatusCode::UNAUTHORIZED);
+                };
+
+                let subject = claims.subject.as_deref().unwrap_or("anonymous");
+                req.extensions_mut()
+                    .insert(Caller(format!("jwt:{}", subject)));
+                if let Some(namespace) = claims.namespace {
+                    req.extensions_mut().insert(Tenant(namespace));
+                }
+                return Ok(next.run(req).await);
+            }
+        }
+    }
+
+    let Some(key) = api_key else {
+        warn!("Missing API key");
+        return Err(StatusCode::UNAUTHORIZED);
+    };
+
+    let key_hash = ApiKey::hash_key(key);
+    match find_configured_key(&config.api, &key_hash) {
+        Some(ConfiguredKey::Main) => {
+            req.extensions_mut().insert(Caller("admin".to_string()));
+            Ok(next.run(req).await)
+        }
+        Some(ConfiguredKey::Tenant(namespace)) => {
+            req.extensions_mut()
+                .insert(Caller(format!("tenant:{}", namespace)));
+            req.extensions_mut().insert(Tenant(namespace));
+            Ok(next.run(req).await)
+        }
+        Some(ConfiguredKey::ReadOnly) => {
+            let caller = Caller(format!("read-only:{}", &key_hash[..16]));
+            req.extensions_mut().insert(caller);
+            req.extensions_mut().insert(ReadOnly);
+            Ok(next.run(req).await)
+        }
+        None => {
+            let stored_key = match &state.api_keys {
+                Some(api_keys) => api_keys.authenticate(key).await.map_err(|e| {
+                    warn!("Failed to look up API key: {}", e);
+                    StatusCode::INTERNAL_SERVER_ERROR
+                })?,
+                None => None,
+            };
+
+            let Some(stored_key) = stored_key else {
+                warn!("Invalid API key");
+                return Err(StatusCode::UNAUTHORIZED);
+            };
+
+            req.extensions_mut()
+                .insert(Caller(format!("key:{}", stored_key.id)));
+            if stored_key.read_only {
+                req.extensions_mut().insert(ReadOnly);
+            }
+            if let Some(namespace) = stored_key.namespace {
+                req.extensions_mut().insert(Tenant(namespace));
+            }
+            Ok(next.run(req).await)
+        }
+    }
+}
+
+/// Kind of a key from the configuration
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfiguredKey {
+    Main,
+    Tenant(String),
+    ReadOnly,
+}
+
+/// Find the configured key with a hash. Every configured hash is compared in
+/// constant time, so response times don't tell how close a guess was.
+pub fn find_configured_key(api: &ApiConfig, key_hash: &str) -> Option<ConfiguredKey> {
+    let matches = |expected: &str| bool::from(key_hash.as_bytes().ct_eq(expected.as_bytes()));
+
+    let mut found = None;
+    if matches(&api.api_key_hash) {
+        found = Some(ConfiguredKey::Main);
+    }
+    for (tenant_hash, namespace) in &api.tenant_keys {
+        if matches(tenant_hash) {
+            found = Some(ConfiguredKey::Tenant(namespace.clone()));
+        }
+    }
+    for read_only_hash in &api.read_only_keys {
+        if matches(read_only_hash) {
+            found = Some(ConfiguredKey::ReadOnly);
+        }
+    }
+    found
+}
+
+/// Whether the request leaves the stored data unchanged. Asking a question is a
+/// POST only because the question doesn't fit in a query string.
+fn only_reads(req: &Request<Body>) -> bool {
+    matches!(*req.method(), Method::GET | Method::HEAD | Method::OPTIONS)
+        || (req.method() == Method::POST && req.uri().path() == "/ask")
+}
+
+/// Refuse requests that change data in read-only mode or with a read-only key
+pub async fn enforce_read_only(
+    State(state): State<Arc<AppState>>,
+    req: Request<Body>,
+    next: axum::middleware::Next,
+) -> Result<Response, ApiError> {
+    let reads = only_reads(&req);
+
+    if !reads && state.read_only {
+        return Err(ApiError::Forbidden(
+            "The server is in read-only mode".to_string(),
+        ));
+    }
+    if !reads && req.extensions().get::<ReadOnly>().is_some() {
+        return Err(ApiError::Forbidden("This API key is read-only".to_string()));
+    }
+
+    Ok(next.run(req).await)
+}
+
+/// Refuse requests that change data in maintenance mode, except switching it off
+pub async fn enforce_maintenance(
+    State(state): State<Arc<AppState>>,
+    req: Request<Body>,
+    next: axum::middleware::Next,
+) -> Result<Response, ApiError> {
+    let reads = only_reads(&req);
+
+    if !reads && req.uri().path() != maintenance::PATH {
+        if let Some(message) = state.maintenance.message() {
+            return Err(ApiError::ServiceUnavailable(message));
+        }
+    }
+
+    Ok(next.run(req).await)
+}
+
+/// Client address of a request, from `X-Forwarded-For` when the proxy in front is trusted
+fn client_ip(req: &Request<Body>, trust_forwarded_for: bool) -> Option<IpAddr> {
+    if trust_forwarded_for {
+        // The last address is the one the proxy added, earlier ones can be forged
+        let forwarded = req
+            .headers()
+            .get("X-Forwarded-For")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.rsplit(',').next())
+            .and_then(|ip| ip.trim().parse().ok());
+        if forwarded.is_some() {
+            return forwarded;
+        }
+    }
+
+    req.extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip())
+}
+
+/// Refuse locked out and overactive client addresses before any other work is done
+pub async fn guard_client_ip(
+    State(state): State<Arc<AppState>>,
+    req: Request<Body>,
+    next: axum::middleware::Next,
+) -> Result<Response, ApiError> {
+    let Some(ip) = client_ip(&req, state.trust_forwarded_for) else {
+        return Ok(next.run(req).await);
+    };
+
+    if let Some(lockout) = &state.auth_lockout {
+        if let Some(locked_for) = lockout.locked_for(ip).await {
+            return Err(ApiError::TooManyRequests(locked_for));
+        }
+    }
+
+    if let Some(rate_limiter) = &state.rate_limiter {
+        match rate_limiter.check_ip(ip).await {
+            Ok(Some(retry_after)) => {
+                warn!("Rate limit exceeded for {}", ip);
+                return Err(ApiError::TooManyRequests(retry_after));
+            }
+            Ok(None) => {}
+            Err(e) => warn!("Failed to check rate limit: {}", e),
+        }
+    }
+
+    let response = next.run(req).await;
+
+    if response.status() == StatusCode::UNAUTHORIZED {
+        if let Some(lockout) = &state.auth_lockout {
+            // Slow down guessing, on top of locking the address out
+            let delay = lockout.record_failure(ip).await;
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    Ok(response)
+}
+
+/// Limit the requests of each caller, after `validate_api_key` identified it
+pub async fn rate_limit(
+    State(state): State<Arc<AppState>>,
+    req: Request<Body>,
+    next: axum::middleware::Next,
+) -> Result<Response, ApiError> {
+    let (Some(rate_limiter), Some(Caller(caller))) =
+        (&state.rate_limiter, req.extensions().get::<Caller>())
+    else {
+        return Ok(next.run(req).await);
+    };
+
+    let namespace = req
+        .extensions()
+        .get::<Tenant>()
+        .map(|Tenant(ns)| ns.as_str());
+    let class = route_class(req.method(), req.uri().path());
+
+    // Rather serve requests without limits than fail them when the store is down
+    match rate_limiter.check(caller, namespace, class).await {
+        Ok(Some(retry_after)) => {
+            warn!("Rate limit exceeded for {} on {:?} routes", caller, class);
+            Err(ApiError::TooManyRequests(retry_after))
+        }
+        Ok(None) => Ok(next.run(req).await),
+        Err(e) => {
+            warn!("Failed to check rate limit: {}", e);
+            Ok(next.run(req).await)
+        }
+    }
+}
+
+/// Account that usage is counted for, tenants share one account for all their credentials
+pub fn usage_account(caller: &str, namespace: Option<&str>) -> String {
+    match namespace {
+        Some(namespace) => format!("tenant:{}", namespace),
+        None => caller.to_string(),
+    }
+}
+
+/// Refuse classifications once the caller's monthly quota is used up and count
+/// the usage of the request afterwards. The main API key has no quota.
+pub async fn track_usage(
+    State(state): State<Arc<AppState>>,
+    mut req: Request<Body>,
+    next: axum::middleware::Next,
+) -> Result<Response, ApiError> {
+    let (Some(usage), Some(Caller(caller))) = (&state.usage, req.extensions().get::<Caller>())
+    else {
+        return Ok(next.run(req).await);
+    };
+
+    let namespace = req
+        .extensions()
+        .get::<Tenant>()
+        .map(|Tenant(ns)| ns.as_str());
+    let account = usage_account(caller, namespace);
+
+    if caller != "admin" && route_class(req.method(), req.uri().path()) == RouteClass::Classify {
+        // Like rate limits, quotas fail open when the store is down
+        match usage.exceeded(&account, namespace).await {
+            Ok(Some(limit)) => {
+                warn!("Monthly {} quota exceeded for {}", limit, account);
+                return Err(ApiError::QuotaExceeded(limit, until_next_month(Utc::now())));
+            }
+            Ok(None) => {}
+            Err(e) => warn!("Failed to check quota: {}", e),
+        }
+    }
+
+    req.extensions_mut().insert(UsageAccount(account.clone()));
+    let (response, recorded) = track(next.run(req)).await;
+
+    if let Err(e) = usage.add(&account, &recorded).await {
+        warn!("Failed to record usage for {}: {}", account, e);
+    }
+
+    Ok(response)
+}
+
+/// Application state scoped to the tenant of the request, unscoped for the main API key
+pub struct TenantState(pub Arc<AppState>);
+
+#[async_trait]
+impl FromRequestParts<Arc<AppState>> for TenantState {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        match parts.extensions.get::<Tenant>() {
+            Some(Tenant(namespace)) => Ok(Self(Arc::new(state.for_namespace(Some(namespace))))),
+            None => Ok(Self(state.clone())),
         }
     }
 }
@@ -1,33 +1,160 @@
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
-    middleware::from_fn_with_state,
+    http::{
+        header::{CONTENT_TYPE, RETRY_AFTER},
+        HeaderMap, StatusCode,
+    },
+    middleware::{from_fn, from_fn_with_state},
     response::{IntoResponse, Response},
-    routing::{delete, get, post},
-    Json, Router,
+    routing::{delete, get, patch, post, put},
+    Extension, Json, Router,
 };
+use chrono::{DateTime, Utc};
+use metrics_exporter_prometheus::PrometheusHandle;
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::net::TcpListener;
-use tracing::{error, info};
+use tracing::{error, info, warn};
+use uuid::Uuid;
 
+use crate::activity::{Activity, ActivityLog};
 use crate::classifier::Classifier;
-use crate::storage::{ContentStorage, TagStorage};
+use crate::config::{
+    AccessLogConfig, AuthLockoutConfig, ContentOrder, DigestPeriod, ImportConfig, IngestConfig,
+    JwtConfig, SlackConfig, TagMatch, TlsConfig,
+};
+use crate::embedding::index::SemanticIndex;
+use crate::ingest::bookmarks::parse_bookmarks;
+use crate::ingest::import::{
+    parse_url_list, ImportJob, ImportManager, ImportRequest, SitemapImportRequest,
+};
+use crate::ingest::lock::DedupLock;
+use crate::ingest::slack::{self, LinkShared, SlackEvent, SlashCommand};
+use crate::ingest::webhook::extract_content;
+use crate::ingest::{classify_stored, ingest_with_metadata, ingest_with_tags, Ingested};
+use crate::jobs::heartbeat::Heartbeat;
+use crate::queue::{enqueue, ClassificationJob, JobQueue, QueuedJob};
+use crate::shutdown;
+use crate::storage::api_key::ApiKeys;
+use crate::storage::hash_cache::HashLookupCache;
+use crate::storage::namespace::{
+    update_shares, NamespacedAtomicStorage, NamespacedContentStorage, NamespacedTagStorage,
+};
+use crate::storage::purge::purge;
+use crate::storage::transaction::StorageTransaction;
+use crate::storage::{AtomicStorage, ContentStorage, TagStorage};
+use crate::trends::{TagTrends, TrendWindow};
+use crate::usage::UsageTracker;
+use crate::web::sitemap::fetch_sitemap_pages;
 use crate::{
-    ClassifyError, ClassifyRequest, ClassifyResponse, Content, ContentQueryResponse, TagsResponse,
+    ApiKeyResponse, ApiKeysResponse, AskRequest, AskResponse, BlockedTagsResponse, ClassifyError,
+    ClassifyRequest, ClassifyResponse, ClusterRequest, ClusterResponse, Content,
+    ContentAccessRequest, ContentQueryResponse, ContentTagsRequest, CreateApiKeyRequest,
+    DigestResponse, HealthResponse, LinkStatus, LogLevelRequest, LogLevelResponse,
+    MaintenanceRequest, MaintenanceResponse, MetadataPatchRequest, PurgeRequest, PurgeResponse,
+    ReclassifyResponse, RecommendationsResponse, RuntimeStatsResponse, SemanticSearchHit,
+    SemanticSearchResponse, ShareRequest, ShareResponse, TagCountsResponse, TagDuplicatesResponse,
+    TagMergeRequest, TagMergeResponse, TagShareRequest, TagShareResponse, TagsResponse,
+    TrendingTagsResponse, UsageResponse, Visibility,
 };
 
+pub mod access;
+pub mod access_log;
+#[cfg(test)]
+mod access_log_test;
+pub mod health;
+pub mod jwt;
+#[cfg(test)]
+mod jwt_test;
+pub mod lockout;
+#[cfg(test)]
+mod lockout_test;
+pub mod maintenance;
 mod middleware;
+pub mod rate_limit;
+#[cfg(test)]
+mod rate_limit_test;
+pub mod share;
+#[cfg(test)]
+mod share_test;
 #[cfg(test)]
 mod tests;
+pub mod tls;
+#[cfg(test)]
+mod tls_test;
+pub mod ui;
+
+use access::Access;
+use jwt::JwtValidator;
+use lockout::AuthLockout;
+use maintenance::Maintenance;
+pub use middleware::{Caller, RequestId, Tenant, TenantState, UsageAccount};
+use rate_limit::RateLimiter;
+use share::ShareLinks;
+
+/// Attachment name used for archived page snapshots
+pub const SNAPSHOT_ATTACHMENT: &str = "snapshot.html";
 
 #[derive(Clone)]
 pub struct AppState {
     pub classifier: Arc<dyn Classifier>,
     pub content_storage: Arc<dyn ContentStorage>,
     pub tag_storage: Arc<dyn TagStorage>,
+    /// Tag storage without tenant scoping, for the indexes of shared content
+    pub root_tag_storage: Arc<dyn TagStorage>,
+    /// Writes content and tags together when both storages share a backend
+    pub atomic_storage: Option<Arc<dyn AtomicStorage>>,
+    /// Hashes recently found not to be stored, skips repeated duplicate lookups
+    pub hash_cache: Option<Arc<HashLookupCache>>,
+    /// Makes concurrent requests for the same content wait for one classification
+    pub dedup_lock: Option<Arc<DedupLock>>,
+    /// Embeddings of stored content behind `/search/semantic`
+    pub semantic_index: Option<Arc<SemanticIndex>>,
+    /// Daily tag counters behind `/tags/trending`
+    pub tag_trends: Option<Arc<TagTrends>>,
+    /// What each caller looks at, behind `/recommendations`
+    pub activity_log: Option<Arc<ActivityLog>>,
+    pub http_client: reqwest::Client,
+    /// Store the fetched page of classified URLs as an attachment
+    pub archive_snapshots: bool,
+    pub ingest: IngestConfig,
+    pub imports: Arc<ImportManager>,
+    pub sitemap_max_pages: usize,
+    /// Content mapping per webhook source
+    pub webhooks: Arc<HashMap<String, String>>,
+    pub slack: Option<Arc<SlackConfig>>,
+    /// Tenant whose data this state sees, all data when unset
+    pub namespace: Option<String>,
+    /// API keys created at runtime through the admin endpoints
+    pub api_keys: Option<Arc<ApiKeys>>,
+    /// Validates `Authorization: Bearer` tokens when JWT authentication is configured
+    pub jwt: Option<Arc<JwtValidator>>,
+    pub rate_limiter: Option<Arc<RateLimiter>>,
+    pub auth_lockout: Option<Arc<AuthLockout>>,
+    /// Take the client address from `X-Forwarded-For`
+    pub trust_forwarded_for: bool,
+    /// Refuse all requests that change data
+    pub read_only: bool,
+    /// Refuse requests that change data with `503` while switched on
+    pub maintenance: Arc<Maintenance>,
+    /// Counts usage per account and enforces the monthly quotas
+    pub usage: Option<Arc<UsageTracker>>,
+    /// Signs links to content that work without an API key
+    pub share_links: Option<Arc<ShareLinks>>,
+    /// Log a line per request when set
+    pub access_log: Option<Arc<AccessLogConfig>>,
+    /// Renders the recorded metrics for `/metrics`
+    pub metrics: Option<PrometheusHandle>,
+    /// Pinged after successful classifications
+    pub heartbeat: Option<Arc<Heartbeat>>,
+    /// Classifications waiting for a worker
+    pub queue: Option<Arc<dyn JobQueue>>,
+    /// Queue every classification request instead of classifying it right
+    /// away, for `classify serve`
+    pub classify_async: bool,
 }
 
 impl AppState {
@@ -39,17 +166,321 @@ impl AppState {
         Self {
             classifier,
             content_storage,
+            root_tag_storage: tag_storage.clone(),
             tag_storage,
+            atomic_storage: None,
+            hash_cache: None,
+            dedup_lock: None,
+            semantic_index: None,
+            tag_trends: None,
+            activity_log: None,
+            http_client: reqwest::Client::new(),
+            archive_snapshots: false,
+            ingest: IngestConfig::default(),
+            imports: Arc::new(ImportManager::new(ImportConfig::default())),
+            sitemap_max_pages: ImportConfig::default().sitemap_max_pages,
+            webhooks: Arc::new(HashMap::new()),
+            slack: None,
+            namespace: None,
+            api_keys: None,
+            jwt: None,
+            rate_limiter: None,
+            auth_lockout: None,
+            trust_forwarded_for: false,
+            read_only: false,
+            maintenance: Arc::new(Maintenance::default()),
+            usage: None,
+            share_links: None,
+            access_log: None,
+            metrics: None,
+            heartbeat: None,
+            queue: None,
+            classify_async: false,
+        }
+    }
+
+    /// State that only sees the data of a tenant, or all data without a namespace.
+    /// Meant to be called on the unscoped state.
+    pub fn for_namespace(&self, namespace: Option<&str>) -> Self {
+        let Some(namespace) = namespace else {
+            return self.clone();
+        };
+
+        Self {
+            content_storage: Arc::new(NamespacedContentStorage::new(
+                self.content_storage.clone(),
+                namespace,
+            )),
+            tag_storage: Arc::new(NamespacedTagStorage::new(
+                self.tag_storage.clone(),
+                namespace,
+            )),
+            atomic_storage: self.atomic_storage.clone().map(|atomic_storage| {
+                Arc::new(NamespacedAtomicStorage::new(
+                    atomic_storage,
+                    self.tag_storage.clone(),
+                    namespace,
+                )) as Arc<dyn AtomicStorage>
+            }),
+            namespace: Some(namespace.to_string()),
+            ..self.clone()
         }
     }
+
+    /// Coordinates writes that span content and tag storage
+    pub fn storage_transaction(&self) -> StorageTransaction<'_> {
+        StorageTransaction::new(self.content_storage.as_ref(), self.tag_storage.as_ref())
+            .with_atomic_storage(self.atomic_storage.as_deref())
+    }
+
+    pub fn with_atomic_storage(mut self, atomic_storage: Option<Arc<dyn AtomicStorage>>) -> Self {
+        self.atomic_storage = atomic_storage;
+        self
+    }
+
+    pub fn with_hash_cache(mut self, hash_cache: Option<HashLookupCache>) -> Self {
+        self.hash_cache = hash_cache.map(Arc::new);
+        self
+    }
+
+    pub fn with_webhooks(mut self, webhooks: HashMap<String, String>) -> Self {
+        self.webhooks = Arc::new(webhooks);
+        self
+    }
+
+    pub fn with_slack(mut self, slack: Option<SlackConfig>) -> Self {
+        self.slack = slack.map(Arc::new);
+        self
+    }
+
+    pub fn with_ingest_config(mut self, config: IngestConfig) -> Self {
+        self.ingest = config;
+        self
+    }
+
+    pub fn with_import_config(mut self, config: ImportConfig) -> Self {
+        self.sitemap_max_pages = config.sitemap_max_pages;
+        self.imports = Arc::new(ImportManager::new(config));
+        self
+    }
+
+    pub fn with_archive_snapshots(mut self, archive_snapshots: bool) -> Self {
+        self.archive_snapshots = archive_snapshots;
+        self
+    }
+
+    pub fn with_api_keys(mut self, api_keys: Option<ApiKeys>) -> Self {
+        self.api_keys = api_keys.map(Arc::new);
+        self
+    }
+
+    pub fn with_jwt(mut self, config: Option<JwtConfig>) -> Self {
+        self.jwt = config.map(|config| Arc::new(JwtValidator::new(config)));
+        self
+    }
+
+    pub fn with_rate_limiter(mut self, rate_limiter: Option<RateLimiter>) -> Self {
+        self.rate_limiter = rate_limiter.map(Arc::new);
+        self
+    }
+
+    pub fn with_auth_lockout(mut self, config: Option<AuthLockoutConfig>) -> Self {
+        self.auth_lockout = config.map(|config| Arc::new(AuthLockout::new(config)));
+        self
+    }
+
+    pub fn with_trust_forwarded_for(mut self, trust_forwarded_for: bool) -> Self {
+        self.trust_forwarded_for = trust_forwarded_for;
+        self
+    }
+
+    pub fn with_read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    pub fn with_heartbeat(mut self, heartbeat: Option<Heartbeat>) -> Self {
+        self.heartbeat = heartbeat.map(Arc::new);
+        self
+    }
+
+    pub fn with_dedup_lock(mut self, dedup_lock: Option<DedupLock>) -> Self {
+        self.dedup_lock = dedup_lock.map(Arc::new);
+        self
+    }
+
+    pub fn with_semantic_index(mut self, semantic_index: Option<SemanticIndex>) -> Self {
+        self.semantic_index = semantic_index.map(Arc::new);
+        self
+    }
+
+    pub fn with_tag_trends(mut self, tag_trends: Option<TagTrends>) -> Self {
+        self.tag_trends = tag_trends.map(Arc::new);
+        self
+    }
+
+    pub fn with_activity_log(mut self, activity_log: Option<ActivityLog>) -> Self {
+        self.activity_log = activity_log.map(Arc::new);
+        self
+    }
+
+    pub fn with_queue(mut self, queue: Option<Arc<dyn JobQueue>>) -> Self {
+        self.queue = queue;
+        self
+    }
+
+    pub fn with_classify_async(mut self, classify_async: bool) -> Self {
+        self.classify_async = classify_async;
+        self
+    }
+
+    pub fn with_maintenance(mut self, maintenance: Maintenance) -> Self {
+        self.maintenance = Arc::new(maintenance);
+        self
+    }
+
+    pub fn with_usage(mut self, usage: Option<UsageTracker>) -> Self {
+        self.usage = usage.map(Arc::new);
+        self
+    }
+
+    pub fn with_share_links(mut self, share_links: Option<ShareLinks>) -> Self {
+        self.share_links = share_links.map(Arc::new);
+        self
+    }
+
+    pub fn with_access_log(mut self, config: Option<AccessLogConfig>) -> Self {
+        self.access_log = config.map(Arc::new);
+        self
+    }
+
+    pub fn with_metrics(mut self, metrics: Option<PrometheusHandle>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// API key manager, for requests made with the main API key only
+    fn admin_api_keys(&self, caller: Option<Extension<Caller>>) -> Result<&ApiKeys, ApiError> {
+        require_admin(caller, "API keys can only be managed with the main API key")?;
+
+        self.api_keys
+            .as_deref()
+            .ok_or_else(|| ApiError::BadRequest("API key storage is not configured".to_string()))
+    }
+}
+
+/// Refuse requests not made with the main API key. Having no tenant isn't
+/// enough: read-only keys, stored keys, JWTs and client certificates may
+/// have none either.
+fn require_admin(caller: Option<Extension<Caller>>, message: &str) -> Result<(), ApiError> {
+    match caller {
+        Some(Extension(Caller(caller))) if caller == "admin" => Ok(()),
+        _ => Err(ApiError::Forbidden(message.to_string())),
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ClassifyParams {
+    /// Queue the content for a worker and answer with the job right away
+    #[serde(default, rename = "async")]
+    pub run_async: bool,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct QueryParams {
+    /// Tags separated by commas, a tag also matches the tags below it
     pub tags: String,
+    /// Metadata filter as `key:value` pairs separated by commas
+    pub metadata: Option<String>,
+    /// Only pinned content with `true`, only other content with `false`
+    pub pinned: Option<bool>,
+    /// Whether content needs `any` of the tags, the default, or `all` of them
+    #[serde(default)]
+    pub mode: TagMatch,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TagsParams {
+    /// Also return the tags arranged by their levels
+    #[serde(default)]
+    pub tree: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TagDuplicatesParams {
+    /// Also compare the meaning of tags, needs semantic search
+    #[serde(default)]
+    pub semantic: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DigestParams {
+    #[serde(default)]
+    pub period: DigestPeriod,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SemanticSearchParams {
+    pub q: String,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RecommendationParams {
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TrendParams {
+    pub recent_days: Option<u32>,
+    pub baseline_days: Option<u32>,
+    pub min_count: Option<u64>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListParams {
+    pub status: Option<LinkStatus>,
+    /// Metadata filter as `key:value` pairs separated by commas
+    pub metadata: Option<String>,
+    /// Only pinned content with `true`, only other content with `false`
+    pub pinned: Option<bool>,
+    /// Only content with its `order` timestamp at or after this time
+    pub since: Option<DateTime<Utc>>,
+    /// Only content with its `order` timestamp before this time
+    pub until: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub order: ContentOrder,
+    /// Number of matching items to skip
+    #[serde(default)]
+    pub offset: usize,
+    /// Maximum number of items returned, all of them when unset
+    pub limit: Option<usize>,
+}
+
+/// Parse a metadata filter such as `source:slack,author:bob`
+fn parse_metadata_filter(filter: Option<&str>) -> Result<HashMap<String, String>, ApiError> {
+    let Some(filter) = filter else {
+        return Ok(HashMap::new());
+    };
+
+    filter
+        .split(',')
+        .map(str::trim)
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| match pair.split_once(':') {
+            Some((key, value)) if !key.trim().is_empty() => {
+                Ok((key.trim().to_string(), value.trim().to_string()))
+            }
+            _ => Err(ApiError::BadRequest(format!(
+                "Invalid metadata filter '{}', expected key:value",
+                pair
+            ))),
+        })
+        .collect()
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct DeleteResponse {
     pub success: bool,
     pub id: Option<String>,
@@ -62,10 +493,67 @@ pub fn create_router(state: AppState) -> Router {
 
     let protected_routes = Router::new()
         .route("/classify", post(classify_content))
+        .route("/jobs/:id", get(get_classification_job))
         .route("/query", get(query_content))
+        .route("/search/semantic", get(semantic_search))
+        .route("/ask", post(ask))
+        .route("/recommendations", get(get_recommendations))
+        .route("/content", get(list_content))
         .route("/content/:id", delete(delete_content))
         .route("/content/:id", get(get_content_text))
+        .route("/content/:id/snapshot", get(get_content_snapshot))
+        .route("/content/:id/share", post(share_content))
+        .route("/content/:id/access", put(set_content_access))
+        .route("/content/:id/metadata", patch(patch_content_metadata))
+        .route("/content/:id/tags", put(set_content_tags))
+        .route("/content/:id/reclassify", post(reclassify_content))
+        .route("/content/:id/pin", put(pin_content))
+        .route("/content/:id/pin", delete(unpin_content))
         .route("/tags", get(get_tags))
+        .route("/tags/counts", get(get_tag_counts))
+        .route("/tags/trending", get(get_trending_tags))
+        .route("/digest", get(get_digest))
+        .route("/tags/:tag/share", post(share_tag))
+        .route("/import/urls", post(import_urls))
+        .route("/import/sitemap", post(import_sitemap))
+        .route("/import/bookmarks", post(import_bookmarks))
+        .route("/import/:id", get(get_import_job))
+        .route("/ingest/webhook/:source", post(ingest_webhook))
+        .route("/admin/purge", post(purge_content))
+        .route("/admin/cluster", post(cluster_content))
+        .route("/admin/tags/blocklist", post(strip_blocked_tags))
+        .route("/admin/tags/duplicates", get(get_tag_duplicates))
+        .route("/admin/tags/merge", post(merge_tags))
+        .route("/admin/keys", post(create_api_key))
+        .route("/admin/keys", get(list_api_keys))
+        .route("/admin/keys/:id", delete(revoke_api_key))
+        .route("/admin/keys/:id/rotate", post(rotate_api_key))
+        .route("/usage", get(get_usage))
+        .route("/metrics", get(get_metrics))
+        .route("/admin/log-level", get(get_log_level))
+        .route("/admin/log-level", put(set_log_level))
+        .route("/admin/debug/runtime", get(get_runtime_stats))
+        .route(maintenance::PATH, get(get_maintenance))
+        .route(maintenance::PATH, put(set_maintenance))
+        // Layers run bottom up: the caller is identified, then checked for
+        // read-only access and maintenance mode, rate limited and held to its quota
+        .layer(from_fn(access_log::expose_caller))
+        .layer(from_fn_with_state(
+            shared_state.clone(),
+            middleware::track_usage,
+        ))
+        .layer(from_fn_with_state(
+            shared_state.clone(),
+            middleware::rate_limit,
+        ))
+        .layer(from_fn_with_state(
+            shared_state.clone(),
+            middleware::enforce_maintenance,
+        ))
+        .layer(from_fn_with_state(
+            shared_state.clone(),
+            middleware::enforce_read_only,
+        ))
         .layer(from_fn_with_state(
             shared_state.clone(),
             middleware::validate_api_key,
@@ -73,25 +561,94 @@ pub fn create_router(state: AppState) -> Router {
 
     Router::new()
         .route("/", get(health_check))
+        .route("/health", get(deep_health_check))
+        // Probes for orchestrators such as Kubernetes
+        .route("/healthz", get(health_check))
+        .route("/readyz", get(readiness_check))
+        .route(ui::PATH, get(ui::index))
+        .route("/ui/", get(ui::index))
+        .route("/ui/*path", get(ui::asset))
+        // Share links carry their own signature instead of the API key
+        .route("/shared/:id", get(get_shared_content))
+        // Slack signs its requests instead of sending the API key
+        .route(
+            "/slack",
+            post(slack_request)
+                .layer(from_fn_with_state(
+                    shared_state.clone(),
+                    middleware::enforce_maintenance,
+                ))
+                .layer(from_fn_with_state(
+                    shared_state.clone(),
+                    middleware::enforce_read_only,
+                )),
+        )
         .merge(protected_routes)
+        .layer(from_fn_with_state(
+            shared_state.clone(),
+            middleware::guard_client_ip,
+        ))
+        .layer(from_fn_with_state(
+            shared_state.clone(),
+            access_log::access_log,
+        ))
+        .layer(from_fn(middleware::request_id))
         .with_state(shared_state)
 }
 
-pub async fn start_server(app_state: AppState, addr: SocketAddr) -> Result<(), ClassifyError> {
+/// Serve the API until shutdown is requested, then let requests in flight finish
+/// for up to `shutdown_timeout`
+pub async fn start_server(
+    app_state: AppState,
+    addr: SocketAddr,
+    tls: Option<&TlsConfig>,
+    shutdown_timeout: Duration,
+) -> Result<(), ClassifyError> {
     let app = create_router(app_state);
 
+    // Load the certificates before binding so a bad configuration fails fast
+    let server_config = tls.map(tls::load_server_config).transpose()?;
+
     info!("Starting server on {}", addr);
 
     let listener = TcpListener::bind(addr)
         .await
         .map_err(|e| ClassifyError::ApiError(format!("Failed to bind: {}", e)))?;
 
-    axum::serve(listener, app)
+    let server = async {
+        if let (Some(tls), Some(server_config)) = (tls, server_config) {
+            return tls::serve_tls(listener, app, tls, server_config).await;
+        }
+
+        axum::serve(
+            listener,
+            app.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .with_graceful_shutdown(shutdown::requested())
         .await
         .map_err(|e| ClassifyError::ApiError(format!("Server error: {}", e)))
+    };
+
+    let deadline = async {
+        shutdown::requested().await;
+        info!("Stopped accepting connections, finishing requests in flight");
+        tokio::time::sleep(shutdown_timeout).await;
+    };
+
+    tokio::select! {
+        result = server => result,
+        _ = deadline => {
+            warn!(
+                "Requests still in flight after {}s, stopping anyway",
+                shutdown_timeout.as_secs()
+            );
+            Ok(())
+        }
+    }
 }
 
-/// Health check endpoint
+/// Health check endpoint, also the liveness probe: it only shows that the
+/// process answers, so a dependency being down doesn't get the process restarted
 async fn health_check() -> Response {
     Response::builder()
         .status(StatusCode::OK)
@@ -99,46 +656,91 @@ async fn health_check() -> Response {
         .unwrap()
 }
 
-/// Classify content endpoint
-async fn classify_content(
+#[derive(Debug, Deserialize)]
+pub struct HealthParams {
+    /// Also check that the classifier's provider accepts the configured credentials
+    #[serde(default)]
+    pub classifier: bool,
+}
+
+/// Health check of the storages and optionally the classifier, 503 when any is down
+async fn deep_health_check(
     State(state): State<Arc<AppState>>,
-    Json(request): Json<ClassifyRequest>,
-) -> Result<Json<ClassifyResponse>, ApiError> {
-    info!("Received classification request");
+    Query(params): Query<HealthParams>,
+) -> Response {
+    health_response(health::check_components(&state, params.classifier).await)
+}
 
-    let content_hash = Content::generate_hash(&request.content);
+/// Readiness probe, 503 takes the instance out of the load balancer without
+/// restarting it
+async fn readiness_check(State(state): State<Arc<AppState>>) -> Response {
+    health_response(health::check_readiness(&state).await)
+}
 
-    if let Some(existing_content) = state.content_storage.find_by_hash(&content_hash).await? {
-        info!("Found existing content with the same hash");
+fn health_response(health: HealthResponse) -> Response {
+    let status = if health.healthy {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
 
-        let response = ClassifyResponse {
-            content: existing_content,
-            success: true,
-            error: None,
-        };
+    (status, Json(health)).into_response()
+}
 
-        return Err(ApiError::Conflict(response));
-    }
+/// Queue content for a worker, answering `202 Accepted` with the job to poll
+async fn queue_classification(
+    state: &AppState,
+    content: String,
+    tags: Vec<String>,
+    metadata: HashMap<String, String>,
+    account: Option<Extension<UsageAccount>>,
+) -> Result<Response, ApiError> {
+    let queue = state.queue.as_deref().ok_or_else(|| {
+        ApiError::BadRequest("Asynchronous classification is not configured".to_string())
+    })?;
 
-    let content = Content::new(request.content.clone());
+    // The worker counts the job's usage, the request only queues it
+    let job = QueuedJob::new(content, tags, metadata, state.namespace.clone())
+        .with_account(account.map(|Extension(UsageAccount(account))| account));
+    let job = enqueue(queue, &job).await?;
+    info!("Queued classification job {}", job.id);
 
-    let tags = if content.is_url() {
-        info!("Detected URL: {}", &content.content);
-        state.classifier.classify_url(&content.content).await?
-    } else {
-        info!("Detected text content");
-        state.classifier.classify(&content.content).await?
-    };
+    Ok((StatusCode::ACCEPTED, Json(job)).into_response())
+}
 
-    let content = content.with_tags(tags.clone());
+/// Classify content endpoint
+async fn classify_content(
+    TenantState(state): TenantState,
+    account: Option<Extension<UsageAccount>>,
+    Query(params): Query<ClassifyParams>,
+    Json(request): Json<ClassifyRequest>,
+) -> Result<Response, ApiError> {
+    info!("Received classification request");
 
-    // RESEARCH: should the next two lines be in a transaction?
-    state.content_storage.store(&content).await?;
+    if params.run_async || state.classify_async {
+        return queue_classification(
+            &state,
+            request.content,
+            Vec::new(),
+            request.metadata,
+            account,
+        )
+        .await;
+    }
 
-    state
-        .tag_storage
-        .add_tags(&content.id.to_string(), &tags)
-        .await?;
+    let content = match ingest_with_metadata(&state, request.content, &[], request.metadata).await?
+    {
+        Ingested::Created(content) => content,
+        Ingested::Duplicate(existing_content) => {
+            let response = ClassifyResponse {
+                content: existing_content,
+                success: true,
+                error: None,
+            };
+
+            return Err(ApiError::Conflict(Box::new(response)));
+        }
+    };
 
     let response = ClassifyResponse {
         content,
@@ -146,50 +748,78 @@ async fn classify_content(
         error: None,
     };
 
-    Ok(Json(response))
+    Ok(Json(response).into_response())
+}
+
+/// Get the progress of a queued classification
+async fn get_classification_job(
+    TenantState(state): TenantState,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ClassificationJob>, ApiError> {
+    let queue = state.queue.as_deref().ok_or_else(|| {
+        ApiError::BadRequest("Asynchronous classification is not configured".to_string())
+    })?;
+
+    queue
+        .status(&id)
+        .await?
+        .filter(|job| state.namespace.is_none() || job.namespace == state.namespace)
+        .map(Json)
+        .ok_or_else(|| ApiError::BadRequest(format!("Job {} not found", id)))
 }
 
 async fn query_content(
-    State(state): State<Arc<AppState>>,
+    TenantState(state): TenantState,
+    access: Access,
     Query(params): Query<QueryParams>,
 ) -> Result<Json<ContentQueryResponse>, ApiError> {
     info!("Received content query request for tags: {}", params.tags);
 
     // Parse tags from query string
-    let tags: Vec<String> = params
+    let tags = state
+        .ingest
         .tags
-        .split(',')
-        .map(|s| s.trim().to_string())
-        .filter(|s| !s.is_empty())
-        .collect();
+        .normalize_tags(&params.tags.split(',').collect::<Vec<_>>());
 
     if tags.is_empty() {
         return Err(ApiError::BadRequest("No valid tags provided".to_string()));
     }
 
-    let mut content_ids = HashSet::new();
-    for tag in &tags {
-        let tag_content_ids = state.tag_storage.find_by_tag(tag).await?;
-        for id in tag_content_ids {
-            content_ids.insert(id);
+    let metadata = parse_metadata_filter(params.metadata.as_deref())?;
+    record_activity(&state, &access, Activity::query(&tags)).await;
+
+    let content_ids: HashSet<String> = match params.mode {
+        TagMatch::Any => {
+            let mut content_ids = HashSet::new();
+            for tag in &tags {
+                content_ids.extend(state.tag_storage.find_by_tag_tree(tag).await?);
+            }
+            content_ids
         }
-    }
+        TagMatch::All => state
+            .tag_storage
+            .find_by_all_tags(&tags)
+            .await?
+            .into_iter()
+            .collect(),
+    };
 
     info!(
         "Found {} content items matching the tags",
         content_ids.len()
     );
 
-    let mut items = Vec::new();
-    for content_id in content_ids {
-        if let Some(content) = state.content_storage.get(&content_id).await? {
-            items.push(content);
-        }
-    }
+    let content_ids: Vec<String> = content_ids.into_iter().collect();
+    let mut items = state.content_storage.get_many(&content_ids).await?;
+    items.retain(|item| {
+        item.matches_metadata(&metadata)
+            && params.pinned.is_none_or(|pinned| item.pinned == pinned)
+            && access.can_read(item)
+    });
 
     info!("Retrieved {} content items", items.len());
 
-    items.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+    sort_pinned_first(&mut items, ContentOrder::UpdatedAt);
 
     let count = items.len();
 
@@ -204,68 +834,207 @@ async fn query_content(
     Ok(Json(response))
 }
 
-async fn delete_content(
-    State(state): State<Arc<AppState>>,
-    Path(id): Path<String>,
-) -> Result<Json<DeleteResponse>, ApiError> {
-    info!("Received delete content request for ID: {}", id);
+/// Content closest in meaning to a free-text query
+async fn semantic_search(
+    TenantState(state): TenantState,
+    access: Access,
+    Query(params): Query<SemanticSearchParams>,
+) -> Result<Json<SemanticSearchResponse>, ApiError> {
+    info!("Received semantic search request: {}", params.q);
 
-    if (state.content_storage.get(&id).await?).is_some() {
-        let tags = state.tag_storage.get_tags(&id).await?;
-        info!("Content has {} tags that may need cleanup", tags.len());
+    let index = state
+        .semantic_index
+        .as_ref()
+        .ok_or_else(|| ApiError::BadRequest("Semantic search is not configured".to_string()))?;
 
-        // RESEARCH: should deletion of content and tags be transactional?
+    let query = params.q.trim();
+    if query.is_empty() {
+        return Err(ApiError::BadRequest("No search query provided".to_string()));
+    }
+    let limit = params.limit.unwrap_or(10).clamp(1, 100);
+    record_activity(&state, &access, Activity::search(query)).await;
 
-        let deleted = state.content_storage.delete(&id).await?;
+    // Extra candidates make up for private content of other users and content
+    // deleted since it was embedded
+    let nearest = index
+        .search(query, state.namespace.as_deref(), limit * 2)
+        .await?;
+    let ids: Vec<String> = nearest.iter().map(|(id, _)| id.clone()).collect();
+    let mut found: HashMap<String, Content> = state
+        .content_storage
+        .get_many(&ids)
+        .await?
+        .into_iter()
+        .filter(|content| access.can_read(content))
+        .map(|content| (content.id.to_string(), content))
+        .collect();
 
-        if !deleted {
-            return Err(ApiError::BadRequest(format!(
-                "Failed to delete content with ID: {}",
-                id
-            )));
-        }
+    let items: Vec<SemanticSearchHit> = nearest
+        .into_iter()
+        .filter_map(|(id, score)| {
+            Some(SemanticSearchHit {
+                content: found.remove(&id)?,
+                score,
+            })
+        })
+        .take(limit)
+        .collect();
 
-        let mut orphaned_tags = Vec::new();
+    info!("Found {} content items for the query", items.len());
 
-        for tag in &tags {
-            let content_with_tag = state.tag_storage.find_by_tag(tag).await?;
+    Ok(Json(SemanticSearchResponse {
+        query: query.to_string(),
+        count: items.len(),
+        items,
+        success: true,
+        error: None,
+    }))
+}
 
-            if content_with_tag.is_empty() {
-                info!("Tag '{}' is now orphaned, will be removed", tag);
-                orphaned_tags.push(tag.clone());
-            }
-        }
+/// Answer a question from the stored content most relevant to it, citing the
+/// content the answer is based on
+async fn ask(
+    TenantState(state): TenantState,
+    access: Access,
+    Json(request): Json<AskRequest>,
+) -> Result<Json<AskResponse>, ApiError> {
+    info!("Received question: {}", request.question);
 
-        state.tag_storage.remove_tags(&id, &tags).await?;
+    let question = request.question.trim();
+    if question.is_empty() {
+        return Err(ApiError::BadRequest("No question provided".to_string()));
+    }
+    let limit = request.limit.unwrap_or(5).clamp(1, 20);
+    record_activity(&state, &access, Activity::search(question)).await;
 
-        let response = DeleteResponse {
+    let found =
+        crate::ask::retrieve(&state, question, limit, |content| access.can_read(content)).await?;
+    if found.is_empty() {
+        return Ok(Json(AskResponse {
+            answer: "None of the stored content matches the question.".to_string(),
+            citations: Vec::new(),
+            sources: 0,
             success: true,
-            id: Some(id),
-            removed_tags: orphaned_tags,
             error: None,
-        };
+        }));
+    }
 
-        Ok(Json(response))
-    } else {
-        Err(ApiError::BadRequest(format!(
-            "Content with ID {} not found",
-            id
-        )))
+    let mut sources = Vec::new();
+    for content in &found {
+        let text = crate::ask::source_text(&state, content).await;
+        sources.push((content.clone(), text));
     }
+    let prompt = crate::ask::build_prompt(question, &sources);
+    let answer = state
+        .classifier
+        .complete(crate::ask::SYSTEM_PROMPT, &prompt)
+        .await?;
+    let citations = crate::ask::citations(&answer, &found);
+
+    info!(
+        "Answered from {} items, citing {}",
+        found.len(),
+        citations.len()
+    );
+
+    Ok(Json(AskResponse {
+        answer,
+        citations,
+        sources: found.len(),
+        success: true,
+        error: None,
+    }))
 }
 
-async fn get_tags(State(state): State<Arc<AppState>>) -> Result<Json<TagsResponse>, ApiError> {
-    info!("Received request for all tags");
+/// Add to the caller's activity, when the activity log is kept
+async fn record_activity(state: &AppState, access: &Access, activity: Activity) {
+    if let Some(activity_log) = &state.activity_log {
+        activity_log
+            .record(access.caller.as_deref().unwrap_or("admin"), activity)
+            .await;
+    }
+}
 
-    // Retrieve all tags from storage
-    let tags = state.tag_storage.list_tags().await?;
-    let count = tags.len();
+/// Content the caller hasn't opened yet that matches the tags they query,
+/// open and set and the searches they make
+async fn get_recommendations(
+    TenantState(state): TenantState,
+    access: Access,
+    Query(params): Query<RecommendationParams>,
+) -> Result<Json<RecommendationsResponse>, ApiError> {
+    info!("Received recommendations request: {:?}", params);
 
-    info!("Retrieved {} tags", count);
+    let activity_log = state
+        .activity_log
+        .as_ref()
+        .ok_or_else(|| ApiError::BadRequest("The activity log is not configured".to_string()))?;
+    let limit = params.limit.unwrap_or(10).clamp(1, 100);
 
-    // Return response
-    let response = TagsResponse {
-        tags,
+    let interests = activity_log
+        .interests(access.caller.as_deref().unwrap_or("admin"))
+        .await?;
+    let items = crate::activity::recommend(&state, &interests, limit, |content| {
+        access.can_read(content)
+    })
+    .await?;
+
+    info!("Recommended {} content items", items.len());
+
+    Ok(Json(RecommendationsResponse {
+        count: items.len(),
+        items,
+        success: true,
+        error: None,
+    }))
+}
+
+/// List stored content, optionally filtered by dead-link status and date, a
+/// page at a time
+async fn list_content(
+    TenantState(state): TenantState,
+    access: Access,
+    Query(params): Query<ListParams>,
+) -> Result<Json<ContentQueryResponse>, ApiError> {
+    info!("Received list content request, status: {:?}", params.status);
+
+    let metadata = parse_metadata_filter(params.metadata.as_deref())?;
+    if let (Some(since), Some(until)) = (params.since, params.until) {
+        if since >= until {
+            return Err(ApiError::BadRequest(
+                "since must be before until".to_string(),
+            ));
+        }
+    }
+
+    let mut items: Vec<Content> = state
+        .content_storage
+        .list()
+        .await?
+        .into_iter()
+        .filter(|content| params.status.is_none() || content.link_status == params.status)
+        .filter(|content| content.matches_metadata(&metadata))
+        .filter(|content| params.pinned.is_none_or(|pinned| content.pinned == pinned))
+        .filter(|content| {
+            let timestamp = order_timestamp(content, params.order);
+            params.since.is_none_or(|since| timestamp >= since)
+                && params.until.is_none_or(|until| timestamp < until)
+        })
+        .filter(|content| access.can_read(content))
+        .collect();
+
+    sort_pinned_first(&mut items, params.order);
+
+    // The count is of all matching content, so clients know how many pages there are
+    let count = items.len();
+    let items = items
+        .into_iter()
+        .skip(params.offset)
+        .take(params.limit.unwrap_or(usize::MAX))
+        .collect();
+
+    let response = ContentQueryResponse {
+        items,
+        tags: Vec::new(),
         count,
         success: true,
         error: None,
@@ -274,17 +1043,939 @@ async fn get_tags(State(state): State<Arc<AppState>>) -> Result<Json<TagsRespons
     Ok(Json(response))
 }
 
-/// Get content by ID endpoint (returns plain text)
-async fn get_content_text(
-    State(state): State<Arc<AppState>>,
-    Path(id): Path<String>,
-) -> Result<Response, ApiError> {
-    info!("Received get content text request for ID: {}", id);
+fn order_timestamp(content: &Content, order: ContentOrder) -> DateTime<Utc> {
+    match order {
+        ContentOrder::CreatedAt => content.created_at,
+        ContentOrder::UpdatedAt => content.updated_at,
+    }
+}
+
+/// Pinned content first, then the newest by `order`
+fn sort_pinned_first(items: &mut [Content], order: ContentOrder) {
+    items.sort_by_key(|item| {
+        (
+            std::cmp::Reverse(item.pinned),
+            std::cmp::Reverse(order_timestamp(item, order)),
+        )
+    });
+}
+
+/// Pin content, so it's listed before other content
+async fn pin_content(
+    TenantState(state): TenantState,
+    access: Access,
+    Path(id): Path<String>,
+) -> Result<Json<ClassifyResponse>, ApiError> {
+    let id = resolve_content_id(&state, &id).await?;
+    set_pinned(&state, &access, &id, true).await
+}
+
+async fn unpin_content(
+    TenantState(state): TenantState,
+    access: Access,
+    Path(id): Path<String>,
+) -> Result<Json<ClassifyResponse>, ApiError> {
+    let id = resolve_content_id(&state, &id).await?;
+    set_pinned(&state, &access, &id, false).await
+}
+
+async fn set_pinned(
+    state: &AppState,
+    access: &Access,
+    id: &str,
+    pinned: bool,
+) -> Result<Json<ClassifyResponse>, ApiError> {
+    info!(
+        "Received pin update for content ID: {}, pinned: {}",
+        id, pinned
+    );
+
+    let mut content = get_modifiable(state, access, id).await?;
+    // Pinning doesn't change the content, so `updated_at` is left alone
+    if content.pinned != pinned {
+        content.pinned = pinned;
+        state.content_storage.store(&content).await?;
+    }
+
+    Ok(Json(ClassifyResponse {
+        content,
+        success: true,
+        error: None,
+    }))
+}
+
+/// Set or remove metadata keys of stored content
+async fn patch_content_metadata(
+    TenantState(state): TenantState,
+    access: Access,
+    Path(id): Path<String>,
+    Json(request): Json<MetadataPatchRequest>,
+) -> Result<Json<ClassifyResponse>, ApiError> {
+    let id = resolve_content_id(&state, &id).await?;
+    info!("Received metadata update for content ID: {}", id);
+
+    let mut content = get_modifiable(&state, &access, &id).await?;
+
+    for (key, value) in request.metadata {
+        match value {
+            Some(value) => content.metadata.insert(key, value),
+            None => content.metadata.remove(&key),
+        };
+    }
+    content.updated_at = chrono::Utc::now();
+
+    state.content_storage.store(&content).await?;
+
+    Ok(Json(ClassifyResponse {
+        content,
+        success: true,
+        error: None,
+    }))
+}
+
+/// Replace the tags of stored content, e.g. to correct the classifier
+async fn set_content_tags(
+    TenantState(state): TenantState,
+    access: Access,
+    Path(id): Path<String>,
+    Json(request): Json<ContentTagsRequest>,
+) -> Result<Json<ClassifyResponse>, ApiError> {
+    let id = resolve_content_id(&state, &id).await?;
+    info!("Received tag update for content ID: {}", id);
+
+    let content = get_modifiable(&state, &access, &id).await?;
+
+    if request
+        .tags
+        .iter()
+        .any(|tag| state.ingest.tags.normalize_tag(tag).is_empty())
+    {
+        return Err(ApiError::BadRequest("Tags can't be empty".to_string()));
+    }
+    let tags = state.ingest.tags.normalize_tags(&request.tags);
+
+    let (content, added, _) = replace_content_tags(&state, content, tags).await?;
+    if !added.is_empty() {
+        record_activity(&state, &access, Activity::tag(&id, &added)).await;
+    }
+
+    Ok(Json(ClassifyResponse {
+        content,
+        success: true,
+        error: None,
+    }))
+}
+
+/// Run the classifier again on stored content and replace its tags, e.g.
+/// after switching models
+async fn reclassify_content(
+    TenantState(state): TenantState,
+    access: Access,
+    Path(id): Path<String>,
+) -> Result<Json<ReclassifyResponse>, ApiError> {
+    let id = resolve_content_id(&state, &id).await?;
+    info!("Received reclassify request for content ID: {}", id);
+
+    let content = get_modifiable(&state, &access, &id).await?;
+    let tags = classify_stored(&state, &content).await?;
+    let (content, added, removed) = replace_content_tags(&state, content, tags).await?;
+
+    info!(
+        "Reclassified content {}: added {:?}, removed {:?}",
+        id, added, removed
+    );
+
+    Ok(Json(ReclassifyResponse {
+        content,
+        added,
+        removed,
+        success: true,
+        error: None,
+    }))
+}
+
+/// Replace the tags of content in tag and content storage, returning the
+/// stored content and the tags added and removed
+async fn replace_content_tags(
+    state: &AppState,
+    content: Content,
+    tags: Vec<String>,
+) -> Result<(Content, Vec<String>, Vec<String>), ApiError> {
+    let id = content.id.to_string();
+    let current = state.tag_storage.get_tags(&id).await?;
+    let removed: Vec<String> = current
+        .iter()
+        .filter(|tag| !tags.contains(tag))
+        .cloned()
+        .collect();
+    let added: Vec<String> = tags
+        .iter()
+        .filter(|tag| !current.contains(tag))
+        .cloned()
+        .collect();
+
+    if !removed.is_empty() {
+        state.tag_storage.remove_tags(&id, &removed).await?;
+    }
+    if !added.is_empty() {
+        state.tag_storage.add_tags(&id, &added).await?;
+    }
+
+    let content = content.with_tags(tags);
+    state.content_storage.store(&content).await?;
+
+    Ok((content, added, removed))
+}
+
+/// The UUID of content given by its UUID or its slug. Unknown slugs are
+/// returned as is, for the handler to report the content as not found.
+async fn resolve_content_id(state: &AppState, id: &str) -> Result<String, ApiError> {
+    if Uuid::parse_str(id).is_ok() {
+        return Ok(id.to_string());
+    }
+    Ok(match state.content_storage.find_by_slug(id).await? {
+        Some(content) => content.id.to_string(),
+        None => id.to_string(),
+    })
+}
+
+/// Content that the caller may change, or why not
+async fn get_modifiable(state: &AppState, access: &Access, id: &str) -> Result<Content, ApiError> {
+    let content = state
+        .content_storage
+        .get(id)
+        .await?
+        .filter(|content| access.can_read(content))
+        .ok_or_else(|| ApiError::BadRequest(format!("Content with ID {} not found", id)))?;
+
+    if !access.can_modify(&content) {
+        return Err(ApiError::Forbidden(format!(
+            "Content with ID {} is read-only",
+            id
+        )));
+    }
+
+    Ok(content)
+}
+
+fn validate_share_namespaces(namespaces: &[String]) -> Result<Vec<String>, ApiError> {
+    let mut valid = Vec::new();
+    for namespace in namespaces {
+        let namespace = namespace.trim().to_lowercase();
+        let is_valid = !namespace.is_empty()
+            && namespace
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+        if !is_valid {
+            return Err(ApiError::BadRequest(format!(
+                "Invalid namespace '{}'",
+                namespace
+            )));
+        }
+        if !valid.contains(&namespace) {
+            valid.push(namespace);
+        }
+    }
+    Ok(valid)
+}
+
+/// Set who may see content: its visibility and the tenants it is shared with
+async fn set_content_access(
+    TenantState(state): TenantState,
+    access: Access,
+    Path(id): Path<String>,
+    Json(request): Json<ContentAccessRequest>,
+) -> Result<Json<ClassifyResponse>, ApiError> {
+    let id = resolve_content_id(&state, &id).await?;
+    info!("Received access update for content ID: {}", id);
+
+    let shared_with = validate_share_namespaces(&request.shared_with)?;
+    if request.visibility == Visibility::Private && !shared_with.is_empty() {
+        return Err(ApiError::BadRequest(
+            "Private content can't be shared".to_string(),
+        ));
+    }
+
+    let previous = get_modifiable(&state, &access, &id).await?;
+
+    let mut content = previous.clone();
+    content.visibility = request.visibility;
+    content.shared_with = shared_with;
+    content.owner = (request.visibility == Visibility::Private)
+        .then(|| access.caller.clone().unwrap_or_else(|| "admin".to_string()));
+    content.updated_at = chrono::Utc::now();
+
+    state.content_storage.store(&content).await?;
+    update_shares(state.root_tag_storage.clone(), &previous, &content).await?;
+
+    Ok(Json(ClassifyResponse {
+        content,
+        success: true,
+        error: None,
+    }))
+}
+
+/// Share the content that currently has a tag with other tenants
+async fn share_tag(
+    TenantState(state): TenantState,
+    access: Access,
+    Path(tag): Path<String>,
+    Json(request): Json<TagShareRequest>,
+) -> Result<Json<TagShareResponse>, ApiError> {
+    info!("Received share request for tag: {}", tag);
+    let tag = state.ingest.tags.normalize_tag(&tag);
+
+    let namespaces = validate_share_namespaces(&request.namespaces)?;
+    if namespaces.is_empty() {
+        return Err(ApiError::BadRequest(
+            "No namespaces to share with".to_string(),
+        ));
+    }
+
+    let ids = state.tag_storage.find_by_tag(&tag).await?;
+    let mut count = 0;
+    for previous in state.content_storage.get_many(&ids).await? {
+        // Private content stays private, content of others can't be re-shared
+        if previous.visibility == Visibility::Private || !access.can_modify(&previous) {
+            continue;
+        }
+
+        let mut content = previous.clone();
+        for namespace in &namespaces {
+            if !content.shared_with.contains(namespace) {
+                content.shared_with.push(namespace.clone());
+            }
+        }
+        if content.shared_with == previous.shared_with {
+            continue;
+        }
+        content.updated_at = chrono::Utc::now();
+
+        state.content_storage.store(&content).await?;
+        update_shares(state.root_tag_storage.clone(), &previous, &content).await?;
+        count += 1;
+    }
+
+    Ok(Json(TagShareResponse {
+        tag,
+        count,
+        success: true,
+        error: None,
+    }))
+}
+
+async fn delete_content(
+    TenantState(state): TenantState,
+    access: Access,
+    Path(id): Path<String>,
+) -> Result<Json<DeleteResponse>, ApiError> {
+    let id = resolve_content_id(&state, &id).await?;
+    info!("Received delete content request for ID: {}", id);
+
+    let content = state
+        .content_storage
+        .get(&id)
+        .await?
+        .filter(|content| access.can_read(content));
+
+    if let Some(content) = content {
+        if !access.can_modify(&content) {
+            return Err(ApiError::Forbidden(format!(
+                "Content with ID {} is read-only",
+                id
+            )));
+        }
+
+        // Remove the content from the tag indexes of the tenants it is shared with
+        let unshared = Content {
+            visibility: Visibility::Team,
+            shared_with: Vec::new(),
+            ..content.clone()
+        };
+        update_shares(state.root_tag_storage.clone(), &content, &unshared).await?;
+
+        let tags = state.tag_storage.get_tags(&id).await?;
+        info!("Content has {} tags that may need cleanup", tags.len());
+
+        let deleted = state
+            .storage_transaction()
+            .delete_with_tags(&id, &tags)
+            .await?;
+
+        if !deleted {
+            return Err(ApiError::BadRequest(format!(
+                "Failed to delete content with ID: {}",
+                id
+            )));
+        }
+
+        if let Some(index) = &state.semantic_index {
+            index.remove(&id).await;
+        }
+
+        let mut orphaned_tags = Vec::new();
+
+        for tag in &tags {
+            let content_with_tag = state.tag_storage.find_by_tag(tag).await?;
+
+            if content_with_tag.is_empty() {
+                info!("Tag '{}' is now orphaned, will be removed", tag);
+                orphaned_tags.push(tag.clone());
+            }
+        }
+
+        let response = DeleteResponse {
+            success: true,
+            id: Some(id),
+            removed_tags: orphaned_tags,
+            error: None,
+        };
+
+        Ok(Json(response))
+    } else {
+        Err(ApiError::BadRequest(format!(
+            "Content with ID {} not found",
+            id
+        )))
+    }
+}
+
+/// Group the embedded content into clusters of similar meaning and propose a
+/// label for each, optionally adding it as a tag
+async fn cluster_content(
+    TenantState(state): TenantState,
+    caller: Option<Extension<Caller>>,
+    Json(request): Json<ClusterRequest>,
+) -> Result<Json<ClusterResponse>, ApiError> {
+    info!("Received cluster request: {:?}", request);
+
+    require_admin(
+        caller,
+        "Content can only be clustered with the main API key",
+    )?;
+
+    if state.semantic_index.is_none() {
+        return Err(ApiError::BadRequest(
+            "Clustering requires semantic search to be configured".to_string(),
+        ));
+    }
+    if request.clusters == Some(0) {
+        return Err(ApiError::BadRequest(
+            "clusters must be at least 1".to_string(),
+        ));
+    }
+
+    let (clusters, tagged) = crate::embedding::cluster::cluster_content(&state, &request).await?;
+    info!(
+        "Proposed {} clusters, tagged {} items",
+        clusters.len(),
+        tagged
+    );
+
+    Ok(Json(ClusterResponse {
+        clusters,
+        tagged,
+        success: true,
+        error: None,
+    }))
+}
+
+/// Hard-delete content matching a hash, URL pattern or metadata key
+async fn purge_content(
+    TenantState(state): TenantState,
+    caller: Option<Extension<Caller>>,
+    Json(request): Json<PurgeRequest>,
+) -> Result<Json<PurgeResponse>, ApiError> {
+    info!("Received purge request: {:?}", request);
+
+    require_admin(caller, "Content can only be purged with the main API key")?;
+
+    if request.hash.is_none() && request.url_pattern.is_none() && request.metadata_key.is_none() {
+        return Err(ApiError::BadRequest(
+            "Provide a hash, url_pattern or metadata_key to purge".to_string(),
+        ));
+    }
+
+    Ok(Json(purge(&state, &request).await?))
+}
+
+/// Remove the tags on `TAG_BLOCKLIST` from content stored before they were blocked
+async fn strip_blocked_tags(
+    TenantState(state): TenantState,
+    caller: Option<Extension<Caller>>,
+) -> Result<Json<BlockedTagsResponse>, ApiError> {
+    info!("Received request to remove blocked tags from stored content");
+
+    require_admin(
+        caller,
+        "Blocked tags can only be removed with the main API key",
+    )?;
+
+    if state.ingest.tags.blocklist.is_empty() {
+        return Err(ApiError::BadRequest(
+            "No tags are blocked, set TAG_BLOCKLIST first".to_string(),
+        ));
+    }
+
+    let removed = crate::tags::strip_blocked_tags(&state).await?;
+
+    Ok(Json(BlockedTagsResponse {
+        blocklist: state.ingest.tags.blocklist.clone(),
+        removed,
+        success: true,
+        error: None,
+    }))
+}
+
+/// Tags that are probably the same tag, with the merge that would combine them
+async fn get_tag_duplicates(
+    TenantState(state): TenantState,
+    Query(params): Query<TagDuplicatesParams>,
+) -> Result<Json<TagDuplicatesResponse>, ApiError> {
+    info!("Received request for duplicate tags");
+
+    if params.semantic && state.semantic_index.is_none() {
+        return Err(ApiError::BadRequest(
+            "Comparing tags by meaning requires semantic search to be configured".to_string(),
+        ));
+    }
+
+    let duplicates = crate::tags::find_duplicates(&state, params.semantic).await?;
+
+    Ok(Json(TagDuplicatesResponse {
+        count: duplicates.len(),
+        duplicates,
+        success: true,
+        error: None,
+    }))
+}
+
+/// Replace tags with another tag on all content
+async fn merge_tags(
+    TenantState(state): TenantState,
+    caller: Option<Extension<Caller>>,
+    Json(request): Json<TagMergeRequest>,
+) -> Result<Json<TagMergeResponse>, ApiError> {
+    info!(
+        "Received request to merge {:?} into {}",
+        request.tags, request.into
+    );
+
+    require_admin(caller, "Tags can only be merged with the main API key")?;
+
+    let into = state.ingest.tags.normalize_tag(&request.into);
+    if into.is_empty() {
+        return Err(ApiError::BadRequest(
+            "The tag to merge into can't be empty".to_string(),
+        ));
+    }
+    // Tags are looked up as stored, so they are not normalized
+    let tags: Vec<String> = request
+        .tags
+        .iter()
+        .map(|tag| tag.trim().to_string())
+        .filter(|tag| !tag.is_empty())
+        .collect();
+    if tags.is_empty() {
+        return Err(ApiError::BadRequest("No tags to merge".to_string()));
+    }
+
+    let merged = crate::tags::merge_tags(&state, &tags, &into).await?;
+
+    Ok(Json(TagMergeResponse {
+        into,
+        merged,
+        success: true,
+        error: None,
+    }))
+}
+
+async fn create_api_key(
+    TenantState(state): TenantState,
+    caller: Option<Extension<Caller>>,
+    Json(request): Json<CreateApiKeyRequest>,
+) -> Result<Json<ApiKeyResponse>, ApiError> {
+    info!("Received request to create API key {}", request.name);
+
+    if request.name.trim().is_empty() {
+        return Err(ApiError::BadRequest("API key name is required".to_string()));
+    }
+
+    let (key, secret) = state
+        .admin_api_keys(caller)?
+        .create(request.name.trim(), request.namespace, request.read_only)
+        .await?;
+
+    Ok(Json(ApiKeyResponse {
+        key,
+        secret: Some(secret),
+        success: true,
+        error: None,
+    }))
+}
+
+async fn list_api_keys(
+    TenantState(state): TenantState,
+    caller: Option<Extension<Caller>>,
+) -> Result<Json<ApiKeysResponse>, ApiError> {
+    info!("Received request to list API keys");
+
+    let keys = state.admin_api_keys(caller)?.list().await?;
+
+    Ok(Json(ApiKeysResponse {
+        count: keys.len(),
+        keys,
+        success: true,
+        error: None,
+    }))
+}
+
+async fn revoke_api_key(
+    TenantState(state): TenantState,
+    caller: Option<Extension<Caller>>,
+    Path(id): Path<String>,
+) -> Result<Json<ApiKeyResponse>, ApiError> {
+    info!("Received request to revoke API key {}", id);
+
+    let key = state
+        .admin_api_keys(caller)?
+        .revoke(&id)
+        .await?
+        .ok_or_else(|| ApiError::BadRequest(format!("API key {} not found", id)))?;
+
+    Ok(Json(ApiKeyResponse {
+        key,
+        secret: None,
+        success: true,
+        error: None,
+    }))
+}
+
+/// Issue a new key in place of an existing one, which stays valid for the grace period
+async fn rotate_api_key(
+    TenantState(state): TenantState,
+    caller: Option<Extension<Caller>>,
+    Path(id): Path<String>,
+) -> Result<Json<ApiKeyResponse>, ApiError> {
+    info!("Received request to rotate API key {}", id);
+
+    let (key, secret) = state
+        .admin_api_keys(caller)?
+        .rotate(&id)
+        .await
+        .map_err(|e| match e {
+            ClassifyError::ApiError(message) => ApiError::BadRequest(message),
+            e => e.into(),
+        })?
+        .ok_or_else(|| ApiError::BadRequest(format!("API key {} not found", id)))?;
+
+    Ok(Json(ApiKeyResponse {
+        key,
+        secret: Some(secret),
+        success: true,
+        error: None,
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UsageParams {
+    /// Month as `YYYY-MM`, the current month when unset
+    pub month: Option<String>,
+}
+
+fn is_month(month: &str) -> bool {
+    chrono::NaiveDate::parse_from_str(&format!("{}-01", month), "%Y-%m-%d").is_ok()
+}
+
+/// Usage of all accounts for the main API key, of the caller's own account otherwise
+async fn get_usage(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<UsageParams>,
+    Extension(Caller(caller)): Extension<Caller>,
+    tenant: Option<Extension<Tenant>>,
+) -> Result<Json<UsageResponse>, ApiError> {
+    info!("Received request for usage");
+
+    let usage = state
+        .usage
+        .as_deref()
+        .ok_or_else(|| ApiError::BadRequest("Usage tracking is not configured".to_string()))?;
+
+    let month = match params.month {
+        Some(month) if is_month(&month) => month,
+        Some(month) => {
+            return Err(ApiError::BadRequest(format!(
+                "Invalid month '{}', expected YYYY-MM",
+                month
+            )))
+        }
+        None => crate::usage::current_month(),
+    };
+
+    let namespace = tenant.as_ref().map(|Extension(Tenant(ns))| ns.as_str());
+    let account = (caller != "admin").then(|| middleware::usage_account(&caller, namespace));
+    let accounts = usage.report(&month, account.as_deref()).await?;
+
+    Ok(Json(UsageResponse {
+        month,
+        accounts,
+        success: true,
+        error: None,
+    }))
+}
+
+/// Metrics in the Prometheus text format, for the main API key only
+async fn get_metrics(
+    State(state): State<Arc<AppState>>,
+    caller: Option<Extension<Caller>>,
+) -> Result<Response, ApiError> {
+    require_admin(caller, "Metrics can only be read with the main API key")?;
+
+    let metrics = state
+        .metrics
+        .as_ref()
+        .ok_or_else(|| ApiError::BadRequest("Metrics are not enabled".to_string()))?;
+
+    Ok((
+        [(CONTENT_TYPE, "text/plain; version=0.0.4")],
+        metrics.render(),
+    )
+        .into_response())
+}
+
+/// Log filter in use, for the main API key only
+async fn get_log_level(
+    caller: Option<Extension<Caller>>,
+) -> Result<Json<LogLevelResponse>, ApiError> {
+    require_admin(
+        caller,
+        "The log level can only be managed with the main API key",
+    )?;
+
+    Ok(Json(LogLevelResponse {
+        filter: crate::logging::current_filter()?,
+        success: true,
+        error: None,
+    }))
+}
+
+/// Change the log filter until the next restart, for the main API key only
+async fn set_log_level(
+    caller: Option<Extension<Caller>>,
+    Json(request): Json<LogLevelRequest>,
+) -> Result<Json<LogLevelResponse>, ApiError> {
+    require_admin(
+        caller,
+        "The log level can only be managed with the main API key",
+    )?;
+
+    let filter = crate::logging::parse_filter(&request.filter)
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    crate::logging::set_filter(filter)?;
+    warn!("Log filter changed to {}", request.filter);
+
+    Ok(Json(LogLevelResponse {
+        filter: crate::logging::current_filter()?,
+        success: true,
+        error: None,
+    }))
+}
+
+/// Whether maintenance mode is on, for the main API key only
+async fn get_maintenance(
+    State(state): State<Arc<AppState>>,
+    caller: Option<Extension<Caller>>,
+) -> Result<Json<MaintenanceResponse>, ApiError> {
+    require_admin(
+        caller,
+        "Maintenance mode can only be managed with the main API key",
+    )?;
+
+    let message = state.maintenance.message();
+    Ok(Json(MaintenanceResponse {
+        enabled: message.is_some(),
+        message,
+        success: true,
+        error: None,
+    }))
+}
+
+/// Switch maintenance mode on or off until the next restart, for the main API key only
+async fn set_maintenance(
+    State(state): State<Arc<AppState>>,
+    caller: Option<Extension<Caller>>,
+    Json(request): Json<MaintenanceRequest>,
+) -> Result<Json<MaintenanceResponse>, ApiError> {
+    require_admin(
+        caller,
+        "Maintenance mode can only be managed with the main API key",
+    )?;
+
+    if request.enabled {
+        state.maintenance.enable(request.message);
+        warn!("Maintenance mode enabled, changes are refused");
+    } else {
+        state.maintenance.disable();
+        warn!("Maintenance mode disabled");
+    }
+
+    let message = state.maintenance.message();
+    Ok(Json(MaintenanceResponse {
+        enabled: message.is_some(),
+        message,
+        success: true,
+        error: None,
+    }))
+}
+
+/// Task counts of the async runtime and stats of the shared storage
+/// connections, for the main API key only
+async fn get_runtime_stats(
+    caller: Option<Extension<Caller>>,
+) -> Result<Json<RuntimeStatsResponse>, ApiError> {
+    require_admin(
+        caller,
+        "Runtime stats can only be read with the main API key",
+    )?;
+
+    let metrics = tokio::runtime::Handle::current().metrics();
+    Ok(Json(RuntimeStatsResponse {
+        workers: metrics.num_workers(),
+        alive_tasks: metrics.num_alive_tasks(),
+        global_queue_depth: metrics.global_queue_depth(),
+        connections: crate::storage::connections::stats(),
+        success: true,
+        error: None,
+    }))
+}
+
+async fn get_tags(
+    TenantState(state): TenantState,
+    Query(params): Query<TagsParams>,
+) -> Result<Json<TagsResponse>, ApiError> {
+    info!("Received request for all tags");
+
+    // Retrieve all tags from storage
+    let tags = state.tag_storage.list_tags().await?;
+    let count = tags.len();
+
+    info!("Retrieved {} tags", count);
+
+    // Return response
+    let response = TagsResponse {
+        tree: params.tree.then(|| crate::tags::tag_tree(&tags)),
+        tags,
+        count,
+        success: true,
+        error: None,
+    };
+
+    Ok(Json(response))
+}
+
+async fn get_tag_counts(
+    TenantState(state): TenantState,
+) -> Result<Json<TagCountsResponse>, ApiError> {
+    info!("Received request for tag counts");
+
+    let counts = state.tag_storage.tag_counts().await?;
+
+    Ok(Json(TagCountsResponse {
+        counts,
+        success: true,
+        error: None,
+    }))
+}
+
+/// Tags used more in the last days than in the weeks before
+async fn get_trending_tags(
+    TenantState(state): TenantState,
+    Query(params): Query<TrendParams>,
+) -> Result<Json<TrendingTagsResponse>, ApiError> {
+    info!("Received trending tags request: {:?}", params);
+
+    let tag_trends = state
+        .tag_trends
+        .as_ref()
+        .ok_or_else(|| ApiError::BadRequest("Tag trends are not configured".to_string()))?;
+
+    let window = TrendWindow {
+        recent_days: params.recent_days.unwrap_or(7),
+        baseline_days: params.baseline_days.unwrap_or(28),
+        min_count: params.min_count.unwrap_or(2),
+    };
+    if window.recent_days == 0 || window.baseline_days == 0 {
+        return Err(ApiError::BadRequest(
+            "recent_days and baseline_days must be at least 1".to_string(),
+        ));
+    }
+    if window.recent_days.saturating_add(window.baseline_days) > tag_trends.retention_days() {
+        return Err(ApiError::BadRequest(format!(
+            "recent_days and baseline_days together can't exceed the {} days tag counts are kept",
+            tag_trends.retention_days()
+        )));
+    }
+
+    let mut trending = tag_trends
+        .trending(
+            state.namespace.as_deref(),
+            &window,
+            chrono::Utc::now().date_naive(),
+        )
+        .await?;
+    trending.truncate(params.limit.unwrap_or(10));
+
+    Ok(Json(TrendingTagsResponse {
+        trending,
+        recent_days: window.recent_days,
+        baseline_days: window.baseline_days,
+        success: true,
+        error: None,
+    }))
+}
+
+/// Content classified over the last day or week, grouped by tag
+async fn get_digest(
+    TenantState(state): TenantState,
+    access: Access,
+    Query(params): Query<DigestParams>,
+) -> Result<Json<DigestResponse>, ApiError> {
+    info!("Received digest request, period: {:?}", params.period);
+
+    let contents = state
+        .content_storage
+        .list()
+        .await?
+        .into_iter()
+        .filter(|content| access.can_read(content))
+        .collect();
+
+    Ok(Json(DigestResponse {
+        digest: crate::jobs::digest::compile(contents, params.period, chrono::Utc::now()),
+        success: true,
+        error: None,
+    }))
+}
+
+/// Get content by ID endpoint (returns plain text)
+async fn get_content_text(
+    TenantState(state): TenantState,
+    access: Access,
+    Path(id): Path<String>,
+) -> Result<Response, ApiError> {
+    let id = resolve_content_id(&state, &id).await?;
+    info!("Received get content text request for ID: {}", id);
 
     // Retrieve content from storage
-    let content_option = state.content_storage.get(&id).await?;
+    let content_option = state
+        .content_storage
+        .get(&id)
+        .await?
+        .filter(|content| access.can_read(content));
 
     if let Some(content) = content_option {
+        record_activity(&state, &access, Activity::view(&content)).await;
+
         // Return the content text with 200 OK status and Content-Type header
         let response = Response::builder()
             .status(StatusCode::OK)
@@ -302,19 +1993,414 @@ async fn get_content_text(
     }
 }
 
+/// An archived page as HTML. The page is third-party content served from the
+/// origin of the UI, which keeps its API key in local storage, so it is
+/// sandboxed: its scripts don't run and it can't read that storage.
+fn snapshot_response(snapshot: Vec<u8>) -> Response {
+    Response::builder()
+        .status(StatusCode::OK)
+        .header("Content-Type", "text/html; charset=utf-8")
+        .header("Content-Security-Policy", "sandbox")
+        .header("X-Content-Type-Options", "nosniff")
+        .body(axum::body::Body::from(snapshot))
+        .unwrap()
+}
+
+/// Get the archived page snapshot of URL content
+async fn get_content_snapshot(
+    TenantState(state): TenantState,
+    access: Access,
+    Path(id): Path<String>,
+) -> Result<Response, ApiError> {
+    let id = resolve_content_id(&state, &id).await?;
+    info!("Received get snapshot request for ID: {}", id);
+
+    // Only private content can be hidden from a caller that sees the snapshot
+    if !access.sees_private() {
+        let readable = state
+            .content_storage
+            .get(&id)
+            .await?
+            .is_some_and(|content| access.can_read(&content));
+        if !readable {
+            return Err(ApiError::BadRequest(format!(
+                "Content with ID {} not found",
+                id
+            )));
+        }
+    }
+
+    let snapshot = state
+        .content_storage
+        .get_attachment(&id, SNAPSHOT_ATTACHMENT)
+        .await?;
+
+    if let Some(snapshot) = snapshot {
+        if state.activity_log.is_some() {
+            if let Some(content) = state.content_storage.get(&id).await? {
+                record_activity(&state, &access, Activity::view(&content)).await;
+            }
+        }
+
+        Ok(snapshot_response(snapshot))
+    } else {
+        Err(ApiError::BadRequest(format!(
+            "No snapshot stored for content with ID {}",
+            id
+        )))
+    }
+}
+
+/// Create a signed link that gives access to one content item without an API key
+async fn share_content(
+    TenantState(state): TenantState,
+    access: Access,
+    Path(id): Path<String>,
+    request: Option<Json<ShareRequest>>,
+) -> Result<Json<ShareResponse>, ApiError> {
+    let id = resolve_content_id(&state, &id).await?;
+    info!("Received share request for ID: {}", id);
+
+    let share_links = state
+        .share_links
+        .as_deref()
+        .ok_or_else(|| ApiError::BadRequest("Share links are not configured".to_string()))?;
+
+    let requested = request
+        .and_then(|Json(request)| request.expires_in_secs)
+        .map(Duration::from_secs);
+    let ttl = share_links.ttl(requested).ok_or_else(|| {
+        ApiError::BadRequest("Requested share link lifetime is too long".to_string())
+    })?;
+
+    // Tenants can only share content they can see themselves
+    let readable = state
+        .content_storage
+        .get(&id)
+        .await?
+        .is_some_and(|content| access.can_read(&content));
+    if !readable {
+        return Err(ApiError::BadRequest(format!(
+            "Content with ID {} not found",
+            id
+        )));
+    }
+
+    let expires_at = chrono::Utc::now()
+        + chrono::Duration::from_std(ttl).map_err(|e| ApiError::BadRequest(e.to_string()))?;
+
+    Ok(Json(ShareResponse {
+        url: share_links.link(&id, expires_at),
+        expires_at,
+        success: true,
+        error: None,
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SharedParams {
+    pub expires: i64,
+    pub signature: String,
+}
+
+/// Content behind a share link: the archived page snapshot when there is one,
+/// otherwise the content text
+async fn get_shared_content(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Query(params): Query<SharedParams>,
+) -> Result<Response, ApiError> {
+    let valid = state.share_links.as_deref().is_some_and(|share_links| {
+        share_links.verify(
+            &id,
+            params.expires,
+            &params.signature,
+            chrono::Utc::now().timestamp(),
+        )
+    });
+    if !valid {
+        return Err(ApiError::Forbidden(
+            "Invalid or expired share link".to_string(),
+        ));
+    }
+
+    info!("Serving shared content {}", id);
+
+    if let Some(snapshot) = state
+        .content_storage
+        .get_attachment(&id, SNAPSHOT_ATTACHMENT)
+        .await?
+    {
+        return Ok(snapshot_response(snapshot));
+    }
+
+    match state.content_storage.get(&id).await? {
+        Some(content) => Ok(Response::builder()
+            .status(StatusCode::OK)
+            .header("Content-Type", "text/plain; charset=utf-8")
+            .header("X-Content-Type-Options", "nosniff")
+            .body(axum::body::Body::from(content.content))
+            .unwrap()),
+        None => Err(ApiError::BadRequest(format!(
+            "Content with ID {} not found",
+            id
+        ))),
+    }
+}
+
+/// Start a bulk import of URLs, given as JSON or as a newline separated list
+async fn import_urls(
+    TenantState(state): TenantState,
+    account: Option<Extension<UsageAccount>>,
+    headers: HeaderMap,
+    body: String,
+) -> Result<Response, ApiError> {
+    let is_json = headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/json"));
+
+    let urls = if is_json {
+        serde_json::from_str::<ImportRequest>(&body)
+            .map_err(|e| ApiError::BadRequest(format!("Invalid import request: {}", e)))?
+            .urls
+    } else {
+        parse_url_list(&body)
+    };
+
+    info!("Received import request for {} URLs", urls.len());
+
+    if urls.is_empty() {
+        return Err(ApiError::BadRequest("No URLs provided".to_string()));
+    }
+
+    let job = state.imports.start(
+        state.clone(),
+        urls,
+        None,
+        account.map(|Extension(UsageAccount(account))| account),
+    );
+
+    Ok((StatusCode::ACCEPTED, Json(job)).into_response())
+}
+
+/// Crawl and classify all pages listed in a sitemap
+async fn import_sitemap(
+    TenantState(state): TenantState,
+    account: Option<Extension<UsageAccount>>,
+    Json(request): Json<SitemapImportRequest>,
+) -> Result<Response, ApiError> {
+    info!("Received sitemap import request for {}", request.url);
+
+    let pages =
+        fetch_sitemap_pages(&state.http_client, &request.url, state.sitemap_max_pages).await?;
+
+    if pages.is_empty() {
+        return Err(ApiError::BadRequest(format!(
+            "No pages found in sitemap {}",
+            request.url
+        )));
+    }
+
+    let job = state.imports.start(
+        state.clone(),
+        pages,
+        Some(request.url),
+        account.map(|Extension(UsageAccount(account))| account),
+    );
+
+    Ok((StatusCode::ACCEPTED, Json(job)).into_response())
+}
+
+/// Classify the bookmarks of a Raindrop.io export or a Chrome or Firefox
+/// bookmark backup, tagged with their folders and tags
+async fn import_bookmarks(
+    TenantState(state): TenantState,
+    account: Option<Extension<UsageAccount>>,
+    Json(export): Json<serde_json::Value>,
+) -> Result<Response, ApiError> {
+    let export = parse_bookmarks(&export).ok_or_else(|| {
+        ApiError::BadRequest("Not a Raindrop.io, Chrome or Firefox bookmark export".to_string())
+    })?;
+
+    info!(
+        "Received {} bookmark import with {} URLs, {} entries skipped",
+        export.format,
+        export.bookmarks.len(),
+        export.skipped
+    );
+
+    if export.bookmarks.is_empty() {
+        return Err(ApiError::BadRequest("No bookmarks found".to_string()));
+    }
+
+    let job = state.imports.start_bookmarks(
+        state.clone(),
+        export,
+        account.map(|Extension(UsageAccount(account))| account),
+    );
+
+    Ok((StatusCode::ACCEPTED, Json(job)).into_response())
+}
+
+/// Get the progress of a bulk import job
+async fn get_import_job(
+    TenantState(state): TenantState,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ImportJob>, ApiError> {
+    state
+        .imports
+        .get(&id)
+        .filter(|job| state.namespace.is_none() || job.namespace == state.namespace)
+        .map(Json)
+        .ok_or_else(|| ApiError::BadRequest(format!("Import job {} not found", id)))
+}
+
+/// Classify the content of an arbitrary JSON payload pushed by an external service
+async fn ingest_webhook(
+    TenantState(state): TenantState,
+    account: Option<Extension<UsageAccount>>,
+    Path(source): Path<String>,
+    Json(payload): Json<serde_json::Value>,
+) -> Result<Response, ApiError> {
+    let source = source.to_lowercase();
+    info!("Received webhook from source: {}", source);
+
+    let mapping = state
+        .webhooks
+        .get(&source)
+        .ok_or_else(|| ApiError::BadRequest(format!("Unknown webhook source: {}", source)))?;
+
+    let text = extract_content(&payload, mapping).ok_or_else(|| {
+        ApiError::BadRequest(format!("No content found in payload for source {}", source))
+    })?;
+
+    let tags = vec![format!("source:{}", source)];
+    if state.classify_async {
+        return queue_classification(&state, text, tags, HashMap::new(), account).await;
+    }
+
+    let content = match ingest_with_tags(&state, text, &tags).await? {
+        Ingested::Created(content) => content,
+        Ingested::Duplicate(existing_content) => {
+            return Err(ApiError::Conflict(Box::new(ClassifyResponse {
+                content: existing_content,
+                success: true,
+                error: None,
+            })));
+        }
+    };
+
+    Ok(Json(ClassifyResponse {
+        content,
+        success: true,
+        error: None,
+    })
+    .into_response())
+}
+
+/// Slash commands and Events API requests from a Slack app
+async fn slack_request(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    body: axum::body::Bytes,
+) -> Result<Response, ApiError> {
+    let Some(config) = state.slack.clone() else {
+        return Err(ApiError::BadRequest(
+            "Slack integration is not configured".to_string(),
+        ));
+    };
+
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default()
+    };
+
+    if !slack::verify_signature(
+        &config.signing_secret,
+        header("X-Slack-Request-Timestamp"),
+        &body,
+        header("X-Slack-Signature"),
+        chrono::Utc::now().timestamp(),
+    ) {
+        warn!("Invalid Slack request signature");
+        return Ok(StatusCode::UNAUTHORIZED.into_response());
+    }
+
+    // Slash commands are form encoded, events are JSON
+    if header(CONTENT_TYPE.as_str()).starts_with("application/x-www-form-urlencoded") {
+        let command = SlashCommand::parse(&body);
+        if command.text.trim().is_empty() {
+            return Ok(Json(serde_json::json!({
+                "response_type": "ephemeral",
+                "text": format!("Usage: {} <link or text>", command.command),
+            }))
+            .into_response());
+        }
+
+        info!("Received Slack command {}", command.command);
+        // Slack expects an answer within 3 seconds, the tags follow via the response URL
+        tokio::spawn(slack::run_command(state.clone(), command));
+
+        return Ok(Json(serde_json::json!({
+            "response_type": "ephemeral",
+            "text": "Classifying...",
+        }))
+        .into_response());
+    }
+
+    let event: SlackEvent = serde_json::from_slice(&body)
+        .map_err(|e| ApiError::BadRequest(format!("Invalid Slack event: {}", e)))?;
+
+    match event {
+        SlackEvent::UrlVerification { challenge } => Ok(challenge.into_response()),
+        SlackEvent::EventCallback { event } => {
+            if event["type"] == "link_shared" {
+                let event: LinkShared = serde_json::from_value(event).map_err(|e| {
+                    ApiError::BadRequest(format!("Invalid link_shared event: {}", e))
+                })?;
+                tokio::spawn(async move {
+                    if let Err(e) = slack::unfurl_links(state.clone(), config, event).await {
+                        warn!("{}", e);
+                    }
+                });
+            }
+            Ok(StatusCode::OK.into_response())
+        }
+        SlackEvent::Other => Ok(StatusCode::OK.into_response()),
+    }
+}
+
 pub enum ApiError {
     InternalError(ClassifyError),
     BadRequest(String),
-    Conflict(ClassifyResponse),
+    Forbidden(String),
+    Conflict(Box<ClassifyResponse>),
+    TooManyRequests(Duration),
+    /// A monthly quota is used up, with the time until it starts over
+    QuotaExceeded(&'static str, Duration),
+    /// Changes are refused in maintenance mode, with the message to show
+    ServiceUnavailable(String),
 }
 
 impl From<ClassifyError> for ApiError {
     fn from(error: ClassifyError) -> Self {
         error!("API error: {}", error);
-        Self::InternalError(error)
+        match error {
+            // Passed on, so clients back off like the classifier did
+            ClassifyError::RateLimited(_, retry_after) => {
+                Self::TooManyRequests(retry_after.unwrap_or(PROVIDER_RETRY_AFTER))
+            }
+            error => Self::InternalError(error),
+        }
     }
 }
 
+/// `Retry-After` sent when a rate limited provider didn't say how long to wait
+const PROVIDER_RETRY_AFTER: Duration = Duration::from_secs(30);
+
 impl IntoResponse for ApiError {
     fn into_response(self) -> Response {
         match self {
@@ -354,6 +2440,90 @@ impl IntoResponse for ApiError {
                     ))
                     .unwrap()
             }
+            Self::Forbidden(message) => {
+                let body = Json(ContentQueryResponse {
+                    items: Vec::new(),
+                    tags: Vec::new(),
+                    count: 0,
+                    success: false,
+                    error: Some(message),
+                });
+
+                // Create response with explicit Content-Type header
+                Response::builder()
+                    .status(StatusCode::FORBIDDEN)
+                    .header("Content-Type", "application/json")
+                    .body(axum::body::Body::from(
+                        serde_json::to_string(&body.0).unwrap(),
+                    ))
+                    .unwrap()
+            }
+            Self::TooManyRequests(retry_after) => {
+                let body = Json(ContentQueryResponse {
+                    items: Vec::new(),
+                    tags: Vec::new(),
+                    count: 0,
+                    success: false,
+                    error: Some("Rate limit exceeded".to_string()),
+                });
+
+                // Create response with explicit Content-Type header
+                Response::builder()
+                    .status(StatusCode::TOO_MANY_REQUESTS)
+                    .header("Content-Type", "application/json")
+                    .header(
+                        RETRY_AFTER,
+                        retry_after.as_secs_f64().ceil().max(1.0) as u64,
+                    )
+                    .body(axum::body::Body::from(
+                        serde_json::to_string(&body.0).unwrap(),
+                    ))
+                    .unwrap()
+            }
+            Self::QuotaExceeded(limit, retry_after) => {
+                let body = Json(ContentQueryResponse {
+                    items: Vec::new(),
+                    tags: Vec::new(),
+                    count: 0,
+                    success: false,
+                    error: Some(format!("Monthly {} quota exceeded", limit)),
+                });
+
+                // Storage is paid for, other limits are waited out until next month
+                let response = if limit == "stored bytes" {
+                    Response::builder().status(StatusCode::PAYMENT_REQUIRED)
+                } else {
+                    Response::builder()
+                        .status(StatusCode::TOO_MANY_REQUESTS)
+                        .header(RETRY_AFTER, retry_after.as_secs().max(1))
+                };
+
+                // Create response with explicit Content-Type header
+                response
+                    .header("Content-Type", "application/json")
+                    .body(axum::body::Body::from(
+                        serde_json::to_string(&body.0).unwrap(),
+                    ))
+                    .unwrap()
+            }
+            Self::ServiceUnavailable(message) => {
+                let body = Json(ContentQueryResponse {
+                    items: Vec::new(),
+                    tags: Vec::new(),
+                    count: 0,
+                    success: false,
+                    error: Some(message),
+                });
+
+                // Create response with explicit Content-Type header
+                Response::builder()
+                    .status(StatusCode::SERVICE_UNAVAILABLE)
+                    .header("Content-Type", "application/json")
+                    .body(axum::body::Body::from(
+                        serde_json::to_string(&body.0).unwrap(),
+                    ))
+                    .unwrap()
+            }
             Self::Conflict(response) => {
                 // Create response with explicit Content-Type header
                 Response::builder()
@@ -0,0 +1,237 @@
+use async_trait::async_trait;
+use axum::http::Method;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+use crate::config::{AppConfig, RateLimit, RateLimitConfig, RateLimitStoreType, RouteClass};
+use crate::{ClassifyError, ClassifyResult};
+
+/// Token buckets shared by the requests of one caller
+#[async_trait]
+pub trait RateLimitStore: Send + Sync {
+    /// Take a token from a bucket, returning how long to wait when it is empty
+    async fn take(&self, bucket: &str, limit: &RateLimit) -> ClassifyResult<Option<Duration>>;
+}
+
+/// Refill a bucket holding `tokens` for the time since it was last used and take a token
+pub(crate) fn take_token(
+    tokens: f64,
+    elapsed: Duration,
+    limit: &RateLimit,
+) -> (f64, Option<Duration>) {
+    let capacity = f64::from(limit.requests);
+    let rate = capacity / limit.period.as_secs_f64();
+    let tokens = (tokens + elapsed.as_secs_f64() * rate).min(capacity);
+
+    if tokens >= 1.0 {
+        (tokens - 1.0, None)
+    } else {
+        (tokens, Some(Duration::from_secs_f64((1.0 - tokens) / rate)))
+    }
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+    period: Duration,
+}
+
+/// Buckets kept in memory, each instance of the service limits on its own
+#[derive(Default)]
+pub struct MemoryRateLimitStore {
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl MemoryRateLimitStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for MemoryRateLimitStore {
+    async fn take(&self, bucket: &str, limit: &RateLimit) -> ClassifyResult<Option<Duration>> {
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().await;
+
+        // Drop buckets that have refilled completely, they behave like new ones
+        if buckets.len() > 10_000 {
+            buckets.retain(|_, bucket| now.duration_since(bucket.updated) < bucket.period);
+        }
+
+        let bucket = buckets.entry(bucket.to_string()).or_insert(Bucket {
+            tokens: f64::from(limit.requests),
+            updated: now,
+            period: limit.period,
+        });
+
+        let (tokens, retry_after) = take_token(bucket.tokens, now - bucket.updated, limit);
+        bucket.tokens = tokens;
+        bucket.updated = now;
+        bucket.period = limit.period;
+
+        Ok(retry_after)
+    }
+}
+
+/// Buckets kept in Redis, shared by all instances of the service
+#[cfg(feature = "redis")]
+pub struct RedisRateLimitStore {
+    connection: crate::storage::redis::SharedConnection,
+    prefix: String,
+    script: redis::Script,
+}
+
+#[cfg(feature = "redis")]
+impl RedisRateLimitStore {
+    /// Refill and take a token in one step, returning the milliseconds to wait
+    const TAKE_SCRIPT: &'static str = r#"
+        local capacity = tonumber(ARGV[1])
+        local period = tonumber(ARGV[2])
+        local now = tonumber(ARGV[3])
+        local bucket = redis.call('HMGET', KEYS[1], 'tokens', 'updated')
+        local tokens = tonumber(bucket[1]) or capacity
+        local updated = tonumber(bucket[2]) or now
+        local rate = capacity / period
+        tokens = math.min(capacity, tokens + math.max(0, now - updated) * rate)
+        local wait = 0
+        if tokens >= 1 then
+            tokens = tokens - 1
+        else
+            wait = math.ceil((1 - tokens) / rate)
+        end
+        redis.call('HSET', KEYS[1], 'tokens', tostring(tokens), 'updated', now)
+        redis.call('PEXPIRE', KEYS[1], period)
+        return wait
+    "#;
+
+    pub fn with_connection(
+        connection: crate::storage::redis::SharedConnection,
+        prefix: Option<&str>,
+    ) -> Self {
+        crate::storage::connections::register("redis", &connection);
+        Self {
+            connection,
+            prefix: prefix.unwrap_or("classify:").to_string(),
+            script: redis::Script::new(Self::TAKE_SCRIPT),
+        }
+    }
+}
+
+#[cfg(feature = "redis")]
+#[async_trait]
+impl RateLimitStore for RedisRateLimitStore {
+    async fn take(&self, bucket: &str, limit: &RateLimit) -> ClassifyResult<Option<Duration>> {
+        let now = chrono::Utc::now().timestamp_millis();
+        let mut conn = self.connection.lock().await;
+
+        let wait: u64 = self
+            .script
+            .key(format!("{}ratelimit:{}", self.prefix, bucket))
+            .arg(limit.requests)
+            .arg(limit.period.as_millis() as u64)
+            .arg(now)
+            .invoke_async(&mut *conn)
+            .await
+            .map_err(|e| {
+                ClassifyError::StorageError(format!("Failed to update rate limit: {}", e))
+            })?;
+
+        Ok((wait > 0).then(|| Duration::from_millis(wait)))
+    }
+}
+
+/// Applies the configured limits to the requests of each caller
+pub struct RateLimiter {
+    store: Arc<dyn RateLimitStore>,
+    config: RateLimitConfig,
+}
+
+impl RateLimiter {
+    pub fn new(store: Arc<dyn RateLimitStore>, config: RateLimitConfig) -> Self {
+        Self { store, config }
+    }
+
+    /// Limit of a route class, a tenant's own limit replaces the default one
+    pub fn limit(&self, class: RouteClass, namespace: Option<&str>) -> Option<&RateLimit> {
+        namespace
+            .and_then(|namespace| self.config.tenant_limits.get(namespace))
+            .and_then(|limits| limits.get(&class))
+            .or_else(|| self.config.limits.get(&class))
+    }
+
+    /// Count a request, returning how long the caller has to wait when it is over its limit
+    pub async fn check(
+        &self,
+        caller: &str,
+        namespace: Option<&str>,
+        class: RouteClass,
+    ) -> ClassifyResult<Option<Duration>> {
+        let Some(limit) = self.limit(class, namespace) else {
+            return Ok(None);
+        };
+
+        let bucket = format!("{}:{}", caller, route_class_name(class));
+        self.store.take(&bucket, limit).await
+    }
+}
+
+impl RateLimiter {
+    /// Count a request from a client address, before it is authenticated
+    pub async fn check_ip(&self, ip: IpAddr) -> ClassifyResult<Option<Duration>> {
+        let Some(limit) = &self.config.ip_limit else {
+            return Ok(None);
+        };
+
+        self.store.take(&format!("ip:{}", ip), limit).await
+    }
+}
+
+/// Route class of a request, routes that call the classifier are the expensive ones
+pub fn route_class(method: &Method, path: &str) -> RouteClass {
+    let classifies = path == "/classify"
+        || path == "/ask"
+        || path.starts_with("/ingest/")
+        || path.starts_with("/import/")
+        || (path.starts_with("/content/") && path.ends_with("/reclassify"));
+
+    if method == Method::POST && classifies {
+        RouteClass::Classify
+    } else {
+        RouteClass::Query
+    }
+}
+
+fn route_class_name(class: RouteClass) -> &'static str {
+    match class {
+        RouteClass::Classify => "classify",
+        RouteClass::Query => "query",
+    }
+}
+
+/// Create the rate limiter, when any limits are configured
+pub async fn create_rate_limiter(config: &AppConfig) -> ClassifyResult<Option<RateLimiter>> {
+    let Some(rate_limit) = &config.api.rate_limit else {
+        return Ok(None);
+    };
+
+    let store: Arc<dyn RateLimitStore> = match rate_limit.store {
+        RateLimitStoreType::Memory => Arc::new(MemoryRateLimitStore::new()),
+        #[cfg(feature = "redis")]
+        RateLimitStoreType::Redis => Arc::new(RedisRateLimitStore::with_connection(
+            crate::storage::redis::connect_tag_storage(&config.tag_storage).await?,
+            config.tag_storage.redis_prefix.as_deref(),
+        )),
+        #[cfg(not(feature = "redis"))]
+        RateLimitStoreType::Redis => {
+            return Err(ClassifyError::ConfigError(
+                "The Redis rate limit store requires building with the redis feature".to_string(),
+            ))
+        }
+    };
+
+    Ok(Some(RateLimiter::new(store, rate_limit.clone())))
+}
@@ -0,0 +1,141 @@
+use crate::api::rate_limit::{route_class, take_token, MemoryRateLimitStore, RateLimiter};
+use crate::config::{RateLimit, RateLimitConfig, RateLimitStoreType, RouteClass};
+use crate::ClassifyResult;
+use axum::http::Method;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limit(requests: u32, seconds: u64) -> RateLimit {
+        RateLimit {
+            requests,
+            period: Duration::from_secs(seconds),
+        }
+    }
+
+    fn rate_limiter() -> RateLimiter {
+        let config = RateLimitConfig {
+            store: RateLimitStoreType::Memory,
+            limits: HashMap::from([
+                (RouteClass::Classify, limit(2, 60)),
+                (RouteClass::Query, limit(100, 60)),
+            ]),
+            tenant_limits: HashMap::from([(
+                "acme".to_string(),
+                HashMap::from([(RouteClass::Classify, limit(5, 60))]),
+            )]),
+            ip_limit: Some(limit(1, 60)),
+        };
+        RateLimiter::new(Arc::new(MemoryRateLimitStore::new()), config)
+    }
+
+    #[test]
+    fn test_take_token_refills_over_time() {
+        let limit = limit(10, 60);
+
+        let (tokens, retry_after) = take_token(0.0, Duration::ZERO, &limit);
+        assert_eq!(tokens, 0.0);
+        assert_eq!(retry_after, Some(Duration::from_secs(6)));
+
+        // One token refills every six seconds
+        let (tokens, retry_after) = take_token(0.0, Duration::from_secs(6), &limit);
+        assert!(tokens.abs() < 1e-9);
+        assert_eq!(retry_after, None);
+
+        // The bucket never holds more than its capacity
+        let (tokens, _) = take_token(5.0, Duration::from_secs(3600), &limit);
+        assert_eq!(tokens, 9.0);
+    }
+
+    #[tokio::test]
+    async fn test_callers_have_their_own_buckets() -> ClassifyResult<()> {
+        let rate_limiter = rate_limiter();
+
+        for _ in 0..2 {
+            assert!(rate_limiter
+                .check("key:a", None, RouteClass::Classify)
+                .await?
+                .is_none());
+        }
+        let retry_after = rate_limiter
+            .check("key:a", None, RouteClass::Classify)
+            .await?
+            .expect("over the limit");
+        assert!(retry_after > Duration::ZERO && retry_after <= Duration::from_secs(30));
+
+        // Other callers and cheaper routes are not affected
+        assert!(rate_limiter
+            .check("key:b", None, RouteClass::Classify)
+            .await?
+            .is_none());
+        assert!(rate_limiter
+            .check("key:a", None, RouteClass::Query)
+            .await?
+            .is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_tenant_limits_replace_defaults() -> ClassifyResult<()> {
+        let rate_limiter = rate_limiter();
+
+        assert_eq!(
+            rate_limiter.limit(RouteClass::Classify, Some("acme")),
+            Some(&limit(5, 60))
+        );
+        assert_eq!(
+            rate_limiter.limit(RouteClass::Query, Some("acme")),
+            Some(&limit(100, 60))
+        );
+        assert_eq!(
+            rate_limiter.limit(RouteClass::Classify, Some("other")),
+            Some(&limit(2, 60))
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_ip_limit() -> ClassifyResult<()> {
+        let rate_limiter = rate_limiter();
+        let ip = "198.51.100.1".parse().unwrap();
+
+        assert!(rate_limiter.check_ip(ip).await?.is_none());
+        assert!(rate_limiter.check_ip(ip).await?.is_some());
+        assert!(rate_limiter
+            .check_ip("198.51.100.2".parse().unwrap())
+            .await?
+            .is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_route_class() {
+        assert_eq!(
+            route_class(&Method::POST, "/classify"),
+            RouteClass::Classify
+        );
+        assert_eq!(
+            route_class(&Method::POST, "/ingest/webhook/github"),
+            RouteClass::Classify
+        );
+        assert_eq!(
+            route_class(&Method::POST, "/import/urls"),
+            RouteClass::Classify
+        );
+        assert_eq!(route_class(&Method::POST, "/ask"), RouteClass::Classify);
+        assert_eq!(
+            route_class(&Method::POST, "/content/k3v9x2qa/reclassify"),
+            RouteClass::Classify
+        );
+        assert_eq!(route_class(&Method::GET, "/import/1234"), RouteClass::Query);
+        assert_eq!(route_class(&Method::GET, "/query"), RouteClass::Query);
+        assert_eq!(
+            route_class(&Method::POST, "/admin/purge"),
+            RouteClass::Query
+        );
+    }
+}
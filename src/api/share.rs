@@ -0,0 +1,78 @@
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::time::Duration;
+
+/// Label the share link key is derived from the API key with, so the API key
+/// itself never signs anything
+const DERIVED_SECRET_LABEL: &str = "classify share link secret v1";
+
+/// Key for share links derived from the API key, for when no secret of their
+/// own is configured
+pub fn derive_secret(api_key: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(api_key.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(DERIVED_SECRET_LABEL.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// Signs links that give access to one content item without an API key
+pub struct ShareLinks {
+    secret: String,
+    ttl: Duration,
+    max_ttl: Duration,
+    public_url: Option<String>,
+}
+
+impl ShareLinks {
+    pub fn new(secret: &str, ttl: Duration, max_ttl: Duration, public_url: Option<String>) -> Self {
+        Self {
+            secret: secret.to_string(),
+            ttl,
+            max_ttl,
+            public_url,
+        }
+    }
+
+    /// Lifetime of a new link, `None` when the requested one is too long
+    pub fn ttl(&self, requested: Option<Duration>) -> Option<Duration> {
+        let ttl = requested.unwrap_or(self.ttl);
+        (ttl <= self.max_ttl).then_some(ttl)
+    }
+
+    fn mac(&self, id: &str, expires: i64) -> Hmac<Sha256> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(self.secret.as_bytes())
+            .expect("HMAC accepts keys of any length");
+        mac.update(format!("share:{}:{}", id, expires).as_bytes());
+        mac
+    }
+
+    pub fn sign(&self, id: &str, expires: i64) -> String {
+        hex::encode(self.mac(id, expires).finalize().into_bytes())
+    }
+
+    /// Create a link to the content that expires at `expires_at`
+    pub fn link(&self, id: &str, expires_at: DateTime<Utc>) -> String {
+        let expires = expires_at.timestamp();
+        format!(
+            "{}/shared/{}?expires={}&signature={}",
+            self.public_url.as_deref().unwrap_or_default(),
+            id,
+            expires,
+            self.sign(id, expires)
+        )
+    }
+
+    /// Check that a link was signed for the content and hasn't expired
+    pub fn verify(&self, id: &str, expires: i64, signature: &str, now: i64) -> bool {
+        if expires < now {
+            return false;
+        }
+
+        let Ok(signature) = hex::decode(signature) else {
+            return false;
+        };
+
+        self.mac(id, expires).verify_slice(&signature).is_ok()
+    }
+}
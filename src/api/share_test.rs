@@ -0,0 +1,71 @@
+use crate::api::share::{derive_secret, ShareLinks};
+use chrono::{TimeZone, Utc};
+use std::time::Duration;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn share_links(public_url: Option<&str>) -> ShareLinks {
+        ShareLinks::new(
+            "secret",
+            Duration::from_secs(3600),
+            Duration::from_secs(86400),
+            public_url.map(String::from),
+        )
+    }
+
+    #[test]
+    fn test_signed_link_verifies_until_it_expires() {
+        let links = share_links(None);
+        let signature = links.sign("abc", 1_000);
+
+        assert!(links.verify("abc", 1_000, &signature, 999));
+        assert!(links.verify("abc", 1_000, &signature, 1_000));
+        assert!(!links.verify("abc", 1_000, &signature, 1_001));
+    }
+
+    #[test]
+    fn test_tampered_link_is_refused() {
+        let links = share_links(None);
+        let signature = links.sign("abc", 1_000);
+
+        assert!(!links.verify("other", 1_000, &signature, 0));
+        assert!(!links.verify("abc", 2_000, &signature, 0));
+        assert!(!links.verify("abc", 1_000, "not-hex", 0));
+
+        let other_secret = ShareLinks::new(
+            "another secret",
+            Duration::from_secs(3600),
+            Duration::from_secs(86400),
+            None,
+        );
+        assert!(!other_secret.verify("abc", 1_000, &signature, 0));
+    }
+
+    #[test]
+    fn test_link_and_ttl() {
+        let links = share_links(Some("https://classify.example.com"));
+        let expires_at = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+
+        let link = links.link("abc", expires_at);
+        assert!(link
+            .starts_with("https://classify.example.com/shared/abc?expires=1700000000&signature="));
+
+        assert_eq!(links.ttl(None), Some(Duration::from_secs(3600)));
+        assert_eq!(
+            links.ttl(Some(Duration::from_secs(60))),
+            Some(Duration::from_secs(60))
+        );
+        assert_eq!(links.ttl(Some(Duration::from_secs(86401))), None);
+    }
+
+    #[test]
+    fn test_derived_secret_is_not_the_api_key() {
+        let secret = derive_secret("api key");
+
+        assert_eq!(secret, derive_secret("api key"));
+        assert_ne!(secret, "api key");
+        assert_ne!(secret, derive_secret("another api key"));
+    }
+}
@@ -1,4 +1,5 @@
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod tests {
     use crate::api::AppState;
     use axum::{
@@ -10,12 +11,20 @@ mod tests {
     };
     use mockall::mock;
     use mockall::predicate::*;
+    use std::collections::HashMap;
     use std::sync::Arc;
     use tower::ServiceExt;
 
     use crate::classifier::Classifier;
-    use crate::storage::{ContentStorage, TagStorage};
-    use crate::{ClassifyRequest, ClassifyResponse, ClassifyResult, Content, TagsResponse};
+    use crate::storage::content::memory::MemoryContentStorage;
+    use crate::storage::tag::memory::MemoryTagStorage;
+    use crate::storage::{AtomicStorage, ContentStorage, TagStorage};
+    use crate::{
+        AskResponse, ClassifyRequest, ClassifyResponse, ClassifyResult, ClusterResponse, Content,
+        ContentQueryResponse, ContentTagsRequest, DigestResponse, MetadataPatchRequest,
+        ReclassifyResponse, RecommendationsResponse, SemanticSearchResponse, TagsResponse,
+        TrendingTagsResponse,
+    };
 
     // Mock Classifier
     mock! {
@@ -24,6 +33,7 @@ mod tests {
         impl Classifier for ClassifierMock {
             async fn classify(&self, content: &str) -> ClassifyResult<Vec<String>>;
             async fn classify_url(&self, url: &str) -> ClassifyResult<Vec<String>>;
+            async fn complete(&self, system: &str, prompt: &str) -> ClassifyResult<String>;
         }
     }
 
@@ -37,6 +47,10 @@ mod tests {
             async fn list(&self) -> ClassifyResult<Vec<Content>>;
             async fn delete(&self, id: &str) -> ClassifyResult<bool>;
             async fn find_by_hash(&self, hash: &str) -> ClassifyResult<Option<Content>>;
+            async fn find_by_slug(&self, slug: &str) -> ClassifyResult<Option<Content>>;
+            async fn slug_exists(&self, slug: &str) -> ClassifyResult<bool>;
+            async fn store_attachment(&self, id: &str, name: &str, data: &[u8]) -> ClassifyResult<()>;
+            async fn get_attachment(&self, id: &str, name: &str) -> ClassifyResult<Option<Vec<u8>>>;
         }
     }
 
@@ -53,6 +67,15 @@ mod tests {
         }
     }
 
+    // Mock AtomicStorage
+    mock! {
+        pub AtomicStorageMock {}
+        #[async_trait::async_trait]
+        impl AtomicStorage for AtomicStorageMock {
+            async fn store_with_tags(&self, content: &Content, tags: &[String]) -> ClassifyResult<()>;
+        }
+    }
+
     #[tokio::test]
     async fn test_classify_duplicate_content() {
         // Mock the config for testing
@@ -60,25 +83,18 @@ mod tests {
 
         // Set up test data
         let test_content = "Test content for duplicate detection";
-        let content_hash = Content::generate_hash(test_content);
         let existing_content = Content::new(test_content.to_string())
             .with_tags(vec!["test".to_string(), "duplicate".to_string()]);
 
-        let classifier_mock = MockClassifierMock::new();
-        let mut content_storage_mock = MockContentStorageMock::new();
-        let tag_storage_mock = MockTagStorageMock::new();
-
-        content_storage_mock
-            .expect_find_by_hash()
-            .with(eq(content_hash))
-            .times(1)
-            .returning(move |_| Ok(Some(existing_content.clone())));
+        let content_storage = Arc::new(MemoryContentStorage::new());
+        content_storage.store(&existing_content).await.unwrap();
 
-        let state = AppState {
-            classifier: Arc::new(classifier_mock),
-            content_storage: Arc::new(content_storage_mock),
-            tag_storage: Arc::new(tag_storage_mock),
-        };
+        // The classifier isn't called for stored content
+        let state = AppState::new(
+            Arc::new(MockClassifierMock::new()),
+            content_storage.clone(),
+            Arc::new(MemoryTagStorage::new()),
+        );
 
         // Create router but without the API key validation middleware for testing
         let app = Router::new()
@@ -91,6 +107,7 @@ mod tests {
             .body(Body::from(
                 serde_json::to_string(&ClassifyRequest {
                     content: test_content.to_string(),
+                    metadata: HashMap::new(),
                 })
                 .unwrap(),
             ))
@@ -104,11 +121,13 @@ mod tests {
 
         let response: ClassifyResponse = serde_json::from_slice(&body).unwrap();
 
+        assert_eq!(response.content.id, existing_content.id);
         assert_eq!(response.content.content, test_content);
         assert!(response.content.tags.contains(&"test".to_string()));
         assert!(response.content.tags.contains(&"duplicate".to_string()));
         assert!(response.success);
         assert!(response.error.is_none());
+        assert_eq!(content_storage.list().await.unwrap().len(), 1);
     }
 
     #[tokio::test]
@@ -116,29 +135,25 @@ mod tests {
         // Mock the config for testing
         let api_key = "test-api-key";
 
-        // Set up mocks
-        let classifier_mock = MockClassifierMock::new();
-        let content_storage_mock = MockContentStorageMock::new();
-        let mut tag_storage_mock = MockTagStorageMock::new();
-
-        // Mock the list_tags method
-        let mock_tags = vec![
-            "rust".to_string(),
-            "programming".to_string(),
-            "web".to_string(),
-        ];
-
-        tag_storage_mock
-            .expect_list_tags()
-            .times(1)
-            .returning(move || Ok(mock_tags.clone()));
+        let tag_storage = Arc::new(MemoryTagStorage::new());
+        tag_storage
+            .add_tags(
+                "content-1",
+                &["rust".to_string(), "programming".to_string()],
+            )
+            .await
+            .unwrap();
+        tag_storage
+            .add_tags("content-2", &["rust".to_string(), "web".to_string()])
+            .await
+            .unwrap();
 
         // Create app state
-        let state = AppState {
-            classifier: Arc::new(classifier_mock),
-            content_storage: Arc::new(content_storage_mock),
-            tag_storage: Arc::new(tag_storage_mock),
-        };
+        let state = AppState::new(
+            Arc::new(MockClassifierMock::new()),
+            Arc::new(MemoryContentStorage::new()),
+            tag_storage,
+        );
 
         // Create router but without the API key validation middleware for testing
         let app = Router::new()
@@ -167,7 +182,7 @@ mod tests {
         let body = response_to_bytes(response).await;
         let response: TagsResponse = serde_json::from_slice(&body).unwrap();
 
-        // Verify the response contains the expected tags
+        // Verify the response contains the expected tags, each once
         assert_eq!(response.count, 3);
         assert!(response.tags.contains(&"rust".to_string()));
         assert!(response.tags.contains(&"programming".to_string()));
@@ -180,30 +195,19 @@ mod tests {
     async fn test_get_content_text() {
         // Mock the config for testing
         let api_key = "test-api-key";
-        let content_id = "test-content-id";
         let test_content = "This is the content text for testing";
 
-        // Set up mocks
-        let classifier_mock = MockClassifierMock::new();
-        let mut content_storage_mock = MockContentStorageMock::new();
-        let tag_storage_mock = MockTagStorageMock::new();
-
         // Create a test content item
         let content = Content::new(test_content.to_string()).with_tags(vec!["test".to_string()]);
-
-        // Mock the get method
-        content_storage_mock
-            .expect_get()
-            .with(eq(content_id))
-            .times(1)
-            .returning(move |_| Ok(Some(content.clone())));
+        let content_storage = Arc::new(MemoryContentStorage::new());
+        content_storage.store(&content).await.unwrap();
 
         // Create app state
-        let state = AppState {
-            classifier: Arc::new(classifier_mock),
-            content_storage: Arc::new(content_storage_mock),
-            tag_storage: Arc::new(tag_storage_mock),
-        };
+        let state = AppState::new(
+            Arc::new(MockClassifierMock::new()),
+            content_storage,
+            Arc::new(MemoryTagStorage::new()),
+        );
 
         // Create router without middleware for testing
         let app = Router::new()
@@ -211,7 +215,7 @@ mod tests {
             .with_state(Arc::new(state));
 
         // Create request
-        let request = Request::get(&format!("/content/{}", content_id))
+        let request = Request::get(format!("/content/{}", content.id))
             .header("X-Api-Key", api_key)
             .body(Body::empty())
             .unwrap();
@@ -236,6 +240,1814 @@ mod tests {
         assert_eq!(text, test_content);
     }
 
+    #[tokio::test]
+    async fn test_get_content_snapshot() {
+        let snapshot = "<html><body>Archived page</body></html>";
+
+        let content = Content::new("https://example.com/archived".to_string());
+        let content_storage = Arc::new(MemoryContentStorage::new());
+        content_storage.store(&content).await.unwrap();
+        content_storage
+            .store_attachment(
+                &content.id.to_string(),
+                crate::api::SNAPSHOT_ATTACHMENT,
+                snapshot.as_bytes(),
+            )
+            .await
+            .unwrap();
+
+        let state = AppState::new(
+            Arc::new(MockClassifierMock::new()),
+            content_storage,
+            Arc::new(MemoryTagStorage::new()),
+        );
+
+        let app = Router::new()
+            .route(
+                "/content/:id/snapshot",
+                get(crate::api::get_content_snapshot),
+            )
+            .with_state(Arc::new(state));
+
+        let request = Request::get(format!("/content/{}/snapshot", content.id))
+            .body(Body::empty())
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get("Content-Type").unwrap(),
+            "text/html; charset=utf-8"
+        );
+        // Scripts of the archived page must not run on the API origin
+        assert_eq!(
+            response.headers().get("Content-Security-Policy").unwrap(),
+            "sandbox"
+        );
+        assert_eq!(
+            response.headers().get("X-Content-Type-Options").unwrap(),
+            "nosniff"
+        );
+
+        let body = response_to_bytes(response).await;
+        assert_eq!(String::from_utf8(body).unwrap(), snapshot);
+    }
+
+    #[tokio::test]
+    async fn test_classify_query_and_delete_with_memory_storage() {
+        let mut classifier_mock = MockClassifierMock::new();
+        classifier_mock
+            .expect_classify()
+            .times(1)
+            .returning(|_| Ok(vec!["rust".to_string(), "memory".to_string()]));
+
+        let state = Arc::new(AppState::new(
+            Arc::new(classifier_mock),
+            Arc::new(MemoryContentStorage::new()),
+            Arc::new(MemoryTagStorage::new()),
+        ));
+
+        let app = Router::new()
+            .route("/classify", post(crate::api::classify_content))
+            .route("/query", get(crate::api::query_content))
+            .route(
+                "/content/:id",
+                axum::routing::delete(crate::api::delete_content),
+            )
+            .with_state(state.clone());
+
+        let request = Request::post("/classify")
+            .header("Content-Type", "application/json")
+            .body(Body::from(
+                serde_json::to_string(&ClassifyRequest {
+                    content: "Rust keeps everything in memory".to_string(),
+                    metadata: HashMap::new(),
+                })
+                .unwrap(),
+            ))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let classified: ClassifyResponse =
+            serde_json::from_slice(&response_to_bytes(response).await).unwrap();
+
+        let request = Request::get("/query?tags=memory")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        let query: ContentQueryResponse =
+            serde_json::from_slice(&response_to_bytes(response).await).unwrap();
+        assert_eq!(query.count, 1);
+        assert_eq!(query.items[0].id, classified.content.id);
+
+        let request = Request::delete(format!("/content/{}", classified.content.id))
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        assert!(state.content_storage.list().await.unwrap().is_empty());
+        assert!(state.tag_storage.list_tags().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_parent_tag_query_includes_child_tags() {
+        let mut classifier_mock = MockClassifierMock::new();
+        classifier_mock
+            .expect_classify()
+            .times(2)
+            .returning(|content| {
+                Ok(if content.contains("Rust") {
+                    vec!["lang > rust".to_string()]
+                } else {
+                    vec!["lang/go".to_string(), "web".to_string()]
+                })
+            });
+
+        let state = Arc::new(AppState::new(
+            Arc::new(classifier_mock),
+            Arc::new(MemoryContentStorage::new()),
+            Arc::new(MemoryTagStorage::new()),
+        ));
+        let app = Router::new()
+            .route("/classify", post(crate::api::classify_content))
+            .route("/query", get(crate::api::query_content))
+            .route("/tags", get(crate::api::get_tags))
+            .with_state(state);
+
+        for text in ["Rust ownership", "Go routines"] {
+            let request = Request::post("/classify")
+                .header("Content-Type", "application/json")
+                .body(Body::from(
+                    serde_json::to_string(&ClassifyRequest {
+                        content: text.to_string(),
+                        metadata: HashMap::new(),
+                    })
+                    .unwrap(),
+                ))
+                .unwrap();
+            let response = app.clone().oneshot(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+
+        let request = Request::get("/query?tags=lang")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        let query: ContentQueryResponse =
+            serde_json::from_slice(&response_to_bytes(response).await).unwrap();
+        assert_eq!(query.count, 2);
+
+        let request = Request::get("/query?tags=lang%3Erust")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        let query: ContentQueryResponse =
+            serde_json::from_slice(&response_to_bytes(response).await).unwrap();
+        assert_eq!(query.count, 1);
+        assert_eq!(query.items[0].tags, vec!["lang/rust"]);
+
+        for (mode, count) in [("", 2), ("&mode=any", 2), ("&mode=all", 1)] {
+            let request = Request::get(format!("/query?tags=lang,web{}", mode))
+                .body(Body::empty())
+                .unwrap();
+            let response = app.clone().oneshot(request).await.unwrap();
+            let query: ContentQueryResponse =
+                serde_json::from_slice(&response_to_bytes(response).await).unwrap();
+            assert_eq!(query.count, count, "query with {:?}", mode);
+        }
+
+        let request = Request::get("/tags?tree=true").body(Body::empty()).unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        let tags: TagsResponse =
+            serde_json::from_slice(&response_to_bytes(response).await).unwrap();
+        let tree = tags.tree.unwrap();
+        assert_eq!(tree.len(), 2);
+        assert_eq!(tree[0].tag, "lang");
+        assert!(!tree[0].tagged);
+        let children: Vec<&str> = tree[0].children.iter().map(|c| c.tag.as_str()).collect();
+        assert_eq!(children, vec!["lang/go", "lang/rust"]);
+    }
+
+    #[tokio::test]
+    async fn test_blocked_tags_are_not_stored_and_can_be_stripped() {
+        use crate::config::{IngestConfig, TagPolicy};
+        use crate::BlockedTagsResponse;
+
+        let mut classifier_mock = MockClassifierMock::new();
+        classifier_mock
+            .expect_classify()
+            .times(1)
+            .returning(|_| Ok(vec!["rust".to_string(), "Misc".to_string()]));
+
+        let content_storage = Arc::new(MemoryContentStorage::new());
+        let tag_storage = Arc::new(MemoryTagStorage::new());
+        let old = Content::new("Classified before misc was blocked".to_string())
+            .with_tags(vec!["misc".to_string(), "notes".to_string()]);
+        content_storage.store(&old).await.unwrap();
+        tag_storage
+            .add_tags(&old.id.to_string(), &old.tags)
+            .await
+            .unwrap();
+
+        let state = AppState::new(
+            Arc::new(classifier_mock),
+            content_storage.clone(),
+            tag_storage.clone(),
+        )
+        .with_ingest_config(IngestConfig {
+            tags: TagPolicy {
+                blocklist: vec!["misc".to_string()],
+                ..TagPolicy::default()
+            },
+            ..IngestConfig::default()
+        });
+        let app = Router::new()
+            .route("/classify", post(crate::api::classify_content))
+            .route(
+                "/admin/tags/blocklist",
+                post(crate::api::strip_blocked_tags),
+            )
+            .layer(axum::Extension(crate::api::Caller("admin".to_string())))
+            .with_state(Arc::new(state));
+
+        let request = Request::post("/classify")
+            .header("Content-Type", "application/json")
+            .body(Body::from(
+                serde_json::to_string(&ClassifyRequest {
+                    content: "Rust miscellany".to_string(),
+                    metadata: HashMap::new(),
+                })
+                .unwrap(),
+            ))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        let classified: ClassifyResponse =
+            serde_json::from_slice(&response_to_bytes(response).await).unwrap();
+        assert_eq!(classified.content.tags, vec!["rust"]);
+
+        let request = Request::post("/admin/tags/blocklist")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let stripped: BlockedTagsResponse =
+            serde_json::from_slice(&response_to_bytes(response).await).unwrap();
+        assert_eq!(stripped.removed.get("misc"), Some(&1));
+
+        let id = old.id.to_string();
+        assert_eq!(tag_storage.get_tags(&id).await.unwrap(), vec!["notes"]);
+        let stored = content_storage.get(&id).await.unwrap().unwrap();
+        assert_eq!(stored.tags, vec!["notes"]);
+    }
+
+    #[tokio::test]
+    async fn test_suggested_duplicate_tags_can_be_merged() {
+        use crate::{TagDuplicatesResponse, TagMergeResponse};
+
+        let content_storage = Arc::new(MemoryContentStorage::new());
+        let tag_storage = Arc::new(MemoryTagStorage::new());
+        for tags in [
+            vec!["database", "sql"],
+            vec!["database"],
+            vec!["databases", "database"],
+            vec!["databases"],
+        ] {
+            let tags: Vec<String> = tags.into_iter().map(String::from).collect();
+            let content =
+                Content::new(format!("About {}", tags.join(" and "))).with_tags(tags.clone());
+            content_storage.store(&content).await.unwrap();
+            tag_storage
+                .add_tags(&content.id.to_string(), &tags)
+                .await
+                .unwrap();
+        }
+
+        let state = AppState::new(
+            Arc::new(MockClassifierMock::new()),
+            content_storage.clone(),
+            tag_storage.clone(),
+        );
+        let app = Router::new()
+            .route(
+                "/admin/tags/duplicates",
+                get(crate::api::get_tag_duplicates),
+            )
+            .route("/admin/tags/merge", post(crate::api::merge_tags))
+            .layer(axum::Extension(crate::api::Caller("admin".to_string())))
+            .with_state(Arc::new(state));
+
+        let request = Request::get("/admin/tags/duplicates")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        let report: TagDuplicatesResponse =
+            serde_json::from_slice(&response_to_bytes(response).await).unwrap();
+        assert_eq!(report.count, 1);
+        let duplicate = &report.duplicates[0];
+        assert_eq!(
+            (duplicate.tag.as_str(), duplicate.into.as_str()),
+            ("databases", "database")
+        );
+
+        let request = Request::post("/admin/tags/merge")
+            .header("Content-Type", "application/json")
+            .body(Body::from(serde_json::to_string(&duplicate.merge).unwrap()))
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let merged: TagMergeResponse =
+            serde_json::from_slice(&response_to_bytes(response).await).unwrap();
+        assert_eq!(merged.merged.get("databases"), Some(&2));
+
+        assert!(tag_storage
+            .find_by_tag("databases")
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(tag_storage.find_by_tag("database").await.unwrap().len(), 4);
+        for content in content_storage.list().await.unwrap() {
+            assert_eq!(
+                content.tags.iter().filter(|tag| *tag == "database").count(),
+                1
+            );
+            assert!(!content.tags.contains(&"databases".to_string()));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pinned_content_is_listed_first() {
+        let content_storage = Arc::new(MemoryContentStorage::new());
+        let tag_storage = Arc::new(MemoryTagStorage::new());
+        let mut ids = Vec::new();
+        for text in ["Older note", "Newer note"] {
+            let content = Content::new(text.to_string()).with_tags(vec!["notes".to_string()]);
+            content_storage.store(&content).await.unwrap();
+            tag_storage
+                .add_tags(&content.id.to_string(), &content.tags)
+                .await
+                .unwrap();
+            ids.push(content.id.to_string());
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        }
+
+        let state = AppState::new(
+            Arc::new(MockClassifierMock::new()),
+            content_storage,
+            tag_storage,
+        );
+        let app = Router::new()
+            .route("/query", get(crate::api::query_content))
+            .route(
+                "/content/:id/pin",
+                axum::routing::put(crate::api::pin_content).delete(crate::api::unpin_content),
+            )
+            .with_state(Arc::new(state));
+
+        let query = |uri: &'static str| {
+            let app = app.clone();
+            async move {
+                let request = Request::get(uri).body(Body::empty()).unwrap();
+                let response = app.oneshot(request).await.unwrap();
+                let query: ContentQueryResponse =
+                    serde_json::from_slice(&response_to_bytes(response).await).unwrap();
+                query
+                    .items
+                    .iter()
+                    .map(|item| item.id.to_string())
+                    .collect::<Vec<_>>()
+            }
+        };
+        assert_eq!(
+            query("/query?tags=notes").await,
+            vec![ids[1].clone(), ids[0].clone()]
+        );
+
+        let request = Request::put(format!("/content/{}/pin", ids[0]))
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        let pinned: ClassifyResponse =
+            serde_json::from_slice(&response_to_bytes(response).await).unwrap();
+        assert!(pinned.content.pinned);
+
+        assert_eq!(
+            query("/query?tags=notes").await,
+            vec![ids[0].clone(), ids[1].clone()]
+        );
+        assert_eq!(
+            query("/query?tags=notes&pinned=true").await,
+            vec![ids[0].clone()]
+        );
+
+        let request = Request::delete(format!("/content/{}/pin", ids[0]))
+            .body(Body::empty())
+            .unwrap();
+        app.clone().oneshot(request).await.unwrap();
+        assert!(query("/query?tags=notes&pinned=true").await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_list_content_pages_and_filters_by_date() {
+        let content_storage = Arc::new(MemoryContentStorage::new());
+        let start = chrono::DateTime::parse_from_rfc3339("2024-03-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+        let mut ids = Vec::new();
+        for day in 0..4 {
+            let mut content = Content::new(format!("Note of day {}", day));
+            content.created_at = start + chrono::Duration::days(day);
+            // Updated in the reverse order of creation
+            content.updated_at = start + chrono::Duration::days(10 - day);
+            content_storage.store(&content).await.unwrap();
+            ids.push(content.id.to_string());
+        }
+
+        let state = AppState::new(
+            Arc::new(MockClassifierMock::new()),
+            content_storage,
+            Arc::new(MemoryTagStorage::new()),
+        );
+        let app = Router::new()
+            .route("/content", get(crate::api::list_content))
+            .with_state(Arc::new(state));
+
+        let list = |uri: &'static str| {
+            let app = app.clone();
+            async move {
+                let request = Request::get(uri).body(Body::empty()).unwrap();
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let list: ContentQueryResponse =
+                    serde_json::from_slice(&response_to_bytes(response).await).unwrap();
+                let items = list
+                    .items
+                    .iter()
+                    .map(|item| item.id.to_string())
+                    .collect::<Vec<_>>();
+                (status, list.count, items)
+            }
+        };
+
+        let (_, count, items) = list("/content").await;
+        assert_eq!(count, 4);
+        assert_eq!(items, ids);
+
+        let (_, count, items) = list("/content?order=created_at&offset=1&limit=2").await;
+        assert_eq!(count, 4);
+        assert_eq!(items, vec![ids[2].clone(), ids[1].clone()]);
+
+        let (_, count, items) =
+            list("/content?order=created_at&since=2024-03-02T00:00:00Z&until=2024-03-04T00:00:00Z")
+                .await;
+        assert_eq!(count, 2);
+        assert_eq!(items, vec![ids[2].clone(), ids[1].clone()]);
+
+        let (status, _, _) =
+            list("/content?since=2024-03-04T00:00:00Z&until=2024-03-02T00:00:00Z").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_content_routes_accept_slug_or_uuid() {
+        let content_storage = Arc::new(MemoryContentStorage::new());
+        let content = Content::new("Reachable by slug".to_string());
+        content_storage.store(&content).await.unwrap();
+        let slug = content.slug.clone().unwrap();
+
+        let state = AppState::new(
+            Arc::new(MockClassifierMock::new()),
+            content_storage,
+            Arc::new(MemoryTagStorage::new()),
+        );
+        let app = Router::new()
+            .route("/content/:id", get(crate::api::get_content_text))
+            .with_state(Arc::new(state));
+
+        for id in [slug, content.id.to_string()] {
+            let request = Request::get(format!("/content/{}", id))
+                .body(Body::empty())
+                .unwrap();
+            let response = app.clone().oneshot(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(response_to_bytes(response).await, b"Reachable by slug");
+        }
+
+        let request = Request::get("/content/unknown1")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_async_classification_is_queued_for_a_worker() {
+        use crate::queue::memory::MemoryJobQueue;
+        use crate::queue::worker::run_job;
+        use crate::queue::{ClassificationJob, JobQueue, JobStatus};
+        use std::time::Duration;
+
+        let mut classifier_mock = MockClassifierMock::new();
+        classifier_mock
+            .expect_classify()
+            .times(1)
+            .returning(|_| Ok(vec!["later".to_string()]));
+
+        let queue = Arc::new(MemoryJobQueue::new(Duration::from_secs(60)));
+        let state = Arc::new(
+            AppState::new(
+                Arc::new(classifier_mock),
+                Arc::new(MemoryContentStorage::new()),
+                Arc::new(MemoryTagStorage::new()),
+            )
+            .with_queue(Some(queue.clone())),
+        );
+
+        let app = Router::new()
+            .route("/classify", post(crate::api::classify_content))
+            .route("/jobs/:id", get(crate::api::get_classification_job))
+            .with_state(state.clone());
+
+        let request = Request::post("/classify?async=true")
+            .header("Content-Type", "application/json")
+            .body(Body::from(
+                serde_json::to_string(&ClassifyRequest {
+                    content: "Classified by a worker".to_string(),
+                    metadata: HashMap::new(),
+                })
+                .unwrap(),
+            ))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        let queued: ClassificationJob =
+            serde_json::from_slice(&response_to_bytes(response).await).unwrap();
+        assert_eq!(queued.status, JobStatus::Queued);
+        assert!(state.content_storage.list().await.unwrap().is_empty());
+
+        let job = queue.pop(Duration::ZERO).await.unwrap().unwrap();
+        let finished = run_job(&state, job).await;
+        queue.set_status(&finished).await.unwrap();
+
+        let request = Request::get(format!("/jobs/{}", queued.id))
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let job: ClassificationJob =
+            serde_json::from_slice(&response_to_bytes(response).await).unwrap();
+        assert_eq!(job.status, JobStatus::Completed);
+
+        let stored = state
+            .content_storage
+            .get(&job.content_id.unwrap().to_string())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.content, "Classified by a worker");
+    }
+
+    #[tokio::test]
+    async fn test_classify_writes_content_and_tags_atomically() {
+        let mut classifier_mock = MockClassifierMock::new();
+        classifier_mock
+            .expect_classify()
+            .times(1)
+            .returning(|_| Ok(vec!["atomic".to_string()]));
+
+        let mut content_storage_mock = MockContentStorageMock::new();
+        content_storage_mock
+            .expect_find_by_hash()
+            .times(1)
+            .returning(|_| Ok(None));
+        content_storage_mock
+            .expect_slug_exists()
+            .returning(|_| Ok(false));
+        content_storage_mock.expect_store().never();
+
+        let mut tag_storage_mock = MockTagStorageMock::new();
+        tag_storage_mock.expect_add_tags().never();
+
+        let mut atomic_storage_mock = MockAtomicStorageMock::new();
+        atomic_storage_mock
+            .expect_store_with_tags()
+            .withf(|content, tags| {
+                content.content == "Stored in one transaction" && tags == ["atomic".to_string()]
+            })
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        let state = AppState::new(
+            Arc::new(classifier_mock),
+            Arc::new(content_storage_mock),
+            Arc::new(tag_storage_mock),
+        )
+        .with_atomic_storage(Some(Arc::new(atomic_storage_mock)));
+
+        let app = Router::new()
+            .route("/classify", post(crate::api::classify_content))
+            .with_state(Arc::new(state));
+
+        let request = Request::post("/classify")
+            .header("Content-Type", "application/json")
+            .body(Body::from(
+                serde_json::to_string(&ClassifyRequest {
+                    content: "Stored in one transaction".to_string(),
+                    metadata: HashMap::new(),
+                })
+                .unwrap(),
+            ))
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_classify_with_metadata_filter_and_patch() {
+        let mut classifier_mock = MockClassifierMock::new();
+        classifier_mock
+            .expect_classify()
+            .times(2)
+            .returning(|_| Ok(vec!["notes".to_string()]));
+
+        let state = Arc::new(AppState::new(
+            Arc::new(classifier_mock),
+            Arc::new(MemoryContentStorage::new()),
+            Arc::new(MemoryTagStorage::new()),
+        ));
+
+        let app = Router::new()
+            .route("/classify", post(crate::api::classify_content))
+            .route("/query", get(crate::api::query_content))
+            .route(
+                "/content/:id/metadata",
+                axum::routing::patch(crate::api::patch_content_metadata),
+            )
+            .with_state(state);
+
+        let mut ids = Vec::new();
+        for (text, author) in [("First note", "alice"), ("Second note", "bob")] {
+            let request = Request::post("/classify")
+                .header("Content-Type", "application/json")
+                .body(Body::from(
+                    serde_json::to_string(&ClassifyRequest {
+                        content: text.to_string(),
+                        metadata: HashMap::from([("author".to_string(), author.to_string())]),
+                    })
+                    .unwrap(),
+                ))
+                .unwrap();
+            let response = app.clone().oneshot(request).await.unwrap();
+            let classified: ClassifyResponse =
+                serde_json::from_slice(&response_to_bytes(response).await).unwrap();
+            assert_eq!(classified.content.metadata["author"], author);
+            ids.push(classified.content.id);
+        }
+
+        let request = Request::get("/query?tags=notes&metadata=author:bob")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        let query: ContentQueryResponse =
+            serde_json::from_slice(&response_to_bytes(response).await).unwrap();
+        assert_eq!(query.count, 1);
+        assert_eq!(query.items[0].id, ids[1]);
+
+        let request = Request::patch(format!("/content/{}/metadata", ids[1]))
+            .header("Content-Type", "application/json")
+            .body(Body::from(
+                serde_json::to_string(&MetadataPatchRequest {
+                    metadata: HashMap::from([
+                        ("author".to_string(), None),
+                        ("project".to_string(), Some("classify".to_string())),
+                    ]),
+                })
+                .unwrap(),
+            ))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let patched: ClassifyResponse =
+            serde_json::from_slice(&response_to_bytes(response).await).unwrap();
+        assert_eq!(
+            patched.content.metadata,
+            HashMap::from([("project".to_string(), "classify".to_string())])
+        );
+
+        let request = Request::get("/query?tags=notes&metadata=author:bob")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        let query: ContentQueryResponse =
+            serde_json::from_slice(&response_to_bytes(response).await).unwrap();
+        assert_eq!(query.count, 0);
+
+        let request = Request::get("/query?tags=notes&metadata=author")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_set_content_tags() {
+        let mut classifier_mock = MockClassifierMock::new();
+        classifier_mock
+            .expect_classify()
+            .times(1)
+            .returning(|_| Ok(vec!["notes".to_string(), "draft".to_string()]));
+
+        let state = Arc::new(AppState::new(
+            Arc::new(classifier_mock),
+            Arc::new(MemoryContentStorage::new()),
+            Arc::new(MemoryTagStorage::new()),
+        ));
+
+        let app = Router::new()
+            .route("/classify", post(crate::api::classify_content))
+            .route("/query", get(crate::api::query_content))
+            .route(
+                "/content/:id/tags",
+                axum::routing::put(crate::api::set_content_tags),
+            )
+            .with_state(state);
+
+        let request = Request::post("/classify")
+            .header("Content-Type", "application/json")
+            .body(Body::from(r#"{"content": "Meeting notes"}"#))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        let classified: ClassifyResponse =
+            serde_json::from_slice(&response_to_bytes(response).await).unwrap();
+        let id = classified.content.id;
+
+        let set_tags = |tags: Vec<&str>| {
+            Request::put(format!("/content/{}/tags", id))
+                .header("Content-Type", "application/json")
+                .body(Body::from(
+                    serde_json::to_string(&ContentTagsRequest {
+                        tags: tags.into_iter().map(str::to_string).collect(),
+                    })
+                    .unwrap(),
+                ))
+                .unwrap()
+        };
+
+        let response = app
+            .clone()
+            .oneshot(set_tags(vec!["notes", " meetings ", "notes"]))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let updated: ClassifyResponse =
+            serde_json::from_slice(&response_to_bytes(response).await).unwrap();
+        assert_eq!(updated.content.tags, vec!["notes", "meetings"]);
+
+        for (tag, count) in [("notes", 1), ("meetings", 1), ("draft", 0)] {
+            let request = Request::get(format!("/query?tags={}", tag))
+                .body(Body::empty())
+                .unwrap();
+            let response = app.clone().oneshot(request).await.unwrap();
+            let query: ContentQueryResponse =
+                serde_json::from_slice(&response_to_bytes(response).await).unwrap();
+            assert_eq!(query.count, count, "content tagged {}", tag);
+        }
+
+        let response = app.oneshot(set_tags(vec!["notes", " "])).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_reclassify_replaces_tags() {
+        use crate::config::{IngestConfig, TagPolicy};
+
+        let mut classifier_mock = MockClassifierMock::new();
+        classifier_mock
+            .expect_classify()
+            .with(eq("Borrowing explained"))
+            .times(1)
+            .returning(|_| Ok(vec!["rust".to_string(), "Ownership".to_string()]));
+
+        let content_storage = Arc::new(MemoryContentStorage::new());
+        let tag_storage = Arc::new(MemoryTagStorage::new());
+        let content = Content::new("Borrowing explained".to_string())
+            .with_tags(vec!["rust".to_string(), "misc".to_string()]);
+        let id = content.id.to_string();
+        content_storage.store(&content).await.unwrap();
+        tag_storage.add_tags(&id, &content.tags).await.unwrap();
+
+        let state = AppState::new(
+            Arc::new(classifier_mock),
+            content_storage.clone(),
+            tag_storage.clone(),
+        )
+        .with_ingest_config(IngestConfig {
+            tags: TagPolicy {
+                lowercase: true,
+                ..TagPolicy::default()
+            },
+            ..IngestConfig::default()
+        });
+        let app = Router::new()
+            .route(
+                "/content/:id/reclassify",
+                post(crate::api::reclassify_content),
+            )
+            .with_state(Arc::new(state));
+
+        let request = Request::post(format!("/content/{}/reclassify", content.slug.unwrap()))
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let reclassified: ReclassifyResponse =
+            serde_json::from_slice(&response_to_bytes(response).await).unwrap();
+
+        assert_eq!(reclassified.content.tags, vec!["rust", "ownership"]);
+        assert_eq!(reclassified.added, vec!["ownership"]);
+        assert_eq!(reclassified.removed, vec!["misc"]);
+        assert_eq!(
+            content_storage.get(&id).await.unwrap().unwrap().tags,
+            vec!["rust", "ownership"]
+        );
+        assert!(tag_storage.find_by_tag("misc").await.unwrap().is_empty());
+        assert_eq!(
+            tag_storage.find_by_tag("ownership").await.unwrap(),
+            vec![id]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_digest_groups_recent_content() {
+        let content_storage = Arc::new(MemoryContentStorage::new());
+        let mut old = Content::new("Last month".to_string()).with_tags(vec!["rust".to_string()]);
+        old.created_at = chrono::Utc::now() - chrono::Duration::days(30);
+        for content in [
+            Content::new("Borrowing explained".to_string()).with_tags(vec!["rust".to_string()]),
+            old,
+        ] {
+            content_storage.store(&content).await.unwrap();
+        }
+
+        let state = Arc::new(AppState::new(
+            Arc::new(MockClassifierMock::new()),
+            content_storage,
+            Arc::new(MemoryTagStorage::new()),
+        ));
+        let app = Router::new()
+            .route("/digest", get(crate::api::get_digest))
+            .with_state(state);
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::get("/digest?period=day")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response: DigestResponse =
+            serde_json::from_slice(&response_to_bytes(response).await).unwrap();
+        assert_eq!(response.digest.count, 1);
+        assert_eq!(response.digest.tags[0].tag, "rust");
+        assert_eq!(
+            response.digest.tags[0].items[0].title,
+            "Borrowing explained"
+        );
+
+        let response = app
+            .oneshot(
+                Request::get("/digest?period=year")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    /// Embeds text by whether it mentions each of a few words
+    struct KeywordEmbedder;
+
+    #[async_trait::async_trait]
+    impl crate::embedding::Embedder for KeywordEmbedder {
+        async fn embed(&self, text: &str) -> ClassifyResult<Vec<f32>> {
+            let text = text.to_lowercase();
+            Ok(["memory", "pasta", "travel"]
+                .iter()
+                .map(|word| if text.contains(word) { 1.0 } else { 0.0 })
+                .collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_semantic_search_finds_classified_content() {
+        let mut classifier_mock = MockClassifierMock::new();
+        classifier_mock
+            .expect_classify()
+            .times(2)
+            .returning(|_| Ok(vec!["notes".to_string()]));
+
+        let semantic_index = crate::embedding::index::SemanticIndex::new(
+            Arc::new(KeywordEmbedder),
+            Arc::new(crate::storage::vector::memory::MemoryVectorStorage::new()),
+        );
+        let state = Arc::new(
+            AppState::new(
+                Arc::new(classifier_mock),
+                Arc::new(MemoryContentStorage::new()),
+                Arc::new(MemoryTagStorage::new()),
+            )
+            .with_semantic_index(Some(semantic_index)),
+        );
+        let app = Router::new()
+            .route("/classify", post(crate::api::classify_content))
+            .route("/search/semantic", get(crate::api::semantic_search))
+            .with_state(state);
+
+        for content in ["Managing memory in Rust", "Fresh pasta for two"] {
+            let request = Request::post("/classify")
+                .header("Content-Type", "application/json")
+                .body(Body::from(
+                    serde_json::to_string(&ClassifyRequest {
+                        content: content.to_string(),
+                        metadata: HashMap::new(),
+                    })
+                    .unwrap(),
+                ))
+                .unwrap();
+            let response = app.clone().oneshot(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::get("/search/semantic?q=homemade%20pasta&limit=1")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response: SemanticSearchResponse =
+            serde_json::from_slice(&response_to_bytes(response).await).unwrap();
+        assert_eq!(response.count, 1);
+        assert_eq!(response.items[0].content.content, "Fresh pasta for two");
+        assert!(response.items[0].score > 0.5);
+
+        let response = app
+            .oneshot(
+                Request::get("/search/semantic?q=%20")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_ask_answers_from_tagged_content() {
+        let content_storage = Arc::new(MemoryContentStorage::new());
+        let tag_storage = Arc::new(MemoryTagStorage::new());
+        let pasta = Content::new("Cook fresh pasta for two minutes".to_string())
+            .with_tags(vec!["pasta".to_string()]);
+        let rust =
+            Content::new("Managing memory in Rust".to_string()).with_tags(vec!["rust".to_string()]);
+        for content in [&pasta, &rust] {
+            content_storage.store(content).await.unwrap();
+            tag_storage
+                .add_tags(&content.id.to_string(), &content.tags)
+                .await
+                .unwrap();
+        }
+
+        let mut classifier_mock = MockClassifierMock::new();
+        classifier_mock
+            .expect_complete()
+            .times(1)
+            .withf(|_, prompt| {
+                prompt.contains("[1] Cook fresh pasta")
+                    && !prompt.contains("Rust")
+                    && prompt.ends_with("Question: How long do I cook pasta?")
+            })
+            .returning(|_, _| Ok("About two minutes [1].".to_string()));
+
+        let state = Arc::new(AppState::new(
+            Arc::new(classifier_mock),
+            content_storage,
+            tag_storage,
+        ));
+        let app = Router::new()
+            .route("/ask", post(crate::api::ask))
+            .with_state(state);
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::post("/ask")
+                    .header("Content-Type", "application/json")
+                    .body(Body::from(r#"{"question": "How long do I cook pasta?"}"#))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response: AskResponse =
+            serde_json::from_slice(&response_to_bytes(response).await).unwrap();
+        assert_eq!(response.answer, "About two minutes [1].");
+        assert_eq!(response.sources, 1);
+        assert_eq!(response.citations.len(), 1);
+        assert_eq!(response.citations[0].id, pasta.id);
+
+        let response = app
+            .oneshot(
+                Request::post("/ask")
+                    .header("Content-Type", "application/json")
+                    .body(Body::from(r#"{"question": " "}"#))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_recommendations_follow_queries_and_skip_viewed_content() {
+        let content_storage = Arc::new(MemoryContentStorage::new());
+        let tag_storage = Arc::new(MemoryTagStorage::new());
+        let viewed = Content::new("Fresh pasta".to_string()).with_tags(vec!["pasta".to_string()]);
+        let unread = Content::new("Pasta sauces".to_string()).with_tags(vec!["pasta".to_string()]);
+        let other = Content::new("Memory leaks".to_string()).with_tags(vec!["rust".to_string()]);
+        for content in [&viewed, &unread, &other] {
+            content_storage.store(content).await.unwrap();
+            tag_storage
+                .add_tags(&content.id.to_string(), &content.tags)
+                .await
+                .unwrap();
+        }
+
+        let activity_log = crate::activity::ActivityLog::new(Arc::new(
+            crate::activity::MemoryActivityStore::new(100),
+        ));
+        let state = Arc::new(
+            AppState::new(
+                Arc::new(MockClassifierMock::new()),
+                content_storage,
+                tag_storage,
+            )
+            .with_activity_log(Some(activity_log)),
+        );
+        let app = Router::new()
+            .route("/query", get(crate::api::query_content))
+            .route("/content/:id", get(crate::api::get_content_text))
+            .route("/recommendations", get(crate::api::get_recommendations))
+            .with_state(state);
+
+        // Without activity the newest content is recommended
+        let response = app
+            .clone()
+            .oneshot(
+                Request::get("/recommendations")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response: RecommendationsResponse =
+            serde_json::from_slice(&response_to_bytes(response).await).unwrap();
+        assert_eq!(response.count, 3);
+
+        for uri in [
+            "/query?tags=pasta".to_string(),
+            format!("/content/{}", viewed.id),
+        ] {
+            let response = app
+                .clone()
+                .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+
+        let response = app
+            .oneshot(
+                Request::get("/recommendations")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response: RecommendationsResponse =
+            serde_json::from_slice(&response_to_bytes(response).await).unwrap();
+        assert_eq!(response.count, 1);
+        assert_eq!(response.items[0].content.id, unread.id);
+        assert_eq!(response.items[0].reasons, vec!["pasta"]);
+    }
+
+    #[tokio::test]
+    async fn test_cluster_labels_and_tags_content() {
+        let mut classifier_mock = MockClassifierMock::new();
+        classifier_mock.expect_classify().returning(|prompt| {
+            Ok(vec![if prompt.contains("pasta") {
+                "cooking".to_string()
+            } else {
+                "rust".to_string()
+            }])
+        });
+
+        let content_storage = Arc::new(MemoryContentStorage::new());
+        let tag_storage = Arc::new(MemoryTagStorage::new());
+        let semantic_index = crate::embedding::index::SemanticIndex::new(
+            Arc::new(KeywordEmbedder),
+            Arc::new(crate::storage::vector::memory::MemoryVectorStorage::new()),
+        );
+        for text in [
+            "Fresh pasta for two",
+            "Pasta sauces",
+            "Managing memory",
+            "Memory leaks",
+        ] {
+            let content = Content::new(text.to_string());
+            content_storage.store(&content).await.unwrap();
+            semantic_index.index(&content, text).await.unwrap();
+        }
+
+        let state = Arc::new(
+            AppState::new(
+                Arc::new(classifier_mock),
+                content_storage.clone(),
+                tag_storage.clone(),
+            )
+            .with_semantic_index(Some(semantic_index)),
+        );
+        let app = Router::new()
+            .route("/admin/cluster", post(crate::api::cluster_content))
+            .layer(axum::Extension(crate::api::Caller("admin".to_string())))
+            .with_state(state);
+
+        let response = app
+            .oneshot(
+                Request::post("/admin/cluster")
+                    .header("Content-Type", "application/json")
+                    .body(Body::from(r#"{"clusters": 2, "apply": true}"#))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response: ClusterResponse =
+            serde_json::from_slice(&response_to_bytes(response).await).unwrap();
+
+        assert_eq!(response.clusters.len(), 2);
+        assert_eq!(response.tagged, 4);
+        let mut labels: Vec<String> = response
+            .clusters
+            .iter()
+            .filter_map(|cluster| cluster.label.clone())
+            .collect();
+        labels.sort();
+        assert_eq!(labels, vec!["cooking", "rust"]);
+        assert_eq!(tag_storage.find_by_tag("cooking").await.unwrap().len(), 2);
+        let tagged = content_storage.list().await.unwrap();
+        assert!(tagged.iter().all(|content| content.tags.len() == 1));
+    }
+
+    #[tokio::test]
+    async fn test_trending_tags_count_classified_content() {
+        let mut classifier_mock = MockClassifierMock::new();
+        classifier_mock
+            .expect_classify()
+            .times(2)
+            .returning(|_| Ok(vec!["rust".to_string()]));
+
+        let tag_trends =
+            crate::trends::TagTrends::new(Arc::new(crate::trends::MemoryTrendStore::new(30)), 30);
+        let state = Arc::new(
+            AppState::new(
+                Arc::new(classifier_mock),
+                Arc::new(MemoryContentStorage::new()),
+                Arc::new(MemoryTagStorage::new()),
+            )
+            .with_tag_trends(Some(tag_trends)),
+        );
+        let app = Router::new()
+            .route("/classify", post(crate::api::classify_content))
+            .route("/tags/trending", get(crate::api::get_trending_tags))
+            .with_state(state);
+
+        for content in ["Ownership in Rust", "Lifetimes in Rust"] {
+            let request = Request::post("/classify")
+                .header("Content-Type", "application/json")
+                .body(Body::from(
+                    serde_json::to_string(&ClassifyRequest {
+                        content: content.to_string(),
+                        metadata: HashMap::new(),
+                    })
+                    .unwrap(),
+                ))
+                .unwrap();
+            let response = app.clone().oneshot(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::get("/tags/trending?recent_days=7&baseline_days=21")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response: TrendingTagsResponse =
+            serde_json::from_slice(&response_to_bytes(response).await).unwrap();
+        assert_eq!(response.trending.len(), 1);
+        assert_eq!(response.trending[0].tag, "rust");
+        assert_eq!(response.trending[0].recent, 2);
+
+        // The windows can't reach past the days counters are kept
+        let response = app
+            .oneshot(
+                Request::get("/tags/trending?recent_days=7&baseline_days=28")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_ui_is_served() {
+        let app = Router::new()
+            .route("/ui", get(crate::api::ui::index))
+            .route("/ui/*path", get(crate::api::ui::asset));
+
+        let response = app
+            .clone()
+            .oneshot(Request::get("/ui").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()["content-type"],
+            "text/html; charset=utf-8"
+        );
+        let page = response_to_bytes(response).await;
+        assert!(String::from_utf8_lossy(&page).contains("/ui/app.js"));
+
+        let response = app
+            .clone()
+            .oneshot(Request::get("/ui/app.js").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()["content-type"],
+            "text/javascript; charset=utf-8"
+        );
+
+        let response = app
+            .oneshot(Request::get("/ui/missing.js").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_read_only_refuses_changes() {
+        let state = Arc::new(
+            AppState::new(
+                Arc::new(MockClassifierMock::new()),
+                Arc::new(MemoryContentStorage::new()),
+                Arc::new(MemoryTagStorage::new()),
+            )
+            .with_read_only(true),
+        );
+
+        let app = Router::new()
+            .route("/classify", post(crate::api::classify_content))
+            .route("/tags", get(crate::api::get_tags))
+            .layer(axum::middleware::from_fn_with_state(
+                state.clone(),
+                crate::api::middleware::enforce_read_only,
+            ))
+            .with_state(state.clone());
+
+        let request = Request::post("/classify")
+            .header("Content-Type", "application/json")
+            .body(Body::from(r#"{"content": "Some text"}"#))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let request = Request::get("/tags").body(Body::empty()).unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // A read-only key is refused the same way when the server is writable
+        let state = Arc::new(AppState::new(
+            Arc::new(MockClassifierMock::new()),
+            Arc::new(MemoryContentStorage::new()),
+            Arc::new(MemoryTagStorage::new()),
+        ));
+        let app = Router::new()
+            .route(
+                "/content/:id",
+                axum::routing::delete(crate::api::delete_content),
+            )
+            .layer(axum::middleware::from_fn_with_state(
+                state.clone(),
+                crate::api::middleware::enforce_read_only,
+            ))
+            .layer(axum::Extension(crate::api::middleware::ReadOnly))
+            .with_state(state);
+
+        let request = Request::delete("/content/some-id")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_maintenance_mode_refuses_changes_until_switched_off() {
+        let state = Arc::new(
+            AppState::new(
+                Arc::new(MockClassifierMock::new()),
+                Arc::new(MemoryContentStorage::new()),
+                Arc::new(MemoryTagStorage::new()),
+            )
+            .with_maintenance(crate::api::maintenance::Maintenance::new(
+                true,
+                Some("Migrating to Postgres".to_string()),
+            )),
+        );
+
+        let app = Router::new()
+            .route(
+                "/content/:id",
+                axum::routing::delete(crate::api::delete_content),
+            )
+            .route("/tags", get(crate::api::get_tags))
+            .route(
+                crate::api::maintenance::PATH,
+                axum::routing::put(crate::api::set_maintenance),
+            )
+            .layer(axum::middleware::from_fn_with_state(
+                state.clone(),
+                crate::api::middleware::enforce_maintenance,
+            ))
+            .layer(axum::Extension(crate::api::Caller("admin".to_string())))
+            .with_state(state.clone());
+
+        let delete = || {
+            Request::delete(format!("/content/{}", uuid::Uuid::new_v4()))
+                .body(Body::empty())
+                .unwrap()
+        };
+        let response = app.clone().oneshot(delete()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        let body: ContentQueryResponse =
+            serde_json::from_slice(&response_to_bytes(response).await).unwrap();
+        assert_eq!(body.error.as_deref(), Some("Migrating to Postgres"));
+
+        let request = Request::get("/tags").body(Body::empty()).unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let request = Request::put(crate::api::maintenance::PATH)
+            .header("Content-Type", "application/json")
+            .body(Body::from(r#"{"enabled": false}"#))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(!state.maintenance.is_enabled());
+
+        let response = app.oneshot(delete()).await.unwrap();
+        // Reaches the handler, which doesn't find the content
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_usage_is_counted_and_quota_enforced() {
+        let mut classifier_mock = MockClassifierMock::new();
+        classifier_mock
+            .expect_classify()
+            .times(1)
+            .returning(|_| Ok(vec!["rust".to_string()]));
+
+        let quota = crate::UsageQuota {
+            classifications: Some(1),
+            ..crate::UsageQuota::default()
+        };
+        let state = Arc::new(
+            AppState::new(
+                Arc::new(classifier_mock),
+                Arc::new(MemoryContentStorage::new()),
+                Arc::new(MemoryTagStorage::new()),
+            )
+            .with_usage(Some(crate::usage::UsageTracker::new(
+                Arc::new(crate::storage::usage::memory::MemoryUsageStorage::new()),
+                quota,
+                HashMap::new(),
+            ))),
+        );
+
+        let app = Router::new()
+            .route("/classify", post(crate::api::classify_content))
+            .route("/usage", get(crate::api::get_usage))
+            .layer(axum::middleware::from_fn_with_state(
+                state.clone(),
+                crate::api::middleware::track_usage,
+            ))
+            .layer(axum::Extension(crate::api::Caller("key:1".to_string())))
+            .with_state(state);
+
+        let classify = |content: &str| {
+            Request::post("/classify")
+                .header("Content-Type", "application/json")
+                .body(Body::from(format!(r#"{{"content": "{}"}}"#, content)))
+                .unwrap()
+        };
+
+        let response = app.clone().oneshot(classify("First text")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = app.clone().oneshot(classify("Second text")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(response.headers().contains_key("Retry-After"));
+
+        let request = Request::get("/usage").body(Body::empty()).unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let usage: crate::UsageResponse =
+            serde_json::from_slice(&response_to_bytes(response).await).unwrap();
+        assert_eq!(usage.accounts.len(), 1);
+        assert_eq!(usage.accounts[0].account, "key:1");
+        assert_eq!(usage.accounts[0].usage.classifications, 1);
+        assert_eq!(
+            usage.accounts[0].usage.stored_bytes,
+            "First text".len() as u64
+        );
+    }
+
+    #[tokio::test]
+    async fn test_request_id_is_propagated_or_assigned() {
+        let app =
+            Router::new()
+                .route("/", get(|| async { "ok" }))
+                .layer(axum::middleware::from_fn(
+                    crate::api::middleware::request_id,
+                ));
+
+        let request = Request::get("/")
+            .header("X-Request-Id", "abc-123")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.headers()["x-request-id"], "abc-123");
+
+        // IDs that aren't safe to log are replaced
+        let request = Request::get("/")
+            .header("X-Request-Id", "abc 123\"")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        let id = response.headers()["x-request-id"].to_str().unwrap();
+        assert!(uuid::Uuid::parse_str(id).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_set_log_level_validates_filter() {
+        let app = Router::new().route(
+            "/admin/log-level",
+            axum::routing::put(crate::api::set_log_level),
+        );
+
+        let set_filter = |filter: &str| {
+            Request::put("/admin/log-level")
+                .header("Content-Type", "application/json")
+                .body(Body::from(format!(r#"{{"filter": "{}"}}"#, filter)))
+                .unwrap()
+        };
+
+        let admin = app
+            .clone()
+            .layer(axum::Extension(crate::api::Caller("admin".to_string())));
+        let response = admin.oneshot(set_filter("info,[")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        // A stored key without a tenant isn't the main API key
+        let app = app.layer(axum::Extension(crate::api::Caller("key:1".to_string())));
+        let response = app.oneshot(set_filter("debug")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_deep_health_check_reports_unavailable_storage() {
+        let mut content_storage_mock = MockContentStorageMock::new();
+        content_storage_mock.expect_get().returning(|_| {
+            Err(crate::ClassifyError::StorageError(
+                "Connection refused".to_string(),
+            ))
+        });
+
+        let state = Arc::new(AppState::new(
+            Arc::new(MockClassifierMock::new()),
+            Arc::new(content_storage_mock),
+            Arc::new(MemoryTagStorage::new()),
+        ));
+        let app = Router::new()
+            .route("/health", get(crate::api::deep_health_check))
+            .with_state(state);
+
+        let request = Request::get("/health").body(Body::empty()).unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        let health: crate::HealthResponse =
+            serde_json::from_slice(&response_to_bytes(response).await).unwrap();
+        assert!(!health.healthy);
+        assert!(!health.components["content_storage"].healthy);
+        assert!(health.components["tag_storage"].healthy);
+        assert!(!health.components.contains_key("classifier"));
+    }
+
+    #[tokio::test]
+    async fn test_deep_health_check_includes_classifier_when_asked() {
+        let state = Arc::new(AppState::new(
+            Arc::new(MockClassifierMock::new()),
+            Arc::new(MemoryContentStorage::new()),
+            Arc::new(MemoryTagStorage::new()),
+        ));
+        let app = Router::new()
+            .route("/health", get(crate::api::deep_health_check))
+            .with_state(state);
+
+        let request = Request::get("/health?classifier=true")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let health: crate::HealthResponse =
+            serde_json::from_slice(&response_to_bytes(response).await).unwrap();
+        assert!(health.healthy);
+        assert!(health.components["classifier"].healthy);
+    }
+
+    #[tokio::test]
+    async fn test_readiness_fails_without_storage_but_liveness_does_not() {
+        let mut tag_storage_mock = MockTagStorageMock::new();
+        tag_storage_mock.expect_get_tags().returning(|_| {
+            Err(crate::ClassifyError::StorageError(
+                "Connection refused".to_string(),
+            ))
+        });
+
+        let state = Arc::new(AppState::new(
+            Arc::new(MockClassifierMock::new()),
+            Arc::new(MemoryContentStorage::new()),
+            Arc::new(tag_storage_mock),
+        ));
+        let app = Router::new()
+            .route("/healthz", get(crate::api::health_check))
+            .route("/readyz", get(crate::api::readiness_check))
+            .with_state(state);
+
+        let request = Request::get("/healthz").body(Body::empty()).unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let request = Request::get("/readyz").body(Body::empty()).unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        let health: crate::HealthResponse =
+            serde_json::from_slice(&response_to_bytes(response).await).unwrap();
+        assert!(!health.components["tag_storage"].healthy);
+        assert!(health.components["config"].healthy);
+    }
+
+    #[tokio::test]
+    async fn test_metrics_for_main_api_key_only() {
+        let recorder = metrics_exporter_prometheus::PrometheusBuilder::new().build_recorder();
+        metrics::with_local_recorder(&recorder, || {
+            metrics::counter!("auth_failures_total").increment(2);
+        });
+
+        let state = Arc::new(
+            AppState::new(
+                Arc::new(MockClassifierMock::new()),
+                Arc::new(MemoryContentStorage::new()),
+                Arc::new(MemoryTagStorage::new()),
+            )
+            .with_metrics(Some(recorder.handle())),
+        );
+
+        let app = Router::new()
+            .route("/metrics", get(crate::api::get_metrics))
+            .with_state(state);
+
+        let request = Request::get("/metrics").body(Body::empty()).unwrap();
+        let response = app
+            .clone()
+            .layer(axum::Extension(crate::api::Caller("admin".to_string())))
+            .oneshot(request)
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = String::from_utf8(response_to_bytes(response).await).unwrap();
+        assert!(body.contains("auth_failures_total 2"));
+
+        for caller in ["tenant:acme", "read-only:0123456789abcdef", "jwt:alice"] {
+            let app = app
+                .clone()
+                .layer(axum::Extension(crate::api::Caller(caller.to_string())));
+            let request = Request::get("/metrics").body(Body::empty()).unwrap();
+            let response = app.oneshot(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_admin_endpoints_refuse_callers_without_tenant() {
+        use crate::storage::api_key::memory::MemoryApiKeyStorage;
+        use crate::storage::api_key::ApiKeys;
+
+        let state = Arc::new(
+            AppState::new(
+                Arc::new(MockClassifierMock::new()),
+                Arc::new(MemoryContentStorage::new()),
+                Arc::new(MemoryTagStorage::new()),
+            )
+            .with_api_keys(Some(ApiKeys::new(
+                Arc::new(MemoryApiKeyStorage::new()),
+                std::time::Duration::ZERO,
+            ))),
+        );
+        let app = Router::new()
+            .route("/admin/keys", get(crate::api::list_api_keys))
+            .route("/admin/purge", post(crate::api::purge_content))
+            .with_state(state);
+
+        let list_keys = || Request::get("/admin/keys").body(Body::empty()).unwrap();
+        let purge = || {
+            Request::post("/admin/purge")
+                .header("Content-Type", "application/json")
+                .body(Body::from(r#"{"url_pattern": "*"}"#))
+                .unwrap()
+        };
+
+        let admin = app
+            .clone()
+            .layer(axum::Extension(crate::api::Caller("admin".to_string())));
+        let response = admin.oneshot(list_keys()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // None of these has a tenant, and none is the main API key
+        for caller in [
+            "read-only:0123456789abcdef",
+            "key:1",
+            "jwt:alice",
+            "cert:ab",
+        ] {
+            let app = app
+                .clone()
+                .layer(axum::Extension(crate::api::Caller(caller.to_string())));
+            let response = app.clone().oneshot(list_keys()).await.unwrap();
+            assert_eq!(response.status(), StatusCode::FORBIDDEN);
+            let response = app.oneshot(purge()).await.unwrap();
+            assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_runtime_stats_for_main_api_key_only() {
+        let connection = Arc::new(tokio::sync::Mutex::new(()));
+        crate::storage::connections::register("test", &connection);
+        let _held = connection.lock().await;
+
+        let state = Arc::new(AppState::new(
+            Arc::new(MockClassifierMock::new()),
+            Arc::new(MemoryContentStorage::new()),
+            Arc::new(MemoryTagStorage::new()),
+        ));
+
+        let app = Router::new()
+            .route("/admin/debug/runtime", get(crate::api::get_runtime_stats))
+            .with_state(state);
+
+        let request = Request::get("/admin/debug/runtime")
+            .body(Body::empty())
+            .unwrap();
+        let response = app
+            .clone()
+            .layer(axum::Extension(crate::api::Caller("admin".to_string())))
+            .oneshot(request)
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let stats: crate::RuntimeStatsResponse =
+            serde_json::from_slice(&response_to_bytes(response).await).unwrap();
+        assert!(stats.workers >= 1);
+        assert!(stats.connections.contains(&crate::ConnectionStats {
+            backend: "test".to_string(),
+            users: 1,
+            in_use: true,
+        }));
+
+        let app = app.layer(axum::Extension(crate::api::Caller(
+            "cert:0123456789abcdef".to_string(),
+        )));
+        let request = Request::get("/admin/debug/runtime")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_share_link_serves_content_without_api_key() {
+        let content_storage = Arc::new(MemoryContentStorage::new());
+        let content = Content::new("Shared text".to_string());
+        content_storage.store(&content).await.unwrap();
+
+        let state = Arc::new(
+            AppState::new(
+                Arc::new(MockClassifierMock::new()),
+                content_storage,
+                Arc::new(MemoryTagStorage::new()),
+            )
+            .with_share_links(Some(crate::api::share::ShareLinks::new(
+                "secret",
+                std::time::Duration::from_secs(3600),
+                std::time::Duration::from_secs(86400),
+                None,
+            ))),
+        );
+
+        let app = Router::new()
+            .route("/content/:id/share", post(crate::api::share_content))
+            .route("/shared/:id", get(crate::api::get_shared_content))
+            .with_state(state);
+
+        let request = Request::post(format!("/content/{}/share", content.id))
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let share: crate::ShareResponse =
+            serde_json::from_slice(&response_to_bytes(response).await).unwrap();
+
+        let request = Request::get(&share.url).body(Body::empty()).unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get("X-Content-Type-Options").unwrap(),
+            "nosniff"
+        );
+        assert_eq!(response_to_bytes(response).await, b"Shared text");
+
+        let tampered = share.url.replace("expires=", "expires=1");
+        let request = Request::get(tampered).body(Body::empty()).unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[test]
+    fn test_private_content_is_only_seen_by_its_owner() {
+        use crate::api::access::Access;
+
+        let mut content =
+            Content::new("Private notes".to_string()).with_namespace(Some("acme".to_string()));
+        content.visibility = crate::Visibility::Private;
+        content.owner = Some("key:1".to_string());
+
+        let access = |namespace: Option<&str>, caller: Option<&str>| Access {
+            namespace: namespace.map(String::from),
+            caller: caller.map(String::from),
+        };
+
+        assert!(access(Some("acme"), Some("key:1")).can_modify(&content));
+        assert!(!access(Some("acme"), Some("tenant:acme")).can_read(&content));
+        assert!(access(None, Some("admin")).can_modify(&content));
+        // Other tenants can read public content but not change it
+        content.visibility = crate::Visibility::Public;
+        assert!(access(Some("globex"), Some("key:2")).can_read(&content));
+        assert!(!access(Some("globex"), Some("key:2")).can_modify(&content));
+    }
+
     async fn response_to_bytes(response: Response) -> Vec<u8> {
         let response_bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
             .await
@@ -0,0 +1,216 @@
+use axum::{extract::ConnectInfo, Extension, Router};
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use hyper_util::server::conn::auto;
+use hyper_util::server::graceful::GracefulShutdown;
+use hyper_util::service::TowerToHyperService;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::server::WebPkiClientVerifier;
+use rustls::{RootCertStore, ServerConfig};
+use sha2::{Digest, Sha256};
+use std::io::BufReader;
+use std::net::SocketAddr;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+use tokio::net::TcpListener;
+use tokio_rustls::TlsAcceptor;
+use tracing::{debug, error, info};
+
+use crate::config::TlsConfig;
+use crate::shutdown;
+use crate::{ClassifyError, ClassifyResult};
+
+/// Client certificate verified against the configured CA, identified by its SHA-256 fingerprint
+#[derive(Debug, Clone)]
+pub struct ClientCertificate(pub String);
+
+impl ClientCertificate {
+    fn from_der(cert: &CertificateDer<'_>) -> Self {
+        Self(format!("{:x}", Sha256::digest(cert.as_ref())))
+    }
+}
+
+fn load_certs(path: &str) -> ClassifyResult<Vec<CertificateDer<'static>>> {
+    let file = std::fs::File::open(path).map_err(|e| {
+        ClassifyError::ConfigError(format!("Failed to open certificate {}: {}", path, e))
+    })?;
+
+    let certs = rustls_pemfile::certs(&mut BufReader::new(file))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| {
+            ClassifyError::ConfigError(format!("Failed to read certificate {}: {}", path, e))
+        })?;
+
+    if certs.is_empty() {
+        return Err(ClassifyError::ConfigError(format!(
+            "No certificates found in {}",
+            path
+        )));
+    }
+
+    Ok(certs)
+}
+
+fn load_private_key(path: &str) -> ClassifyResult<PrivateKeyDer<'static>> {
+    let file = std::fs::File::open(path).map_err(|e| {
+        ClassifyError::ConfigError(format!("Failed to open private key {}: {}", path, e))
+    })?;
+
+    rustls_pemfile::private_key(&mut BufReader::new(file))
+        .map_err(|e| {
+            ClassifyError::ConfigError(format!("Failed to read private key {}: {}", path, e))
+        })?
+        .ok_or_else(|| ClassifyError::ConfigError(format!("No private key found in {}", path)))
+}
+
+/// Build the rustls configuration from the certificate files
+pub fn load_server_config(config: &TlsConfig) -> ClassifyResult<Arc<ServerConfig>> {
+    let certs = load_certs(&config.cert_path)?;
+    let key = load_private_key(&config.key_path)?;
+
+    let builder = match &config.client_ca_path {
+        Some(client_ca_path) => {
+            let mut roots = RootCertStore::empty();
+            for cert in load_certs(client_ca_path)? {
+                roots.add(cert).map_err(|e| {
+                    ClassifyError::ConfigError(format!("Invalid client CA certificate: {}", e))
+                })?;
+            }
+
+            // Clients without a certificate can still authenticate with an API key
+            let verifier = WebPkiClientVerifier::builder(Arc::new(roots))
+                .allow_unauthenticated()
+                .build()
+                .map_err(|e| {
+                    ClassifyError::ConfigError(format!("Invalid client CA certificate: {}", e))
+                })?;
+            ServerConfig::builder().with_client_cert_verifier(verifier)
+        }
+        None => ServerConfig::builder().with_no_client_auth(),
+    };
+
+    let mut server_config = builder
+        .with_single_cert(certs, key)
+        .map_err(|e| ClassifyError::ConfigError(format!("Invalid TLS certificate: {}", e)))?;
+    server_config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+
+    Ok(Arc::new(server_config))
+}
+
+/// Modification times of the certificate files, to notice when they are replaced
+fn modified_times(config: &TlsConfig) -> Vec<Option<SystemTime>> {
+    [
+        Some(&config.cert_path),
+        Some(&config.key_path),
+        config.client_ca_path.as_ref(),
+    ]
+    .into_iter()
+    .flatten()
+    .map(|path| {
+        std::fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    })
+    .collect()
+}
+
+/// Reload the server configuration whenever the certificate files change. New
+/// connections use the new certificates, a configuration that fails to load is
+/// ignored and the previous one stays in use.
+fn spawn_reload(config: TlsConfig, current: Arc<RwLock<Arc<ServerConfig>>>, interval: Duration) {
+    tokio::spawn(async move {
+        let mut modified = modified_times(&config);
+        let mut ticker = tokio::time::interval(interval);
+        ticker.tick().await;
+
+        loop {
+            ticker.tick().await;
+
+            let latest = modified_times(&config);
+            if latest == modified {
+                continue;
+            }
+            modified = latest;
+
+            match load_server_config(&config) {
+                Ok(server_config) => {
+                    *current.write().unwrap_or_else(|e| e.into_inner()) = server_config;
+                    info!("Reloaded TLS certificates");
+                }
+                Err(e) => error!("Failed to reload TLS certificates: {}", e),
+            }
+        }
+    });
+}
+
+/// Serve the router over TLS, passing each request the client address and certificate.
+/// On shutdown, stops accepting connections and returns once the open ones are done.
+pub async fn serve_tls(
+    listener: TcpListener,
+    app: Router,
+    config: &TlsConfig,
+    server_config: Arc<ServerConfig>,
+) -> ClassifyResult<()> {
+    let current = Arc::new(RwLock::new(server_config));
+    if let Some(interval) = config.reload_interval {
+        spawn_reload(config.clone(), current.clone(), interval);
+    }
+
+    info!("Serving HTTPS");
+
+    let graceful = GracefulShutdown::new();
+
+    loop {
+        let accepted = tokio::select! {
+            accepted = listener.accept() => accepted,
+            _ = shutdown::requested() => break,
+        };
+        let (stream, addr) = match accepted {
+            Ok(connection) => connection,
+            Err(e) => {
+                debug!("Failed to accept connection: {}", e);
+                continue;
+            }
+        };
+
+        let acceptor = TlsAcceptor::from(current.read().unwrap_or_else(|e| e.into_inner()).clone());
+        let app = app.clone();
+        let watcher = graceful.watcher();
+
+        tokio::spawn(async move {
+            let stream = match acceptor.accept(stream).await {
+                Ok(stream) => stream,
+                Err(e) => {
+                    debug!("TLS handshake with {} failed: {}", addr, e);
+                    return;
+                }
+            };
+
+            let client_certificate = stream
+                .get_ref()
+                .1
+                .peer_certificates()
+                .and_then(|certs| certs.first())
+                .map(ClientCertificate::from_der);
+
+            let mut app = app.layer(Extension(ConnectInfo::<SocketAddr>(addr)));
+            if let Some(client_certificate) = client_certificate {
+                app = app.layer(Extension(client_certificate));
+            }
+
+            let builder = auto::Builder::new(TokioExecutor::new());
+            let connection =
+                builder.serve_connection(TokioIo::new(stream), TowerToHyperService::new(app));
+            if let Err(e) = watcher.watch(connection).await {
+                debug!("Connection from {} failed: {}", addr, e);
+            }
+        });
+    }
+
+    drop(listener);
+    info!(
+        "Waiting for {} open connections to finish",
+        graceful.count()
+    );
+    graceful.shutdown().await;
+    Ok(())
+}
@@ -0,0 +1,26 @@
+use crate::api::tls::load_server_config;
+use crate::config::TlsConfig;
+use crate::ClassifyError;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_certificate_is_a_config_error() {
+        let config = TlsConfig {
+            cert_path: "/nonexistent/server.pem".to_string(),
+            key_path: "/nonexistent/server.key".to_string(),
+            client_ca_path: None,
+            reload_interval: None,
+        };
+
+        match load_server_config(&config) {
+            Err(ClassifyError::ConfigError(message)) => {
+                assert!(message.contains("/nonexistent/server.pem"))
+            }
+            Err(e) => panic!("Unexpected error: {}", e),
+            Ok(_) => panic!("Loaded a missing certificate"),
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use axum::extract::Path;
+use axum::http::header::{CACHE_CONTROL, CONTENT_TYPE};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use rust_embed::RustEmbed;
+
+/// The web UI, compiled into the binary so it ships without extra files
+#[derive(RustEmbed)]
+#[folder = "ui/"]
+struct Assets;
+
+/// Path the UI is served under
+pub const PATH: &str = "/ui";
+
+fn content_type(path: &str) -> &'static str {
+    match path.rsplit_once('.').map(|(_, extension)| extension) {
+        Some("html") => "text/html; charset=utf-8",
+        Some("js") => "text/javascript; charset=utf-8",
+        Some("css") => "text/css; charset=utf-8",
+        Some("svg") => "image/svg+xml",
+        _ => "application/octet-stream",
+    }
+}
+
+fn serve(path: &str) -> Response {
+    match Assets::get(path) {
+        Some(file) => (
+            [
+                (CONTENT_TYPE, content_type(path)),
+                // Revalidated so a new release is picked up right away
+                (CACHE_CONTROL, "no-cache"),
+            ],
+            file.data.into_owned(),
+        )
+            .into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+/// The page of the UI. It asks for an API key and calls the API with it, the
+/// assets themselves are served without one.
+pub async fn index() -> Response {
+    serve("index.html")
+}
+
+pub async fn asset(Path(path): Path<String>) -> Response {
+    serve(&path)
+}
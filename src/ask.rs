@@ -0,0 +1,144 @@
+use std::collections::HashSet;
+use tracing::{info, warn};
+
+use crate::api::{AppState, SNAPSHOT_ATTACHMENT};
+use crate::extract::html::strip_html;
+use crate::jobs::digest::title;
+use crate::{Citation, ClassifyResult, Content};
+
+/// Characters of each item sent to the LLM as context
+const SOURCE_LENGTH: usize = 3_000;
+
+/// Instructions for answering from the retrieved content only
+pub const SYSTEM_PROMPT: &str = "You answer questions about a personal collection of saved \
+    content. Use only the numbered sources below, not what you know otherwise. Cite the sources \
+    you use by their number in square brackets, like [2]. When the sources don't answer the \
+    question, say so.";
+
+/// Words of a question, to be matched against tags
+fn words(question: &str) -> HashSet<String> {
+    question
+        .split(|c: char| !c.is_alphanumeric() && c != '-')
+        .filter(|word| word.len() > 2)
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Content that may answer `question`: the closest in meaning when semantic
+/// search is configured, then the newest content with tags named in the
+/// question, up to `limit` items that pass `can_read`
+pub async fn retrieve(
+    state: &AppState,
+    question: &str,
+    limit: usize,
+    can_read: impl Fn(&Content) -> bool,
+) -> ClassifyResult<Vec<Content>> {
+    let mut ids: Vec<String> = Vec::new();
+
+    if let Some(index) = &state.semantic_index {
+        match index
+            .search(question, state.namespace.as_deref(), limit * 2)
+            .await
+        {
+            Ok(nearest) => ids.extend(nearest.into_iter().map(|(id, _)| id)),
+            Err(e) => warn!("Answering from tags only, semantic search failed: {}", e),
+        }
+    }
+
+    let words = words(question);
+    let mut tagged: Vec<Content> = Vec::new();
+    for tag in state.tag_storage.list_tags().await? {
+        if words.contains(&tag.to_lowercase()) {
+            let tag_ids = state.tag_storage.find_by_tag(&tag).await?;
+            tagged.extend(state.content_storage.get_many(&tag_ids).await?);
+        }
+    }
+    tagged.sort_by_key(|content| std::cmp::Reverse(content.created_at));
+    ids.extend(tagged.iter().map(|content| content.id.to_string()));
+
+    let mut seen = HashSet::new();
+    ids.retain(|id| seen.insert(id.clone()));
+
+    let mut found = state.content_storage.get_many(&ids).await?;
+    found.retain(|content| can_read(content));
+    // get_many doesn't keep the order of the IDs
+    found.sort_by_key(|content| {
+        ids.iter()
+            .position(|id| *id == content.id.to_string())
+            .unwrap_or(usize::MAX)
+    });
+    found.truncate(limit);
+
+    info!("Retrieved {} items to answer the question", found.len());
+    Ok(found)
+}
+
+/// Text of content given to the LLM: the text itself, or the archived page of a
+/// URL. URLs without a snapshot are given by their address and tags.
+pub async fn source_text(state: &AppState, content: &Content) -> String {
+    let text = if content.is_url() {
+        let snapshot = state
+            .content_storage
+            .get_attachment(&content.id.to_string(), SNAPSHOT_ATTACHMENT)
+            .await
+            .ok()
+            .flatten();
+        match snapshot {
+            Some(page) => strip_html(&String::from_utf8_lossy(&page)),
+            None => content.content.clone(),
+        }
+    } else {
+        content.content.clone()
+    };
+
+    match text.char_indices().nth(SOURCE_LENGTH) {
+        Some((end, _)) => format!("{}...", &text[..end]),
+        None => text,
+    }
+}
+
+/// The question with the numbered sources to answer it from
+pub fn build_prompt(question: &str, sources: &[(Content, String)]) -> String {
+    let mut prompt = String::from("Sources:\n\n");
+    for (number, (content, text)) in sources.iter().enumerate() {
+        prompt.push_str(&format!(
+            "[{}] {}\nTags: {}\n{}\n\n",
+            number + 1,
+            title(content),
+            content.tags.join(", "),
+            text
+        ));
+    }
+    prompt.push_str(&format!("Question: {}", question));
+    prompt
+}
+
+/// Sources cited in an answer as `[n]`, in the order they are first cited
+pub fn citations(answer: &str, sources: &[Content]) -> Vec<Citation> {
+    let mut cited = Vec::new();
+    for part in answer.split('[').skip(1) {
+        let Some((number, _)) = part.split_once(']') else {
+            continue;
+        };
+        // Several sources may be cited together, like [1, 3]
+        for number in number.split(',') {
+            let Ok(number) = number.trim().parse::<usize>() else {
+                continue;
+            };
+            let Some(content) = number.checked_sub(1).and_then(|index| sources.get(index)) else {
+                continue;
+            };
+            if !cited
+                .iter()
+                .any(|citation: &Citation| citation.number == number)
+            {
+                cited.push(Citation {
+                    number,
+                    id: content.id,
+                    title: title(content),
+                });
+            }
+        }
+    }
+    cited
+}
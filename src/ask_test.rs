@@ -0,0 +1,52 @@
+use crate::ask::{build_prompt, citations};
+use crate::Content;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sources() -> Vec<Content> {
+        vec![
+            Content::new("Fresh pasta".to_string()).with_tags(vec!["cooking".to_string()]),
+            Content::new("Risotto".to_string()),
+            Content::new("https://example.com/bread".to_string()),
+        ]
+    }
+
+    #[test]
+    fn test_build_prompt_numbers_sources() {
+        let sources: Vec<(Content, String)> = sources()
+            .into_iter()
+            .take(2)
+            .map(|content| {
+                let text = format!("About {}", content.content);
+                (content, text)
+            })
+            .collect();
+
+        let prompt = build_prompt("What can I cook?", &sources);
+        assert!(prompt.contains("[1] Fresh pasta\nTags: cooking\nAbout Fresh pasta\n"));
+        assert!(prompt.contains("[2] Risotto\nTags: \nAbout Risotto\n"));
+        assert!(prompt.ends_with("Question: What can I cook?"));
+    }
+
+    #[test]
+    fn test_citations_map_numbers_to_content() {
+        let sources = sources();
+        let cited = citations("Try bread [3] or pasta [1, 3], see [1].", &sources);
+
+        assert_eq!(cited.len(), 2);
+        assert_eq!(cited[0].number, 3);
+        assert_eq!(cited[0].id, sources[2].id);
+        assert_eq!(cited[0].title, "https://example.com/bread");
+        assert_eq!(cited[1].number, 1);
+        assert_eq!(cited[1].id, sources[0].id);
+    }
+
+    #[test]
+    fn test_citations_ignore_unknown_sources() {
+        let sources = sources();
+        assert!(citations("See [0], [4] and [note].", &sources).is_empty());
+        assert!(citations("No sources at all", &sources).is_empty());
+    }
+}
@@ -0,0 +1,15 @@
+use std::process::exit;
+
+/// Command-line client for a running classify server
+#[tokio::main]
+async fn main() {
+    let result = match classify::cli::parse_args(std::env::args().skip(1)) {
+        Ok(invocation) => classify::cli::run(invocation).await,
+        Err(e) => Err(e),
+    };
+
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        exit(1);
+    }
+}
@@ -1,19 +1,25 @@
 use async_trait::async_trait;
 use reqwest::header::{HeaderMap, HeaderValue, ACCEPT, AUTHORIZATION, CONTENT_TYPE};
 use serde::{Deserialize, Serialize};
-use url::Url;
 
+use crate::classifier::instrumented::record_tokens;
+use crate::classifier::prompt::TagPrompt;
+use crate::classifier::retry::{self, RetryPolicy};
 use crate::classifier::Classifier;
-use crate::{ClassifyError, ClassifyResult};
+use crate::extract::fetch_and_extract;
+use crate::{ClassifyError, ClassifyResult, Usage};
 
-const MAX_TAGS: usize = 5;
-const OPENAI_API_URL: &str = "https://api.openai.com/v1/chat/completions";
+/// The OpenAI API, used unless another OpenAI-compatible server is configured
+pub const OPENAI_BASE_URL: &str = "https://api.openai.com/v1";
 
 pub struct ChatGptClassifier {
     api_key: Option<String>,
     model: String,
+    base_url: String,
     client: reqwest::Client,
     max_prompt_length: usize,
+    prompt: TagPrompt,
+    retry: RetryPolicy,
 }
 
 #[derive(Debug, Serialize)]
@@ -33,6 +39,17 @@ struct Message {
 #[derive(Debug, Deserialize)]
 struct ChatGptResponse {
     choices: Vec<Choice>,
+    #[serde(default)]
+    usage: Option<ChatGptUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatGptUsage {
+    #[serde(default)]
+    prompt_tokens: u64,
+    #[serde(default)]
+    completion_tokens: u64,
+    total_tokens: u64,
 }
 
 #[derive(Debug, Deserialize)]
@@ -50,8 +67,11 @@ impl ChatGptClassifier {
         Ok(Self {
             api_key: api_key.map(String::from),
             model: "gpt-4o-mini".to_string(), // Use GPT-4o-mini by default
+            base_url: OPENAI_BASE_URL.to_string(),
             client: reqwest::Client::new(),
             max_prompt_length,
+            prompt: TagPrompt::default(),
+            retry: RetryPolicy::default(),
         })
     }
 
@@ -65,11 +85,40 @@ impl ChatGptClassifier {
         Ok(classifier)
     }
 
+    /// Call an OpenAI-compatible server such as vLLM, LM Studio or LiteLLM
+    /// instead of OpenAI, e.g. `http://localhost:8000/v1`
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Ask for tags with another prompt or number of tags
+    pub fn with_prompt(mut self, prompt: TagPrompt) -> Self {
+        self.prompt = prompt;
+        self
+    }
+
+    /// Retry calls with another number of attempts or backoff
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    fn endpoint(&self, path: &str) -> String {
+        format!("{}/{}", self.base_url, path)
+    }
+
+    /// OpenAI needs an API key, self-hosted servers often don't
+    fn has_credentials(&self) -> bool {
+        self.api_key.is_some() || self.base_url != OPENAI_BASE_URL
+    }
+
     pub fn truncate_content(&self, content: &str) -> String {
         if content.len() <= self.max_prompt_length {
             content.to_string()
         } else {
-            let truncated = &content[0..self.max_prompt_length];
+            // Cut on a character boundary so multi-byte text can't panic
+            let truncated = &content[..content.floor_char_boundary(self.max_prompt_length)];
             format!(
                 "{}... [content truncated, original length: {}]",
                 truncated,
@@ -79,60 +128,46 @@ impl ChatGptClassifier {
     }
 
     async fn extract_content_from_url(&self, url: &str) -> ClassifyResult<String> {
-        let url =
-            Url::parse(url).map_err(|e| ClassifyError::UrlError(format!("Invalid URL: {}", e)))?;
-
-        let response = self
-            .client
-            .get(url.as_str())
-            .send()
-            .await
-            .map_err(|e| ClassifyError::HttpError(format!("Failed to fetch URL: {}", e)))?;
+        let content = fetch_and_extract(&self.client, url).await?.text;
+        Ok(self.truncate_content(&content))
+    }
 
-        if !response.status().is_success() {
-            return Err(ClassifyError::HttpError(format!(
-                "Failed to fetch URL: HTTP status {}",
-                response.status()
-            )));
+    async fn call_chatgpt_api(&self, content: &str) -> ClassifyResult<Vec<String>> {
+        if !self.has_credentials() {
+            return self.fallback_classification(content).await;
         }
 
-        let content = response.text().await.map_err(|e| {
-            ClassifyError::HttpError(format!("Failed to read response body: {}", e))
-        })?;
+        let truncated_content = self.truncate_content(content);
 
-        Ok(self.truncate_content(&content))
-    }
+        let tags_text = self
+            .send_chat(
+                self.prompt.system_prompt(),
+                self.prompt.user_prompt(&truncated_content),
+                self.prompt.max_tokens(),
+            )
+            .await?;
 
-    async fn call_chatgpt_api(&self, content: &str) -> ClassifyResult<Vec<String>> {
-        let api_key = match &self.api_key {
-            Some(key) => key,
-            None => return self.fallback_classification(content).await,
-        };
+        Ok(self.prompt.parse_tags(&tags_text))
+    }
 
+    /// Send a system and a user message to the chat API and return the reply
+    async fn send_chat(
+        &self,
+        system_prompt: String,
+        user_prompt: String,
+        max_tokens: u32,
+    ) -> ClassifyResult<String> {
         let mut headers = HeaderMap::new();
         headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
         headers.insert(ACCEPT, HeaderValue::from_static("application/json"));
-        headers.insert(
-            AUTHORIZATION,
-            HeaderValue::from_str(&format!("Bearer {}", api_key)).map_err(|e| {
-                ClassifyError::ClassificationError(format!("Invalid API key: {}", e))
-            })?,
-        );
-
-        let truncated_content = self.truncate_content(content);
-
-        let system_prompt = format!(
-            "You are a helpful content tagger that analyzes text and extracts relevant tags. \
-            Provide exactly up to {} descriptive tags that categorize the content. \
-            Return ONLY the tags separated by commas, nothing else. \
-            Tags should be single words or short phrases.",
-            MAX_TAGS
-        );
-
-        let user_prompt = format!(
-            "Please analyze the following content and provide up to {} descriptive tags: \n\n{}",
-            MAX_TAGS, truncated_content
-        );
+        if let Some(api_key) = &self.api_key {
+            headers.insert(
+                AUTHORIZATION,
+                HeaderValue::from_str(&format!("Bearer {}", api_key)).map_err(|e| {
+                    ClassifyError::ClassificationError(format!("Invalid API key: {}", e))
+                })?,
+            );
+        }
 
         let request = ChatGptRequest {
             model: self.model.clone(),
@@ -147,19 +182,16 @@ impl ChatGptClassifier {
                 },
             ],
             temperature: 0.3,
-            max_tokens: 100,
+            max_tokens,
         };
 
-        let response = self
-            .client
-            .post(OPENAI_API_URL)
-            .headers(headers)
-            .json(&request)
-            .send()
-            .await
-            .map_err(|e| {
-                ClassifyError::ClassificationError(format!("Failed to call OpenAI API: {}", e))
-            })?;
+        let response = retry::send(&self.retry, "OpenAI", || {
+            self.client
+                .post(self.endpoint("chat/completions"))
+                .headers(headers.clone())
+                .json(&request)
+        })
+        .await?;
 
         let status = response.status();
 
@@ -185,16 +217,15 @@ impl ChatGptClassifier {
             ));
         }
 
-        let tags_text = chatgpt_response.choices[0].message.content.clone();
-
-        let tags = tags_text
-            .split(',')
-            .map(|tag| tag.trim().to_string())
-            .filter(|tag| !tag.is_empty())
-            .take(MAX_TAGS)
-            .collect();
+        if let Some(usage) = &chatgpt_response.usage {
+            crate::usage::record(Usage {
+                tokens: usage.total_tokens,
+                ..Usage::default()
+            });
+            record_tokens("chatgpt", usage.prompt_tokens, usage.completion_tokens);
+        }
 
-        Ok(tags)
+        Ok(chatgpt_response.choices[0].message.content.clone())
     }
 
     async fn fallback_classification(&self, _content: &str) -> ClassifyResult<Vec<String>> {
@@ -210,6 +241,45 @@ impl Classifier for ChatGptClassifier {
         self.call_chatgpt_api(content).await
     }
 
+    async fn complete(&self, system: &str, prompt: &str) -> ClassifyResult<String> {
+        if !self.has_credentials() {
+            return Err(ClassifyError::ClassificationError(
+                "OPENAI_API_KEY is required to answer prompts".to_string(),
+            ));
+        }
+        self.send_chat(system.to_string(), self.truncate_content(prompt), 1024)
+            .await
+    }
+
+    async fn health_check(&self) -> ClassifyResult<()> {
+        if !self.has_credentials() {
+            return Err(ClassifyError::ClassificationError(
+                "OpenAI API key is required for classification".to_string(),
+            ));
+        }
+
+        // Listing the models checks the key without spending tokens
+        let mut request = self.client.get(self.endpoint("models"));
+        if let Some(api_key) = &self.api_key {
+            request = request.bearer_auth(api_key);
+        }
+        let status = request
+            .send()
+            .await
+            .map_err(|e| {
+                ClassifyError::ClassificationError(format!("Failed to call OpenAI API: {}", e))
+            })?
+            .status();
+
+        if !status.is_success() {
+            return Err(ClassifyError::ClassificationError(format!(
+                "OpenAI API error: HTTP status {}",
+                status
+            )));
+        }
+        Ok(())
+    }
+
     async fn classify_url(&self, url: &str) -> ClassifyResult<Vec<String>> {
         let content = self.extract_content_from_url(url).await?;
         self.classify(&content).await
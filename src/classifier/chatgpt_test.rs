@@ -1,6 +1,13 @@
 use crate::classifier::chatgpt::ChatGptClassifier;
 use crate::classifier::Classifier;
 use crate::ClassifyResult;
+use axum::{
+    http::HeaderMap,
+    routing::{get, post},
+    Json, Router,
+};
+use serde_json::{json, Value};
+use tokio::net::TcpListener;
 
 #[cfg(test)]
 mod tests {
@@ -29,6 +36,61 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_content_truncation_multi_byte() -> ClassifyResult<()> {
+        let classifier = ChatGptClassifier::new(None, 4).unwrap();
+
+        // The limit falls inside the first two-byte "é"
+        let truncated = classifier.truncate_content("caféé au lait");
+
+        assert!(truncated.starts_with("caf..."));
+        assert!(truncated.contains("original length: 15"));
+
+        Ok(())
+    }
+
+    /// Serve canned replies of an OpenAI-compatible server on a local port
+    async fn compatible_server() -> String {
+        let app = Router::new()
+            .route(
+                "/v1/chat/completions",
+                post(
+                    |headers: HeaderMap, Json(request): Json<Value>| async move {
+                        // Self-hosted servers are called without a key when none is set
+                        assert!(headers.get("authorization").is_none());
+                        assert_eq!(request["model"], "llama-3.1-8b");
+                        Json(json!({
+                            "choices": [{"message": {"content": "rust, self-hosting"}}]
+                        }))
+                    },
+                ),
+            )
+            .route("/v1/models", get(|| async { Json(json!({"data": []})) }));
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        format!("http://{}/v1/", address)
+    }
+
+    #[tokio::test]
+    async fn test_classify_with_compatible_endpoint() -> ClassifyResult<()> {
+        let base_url = compatible_server().await;
+        let classifier =
+            ChatGptClassifier::with_model(None, "llama-3.1-8b", 10000)?.with_base_url(&base_url);
+
+        assert_eq!(
+            classifier.classify("Running models at home").await?,
+            vec!["rust", "self-hosting"]
+        );
+        classifier.health_check().await?;
+
+        // OpenAI itself isn't called without a key
+        assert!(create_test_classifier().classify("text").await.is_err());
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_classify_url_validation() -> ClassifyResult<()> {
         let classifier = create_test_classifier();
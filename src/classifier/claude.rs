@@ -1,13 +1,16 @@
 use async_trait::async_trait;
 use reqwest::header::{HeaderMap, HeaderValue, ACCEPT, CONTENT_TYPE};
 use serde::{Deserialize, Serialize};
-use url::Url;
 
+use crate::classifier::instrumented::{record_fallback, record_tokens};
+use crate::classifier::prompt::TagPrompt;
+use crate::classifier::retry::{self, RetryPolicy};
 use crate::classifier::Classifier;
-use crate::{ClassifyError, ClassifyResult};
+use crate::extract::fetch_and_extract;
+use crate::{ClassifyError, ClassifyResult, Usage};
 
-const MAX_TAGS: usize = 5;
 const CLAUDE_API_URL: &str = "https://api.anthropic.com/v1/messages";
+const CLAUDE_MODELS_URL: &str = "https://api.anthropic.com/v1/models";
 
 /// Claude AI-based classifier
 pub struct ClaudeClassifier {
@@ -17,6 +20,10 @@ pub struct ClaudeClassifier {
     client: reqwest::Client,
     /// Maximum prompt length in characters
     max_prompt_length: usize,
+    /// How tags are asked for
+    prompt: TagPrompt,
+    /// How calls are retried while the API is busy
+    retry: RetryPolicy,
 }
 
 #[derive(Debug, Serialize)]
@@ -36,6 +43,14 @@ struct Message {
 #[derive(Debug, Deserialize)]
 struct ClaudeResponse {
     content: Vec<Content>,
+    #[serde(default)]
+    usage: Option<ClaudeUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ClaudeUsage {
+    input_tokens: u64,
+    output_tokens: u64,
 }
 
 #[derive(Debug, Deserialize)]
@@ -52,15 +67,30 @@ impl ClaudeClassifier {
             api_key: api_key.map(String::from),
             client: reqwest::Client::new(),
             max_prompt_length,
+            prompt: TagPrompt::default(),
+            retry: RetryPolicy::default(),
         })
     }
 
+    /// Ask for tags with another prompt or number of tags
+    pub fn with_prompt(mut self, prompt: TagPrompt) -> Self {
+        self.prompt = prompt;
+        self
+    }
+
+    /// Retry calls with another number of attempts or backoff
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
     /// Truncate content to maximum length
     pub fn truncate_content(&self, content: &str) -> String {
         if content.len() <= self.max_prompt_length {
             content.to_string()
         } else {
-            let truncated = &content[0..self.max_prompt_length];
+            // Cut on a character boundary so multi-byte text can't panic
+            let truncated = &content[..content.floor_char_boundary(self.max_prompt_length)];
             format!(
                 "{}... [content truncated, original length: {}]",
                 truncated,
@@ -71,29 +101,7 @@ impl ClaudeClassifier {
 
     /// Extract content from a URL
     async fn extract_content_from_url(&self, url: &str) -> ClassifyResult<String> {
-        // Validate URL
-        let url =
-            Url::parse(url).map_err(|e| ClassifyError::UrlError(format!("Invalid URL: {}", e)))?;
-
-        // Fetch URL content
-        let response = self
-            .client
-            .get(url.as_str())
-            .send()
-            .await
-            .map_err(|e| ClassifyError::HttpError(format!("Failed to fetch URL: {}", e)))?;
-
-        if !response.status().is_success() {
-            return Err(ClassifyError::HttpError(format!(
-                "Failed to fetch URL: HTTP status {}",
-                response.status()
-            )));
-        }
-
-        // Get text content
-        let content = response.text().await.map_err(|e| {
-            ClassifyError::HttpError(format!("Failed to read response body: {}", e))
-        })?;
+        let content = fetch_and_extract(&self.client, url).await?.text;
 
         // Truncate content if needed
         Ok(self.truncate_content(&content))
@@ -104,9 +112,35 @@ impl ClaudeClassifier {
         // Check if API key is available
         let api_key = match &self.api_key {
             Some(key) => key,
-            None => return self.fallback_classification(content).await,
+            None => {
+                record_fallback("claude");
+                return self.fallback_classification(content).await;
+            }
         };
 
+        // Truncate content if needed
+        let truncated_content = self.truncate_content(content);
+
+        let tags_text = self
+            .send_message(
+                api_key,
+                self.prompt.system_prompt(),
+                self.prompt.user_prompt(&truncated_content),
+                self.prompt.max_tokens(),
+            )
+            .await?;
+
+        Ok(self.prompt.parse_tags(&tags_text))
+    }
+
+    /// Send a single message to the Claude API and return the text of the reply
+    async fn send_message(
+        &self,
+        api_key: &str,
+        system_prompt: String,
+        user_prompt: String,
+        max_tokens: u32,
+    ) -> ClassifyResult<String> {
         // Set up headers
         let mut headers = HeaderMap::new();
         headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
@@ -114,32 +148,15 @@ impl ClaudeClassifier {
         headers.insert("anthropic-version", HeaderValue::from_static("2023-06-01"));
         headers.insert(
             "x-api-key",
-            HeaderValue::from_str(&api_key.to_string()).map_err(|e| {
+            HeaderValue::from_str(api_key).map_err(|e| {
                 ClassifyError::ClassificationError(format!("Invalid API key: {}", e))
             })?,
         );
 
-        // Truncate content if needed
-        let truncated_content = self.truncate_content(content);
-
-        // Prepare the prompt
-        let system_prompt = format!(
-            "You are a helpful content tagger that analyzes text and extracts relevant tags. \
-            Provide exactly up to {} descriptive tags that categorize the content. \
-            Return ONLY the tags separated by commas, nothing else. \
-            Tags should be single words or short phrases.",
-            MAX_TAGS
-        );
-
-        let user_prompt = format!(
-            "Please analyze the following content and provide up to {} descriptive tags: \n\n{}",
-            MAX_TAGS, truncated_content
-        );
-
         // Create the request payload
         let request = ClaudeRequest {
             model: "claude-3-haiku-20240307".to_string(),
-            max_tokens: 100,
+            max_tokens,
             messages: vec![Message {
                 role: "user".to_string(),
                 content: user_prompt,
@@ -147,17 +164,14 @@ impl ClaudeClassifier {
             system: system_prompt,
         };
 
-        // Make the API call
-        let response = self
-            .client
-            .post(CLAUDE_API_URL)
-            .headers(headers)
-            .json(&request)
-            .send()
-            .await
-            .map_err(|e| {
-                ClassifyError::ClassificationError(format!("Failed to call Claude API: {}", e))
-            })?;
+        // Make the API call, retried while the API is busy
+        let response = retry::send(&self.retry, "Claude", || {
+            self.client
+                .post(CLAUDE_API_URL)
+                .headers(headers.clone())
+                .json(&request)
+        })
+        .await?;
 
         let status = response.status();
 
@@ -179,8 +193,16 @@ impl ClaudeClassifier {
             ClassifyError::ClassificationError(format!("Failed to parse Claude response: {}", e))
         })?;
 
-        // Extract tags from the response
-        let tags_text = claude_response
+        if let Some(usage) = &claude_response.usage {
+            crate::usage::record(Usage {
+                tokens: usage.input_tokens + usage.output_tokens,
+                ..Usage::default()
+            });
+            record_tokens("claude", usage.input_tokens, usage.output_tokens);
+        }
+
+        // Join the text blocks of the reply
+        let text = claude_response
             .content
             .iter()
             .filter(|content| content.content_type == "text")
@@ -188,15 +210,7 @@ impl ClaudeClassifier {
             .collect::<Vec<_>>()
             .join("");
 
-        // Split tags by comma and clean them up
-        let tags = tags_text
-            .split(',')
-            .map(|tag| tag.trim().to_string())
-            .filter(|tag| !tag.is_empty())
-            .take(MAX_TAGS)
-            .collect();
-
-        Ok(tags)
+        Ok(text)
     }
 
     /// Fallback classification when API key is not available
@@ -233,8 +247,8 @@ impl ClaudeClassifier {
             tags.push("unclassified".to_string());
         }
 
-        // Limit to MAX_TAGS
-        tags.truncate(MAX_TAGS);
+        // Limit to the configured number of tags
+        tags.truncate(self.prompt.max_tags);
 
         Ok(tags)
     }
@@ -246,6 +260,50 @@ impl Classifier for ClaudeClassifier {
         self.call_claude_api(content).await
     }
 
+    async fn complete(&self, system: &str, prompt: &str) -> ClassifyResult<String> {
+        let Some(api_key) = &self.api_key else {
+            return Err(ClassifyError::ClassificationError(
+                "ANTHROPIC_API_KEY is required to answer prompts".to_string(),
+            ));
+        };
+        self.send_message(
+            api_key,
+            system.to_string(),
+            self.truncate_content(prompt),
+            1024,
+        )
+        .await
+    }
+
+    async fn health_check(&self) -> ClassifyResult<()> {
+        let Some(api_key) = &self.api_key else {
+            return Err(ClassifyError::ClassificationError(
+                "No Anthropic API key, classifying with keywords only".to_string(),
+            ));
+        };
+
+        // Listing the models checks the key without spending tokens
+        let status = self
+            .client
+            .get(CLAUDE_MODELS_URL)
+            .header("anthropic-version", "2023-06-01")
+            .header("x-api-key", api_key)
+            .send()
+            .await
+            .map_err(|e| {
+                ClassifyError::ClassificationError(format!("Failed to call Claude API: {}", e))
+            })?
+            .status();
+
+        if !status.is_success() {
+            return Err(ClassifyError::ClassificationError(format!(
+                "Claude API error: HTTP status {}",
+                status
+            )));
+        }
+        Ok(())
+    }
+
     async fn classify_url(&self, url: &str) -> ClassifyResult<Vec<String>> {
         // Extract content from URL
         let content = self.extract_content_from_url(url).await?;
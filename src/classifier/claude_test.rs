@@ -29,6 +29,19 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_content_truncation_multi_byte() -> ClassifyResult<()> {
+        let classifier = ClaudeClassifier::new(None, 4).unwrap();
+
+        // The limit falls inside the first two-byte "é"
+        let truncated = classifier.truncate_content("caféé au lait");
+
+        assert!(truncated.starts_with("caf..."));
+        assert!(truncated.contains("original length: 15"));
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_classify_url_validation() -> ClassifyResult<()> {
         let classifier = create_test_classifier();
@@ -0,0 +1,158 @@
+use async_trait::async_trait;
+use futures::future::join_all;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::warn;
+
+use crate::classifier::Classifier;
+use crate::extract::fetch_and_extract;
+use crate::{ClassifyError, ClassifyResult};
+
+/// Members that must return a tag when `ENSEMBLE_MIN_VOTES` isn't set
+pub const DEFAULT_MIN_VOTES: usize = 2;
+
+/// Classifier asking several classifiers at once and keeping the tags enough
+/// of them agree on
+pub struct EnsembleClassifier {
+    /// Classifiers asked, with the name they're logged by
+    members: Vec<(String, Arc<dyn Classifier>)>,
+    /// Members that must return a tag for it to be kept
+    min_votes: usize,
+    /// Most tags returned
+    max_tags: usize,
+    /// HTTP client fetching URLs once for all members
+    client: reqwest::Client,
+}
+
+/// A tag and the members that returned it
+struct Tally {
+    tag: String,
+    votes: usize,
+}
+
+impl EnsembleClassifier {
+    pub fn new(
+        members: Vec<(String, Arc<dyn Classifier>)>,
+        min_votes: usize,
+        max_tags: usize,
+    ) -> ClassifyResult<Self> {
+        if members.len() < 2 {
+            return Err(ClassifyError::ConfigError(
+                "The ensemble classifier needs at least two classifiers".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            members,
+            min_votes: min_votes.max(1),
+            max_tags,
+            client: reqwest::Client::new(),
+        })
+    }
+
+    /// Tags returned by at least `min_votes` of the answers, most votes first.
+    /// Tags are compared ignoring case and keep the spelling seen first.
+    fn merge(&self, answers: &[Vec<String>]) -> Vec<String> {
+        let mut tallies: Vec<Tally> = Vec::new();
+        let mut positions: HashMap<String, usize> = HashMap::new();
+        for tags in answers {
+            let mut voted = Vec::new();
+            for tag in tags {
+                let key = tag.trim().to_lowercase();
+                if key.is_empty() || voted.contains(&key) {
+                    continue;
+                }
+                match positions.get(&key) {
+                    Some(&position) => tallies[position].votes += 1,
+                    None => {
+                        positions.insert(key.clone(), tallies.len());
+                        tallies.push(Tally {
+                            tag: tag.trim().to_string(),
+                            votes: 1,
+                        });
+                    }
+                }
+                voted.push(key);
+            }
+        }
+
+        // Stable, so tags with as many votes keep the order they were first seen in
+        tallies.sort_by_key(|tally| std::cmp::Reverse(tally.votes));
+        tallies
+            .into_iter()
+            .filter(|tally| tally.votes >= self.min_votes)
+            .take(self.max_tags)
+            .map(|tally| tally.tag)
+            .collect()
+    }
+}
+
+#[async_trait]
+impl Classifier for EnsembleClassifier {
+    async fn classify(&self, content: &str) -> ClassifyResult<Vec<String>> {
+        let results = join_all(
+            self.members
+                .iter()
+                .map(|(_, classifier)| classifier.classify(content)),
+        )
+        .await;
+
+        let mut answers = Vec::new();
+        let mut last_error = None;
+        for ((name, _), result) in self.members.iter().zip(results) {
+            match result {
+                Ok(tags) => answers.push(tags),
+                Err(e) => {
+                    warn!("Ensemble member {} failed to classify: {}", name, e);
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        // Fewer answers than votes needed can't agree on any tag
+        if answers.len() < self.min_votes {
+            return Err(ClassifyError::ClassificationError(format!(
+                "Only {} of {} ensemble classifiers answered, {} are needed{}",
+                answers.len(),
+                self.members.len(),
+                self.min_votes,
+                last_error.map_or(String::new(), |e| format!(": {}", e))
+            )));
+        }
+
+        Ok(self.merge(&answers))
+    }
+
+    async fn classify_url(&self, url: &str) -> ClassifyResult<Vec<String>> {
+        let content = fetch_and_extract(&self.client, url).await?.text;
+        self.classify(&content).await
+    }
+
+    async fn complete(&self, system: &str, prompt: &str) -> ClassifyResult<String> {
+        // The first member that supports free-form prompts answers
+        let mut last_error = None;
+        for (_, classifier) in &self.members {
+            match classifier.complete(system, prompt).await {
+                Ok(reply) => return Ok(reply),
+                Err(e) => last_error = Some(e),
+            }
+        }
+        Err(last_error.expect("an ensemble has members"))
+    }
+
+    async fn health_check(&self) -> ClassifyResult<()> {
+        let results = join_all(
+            self.members
+                .iter()
+                .map(|(_, classifier)| classifier.health_check()),
+        )
+        .await;
+
+        for ((name, _), result) in self.members.iter().zip(results) {
+            result.map_err(|e| {
+                ClassifyError::ClassificationError(format!("Ensemble member {}: {}", name, e))
+            })?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,89 @@
+use crate::classifier::ensemble::EnsembleClassifier;
+use crate::classifier::Classifier;
+use crate::{ClassifyError, ClassifyResult};
+use async_trait::async_trait;
+use std::sync::Arc;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Classifier returning the same tags for everything, or failing without any
+    struct FixedClassifier(Option<Vec<&'static str>>);
+
+    #[async_trait]
+    impl Classifier for FixedClassifier {
+        async fn classify(&self, _content: &str) -> ClassifyResult<Vec<String>> {
+            match &self.0 {
+                Some(tags) => Ok(tags.iter().map(|tag| tag.to_string()).collect()),
+                None => Err(ClassifyError::ClassificationError("Timeout".to_string())),
+            }
+        }
+
+        async fn classify_url(&self, _url: &str) -> ClassifyResult<Vec<String>> {
+            unimplemented!()
+        }
+    }
+
+    fn ensemble(
+        members: Vec<Option<Vec<&'static str>>>,
+        min_votes: usize,
+    ) -> ClassifyResult<EnsembleClassifier> {
+        EnsembleClassifier::new(
+            members
+                .into_iter()
+                .enumerate()
+                .map(|(i, tags)| {
+                    (
+                        format!("member{}", i),
+                        Arc::new(FixedClassifier(tags)) as Arc<dyn Classifier>,
+                    )
+                })
+                .collect(),
+            min_votes,
+            5,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_tags_are_kept_by_vote() -> ClassifyResult<()> {
+        let classifier = ensemble(
+            vec![
+                Some(vec!["rust", "programming", "async"]),
+                Some(vec!["Programming", "Rust", "tokio"]),
+                Some(vec!["programming", "web"]),
+            ],
+            2,
+        )?;
+
+        // Most votes first, spelled as first returned
+        assert_eq!(
+            classifier.classify("Some text").await?,
+            vec!["programming".to_string(), "rust".to_string()]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_too_few_answers_fail() -> ClassifyResult<()> {
+        // One provider down leaves nothing to agree with
+        let classifier = ensemble(vec![Some(vec!["rust", "async"]), None], 2)?;
+        assert!(classifier.classify("Some text").await.is_err());
+
+        // Members that answered still need to agree on a tag
+        let classifier = ensemble(
+            vec![Some(vec!["rust", "async"]), Some(vec!["rust"]), None],
+            2,
+        )?;
+        assert_eq!(
+            classifier.classify("Some text").await?,
+            vec!["rust".to_string()]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_single_member_is_rejected() {
+        assert!(ensemble(vec![Some(vec!["rust"])], 1).is_err());
+    }
+}
@@ -0,0 +1,78 @@
+use async_trait::async_trait;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::classifier::Classifier;
+use crate::ClassifyResult;
+
+/// Count the tokens a classifier's provider billed for a call
+pub fn record_tokens(classifier: &'static str, input: u64, output: u64) {
+    metrics::counter!("classifier_tokens_total", "classifier" => classifier, "direction" => "input")
+        .increment(input);
+    metrics::counter!("classifier_tokens_total", "classifier" => classifier, "direction" => "output")
+        .increment(output);
+}
+
+/// Count a classification done without the provider, e.g. for a missing API key
+pub fn record_fallback(classifier: &'static str) {
+    metrics::counter!("classifier_fallbacks_total", "classifier" => classifier).increment(1);
+}
+
+/// Classifier that records the count, latency and errors of its calls
+pub struct InstrumentedClassifier {
+    inner: Arc<dyn Classifier>,
+    name: String,
+}
+
+impl InstrumentedClassifier {
+    pub fn new(inner: Arc<dyn Classifier>, name: &str) -> Self {
+        Self {
+            inner,
+            name: name.to_string(),
+        }
+    }
+
+    async fn measure<T>(
+        &self,
+        operation: &'static str,
+        future: impl Future<Output = ClassifyResult<T>>,
+    ) -> ClassifyResult<T> {
+        let started = Instant::now();
+        let result = future.await;
+
+        let labels = [
+            ("classifier", self.name.clone()),
+            ("operation", operation.to_string()),
+        ];
+        metrics::counter!("classifier_requests_total", &labels).increment(1);
+        metrics::histogram!("classifier_request_seconds", &labels)
+            .record(started.elapsed().as_secs_f64());
+        if result.is_err() {
+            metrics::counter!("classifier_errors_total", &labels).increment(1);
+        }
+
+        result
+    }
+}
+
+#[async_trait]
+impl Classifier for InstrumentedClassifier {
+    async fn classify(&self, content: &str) -> ClassifyResult<Vec<String>> {
+        self.measure("classify", self.inner.classify(content)).await
+    }
+
+    async fn classify_url(&self, url: &str) -> ClassifyResult<Vec<String>> {
+        self.measure("classify_url", self.inner.classify_url(url))
+            .await
+    }
+
+    async fn complete(&self, system: &str, prompt: &str) -> ClassifyResult<String> {
+        self.measure("complete", self.inner.complete(system, prompt))
+            .await
+    }
+
+    async fn health_check(&self) -> ClassifyResult<()> {
+        self.inner.health_check().await
+    }
+}
@@ -0,0 +1,49 @@
+use crate::classifier::instrumented::InstrumentedClassifier;
+use crate::classifier::Classifier;
+use crate::{ClassifyError, ClassifyResult};
+use async_trait::async_trait;
+use metrics_exporter_prometheus::PrometheusBuilder;
+use std::sync::Arc;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FlakyClassifier;
+
+    #[async_trait]
+    impl Classifier for FlakyClassifier {
+        async fn classify(&self, _content: &str) -> ClassifyResult<Vec<String>> {
+            Ok(vec!["rust".to_string()])
+        }
+
+        async fn classify_url(&self, _url: &str) -> ClassifyResult<Vec<String>> {
+            Err(ClassifyError::ClassificationError("Timeout".to_string()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_calls_and_errors_are_counted() {
+        let recorder = PrometheusBuilder::new().build_recorder();
+        let handle = recorder.handle();
+        let _guard = metrics::set_default_local_recorder(&recorder);
+
+        let classifier = InstrumentedClassifier::new(Arc::new(FlakyClassifier), "flaky");
+        assert_eq!(
+            classifier.classify("Some text").await.unwrap(),
+            vec!["rust".to_string()]
+        );
+        assert!(classifier
+            .classify_url("https://example.com")
+            .await
+            .is_err());
+
+        let rendered = handle.render();
+        assert!(rendered
+            .contains(r#"classifier_requests_total{classifier="flaky",operation="classify"} 1"#));
+        assert!(rendered
+            .contains(r#"classifier_errors_total{classifier="flaky",operation="classify_url"} 1"#));
+        assert!(!rendered
+            .contains(r#"classifier_errors_total{classifier="flaky",operation="classify"}"#));
+    }
+}
@@ -1,11 +1,25 @@
+#[cfg(feature = "chatgpt")]
 pub mod chatgpt;
+#[cfg(feature = "claude")]
 pub mod claude;
+pub mod ensemble;
+pub mod instrumented;
+pub mod prompt;
+pub mod retry;
 
-#[cfg(test)]
+#[cfg(all(test, feature = "claude"))]
 mod claude_test;
 
-#[cfg(test)]
+#[cfg(all(test, feature = "chatgpt"))]
 mod chatgpt_test;
+#[cfg(test)]
+mod ensemble_test;
+#[cfg(test)]
+mod instrumented_test;
+#[cfg(test)]
+mod prompt_test;
+#[cfg(test)]
+mod retry_test;
 
 use crate::ClassifyResult;
 use async_trait::async_trait;
@@ -16,6 +30,21 @@ use std::sync::Arc;
 pub trait Classifier: Send + Sync {
     async fn classify(&self, content: &str) -> ClassifyResult<Vec<String>>;
     async fn classify_url(&self, url: &str) -> ClassifyResult<Vec<String>>;
+
+    /// Free-form reply to `prompt`, following the instructions in `system`,
+    /// for features beyond tagging like answering questions. Classifiers that
+    /// only produce tags don't support it.
+    async fn complete(&self, _system: &str, _prompt: &str) -> ClassifyResult<String> {
+        Err(crate::ClassifyError::ClassificationError(
+            "The classifier doesn't support free-form prompts".to_string(),
+        ))
+    }
+
+    /// Check that the provider can be reached with the configured credentials,
+    /// without classifying anything
+    async fn health_check(&self) -> ClassifyResult<()> {
+        Ok(())
+    }
 }
 
 /// Classifier factory
@@ -24,28 +53,66 @@ pub async fn create_classifier(
     config: &crate::config::ClassifierConfig,
 ) -> ClassifyResult<Arc<dyn Classifier>> {
     match classifier_type {
+        #[cfg(feature = "claude")]
         crate::config::ClassifierType::Claude => {
             let classifier = claude::ClaudeClassifier::new(
                 config.anthropic_api_key.as_deref(),
                 config.max_prompt_length,
-            )?;
+            )?
+            .with_prompt(prompt::TagPrompt::from_config(config))
+            .with_retry(config.retry);
             Ok(Arc::new(classifier))
         }
+        #[cfg(feature = "chatgpt")]
         crate::config::ClassifierType::ChatGpt => {
-            if let Some(model) = &config.openai_model {
-                let classifier = chatgpt::ChatGptClassifier::with_model(
+            let mut classifier = if let Some(model) = &config.openai_model {
+                chatgpt::ChatGptClassifier::with_model(
                     config.openai_api_key.as_deref(),
                     model,
                     config.max_prompt_length,
-                )?;
-                Ok(Arc::new(classifier))
+                )?
             } else {
-                let classifier = chatgpt::ChatGptClassifier::new(
+                chatgpt::ChatGptClassifier::new(
                     config.openai_api_key.as_deref(),
                     config.max_prompt_length,
-                )?;
-                Ok(Arc::new(classifier))
+                )?
+            };
+            if let Some(base_url) = &config.openai_base_url {
+                classifier = classifier.with_base_url(base_url);
             }
+            Ok(Arc::new(
+                classifier
+                    .with_prompt(prompt::TagPrompt::from_config(config))
+                    .with_retry(config.retry),
+            ))
+        }
+        #[cfg(not(feature = "claude"))]
+        crate::config::ClassifierType::Claude => Err(crate::ClassifyError::ConfigError(
+            "The Claude classifier requires building with the claude feature".to_string(),
+        )),
+        #[cfg(not(feature = "chatgpt"))]
+        crate::config::ClassifierType::ChatGpt => Err(crate::ClassifyError::ConfigError(
+            "The ChatGPT classifier requires building with the chatgpt feature".to_string(),
+        )),
+        crate::config::ClassifierType::Ensemble => {
+            let mut members = Vec::new();
+            for member in &config.ensemble_classifiers {
+                if *member == crate::config::ClassifierType::Ensemble {
+                    return Err(crate::ClassifyError::ConfigError(
+                        "An ensemble classifier can't be a member of itself".to_string(),
+                    ));
+                }
+                let classifier = Box::pin(create_classifier(member, config)).await?;
+                members.push((member.name(), classifier));
+            }
+            Ok(Arc::new(ensemble::EnsembleClassifier::new(
+                members,
+                config.ensemble_min_votes,
+                config.max_tags,
+            )?))
+        }
+        crate::config::ClassifierType::Custom(name) => {
+            crate::registry::create_classifier(name, config).await
         }
     }
 }
@@ -0,0 +1,75 @@
+use crate::config::ClassifierConfig;
+
+/// Number of tags asked for when `CLASSIFIER_MAX_TAGS` isn't set
+pub const DEFAULT_MAX_TAGS: usize = 5;
+
+/// System prompt used when `CLASSIFIER_SYSTEM_PROMPT` isn't set
+pub const DEFAULT_SYSTEM_PROMPT: &str =
+    "You are a helpful content tagger that analyzes text and extracts relevant tags. \
+    Provide exactly up to {max_tags} descriptive tags that categorize the content. \
+    Return ONLY the tags separated by commas, nothing else. \
+    Tags should be single words or short phrases.";
+
+/// Placeholder in the system prompt replaced by the number of tags
+pub const MAX_TAGS_PLACEHOLDER: &str = "{max_tags}";
+
+/// Reply tokens allowed per requested tag, enough for a short phrase and a comma
+const TOKENS_PER_TAG: u32 = 20;
+
+/// How the LLM classifiers ask for tags and read the reply
+#[derive(Debug, Clone, PartialEq)]
+pub struct TagPrompt {
+    pub max_tags: usize,
+    /// System prompt, with `{max_tags}` where the number of tags goes
+    pub system_prompt_template: String,
+}
+
+impl Default for TagPrompt {
+    fn default() -> Self {
+        Self {
+            max_tags: DEFAULT_MAX_TAGS,
+            system_prompt_template: DEFAULT_SYSTEM_PROMPT.to_string(),
+        }
+    }
+}
+
+impl TagPrompt {
+    pub fn from_config(config: &ClassifierConfig) -> Self {
+        Self {
+            max_tags: config.max_tags,
+            system_prompt_template: config
+                .system_prompt_template
+                .clone()
+                .unwrap_or_else(|| DEFAULT_SYSTEM_PROMPT.to_string()),
+        }
+    }
+
+    pub fn system_prompt(&self) -> String {
+        self.system_prompt_template
+            .replace(MAX_TAGS_PLACEHOLDER, &self.max_tags.to_string())
+    }
+
+    pub fn user_prompt(&self, content: &str) -> String {
+        format!(
+            "Please analyze the following content and provide up to {} descriptive tags: \n\n{}",
+            self.max_tags, content
+        )
+    }
+
+    /// Tokens the reply may use, at least the 100 the prompt was tuned with
+    pub fn max_tokens(&self) -> u32 {
+        (self.max_tags as u32)
+            .saturating_mul(TOKENS_PER_TAG)
+            .max(100)
+    }
+
+    /// The comma separated tags of a reply, at most `max_tags` of them
+    pub fn parse_tags(&self, reply: &str) -> Vec<String> {
+        reply
+            .split(',')
+            .map(|tag| tag.trim().to_string())
+            .filter(|tag| !tag.is_empty())
+            .take(self.max_tags)
+            .collect()
+    }
+}
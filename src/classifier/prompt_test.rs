@@ -0,0 +1,41 @@
+use crate::classifier::prompt::{TagPrompt, DEFAULT_MAX_TAGS};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_prompt() {
+        let prompt = TagPrompt::default();
+
+        assert_eq!(prompt.max_tags, DEFAULT_MAX_TAGS);
+        assert!(prompt.system_prompt().contains("up to 5 descriptive tags"));
+        assert!(!prompt.system_prompt().contains("{max_tags}"));
+        assert_eq!(prompt.max_tokens(), 100);
+    }
+
+    #[test]
+    fn test_template_and_tag_count() {
+        let prompt = TagPrompt {
+            max_tags: 8,
+            system_prompt_template:
+                "Tag recipes with at most {max_tags} ingredients and cuisines, comma separated."
+                    .to_string(),
+        };
+
+        assert_eq!(
+            prompt.system_prompt(),
+            "Tag recipes with at most 8 ingredients and cuisines, comma separated."
+        );
+        assert!(prompt
+            .user_prompt("Pasta")
+            .contains("up to 8 descriptive tags"));
+        assert_eq!(prompt.max_tokens(), 160);
+
+        let reply = "pasta, italian, , tomato, basil, garlic, olive oil, parmesan, dinner, quick";
+        let tags = prompt.parse_tags(reply);
+        assert_eq!(tags.len(), 8);
+        assert_eq!(tags[0], "pasta");
+        assert_eq!(tags[7], "dinner");
+    }
+}
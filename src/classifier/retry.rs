@@ -0,0 +1,137 @@
+use reqwest::header::RETRY_AFTER;
+use reqwest::{RequestBuilder, Response, StatusCode};
+use serde::Deserialize;
+use std::time::Duration;
+use tracing::warn;
+
+use crate::{ClassifyError, ClassifyResult};
+
+/// Attempts of a provider call when `CLASSIFIER_MAX_ATTEMPTS` isn't set
+pub const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+
+/// Wait before the first retry when `CLASSIFIER_RETRY_BACKOFF_MS` isn't set
+pub const DEFAULT_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Longest wait between attempts. A provider asking to wait longer is not retried.
+const MAX_DELAY: Duration = Duration::from_secs(30);
+
+/// Anthropic's status for an overloaded API
+const OVERLOADED: u16 = 529;
+
+/// How provider calls are retried when the provider is busy
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct RetryPolicy {
+    /// Attempts in total, 1 doesn't retry
+    pub max_attempts: u32,
+    /// Wait before the first retry, doubled for every next one
+    pub backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            backoff: DEFAULT_BACKOFF,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Wait before retrying after `attempt` failed attempts: the `Retry-After`
+    /// of the provider when it sent one, otherwise the backoff doubled for
+    /// every earlier failure, with up to half of it left out at random so
+    /// clients that failed together don't retry together
+    pub fn delay(&self, attempt: u32, retry_after: Option<Duration>) -> Duration {
+        if let Some(retry_after) = retry_after {
+            return retry_after;
+        }
+
+        let delay = self
+            .backoff
+            .saturating_mul(1 << attempt.saturating_sub(1).min(10))
+            .min(MAX_DELAY);
+        delay.mul_f64(1.0 - jitter() / 2.0)
+    }
+}
+
+/// Random fraction between 0 and 1
+fn jitter() -> f64 {
+    (uuid::Uuid::new_v4().as_u128() as u32) as f64 / u32::MAX as f64
+}
+
+/// Whether the provider may succeed when asked again
+fn is_retryable(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS
+        || status == StatusCode::REQUEST_TIMEOUT
+        || status.is_server_error()
+}
+
+fn is_rate_limited(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.as_u16() == OVERLOADED
+}
+
+/// The `Retry-After` of a response, in seconds. Dates aren't sent by the
+/// providers and are ignored, as are waits too long for a `Duration`.
+pub fn retry_after(response: &Response) -> Option<Duration> {
+    response
+        .headers()
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+}
+
+/// Send the request built by `request`, retrying while `provider` is busy or
+/// can't be reached. A provider that still limits the rate after the last
+/// attempt, or asks to wait longer than retrying is worth, fails with
+/// [`ClassifyError::RateLimited`]. Other failed responses are returned for the
+/// caller to report.
+pub async fn send<F>(policy: &RetryPolicy, provider: &str, request: F) -> ClassifyResult<Response>
+where
+    F: Fn() -> RequestBuilder,
+{
+    let mut attempt = 1;
+    loop {
+        let last = attempt >= policy.max_attempts;
+        let (retry_after, problem) = match request().send().await {
+            Ok(response) if !is_retryable(response.status()) => return Ok(response),
+            Ok(response) => {
+                let status = response.status();
+                let retry_after = retry_after(&response);
+                let gives_up = last || retry_after.is_some_and(|wait| wait > MAX_DELAY);
+                if gives_up && is_rate_limited(status) {
+                    return Err(ClassifyError::RateLimited(
+                        provider.to_string(),
+                        retry_after,
+                    ));
+                }
+                if gives_up {
+                    return Ok(response);
+                }
+                (retry_after, format!("HTTP status {}", status))
+            }
+            Err(e) if last => {
+                return Err(ClassifyError::ClassificationError(format!(
+                    "Failed to call {} API: {}",
+                    provider, e
+                )))
+            }
+            Err(e) => (None, e.to_string()),
+        };
+
+        let delay = policy.delay(attempt, retry_after);
+        warn!(
+            "{} API call failed ({}), retrying in {}ms, attempt {} of {}",
+            provider,
+            problem,
+            delay.as_millis(),
+            attempt + 1,
+            policy.max_attempts
+        );
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
@@ -0,0 +1,123 @@
+use crate::classifier::retry::{send, RetryPolicy};
+use crate::ClassifyError;
+use axum::{http::StatusCode, response::IntoResponse, routing::get, Router};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpListener;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            backoff: Duration::from_millis(1),
+        }
+    }
+
+    /// Serve `failures` responses with `status` and `retry_after`, then succeed,
+    /// returning the URL and the number of requests served
+    async fn flaky_server(
+        failures: u32,
+        status: StatusCode,
+        retry_after: &'static str,
+    ) -> (String, Arc<AtomicU32>) {
+        let calls = Arc::new(AtomicU32::new(0));
+        let served = calls.clone();
+        let app = Router::new().route(
+            "/",
+            get(move || {
+                let served = served.clone();
+                async move {
+                    if served.fetch_add(1, Ordering::SeqCst) < failures {
+                        (status, [("retry-after", retry_after)], "busy").into_response()
+                    } else {
+                        "ok".into_response()
+                    }
+                }
+            }),
+        );
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        (format!("http://{}/", address), calls)
+    }
+
+    #[test]
+    fn test_delay_doubles_with_jitter_or_follows_retry_after() {
+        let policy = RetryPolicy {
+            max_attempts: 5,
+            backoff: Duration::from_millis(100),
+        };
+
+        for (attempt, full) in [(1, 100), (2, 200), (3, 400)] {
+            let delay = policy.delay(attempt, None);
+            assert!(delay <= Duration::from_millis(full));
+            assert!(delay >= Duration::from_millis(full / 2));
+        }
+        // Capped however many attempts failed
+        assert!(policy.delay(30, None) <= Duration::from_secs(30));
+
+        assert_eq!(
+            policy.delay(1, Some(Duration::from_secs(2))),
+            Duration::from_secs(2)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_busy_provider_is_retried_until_it_answers() {
+        let client = reqwest::Client::new();
+        let (url, calls) = flaky_server(2, StatusCode::TOO_MANY_REQUESTS, "0").await;
+
+        let response = send(&policy(3), "Test", || client.get(&url)).await.unwrap();
+
+        assert_eq!(response.status().as_u16(), 200);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_after_last_attempt_is_an_error() {
+        let client = reqwest::Client::new();
+        let (url, calls) = flaky_server(5, StatusCode::from_u16(529).unwrap(), "0.01").await;
+
+        let result = send(&policy(2), "Test", || client.get(&url)).await;
+
+        assert!(matches!(
+            result,
+            Err(ClassifyError::RateLimited(provider, Some(_))) if provider == "Test"
+        ));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_long_retry_after_and_client_errors_are_not_retried() {
+        let client = reqwest::Client::new();
+
+        let (url, calls) = flaky_server(5, StatusCode::TOO_MANY_REQUESTS, "3600").await;
+        let result = send(&policy(3), "Test", || client.get(&url)).await;
+        assert!(matches!(
+            result,
+            Err(ClassifyError::RateLimited(_, Some(wait))) if wait == Duration::from_secs(3600)
+        ));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        let (url, calls) = flaky_server(5, StatusCode::UNAUTHORIZED, "0").await;
+        let response = send(&policy(3), "Test", || client.get(&url)).await.unwrap();
+        assert_eq!(response.status().as_u16(), 401);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_retry_after_beyond_a_duration_is_ignored() {
+        let client = reqwest::Client::new();
+        let (url, calls) = flaky_server(1, StatusCode::SERVICE_UNAVAILABLE, "1e300").await;
+
+        let response = send(&policy(3), "Test", || client.get(&url)).await.unwrap();
+
+        assert_eq!(response.status().as_u16(), 200);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+}
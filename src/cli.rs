@@ -0,0 +1,429 @@
+use reqwest::{Method, StatusCode};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use crate::api::DeleteResponse;
+use crate::queue::ClassificationJob;
+use crate::{
+    ClassifyError, ClassifyRequest, ClassifyResponse, ClassifyResult, ContentQueryResponse,
+    TagsResponse,
+};
+
+/// Server used when neither `--url`, `CLASSIFY_URL` nor the config file set one
+const DEFAULT_URL: &str = "http://127.0.0.1:3000";
+
+pub const USAGE: &str = "Usage: classify-cli [--url <url>] [--api-key <key>] [--json] <command>
+
+Commands:
+  classify <text|url|file|->   Classify text, a URL, a file or standard input
+      --meta <key=value>       Store metadata with the content, repeatable
+      --async                  Queue the classification and print the job
+  query --tags <a,b>           List content with any of the tags
+      --all                    Only content with all of the tags
+      --metadata <key:value>   Only content with this metadata, comma separated
+  tags                         List all tags
+  delete <id>                  Delete content
+  export [--output <file>]     Write all content as JSON Lines
+
+The server and API key are taken from --url and --api-key, then CLASSIFY_URL
+and CLASSIFY_API_KEY, then `url` and `api_key` in ~/.config/classify/cli.toml
+or the file in CLASSIFY_CLI_CONFIG.";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Classify {
+        /// Text, URL or file path, `-` for standard input
+        input: String,
+        metadata: HashMap<String, String>,
+        run_async: bool,
+    },
+    Query {
+        tags: String,
+        metadata: Option<String>,
+        /// Only content with all of the tags instead of any of them
+        all: bool,
+    },
+    Tags,
+    Delete {
+        id: String,
+    },
+    Export {
+        /// File to write to, standard output when unset
+        output: Option<String>,
+    },
+    Help,
+}
+
+/// A command with the options given before it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Invocation {
+    pub command: Command,
+    pub url: Option<String>,
+    pub api_key: Option<String>,
+    /// Print the responses of the server as they are
+    pub json: bool,
+}
+
+fn usage_error(message: impl Into<String>) -> ClassifyError {
+    ClassifyError::ConfigError(format!("{}\n\n{}", message.into(), USAGE))
+}
+
+/// The value of an option, from `--name value` or `--name=value`
+fn option_value(
+    arg: &str,
+    name: &str,
+    args: &mut impl Iterator<Item = String>,
+) -> ClassifyResult<Option<String>> {
+    if arg == name {
+        return args
+            .next()
+            .map(Some)
+            .ok_or_else(|| usage_error(format!("{} needs a value", name)));
+    }
+    Ok(arg
+        .strip_prefix(name)
+        .and_then(|rest| rest.strip_prefix('='))
+        .map(str::to_string))
+}
+
+/// Parse the arguments after the program name
+pub fn parse_args(args: impl IntoIterator<Item = String>) -> ClassifyResult<Invocation> {
+    let mut args = args.into_iter();
+    let mut url = None;
+    let mut api_key = None;
+    let mut json = false;
+
+    let command = loop {
+        let Some(arg) = args.next() else {
+            break "help".to_string();
+        };
+        if arg == "--json" {
+            json = true;
+        } else if let Some(value) = option_value(&arg, "--url", &mut args)? {
+            url = Some(value);
+        } else if let Some(value) = option_value(&arg, "--api-key", &mut args)? {
+            api_key = Some(value);
+        } else {
+            break arg;
+        }
+    };
+
+    let mut words = Vec::new();
+    let mut metadata = HashMap::new();
+    let mut run_async = false;
+    let mut all = false;
+    let mut tags = None;
+    let mut metadata_filter = None;
+    let mut output = None;
+
+    while let Some(arg) = args.next() {
+        if command == "classify" && arg == "--async" {
+            run_async = true;
+        } else if command == "query" && arg == "--all" {
+            all = true;
+        } else if let Some(pair) = option_value(&arg, "--meta", &mut args)? {
+            let (key, value) = pair
+                .split_once('=')
+                .ok_or_else(|| usage_error(format!("--meta needs key=value, got {}", pair)))?;
+            metadata.insert(key.to_string(), value.to_string());
+        } else if let Some(value) = option_value(&arg, "--tags", &mut args)? {
+            tags = Some(value);
+        } else if let Some(value) = option_value(&arg, "--metadata", &mut args)? {
+            metadata_filter = Some(value);
+        } else if let Some(value) = option_value(&arg, "--output", &mut args)? {
+            output = Some(value);
+        } else if arg == "--json" {
+            json = true;
+        } else if arg.starts_with("--") {
+            return Err(usage_error(format!("Unknown option {}", arg)));
+        } else {
+            words.push(arg);
+        }
+    }
+
+    let command = match command.as_str() {
+        "classify" if !words.is_empty() => Command::Classify {
+            // Unquoted text arrives as separate words
+            input: words.join(" "),
+            metadata,
+            run_async,
+        },
+        "classify" => return Err(usage_error("classify needs text, a URL, a file or -")),
+        "query" => Command::Query {
+            tags: tags.ok_or_else(|| usage_error("query needs --tags"))?,
+            metadata: metadata_filter,
+            all,
+        },
+        "tags" => Command::Tags,
+        "delete" => match words.as_slice() {
+            [id] => Command::Delete { id: id.clone() },
+            _ => return Err(usage_error("delete needs the id of the content")),
+        },
+        "export" => Command::Export { output },
+        "help" | "--help" | "-h" => Command::Help,
+        other => return Err(usage_error(format!("Unknown command {}", other))),
+    };
+
+    Ok(Invocation {
+        command,
+        url,
+        api_key,
+        json,
+    })
+}
+
+/// Content to classify: standard input for `-`, the contents of an existing
+/// file, otherwise the text or URL itself
+pub fn read_input(input: &str) -> ClassifyResult<String> {
+    if input == "-" {
+        let mut text = String::new();
+        std::io::stdin().read_to_string(&mut text)?;
+        return Ok(text);
+    }
+
+    let path = Path::new(input);
+    if !crate::Content::looks_like_url(input) && path.is_file() {
+        return Ok(std::fs::read_to_string(path)?);
+    }
+
+    Ok(input.to_string())
+}
+
+/// Where the client connects to
+#[derive(Debug, Clone, PartialEq)]
+pub struct CliConfig {
+    pub url: String,
+    pub api_key: Option<String>,
+}
+
+impl CliConfig {
+    /// Settings of the invocation, falling back to the environment and then to
+    /// the config file
+    pub fn load(invocation: &Invocation) -> ClassifyResult<Self> {
+        let file = match config_file() {
+            Some(path) => crate::config::file::read(&path)?,
+            None => Default::default(),
+        };
+        let setting = |given: &Option<String>, var: &str, key: &str| {
+            given
+                .clone()
+                .or_else(|| std::env::var(var).ok())
+                .or_else(|| file.get(key).cloned())
+                .filter(|value| !value.is_empty())
+        };
+
+        Ok(Self {
+            url: setting(&invocation.url, "CLASSIFY_URL", "URL")
+                .unwrap_or_else(|| DEFAULT_URL.to_string())
+                .trim_end_matches('/')
+                .to_string(),
+            api_key: setting(&invocation.api_key, "CLASSIFY_API_KEY", "API_KEY"),
+        })
+    }
+}
+
+/// `CLASSIFY_CLI_CONFIG`, or `~/.config/classify/cli.toml` when it exists
+fn config_file() -> Option<PathBuf> {
+    if let Ok(path) = std::env::var("CLASSIFY_CLI_CONFIG") {
+        return Some(PathBuf::from(path));
+    }
+
+    let path = PathBuf::from(std::env::var("HOME").ok()?).join(".config/classify/cli.toml");
+    path.is_file().then_some(path)
+}
+
+/// Client for the API of a running server
+pub struct Client {
+    http: reqwest::Client,
+    config: CliConfig,
+}
+
+impl Client {
+    pub fn new(config: CliConfig) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            config,
+        }
+    }
+
+    /// Send a request and return the JSON body, failing with the error the
+    /// server gave for unsuccessful responses
+    async fn send(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(&str, &str)],
+        body: Option<&ClassifyRequest>,
+    ) -> ClassifyResult<(StatusCode, Value)> {
+        let mut request = self
+            .http
+            .request(method, format!("{}{}", self.config.url, path))
+            .query(query);
+        if let Some(api_key) = &self.config.api_key {
+            request = request.header("X-Api-Key", api_key);
+        }
+        if let Some(body) = body {
+            request = request.json(body);
+        }
+
+        let response = request.send().await.map_err(|e| {
+            ClassifyError::HttpError(format!("Failed to reach {}: {}", self.config.url, e))
+        })?;
+        let status = response.status();
+        let text = response
+            .text()
+            .await
+            .map_err(|e| ClassifyError::HttpError(format!("Failed to read the response: {}", e)))?;
+        let body: Value = serde_json::from_str(&text).unwrap_or(Value::String(text));
+
+        if !status.is_success() {
+            let error = match &body {
+                Value::String(text) if !text.is_empty() => text.clone(),
+                body => match body.get("error").and_then(Value::as_str) {
+                    Some(error) => error.to_string(),
+                    None => return Err(ClassifyError::ApiError(status.to_string())),
+                },
+            };
+            return Err(ClassifyError::ApiError(format!("{} ({})", error, status)));
+        }
+        Ok((status, body))
+    }
+
+    pub async fn classify(
+        &self,
+        content: String,
+        metadata: HashMap<String, String>,
+        run_async: bool,
+    ) -> ClassifyResult<(StatusCode, Value)> {
+        let query: &[(&str, &str)] = if run_async { &[("async", "true")] } else { &[] };
+        let request = ClassifyRequest { content, metadata };
+        self.send(Method::POST, "/classify", query, Some(&request))
+            .await
+    }
+
+    pub async fn query(
+        &self,
+        tags: &str,
+        metadata: Option<&str>,
+        all: bool,
+    ) -> ClassifyResult<Value> {
+        let mut query = vec![("tags", tags)];
+        if all {
+            query.push(("mode", "all"));
+        }
+        if let Some(metadata) = metadata {
+            query.push(("metadata", metadata));
+        }
+        Ok(self.send(Method::GET, "/query", &query, None).await?.1)
+    }
+
+    pub async fn tags(&self) -> ClassifyResult<Value> {
+        Ok(self.send(Method::GET, "/tags", &[], None).await?.1)
+    }
+
+    pub async fn delete(&self, id: &str) -> ClassifyResult<Value> {
+        let path = format!("/content/{}", id);
+        Ok(self.send(Method::DELETE, &path, &[], None).await?.1)
+    }
+
+    pub async fn list(&self) -> ClassifyResult<Value> {
+        Ok(self.send(Method::GET, "/content", &[], None).await?.1)
+    }
+}
+
+fn parse<T: DeserializeOwned>(body: Value) -> ClassifyResult<T> {
+    Ok(serde_json::from_value(body)?)
+}
+
+fn print_json(body: &Value) -> ClassifyResult<()> {
+    println!("{}", serde_json::to_string_pretty(body)?);
+    Ok(())
+}
+
+/// Run a command against the server, printing the result
+pub async fn run(invocation: Invocation) -> ClassifyResult<()> {
+    if invocation.command == Command::Help {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+
+    let client = Client::new(CliConfig::load(&invocation)?);
+    let json = invocation.json;
+
+    match invocation.command {
+        Command::Classify {
+            input,
+            metadata,
+            run_async,
+        } => {
+            let content = read_input(&input)?;
+            let (status, body) = client.classify(content, metadata, run_async).await?;
+            if json {
+                return print_json(&body);
+            }
+            if status == StatusCode::ACCEPTED {
+                let job: ClassificationJob = parse(body)?;
+                println!("Queued job {}", job.id);
+            } else {
+                let response: ClassifyResponse = parse(body)?;
+                println!(
+                    "{}\t{}",
+                    response.content.id,
+                    response.content.tags.join(", ")
+                );
+            }
+        }
+        Command::Query {
+            tags,
+            metadata,
+            all,
+        } => {
+            let body = client.query(&tags, metadata.as_deref(), all).await?;
+            if json {
+                return print_json(&body);
+            }
+            let response: ContentQueryResponse = parse(body)?;
+            for item in response.items {
+                println!("{}\t{}\t{}", item.id, item.content, item.tags.join(", "));
+            }
+        }
+        Command::Tags => {
+            let body = client.tags().await?;
+            if json {
+                return print_json(&body);
+            }
+            let response: TagsResponse = parse(body)?;
+            for tag in response.tags {
+                println!("{}", tag);
+            }
+        }
+        Command::Delete { id } => {
+            let body = client.delete(&id).await?;
+            if json {
+                return print_json(&body);
+            }
+            let response: DeleteResponse = parse(body)?;
+            println!("Deleted {}", response.id.unwrap_or(id));
+        }
+        Command::Export { output } => {
+            let response: ContentQueryResponse = parse(client.list().await?)?;
+            let mut writer: Box<dyn Write> = match &output {
+                Some(path) => Box::new(std::io::BufWriter::new(std::fs::File::create(path)?)),
+                None => Box::new(std::io::stdout().lock()),
+            };
+            for item in &response.items {
+                writeln!(writer, "{}", serde_json::to_string(item)?)?;
+            }
+            writer.flush()?;
+            if let Some(path) = output {
+                eprintln!("Exported {} items to {}", response.items.len(), path);
+            }
+        }
+        Command::Help => {}
+    }
+
+    Ok(())
+}
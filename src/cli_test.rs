@@ -0,0 +1,114 @@
+use crate::cli::{parse_args, read_input, Command, Invocation};
+use std::collections::HashMap;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Invocation {
+        parse_args(args.iter().map(|arg| arg.to_string())).unwrap()
+    }
+
+    #[test]
+    fn test_parse_classify_with_options() {
+        let invocation = parse(&[
+            "--url",
+            "https://classify.example.com",
+            "--api-key=secret",
+            "classify",
+            "Some",
+            "text",
+            "--meta",
+            "source=cli",
+            "--async",
+        ]);
+
+        assert_eq!(
+            invocation,
+            Invocation {
+                command: Command::Classify {
+                    input: "Some text".to_string(),
+                    metadata: HashMap::from([("source".to_string(), "cli".to_string())]),
+                    run_async: true,
+                },
+                url: Some("https://classify.example.com".to_string()),
+                api_key: Some("secret".to_string()),
+                json: false,
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!(
+            parse(&["query", "--tags", "rust,web", "--metadata=source:slack"]).command,
+            Command::Query {
+                tags: "rust,web".to_string(),
+                metadata: Some("source:slack".to_string()),
+                all: false,
+            }
+        );
+        assert_eq!(
+            parse(&["query", "--all", "--tags=rust,web"]).command,
+            Command::Query {
+                tags: "rust,web".to_string(),
+                metadata: None,
+                all: true,
+            }
+        );
+        assert_eq!(parse(&["tags", "--json"]).command, Command::Tags);
+        assert!(parse(&["tags", "--json"]).json);
+        assert_eq!(
+            parse(&["delete", "42"]).command,
+            Command::Delete {
+                id: "42".to_string()
+            }
+        );
+        assert_eq!(
+            parse(&["export", "--output", "backup.jsonl"]).command,
+            Command::Export {
+                output: Some("backup.jsonl".to_string())
+            }
+        );
+        assert_eq!(parse(&[]).command, Command::Help);
+    }
+
+    #[test]
+    fn test_parse_rejects_incomplete_commands() {
+        for args in [
+            vec!["classify"],
+            vec!["query"],
+            vec!["delete"],
+            vec!["tags", "--verbose"],
+            vec!["classify", "text", "--meta", "no-value"],
+            vec!["publish"],
+            vec!["--url"],
+        ] {
+            assert!(
+                parse_args(args.iter().map(|arg| arg.to_string())).is_err(),
+                "{:?} should be rejected",
+                args
+            );
+        }
+    }
+
+    #[test]
+    fn test_read_input_from_file() {
+        let dir = std::env::temp_dir().join(format!("classify-cli-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("note.md");
+        std::fs::write(&path, "# Notes on Rust").unwrap();
+
+        assert_eq!(
+            read_input(path.to_str().unwrap()).unwrap(),
+            "# Notes on Rust"
+        );
+        assert_eq!(
+            read_input("https://example.com/post").unwrap(),
+            "https://example.com/post"
+        );
+        assert_eq!(read_input("Just some text").unwrap(), "Just some text");
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,112 @@
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use crate::{ClassifyError, ClassifyResult};
+
+/// File read when neither `--config` nor `CONFIG_PATH` is given, if it exists
+const DEFAULT_PATH: &str = "classify.toml";
+
+/// Settings from the configuration file, by the name of the variable they set
+static VALUES: RwLock<BTreeMap<String, String>> = RwLock::new(BTreeMap::new());
+
+/// Path of the configuration file: the `--config` argument, then `CONFIG_PATH`,
+/// then `classify.toml` when it exists
+pub fn config_path() -> Option<PathBuf> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--config" {
+            return args.next().map(PathBuf::from);
+        }
+        if let Some(path) = arg.strip_prefix("--config=") {
+            return Some(PathBuf::from(path));
+        }
+    }
+
+    if let Ok(path) = std::env::var("CONFIG_PATH") {
+        return Some(PathBuf::from(path));
+    }
+
+    Path::new(DEFAULT_PATH)
+        .exists()
+        .then(|| PathBuf::from(DEFAULT_PATH))
+}
+
+/// Read a TOML or YAML file, by its extension, into variables. Nested tables
+/// are joined with `_`, so `[content_storage] type = "redis"` sets
+/// `CONTENT_STORAGE_TYPE`, and lists are joined with `,`.
+pub fn read(path: &Path) -> ClassifyResult<BTreeMap<String, String>> {
+    let value: Value = config::Config::builder()
+        .add_source(config::File::from(path))
+        .build()
+        .and_then(|config| config.try_deserialize())
+        .map_err(|e| {
+            ClassifyError::ConfigError(format!(
+                "Failed to read configuration file {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+
+    let mut values = BTreeMap::new();
+    flatten("", &value, &mut values);
+    Ok(values)
+}
+
+fn flatten(name: &str, value: &Value, values: &mut BTreeMap<String, String>) {
+    match value {
+        Value::Object(fields) => {
+            for (key, value) in fields {
+                let key = key.to_uppercase();
+                let name = if name.is_empty() {
+                    key
+                } else {
+                    format!("{}_{}", name, key)
+                };
+                flatten(&name, value, values);
+            }
+        }
+        Value::Array(items) => {
+            let items: Vec<String> = items.iter().map(scalar).collect();
+            values.insert(name.to_string(), items.join(","));
+        }
+        Value::Null => {}
+        value => {
+            values.insert(name.to_string(), scalar(value));
+        }
+    }
+}
+
+fn scalar(value: &Value) -> String {
+    match value {
+        Value::String(value) => value.clone(),
+        value => value.to_string(),
+    }
+}
+
+/// Read the configuration file, if there is one, replacing the settings read
+/// before. Returns the path of the file that was read.
+pub fn load() -> ClassifyResult<Option<PathBuf>> {
+    let Some(path) = config_path() else {
+        return Ok(None);
+    };
+
+    let values = read(&path)?;
+    *VALUES.write().unwrap_or_else(|e| e.into_inner()) = values;
+    Ok(Some(path))
+}
+
+/// Value set by the configuration file
+pub(crate) fn file_var(name: &str) -> Option<String> {
+    VALUES
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(name)
+        .cloned()
+}
+
+/// All variables of the configuration file
+pub(crate) fn file_vars() -> BTreeMap<String, String> {
+    VALUES.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
@@ -0,0 +1,70 @@
+use crate::config::file::read;
+use std::path::PathBuf;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(name: &str, contents: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("{}-{}", uuid::Uuid::new_v4(), name));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_toml_tables_set_variables() {
+        let path = write(
+            "classify.toml",
+            r#"
+classifier_type = "claude"
+read_only = false
+
+[content_storage]
+type = "redis"
+
+[tenant.acme]
+api_key = "acme_key"
+
+[api]
+port = 3000
+read_only_keys = ["dashboard_key", "reporting_key"]
+"#,
+        );
+
+        let values = read(&path).unwrap();
+        std::fs::remove_file(path).unwrap();
+
+        assert_eq!(values["CLASSIFIER_TYPE"], "claude");
+        assert_eq!(values["READ_ONLY"], "false");
+        assert_eq!(values["CONTENT_STORAGE_TYPE"], "redis");
+        assert_eq!(values["TENANT_ACME_API_KEY"], "acme_key");
+        assert_eq!(values["API_PORT"], "3000");
+        assert_eq!(values["API_READ_ONLY_KEYS"], "dashboard_key,reporting_key");
+    }
+
+    #[test]
+    fn test_yaml_file_sets_variables() {
+        let path = write(
+            "classify.yaml",
+            "tag_storage:\n  type: redis\n  redis_url: redis://localhost:6379\n",
+        );
+
+        let values = read(&path).unwrap();
+        std::fs::remove_file(path).unwrap();
+
+        assert_eq!(values["TAG_STORAGE_TYPE"], "redis");
+        assert_eq!(values["TAG_STORAGE_REDIS_URL"], "redis://localhost:6379");
+    }
+
+    #[test]
+    fn test_invalid_file_is_a_config_error() {
+        let path = write("classify.toml", "classifier_type = \n");
+
+        let error = read(&path).unwrap_err();
+        std::fs::remove_file(path).unwrap();
+
+        assert!(error
+            .to_string()
+            .contains("Failed to read configuration file"));
+    }
+}
@@ -1,18 +1,147 @@
-use crate::ClassifyError;
-use serde::Deserialize;
+use crate::{ClassifyError, UsageQuota};
+use secrets::{env_var, env_vars};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::net::{IpAddr, SocketAddr};
 use std::str::FromStr;
-use std::sync::OnceLock;
+use std::sync::RwLock;
+use std::time::Duration;
+use tracing::info;
 use uuid;
 
-static CONFIG: OnceLock<AppConfig> = OnceLock::new();
+pub mod file;
+#[cfg(test)]
+mod file_test;
+pub mod secrets;
+#[cfg(test)]
+mod secrets_test;
+pub mod validate;
+#[cfg(test)]
+mod validate_test;
+
+/// Configuration in use, replaced when [`AppConfig::reload`] picks up changed secrets
+static CONFIG: RwLock<Option<&'static AppConfig>> = RwLock::new(None);
+/// Why the last reload failed, the previous configuration stays in use
+static RELOAD_ERROR: RwLock<Option<String>> = RwLock::new(None);
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct AppConfig {
     pub api: ApiConfig,
     pub storage: StorageConfig,
     pub tag_storage: TagStorageConfig,
+    /// Single backend serving both content and tags, overriding the storage types
+    pub storage_backend: Option<StorageBackend>,
     pub classifier: ClassifierConfig,
+    /// Embeddings of stored content for semantic search, off when unset
+    pub embedding: Option<EmbeddingConfig>,
+    /// Log of what each caller looks at, for `/recommendations`. Off when unset.
+    pub activity: Option<ActivityConfig>,
+    pub jobs: JobsConfig,
+    pub ingest: IngestConfig,
+    pub import: ImportConfig,
+    pub queue: QueueConfig,
+    pub imap: Option<ImapConfig>,
+    pub kafka: Option<KafkaConfig>,
+    pub mqtt: Option<MqttConfig>,
+    pub telegram: Option<TelegramConfig>,
+    pub slack: Option<SlackConfig>,
+    /// Content mapping (JSONPath or template) per webhook source
+    pub webhooks: HashMap<String, String>,
+    /// How often secrets referenced from Vault or AWS Secrets Manager are fetched again
+    pub secrets_refresh: Option<Duration>,
+    pub logging: LoggingConfig,
+}
+
+/// Output of the tracing subscriber
+#[derive(Debug, Clone, Deserialize)]
+pub struct LoggingConfig {
+    pub format: LogFormat,
+    /// Filter in the `RUST_LOG` syntax, from `RUST_LOG` or else `LOG_LEVEL`
+    pub filter: String,
+    /// Also write logs to files, next to standard output
+    pub file: Option<LogFileConfig>,
+}
+
+/// Log files, for deployments without a log shipper reading standard output
+#[derive(Debug, Clone, Deserialize)]
+pub struct LogFileConfig {
+    pub directory: String,
+    /// Name of the file, rotated files get the date or a number appended
+    pub name: String,
+    pub rotation: LogRotation,
+    /// Size at which the file is rotated with `size` rotation
+    pub max_bytes: u64,
+    /// Number of rotated files kept, older ones are deleted
+    pub max_files: usize,
+}
+
+/// When log files are rotated
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogRotation {
+    #[default]
+    Daily,
+    Hourly,
+    /// When the file reaches its maximum size
+    Size,
+    Never,
+}
+
+impl LoggingConfig {
+    /// Read the logging settings only, so logging can start before the rest of
+    /// the configuration is loaded
+    pub fn from_env() -> Result<Self, ClassifyError> {
+        let format = env_var("LOG_FORMAT")
+            .unwrap_or_else(|_| "text".to_string())
+            .parse()
+            .map_err(|e| ClassifyError::ConfigError(format!("Invalid LOG_FORMAT: {}", e)))?;
+
+        let filter = env_var("RUST_LOG")
+            .or_else(|_| env_var("LOG_LEVEL"))
+            .unwrap_or_else(|_| "info".to_string());
+
+        let file = match env_var("LOG_FILE_DIR") {
+            Ok(directory) => Some(LogFileConfig {
+                directory,
+                name: env_var("LOG_FILE_NAME").unwrap_or_else(|_| "classify.log".to_string()),
+                rotation: env_var("LOG_FILE_ROTATION")
+                    .unwrap_or_else(|_| "daily".to_string())
+                    .parse()
+                    .map_err(|e| {
+                        ClassifyError::ConfigError(format!("Invalid LOG_FILE_ROTATION: {}", e))
+                    })?,
+                max_bytes: env_var("LOG_FILE_MAX_BYTES")
+                    .unwrap_or_else(|_| (100 * 1024 * 1024).to_string())
+                    .parse::<u64>()
+                    .map_err(|e| {
+                        ClassifyError::ConfigError(format!("Invalid LOG_FILE_MAX_BYTES: {}", e))
+                    })?,
+                max_files: env_var("LOG_FILE_MAX_FILES")
+                    .unwrap_or_else(|_| "7".to_string())
+                    .parse::<usize>()
+                    .map_err(|e| {
+                        ClassifyError::ConfigError(format!("Invalid LOG_FILE_MAX_FILES: {}", e))
+                    })?,
+            }),
+            Err(_) => None,
+        };
+
+        Ok(Self {
+            format,
+            filter,
+            file,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// Human readable lines
+    #[default]
+    Text,
+    /// One JSON object per line, for log collectors
+    Json,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -20,28 +149,203 @@ pub struct ApiConfig {
     pub host: String,
     pub port: u16,
     pub api_key: String,
+    /// SHA-256 hash of the main API key, requests are authenticated against the hash
+    pub api_key_hash: String,
+    /// Namespace per tenant API key hash, tenants only see the data of their namespace
+    pub tenant_keys: HashMap<String, String>,
+    /// Where API keys created through the admin endpoints are stored
+    pub key_storage: ApiKeyStorageType,
+    /// File for the `file` API key storage
+    pub key_storage_path: String,
+    /// How long a rotated API key keeps working next to its replacement
+    pub key_rotation_grace: Duration,
+    /// Accept `Authorization: Bearer` JWTs from an OIDC issuer
+    pub jwt: Option<JwtConfig>,
+    /// Request limits per API key and client address, unlimited when unset
+    pub rate_limit: Option<RateLimitConfig>,
+    /// Temporarily refuse client addresses that repeatedly fail authentication
+    pub auth_lockout: Option<AuthLockoutConfig>,
+    /// Take the client address from `X-Forwarded-For`, set behind a reverse proxy
+    pub trust_forwarded_for: bool,
+    /// Serve HTTPS instead of plain HTTP
+    pub tls: Option<TlsConfig>,
+    /// Log a line per request, off when unset
+    pub access_log: Option<AccessLogConfig>,
+    /// Accept all requests as the main API key, for local development only
+    pub auth_disabled: bool,
+    /// Hashes of the API keys that may only read data
+    pub read_only_keys: HashSet<String>,
+    /// Refuse all requests that change data, for maintenance windows
+    pub read_only: bool,
+    /// Start in maintenance mode, refusing changes with `503` until it is switched off
+    pub maintenance: bool,
+    pub maintenance_message: Option<String>,
+    /// File for usage counters with the `file` API key storage
+    pub usage_storage_path: String,
+    /// Monthly quota of every account except the main API key
+    pub quota: UsageQuota,
+    /// Limits replacing those of the default quota for tenant namespaces
+    pub tenant_quotas: HashMap<String, UsageQuota>,
+    /// Key that share links are signed with, share links are off without one
+    pub share_link_secret: Option<String>,
+    /// Whether the share link key was derived from `API_KEY` for lack of
+    /// `SHARE_LINK_SECRET`
+    pub share_link_secret_derived: bool,
+    /// Lifetime of share links when the request doesn't ask for one
+    pub share_link_ttl: Duration,
+    /// Longest lifetime a share link may be created with
+    pub share_link_max_ttl: Duration,
+    /// Public base URL of the service, for absolute share links
+    pub public_url: Option<String>,
+    /// How long requests in flight may take to finish once shutdown is requested
+    pub shutdown_timeout: Duration,
+}
+
+/// Certificate of the server, and the CA of client certificates for mutual TLS
+#[derive(Debug, Clone, Deserialize)]
+pub struct TlsConfig {
+    pub cert_path: String,
+    pub key_path: String,
+    /// Accept client certificates signed by this CA in place of an API key
+    pub client_ca_path: Option<String>,
+    /// How often to check the files for changes, never reloaded when unset
+    pub reload_interval: Option<Duration>,
+}
+
+/// Lock out a client address after `max_failures` failed authentications within `window`
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
+pub struct AuthLockoutConfig {
+    pub max_failures: u32,
+    pub window: Duration,
+    /// Length of the first lockout, repeated lockouts double it
+    pub duration: Duration,
+    /// Longest lockout, also how long earlier lockouts are remembered
+    pub max_duration: Duration,
+    /// Delay of the response to the first failure, doubled with every next failure
+    pub delay: Duration,
+}
+
+/// Access log of all requests
+#[derive(Debug, Clone, Deserialize)]
+pub struct AccessLogConfig {
+    /// Query parameters whose values are left out of the log
+    pub redact_params: Vec<String>,
+}
+
+/// Token bucket limits per API key and route class
+#[derive(Debug, Clone, Deserialize)]
+pub struct RateLimitConfig {
+    pub store: RateLimitStoreType,
+    /// Limits for all keys
+    pub limits: HashMap<RouteClass, RateLimit>,
+    /// Limits replacing the defaults for the keys of a tenant namespace
+    pub tenant_limits: HashMap<String, HashMap<RouteClass, RateLimit>>,
+    /// Limit on all requests from a client address, checked before authentication
+    pub ip_limit: Option<RateLimit>,
+}
+
+/// Routes sharing a rate limit
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum RouteClass {
+    /// Routes that call the classifier
+    Classify,
+    /// Everything else
+    Query,
+}
+
+/// A bucket of `requests` that refills completely over `period`
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
+pub struct RateLimit {
+    pub requests: u32,
+    pub period: Duration,
+}
+
+/// Where rate limit buckets are kept
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum RateLimitStoreType {
+    /// Per process, each instance has its own buckets
+    Memory,
+    /// Shared by all instances
+    Redis,
+}
+
+/// OIDC issuer whose JWTs authenticate requests
+#[derive(Debug, Clone, Deserialize)]
+pub struct JwtConfig {
+    pub issuer: String,
+    /// Expected `aud` claim, not checked when unset
+    pub audience: Option<String>,
+    /// Signing keys, discovered from the issuer's OpenID configuration when unset
+    pub jwks_url: Option<String>,
+    /// How long fetched signing keys are reused
+    pub jwks_cache_ttl: Duration,
+    /// Claim holding the tenant namespace of the caller
+    pub namespace_claim: Option<String>,
+}
+
+/// API key storage types
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ApiKeyStorageType {
+    File,
+    Memory,
+    Redis,
+    Postgres,
 }
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct StorageConfig {
     pub storage_type: StorageType,
     pub content_storage_path: String,
+    /// SQLite database file, in `content_storage_path`
+    pub sqlite_path: String,
     pub redis_url: Option<String>,
+    pub redis_username: Option<String>,
     pub redis_password: Option<String>,
+    pub redis_tls_insecure: bool,
+    pub redis_db: Option<i64>,
     pub redis_prefix: Option<String>,
+    /// Number of hashes remembered as not stored, zero disables the cache
+    pub hash_cache_size: usize,
+    /// How long a hash is remembered as not stored
+    pub hash_cache_ttl: Duration,
+    pub redis_sentinel: Option<RedisSentinelConfig>,
     pub s3_bucket: Option<String>,
     pub s3_prefix: Option<String>,
     pub s3_region: Option<String>,
     pub s3_profile: Option<String>,
     pub s3_access_key: Option<String>,
     pub s3_secret_key: Option<String>,
+    pub s3_endpoint_url: Option<String>,
+    pub s3_force_path_style: bool,
+    pub postgres_url: Option<String>,
+    pub dynamodb_table: Option<String>,
+    pub dynamodb_region: Option<String>,
+    pub archive_snapshots: bool,
+    /// Storage operations taking at least this long are logged, never when unset
+    pub slow_operation_threshold: Option<Duration>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct TagStorageConfig {
     pub tag_storage_type: TagStorageType,
     pub redis_url: String,
+    pub redis_username: Option<String>,
     pub redis_password: Option<String>,
+    pub redis_tls_insecure: bool,
+    pub redis_db: Option<i64>,
+    /// Key prefix, defaults to `classify:`
+    pub redis_prefix: Option<String>,
+    pub redis_sentinel: Option<RedisSentinelConfig>,
+    pub postgres_url: Option<String>,
+    /// Database file for the embedded tag storage
+    pub tag_storage_path: String,
+    /// SQLite database file, shared with the SQLite content storage
+    pub sqlite_path: String,
+    pub dynamodb_table: Option<String>,
+    pub dynamodb_region: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -50,44 +354,540 @@ pub struct ClassifierConfig {
     pub anthropic_api_key: Option<String>,
     pub openai_api_key: Option<String>,
     pub openai_model: Option<String>,
+    /// OpenAI-compatible API the ChatGPT classifier calls instead of OpenAI,
+    /// e.g. a vLLM or LM Studio server
+    pub openai_base_url: Option<String>,
     pub max_prompt_length: usize,
+    /// Most tags the LLM classifiers ask for
+    pub max_tags: usize,
+    /// System prompt of the LLM classifiers, `{max_tags}` is replaced by
+    /// `max_tags`. The built-in prompt when unset.
+    pub system_prompt_template: Option<String>,
+    /// How calls to the LLM providers are retried while they're busy
+    pub retry: crate::classifier::retry::RetryPolicy,
+    /// Classifiers asked by the ensemble classifier
+    pub ensemble_classifiers: Vec<ClassifierType>,
+    /// Members of the ensemble that must return a tag for it to be kept
+    pub ensemble_min_votes: usize,
 }
 
-/// Storage types
+#[derive(Debug, Clone, Deserialize)]
+pub struct EmbeddingConfig {
+    pub provider: EmbeddingProvider,
+    pub model: String,
+    pub storage_type: VectorStorageType,
+    /// Set when the vectors are kept in Qdrant
+    pub qdrant: Option<QdrantConfig>,
+}
+
+/// Service turning text into embedding vectors
 #[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
+pub enum EmbeddingProvider {
+    /// The OpenAI embeddings API, with `OPENAI_API_KEY`
+    OpenAi,
+}
+
+/// Where embedding vectors are kept
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum VectorStorageType {
+    /// Per process, embedded again by the backfill after a restart
+    Memory,
+    /// A Redis vector set, requires Redis 8
+    Redis,
+    /// The pgvector extension of Postgres
+    Postgres,
+    Qdrant,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct QdrantConfig {
+    pub url: String,
+    pub api_key: Option<String>,
+    pub collection: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ActivityConfig {
+    pub store: ActivityStoreType,
+    /// Activities kept per caller, the oldest dropped first
+    pub history: usize,
+}
+
+/// Where the activity log is kept
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ActivityStoreType {
+    /// Per process, lost on restart
+    Memory,
+    /// Shared by all instances
+    Redis,
+}
+
+/// Failure rates that trigger a notification when they cross their threshold
+#[derive(Debug, Clone, Deserialize)]
+pub struct AlertsConfig {
+    /// Receives `{"text": ...}` posts, like a Slack incoming webhook
+    pub webhook_url: String,
+    /// Period the error rates are computed over
+    pub window: Duration,
+    pub check_interval: Duration,
+    /// Fewer operations in the window never fire an alert
+    pub min_operations: u64,
+    /// Thresholds from 0 to 1, unset to not watch the rate
+    pub classifier_error_rate: Option<f64>,
+    pub storage_error_rate: Option<f64>,
+}
+
+/// Uptime monitor pinged on a schedule and after classifications
+#[derive(Debug, Clone, Deserialize)]
+pub struct HeartbeatConfig {
+    pub url: String,
+    pub interval: Duration,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct JobsConfig {
+    /// How often stored URLs are re-fetched, disabled when unset
+    pub refetch_interval: Option<Duration>,
+    pub refetch_mode: RefetchMode,
+    /// How often stored URLs are probed for dead links, disabled when unset
+    pub deadlink_interval: Option<Duration>,
+    pub deadlink_timeout: Duration,
+    pub retention: RetentionPolicy,
+    /// How often expired content is swept, disabled without a retention policy
+    pub retention_interval: Option<Duration>,
+    pub heartbeat: Option<HeartbeatConfig>,
+    pub alerts: Option<AlertsConfig>,
+    pub digest: Option<DigestConfig>,
+    /// How often stored content without an embedding is embedded, only with
+    /// semantic search
+    pub embedding_backfill_interval: Option<Duration>,
+}
+
+/// Digest of newly classified content, posted to a webhook every period
+#[derive(Debug, Clone, Deserialize)]
+pub struct DigestConfig {
+    /// Receives the digest as `{"text": ..., "digest": ...}`
+    pub webhook_url: String,
+    pub period: DigestPeriod,
+}
+
+/// Whether queried content needs one of the tags or all of them
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TagMatch {
+    #[default]
+    Any,
+    All,
+}
+
+/// Timestamp listed content is filtered on and ordered by, newest first
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ContentOrder {
+    CreatedAt,
+    #[default]
+    UpdatedAt,
+}
+
+/// Period a digest covers
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum DigestPeriod {
+    Day,
+    #[default]
+    Week,
+}
+
+impl DigestPeriod {
+    pub fn duration(self) -> Duration {
+        match self {
+            Self::Day => Duration::from_secs(24 * 60 * 60),
+            Self::Week => Duration::from_secs(7 * 24 * 60 * 60),
+        }
+    }
+}
+
+/// How long content is kept before the retention sweeper deletes it
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RetentionPolicy {
+    /// Maximum age of any content, kept forever when unset
+    pub max_age: Option<Duration>,
+    /// Maximum age of content with a given tag, overriding `max_age`
+    pub tag_max_age: HashMap<String, Duration>,
+}
+
+impl RetentionPolicy {
+    pub fn is_enabled(&self) -> bool {
+        self.max_age.is_some() || !self.tag_max_age.is_empty()
+    }
+
+    /// Maximum age of content with these tags. When several tags have a rule,
+    /// the longest one wins so content is kept as long as any of its tags asks.
+    pub fn max_age_for(&self, tags: &[String]) -> Option<Duration> {
+        tags.iter()
+            .filter_map(|tag| self.tag_max_age.get(tag).copied())
+            .max()
+            .or(self.max_age)
+    }
+}
+
+/// Storage types
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
 pub enum StorageType {
     Filesystem,
+    Memory,
+    Postgres,
+    DynamoDb,
     Redis,
     S3,
+    Sqlite,
+    /// A content storage registered through `crate::registry`
+    #[serde(skip)]
+    Custom(String),
 }
 
-/// Tag storage types
+/// Sentinel deployment used to find the Redis master, instead of a fixed URL
+#[derive(Debug, Clone, Deserialize)]
+pub struct RedisSentinelConfig {
+    /// Sentinel URLs, e.g. `redis://sentinel-1:26379`
+    pub nodes: Vec<String>,
+    pub master_name: String,
+}
+
+/// Backends that can serve both content and tags, the servers over one connection
 #[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
+pub enum StorageBackend {
+    Redis,
+    Postgres,
+    /// Content and tags in memory, for development and tests
+    Memory,
+}
+
+/// Tag storage types
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
 pub enum TagStorageType {
+    Embedded,
+    Memory,
+    DynamoDb,
     Redis,
+    Postgres,
+    Sqlite,
+    /// A tag storage registered through `crate::registry`
+    #[serde(skip)]
+    Custom(String),
 }
 
-#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum ClassifierType {
     Claude,
     ChatGpt,
+    /// Several classifiers asked at once, keeping the tags they agree on
+    Ensemble,
+    /// A classifier registered through `crate::registry`
+    #[serde(skip)]
+    Custom(String),
+}
+
+/// How content is prepared before classification
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct IngestConfig {
+    /// Also classify and store the URLs linked from Markdown content
+    pub classify_markdown_links: bool,
+    /// Hash function used for duplicate detection and change detection
+    pub hash_algorithm: HashAlgorithm,
+    /// How text is normalized before hashing, so trivial variations dedupe
+    pub hash_normalization: HashNormalization,
+    /// Lock on the hash of content being classified, so content arriving at
+    /// several instances at once is classified once. Off when unset.
+    pub dedup_lock: Option<DedupLockConfig>,
+    /// Daily tag counters for `/tags/trending`. Off when unset.
+    pub trends: Option<TrendsConfig>,
+    /// Rules applied to the tags of the classifier before they are stored
+    pub tags: TagPolicy,
+}
+
+/// How tags are normalized and filtered, for the tags of the classifier as
+/// well as tags set by hand and queried
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TagPolicy {
+    /// Tags that are never stored, e.g. `misc`, matched regardless of case
+    pub blocklist: Vec<String>,
+    pub lowercase: bool,
+    pub word_separator: TagWordSeparator,
+    /// Turn a plural last word into its singular, `databases` into `database`
+    pub singularize: bool,
+    /// Drop accents, `café` becomes `cafe`
+    pub fold_unicode: bool,
+}
+
+/// What goes between the words of a tag
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TagWordSeparator {
+    /// As the classifier or caller wrote it
+    #[default]
+    Keep,
+    /// `machine-learning`
+    Hyphen,
+    /// `machine learning`
+    Space,
+    /// `machine_learning`
+    Underscore,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DedupLockConfig {
+    pub store: DedupLockStoreType,
+    /// How long a lock is held at most, in case its holder dies while classifying
+    pub ttl: Duration,
+    /// How long to wait for another holder to store the content before
+    /// classifying it anyway
+    pub wait: Duration,
+}
+
+/// Where duplicate-check locks are kept
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum DedupLockStoreType {
+    /// Per process, only requests to the same instance wait for each other
+    Memory,
+    /// Shared by all instances
+    Redis,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TrendsConfig {
+    pub store: TrendStoreType,
+    /// Days the counters are kept
+    pub retention_days: u32,
+}
+
+/// Where tag trend counters are kept
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TrendStoreType {
+    /// Per process, counted again from stored content at startup
+    Memory,
+    /// Shared by all instances
+    Redis,
+}
+
+/// Normalization applied to content before it is hashed
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq)]
+pub struct HashNormalization {
+    /// Trim the text and collapse runs of whitespace into a single space
+    pub collapse_whitespace: bool,
+    /// Lowercase the text, except for URLs whose paths are case sensitive
+    pub case_fold: bool,
+}
+
+/// Hash functions available for content hashes
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum HashAlgorithm {
+    #[default]
+    Sha256,
+    /// Considerably faster than SHA-256 on large fetched pages
+    Blake3,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ImportConfig {
+    /// Number of URLs fetched and classified concurrently
+    pub workers: usize,
+    /// Minimum time between two requests to the same host
+    pub host_interval: Duration,
+    /// Maximum number of pages taken from a sitemap
+    pub sitemap_max_pages: usize,
+    /// Directory where import progress is saved so imports resume after a restart
+    pub state_path: Option<String>,
+    /// How long a finished import can be looked up before it is forgotten
+    pub retention: Duration,
+}
+
+/// Queue of classifications waiting for a worker
+#[derive(Debug, Clone, Deserialize)]
+pub struct QueueConfig {
+    pub queue_type: QueueType,
+    /// Number of jobs a worker process classifies concurrently
+    pub concurrency: usize,
+    /// How long the status of a job can be looked up
+    pub status_ttl: Duration,
+    /// Attempts of a failing job before it is set aside as a dead letter
+    pub max_attempts: u32,
+    /// Wait before the first retry, doubled for every next one
+    pub retry_backoff: Duration,
+    /// Jobs taken by a worker that didn't finish them in this time, e.g. because
+    /// it crashed, are taken over by another worker
+    pub claim_idle: Duration,
+    /// Redis consumer group the workers share
+    pub consumer_group: String,
+    /// Name of this process in the consumer group
+    pub consumer_name: String,
+}
+
+/// Where queued classifications are kept
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum QueueType {
+    /// In the process, only a worker in the same process sees the jobs
+    Memory,
+    /// Shared by all instances, for separate `serve` and `worker` processes
+    Redis,
+}
+
+/// How long finished imports are kept when `IMPORT_RETENTION_SECS` isn't set
+pub const DEFAULT_IMPORT_RETENTION: Duration = Duration::from_secs(24 * 60 * 60);
+
+impl Default for ImportConfig {
+    fn default() -> Self {
+        Self {
+            workers: 4,
+            host_interval: Duration::from_secs(1),
+            sitemap_max_pages: 10000,
+            state_path: None,
+            retention: DEFAULT_IMPORT_RETENTION,
+        }
+    }
+}
+
+/// Mailbox polled by the email ingestion worker
+#[derive(Debug, Clone, Deserialize)]
+pub struct ImapConfig {
+    pub host: String,
+    pub port: u16,
+    pub username: String,
+    pub password: String,
+    pub mailbox: String,
+    pub poll_interval: Duration,
+}
+
+/// Topic consumed by the Kafka ingestion worker
+#[derive(Debug, Clone, Deserialize)]
+pub struct KafkaConfig {
+    pub brokers: String,
+    pub topic: String,
+    pub group_id: String,
+    /// Attempts after the first before a failing message is skipped
+    pub max_retries: u32,
+}
+
+/// Broker and topics used by the MQTT ingestion worker
+#[derive(Debug, Clone, Deserialize)]
+pub struct MqttConfig {
+    pub host: String,
+    pub port: u16,
+    pub client_id: String,
+    pub topics: Vec<String>,
+    pub username: Option<String>,
+    pub password: Option<String>,
+}
+
+/// Telegram bot used for ingestion
+#[derive(Debug, Clone, Deserialize)]
+pub struct TelegramConfig {
+    pub bot_token: String,
+    /// Chats allowed to use the bot, any chat when empty
+    pub allowed_chats: Vec<i64>,
+}
+
+/// Slack app posting slash commands and events to `/slack`
+#[derive(Debug, Clone, Deserialize)]
+pub struct SlackConfig {
+    pub signing_secret: String,
+    /// Needed to unfurl shared links, slash commands work without it
+    pub bot_token: Option<String>,
+}
+
+/// What to do when a re-fetched page has changed
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum RefetchMode {
+    /// Only record when the change was detected
+    Record,
+    /// Re-classify the page and replace its tags
+    Retag,
 }
 
 impl AppConfig {
+    /// Load the `.env` file and the configuration file, resolve secret references
+    /// and initialize the configuration. Environment variables override the
+    /// settings of the configuration file.
+    pub async fn load() -> Result<&'static Self, ClassifyError> {
+        dotenvy::dotenv().ok();
+        if let Some(path) = file::load()? {
+            info!("Read configuration file {}", path.display());
+        }
+        secrets::resolve_secrets().await?;
+        Self::init()
+    }
+
     pub fn init() -> Result<&'static Self, ClassifyError> {
         dotenvy::dotenv().ok();
 
-        let api_host = std::env::var("API_HOST").unwrap_or_else(|_| "127.0.0.1".to_string());
-        let api_port = std::env::var("API_PORT")
+        let mut current = CONFIG.write().unwrap_or_else(|e| e.into_inner());
+        if let Some(config) = *current {
+            return Ok(config);
+        }
+
+        let config: &'static Self = Box::leak(Box::new(Self::from_env(None)?));
+        *current = Some(config);
+        Ok(config)
+    }
+
+    /// Read the configuration again, e.g. after secrets changed. Values read per
+    /// request, like the API keys, change right away; storage connections and
+    /// classifiers keep the values they were created with.
+    pub fn reload() -> Result<&'static Self, ClassifyError> {
+        let mut current = CONFIG.write().unwrap_or_else(|e| e.into_inner());
+        // Keep a generated API key, clients already use it
+        let config = Self::from_env(current.map(|config| config.api.api_key.as_str()));
+        *RELOAD_ERROR.write().unwrap_or_else(|e| e.into_inner()) =
+            config.as_ref().err().map(|e| e.to_string());
+        let config = config?;
+
+        // Leaked like the first configuration, callers hold on to `&'static`
+        // references. Secrets rarely change, so few configurations pile up.
+        let config: &'static Self = Box::leak(Box::new(config));
+        *current = Some(config);
+        info!("Configuration reloaded");
+        Ok(config)
+    }
+
+    /// Error of the last reload, while the configuration it replaced is still in use
+    pub fn reload_error() -> Option<String> {
+        RELOAD_ERROR
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    fn from_env(generated_api_key: Option<&str>) -> Result<Self, ClassifyError> {
+        let api_host = env_var("API_HOST").unwrap_or_else(|_| "127.0.0.1".to_string());
+        let api_port = env_var("API_PORT")
             .unwrap_or_else(|_| "3000".to_string())
             .parse::<u16>()
             .map_err(|e| ClassifyError::ConfigError(format!("Invalid API_PORT: {}", e)))?;
 
-        let api_key = std::env::var("API_KEY").unwrap_or_else(|_| {
+        let auth_disabled = env_flag("AUTH_DISABLED");
+
+        let api_key_configured = env_var("API_KEY").is_ok();
+        let api_key = env_var("API_KEY").unwrap_or_else(|_| {
+            if let Some(key) = generated_api_key {
+                return key.to_string();
+            }
             let random_key = uuid::Uuid::new_v4().to_string();
+            // Without authentication nobody needs to know the key
+            if auth_disabled {
+                return random_key;
+            }
             eprintln!(
                 "No API_KEY found in environment, generated random key: {}",
                 random_key
@@ -95,7 +895,57 @@ impl AppConfig {
             random_key
         });
 
-        let storage_type = std::env::var("CONTENT_STORAGE_TYPE")
+        let api_key_hash = key_hash(&api_key);
+
+        // TENANT_<NAMESPACE>_API_KEY gives a tenant its own namespace
+        let mut tenant_keys = HashMap::new();
+        for (name, key) in env_vars() {
+            let Some(namespace) = name
+                .strip_prefix("TENANT_")
+                .and_then(|name| name.strip_suffix("_API_KEY"))
+            else {
+                continue;
+            };
+            let namespace = namespace.to_lowercase();
+            if namespace.is_empty()
+                || !namespace
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+            {
+                return Err(ClassifyError::ConfigError(format!(
+                    "Invalid tenant namespace in {}",
+                    name
+                )));
+            }
+            let key = key_hash(&key);
+            if key == api_key_hash || tenant_keys.insert(key, namespace).is_some() {
+                return Err(ClassifyError::ConfigError(format!(
+                    "{} reuses an API key of another tenant",
+                    name
+                )));
+            }
+        }
+
+        let read_only_keys: HashSet<String> = env_var("READ_ONLY_API_KEYS")
+            .map(|keys| {
+                keys.split(',')
+                    .map(str::trim)
+                    .filter(|key| !key.is_empty())
+                    .map(key_hash)
+                    .collect()
+            })
+            .unwrap_or_default();
+        if read_only_keys.contains(&api_key_hash)
+            || read_only_keys
+                .iter()
+                .any(|key| tenant_keys.contains_key(key))
+        {
+            return Err(ClassifyError::ConfigError(
+                "READ_ONLY_API_KEYS reuses API_KEY or a tenant API key".to_string(),
+            ));
+        }
+
+        let storage_type = env_var("CONTENT_STORAGE_TYPE")
             .unwrap_or_else(|_| "filesystem".to_string())
             .parse()
             .map_err(|e| {
@@ -103,86 +953,824 @@ impl AppConfig {
             })?;
 
         let content_storage_path =
-            std::env::var("CONTENT_STORAGE_PATH").unwrap_or_else(|_| "./data/content".to_string());
+            env_var("CONTENT_STORAGE_PATH").unwrap_or_else(|_| "./data/content".to_string());
+        let sqlite_path = std::path::Path::new(&content_storage_path)
+            .join(crate::storage::sqlite::DATABASE_FILE)
+            .to_string_lossy()
+            .into_owned();
+
+        let archive_snapshots = env_flag("ARCHIVE_SNAPSHOTS");
+
+        let hash_cache_size = env_var("HASH_CACHE_SIZE")
+            .unwrap_or_else(|_| "10000".to_string())
+            .parse::<usize>()
+            .map_err(|e| ClassifyError::ConfigError(format!("Invalid HASH_CACHE_SIZE: {}", e)))?;
+        let hash_cache_ttl =
+            env_seconds("HASH_CACHE_TTL_SECS")?.unwrap_or_else(|| Duration::from_secs(60));
+        let slow_operation_threshold = env_var("STORAGE_SLOW_OPERATION_MS")
+            .unwrap_or_else(|_| "500".to_string())
+            .parse::<u64>()
+            .map(|millis| (millis > 0).then(|| Duration::from_millis(millis)))
+            .map_err(|e| {
+                ClassifyError::ConfigError(format!("Invalid STORAGE_SLOW_OPERATION_MS: {}", e))
+            })?;
 
         // Redis configuration for content storage
-        let content_redis_url = std::env::var("CONTENT_REDIS_URL").ok();
-        let content_redis_password = std::env::var("CONTENT_REDIS_PASSWORD").ok();
-        let content_redis_prefix = std::env::var("CONTENT_REDIS_PREFIX").ok();
+        let content_redis_url = env_var("CONTENT_REDIS_URL").ok();
+        let content_redis_username = env_var("CONTENT_REDIS_USERNAME").ok();
+        let content_redis_password = env_var("CONTENT_REDIS_PASSWORD").ok();
+        let content_redis_prefix = env_var("CONTENT_REDIS_PREFIX").ok();
+        let content_redis_db = env_db_index("CONTENT_REDIS_DB")?;
 
         // S3 configuration
-        let s3_bucket = std::env::var("S3_BUCKET").ok();
-        let s3_prefix = std::env::var("S3_PREFIX").ok();
-        let s3_region = std::env::var("S3_REGION").ok();
-        let s3_profile = std::env::var("AWS_PROFILE").ok();
-        let s3_access_key = std::env::var("AWS_ACCESS_KEY_ID").ok();
-        let s3_secret_key = std::env::var("AWS_SECRET_ACCESS_KEY").ok();
-
-        let tag_storage_type = std::env::var("TAG_STORAGE_TYPE")
+        let s3_bucket = env_var("S3_BUCKET").ok();
+        let s3_prefix = env_var("S3_PREFIX").ok();
+        let s3_region = env_var("S3_REGION").ok();
+        let s3_profile = env_var("AWS_PROFILE").ok();
+        let dynamodb_region = env_var("DYNAMODB_REGION").ok();
+        let postgres_url = env_var("POSTGRES_URL").ok();
+
+        // REDIS_SENTINELS replaces the Redis URLs with the master the sentinels point to
+        let redis_sentinel = match env_var("REDIS_SENTINELS") {
+            Ok(nodes) => Some(RedisSentinelConfig {
+                nodes: nodes
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|node| !node.is_empty())
+                    .map(String::from)
+                    .collect(),
+                master_name: env_var("REDIS_SENTINEL_MASTER").map_err(|_| {
+                    ClassifyError::ConfigError(
+                        "REDIS_SENTINEL_MASTER is required with REDIS_SENTINELS".to_string(),
+                    )
+                })?,
+            }),
+            Err(_) => None,
+        };
+
+        let storage_backend = env_var("STORAGE_BACKEND")
+            .ok()
+            .map(|backend| backend.parse())
+            .transpose()
+            .map_err(|e| ClassifyError::ConfigError(format!("Invalid STORAGE_BACKEND: {}", e)))?;
+        let s3_access_key = env_var("AWS_ACCESS_KEY_ID").ok();
+        let s3_secret_key = env_var("AWS_SECRET_ACCESS_KEY").ok();
+
+        let tag_storage_type: TagStorageType = env_var("TAG_STORAGE_TYPE")
             .unwrap_or_else(|_| "redis".to_string())
             .parse()
             .map_err(|e| ClassifyError::ConfigError(format!("Invalid TAG_STORAGE_TYPE: {}", e)))?;
 
+        // API keys are kept in the tag storage backend unless configured otherwise
+        let key_storage = match env_var("API_KEY_STORAGE") {
+            Ok(value) => value.parse().map_err(|e| {
+                ClassifyError::ConfigError(format!("Invalid API_KEY_STORAGE: {}", e))
+            })?,
+            Err(_) => match &tag_storage_type {
+                TagStorageType::Redis => ApiKeyStorageType::Redis,
+                TagStorageType::Postgres => ApiKeyStorageType::Postgres,
+                TagStorageType::Memory => ApiKeyStorageType::Memory,
+                _ => ApiKeyStorageType::File,
+            },
+        };
+        let key_storage_path =
+            env_var("API_KEY_STORAGE_PATH").unwrap_or_else(|_| "./data/api_keys.json".to_string());
+        let key_rotation_grace = env_seconds("API_KEY_ROTATION_GRACE_SECS")?
+            .unwrap_or_else(|| Duration::from_secs(24 * 60 * 60));
+
+        let jwt = match env_var("JWT_ISSUER") {
+            Ok(issuer) => Some(JwtConfig {
+                issuer: issuer.trim_end_matches('/').to_string(),
+                audience: env_var("JWT_AUDIENCE").ok(),
+                jwks_url: env_var("JWT_JWKS_URL").ok(),
+                jwks_cache_ttl: env_seconds("JWT_JWKS_CACHE_SECS")?
+                    .unwrap_or_else(|| Duration::from_secs(60 * 60)),
+                namespace_claim: env_var("JWT_NAMESPACE_CLAIM").ok(),
+            }),
+            Err(_) => None,
+        };
+
+        // RATE_LIMIT_<CLASS> sets a limit for all keys, RATE_LIMIT_<NAMESPACE>_<CLASS> for a tenant
+        let mut rate_limits = HashMap::new();
+        let mut tenant_rate_limits: HashMap<String, HashMap<RouteClass, RateLimit>> =
+            HashMap::new();
+        for (name, value) in env_vars() {
+            let Some(rest) = name.strip_prefix("RATE_LIMIT_") else {
+                continue;
+            };
+            let (namespace, class) = match rest.rsplit_once('_') {
+                Some((namespace, class)) => (Some(namespace.to_lowercase()), class),
+                None => (None, rest),
+            };
+            let Ok(class) = class.parse::<RouteClass>() else {
+                continue;
+            };
+            let limit = value
+                .parse::<RateLimit>()
+                .map_err(|e| ClassifyError::ConfigError(format!("Invalid {}: {}", name, e)))?;
+            match namespace {
+                Some(namespace) => {
+                    tenant_rate_limits
+                        .entry(namespace)
+                        .or_default()
+                        .insert(class, limit);
+                }
+                None => {
+                    rate_limits.insert(class, limit);
+                }
+            }
+        }
+        let rate_limit_store = match env_var("RATE_LIMIT_STORE") {
+            Ok(value) => value.parse().map_err(|e| {
+                ClassifyError::ConfigError(format!("Invalid RATE_LIMIT_STORE: {}", e))
+            })?,
+            Err(_) if tag_storage_type == TagStorageType::Redis => RateLimitStoreType::Redis,
+            Err(_) => RateLimitStoreType::Memory,
+        };
+        let ip_rate_limit = env_var("RATE_LIMIT_IP")
+            .ok()
+            .map(|value| value.parse::<RateLimit>())
+            .transpose()
+            .map_err(|e| ClassifyError::ConfigError(format!("Invalid RATE_LIMIT_IP: {}", e)))?;
+        let rate_limit =
+            (!rate_limits.is_empty() || !tenant_rate_limits.is_empty() || ip_rate_limit.is_some())
+                .then_some(RateLimitConfig {
+                    store: rate_limit_store,
+                    limits: rate_limits,
+                    tenant_limits: tenant_rate_limits,
+                    ip_limit: ip_rate_limit,
+                });
+
+        let tls = match (env_var("TLS_CERT_PATH"), env_var("TLS_KEY_PATH")) {
+            (Ok(cert_path), Ok(key_path)) => Some(TlsConfig {
+                cert_path,
+                key_path,
+                client_ca_path: env_var("TLS_CLIENT_CA_PATH").ok(),
+                // Renewed certificates are picked up within 30 seconds unless set to 0
+                reload_interval: match env_var("TLS_RELOAD_INTERVAL_SECS") {
+                    Ok(_) => env_seconds("TLS_RELOAD_INTERVAL_SECS")?,
+                    Err(_) => Some(Duration::from_secs(30)),
+                },
+            }),
+            (Err(_), Err(_)) if env_var("TLS_CLIENT_CA_PATH").is_ok() => {
+                return Err(ClassifyError::ConfigError(
+                    "TLS_CLIENT_CA_PATH requires TLS_CERT_PATH and TLS_KEY_PATH".to_string(),
+                ))
+            }
+            (Err(_), Err(_)) => None,
+            _ => {
+                return Err(ClassifyError::ConfigError(
+                    "TLS_CERT_PATH and TLS_KEY_PATH must be set together".to_string(),
+                ))
+            }
+        };
+
+        // QUOTA_<LIMIT> sets the monthly quota of all accounts, QUOTA_<NAMESPACE>_<LIMIT> of a tenant
+        let mut quota = UsageQuota::default();
+        let mut tenant_quotas: HashMap<String, UsageQuota> = HashMap::new();
+        for (name, value) in env_vars() {
+            let Some(rest) = name.strip_prefix("QUOTA_") else {
+                continue;
+            };
+            let Some((namespace, limit)) = ["CLASSIFICATIONS", "STORED_BYTES", "TOKENS"]
+                .into_iter()
+                .find_map(|limit| {
+                    if rest == limit {
+                        Some((None, limit))
+                    } else {
+                        rest.strip_suffix(limit)
+                            .and_then(|namespace| namespace.strip_suffix('_'))
+                            .map(|namespace| (Some(namespace.to_lowercase()), limit))
+                    }
+                })
+            else {
+                continue;
+            };
+            let value = value
+                .parse::<u64>()
+                .map_err(|e| ClassifyError::ConfigError(format!("Invalid {}: {}", name, e)))?;
+
+            let quota = match namespace {
+                Some(namespace) => tenant_quotas.entry(namespace).or_default(),
+                None => &mut quota,
+            };
+            match limit {
+                "CLASSIFICATIONS" => quota.classifications = Some(value),
+                "STORED_BYTES" => quota.stored_bytes = Some(value),
+                _ => quota.tokens = Some(value),
+            }
+        }
+
+        let auth_lockout = match env_var("AUTH_LOCKOUT_FAILURES") {
+            Ok(value) => Some(AuthLockoutConfig {
+                max_failures: value.parse::<u32>().map_err(|e| {
+                    ClassifyError::ConfigError(format!("Invalid AUTH_LOCKOUT_FAILURES: {}", e))
+                })?,
+                window: env_seconds("AUTH_LOCKOUT_WINDOW_SECS")?
+                    .unwrap_or_else(|| Duration::from_secs(5 * 60)),
+                duration: env_seconds("AUTH_LOCKOUT_SECS")?
+                    .unwrap_or_else(|| Duration::from_secs(15 * 60)),
+                max_duration: env_seconds("AUTH_LOCKOUT_MAX_SECS")?
+                    .unwrap_or_else(|| Duration::from_secs(24 * 60 * 60)),
+                delay: env_var("AUTH_FAILURE_DELAY_MS")
+                    .unwrap_or_else(|_| "250".to_string())
+                    .parse::<u64>()
+                    .map(Duration::from_millis)
+                    .map_err(|e| {
+                        ClassifyError::ConfigError(format!("Invalid AUTH_FAILURE_DELAY_MS: {}", e))
+                    })?,
+            })
+            .filter(|lockout| lockout.max_failures > 0),
+            Err(_) => None,
+        };
+
         let redis_url =
-            std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string());
+            env_var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string());
 
-        let redis_password = std::env::var("REDIS_PASSWORD").ok();
+        let redis_username = env_var("REDIS_USERNAME").ok();
+        let redis_password = env_var("REDIS_PASSWORD").ok();
+        let redis_db = env_db_index("REDIS_DB")?;
+        let redis_prefix = env_var("REDIS_PREFIX").ok();
+        // Applies to rediss:// URLs of both Redis storages
+        let redis_tls_insecure = env_flag("REDIS_TLS_INSECURE");
 
-        let classifier_type = std::env::var("CLASSIFIER_TYPE")
+        let classifier_type = env_var("CLASSIFIER_TYPE")
             .unwrap_or_else(|_| "claude".to_string())
             .parse()
             .map_err(|e| ClassifyError::ConfigError(format!("Invalid CLASSIFIER_TYPE: {}", e)))?;
 
-        let anthropic_api_key = std::env::var("ANTHROPIC_API_KEY").ok();
-        let openai_api_key = std::env::var("OPENAI_API_KEY").ok();
-        let openai_model = std::env::var("OPENAI_MODEL").ok();
+        let anthropic_api_key = env_var("ANTHROPIC_API_KEY").ok();
+        let openai_api_key = env_var("OPENAI_API_KEY").ok();
+        let openai_model = env_var("OPENAI_MODEL").ok();
+        let openai_base_url = env_var("OPENAI_BASE_URL").ok();
+
+        // EMBEDDING_STORE is the older name of the setting
+        let vector_storage_type =
+            match env_var("VECTOR_STORAGE_TYPE").or_else(|_| env_var("EMBEDDING_STORE")) {
+                Ok(value) => value.parse().map_err(|e| {
+                    ClassifyError::ConfigError(format!("Invalid VECTOR_STORAGE_TYPE: {}", e))
+                })?,
+                Err(_) if tag_storage_type == TagStorageType::Redis => VectorStorageType::Redis,
+                Err(_) => VectorStorageType::Memory,
+            };
+        let embedding = match env_var("EMBEDDING_PROVIDER") {
+            Ok(provider) => Some(EmbeddingConfig {
+                provider: provider.parse().map_err(|e| {
+                    ClassifyError::ConfigError(format!("Invalid EMBEDDING_PROVIDER: {}", e))
+                })?,
+                model: env_var("EMBEDDING_MODEL")
+                    .unwrap_or_else(|_| "text-embedding-3-small".to_string()),
+                storage_type: vector_storage_type,
+                qdrant: (vector_storage_type == VectorStorageType::Qdrant).then(|| QdrantConfig {
+                    url: env_var("QDRANT_URL")
+                        .unwrap_or_else(|_| "http://localhost:6333".to_string()),
+                    api_key: env_var("QDRANT_API_KEY").ok(),
+                    collection: env_var("QDRANT_COLLECTION")
+                        .unwrap_or_else(|_| "classify".to_string()),
+                }),
+            }),
+            Err(_) => None,
+        };
+        let embedding_backfill_interval = embedding
+            .is_some()
+            .then(|| env_seconds("EMBEDDING_BACKFILL_INTERVAL_SECS"))
+            .transpose()?
+            .map(|interval| interval.unwrap_or(Duration::from_secs(600)));
+
+        let activity = match env_var("ACTIVITY_STORE") {
+            Ok(store) => Some(ActivityConfig {
+                store: store.parse().map_err(|e| {
+                    ClassifyError::ConfigError(format!("Invalid ACTIVITY_STORE: {}", e))
+                })?,
+                history: env_var("ACTIVITY_HISTORY")
+                    .unwrap_or_else(|_| "200".to_string())
+                    .parse::<usize>()
+                    .map_err(|e| {
+                        ClassifyError::ConfigError(format!("Invalid ACTIVITY_HISTORY: {}", e))
+                    })?
+                    .max(1),
+            }),
+            Err(_) => None,
+        };
 
-        let max_prompt_length = std::env::var("MAX_PROMPT_LENGTH")
+        let max_prompt_length = env_var("MAX_PROMPT_LENGTH")
             .unwrap_or_else(|_| "200000".to_string())
             .parse::<usize>()
             .map_err(|e| ClassifyError::ConfigError(format!("Invalid MAX_PROMPT_LENGTH: {}", e)))?;
 
+        let max_tags = match env_var("CLASSIFIER_MAX_TAGS") {
+            Ok(value) => value
+                .parse::<usize>()
+                .ok()
+                .filter(|max_tags| *max_tags > 0)
+                .ok_or_else(|| {
+                    ClassifyError::ConfigError(format!(
+                        "Invalid CLASSIFIER_MAX_TAGS: {}, expected a number of at least 1",
+                        value
+                    ))
+                })?,
+            Err(_) => crate::classifier::prompt::DEFAULT_MAX_TAGS,
+        };
+        let retry = crate::classifier::retry::RetryPolicy {
+            max_attempts: match env_var("CLASSIFIER_MAX_ATTEMPTS") {
+                Ok(value) => value
+                    .parse::<u32>()
+                    .ok()
+                    .filter(|attempts| *attempts > 0)
+                    .ok_or_else(|| {
+                        ClassifyError::ConfigError(format!(
+                            "Invalid CLASSIFIER_MAX_ATTEMPTS: {}, expected a number of at least 1",
+                            value
+                        ))
+                    })?,
+                Err(_) => crate::classifier::retry::DEFAULT_MAX_ATTEMPTS,
+            },
+            backoff: match env_var("CLASSIFIER_RETRY_BACKOFF_MS") {
+                Ok(value) => value
+                    .parse::<u64>()
+                    .map(Duration::from_millis)
+                    .map_err(|e| {
+                        ClassifyError::ConfigError(format!(
+                            "Invalid CLASSIFIER_RETRY_BACKOFF_MS: {}",
+                            e
+                        ))
+                    })?,
+                Err(_) => crate::classifier::retry::DEFAULT_BACKOFF,
+            },
+        };
+        let ensemble_classifiers = match env_var("ENSEMBLE_CLASSIFIERS") {
+            Ok(value) => value
+                .split(',')
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .map(|name| {
+                    name.parse().map_err(|e| {
+                        ClassifyError::ConfigError(format!("Invalid ENSEMBLE_CLASSIFIERS: {}", e))
+                    })
+                })
+                .collect::<Result<Vec<ClassifierType>, ClassifyError>>()?,
+            Err(_) => Vec::new(),
+        };
+        let ensemble_min_votes = match env_var("ENSEMBLE_MIN_VOTES") {
+            Ok(value) => value
+                .parse::<usize>()
+                .ok()
+                .filter(|votes| *votes > 0)
+                .ok_or_else(|| {
+                    ClassifyError::ConfigError(format!(
+                        "Invalid ENSEMBLE_MIN_VOTES: {}, expected a number of at least 1",
+                        value
+                    ))
+                })?,
+            Err(_) => crate::classifier::ensemble::DEFAULT_MIN_VOTES,
+        };
+        let system_prompt_template = env_var("CLASSIFIER_SYSTEM_PROMPT")
+            .ok()
+            .filter(|prompt| !prompt.trim().is_empty());
+
+        let hash_algorithm = env_var("HASH_ALGORITHM")
+            .unwrap_or_else(|_| "sha256".to_string())
+            .parse()
+            .map_err(|e| ClassifyError::ConfigError(format!("Invalid HASH_ALGORITHM: {}", e)))?;
+
+        // Classifying a URL fetches the page and calls the classifier, both with
+        // their own timeouts, so a lock outlives a slow classification
+        let dedup_lock_ttl =
+            env_seconds("DEDUP_LOCK_TTL_SECS")?.unwrap_or_else(|| Duration::from_secs(120));
+        let dedup_lock = match env_var("DEDUP_LOCK_STORE").as_deref() {
+            Ok("none") => None,
+            value => Some(DedupLockConfig {
+                store: match value {
+                    Ok(value) => value.parse().map_err(|e| {
+                        ClassifyError::ConfigError(format!("Invalid DEDUP_LOCK_STORE: {}", e))
+                    })?,
+                    Err(_) if tag_storage_type == TagStorageType::Redis => {
+                        DedupLockStoreType::Redis
+                    }
+                    Err(_) => DedupLockStoreType::Memory,
+                },
+                ttl: dedup_lock_ttl,
+                wait: env_seconds("DEDUP_LOCK_WAIT_SECS")?.unwrap_or(dedup_lock_ttl),
+            }),
+        };
+
+        let trends = match env_var("TREND_STORE").as_deref() {
+            Ok("none") => None,
+            value => Some(TrendsConfig {
+                store: match value {
+                    Ok(value) => value.parse().map_err(|e| {
+                        ClassifyError::ConfigError(format!("Invalid TREND_STORE: {}", e))
+                    })?,
+                    Err(_) if tag_storage_type == TagStorageType::Redis => TrendStoreType::Redis,
+                    Err(_) => TrendStoreType::Memory,
+                },
+                retention_days: env_var("TREND_RETENTION_DAYS")
+                    .unwrap_or_else(|_| "90".to_string())
+                    .parse::<u32>()
+                    .map_err(|e| {
+                        ClassifyError::ConfigError(format!("Invalid TREND_RETENTION_DAYS: {}", e))
+                    })?
+                    .max(2),
+            }),
+        };
+
+        let refetch_interval = env_seconds("REFETCH_INTERVAL_SECS")?;
+        let refetch_mode = env_var("REFETCH_MODE")
+            .unwrap_or_else(|_| "record".to_string())
+            .parse()
+            .map_err(|e| ClassifyError::ConfigError(format!("Invalid REFETCH_MODE: {}", e)))?;
+
+        let deadlink_interval = env_seconds("DEADLINK_INTERVAL_SECS")?;
+        let deadlink_timeout =
+            env_seconds("DEADLINK_TIMEOUT_SECS")?.unwrap_or_else(|| Duration::from_secs(10));
+
+        // RETENTION_TAG_DAYS holds per-tag rules such as `news=90,scratch=7`
+        let retention = RetentionPolicy {
+            max_age: env_days("RETENTION_DAYS")?,
+            tag_max_age: match env_var("RETENTION_TAG_DAYS") {
+                Ok(rules) => parse_tag_days(&rules)?,
+                Err(_) => HashMap::new(),
+            },
+        };
+        let retention_interval = match env_seconds("RETENTION_SWEEP_INTERVAL_SECS")? {
+            Some(interval) => Some(interval),
+            None => retention.is_enabled().then(|| Duration::from_secs(3600)),
+        };
+
+        let heartbeat = match env_var("HEARTBEAT_URL") {
+            Ok(url) => Some(HeartbeatConfig {
+                url,
+                interval: env_seconds("HEARTBEAT_INTERVAL_SECS")?
+                    .unwrap_or(Duration::from_secs(60)),
+            }),
+            Err(_) => None,
+        };
+
+        let alerts = match env_var("ALERT_WEBHOOK_URL") {
+            Ok(webhook_url) => Some(AlertsConfig {
+                webhook_url,
+                window: env_seconds("ALERT_WINDOW_SECS")?.unwrap_or(Duration::from_secs(300)),
+                check_interval: env_seconds("ALERT_CHECK_INTERVAL_SECS")?
+                    .unwrap_or(Duration::from_secs(60)),
+                min_operations: env_var("ALERT_MIN_OPERATIONS")
+                    .unwrap_or_else(|_| "10".to_string())
+                    .parse::<u64>()
+                    .map_err(|e| {
+                        ClassifyError::ConfigError(format!("Invalid ALERT_MIN_OPERATIONS: {}", e))
+                    })?,
+                classifier_error_rate: match env_var("ALERT_CLASSIFIER_ERROR_RATE") {
+                    Ok(rate) => parse_rate("ALERT_CLASSIFIER_ERROR_RATE", &rate)?,
+                    Err(_) => Some(0.2),
+                },
+                storage_error_rate: match env_var("ALERT_STORAGE_ERROR_RATE") {
+                    Ok(rate) => parse_rate("ALERT_STORAGE_ERROR_RATE", &rate)?,
+                    Err(_) => None,
+                },
+            }),
+            Err(_) => None,
+        };
+
+        let digest = match env_var("DIGEST_WEBHOOK_URL") {
+            Ok(webhook_url) => Some(DigestConfig {
+                webhook_url,
+                period: env_var("DIGEST_PERIOD")
+                    .unwrap_or_else(|_| "week".to_string())
+                    .parse()
+                    .map_err(|e| {
+                        ClassifyError::ConfigError(format!("Invalid DIGEST_PERIOD: {}", e))
+                    })?,
+            }),
+            Err(_) => None,
+        };
+
+        let import_workers = env_var("IMPORT_WORKERS")
+            .unwrap_or_else(|_| "4".to_string())
+            .parse::<usize>()
+            .map_err(|e| ClassifyError::ConfigError(format!("Invalid IMPORT_WORKERS: {}", e)))?;
+        let import_host_interval = env_var("IMPORT_HOST_INTERVAL_MS")
+            .unwrap_or_else(|_| "1000".to_string())
+            .parse::<u64>()
+            .map(Duration::from_millis)
+            .map_err(|e| {
+                ClassifyError::ConfigError(format!("Invalid IMPORT_HOST_INTERVAL_MS: {}", e))
+            })?;
+
+        let queue_type = match env_var("QUEUE_TYPE") {
+            Ok(value) => value
+                .parse()
+                .map_err(|e| ClassifyError::ConfigError(format!("Invalid QUEUE_TYPE: {}", e)))?,
+            Err(_) if tag_storage_type == TagStorageType::Redis => QueueType::Redis,
+            Err(_) => QueueType::Memory,
+        };
+        let worker_concurrency = env_var("WORKER_CONCURRENCY")
+            .unwrap_or_else(|_| "4".to_string())
+            .parse::<usize>()
+            .map_err(|e| {
+                ClassifyError::ConfigError(format!("Invalid WORKER_CONCURRENCY: {}", e))
+            })?;
+
+        let queue_max_attempts = env_var("QUEUE_MAX_ATTEMPTS")
+            .unwrap_or_else(|_| "3".to_string())
+            .parse::<u32>()
+            .map_err(|e| {
+                ClassifyError::ConfigError(format!("Invalid QUEUE_MAX_ATTEMPTS: {}", e))
+            })?;
+
+        let imap = match env_var("IMAP_HOST") {
+            Ok(host) => Some(ImapConfig {
+                host,
+                port: env_var("IMAP_PORT")
+                    .unwrap_or_else(|_| "993".to_string())
+                    .parse::<u16>()
+                    .map_err(|e| ClassifyError::ConfigError(format!("Invalid IMAP_PORT: {}", e)))?,
+                username: env_var("IMAP_USERNAME").map_err(|_| {
+                    ClassifyError::ConfigError(
+                        "IMAP_USERNAME is required with IMAP_HOST".to_string(),
+                    )
+                })?,
+                password: env_var("IMAP_PASSWORD").map_err(|_| {
+                    ClassifyError::ConfigError(
+                        "IMAP_PASSWORD is required with IMAP_HOST".to_string(),
+                    )
+                })?,
+                mailbox: env_var("IMAP_MAILBOX").unwrap_or_else(|_| "INBOX".to_string()),
+                poll_interval: env_seconds("IMAP_POLL_INTERVAL_SECS")?
+                    .unwrap_or_else(|| Duration::from_secs(60)),
+            }),
+            Err(_) => None,
+        };
+
+        let kafka = match env_var("KAFKA_BROKERS") {
+            Ok(brokers) => Some(KafkaConfig {
+                brokers,
+                topic: env_var("KAFKA_TOPIC").unwrap_or_else(|_| "classify".to_string()),
+                group_id: env_var("KAFKA_GROUP_ID").unwrap_or_else(|_| "classify".to_string()),
+                max_retries: env_var("KAFKA_MAX_RETRIES")
+                    .unwrap_or_else(|_| "3".to_string())
+                    .parse::<u32>()
+                    .map_err(|e| {
+                        ClassifyError::ConfigError(format!("Invalid KAFKA_MAX_RETRIES: {}", e))
+                    })?,
+            }),
+            Err(_) => None,
+        };
+
+        let mqtt = match env_var("MQTT_HOST") {
+            Ok(host) => Some(MqttConfig {
+                host,
+                port: env_var("MQTT_PORT")
+                    .unwrap_or_else(|_| "1883".to_string())
+                    .parse::<u16>()
+                    .map_err(|e| ClassifyError::ConfigError(format!("Invalid MQTT_PORT: {}", e)))?,
+                client_id: env_var("MQTT_CLIENT_ID").unwrap_or_else(|_| "classify".to_string()),
+                topics: env_var("MQTT_TOPICS")
+                    .unwrap_or_else(|_| "classify/#".to_string())
+                    .split(',')
+                    .map(|topic| topic.trim().to_string())
+                    .filter(|topic| !topic.is_empty())
+                    .collect(),
+                username: env_var("MQTT_USERNAME").ok(),
+                password: env_var("MQTT_PASSWORD").ok(),
+            }),
+            Err(_) => None,
+        };
+
+        let telegram = match env_var("TELEGRAM_BOT_TOKEN") {
+            Ok(bot_token) => Some(TelegramConfig {
+                bot_token,
+                allowed_chats: env_var("TELEGRAM_ALLOWED_CHATS")
+                    .unwrap_or_default()
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|chat| !chat.is_empty())
+                    .map(|chat| chat.parse::<i64>())
+                    .collect::<Result<_, _>>()
+                    .map_err(|e| {
+                        ClassifyError::ConfigError(format!("Invalid TELEGRAM_ALLOWED_CHATS: {}", e))
+                    })?,
+            }),
+            Err(_) => None,
+        };
+
+        let slack = env_var("SLACK_SIGNING_SECRET")
+            .ok()
+            .map(|signing_secret| SlackConfig {
+                signing_secret,
+                bot_token: env_var("SLACK_BOT_TOKEN").ok(),
+            });
+
+        // WEBHOOK_<SOURCE>_CONTENT configures the mapping for /ingest/webhook/<source>
+        let webhooks = env_vars()
+            .filter_map(|(name, value)| {
+                let source = name.strip_prefix("WEBHOOK_")?.strip_suffix("_CONTENT")?;
+                Some((source.to_lowercase(), value))
+            })
+            .collect();
+
+        let sitemap_max_pages = env_var("SITEMAP_MAX_PAGES")
+            .unwrap_or_else(|_| "10000".to_string())
+            .parse::<usize>()
+            .map_err(|e| ClassifyError::ConfigError(format!("Invalid SITEMAP_MAX_PAGES: {}", e)))?;
+        let import_state_path = env_var("IMPORT_STATE_PATH").ok();
+
+        // A generated API key changes on every start, and so would a key derived from it
+        let share_link_secret_derived = env_var("SHARE_LINK_SECRET").is_err() && api_key_configured;
+        let share_link_secret = match env_var("SHARE_LINK_SECRET") {
+            Ok(secret) => Some(secret),
+            Err(_) if share_link_secret_derived => Some(crate::api::share::derive_secret(&api_key)),
+            Err(_) => None,
+        };
+
         let config = AppConfig {
             api: ApiConfig {
                 host: api_host,
                 port: api_port,
                 api_key,
+                api_key_hash,
+                tenant_keys,
+                key_storage,
+                key_storage_path,
+                key_rotation_grace,
+                jwt,
+                rate_limit,
+                auth_lockout,
+                trust_forwarded_for: env_flag("TRUST_FORWARDED_FOR"),
+                tls,
+                access_log: env_flag("ACCESS_LOG").then(|| AccessLogConfig {
+                    redact_params: env_var("ACCESS_LOG_REDACT_PARAMS")
+                        .unwrap_or_else(|_| {
+                            "signature,token,key,api_key,access_token,secret,password".to_string()
+                        })
+                        .split(',')
+                        .map(|param| param.trim().to_string())
+                        .filter(|param| !param.is_empty())
+                        .collect(),
+                }),
+                read_only_keys,
+                read_only: env_flag("READ_ONLY"),
+                maintenance: env_flag("MAINTENANCE_MODE"),
+                maintenance_message: env_var("MAINTENANCE_MESSAGE").ok(),
+                auth_disabled,
+                share_link_secret,
+                share_link_secret_derived,
+                share_link_ttl: env_seconds("SHARE_LINK_TTL_SECS")?
+                    .unwrap_or(Duration::from_secs(7 * 24 * 3600)),
+                share_link_max_ttl: env_seconds("SHARE_LINK_MAX_TTL_SECS")?
+                    .unwrap_or(Duration::from_secs(30 * 24 * 3600)),
+                public_url: env_var("PUBLIC_URL")
+                    .ok()
+                    .map(|url| url.trim_end_matches('/').to_string()),
+                shutdown_timeout: env_seconds("SHUTDOWN_TIMEOUT_SECS")?
+                    .unwrap_or(Duration::from_secs(30)),
+                usage_storage_path: env_var("USAGE_STORAGE_PATH")
+                    .unwrap_or_else(|_| "./data/usage.json".to_string()),
+                quota,
+                tenant_quotas,
             },
             storage: StorageConfig {
                 storage_type,
                 content_storage_path,
+                sqlite_path: sqlite_path.clone(),
                 redis_url: content_redis_url,
+                redis_username: content_redis_username,
                 redis_password: content_redis_password,
+                redis_tls_insecure,
+                redis_db: content_redis_db,
                 redis_prefix: content_redis_prefix,
+                hash_cache_size,
+                hash_cache_ttl,
+                slow_operation_threshold,
+                redis_sentinel: redis_sentinel.clone(),
                 s3_bucket,
                 s3_prefix,
                 s3_region,
                 s3_profile,
+                s3_endpoint_url: env_var("S3_ENDPOINT_URL").ok(),
+                s3_force_path_style: env_flag("S3_FORCE_PATH_STYLE"),
+                postgres_url: postgres_url.clone(),
+                dynamodb_table: env_var("DYNAMODB_CONTENT_TABLE").ok(),
+                dynamodb_region: dynamodb_region.clone(),
                 s3_access_key,
                 s3_secret_key,
+                archive_snapshots,
             },
+            storage_backend,
             tag_storage: TagStorageConfig {
                 tag_storage_type,
                 redis_url,
+                redis_username,
                 redis_password,
+                redis_tls_insecure,
+                redis_db,
+                redis_prefix,
+                redis_sentinel,
+                postgres_url,
+                tag_storage_path: env_var("TAG_STORAGE_PATH")
+                    .unwrap_or_else(|_| "./data/tags.redb".to_string()),
+                sqlite_path,
+                dynamodb_table: env_var("DYNAMODB_TAG_TABLE").ok(),
+                dynamodb_region: dynamodb_region.clone(),
             },
             classifier: ClassifierConfig {
                 classifier_type,
                 anthropic_api_key,
                 openai_api_key,
                 openai_model,
+                openai_base_url,
+                max_tags,
+                system_prompt_template,
+                retry,
+                ensemble_classifiers,
+                ensemble_min_votes,
                 max_prompt_length,
             },
+            embedding,
+            activity,
+            jobs: JobsConfig {
+                refetch_interval,
+                refetch_mode,
+                deadlink_interval,
+                deadlink_timeout,
+                retention,
+                retention_interval,
+                heartbeat,
+                alerts,
+                digest,
+                embedding_backfill_interval,
+            },
+            ingest: IngestConfig {
+                classify_markdown_links: env_flag("MARKDOWN_CLASSIFY_LINKS"),
+                hash_algorithm,
+                hash_normalization: HashNormalization {
+                    collapse_whitespace: env_flag("HASH_NORMALIZE_WHITESPACE"),
+                    case_fold: env_flag("HASH_CASE_FOLD"),
+                },
+                dedup_lock,
+                trends,
+                tags: TagPolicy {
+                    blocklist: env_var("TAG_BLOCKLIST")
+                        .unwrap_or_default()
+                        .split(',')
+                        .map(crate::tags::normalize_tag_path)
+                        .filter(|tag| !tag.is_empty())
+                        .collect(),
+                    lowercase: env_flag("TAG_LOWERCASE"),
+                    word_separator: env_var("TAG_WORD_SEPARATOR")
+                        .unwrap_or_else(|_| "keep".to_string())
+                        .parse()
+                        .map_err(|e| {
+                            ClassifyError::ConfigError(format!("Invalid TAG_WORD_SEPARATOR: {}", e))
+                        })?,
+                    singularize: env_flag("TAG_SINGULARIZE"),
+                    fold_unicode: env_flag("TAG_FOLD_UNICODE"),
+                },
+            },
+            import: ImportConfig {
+                workers: import_workers,
+                host_interval: import_host_interval,
+                sitemap_max_pages,
+                state_path: import_state_path,
+                retention: env_seconds("IMPORT_RETENTION_SECS")?
+                    .unwrap_or(DEFAULT_IMPORT_RETENTION),
+            },
+            queue: QueueConfig {
+                queue_type,
+                concurrency: worker_concurrency.max(1),
+                status_ttl: env_seconds("JOB_STATUS_TTL_SECS")?
+                    .unwrap_or(Duration::from_secs(24 * 3600)),
+                max_attempts: queue_max_attempts.max(1),
+                retry_backoff: env_seconds("QUEUE_RETRY_BACKOFF_SECS")?
+                    .unwrap_or(Duration::from_secs(10)),
+                claim_idle: env_seconds("QUEUE_CLAIM_IDLE_SECS")?
+                    .unwrap_or(Duration::from_secs(600)),
+                consumer_group: env_var("QUEUE_CONSUMER_GROUP")
+                    .unwrap_or_else(|_| "classify".to_string()),
+                // Containers get their hostname set, so a restarted pod keeps its name
+                consumer_name: env_var("QUEUE_CONSUMER_NAME")
+                    .or_else(|_| env_var("HOSTNAME"))
+                    .unwrap_or_else(|_| format!("worker-{}", uuid::Uuid::new_v4().simple())),
+            },
+            imap,
+            kafka,
+            mqtt,
+            telegram,
+            slack,
+            webhooks,
+            secrets_refresh: env_seconds("SECRETS_REFRESH_SECS")?,
+            logging: LoggingConfig::from_env()?,
         };
 
-        CONFIG.get_or_init(|| config);
-        Ok(CONFIG.get().unwrap())
+        // Report every missing or invalid setting at once, before the factories
+        // fail on the first one
+        config.validate()?;
+        Ok(config)
     }
 
     /// Get the application configuration
     pub fn get() -> Result<&'static Self, ClassifyError> {
         CONFIG
-            .get()
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
             .ok_or_else(|| ClassifyError::ConfigError("Configuration not initialized".to_string()))
     }
 
@@ -194,15 +1782,299 @@ impl AppConfig {
     }
 }
 
+/// Hash of a configured API key, given as the key itself or as `sha256:<hex digest>`
+/// so the key doesn't have to be kept in the environment
+fn key_hash(value: &str) -> String {
+    match value.strip_prefix("sha256:") {
+        Some(hash) => hash.trim().to_lowercase(),
+        None => crate::ApiKey::hash_key(value),
+    }
+}
+
+/// Parse an error rate given as a fraction like `0.2` or a percentage like `20%`,
+/// `None` for `off`
+pub fn parse_rate(name: &str, value: &str) -> Result<Option<f64>, ClassifyError> {
+    let value = value.trim();
+    if value.eq_ignore_ascii_case("off") {
+        return Ok(None);
+    }
+
+    let rate = match value.strip_suffix('%') {
+        Some(percent) => percent.trim().parse::<f64>().map(|percent| percent / 100.0),
+        None => value.parse::<f64>(),
+    }
+    .map_err(|e| ClassifyError::ConfigError(format!("Invalid {}: {}", name, e)))?;
+
+    if !(0.0..=1.0).contains(&rate) {
+        return Err(ClassifyError::ConfigError(format!(
+            "Invalid {}: {} is not between 0% and 100%",
+            name, value
+        )));
+    }
+    Ok(Some(rate))
+}
+
+/// Read a boolean flag from the environment, treating "true", "1" and "yes" as set
+fn env_flag(name: &str) -> bool {
+    env_var(name)
+        .map(|value| matches!(value.to_lowercase().as_str(), "true" | "1" | "yes"))
+        .unwrap_or(false)
+}
+
+/// Read an optional Redis database index from the environment
+fn env_db_index(name: &str) -> Result<Option<i64>, ClassifyError> {
+    env_var(name)
+        .ok()
+        .map(|value| {
+            value
+                .parse::<i64>()
+                .map_err(|e| ClassifyError::ConfigError(format!("Invalid {}: {}", name, e)))
+        })
+        .transpose()
+}
+
+/// Read an optional number of days from the environment
+fn env_days(name: &str) -> Result<Option<Duration>, ClassifyError> {
+    env_var(name)
+        .ok()
+        .map(|value| {
+            parse_days(&value)
+                .map_err(|e| ClassifyError::ConfigError(format!("Invalid {}: {}", name, e)))
+        })
+        .transpose()
+}
+
+fn parse_days(value: &str) -> Result<Duration, std::num::ParseIntError> {
+    Ok(Duration::from_secs(
+        value.trim().parse::<u64>()? * 24 * 60 * 60,
+    ))
+}
+
+/// Parse comma separated `tag=days` rules
+pub fn parse_tag_days(rules: &str) -> Result<HashMap<String, Duration>, ClassifyError> {
+    rules
+        .split(',')
+        .map(str::trim)
+        .filter(|rule| !rule.is_empty())
+        .map(|rule| {
+            let (tag, days) = rule.split_once('=').ok_or_else(|| {
+                ClassifyError::ConfigError(format!(
+                    "Invalid retention rule '{}', expected tag=days",
+                    rule
+                ))
+            })?;
+            let days = parse_days(days).map_err(|e| {
+                ClassifyError::ConfigError(format!("Invalid retention rule '{}': {}", rule, e))
+            })?;
+            Ok((tag.trim().to_string(), days))
+        })
+        .collect()
+}
+
+/// Read an optional interval in seconds from the environment, zero disables it
+fn env_seconds(name: &str) -> Result<Option<Duration>, ClassifyError> {
+    match env_var(name) {
+        Ok(value) => {
+            let secs = value
+                .parse::<u64>()
+                .map_err(|e| ClassifyError::ConfigError(format!("Invalid {}: {}", name, e)))?;
+            Ok((secs > 0).then(|| Duration::from_secs(secs)))
+        }
+        Err(_) => Ok(None),
+    }
+}
+
 impl FromStr for StorageType {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.to_lowercase().as_str() {
             "filesystem" => Ok(StorageType::Filesystem),
+            "memory" => Ok(StorageType::Memory),
+            "postgres" => Ok(StorageType::Postgres),
+            "dynamodb" => Ok(StorageType::DynamoDb),
             "redis" => Ok(StorageType::Redis),
             "s3" => Ok(StorageType::S3),
-            _ => Err(format!("Unknown storage type: {}", s)),
+            "sqlite" => Ok(StorageType::Sqlite),
+            "" => Err("Storage type must not be empty".to_string()),
+            name => Ok(StorageType::Custom(name.to_string())),
+        }
+    }
+}
+
+impl FromStr for StorageBackend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "redis" => Ok(StorageBackend::Redis),
+            "postgres" => Ok(StorageBackend::Postgres),
+            "memory" => Ok(StorageBackend::Memory),
+            _ => Err(format!("Unknown storage backend: {}", s)),
+        }
+    }
+}
+
+impl FromStr for RouteClass {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "classify" => Ok(RouteClass::Classify),
+            "query" => Ok(RouteClass::Query),
+            _ => Err(format!("Unknown route class: {}", s)),
+        }
+    }
+}
+
+impl FromStr for RateLimit {
+    type Err = String;
+
+    /// Parse `<requests>/<seconds>`, such as `10/60` for ten requests a minute
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (requests, seconds) = s
+            .split_once('/')
+            .ok_or_else(|| format!("Expected <requests>/<seconds>, got {}", s))?;
+        let requests = requests
+            .trim()
+            .parse::<u32>()
+            .map_err(|e| format!("Invalid request count: {}", e))?;
+        let seconds = seconds
+            .trim()
+            .parse::<u64>()
+            .map_err(|e| format!("Invalid period: {}", e))?;
+        if requests == 0 || seconds == 0 {
+            return Err("Request count and period must be positive".to_string());
+        }
+
+        Ok(RateLimit {
+            requests,
+            period: Duration::from_secs(seconds),
+        })
+    }
+}
+
+impl FromStr for RateLimitStoreType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "memory" => Ok(RateLimitStoreType::Memory),
+            "redis" => Ok(RateLimitStoreType::Redis),
+            _ => Err(format!("Unknown rate limit store: {}", s)),
+        }
+    }
+}
+
+impl FromStr for DedupLockStoreType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "memory" => Ok(DedupLockStoreType::Memory),
+            "redis" => Ok(DedupLockStoreType::Redis),
+            _ => Err(format!("Unknown dedup lock store: {}", s)),
+        }
+    }
+}
+
+impl FromStr for TrendStoreType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "memory" => Ok(TrendStoreType::Memory),
+            "redis" => Ok(TrendStoreType::Redis),
+            _ => Err(format!("Unknown trend store: {}", s)),
+        }
+    }
+}
+
+impl FromStr for EmbeddingProvider {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "openai" => Ok(EmbeddingProvider::OpenAi),
+            _ => Err(format!("Unknown embedding provider: {}", s)),
+        }
+    }
+}
+
+impl FromStr for TagWordSeparator {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "keep" => Ok(TagWordSeparator::Keep),
+            "hyphen" | "kebab" => Ok(TagWordSeparator::Hyphen),
+            "space" => Ok(TagWordSeparator::Space),
+            "underscore" | "snake" => Ok(TagWordSeparator::Underscore),
+            _ => Err(format!("Unknown tag word separator: {}", s)),
+        }
+    }
+}
+
+impl FromStr for VectorStorageType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "memory" => Ok(VectorStorageType::Memory),
+            "redis" => Ok(VectorStorageType::Redis),
+            "postgres" | "pgvector" => Ok(VectorStorageType::Postgres),
+            "qdrant" => Ok(VectorStorageType::Qdrant),
+            _ => Err(format!("Unknown vector storage type: {}", s)),
+        }
+    }
+}
+
+impl FromStr for ActivityStoreType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "memory" => Ok(ActivityStoreType::Memory),
+            "redis" => Ok(ActivityStoreType::Redis),
+            _ => Err(format!("Unknown activity store: {}", s)),
+        }
+    }
+}
+
+impl FromStr for DigestPeriod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "day" | "daily" => Ok(DigestPeriod::Day),
+            "week" | "weekly" => Ok(DigestPeriod::Week),
+            _ => Err(format!("Unknown digest period: {}", s)),
+        }
+    }
+}
+
+impl FromStr for QueueType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "memory" => Ok(QueueType::Memory),
+            "redis" => Ok(QueueType::Redis),
+            _ => Err(format!("Unknown queue type: {}", s)),
+        }
+    }
+}
+
+impl FromStr for ApiKeyStorageType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "file" => Ok(ApiKeyStorageType::File),
+            "memory" => Ok(ApiKeyStorageType::Memory),
+            "redis" => Ok(ApiKeyStorageType::Redis),
+            "postgres" => Ok(ApiKeyStorageType::Postgres),
+            _ => Err(format!("Unknown API key storage type: {}", s)),
         }
     }
 }
@@ -212,8 +2084,24 @@ impl FromStr for TagStorageType {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.to_lowercase().as_str() {
+            "embedded" => Ok(TagStorageType::Embedded),
+            "memory" => Ok(TagStorageType::Memory),
+            "dynamodb" => Ok(TagStorageType::DynamoDb),
             "redis" => Ok(TagStorageType::Redis),
-            _ => Err(format!("Unknown tag storage type: {}", s)),
+            "postgres" => Ok(TagStorageType::Postgres),
+            "sqlite" => Ok(TagStorageType::Sqlite),
+            "" => Err("Tag storage type must not be empty".to_string()),
+            name => Ok(TagStorageType::Custom(name.to_string())),
+        }
+    }
+}
+
+impl ClassifierType {
+    /// Name the classifier is configured by
+    pub fn name(&self) -> String {
+        match self {
+            ClassifierType::Custom(name) => name.clone(),
+            other => format!("{:?}", other).to_lowercase(),
         }
     }
 }
@@ -225,7 +2113,69 @@ impl FromStr for ClassifierType {
         match s.to_lowercase().as_str() {
             "claude" => Ok(ClassifierType::Claude),
             "chatgpt" => Ok(ClassifierType::ChatGpt),
-            _ => Err(format!("Unknown classifier type: {}", s)),
+            "ensemble" => Ok(ClassifierType::Ensemble),
+            "" => Err("Classifier type must not be empty".to_string()),
+            name => Ok(ClassifierType::Custom(name.to_string())),
+        }
+    }
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(format!("Unknown log format: {}", s)),
+        }
+    }
+}
+
+impl FromStr for LogRotation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "daily" => Ok(LogRotation::Daily),
+            "hourly" => Ok(LogRotation::Hourly),
+            "size" => Ok(LogRotation::Size),
+            "never" => Ok(LogRotation::Never),
+            _ => Err(format!("Unknown log rotation: {}", s)),
+        }
+    }
+}
+
+impl HashAlgorithm {
+    /// Name the algorithm is configured by, also the prefix of its hashes
+    pub fn name(&self) -> &'static str {
+        match self {
+            HashAlgorithm::Sha256 => "sha256",
+            HashAlgorithm::Blake3 => "blake3",
+        }
+    }
+}
+
+impl FromStr for HashAlgorithm {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "sha256" => Ok(HashAlgorithm::Sha256),
+            "blake3" => Ok(HashAlgorithm::Blake3),
+            _ => Err(format!("Unknown hash algorithm: {}", s)),
+        }
+    }
+}
+
+impl FromStr for RefetchMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "record" => Ok(RefetchMode::Record),
+            "retag" => Ok(RefetchMode::Retag),
+            _ => Err(format!("Unknown refetch mode: {}", s)),
         }
     }
 }
@@ -0,0 +1,237 @@
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::env::VarError;
+use std::sync::RwLock;
+use tracing::info;
+
+use crate::config::file::{file_var, file_vars};
+use crate::config::AppConfig;
+use crate::{ClassifyError, ClassifyResult};
+
+/// Resolved values of the environment variables that reference a secret
+static RESOLVED: RwLock<BTreeMap<String, String>> = RwLock::new(BTreeMap::new());
+
+/// Secret kept outside of the environment, referenced by the value of a variable
+#[derive(Debug, Clone, PartialEq)]
+pub enum SecretReference {
+    /// `vault:<path>#<field>`, a field of a secret read from Vault at `VAULT_ADDR`
+    Vault { path: String, field: String },
+    /// `aws-sm:<secret id>` for the whole secret string, or `aws-sm:<secret id>#<field>`
+    /// for a field of a JSON secret
+    SecretsManager {
+        secret_id: String,
+        field: Option<String>,
+    },
+}
+
+impl SecretReference {
+    /// Parse a variable value, `None` for plain values
+    pub fn parse(value: &str) -> Option<Self> {
+        if let Some(reference) = value.strip_prefix("vault:") {
+            let (path, field) = reference.rsplit_once('#')?;
+            let path = path.trim_matches('/');
+            if path.is_empty() || field.is_empty() {
+                return None;
+            }
+            return Some(Self::Vault {
+                path: path.to_string(),
+                field: field.to_string(),
+            });
+        }
+
+        let reference = value.strip_prefix("aws-sm:")?;
+        let (secret_id, field) = match reference.rsplit_once('#') {
+            Some((secret_id, field)) => (secret_id, Some(field.to_string())),
+            None => (reference, None),
+        };
+        if secret_id.is_empty() || field.as_deref() == Some("") {
+            return None;
+        }
+        Some(Self::SecretsManager {
+            secret_id: secret_id.to_string(),
+            field,
+        })
+    }
+}
+
+/// Read a variable like `std::env::var`, falling back to the configuration file,
+/// with secret references replaced by the secret
+pub(crate) fn env_var(name: &str) -> Result<String, VarError> {
+    if let Some(value) = RESOLVED.read().unwrap_or_else(|e| e.into_inner()).get(name) {
+        return Ok(value.clone());
+    }
+
+    std::env::var(name).or_else(|e| file_var(name).ok_or(e))
+}
+
+/// Variables set in the environment, and in the configuration file where the
+/// environment doesn't set them
+fn raw_vars() -> BTreeMap<String, String> {
+    let mut vars = file_vars();
+    vars.extend(std::env::vars());
+    vars
+}
+
+/// All variables like `std::env::vars`, with secret references replaced by the secret
+pub(crate) fn env_vars() -> impl Iterator<Item = (String, String)> {
+    let resolved = RESOLVED.read().unwrap_or_else(|e| e.into_inner()).clone();
+
+    raw_vars()
+        .into_iter()
+        .map(move |(name, value)| match resolved.get(&name) {
+            Some(secret) => (name, secret.clone()),
+            None => (name, value),
+        })
+}
+
+/// Field of a Vault secret, under `data.data` for the KV version 2 engine and
+/// under `data` for version 1
+pub fn vault_field(response: &Value, field: &str) -> Option<String> {
+    let data = &response["data"];
+    let value = data["data"].get(field).or_else(|| data.get(field))?;
+    match value {
+        Value::String(value) => Some(value.clone()),
+        Value::Null => None,
+        value => Some(value.to_string()),
+    }
+}
+
+/// Field of a JSON secret string, or the whole string without a field
+pub fn secret_field(secret: &str, field: Option<&str>) -> Option<String> {
+    let Some(field) = field else {
+        return Some(secret.to_string());
+    };
+
+    let json: Value = serde_json::from_str(secret).ok()?;
+    match json.get(field)? {
+        Value::String(value) => Some(value.clone()),
+        Value::Null => None,
+        value => Some(value.to_string()),
+    }
+}
+
+async fn fetch_vault(
+    http_client: &reqwest::Client,
+    path: &str,
+    field: &str,
+) -> ClassifyResult<String> {
+    let addr = std::env::var("VAULT_ADDR").map_err(|_| {
+        ClassifyError::ConfigError("VAULT_ADDR is required for vault: references".to_string())
+    })?;
+    let token = std::env::var("VAULT_TOKEN").map_err(|_| {
+        ClassifyError::ConfigError("VAULT_TOKEN is required for vault: references".to_string())
+    })?;
+
+    let mut request = http_client
+        .get(format!("{}/v1/{}", addr.trim_end_matches('/'), path))
+        .header("X-Vault-Token", token);
+    if let Ok(namespace) = std::env::var("VAULT_NAMESPACE") {
+        request = request.header("X-Vault-Namespace", namespace);
+    }
+
+    let response: Value = request
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| {
+            ClassifyError::HttpError(format!("Failed to read Vault secret {}: {}", path, e))
+        })?
+        .json()
+        .await
+        .map_err(|e| {
+            ClassifyError::HttpError(format!("Failed to parse Vault secret {}: {}", path, e))
+        })?;
+
+    vault_field(&response, field).ok_or_else(|| {
+        ClassifyError::ConfigError(format!("Vault secret {} has no field {}", path, field))
+    })
+}
+
+#[cfg(feature = "secretsmanager")]
+async fn fetch_secrets_manager(
+    client: &aws_sdk_secretsmanager::Client,
+    secret_id: &str,
+    field: Option<&str>,
+) -> ClassifyResult<String> {
+    let output = client
+        .get_secret_value()
+        .secret_id(secret_id)
+        .send()
+        .await
+        .map_err(|e| {
+            ClassifyError::ConfigError(format!("Failed to read secret {}: {}", secret_id, e))
+        })?;
+
+    let secret = output.secret_string().ok_or_else(|| {
+        ClassifyError::ConfigError(format!("Secret {} is not a string", secret_id))
+    })?;
+
+    secret_field(secret, field).ok_or_else(|| {
+        ClassifyError::ConfigError(format!(
+            "Secret {} has no field {}",
+            secret_id,
+            field.unwrap_or_default()
+        ))
+    })
+}
+
+/// Fetch the secrets referenced by environment variables, returning whether any
+/// of them changed since they were last fetched
+pub async fn resolve_secrets() -> ClassifyResult<bool> {
+    let references: Vec<(String, SecretReference)> = raw_vars()
+        .into_iter()
+        .filter_map(|(name, value)| SecretReference::parse(&value).map(|r| (name, r)))
+        .collect();
+
+    if references.is_empty() {
+        return Ok(false);
+    }
+
+    let http_client = reqwest::Client::new();
+    #[cfg(feature = "secretsmanager")]
+    let mut secrets_manager = None;
+
+    let mut resolved = BTreeMap::new();
+    for (name, reference) in references {
+        let value = match &reference {
+            SecretReference::Vault { path, field } => {
+                fetch_vault(&http_client, path, field).await?
+            }
+            #[cfg(feature = "secretsmanager")]
+            SecretReference::SecretsManager { secret_id, field } => {
+                if secrets_manager.is_none() {
+                    let config = aws_config::from_env().load().await;
+                    secrets_manager = Some(aws_sdk_secretsmanager::Client::new(&config));
+                }
+                let client = secrets_manager.as_ref().unwrap();
+                fetch_secrets_manager(client, secret_id, field.as_deref()).await?
+            }
+            #[cfg(not(feature = "secretsmanager"))]
+            SecretReference::SecretsManager { .. } => {
+                return Err(ClassifyError::ConfigError(format!(
+                    "{} references AWS Secrets Manager, which requires building with the secretsmanager feature",
+                    name
+                )))
+            }
+        };
+        resolved.insert(name, value);
+    }
+
+    let mut current = RESOLVED.write().unwrap_or_else(|e| e.into_inner());
+    let changed = *current != resolved;
+    if changed {
+        info!("Resolved {} secrets", resolved.len());
+        *current = resolved;
+    }
+
+    Ok(changed)
+}
+
+/// Fetch the referenced secrets again and reload the configuration when they changed
+pub async fn refresh_secrets() -> ClassifyResult<()> {
+    if resolve_secrets().await? {
+        AppConfig::reload()?;
+    }
+
+    Ok(())
+}
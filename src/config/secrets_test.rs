@@ -0,0 +1,61 @@
+use crate::config::secrets::{secret_field, vault_field, SecretReference};
+use serde_json::json;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_references() {
+        assert_eq!(
+            SecretReference::parse("vault:secret/data/classify#api_key"),
+            Some(SecretReference::Vault {
+                path: "secret/data/classify".to_string(),
+                field: "api_key".to_string(),
+            })
+        );
+        assert_eq!(
+            SecretReference::parse("aws-sm:prod/classify#redis_password"),
+            Some(SecretReference::SecretsManager {
+                secret_id: "prod/classify".to_string(),
+                field: Some("redis_password".to_string()),
+            })
+        );
+        assert_eq!(
+            SecretReference::parse("aws-sm:arn:aws:secretsmanager:eu-west-1:123:secret:key"),
+            Some(SecretReference::SecretsManager {
+                secret_id: "arn:aws:secretsmanager:eu-west-1:123:secret:key".to_string(),
+                field: None,
+            })
+        );
+    }
+
+    #[test]
+    fn test_plain_and_incomplete_values_are_not_references() {
+        assert_eq!(SecretReference::parse("plain-api-key"), None);
+        assert_eq!(SecretReference::parse("vault:secret/data/classify"), None);
+        assert_eq!(SecretReference::parse("vault:#api_key"), None);
+        assert_eq!(SecretReference::parse("aws-sm:prod/classify#"), None);
+    }
+
+    #[test]
+    fn test_vault_field_of_kv_v1_and_v2() {
+        let v2 = json!({"data": {"data": {"api_key": "secret", "port": 6379}, "metadata": {}}});
+        assert_eq!(vault_field(&v2, "api_key").as_deref(), Some("secret"));
+        assert_eq!(vault_field(&v2, "port").as_deref(), Some("6379"));
+        assert_eq!(vault_field(&v2, "missing"), None);
+
+        let v1 = json!({"data": {"api_key": "secret"}});
+        assert_eq!(vault_field(&v1, "api_key").as_deref(), Some("secret"));
+    }
+
+    #[test]
+    fn test_secret_field() {
+        assert_eq!(secret_field("plain", None).as_deref(), Some("plain"));
+        assert_eq!(
+            secret_field(r#"{"password": "hunter2"}"#, Some("password")).as_deref(),
+            Some("hunter2")
+        );
+        assert_eq!(secret_field("not json", Some("password")), None);
+    }
+}
@@ -0,0 +1,291 @@
+use std::fmt;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use crate::classifier::{create_classifier, Classifier};
+use crate::config::AppConfig;
+use crate::storage::{
+    create_api_key_storage, create_content_storage, create_shared_storage, create_tag_storage,
+    ContentStorage, TagStorage,
+};
+use crate::{ClassifyError, ClassifyResult, Content};
+
+/// Longest a single check may take, connecting to an unreachable host can
+/// otherwise hang for minutes
+const CHECK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Tag stored and removed again by the storage round-trip
+const SELF_TEST_TAG: &str = "classify-doctor";
+
+/// Outcome of a single check
+#[derive(Debug, Clone)]
+pub struct Check {
+    pub name: String,
+    pub passed: bool,
+    /// What was found, or why the check failed
+    pub detail: String,
+    pub elapsed: Duration,
+}
+
+/// Checks run by `classify doctor`, in the order they ran
+#[derive(Debug, Clone, Default)]
+pub struct Report {
+    pub checks: Vec<Check>,
+}
+
+impl Report {
+    /// Whether every check passed
+    pub fn passed(&self) -> bool {
+        self.checks.iter().all(|check| check.passed)
+    }
+
+    /// Run a check and record its outcome, returning its value when it passed
+    pub async fn check<T, F>(&mut self, name: &str, check: F) -> Option<T>
+    where
+        T: fmt::Display,
+        F: Future<Output = ClassifyResult<T>>,
+    {
+        let started = Instant::now();
+        let result = match tokio::time::timeout(CHECK_TIMEOUT, check).await {
+            Ok(result) => result,
+            Err(_) => Err(ClassifyError::StorageError(format!(
+                "No answer within {}s",
+                CHECK_TIMEOUT.as_secs()
+            ))),
+        };
+
+        let (passed, detail, value) = match result {
+            Ok(value) => (true, value.to_string(), Some(value)),
+            Err(e) => (false, e.to_string(), None),
+        };
+        self.checks.push(Check {
+            name: name.to_string(),
+            passed,
+            detail,
+            elapsed: started.elapsed(),
+        });
+        value
+    }
+
+    fn skip(&mut self, name: &str, reason: &str) {
+        self.checks.push(Check {
+            name: name.to_string(),
+            passed: false,
+            detail: format!("Skipped, {}", reason),
+            elapsed: Duration::ZERO,
+        });
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for check in &self.checks {
+            writeln!(
+                f,
+                "[{}] {:<22} {:>6}ms  {}",
+                if check.passed { "PASS" } else { "FAIL" },
+                check.name,
+                check.elapsed.as_millis(),
+                check.detail
+            )?;
+        }
+
+        let failed = self.checks.iter().filter(|check| !check.passed).count();
+        if failed == 0 {
+            write!(f, "All {} checks passed", self.checks.len())
+        } else {
+            write!(f, "{} of {} checks failed", failed, self.checks.len())
+        }
+    }
+}
+
+/// Store, read back and delete a content item, and add, read back and remove
+/// a tag on it. The item is deleted even when a later step fails.
+pub async fn storage_round_trip(
+    content_storage: &dyn ContentStorage,
+    tag_storage: &dyn TagStorage,
+    report: &mut Report,
+) {
+    let content = Content::new(format!(
+        "classify doctor self-test {}",
+        uuid::Uuid::new_v4()
+    ));
+    let id = content.id.to_string();
+
+    let stored = report
+        .check("content round-trip", async {
+            content_storage.store(&content).await?;
+            match content_storage.get(&id).await? {
+                Some(read) if read.content == content.content => Ok("stored and read back"),
+                Some(_) => Err(ClassifyError::StorageError(
+                    "Read back different content".to_string(),
+                )),
+                None => Err(ClassifyError::StorageError(
+                    "Stored content not found".to_string(),
+                )),
+            }
+        })
+        .await
+        .is_some();
+
+    if stored {
+        let tags = vec![SELF_TEST_TAG.to_string()];
+        report
+            .check("tag round-trip", async {
+                tag_storage.add_tags(&id, &tags).await?;
+                let found = tag_storage.get_tags(&id).await?;
+                tag_storage.remove_tags(&id, &tags).await?;
+                if found.contains(&tags[0]) {
+                    Ok("added, read back and removed")
+                } else {
+                    Err(ClassifyError::StorageError(
+                        "Added tag not found".to_string(),
+                    ))
+                }
+            })
+            .await;
+    } else {
+        report.skip("tag round-trip", "content round-trip failed");
+    }
+
+    report
+        .check("content cleanup", async {
+            content_storage.delete(&id).await?;
+            match content_storage.get(&id).await? {
+                None => Ok("deleted"),
+                Some(_) => Err(ClassifyError::StorageError(
+                    "Content still present after delete".to_string(),
+                )),
+            }
+        })
+        .await;
+}
+
+/// Check the classifier. A dry run only checks that the provider accepts the
+/// API key, a full check classifies a short text, which costs a few tokens.
+pub async fn check_classifier(classifier: &dyn Classifier, dry_run: bool, report: &mut Report) {
+    if dry_run {
+        report
+            .check("classifier", async {
+                classifier.health_check().await?;
+                Ok("reachable, dry run")
+            })
+            .await;
+    } else {
+        report
+            .check("classifier", async {
+                let tags = classifier
+                    .classify("Rust is a systems programming language focused on safety.")
+                    .await?;
+                if tags.is_empty() {
+                    Err(ClassifyError::ClassificationError(
+                        "Classification returned no tags".to_string(),
+                    ))
+                } else {
+                    Ok(format!("tags: {}", tags.join(", ")))
+                }
+            })
+            .await;
+    }
+}
+
+/// Check the configuration, the storage backends and the classifier, the way
+/// the server would use them
+pub async fn run(dry_run: bool) -> Report {
+    let mut report = Report::default();
+
+    let Some(Connected(_, config)) = report
+        .check("configuration", async {
+            AppConfig::load()
+                .await
+                .map(|config| Connected("loaded".to_string(), config))
+        })
+        .await
+    else {
+        return report;
+    };
+
+    let storages = match &config.storage_backend {
+        Some(backend) => report
+            .check("storage", async {
+                create_shared_storage(backend, config)
+                    .await
+                    .map(|storage| Connected(format!("{:?}", backend), storage))
+            })
+            .await
+            .map(|Connected(_, storage)| (storage.content_storage, storage.tag_storage)),
+        None => {
+            let content_storage = report
+                .check("content storage", async {
+                    create_content_storage(&config.storage.storage_type, &config.storage)
+                        .await
+                        .map(|storage| {
+                            Connected(format!("{:?}", config.storage.storage_type), storage)
+                        })
+                })
+                .await;
+            let tag_storage = report
+                .check("tag storage", async {
+                    create_tag_storage(&config.tag_storage.tag_storage_type, &config.tag_storage)
+                        .await
+                        .map(|storage| {
+                            Connected(
+                                format!("{:?}", config.tag_storage.tag_storage_type),
+                                storage,
+                            )
+                        })
+                })
+                .await;
+            content_storage.zip(tag_storage).map(
+                |(Connected(_, content_storage), Connected(_, tag_storage))| {
+                    (content_storage, tag_storage)
+                },
+            )
+        }
+    };
+
+    match storages {
+        Some((content_storage, tag_storage)) => {
+            storage_round_trip(content_storage.as_ref(), tag_storage.as_ref(), &mut report).await
+        }
+        None => report.skip("storage round-trip", "storage not connected"),
+    }
+
+    report
+        .check("api key storage", async {
+            let storage = create_api_key_storage(config).await?;
+            let keys = storage.list().await?;
+            Ok(format!("{:?}, {} keys", config.api.key_storage, keys.len()))
+        })
+        .await;
+
+    let classifier = report
+        .check("classifier setup", async {
+            create_classifier(&config.classifier.classifier_type, &config.classifier)
+                .await
+                .map(|classifier| {
+                    Connected(
+                        format!("{:?}", config.classifier.classifier_type),
+                        classifier,
+                    )
+                })
+        })
+        .await;
+    match classifier {
+        Some(Connected(_, classifier)) => {
+            check_classifier(classifier.as_ref(), dry_run, &mut report).await
+        }
+        None => report.skip("classifier", "classifier setup failed"),
+    }
+
+    report
+}
+
+/// A backend that was set up, shown by its name in the report
+struct Connected<T>(String, T);
+
+impl<T> fmt::Display for Connected<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
@@ -0,0 +1,71 @@
+use crate::classifier::Classifier;
+use crate::doctor::{check_classifier, storage_round_trip, Report};
+use crate::{ClassifyError, ClassifyResult};
+use mockall::mock;
+
+mock! {
+    pub ClassifierMock {}
+
+    #[async_trait::async_trait]
+    impl Classifier for ClassifierMock {
+        async fn classify(&self, content: &str) -> ClassifyResult<Vec<String>>;
+        async fn classify_url(&self, url: &str) -> ClassifyResult<Vec<String>>;
+        async fn health_check(&self) -> ClassifyResult<()>;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::content::memory::MemoryContentStorage;
+    use crate::storage::tag::memory::MemoryTagStorage;
+    use crate::storage::{ContentStorage, TagStorage};
+
+    #[tokio::test]
+    async fn test_storage_round_trip_leaves_nothing_behind() {
+        let content_storage = MemoryContentStorage::new();
+        let tag_storage = MemoryTagStorage::new();
+        let mut report = Report::default();
+
+        storage_round_trip(&content_storage, &tag_storage, &mut report).await;
+
+        let names: Vec<&str> = report.checks.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["content round-trip", "tag round-trip", "content cleanup"]
+        );
+        assert!(report.passed(), "{}", report);
+        assert!(content_storage.list().await.unwrap().is_empty());
+        assert!(tag_storage.list_tags().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_classifier_failure_fails_report() {
+        let mut classifier = MockClassifierMock::new();
+        classifier.expect_classify().returning(|_| {
+            Err(ClassifyError::ClassificationError(
+                "invalid key".to_string(),
+            ))
+        });
+        let mut report = Report::default();
+
+        check_classifier(&classifier, false, &mut report).await;
+
+        assert!(!report.passed());
+        assert!(report.to_string().contains("[FAIL] classifier"));
+        assert!(report.to_string().contains("invalid key"));
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_does_not_classify() {
+        let mut classifier = MockClassifierMock::new();
+        classifier.expect_classify().never();
+        classifier.expect_health_check().returning(|| Ok(()));
+        let mut report = Report::default();
+
+        check_classifier(&classifier, true, &mut report).await;
+
+        assert!(report.passed());
+        assert!(report.to_string().ends_with("All 1 checks passed"));
+    }
+}
@@ -0,0 +1,236 @@
+use std::collections::HashMap;
+use tracing::{info, warn};
+
+use crate::api::AppState;
+use crate::jobs::digest::title;
+use crate::storage::vector::cosine_similarity;
+use crate::{ClassifyError, ClassifyResult, ClusterRequest, Content, ContentCluster};
+
+/// Rounds of k-means at most, it usually settles much sooner
+const MAX_ITERATIONS: usize = 50;
+
+/// Clusters proposed when the request doesn't say how many
+const MAX_DEFAULT_CLUSTERS: usize = 20;
+
+/// Items closest to the middle of a cluster sent to the classifier to name it
+const LABEL_SAMPLE: usize = 10;
+
+/// Vectors grouped by k-means
+#[derive(Debug, Clone, PartialEq)]
+pub struct Clustering {
+    /// Cluster of each vector, in the order of the input
+    pub assignments: Vec<usize>,
+    /// Middle of each cluster, as a unit vector
+    pub centroids: Vec<Vec<f32>>,
+}
+
+fn normalized(vector: &[f32]) -> Vec<f32> {
+    let norm = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm == 0.0 {
+        return vector.to_vec();
+    }
+    vector.iter().map(|x| x / norm).collect()
+}
+
+fn distance(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum()
+}
+
+fn nearest_centroid(vector: &[f32], centroids: &[Vec<f32>]) -> usize {
+    centroids
+        .iter()
+        .enumerate()
+        .min_by(|(_, a), (_, b)| distance(vector, a).total_cmp(&distance(vector, b)))
+        .map(|(index, _)| index)
+        .unwrap_or_default()
+}
+
+/// Group vectors into at most `k` clusters with k-means on unit vectors, so
+/// that closeness follows cosine similarity. The first centroids are the
+/// vectors farthest apart, which makes the outcome repeatable.
+pub fn kmeans(vectors: &[Vec<f32>], k: usize) -> Clustering {
+    let vectors: Vec<Vec<f32>> = vectors.iter().map(|vector| normalized(vector)).collect();
+    let k = k.min(vectors.len());
+    if k == 0 {
+        return Clustering {
+            assignments: vec![0; vectors.len()],
+            centroids: Vec::new(),
+        };
+    }
+
+    let mut centroids = vec![vectors[0].clone()];
+    while centroids.len() < k {
+        let farthest = vectors
+            .iter()
+            .max_by(|a, b| {
+                let closest = |vector: &Vec<f32>| {
+                    centroids
+                        .iter()
+                        .map(|centroid| distance(vector, centroid))
+                        .fold(f32::INFINITY, f32::min)
+                };
+                closest(a).total_cmp(&closest(b))
+            })
+            .cloned()
+            .unwrap_or_default();
+        centroids.push(farthest);
+    }
+
+    let mut assignments = vec![usize::MAX; vectors.len()];
+    for _ in 0..MAX_ITERATIONS {
+        let next: Vec<usize> = vectors
+            .iter()
+            .map(|vector| nearest_centroid(vector, &centroids))
+            .collect();
+        if next == assignments {
+            break;
+        }
+        assignments = next;
+
+        for (cluster, centroid) in centroids.iter_mut().enumerate() {
+            let members: Vec<&Vec<f32>> = vectors
+                .iter()
+                .zip(&assignments)
+                .filter(|(_, assigned)| **assigned == cluster)
+                .map(|(vector, _)| vector)
+                .collect();
+            // An empty cluster keeps its centroid
+            if members.is_empty() {
+                continue;
+            }
+            let mut sum = vec![0.0; centroid.len()];
+            for member in &members {
+                for (total, x) in sum.iter_mut().zip(member.iter()) {
+                    *total += x;
+                }
+            }
+            *centroid = normalized(&sum);
+        }
+    }
+
+    Clustering {
+        assignments,
+        centroids,
+    }
+}
+
+/// Clusters for `count` items when the request doesn't say: about the square
+/// root of half the items
+pub fn default_clusters(count: usize) -> usize {
+    (((count / 2) as f64).sqrt().round() as usize).clamp(2, MAX_DEFAULT_CLUSTERS)
+}
+
+/// Text sent to the classifier to name a cluster
+pub fn label_prompt(titles: &[String]) -> String {
+    let mut prompt = String::from("A collection of saved items with these titles:\n");
+    for title in titles {
+        prompt.push_str(&format!("- {}\n", title));
+    }
+    prompt
+}
+
+/// Cluster the embedded content of the tenant, name every cluster with the
+/// classifier and, when asked, add the name as a tag to its content. Returns
+/// the clusters, the largest first, and the number of items tagged.
+pub async fn cluster_content(
+    state: &AppState,
+    request: &ClusterRequest,
+) -> ClassifyResult<(Vec<ContentCluster>, usize)> {
+    let index = state.semantic_index.as_ref().ok_or_else(|| {
+        ClassifyError::ConfigError("Clustering requires EMBEDDING_PROVIDER".to_string())
+    })?;
+
+    let embeddings = index.embeddings(state.namespace.as_deref()).await?;
+    let ids: Vec<String> = embeddings.iter().map(|(id, _)| id.clone()).collect();
+    let mut contents: HashMap<String, Content> = state
+        .content_storage
+        .get_many(&ids)
+        .await?
+        .into_iter()
+        .map(|content| (content.id.to_string(), content))
+        .collect();
+
+    // Embeddings of content deleted since it was embedded are left out
+    let (ids, vectors): (Vec<String>, Vec<Vec<f32>>) = embeddings
+        .into_iter()
+        .filter(|(id, _)| contents.contains_key(id))
+        .map(|(id, embedding)| (id, embedding.vector))
+        .unzip();
+    if ids.is_empty() {
+        return Ok((Vec::new(), 0));
+    }
+
+    let k = request
+        .clusters
+        .unwrap_or_else(|| default_clusters(ids.len()))
+        .max(1);
+    let clustering = kmeans(&vectors, k);
+    info!(
+        "Grouped {} items into {} clusters",
+        ids.len(),
+        clustering.centroids.len()
+    );
+
+    let mut clusters = Vec::new();
+    let mut tagged = 0;
+    for (cluster, centroid) in clustering.centroids.iter().enumerate() {
+        // Members closest to the middle of the cluster first
+        let mut members: Vec<(&String, f32)> = ids
+            .iter()
+            .zip(&vectors)
+            .zip(&clustering.assignments)
+            .filter(|(_, assigned)| **assigned == cluster)
+            .map(|((id, vector), _)| (id, cosine_similarity(vector, centroid)))
+            .collect();
+        if members.is_empty() {
+            continue;
+        }
+        members.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        let titles: Vec<String> = members
+            .iter()
+            .take(LABEL_SAMPLE)
+            .filter_map(|(id, _)| contents.get(*id).map(title))
+            .collect();
+        let label = match state.classifier.classify(&label_prompt(&titles)).await {
+            Ok(tags) => state.ingest.tags.apply(&tags).into_iter().next(),
+            Err(e) => {
+                warn!("Failed to name cluster {}: {}", cluster, e);
+                None
+            }
+        };
+
+        let content_ids: Vec<String> = members.iter().map(|(id, _)| (*id).clone()).collect();
+        if let Some(label) = label.as_ref().filter(|_| request.apply) {
+            for id in &content_ids {
+                let Some(content) = contents.remove(id) else {
+                    continue;
+                };
+                if content.tags.contains(label) {
+                    continue;
+                }
+                state
+                    .tag_storage
+                    .add_tags(id, std::slice::from_ref(label))
+                    .await?;
+                let mut tags = content.tags.clone();
+                tags.push(label.clone());
+                state
+                    .content_storage
+                    .store(&content.with_tags(tags))
+                    .await?;
+                tagged += 1;
+            }
+        }
+
+        clusters.push(ContentCluster {
+            label,
+            size: content_ids.len(),
+            titles,
+            content_ids,
+        });
+    }
+
+    clusters.sort_by_key(|cluster| std::cmp::Reverse(cluster.size));
+    Ok((clusters, tagged))
+}
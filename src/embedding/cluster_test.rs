@@ -0,0 +1,46 @@
+use crate::embedding::cluster::{default_clusters, kmeans, label_prompt};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kmeans_separates_groups() {
+        let vectors = vec![
+            vec![1.0, 0.1, 0.0],
+            vec![0.0, 1.0, 0.1],
+            vec![0.9, 0.0, 0.1],
+            vec![0.1, 0.9, 0.0],
+            vec![2.0, 0.2, 0.1],
+        ];
+
+        let clustering = kmeans(&vectors, 2);
+        assert_eq!(clustering.centroids.len(), 2);
+        let a = clustering.assignments[0];
+        let b = clustering.assignments[1];
+        assert_ne!(a, b);
+        assert_eq!(clustering.assignments, vec![a, b, a, b, a]);
+    }
+
+    #[test]
+    fn test_kmeans_with_fewer_vectors_than_clusters() {
+        let clustering = kmeans(&[vec![1.0, 0.0], vec![0.0, 1.0]], 5);
+        assert_eq!(clustering.centroids.len(), 2);
+        assert_ne!(clustering.assignments[0], clustering.assignments[1]);
+
+        assert!(kmeans(&[], 3).assignments.is_empty());
+    }
+
+    #[test]
+    fn test_default_clusters() {
+        assert_eq!(default_clusters(3), 2);
+        assert_eq!(default_clusters(200), 10);
+        assert_eq!(default_clusters(100_000), 20);
+    }
+
+    #[test]
+    fn test_label_prompt_lists_titles() {
+        let prompt = label_prompt(&["Fresh pasta".to_string(), "Risotto".to_string()]);
+        assert!(prompt.ends_with("- Fresh pasta\n- Risotto\n"));
+    }
+}
@@ -0,0 +1,118 @@
+use std::collections::HashSet;
+use std::future::Future;
+use std::sync::Arc;
+use tracing::warn;
+
+use crate::embedding::Embedder;
+use crate::storage::vector::StoredVector;
+use crate::storage::VectorStorage;
+use crate::{ClassifyResult, Content};
+
+/// Text that is embedded for content: the text it was classified from,
+/// followed by its tags
+pub fn embedding_text(text: &str, tags: &[String]) -> String {
+    if tags.is_empty() {
+        return text.trim().to_string();
+    }
+    format!("{}\n\nTags: {}", text.trim(), tags.join(", "))
+}
+
+/// Embeddings of stored content, to find content by meaning
+pub struct SemanticIndex {
+    embedder: Arc<dyn Embedder>,
+    store: Arc<dyn VectorStorage>,
+}
+
+impl SemanticIndex {
+    pub fn new(embedder: Arc<dyn Embedder>, store: Arc<dyn VectorStorage>) -> Self {
+        Self { embedder, store }
+    }
+
+    /// Embed content from the text it was classified from
+    pub async fn index(&self, content: &Content, text: &str) -> ClassifyResult<()> {
+        let vector = self
+            .embedder
+            .embed(&embedding_text(text, &content.tags))
+            .await?;
+        self.store
+            .store(
+                &content.id.to_string(),
+                &StoredVector {
+                    namespace: content.namespace.clone(),
+                    vector,
+                },
+            )
+            .await
+    }
+
+    /// Forget the embedding of deleted content. Failures are logged, the search
+    /// skips content that no longer exists.
+    pub async fn remove(&self, id: &str) {
+        if let Err(e) = self.store.delete(id).await {
+            warn!("Failed to delete the embedding of content {}: {}", id, e);
+        }
+    }
+
+    /// Vector of free text, e.g. to compare tags by meaning
+    pub async fn embed(&self, text: &str) -> ClassifyResult<Vec<f32>> {
+        self.embedder.embed(text).await
+    }
+
+    /// IDs of the content closest in meaning to `query`, the most similar first
+    pub async fn search(
+        &self,
+        query: &str,
+        namespace: Option<&str>,
+        limit: usize,
+    ) -> ClassifyResult<Vec<(String, f32)>> {
+        let vector = self.embedder.embed(query).await?;
+        self.store.nearest(&vector, namespace, limit).await
+    }
+
+    /// All embeddings, only of the tenant when `namespace` is set
+    pub async fn embeddings(
+        &self,
+        namespace: Option<&str>,
+    ) -> ClassifyResult<Vec<(String, StoredVector)>> {
+        self.store.vectors(namespace).await
+    }
+
+    /// Embed the content that has no embedding yet, with the text from
+    /// `text_of`, and drop the embeddings of content that was deleted. Content
+    /// that fails to embed is tried again on the next run. Returns the number
+    /// of items embedded and embeddings dropped.
+    pub async fn backfill<F, Fut>(
+        &self,
+        contents: &[Content],
+        text_of: F,
+    ) -> ClassifyResult<(usize, usize)>
+    where
+        F: Fn(Content) -> Fut,
+        Fut: Future<Output = String>,
+    {
+        let embedded_ids = self.store.ids().await?;
+        let stored_ids: HashSet<String> = contents
+            .iter()
+            .map(|content| content.id.to_string())
+            .collect();
+
+        let mut dropped = 0;
+        for id in embedded_ids.difference(&stored_ids) {
+            self.store.delete(id).await?;
+            dropped += 1;
+        }
+
+        let mut embedded = 0;
+        for content in contents {
+            if embedded_ids.contains(&content.id.to_string()) {
+                continue;
+            }
+            match self.index(content, &text_of(content.clone()).await).await {
+                Ok(()) => embedded += 1,
+                Err(e) => warn!("Failed to embed content {}: {}", content.id, e),
+            }
+        }
+
+        Ok((embedded, dropped))
+    }
+}
@@ -0,0 +1,101 @@
+use crate::embedding::index::{embedding_text, SemanticIndex};
+use crate::embedding::Embedder;
+use crate::storage::vector::memory::MemoryVectorStorage;
+use crate::storage::VectorStorage;
+use crate::{ClassifyError, ClassifyResult, Content};
+use async_trait::async_trait;
+use std::sync::Arc;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOPICS: [&str; 3] = ["rust", "cooking", "travel"];
+
+    /// Embeds text by how often it mentions each topic
+    struct TopicEmbedder;
+
+    #[async_trait]
+    impl Embedder for TopicEmbedder {
+        async fn embed(&self, text: &str) -> ClassifyResult<Vec<f32>> {
+            if text.contains("unreachable") {
+                return Err(ClassifyError::EmbeddingError(
+                    "Provider is down".to_string(),
+                ));
+            }
+            let text = text.to_lowercase();
+            Ok(TOPICS
+                .iter()
+                .map(|topic| text.matches(topic).count() as f32)
+                .collect())
+        }
+    }
+
+    fn content(text: &str, tags: &[&str]) -> Content {
+        Content::new(text.to_string()).with_tags(tags.iter().map(|tag| tag.to_string()).collect())
+    }
+
+    #[test]
+    fn test_embedding_text_appends_tags() {
+        assert_eq!(
+            embedding_text("  Some text\n", &["rust".to_string(), "async".to_string()]),
+            "Some text\n\nTags: rust, async"
+        );
+        assert_eq!(embedding_text("Some text", &[]), "Some text");
+    }
+
+    #[tokio::test]
+    async fn test_search_finds_content_by_meaning() {
+        let index = SemanticIndex::new(
+            Arc::new(TopicEmbedder),
+            Arc::new(MemoryVectorStorage::new()),
+        );
+        let rust = content("Borrowing explained", &["rust"]);
+        let pasta = content("Fresh pasta at home", &["cooking"]);
+        index.index(&rust, &rust.content).await.unwrap();
+        index.index(&pasta, &pasta.content).await.unwrap();
+
+        let found = index.search("cooking dinner", None, 10).await.unwrap();
+        assert_eq!(found[0].0, pasta.id.to_string());
+
+        index.remove(&pasta.id.to_string()).await;
+        let found = index.search("cooking dinner", None, 10).await.unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].0, rust.id.to_string());
+    }
+
+    #[tokio::test]
+    async fn test_backfill_embeds_missing_and_drops_deleted() {
+        let store = Arc::new(MemoryVectorStorage::new());
+        let index = SemanticIndex::new(Arc::new(TopicEmbedder), store.clone());
+
+        let kept = content("Ownership in Rust", &["rust"]);
+        let deleted = content("Lisbon in spring", &["travel"]);
+        index.index(&kept, &kept.content).await.unwrap();
+        index.index(&deleted, &deleted.content).await.unwrap();
+
+        let added = content("Weeknight cooking", &["cooking"]);
+        let contents = vec![kept.clone(), added.clone()];
+        let (embedded, dropped) = index
+            .backfill(&contents, |content| async move { content.content })
+            .await
+            .unwrap();
+        assert_eq!((embedded, dropped), (1, 1));
+
+        let ids = store.ids().await.unwrap();
+        assert!(ids.contains(&kept.id.to_string()));
+        assert!(ids.contains(&added.id.to_string()));
+        assert!(!ids.contains(&deleted.id.to_string()));
+
+        // Content that fails to embed is left for the next run
+        let failing = content("unreachable", &[]);
+        let (embedded, _) = index
+            .backfill(std::slice::from_ref(&failing), |content| async move {
+                content.content
+            })
+            .await
+            .unwrap();
+        assert_eq!(embedded, 0);
+        assert!(!store.ids().await.unwrap().contains(&failing.id.to_string()));
+    }
+}
@@ -0,0 +1,43 @@
+pub mod cluster;
+#[cfg(test)]
+mod cluster_test;
+pub mod index;
+#[cfg(test)]
+mod index_test;
+pub mod openai;
+
+use async_trait::async_trait;
+use std::sync::Arc;
+
+use crate::config::{AppConfig, EmbeddingProvider};
+use crate::embedding::index::SemanticIndex;
+use crate::storage::create_vector_storage;
+use crate::{ClassifyError, ClassifyResult};
+
+/// Turns text into a vector, texts with similar meaning get similar vectors
+#[async_trait]
+pub trait Embedder: Send + Sync {
+    async fn embed(&self, text: &str) -> ClassifyResult<Vec<f32>>;
+}
+
+/// Create the semantic index configured by `EMBEDDING_PROVIDER` and `VECTOR_STORAGE_TYPE`
+pub async fn create_semantic_index(config: &AppConfig) -> ClassifyResult<Option<SemanticIndex>> {
+    let Some(embedding) = &config.embedding else {
+        return Ok(None);
+    };
+
+    let embedder: Arc<dyn Embedder> = match embedding.provider {
+        EmbeddingProvider::OpenAi => {
+            let api_key = config.classifier.openai_api_key.as_deref().ok_or_else(|| {
+                ClassifyError::ConfigError(
+                    "OPENAI_API_KEY is required for EMBEDDING_PROVIDER=openai".to_string(),
+                )
+            })?;
+            Arc::new(openai::OpenAiEmbedder::new(api_key, &embedding.model))
+        }
+    };
+
+    let store = create_vector_storage(config, embedding).await?;
+
+    Ok(Some(SemanticIndex::new(embedder, store)))
+}
@@ -0,0 +1,109 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::embedding::Embedder;
+use crate::{ClassifyError, ClassifyResult, Usage};
+
+const OPENAI_EMBEDDINGS_URL: &str = "https://api.openai.com/v1/embeddings";
+
+/// Characters sent to be embedded, well within the token limit of the models
+const MAX_INPUT_LENGTH: usize = 24_000;
+
+/// Embeds text with the OpenAI embeddings API
+pub struct OpenAiEmbedder {
+    api_key: String,
+    model: String,
+    client: reqwest::Client,
+}
+
+#[derive(Debug, Serialize)]
+struct EmbeddingRequest<'a> {
+    model: &'a str,
+    input: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+struct EmbeddingResponse {
+    data: Vec<EmbeddingData>,
+    #[serde(default)]
+    usage: Option<EmbeddingUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EmbeddingData {
+    embedding: Vec<f32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EmbeddingUsage {
+    total_tokens: u64,
+}
+
+impl OpenAiEmbedder {
+    pub fn new(api_key: &str, model: &str) -> Self {
+        Self {
+            api_key: api_key.to_string(),
+            model: model.to_string(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// The start of `text` that fits in a request
+    pub fn truncate(text: &str) -> &str {
+        match text.char_indices().nth(MAX_INPUT_LENGTH) {
+            Some((end, _)) => &text[..end],
+            None => text,
+        }
+    }
+}
+
+#[async_trait]
+impl Embedder for OpenAiEmbedder {
+    async fn embed(&self, text: &str) -> ClassifyResult<Vec<f32>> {
+        let response = self
+            .client
+            .post(OPENAI_EMBEDDINGS_URL)
+            .bearer_auth(&self.api_key)
+            .json(&EmbeddingRequest {
+                model: &self.model,
+                input: Self::truncate(text),
+            })
+            .send()
+            .await
+            .map_err(|e| {
+                ClassifyError::EmbeddingError(format!("Failed to call OpenAI API: {}", e))
+            })?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unknown error".to_string());
+            return Err(ClassifyError::EmbeddingError(format!(
+                "OpenAI API error: HTTP status {}, {}",
+                status, error_text
+            )));
+        }
+
+        let response = response.json::<EmbeddingResponse>().await.map_err(|e| {
+            ClassifyError::EmbeddingError(format!("Failed to parse OpenAI response: {}", e))
+        })?;
+
+        if let Some(usage) = &response.usage {
+            crate::usage::record(Usage {
+                tokens: usage.total_tokens,
+                ..Usage::default()
+            });
+        }
+
+        response
+            .data
+            .into_iter()
+            .next()
+            .map(|data| data.embedding)
+            .ok_or_else(|| {
+                ClassifyError::EmbeddingError("Empty response from OpenAI API".to_string())
+            })
+    }
+}
@@ -0,0 +1,59 @@
+/// Broad kind of a document, deciding which extractor turns it into text
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DocumentKind {
+    Html,
+    Pdf,
+    Image,
+    Text,
+    Other,
+}
+
+impl DocumentKind {
+    pub fn from_content_type(content_type: &str) -> Self {
+        match content_type {
+            "text/html" | "application/xhtml+xml" => Self::Html,
+            "application/pdf" => Self::Pdf,
+            _ if content_type.starts_with("image/") => Self::Image,
+            _ if content_type.starts_with("text/") => Self::Text,
+            "application/json"
+            | "application/xml"
+            | "application/rss+xml"
+            | "application/atom+xml" => Self::Text,
+            _ => Self::Other,
+        }
+    }
+}
+
+/// Normalize a `Content-Type` header to its lowercase MIME type without parameters
+fn mime_essence(header: &str) -> Option<String> {
+    let essence = header.split(';').next()?.trim().to_lowercase();
+    (!essence.is_empty()).then_some(essence)
+}
+
+/// Determine the MIME type of a document from its declared content type,
+/// falling back to sniffing the body when the declaration is missing or generic
+pub fn detect_content_type(declared: Option<&str>, body: &[u8]) -> String {
+    let declared = declared.and_then(mime_essence).filter(|essence| {
+        essence != "application/octet-stream" && essence != "binary/octet-stream"
+    });
+
+    if let Some(essence) = declared {
+        return essence;
+    }
+
+    if let Some(kind) = infer::get(body) {
+        return kind.mime_type().to_string();
+    }
+
+    match std::str::from_utf8(body) {
+        Ok(text) if looks_like_html(text) => "text/html".to_string(),
+        Ok(_) => "text/plain".to_string(),
+        Err(_) => "application/octet-stream".to_string(),
+    }
+}
+
+fn looks_like_html(text: &str) -> bool {
+    let start = text.trim_start().get(..512).unwrap_or(text.trim_start());
+    let start = start.to_lowercase();
+    start.starts_with("<!doctype html") || start.starts_with("<html") || start.contains("<body")
+}
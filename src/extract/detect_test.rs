@@ -0,0 +1,64 @@
+use crate::extract::detect::{detect_content_type, DocumentKind};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_declared_content_type() {
+        assert_eq!(
+            detect_content_type(Some("text/HTML; charset=UTF-8"), b"<p>hi</p>"),
+            "text/html"
+        );
+        assert_eq!(
+            detect_content_type(Some("application/pdf"), b""),
+            "application/pdf"
+        );
+    }
+
+    #[test]
+    fn test_sniff_content_type() {
+        // Generic or missing headers fall back to the body's magic bytes
+        assert_eq!(
+            detect_content_type(Some("application/octet-stream"), b"%PDF-1.7\n..."),
+            "application/pdf"
+        );
+        assert_eq!(
+            detect_content_type(None, b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"),
+            "image/png"
+        );
+        assert_eq!(
+            detect_content_type(None, b"  <!DOCTYPE html><html><body>Hi</body></html>"),
+            "text/html"
+        );
+        assert_eq!(detect_content_type(None, b"Just some text"), "text/plain");
+        assert_eq!(
+            detect_content_type(None, &[0xff, 0xfe, 0x00, 0x01]),
+            "application/octet-stream"
+        );
+    }
+
+    #[test]
+    fn test_document_kind() {
+        assert_eq!(
+            DocumentKind::from_content_type("text/html"),
+            DocumentKind::Html
+        );
+        assert_eq!(
+            DocumentKind::from_content_type("application/pdf"),
+            DocumentKind::Pdf
+        );
+        assert_eq!(
+            DocumentKind::from_content_type("image/jpeg"),
+            DocumentKind::Image
+        );
+        assert_eq!(
+            DocumentKind::from_content_type("text/markdown"),
+            DocumentKind::Text
+        );
+        assert_eq!(
+            DocumentKind::from_content_type("application/zip"),
+            DocumentKind::Other
+        );
+    }
+}
@@ -0,0 +1,210 @@
+use regex::Regex;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// Elements that never hold the text of a page
+const HIDDEN: &[&str] = &[
+    "head", "script", "style", "noscript", "template", "svg", "iframe", "form",
+];
+
+/// Elements around the text of a page, like menus and page footers
+const BOILERPLATE: &[&str] = &["nav", "header", "footer", "aside"];
+
+/// Elements holding the text of a page, when the page marks it
+const MAIN: &[&str] = &["article", "main"];
+
+/// What a page says about itself for link previews, mostly from its
+/// OpenGraph tags
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PageMetadata {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    /// Absolute URL of the preview image
+    pub image_url: Option<String>,
+    pub site_name: Option<String>,
+}
+
+/// Reduce an HTML document to its visible text
+pub fn strip_html(html: &str) -> String {
+    static BLOCKS: OnceLock<Regex> = OnceLock::new();
+    static TAGS: OnceLock<Regex> = OnceLock::new();
+    let blocks = BLOCKS
+        .get_or_init(|| Regex::new(r"(?is)<(script|style)[^>]*>.*?</(script|style)>").unwrap());
+    let tags = TAGS.get_or_init(|| Regex::new(r"(?s)<[^>]+>").unwrap());
+
+    let text = blocks.replace_all(html, " ");
+    let text = tags.replace_all(&text, " ");
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Reduce an HTML page to what a reader would read: its title, its meta
+/// description and the text of its main content, leaving out scripts,
+/// styles, menus, page headers and footers
+pub fn readable_text(html: &str) -> String {
+    static COMMENTS: OnceLock<Regex> = OnceLock::new();
+    let comments = COMMENTS.get_or_init(|| Regex::new(r"(?s)<!--.*?-->").unwrap());
+
+    let html = comments.replace_all(html, " ");
+    let title = title(&html);
+    let description = meta_content(&html, "description");
+
+    let visible = remove_elements(&html, HIDDEN);
+    let text =
+        main_content(&visible).unwrap_or_else(|| text_of(&remove_elements(&visible, BOILERPLATE)));
+    // A page that is all boilerplate still has some text to classify
+    let text = if text.is_empty() {
+        text_of(&visible)
+    } else {
+        text
+    };
+
+    [title, description, Some(text)]
+        .into_iter()
+        .flatten()
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Preview metadata of the page at `url`: the OpenGraph tags, with the
+/// `<title>` element, the meta description and Twitter cards in their place
+/// when a page has no OpenGraph tags
+pub fn page_metadata(html: &str, url: &str) -> PageMetadata {
+    let first = |names: &[&str]| names.iter().find_map(|name| meta_content(html, name));
+
+    PageMetadata {
+        title: first(&["og:title", "twitter:title"]).or_else(|| title(html)),
+        description: first(&["og:description", "description", "twitter:description"]),
+        // Pages often link their image relative to themselves
+        image_url: first(&["og:image", "og:image:url", "twitter:image"]).and_then(|image| {
+            reqwest::Url::parse(url)
+                .and_then(|base| base.join(&image))
+                .ok()
+                .filter(|image| matches!(image.scheme(), "http" | "https"))
+                .map(String::from)
+        }),
+        site_name: first(&["og:site_name"]),
+    }
+}
+
+/// Text of the `<title>` element
+pub fn title(html: &str) -> Option<String> {
+    static TITLE: OnceLock<Regex> = OnceLock::new();
+    let title = TITLE.get_or_init(|| Regex::new(r"(?is)<title\b[^>]*>(.*?)</title\s*>").unwrap());
+
+    let text = text_of(&title.captures(html)?[1]);
+    (!text.is_empty()).then_some(text)
+}
+
+/// Content of the `<meta>` tag with the given `name` or `property`, like
+/// `description` or `og:title`
+pub fn meta_content(html: &str, name: &str) -> Option<String> {
+    static META: OnceLock<Regex> = OnceLock::new();
+    let meta = META.get_or_init(|| Regex::new(r"(?is)<meta\b([^>]*)>").unwrap());
+
+    meta.captures_iter(html).find_map(|tag| {
+        let attributes = attributes(&tag[1]);
+        let named = ["name", "property"].iter().any(|key| {
+            attributes
+                .get(*key)
+                .is_some_and(|value| value.eq_ignore_ascii_case(name))
+        });
+        let content = decode_entities(attributes.get("content")?.trim());
+        (named && !content.is_empty()).then_some(content)
+    })
+}
+
+/// Attributes of a tag by lowercased name, values not yet decoded
+fn attributes(tag: &str) -> HashMap<String, String> {
+    static ATTRIBUTE: OnceLock<Regex> = OnceLock::new();
+    let attribute = ATTRIBUTE.get_or_init(|| {
+        Regex::new(r#"([A-Za-z_:][-A-Za-z0-9_:.]*)\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s"'>]+))"#)
+            .unwrap()
+    });
+
+    attribute
+        .captures_iter(tag)
+        .map(|captures| {
+            let value = captures
+                .get(2)
+                .or_else(|| captures.get(3))
+                .or_else(|| captures.get(4))
+                .map_or("", |value| value.as_str());
+            (captures[1].to_lowercase(), value.to_string())
+        })
+        .collect()
+}
+
+/// Text of the longest `<article>` or `<main>` element, None when the page
+/// marks neither
+fn main_content(html: &str) -> Option<String> {
+    static PATTERNS: OnceLock<Vec<Regex>> = OnceLock::new();
+    let patterns = PATTERNS.get_or_init(|| element_patterns(MAIN));
+
+    patterns
+        .iter()
+        .flat_map(|pattern| pattern.captures_iter(html))
+        .map(|element| text_of(&remove_elements(&element[2], BOILERPLATE)))
+        .max_by_key(|text| text.len())
+        .filter(|text| !text.is_empty())
+}
+
+/// The document without the given elements and everything in them
+fn remove_elements(html: &str, names: &[&str]) -> String {
+    static PATTERNS: OnceLock<HashMap<&'static str, Regex>> = OnceLock::new();
+    let patterns = PATTERNS.get_or_init(|| {
+        let names = [HIDDEN, BOILERPLATE].concat();
+        names
+            .iter()
+            .copied()
+            .zip(element_patterns(&names))
+            .collect()
+    });
+
+    names.iter().fold(html.to_string(), |html, name| {
+        patterns[name].replace_all(&html, " ").into_owned()
+    })
+}
+
+/// A pattern per element, capturing its name and what is inside it. Elements
+/// of the same name nested in each other end at the first closing tag.
+fn element_patterns(names: &[&str]) -> Vec<Regex> {
+    names
+        .iter()
+        .map(|name| Regex::new(&format!(r"(?is)<({})\b[^>]*>(.*?)</{}\s*>", name, name)).unwrap())
+        .collect()
+}
+
+/// Visible text of an HTML fragment, with character references replaced
+fn text_of(html: &str) -> String {
+    decode_entities(&strip_html(html))
+}
+
+/// Replace the character references common in titles and descriptions
+fn decode_entities(text: &str) -> String {
+    static ENTITIES: OnceLock<Regex> = OnceLock::new();
+    let entities =
+        ENTITIES.get_or_init(|| Regex::new(r"&(#[0-9]+|#[xX][0-9a-fA-F]+|[a-zA-Z]+);").unwrap());
+
+    entities
+        .replace_all(text, |captures: &regex::Captures| {
+            let entity = &captures[1];
+            let decoded = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some(' '),
+                _ if entity.starts_with("#x") || entity.starts_with("#X") => {
+                    u32::from_str_radix(&entity[2..], 16)
+                        .ok()
+                        .and_then(char::from_u32)
+                }
+                _ if entity.starts_with('#') => entity[1..].parse().ok().and_then(char::from_u32),
+                _ => None,
+            };
+            decoded.map_or_else(|| captures[0].to_string(), String::from)
+        })
+        .into_owned()
+}
@@ -0,0 +1,87 @@
+use crate::extract::html::{meta_content, page_metadata, readable_text, title, PageMetadata};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+  <title>Async Rust &amp; Tokio</title>
+  <meta content="A tour of the Tokio runtime" name="description">
+  <meta property='og:site_name' content='Rust Blog'>
+  <style>body { color: red; }</style>
+  <script>trackVisitor();</script>
+</head>
+<body>
+  <header><a href="/">Home</a></header>
+  <nav><ul><li>Archive</li><li>About</li></ul></nav>
+  <!-- <p>Old intro</p> -->
+  <main>
+    <article>
+      <h1>Async Rust</h1>
+      <p>Tokio schedules futures on a pool of worker threads.</p>
+      <aside>Subscribe to the newsletter</aside>
+    </article>
+  </main>
+  <footer>Copyright 2024</footer>
+</body>
+</html>"#;
+
+    #[test]
+    fn test_readable_text_keeps_title_description_and_main_content() {
+        assert_eq!(
+            readable_text(PAGE),
+            "Async Rust & Tokio\n\n\
+             A tour of the Tokio runtime\n\n\
+             Async Rust Tokio schedules futures on a pool of worker threads."
+        );
+    }
+
+    #[test]
+    fn test_readable_text_without_main_element_drops_boilerplate() {
+        let page = "<body><nav>Menu</nav><div><p>Rust 1.80 was released.</p></div>\
+                    <footer>Contact</footer><script>x()</script></body>";
+        assert_eq!(readable_text(page), "Rust 1.80 was released.");
+
+        // A page of nothing but boilerplate still yields its text
+        assert_eq!(readable_text("<nav>Only a menu</nav>"), "Only a menu");
+    }
+
+    #[test]
+    fn test_title_and_meta_content() {
+        assert_eq!(title(PAGE).as_deref(), Some("Async Rust & Tokio"));
+        assert_eq!(
+            meta_content(PAGE, "og:site_name").as_deref(),
+            Some("Rust Blog")
+        );
+        assert_eq!(meta_content(PAGE, "og:image"), None);
+        assert_eq!(title("<p>No title</p>"), None);
+    }
+
+    #[test]
+    fn test_page_metadata_prefers_opengraph() {
+        let page = r#"<head>
+            <title>Fallback title</title>
+            <meta property="og:title" content="Async Rust">
+            <meta name="description" content="A tour of Tokio">
+            <meta property="og:image" content="/images/tokio.png">
+            <meta property="og:site_name" content="Rust Blog">
+        </head>"#;
+
+        assert_eq!(
+            page_metadata(page, "https://blog.example.com/posts/async"),
+            PageMetadata {
+                title: Some("Async Rust".to_string()),
+                description: Some("A tour of Tokio".to_string()),
+                image_url: Some("https://blog.example.com/images/tokio.png".to_string()),
+                site_name: Some("Rust Blog".to_string()),
+            }
+        );
+
+        // Without OpenGraph tags the title element is used
+        let metadata = page_metadata(PAGE, "https://blog.example.com/");
+        assert_eq!(metadata.title.as_deref(), Some("Async Rust & Tokio"));
+        assert_eq!(metadata.image_url, None);
+    }
+}
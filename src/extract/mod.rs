@@ -0,0 +1,66 @@
+pub mod detect;
+pub mod html;
+
+#[cfg(test)]
+mod detect_test;
+#[cfg(test)]
+mod html_test;
+
+pub use detect::{detect_content_type, DocumentKind};
+pub use html::{page_metadata, readable_text, strip_html, PageMetadata};
+
+use crate::web::fetch_document;
+use crate::{ClassifyError, ClassifyResult};
+
+/// A fetched document together with the text extracted from it
+#[derive(Debug, Clone)]
+pub struct Extracted {
+    pub content_type: String,
+    pub body: Vec<u8>,
+    pub text: String,
+}
+
+impl Extracted {
+    pub fn kind(&self) -> DocumentKind {
+        DocumentKind::from_content_type(&self.content_type)
+    }
+}
+
+/// Turn a document into text for classification, using the extractor for its kind
+pub async fn extract_text(content_type: &str, body: &[u8], url: &str) -> ClassifyResult<String> {
+    match DocumentKind::from_content_type(content_type) {
+        DocumentKind::Html => Ok(readable_text(&String::from_utf8_lossy(body))),
+        DocumentKind::Text => Ok(String::from_utf8_lossy(body).into_owned()),
+        DocumentKind::Pdf => {
+            let body = body.to_vec();
+            // PDF parsing is CPU bound and may panic on malformed files
+            tokio::task::spawn_blocking(move || pdf_extract::extract_text_from_mem(&body))
+                .await
+                .map_err(|e| {
+                    ClassifyError::ExtractionError(format!("PDF extraction failed: {}", e))
+                })?
+                .map_err(|e| {
+                    ClassifyError::ExtractionError(format!("Failed to extract PDF text: {}", e))
+                })
+        }
+        // Without OCR the best description of an image is where it lives
+        DocumentKind::Image => Ok(format!("Image ({}) at {}", content_type, url)),
+        DocumentKind::Other => Err(ClassifyError::ExtractionError(format!(
+            "Unsupported content type: {}",
+            content_type
+        ))),
+    }
+}
+
+/// Fetch a URL, detect its content type and extract its text
+pub async fn fetch_and_extract(client: &reqwest::Client, url: &str) -> ClassifyResult<Extracted> {
+    let document = fetch_document(client, url).await?;
+    let content_type = detect_content_type(document.content_type.as_deref(), &document.body);
+    let text = extract_text(&content_type, &document.body, url).await?;
+
+    Ok(Extracted {
+        content_type,
+        body: document.body,
+        text,
+    })
+}
@@ -0,0 +1,193 @@
+use serde_json::Value;
+use std::collections::HashMap;
+
+use crate::tags::TAG_SEPARATOR;
+use crate::Content;
+
+/// Type of Firefox folders, bookmarks are `text/x-moz-place`
+const FIREFOX_FOLDER: &str = "text/x-moz-place-container";
+
+/// A bookmark from an export, with the tags given by its folders and labels
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bookmark {
+    pub url: String,
+    pub tags: Vec<String>,
+}
+
+/// The bookmarks of an export
+#[derive(Debug, Clone, Default)]
+pub struct BookmarkExport {
+    /// Tool the export came from: `raindrop`, `chrome` or `firefox`
+    pub format: &'static str,
+    /// Bookmarks with an http(s) URL, each URL once
+    pub bookmarks: Vec<Bookmark>,
+    /// Number of entries left out because they have no http(s) URL, like
+    /// `javascript:` bookmarklets and `place:` queries
+    pub skipped: usize,
+}
+
+impl BookmarkExport {
+    /// The tags of each bookmarked URL
+    pub fn tags_by_url(&self) -> HashMap<String, Vec<String>> {
+        self.bookmarks
+            .iter()
+            .map(|bookmark| (bookmark.url.clone(), bookmark.tags.clone()))
+            .collect()
+    }
+}
+
+/// Parse a Raindrop.io JSON export or a Chrome or Firefox bookmark backup,
+/// `None` when the JSON is none of these.
+///
+/// Folders become hierarchical tags, `Dev > Rust` is tagged `Dev/Rust`, next
+/// to the tags of Raindrop and Firefox. The folders every browser has, like
+/// the bookmarks bar, are left out. A URL bookmarked more than once is kept
+/// once with the tags of all its entries.
+pub fn parse_bookmarks(json: &Value) -> Option<BookmarkExport> {
+    let mut collector = Collector::default();
+    if let Some(roots) = json.get("roots").and_then(Value::as_object) {
+        collector.export.format = "chrome";
+        for root in roots.values() {
+            collector.chrome_node(root, &[], true);
+        }
+    } else if json.get("type").and_then(Value::as_str) == Some(FIREFOX_FOLDER) {
+        collector.export.format = "firefox";
+        collector.firefox_node(json, &[]);
+    } else if let Some(items) = json
+        .get("items")
+        .and_then(Value::as_array)
+        .or_else(|| json.as_array())
+    {
+        collector.export.format = "raindrop";
+        for item in items {
+            collector.raindrop_item(item);
+        }
+    } else {
+        return None;
+    }
+
+    Some(collector.export)
+}
+
+#[derive(Default)]
+struct Collector {
+    export: BookmarkExport,
+    /// Position of each URL in the bookmarks
+    positions: HashMap<String, usize>,
+}
+
+impl Collector {
+    fn chrome_node(&mut self, node: &Value, folders: &[String], root: bool) {
+        match node.get("type").and_then(Value::as_str) {
+            Some("url") => {
+                let url = node.get("url").and_then(Value::as_str).unwrap_or_default();
+                self.add(url, folders, Vec::new());
+            }
+            Some("folder") => {
+                let folders = subfolder(folders, node.get("name"), root);
+                for child in children(node) {
+                    self.chrome_node(child, &folders, false);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn firefox_node(&mut self, node: &Value, folders: &[String]) {
+        if node.get("type").and_then(Value::as_str) == Some(FIREFOX_FOLDER) {
+            // The menu, toolbar and other folders of the browser have a `root`
+            let folders = subfolder(folders, node.get("title"), node.get("root").is_some());
+            for child in children(node) {
+                self.firefox_node(child, &folders);
+            }
+            return;
+        }
+
+        // Separators have neither children nor a URI
+        if let Some(url) = node.get("uri").and_then(Value::as_str) {
+            let tags = node
+                .get("tags")
+                .and_then(Value::as_str)
+                .map(|tags| tags.split(',').map(String::from).collect())
+                .unwrap_or_default();
+            self.add(url, folders, tags);
+        }
+    }
+
+    fn raindrop_item(&mut self, item: &Value) {
+        let url = item.get("link").and_then(Value::as_str).unwrap_or_default();
+        let tags = item
+            .get("tags")
+            .and_then(Value::as_array)
+            .map(|tags| {
+                tags.iter()
+                    .filter_map(Value::as_str)
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        // Raindrop exports name the collection `folder`, its API nests it
+        let folder = item
+            .get("folder")
+            .or_else(|| item.get("collection").and_then(|c| c.get("title")))
+            .and_then(Value::as_str)
+            .filter(|folder| !folder.eq_ignore_ascii_case("unsorted"))
+            .map(|folder| folder.replace(TAG_SEPARATOR, " "));
+
+        self.add(url, &Vec::from_iter(folder), tags);
+    }
+
+    fn add(&mut self, url: &str, folders: &[String], mut tags: Vec<String>) {
+        let url = url.trim();
+        if !Content::looks_like_url(url) {
+            self.export.skipped += 1;
+            return;
+        }
+
+        if !folders.is_empty() {
+            tags.insert(0, folders.join(&TAG_SEPARATOR.to_string()));
+        }
+        tags.retain(|tag| !tag.trim().is_empty());
+
+        match self.positions.get(url) {
+            Some(&position) => {
+                let bookmark = &mut self.export.bookmarks[position];
+                for tag in tags {
+                    if !bookmark.tags.contains(&tag) {
+                        bookmark.tags.push(tag);
+                    }
+                }
+            }
+            None => {
+                self.positions
+                    .insert(url.to_string(), self.export.bookmarks.len());
+                self.export.bookmarks.push(Bookmark {
+                    url: url.to_string(),
+                    tags,
+                });
+            }
+        }
+    }
+}
+
+fn children(node: &Value) -> &[Value] {
+    node.get("children")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+}
+
+/// The folder path below the folder `name`, unchanged for the folders of the browser
+fn subfolder(folders: &[String], name: Option<&Value>, root: bool) -> Vec<String> {
+    let mut folders = folders.to_vec();
+    let name = name
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .unwrap_or_default();
+    if !root && !name.is_empty() {
+        // A `/` in a folder name would read as another level
+        folders.push(name.replace(TAG_SEPARATOR, " "));
+    }
+    folders
+}
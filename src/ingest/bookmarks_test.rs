@@ -0,0 +1,117 @@
+use crate::ingest::bookmarks::{parse_bookmarks, Bookmark};
+use serde_json::json;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bookmark(url: &str, tags: &[&str]) -> Bookmark {
+        Bookmark {
+            url: url.to_string(),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_parse_chrome_bookmarks() {
+        let json = json!({
+            "checksum": "abc",
+            "roots": {
+                "bookmark_bar": {
+                    "type": "folder",
+                    "name": "Bookmarks bar",
+                    "children": [
+                        {"type": "url", "name": "Rust", "url": "https://www.rust-lang.org/"},
+                        {"type": "folder", "name": "Dev", "children": [
+                            {"type": "folder", "name": "CI/CD", "children": [
+                                {"type": "url", "name": "Actions", "url": "https://github.com/features/actions"}
+                            ]},
+                            {"type": "url", "name": "Rust", "url": "https://www.rust-lang.org/"}
+                        ]}
+                    ]
+                },
+                "other": {
+                    "type": "folder",
+                    "name": "Other bookmarks",
+                    "children": [
+                        {"type": "url", "name": "Bookmarklet", "url": "javascript:alert(1)"}
+                    ]
+                }
+            },
+            "version": 1
+        });
+
+        let export = parse_bookmarks(&json).unwrap();
+
+        assert_eq!(export.format, "chrome");
+        assert_eq!(
+            export.bookmarks,
+            vec![
+                bookmark("https://www.rust-lang.org/", &["Dev"]),
+                bookmark("https://github.com/features/actions", &["Dev/CI CD"]),
+            ]
+        );
+        assert_eq!(export.skipped, 1);
+    }
+
+    #[test]
+    fn test_parse_firefox_bookmarks() {
+        let json = json!({
+            "title": "",
+            "type": "text/x-moz-place-container",
+            "root": "placesRoot",
+            "children": [{
+                "title": "menu",
+                "type": "text/x-moz-place-container",
+                "root": "bookmarksMenuFolder",
+                "children": [
+                    {"type": "text/x-moz-place-separator"},
+                    {"title": "Recipes", "type": "text/x-moz-place-container", "children": [
+                        {"title": "Pasta", "type": "text/x-moz-place", "uri": "https://example.com/pasta", "tags": "italian,dinner"}
+                    ]},
+                    {"title": "Most Visited", "type": "text/x-moz-place", "uri": "place:sort=8"}
+                ]
+            }]
+        });
+
+        let export = parse_bookmarks(&json).unwrap();
+
+        assert_eq!(export.format, "firefox");
+        assert_eq!(
+            export.bookmarks,
+            vec![bookmark(
+                "https://example.com/pasta",
+                &["Recipes", "italian", "dinner"]
+            )]
+        );
+        assert_eq!(export.skipped, 1);
+    }
+
+    #[test]
+    fn test_parse_raindrop_export() {
+        let json = json!({
+            "items": [
+                {"link": "https://example.com/a", "title": "A", "tags": ["rust"], "folder": "Reading"},
+                {"link": "https://example.com/b", "tags": [], "collection": {"title": "Unsorted"}},
+                {"link": "https://example.com/a", "tags": ["async"], "folder": "Reading"}
+            ]
+        });
+
+        let export = parse_bookmarks(&json).unwrap();
+
+        assert_eq!(export.format, "raindrop");
+        assert_eq!(
+            export.bookmarks,
+            vec![
+                bookmark("https://example.com/a", &["Reading", "rust", "async"]),
+                bookmark("https://example.com/b", &[]),
+            ]
+        );
+        assert_eq!(export.skipped, 0);
+    }
+
+    #[test]
+    fn test_parse_unknown_export() {
+        assert!(parse_bookmarks(&json!({"urls": ["https://example.com"]})).is_none());
+    }
+}
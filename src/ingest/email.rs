@@ -0,0 +1,237 @@
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use regex::Regex;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use crate::extract::strip_html;
+use crate::Content;
+
+/// A parsed email message
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmailMessage {
+    pub subject: String,
+    pub from: String,
+    pub body: String,
+}
+
+impl EmailMessage {
+    /// Email address of the sender, without display name
+    pub fn sender_address(&self) -> Option<String> {
+        let from = self.from.trim();
+        let address = match (from.rfind('<'), from.rfind('>')) {
+            (Some(start), Some(end)) if start < end => &from[start + 1..end],
+            _ => from,
+        };
+
+        address.contains('@').then(|| address.trim().to_lowercase())
+    }
+
+    /// Domain of the sender's email address
+    pub fn sender_domain(&self) -> Option<String> {
+        self.sender_address().and_then(|address| {
+            address
+                .rsplit_once('@')
+                .map(|(_, domain)| domain.to_string())
+        })
+    }
+
+    /// Tags describing where the message came from
+    pub fn sender_tags(&self) -> Vec<String> {
+        let mut tags = Vec::new();
+        if let Some(address) = self.sender_address() {
+            tags.push(format!("from:{}", address));
+        }
+        if let Some(domain) = self.sender_domain() {
+            tags.push(format!("domain:{}", domain));
+        }
+        tags
+    }
+
+    /// Text to classify: a link mailed on its own is classified as that URL,
+    /// anything else as subject plus body
+    pub fn classification_text(&self) -> String {
+        let body = self.body.trim();
+        if Content::looks_like_url(body) && !body.contains(char::is_whitespace) {
+            return body.to_string();
+        }
+
+        format!("{}\n\n{}", self.subject.trim(), body)
+            .trim()
+            .to_string()
+    }
+}
+
+/// Parse a raw RFC 822 message into subject, sender and plain text body
+pub fn parse_message(raw: &[u8]) -> EmailMessage {
+    let raw = String::from_utf8_lossy(raw);
+    let (headers, body) = split_headers(&raw);
+
+    EmailMessage {
+        subject: headers
+            .get("subject")
+            .map(|value| decode_header_value(value))
+            .unwrap_or_default(),
+        from: headers
+            .get("from")
+            .map(|value| decode_header_value(value))
+            .unwrap_or_default(),
+        body: extract_text(&headers, body).unwrap_or_default(),
+    }
+}
+
+/// Split a message or MIME part into its unfolded headers and its body
+fn split_headers(raw: &str) -> (HashMap<String, String>, &str) {
+    let (head, body) = match raw.find("\r\n\r\n") {
+        Some(index) => (&raw[..index], &raw[index + 4..]),
+        None => match raw.find("\n\n") {
+            Some(index) => (&raw[..index], &raw[index + 2..]),
+            None => (raw, ""),
+        },
+    };
+
+    let mut headers = HashMap::new();
+    let mut current: Option<(String, String)> = None;
+
+    for line in head.lines() {
+        if line.starts_with([' ', '\t']) {
+            if let Some((_, value)) = current.as_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+            continue;
+        }
+
+        if let Some((name, value)) = current.take() {
+            headers.entry(name).or_insert(value);
+        }
+
+        if let Some((name, value)) = line.split_once(':') {
+            current = Some((name.trim().to_lowercase(), value.trim().to_string()));
+        }
+    }
+
+    if let Some((name, value)) = current {
+        headers.entry(name).or_insert(value);
+    }
+
+    (headers, body)
+}
+
+/// Get a parameter such as `boundary` or `charset` from a header value
+fn header_param(value: &str, name: &str) -> Option<String> {
+    value.split(';').skip(1).find_map(|param| {
+        let (key, value) = param.split_once('=')?;
+        (key.trim().eq_ignore_ascii_case(name)).then(|| value.trim().trim_matches('"').to_string())
+    })
+}
+
+/// Find the best plain text representation of a message body
+fn extract_text(headers: &HashMap<String, String>, body: &str) -> Option<String> {
+    let content_type = headers
+        .get("content-type")
+        .map(|value| value.to_lowercase())
+        .unwrap_or_else(|| "text/plain".to_string());
+
+    if content_type.starts_with("multipart/") {
+        let boundary = header_param(headers.get("content-type")?, "boundary")?;
+        let delimiter = format!("--{}", boundary);
+
+        let parts: Vec<(HashMap<String, String>, &str)> = body
+            .split(delimiter.as_str())
+            .skip(1)
+            .take_while(|part| !part.starts_with("--"))
+            .map(|part| split_headers(part.trim_start_matches(['\r', '\n'])))
+            .collect();
+
+        // Prefer a plain text alternative over HTML
+        let plain = parts.iter().find_map(|(headers, body)| {
+            let is_html = headers
+                .get("content-type")
+                .is_some_and(|value| value.to_lowercase().starts_with("text/html"));
+            (!is_html).then(|| extract_text(headers, body)).flatten()
+        });
+
+        return plain.or_else(|| {
+            parts
+                .iter()
+                .find_map(|(headers, body)| extract_text(headers, body))
+        });
+    }
+
+    if !content_type.starts_with("text/") {
+        return None;
+    }
+
+    let encoding = headers
+        .get("content-transfer-encoding")
+        .map(|value| value.to_lowercase())
+        .unwrap_or_default();
+
+    let decoded = match encoding.as_str() {
+        "base64" => {
+            let compact: String = body.chars().filter(|c| !c.is_whitespace()).collect();
+            String::from_utf8_lossy(&BASE64.decode(compact).ok()?).into_owned()
+        }
+        "quoted-printable" => decode_quoted_printable(body),
+        _ => body.to_string(),
+    };
+
+    let text = if content_type.starts_with("text/html") {
+        strip_html(&decoded)
+    } else {
+        decoded
+    };
+
+    Some(text.trim().to_string()).filter(|text| !text.is_empty())
+}
+
+fn decode_quoted_printable(input: &str) -> String {
+    let mut bytes = Vec::with_capacity(input.len());
+    let input = input.replace("=\r\n", "").replace("=\n", "");
+    let raw = input.as_bytes();
+    let mut i = 0;
+
+    while i < raw.len() {
+        // The digits are read as bytes, the text after `=` need not be ASCII
+        if raw[i] == b'=' && i + 2 < raw.len() {
+            let high = (raw[i + 1] as char).to_digit(16);
+            let low = (raw[i + 2] as char).to_digit(16);
+            if let (Some(high), Some(low)) = (high, low) {
+                bytes.push((high * 16 + low) as u8);
+                i += 3;
+                continue;
+            }
+        }
+        bytes.push(raw[i]);
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// Decode RFC 2047 encoded words such as `=?UTF-8?B?...?=`
+fn decode_header_value(value: &str) -> String {
+    static ADJACENT: OnceLock<Regex> = OnceLock::new();
+    static ENCODED_WORD: OnceLock<Regex> = OnceLock::new();
+    let adjacent = ADJACENT.get_or_init(|| Regex::new(r"\?=\s+=\?").unwrap());
+    let encoded_word =
+        ENCODED_WORD.get_or_init(|| Regex::new(r"=\?[^?]+\?([bBqQ])\?([^?]*)\?=").unwrap());
+
+    // Whitespace between adjacent encoded words is not part of the text
+    let value = adjacent.replace_all(value, "?==?");
+
+    let decoded = encoded_word.replace_all(&value, |captures: &regex::Captures| {
+        let text = &captures[2];
+        if captures[1].eq_ignore_ascii_case("b") {
+            BASE64
+                .decode(text)
+                .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+                .unwrap_or_else(|_| text.to_string())
+        } else {
+            decode_quoted_printable(&text.replace('_', " "))
+        }
+    });
+
+    decoded.into_owned()
+}
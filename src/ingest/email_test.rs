@@ -0,0 +1,67 @@
+use crate::ingest::email::parse_message;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_plain_message() {
+        let raw = b"From: Alice Example <Alice@Example.com>\r\n\
+Subject: =?UTF-8?B?UnVzdCBhc3luYw==?= notes\r\n\
+Content-Type: text/plain; charset=utf-8\r\n\
+\r\n\
+Tokio tasks and futures\r\n";
+
+        let message = parse_message(raw);
+
+        assert_eq!(message.subject, "Rust async notes");
+        assert_eq!(message.body, "Tokio tasks and futures");
+        assert_eq!(
+            message.sender_tags(),
+            vec!["from:alice@example.com", "domain:example.com"]
+        );
+        assert_eq!(
+            message.classification_text(),
+            "Rust async notes\n\nTokio tasks and futures"
+        );
+    }
+
+    #[test]
+    fn test_parse_multipart_prefers_plain_text() {
+        let raw = b"From: bob@example.org\n\
+Subject: Link\n\
+Content-Type: multipart/alternative; boundary=\"sep\"\n\
+\n\
+--sep\n\
+Content-Type: text/html\n\
+\n\
+<p>https://example.org/article</p>\n\
+--sep\n\
+Content-Type: text/plain\n\
+Content-Transfer-Encoding: quoted-printable\n\
+\n\
+https://example.org/art=\n\
+icle\n\
+--sep--\n";
+
+        let message = parse_message(raw);
+
+        assert_eq!(message.body, "https://example.org/article");
+        assert_eq!(message.classification_text(), "https://example.org/article");
+    }
+
+    #[test]
+    fn test_quoted_printable_with_non_ascii_after_equals_sign() {
+        let raw = "From: carol@example.com\n\
+Subject: =?UTF-8?Q?caf=C3=A9_=été?=\n\
+Content-Type: text/plain; charset=utf-8\n\
+Content-Transfer-Encoding: quoted-printable\n\
+\n\
+Prix =égal =3D 5 =€ =1é\n";
+
+        let message = parse_message(raw.as_bytes());
+
+        assert_eq!(message.subject, "café =été");
+        assert_eq!(message.body, "Prix =égal = 5 =€ =1é");
+    }
+}
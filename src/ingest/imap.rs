@@ -0,0 +1,211 @@
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio_native_tls::{native_tls, TlsStream};
+use tracing::{info, warn};
+
+use crate::api::AppState;
+use crate::config::ImapConfig;
+use crate::ingest::email::parse_message;
+use crate::ingest::ingest_with_tags;
+use crate::{ClassifyError, ClassifyResult};
+
+/// An untagged server response line with any literals that followed it
+struct Untagged {
+    line: String,
+    literals: Vec<Vec<u8>>,
+}
+
+/// Minimal IMAP4rev1 client over TLS, supporting just what mailbox polling needs
+struct ImapSession {
+    stream: BufReader<TlsStream<TcpStream>>,
+    next_tag: u32,
+}
+
+fn imap_error(message: impl std::fmt::Display) -> ClassifyError {
+    ClassifyError::IngestError(format!("IMAP: {}", message))
+}
+
+/// Quote a string argument for an IMAP command
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+impl ImapSession {
+    async fn connect(host: &str, port: u16) -> ClassifyResult<Self> {
+        let tcp = TcpStream::connect((host, port))
+            .await
+            .map_err(|e| imap_error(format!("failed to connect to {}:{}: {}", host, port, e)))?;
+
+        let connector = native_tls::TlsConnector::new().map_err(imap_error)?;
+        let tls = tokio_native_tls::TlsConnector::from(connector)
+            .connect(host, tcp)
+            .await
+            .map_err(|e| imap_error(format!("TLS handshake failed: {}", e)))?;
+
+        let mut session = Self {
+            stream: BufReader::new(tls),
+            next_tag: 1,
+        };
+
+        let greeting = session.read_line().await?;
+        if !greeting.starts_with("* OK") {
+            return Err(imap_error(format!("unexpected greeting: {}", greeting)));
+        }
+
+        Ok(session)
+    }
+
+    async fn read_line(&mut self) -> ClassifyResult<String> {
+        let mut line = Vec::new();
+        let read = self
+            .stream
+            .read_until(b'\n', &mut line)
+            .await
+            .map_err(imap_error)?;
+
+        if read == 0 {
+            return Err(imap_error("connection closed by server"));
+        }
+
+        Ok(String::from_utf8_lossy(&line).trim_end().to_string())
+    }
+
+    /// Literal size announced at the end of a line, as in `BODY[] {1234}`
+    fn literal_size(line: &str) -> Option<usize> {
+        let start = line.rfind('{')?;
+        line.strip_suffix('}')?[start + 1..].parse().ok()
+    }
+
+    /// Send a command and collect the untagged responses until its tagged completion
+    async fn command(&mut self, command: &str) -> ClassifyResult<Vec<Untagged>> {
+        let tag = format!("a{}", self.next_tag);
+        self.next_tag += 1;
+
+        self.stream
+            .get_mut()
+            .write_all(format!("{} {}\r\n", tag, command).as_bytes())
+            .await
+            .map_err(imap_error)?;
+
+        let mut responses: Vec<Untagged> = Vec::new();
+
+        loop {
+            let line = self.read_line().await?;
+
+            if let Some(status) = line.strip_prefix(&format!("{} ", tag)) {
+                if status.starts_with("OK") {
+                    return Ok(responses);
+                }
+                // Never echo the LOGIN arguments back into logs
+                let verb = command.split_whitespace().next().unwrap_or_default();
+                return Err(imap_error(format!("{} failed: {}", verb, status)));
+            }
+
+            let size = Self::literal_size(&line);
+            if line.starts_with('*') {
+                responses.push(Untagged {
+                    line,
+                    literals: Vec::new(),
+                });
+            }
+
+            if let Some(size) = size {
+                let mut literal = vec![0; size];
+                self.stream
+                    .read_exact(&mut literal)
+                    .await
+                    .map_err(imap_error)?;
+                if let Some(response) = responses.last_mut() {
+                    response.literals.push(literal);
+                }
+            }
+        }
+    }
+
+    async fn login(&mut self, username: &str, password: &str) -> ClassifyResult<()> {
+        self.command(&format!("LOGIN {} {}", quote(username), quote(password)))
+            .await
+            .map(|_| ())
+    }
+
+    async fn select(&mut self, mailbox: &str) -> ClassifyResult<()> {
+        self.command(&format!("SELECT {}", quote(mailbox)))
+            .await
+            .map(|_| ())
+    }
+
+    async fn search_unseen(&mut self) -> ClassifyResult<Vec<u32>> {
+        let responses = self.command("UID SEARCH UNSEEN").await?;
+
+        Ok(responses
+            .iter()
+            .filter_map(|response| response.line.strip_prefix("* SEARCH"))
+            .flat_map(|ids| ids.split_whitespace().filter_map(|id| id.parse().ok()))
+            .collect())
+    }
+
+    async fn fetch(&mut self, uid: u32) -> ClassifyResult<Option<Vec<u8>>> {
+        let responses = self
+            .command(&format!("UID FETCH {} BODY.PEEK[]", uid))
+            .await?;
+
+        Ok(responses
+            .into_iter()
+            .find(|response| response.line.contains("FETCH"))
+            .and_then(|response| response.literals.into_iter().next()))
+    }
+
+    async fn mark_seen(&mut self, uid: u32) -> ClassifyResult<()> {
+        self.command(&format!("UID STORE {} +FLAGS (\\Seen)", uid))
+            .await
+            .map(|_| ())
+    }
+
+    async fn logout(mut self) {
+        let _ = self.command("LOGOUT").await;
+    }
+}
+
+/// Classify and store all unread messages in the configured mailbox,
+/// marking each message as read once it has been stored
+pub async fn poll_mailbox(state: Arc<AppState>, config: ImapConfig) -> ClassifyResult<()> {
+    let mut session = ImapSession::connect(&config.host, config.port).await?;
+    session.login(&config.username, &config.password).await?;
+    session.select(&config.mailbox).await?;
+
+    let uids = session.search_unseen().await?;
+    info!("Found {} unread messages in {}", uids.len(), config.mailbox);
+
+    for uid in uids {
+        let Some(raw) = session.fetch(uid).await? else {
+            warn!("Message {} has no body, skipping", uid);
+            continue;
+        };
+
+        let message = parse_message(&raw);
+        let text = message.classification_text();
+
+        if text.is_empty() {
+            warn!("Message {} has no content, skipping", uid);
+            session.mark_seen(uid).await?;
+            continue;
+        }
+
+        match ingest_with_tags(&state, text, &message.sender_tags()).await {
+            Ok(ingested) => {
+                info!(
+                    "Ingested message {} as content {}",
+                    uid,
+                    ingested.content().id
+                );
+                session.mark_seen(uid).await?;
+            }
+            // Leave the message unread so it is retried on the next poll
+            Err(e) => warn!("Failed to ingest message {}: {}", uid, e),
+        }
+    }
+
+    session.logout().await;
+    Ok(())
+}
@@ -0,0 +1,474 @@
+use chrono::{DateTime, Utc};
+use futures::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+use url::Url;
+use uuid::Uuid;
+
+use crate::api::AppState;
+use crate::config::ImportConfig;
+use crate::ingest::bookmarks::BookmarkExport;
+use crate::ingest::{ingest_with_tags, Ingested};
+use crate::{ClassifyError, ClassifyResult, Content};
+
+/// Number of processed URLs between two saves of an import's progress
+const SAVE_EVERY: usize = 10;
+
+/// Request body for a bulk URL import
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportRequest {
+    pub urls: Vec<String>,
+}
+
+/// Request body for crawling the pages listed in a sitemap
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SitemapImportRequest {
+    pub url: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportStatus {
+    Running,
+    Completed,
+}
+
+/// A URL that could not be imported
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportFailure {
+    pub url: String,
+    pub error: String,
+}
+
+/// Progress of a bulk import job
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportJob {
+    pub id: Uuid,
+    /// Where the URLs came from, e.g. a sitemap URL
+    pub source: Option<String>,
+    pub status: ImportStatus,
+    /// Number of URLs submitted
+    pub total: usize,
+    /// Number of URLs handled so far
+    pub processed: usize,
+    /// Number of URLs classified and stored
+    pub created: usize,
+    /// Number of URLs that were already stored
+    pub duplicates: usize,
+    /// Number of entries left out before the import, like bookmarks without a URL
+    #[serde(default)]
+    pub skipped: usize,
+    pub failures: Vec<ImportFailure>,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    /// Tenant that started the import
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+}
+
+/// Parse a newline separated list of URLs, skipping blank lines and `#` comments
+pub fn parse_url_list(body: &str) -> Vec<String> {
+    body.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(String::from)
+        .collect()
+}
+
+/// Classify and store one URL of an import. The import runs after its request
+/// was answered, so its usage is counted here, and URLs are refused once the
+/// account's quota is used up.
+async fn import_url(
+    state: &AppState,
+    url: String,
+    tags: &[String],
+    account: Option<&str>,
+) -> Result<Ingested, String> {
+    let (Some(usage), Some(account)) = (state.usage.as_deref(), account) else {
+        return ingest_with_tags(state, url, tags)
+            .await
+            .map_err(|e| e.to_string());
+    };
+
+    // The main API key has no quota, and quotas fail open when the store is down
+    if account != "admin" {
+        match usage.exceeded(account, state.namespace.as_deref()).await {
+            Ok(Some(limit)) => return Err(format!("Monthly {} quota exceeded", limit)),
+            Ok(None) => {}
+            Err(e) => warn!("Failed to check quota: {}", e),
+        }
+    }
+
+    usage
+        .count(account, ingest_with_tags(state, url, tags))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Spaces out requests to the same host
+pub struct HostRateLimiter {
+    interval: Duration,
+    next_slot: Mutex<HashMap<String, Instant>>,
+}
+
+impl HostRateLimiter {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            next_slot: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Reserve the next request slot for a host and return how long to wait for it
+    pub fn reserve(&self, host: &str) -> Duration {
+        let now = Instant::now();
+        let mut next_slot = self.next_slot.lock().unwrap();
+        let slot = next_slot
+            .get(host)
+            .copied()
+            .filter(|slot| *slot > now)
+            .unwrap_or(now);
+        next_slot.insert(host.to_string(), slot + self.interval);
+        slot - now
+    }
+
+    /// Wait until a request to the host of `url` is allowed
+    pub async fn wait(&self, url: &str) {
+        let host = Url::parse(url)
+            .ok()
+            .and_then(|url| url.host_str().map(String::from))
+            .unwrap_or_default();
+
+        let delay = self.reserve(&host);
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+/// An import job together with the URLs it still has to process
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TrackedImport {
+    job: ImportJob,
+    pending: HashSet<String>,
+    /// Tags added to the classifier's tags of each URL
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    tags: HashMap<String, Vec<String>>,
+    /// Account the usage of the import is counted towards
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    account: Option<String>,
+}
+
+/// Runs bulk imports in the background and keeps track of their progress.
+///
+/// When a state directory is configured, progress is saved there so that
+/// imports interrupted by a restart are resumed with their pending URLs.
+pub struct ImportManager {
+    config: ImportConfig,
+    jobs: RwLock<HashMap<Uuid, TrackedImport>>,
+}
+
+impl ImportManager {
+    pub fn new(config: ImportConfig) -> Self {
+        Self {
+            config,
+            jobs: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Forget the imports that finished more than the retention period before
+    /// `now`, with their saved progress, returning how many were forgotten
+    pub fn evict_finished(&self, now: DateTime<Utc>) -> usize {
+        let retention =
+            chrono::Duration::from_std(self.config.retention).unwrap_or(chrono::Duration::MAX);
+        let expired = |tracked: &TrackedImport| {
+            tracked
+                .job
+                .finished_at
+                .and_then(|finished_at| finished_at.checked_add_signed(retention))
+                .is_some_and(|expires_at| expires_at < now)
+        };
+
+        let evicted: Vec<Uuid> = {
+            let mut jobs = self.jobs.write().unwrap();
+            let evicted = jobs
+                .values()
+                .filter(|tracked| expired(tracked))
+                .map(|tracked| tracked.job.id)
+                .collect::<Vec<_>>();
+            for id in &evicted {
+                jobs.remove(id);
+            }
+            evicted
+        };
+
+        for id in &evicted {
+            self.remove_saved(id);
+        }
+        evicted.len()
+    }
+
+    /// Get the progress of an import job
+    pub fn get(&self, id: &Uuid) -> Option<ImportJob> {
+        self.jobs
+            .read()
+            .unwrap()
+            .get(id)
+            .map(|tracked| tracked.job.clone())
+    }
+
+    /// Start importing `urls` in the background and return the new job. Usage
+    /// is counted towards `account`, as it would be for the request itself.
+    pub fn start(
+        &self,
+        state: Arc<AppState>,
+        urls: Vec<String>,
+        source: Option<String>,
+        account: Option<String>,
+    ) -> ImportJob {
+        self.start_tagged(state, urls, HashMap::new(), 0, source, account)
+    }
+
+    /// Start importing the bookmarks of an export, tagged with their folders and tags
+    pub fn start_bookmarks(
+        &self,
+        state: Arc<AppState>,
+        export: BookmarkExport,
+        account: Option<String>,
+    ) -> ImportJob {
+        let urls = export
+            .bookmarks
+            .iter()
+            .map(|bookmark| bookmark.url.clone())
+            .collect();
+        let source = format!("{} bookmarks", export.format);
+        self.start_tagged(
+            state,
+            urls,
+            export.tags_by_url(),
+            export.skipped,
+            Some(source),
+            account,
+        )
+    }
+
+    fn start_tagged(
+        &self,
+        state: Arc<AppState>,
+        urls: Vec<String>,
+        tags: HashMap<String, Vec<String>>,
+        skipped: usize,
+        source: Option<String>,
+        account: Option<String>,
+    ) -> ImportJob {
+        // Finished imports are forgotten as new ones come in
+        self.evict_finished(Utc::now());
+
+        let job = ImportJob {
+            id: Uuid::new_v4(),
+            source,
+            status: ImportStatus::Running,
+            total: urls.len(),
+            processed: 0,
+            created: 0,
+            duplicates: 0,
+            skipped,
+            failures: Vec::new(),
+            started_at: Utc::now(),
+            finished_at: None,
+            namespace: state.namespace.clone(),
+        };
+
+        let tracked = TrackedImport {
+            job: job.clone(),
+            pending: urls.iter().cloned().collect(),
+            tags,
+            account,
+        };
+        self.save(&tracked);
+        let tags = tracked.tags.clone();
+        let account = tracked.account.clone();
+        self.jobs.write().unwrap().insert(job.id, tracked);
+
+        self.spawn(state, job.id, urls, tags, account);
+
+        job
+    }
+
+    /// Resume imports that were still running when the process stopped
+    pub fn resume_saved(&self, state: Arc<AppState>) -> ClassifyResult<()> {
+        let Some(dir) = &self.config.state_path else {
+            return Ok(());
+        };
+
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => {
+                return Err(ClassifyError::StorageError(format!(
+                    "Failed to read import state directory: {}",
+                    e
+                )))
+            }
+        };
+
+        for entry in entries.flatten() {
+            let tracked = std::fs::read(entry.path())
+                .ok()
+                .and_then(|data| serde_json::from_slice::<TrackedImport>(&data).ok());
+
+            let Some(tracked) = tracked else {
+                warn!("Ignoring unreadable import state {:?}", entry.path());
+                continue;
+            };
+
+            let job_id = tracked.job.id;
+            let job_state = Arc::new(state.for_namespace(tracked.job.namespace.as_deref()));
+            let running = tracked.job.status == ImportStatus::Running;
+            let pending: Vec<String> = tracked.pending.iter().cloned().collect();
+            let tags = tracked.tags.clone();
+            let account = tracked.account.clone();
+            self.jobs.write().unwrap().insert(job_id, tracked);
+
+            if running {
+                info!(
+                    "Resuming import job {} with {} pending URLs",
+                    job_id,
+                    pending.len()
+                );
+                self.spawn(job_state, job_id, pending, tags, account);
+            }
+        }
+
+        self.evict_finished(Utc::now());
+        Ok(())
+    }
+
+    fn spawn(
+        &self,
+        state: Arc<AppState>,
+        job_id: Uuid,
+        urls: Vec<String>,
+        tags: HashMap<String, Vec<String>>,
+        account: Option<String>,
+    ) {
+        let workers = self.config.workers.max(1);
+        let limiter = HostRateLimiter::new(self.config.host_interval);
+
+        info!(
+            "Starting import job {} with {} URLs and {} workers",
+            job_id,
+            urls.len(),
+            workers
+        );
+
+        tokio::spawn(async move {
+            stream::iter(urls)
+                .for_each_concurrent(workers, |url| {
+                    let state = state.clone();
+                    let limiter = &limiter;
+                    let tags = tags.get(&url).map(Vec::as_slice).unwrap_or_default();
+                    let account = account.as_deref();
+                    async move {
+                        let result = if Content::looks_like_url(&url) {
+                            limiter.wait(&url).await;
+                            import_url(&state, url.clone(), tags, account).await
+                        } else {
+                            Err("Not a URL".to_string())
+                        };
+
+                        state.imports.record(&job_id, url, result);
+                    }
+                })
+                .await;
+
+            state.imports.finish(&job_id);
+        });
+    }
+
+    fn record(&self, id: &Uuid, url: String, result: Result<Ingested, String>) {
+        let snapshot = {
+            let mut jobs = self.jobs.write().unwrap();
+            let Some(tracked) = jobs.get_mut(id) else {
+                return;
+            };
+
+            tracked.pending.remove(&url);
+            let job = &mut tracked.job;
+            job.processed += 1;
+            match result {
+                Ok(Ingested::Created(_)) => job.created += 1,
+                Ok(Ingested::Duplicate(_)) => job.duplicates += 1,
+                Err(error) => {
+                    warn!("Failed to import {}: {}", url, error);
+                    job.failures.push(ImportFailure { url, error });
+                }
+            }
+
+            // Saving after every URL would rewrite large pending sets constantly
+            (job.processed % SAVE_EVERY == 0).then(|| tracked.clone())
+        };
+
+        if let Some(tracked) = snapshot {
+            self.save(&tracked);
+        }
+    }
+
+    fn finish(&self, id: &Uuid) {
+        let snapshot = self.jobs.write().unwrap().get_mut(id).map(|tracked| {
+            let job = &mut tracked.job;
+            info!(
+                "Import job {} completed: {} created, {} duplicates, {} skipped, {} failed",
+                id,
+                job.created,
+                job.duplicates,
+                job.skipped,
+                job.failures.len()
+            );
+            job.status = ImportStatus::Completed;
+            job.finished_at = Some(Utc::now());
+            tracked.clone()
+        });
+
+        if let Some(tracked) = snapshot {
+            self.save(&tracked);
+        }
+    }
+
+    fn state_file(&self, id: &Uuid) -> Option<PathBuf> {
+        let dir = self.config.state_path.as_ref()?;
+        Some(PathBuf::from(dir).join(format!("{}.json", id)))
+    }
+
+    fn remove_saved(&self, id: &Uuid) {
+        let Some(path) = self.state_file(id) else {
+            return;
+        };
+
+        match std::fs::remove_file(&path) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => warn!("Failed to remove import state {:?}: {}", path, e),
+        }
+    }
+
+    fn save(&self, tracked: &TrackedImport) {
+        let (Some(dir), Some(path)) = (&self.config.state_path, self.state_file(&tracked.job.id))
+        else {
+            return;
+        };
+
+        let result = std::fs::create_dir_all(dir).and_then(|_| {
+            let json = serde_json::to_vec(tracked).map_err(std::io::Error::other)?;
+            std::fs::write(&path, json)
+        });
+
+        if let Err(e) = result {
+            warn!("Failed to save import state {:?}: {}", path, e);
+        }
+    }
+}
@@ -0,0 +1,143 @@
+use crate::classifier::Classifier;
+use crate::config::ImportConfig;
+use crate::ingest::import::{parse_url_list, HostRateLimiter, ImportStatus};
+use crate::ClassifyResult;
+use mockall::mock;
+use std::time::Duration;
+
+mock! {
+    pub ClassifierMock {}
+
+    #[async_trait::async_trait]
+    impl Classifier for ClassifierMock {
+        async fn classify(&self, content: &str) -> ClassifyResult<Vec<String>>;
+        async fn classify_url(&self, url: &str) -> ClassifyResult<Vec<String>>;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::AppState;
+    use crate::storage::content::memory::MemoryContentStorage;
+    use crate::storage::tag::memory::MemoryTagStorage;
+    use crate::storage::usage::memory::MemoryUsageStorage;
+    use crate::usage::{current_month, UsageTracker};
+    use crate::UsageQuota;
+    use axum::{routing::get, Router};
+    use chrono::Utc;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn test_parse_url_list() {
+        let body = "https://example.com/a\n\n  https://example.com/b  \n# comment\n";
+        let urls = parse_url_list(body);
+
+        assert_eq!(urls, vec!["https://example.com/a", "https://example.com/b"]);
+    }
+
+    #[test]
+    fn test_host_rate_limiter_spaces_same_host() {
+        let limiter = HostRateLimiter::new(Duration::from_secs(10));
+
+        assert!(limiter.reserve("example.com").is_zero());
+        assert!(limiter.reserve("example.com") > Duration::from_secs(9));
+        assert!(limiter.reserve("example.com") > Duration::from_secs(19));
+
+        assert!(limiter.reserve("other.com").is_zero());
+    }
+
+    #[tokio::test]
+    async fn test_finished_imports_are_evicted_after_retention() {
+        let state = Arc::new(
+            AppState::new(
+                Arc::new(MockClassifierMock::new()),
+                Arc::new(MemoryContentStorage::new()),
+                Arc::new(MemoryTagStorage::new()),
+            )
+            .with_import_config(ImportConfig {
+                retention: Duration::from_secs(3600),
+                ..ImportConfig::default()
+            }),
+        );
+
+        let job = state.imports.start(state.clone(), Vec::new(), None, None);
+        while state.imports.get(&job.id).unwrap().status != ImportStatus::Completed {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+
+        // Still looked up within the retention period
+        assert_eq!(state.imports.evict_finished(Utc::now()), 0);
+        assert!(state.imports.get(&job.id).is_some());
+
+        let later = Utc::now() + chrono::Duration::hours(2);
+        assert_eq!(state.imports.evict_finished(later), 1);
+        assert!(state.imports.get(&job.id).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_imports_count_usage_towards_the_caller() {
+        let app = Router::new()
+            .route("/a", get(|| async { "Async Rust" }))
+            .route("/b", get(|| async { "Tokio runtime" }));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let mut classifier = MockClassifierMock::new();
+        classifier
+            .expect_classify()
+            .returning(|_| Ok(vec!["rust".to_string()]));
+
+        let quota = UsageQuota {
+            classifications: Some(1),
+            ..UsageQuota::default()
+        };
+        let state = Arc::new(
+            AppState::new(
+                Arc::new(classifier),
+                Arc::new(MemoryContentStorage::new()),
+                Arc::new(MemoryTagStorage::new()),
+            )
+            .with_import_config(ImportConfig {
+                workers: 1,
+                host_interval: Duration::ZERO,
+                ..ImportConfig::default()
+            })
+            .with_usage(Some(UsageTracker::new(
+                Arc::new(MemoryUsageStorage::new()),
+                quota,
+                HashMap::new(),
+            ))),
+        );
+
+        let urls = vec![
+            format!("http://{}/a", address),
+            format!("http://{}/b", address),
+        ];
+        let job = state
+            .imports
+            .start(state.clone(), urls, None, Some("key:1".to_string()));
+        while state.imports.get(&job.id).unwrap().status != ImportStatus::Completed {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+
+        let usage = state
+            .usage
+            .as_ref()
+            .unwrap()
+            .report(&current_month(), Some("key:1"))
+            .await
+            .unwrap();
+        assert_eq!(usage[0].usage.classifications, 1);
+        assert!(usage[0].usage.stored_bytes > 0);
+
+        // The quota ran out after the first URL
+        let job = state.imports.get(&job.id).unwrap();
+        assert_eq!(job.created, 1);
+        assert_eq!(job.failures.len(), 1);
+        assert!(job.failures[0].error.contains("quota"));
+    }
+}
@@ -0,0 +1,105 @@
+use rdkafka::config::ClientConfig;
+use rdkafka::consumer::{CommitMode, Consumer, StreamConsumer};
+use rdkafka::Message;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+use crate::api::AppState;
+use crate::config::KafkaConfig;
+use crate::ingest::ingest;
+use crate::ingest::payload::payload_text;
+use crate::shutdown;
+use crate::{ClassifyError, ClassifyResult};
+
+/// Consume content messages from the configured topic, committing each
+/// offset only after the content has been stored
+pub async fn consume(state: Arc<AppState>, config: KafkaConfig) -> ClassifyResult<()> {
+    let consumer: StreamConsumer = ClientConfig::new()
+        .set("bootstrap.servers", &config.brokers)
+        .set("group.id", &config.group_id)
+        .set("enable.auto.commit", "false")
+        .set("auto.offset.reset", "earliest")
+        .create()
+        .map_err(|e| {
+            ClassifyError::IngestError(format!("Failed to create Kafka consumer: {}", e))
+        })?;
+
+    consumer
+        .subscribe(&[config.topic.as_str()])
+        .map_err(|e| ClassifyError::IngestError(format!("Failed to subscribe to topic: {}", e)))?;
+
+    info!(
+        "Consuming content from Kafka topic {} as group {}",
+        config.topic, config.group_id
+    );
+
+    // Receive errors in a row, e.g. while the brokers are down
+    let mut failures = 0;
+    loop {
+        let message = match consumer.recv().await {
+            Ok(message) => {
+                failures = 0;
+                message
+            }
+            Err(e) => {
+                failures += 1;
+                let delay = Duration::from_secs(1 << failures.min(6));
+                warn!(
+                    "Failed to receive Kafka message (attempt {}), retrying in {}s: {}",
+                    failures,
+                    delay.as_secs(),
+                    e
+                );
+                tokio::select! {
+                    _ = tokio::time::sleep(delay) => continue,
+                    _ = shutdown::requested() => return Ok(()),
+                }
+            }
+        };
+
+        match message.payload().and_then(payload_text) {
+            Some(text) => {
+                let mut attempt = 0;
+                loop {
+                    match ingest(&state, text.clone()).await {
+                        Ok(ingested) => {
+                            info!(
+                                "Ingested Kafka message at offset {} as content {}",
+                                message.offset(),
+                                ingested.content().id
+                            );
+                            break;
+                        }
+                        Err(e) if attempt < config.max_retries => {
+                            attempt += 1;
+                            warn!(
+                                "Failed to ingest Kafka message at offset {} (attempt {}): {}",
+                                message.offset(),
+                                attempt,
+                                e
+                            );
+                            tokio::time::sleep(Duration::from_secs(1 << attempt.min(6))).await;
+                        }
+                        Err(e) => {
+                            error!(
+                                "Giving up on Kafka message at offset {}: {}",
+                                message.offset(),
+                                e
+                            );
+                            break;
+                        }
+                    }
+                }
+            }
+            None => warn!(
+                "Skipping Kafka message at offset {} without content",
+                message.offset()
+            ),
+        }
+
+        if let Err(e) = consumer.commit_message(&message, CommitMode::Async) {
+            warn!("Failed to commit Kafka offset {}: {}", message.offset(), e);
+        }
+    }
+}
@@ -0,0 +1,245 @@
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::config::{AppConfig, DedupLockConfig, DedupLockStoreType};
+use crate::{ClassifyError, ClassifyResult, Content};
+
+/// How often a waiting request looks for the content stored by the holder
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Short-lived locks, each taken with a token only its holder knows
+#[async_trait]
+pub trait LockStore: Send + Sync {
+    /// Take the lock on `key` for `ttl` unless it is held, returning whether it was taken
+    async fn acquire(&self, key: &str, token: &str, ttl: Duration) -> ClassifyResult<bool>;
+
+    /// Release the lock on `key` if it is still held with `token`. A lock that
+    /// expired and was taken by someone else is left alone.
+    async fn release(&self, key: &str, token: &str) -> ClassifyResult<()>;
+}
+
+/// Locks kept in memory, only requests to the same instance wait for each other
+#[derive(Default)]
+pub struct MemoryLockStore {
+    locks: Mutex<HashMap<String, (String, Instant)>>,
+}
+
+impl MemoryLockStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl LockStore for MemoryLockStore {
+    async fn acquire(&self, key: &str, token: &str, ttl: Duration) -> ClassifyResult<bool> {
+        let now = Instant::now();
+        let mut locks = self.locks.lock().unwrap();
+        locks.retain(|_, (_, expires)| *expires > now);
+
+        if locks.contains_key(key) {
+            return Ok(false);
+        }
+        locks.insert(key.to_string(), (token.to_string(), now + ttl));
+        Ok(true)
+    }
+
+    async fn release(&self, key: &str, token: &str) -> ClassifyResult<()> {
+        let mut locks = self.locks.lock().unwrap();
+        if locks.get(key).is_some_and(|(holder, _)| holder == token) {
+            locks.remove(key);
+        }
+        Ok(())
+    }
+}
+
+/// Locks kept in Redis with `SET NX PX`, shared by all instances of the service
+#[cfg(feature = "redis")]
+pub struct RedisLockStore {
+    connection: crate::storage::redis::SharedConnection,
+    prefix: String,
+    release: redis::Script,
+}
+
+#[cfg(feature = "redis")]
+impl RedisLockStore {
+    /// Delete the lock only while it holds our token
+    const RELEASE_SCRIPT: &'static str = r#"
+        if redis.call('GET', KEYS[1]) == ARGV[1] then
+            return redis.call('DEL', KEYS[1])
+        end
+        return 0
+    "#;
+
+    pub fn with_connection(
+        connection: crate::storage::redis::SharedConnection,
+        prefix: Option<&str>,
+    ) -> Self {
+        crate::storage::connections::register("redis", &connection);
+        Self {
+            connection,
+            prefix: prefix.unwrap_or("classify:").to_string(),
+            release: redis::Script::new(Self::RELEASE_SCRIPT),
+        }
+    }
+
+    fn lock_key(&self, key: &str) -> String {
+        format!("{}lock:{}", self.prefix, key)
+    }
+}
+
+#[cfg(feature = "redis")]
+#[async_trait]
+impl LockStore for RedisLockStore {
+    async fn acquire(&self, key: &str, token: &str, ttl: Duration) -> ClassifyResult<bool> {
+        let mut conn = self.connection.lock().await;
+        let reply: Option<String> = redis::cmd("SET")
+            .arg(self.lock_key(key))
+            .arg(token)
+            .arg("NX")
+            .arg("PX")
+            .arg(ttl.as_millis().max(1) as u64)
+            .query_async(&mut *conn)
+            .await
+            .map_err(|e| ClassifyError::StorageError(format!("Failed to take lock: {}", e)))?;
+        Ok(reply.is_some())
+    }
+
+    async fn release(&self, key: &str, token: &str) -> ClassifyResult<()> {
+        let mut conn = self.connection.lock().await;
+        self.release
+            .key(self.lock_key(key))
+            .arg(token)
+            .invoke_async::<_, i64>(&mut *conn)
+            .await
+            .map_err(|e| ClassifyError::StorageError(format!("Failed to release lock: {}", e)))?;
+        Ok(())
+    }
+}
+
+/// A lock on a content hash, released once the content is stored
+#[derive(Debug)]
+pub struct HashLock {
+    key: String,
+    token: String,
+}
+
+/// What a request classifying new content should do
+#[derive(Debug)]
+pub enum Claim {
+    /// The lock was taken, classify and store the content, then release it
+    Acquired(HashLock),
+    /// Another request stored the content while this one waited
+    Stored(Box<Content>),
+    /// Classify the content without the lock, because the holder didn't store
+    /// it in time or the lock store failed
+    Unlocked,
+}
+
+/// Locks on the hashes of content being classified, so content arriving at
+/// several instances at once is sent to the classifier only once. The others
+/// wait for it to be stored and return it as a duplicate.
+pub struct DedupLock {
+    store: Arc<dyn LockStore>,
+    ttl: Duration,
+    wait: Duration,
+}
+
+impl DedupLock {
+    pub fn new(store: Arc<dyn LockStore>, config: &DedupLockConfig) -> Self {
+        Self {
+            store,
+            ttl: config.ttl,
+            wait: config.wait,
+        }
+    }
+
+    /// Take the lock on `hash`, or wait for its holder to store the content,
+    /// looking it up with `find`. Without a working lock store the content is
+    /// classified without a lock, as it was before locking.
+    pub async fn claim<F, Fut>(&self, hash: &str, find: F) -> ClassifyResult<Claim>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = ClassifyResult<Option<Content>>>,
+    {
+        let key = format!("hash:{}", hash);
+        let token = Uuid::new_v4().to_string();
+        let deadline = Instant::now() + self.wait;
+        let mut waited = false;
+
+        loop {
+            match self.store.acquire(&key, &token, self.ttl).await {
+                Ok(true) => {
+                    // The holder we waited for may have stored the content
+                    // and released the lock between two lookups
+                    if waited {
+                        if let Some(content) = find().await? {
+                            self.release(HashLock { key, token }).await;
+                            return Ok(Claim::Stored(Box::new(content)));
+                        }
+                    }
+                    return Ok(Claim::Acquired(HashLock { key, token }));
+                }
+                Ok(false) => {}
+                Err(e) => {
+                    warn!("Classifying without a duplicate-check lock: {}", e);
+                    return Ok(Claim::Unlocked);
+                }
+            }
+
+            if Instant::now() >= deadline {
+                warn!(
+                    "Gave up waiting for content with hash {} to be classified elsewhere",
+                    hash
+                );
+                return Ok(Claim::Unlocked);
+            }
+
+            if !waited {
+                info!("Content with the same hash is being classified, waiting for it");
+                waited = true;
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+
+            if let Some(content) = find().await? {
+                return Ok(Claim::Stored(Box::new(content)));
+            }
+        }
+    }
+
+    /// Release a lock, it expires by itself when that fails
+    pub async fn release(&self, lock: HashLock) {
+        if let Err(e) = self.store.release(&lock.key, &lock.token).await {
+            warn!("Failed to release lock {}: {}", lock.key, e);
+        }
+    }
+}
+
+/// Create the duplicate-check lock configured by `DEDUP_LOCK_STORE`
+pub async fn create_dedup_lock(config: &AppConfig) -> ClassifyResult<Option<DedupLock>> {
+    let Some(dedup_lock) = &config.ingest.dedup_lock else {
+        return Ok(None);
+    };
+
+    let store: Arc<dyn LockStore> = match dedup_lock.store {
+        DedupLockStoreType::Memory => Arc::new(MemoryLockStore::new()),
+        #[cfg(feature = "redis")]
+        DedupLockStoreType::Redis => Arc::new(RedisLockStore::with_connection(
+            crate::storage::redis::connect_tag_storage(&config.tag_storage).await?,
+            config.tag_storage.redis_prefix.as_deref(),
+        )),
+        #[cfg(not(feature = "redis"))]
+        DedupLockStoreType::Redis => {
+            return Err(ClassifyError::ConfigError(
+                "The Redis dedup lock store requires building with the redis feature".to_string(),
+            ))
+        }
+    };
+
+    Ok(Some(DedupLock::new(store, dedup_lock)))
+}
@@ -0,0 +1,128 @@
+use crate::config::{DedupLockConfig, DedupLockStoreType};
+use crate::ingest::lock::{Claim, DedupLock, LockStore, MemoryLockStore};
+use crate::{ClassifyResult, Content};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dedup_lock(store: Arc<MemoryLockStore>, wait: Duration) -> DedupLock {
+        let config = DedupLockConfig {
+            store: DedupLockStoreType::Memory,
+            ttl: Duration::from_secs(60),
+            wait,
+        };
+        DedupLock::new(store, &config)
+    }
+
+    #[tokio::test]
+    async fn test_memory_lock_store_releases_only_with_token() {
+        let store = MemoryLockStore::new();
+        let ttl = Duration::from_secs(60);
+
+        assert!(store.acquire("hash:a", "first", ttl).await.unwrap());
+        assert!(!store.acquire("hash:a", "second", ttl).await.unwrap());
+        assert!(store.acquire("hash:b", "second", ttl).await.unwrap());
+
+        store.release("hash:a", "second").await.unwrap();
+        assert!(!store.acquire("hash:a", "second", ttl).await.unwrap());
+
+        store.release("hash:a", "first").await.unwrap();
+        assert!(store.acquire("hash:a", "second", ttl).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_memory_lock_store_expires_locks() {
+        let store = MemoryLockStore::new();
+
+        assert!(store
+            .acquire("hash:a", "first", Duration::from_millis(20))
+            .await
+            .unwrap());
+        tokio::time::sleep(Duration::from_millis(40)).await;
+        assert!(store
+            .acquire("hash:a", "second", Duration::from_secs(60))
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_waiting_request_returns_stored_content() {
+        let store = Arc::new(MemoryLockStore::new());
+        let lock = dedup_lock(store.clone(), Duration::from_secs(5));
+        let stored: Arc<Mutex<Option<Content>>> = Arc::new(Mutex::new(None));
+        let find = || {
+            let stored = stored.clone();
+            async move { ClassifyResult::Ok(stored.lock().unwrap().clone()) }
+        };
+
+        let Claim::Acquired(held) = lock.claim("abc", find).await.unwrap() else {
+            panic!("Expected the free lock to be taken");
+        };
+
+        // The holder stores the content while the second request waits
+        let content = Content::new("Some text".to_string());
+        let holder = {
+            let stored = stored.clone();
+            let content = content.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(Duration::from_millis(300)).await;
+                *stored.lock().unwrap() = Some(content);
+            })
+        };
+
+        match lock.claim("abc", find).await.unwrap() {
+            Claim::Stored(found) => assert_eq!(found.id, content.id),
+            other => panic!("Expected the stored content, got {:?}", other),
+        }
+        holder.await.unwrap();
+
+        lock.release(held).await;
+        assert!(store
+            .acquire("hash:abc", "next", Duration::from_secs(60))
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_waiting_after_wait() {
+        let store = Arc::new(MemoryLockStore::new());
+        let lock = dedup_lock(store.clone(), Duration::from_millis(100));
+        let find = || async { ClassifyResult::Ok(None) };
+
+        let Claim::Acquired(_held) = lock.claim("abc", find).await.unwrap() else {
+            panic!("Expected the free lock to be taken");
+        };
+
+        assert!(matches!(
+            lock.claim("abc", find).await.unwrap(),
+            Claim::Unlocked
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_lock_is_taken_after_holder_failed() {
+        let store = Arc::new(MemoryLockStore::new());
+        let lock = dedup_lock(store.clone(), Duration::from_secs(5));
+        let find = || async { ClassifyResult::Ok(None) };
+
+        let Claim::Acquired(held) = lock.claim("abc", find).await.unwrap() else {
+            panic!("Expected the free lock to be taken");
+        };
+
+        // The holder fails to classify and releases the lock without storing
+        let release = tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            lock.release(held).await;
+            lock
+        });
+        let lock = release.await.unwrap();
+
+        assert!(matches!(
+            lock.claim("abc", find).await.unwrap(),
+            Claim::Acquired(_)
+        ));
+    }
+}
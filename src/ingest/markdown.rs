@@ -0,0 +1,105 @@
+use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+use regex::Regex;
+use std::sync::OnceLock;
+
+/// Markdown stripped down to what matters for classification
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarkdownDocument {
+    /// Text without front matter and markup
+    pub text: String,
+    /// Absolute http(s) URLs linked from the document, in order of appearance
+    pub links: Vec<String>,
+}
+
+fn markdown_patterns() -> &'static [Regex] {
+    static PATTERNS: OnceLock<Vec<Regex>> = OnceLock::new();
+    PATTERNS.get_or_init(|| {
+        [
+            r"(?m)^#{1,6}\s+\S",           // ATX headings
+            r"\[[^\]]+\]\([^)\s]+\)",      // inline links and images
+            r"(?m)^\s*```",                // fenced code blocks
+            r"(?m)^\s*[-*+]\s+\S",         // bullet lists
+            r"(?m)^\s*>\s",                // block quotes
+            r"(\*\*|__)[^*_\n]+(\*\*|__)", // bold
+            r"(?m)^\[[^\]]+\]:\s+\S",      // link reference definitions
+        ]
+        .iter()
+        .map(|pattern| Regex::new(pattern).unwrap())
+        .collect()
+    })
+}
+
+/// Strip a leading YAML (`---`) or TOML (`+++`) front matter block
+fn strip_front_matter(text: &str) -> Option<&str> {
+    let trimmed = text.trim_start();
+    let fence = ["---", "+++"]
+        .into_iter()
+        .find(|fence| trimmed.starts_with(&format!("{}\n", fence)))?;
+
+    let rest = &trimmed[fence.len() + 1..];
+    let end = rest
+        .match_indices(fence)
+        .find(|(index, _)| *index == 0 || rest[..*index].ends_with('\n'))?
+        .0;
+
+    Some(rest[end + fence.len()..].trim_start_matches(['\r', '\n']))
+}
+
+/// Whether text looks like Markdown rather than plain prose
+pub fn looks_like_markdown(text: &str) -> bool {
+    if strip_front_matter(text).is_some() {
+        return true;
+    }
+
+    // A single bullet or bold word is common in plain text, require two signals
+    markdown_patterns()
+        .iter()
+        .filter(|pattern| pattern.is_match(text))
+        .count()
+        >= 2
+}
+
+/// Parse Markdown into plain text and the URLs it links to
+pub fn parse_markdown(markdown: &str) -> MarkdownDocument {
+    let body = strip_front_matter(markdown).unwrap_or(markdown);
+
+    let mut text = String::new();
+    let mut links: Vec<String> = Vec::new();
+
+    let options =
+        Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+    for event in Parser::new_ext(body, options) {
+        match event {
+            Event::Text(value) | Event::Code(value) => text.push_str(&value),
+            Event::SoftBreak => text.push(' '),
+            Event::HardBreak => text.push('\n'),
+            Event::Start(Tag::Link { dest_url, .. }) => {
+                let url = dest_url.to_string();
+                if (url.starts_with("http://") || url.starts_with("https://"))
+                    && !links.contains(&url)
+                {
+                    links.push(url);
+                }
+            }
+            Event::End(
+                TagEnd::Paragraph
+                | TagEnd::Heading(_)
+                | TagEnd::Item
+                | TagEnd::CodeBlock
+                | TagEnd::TableRow
+                | TagEnd::BlockQuote(_),
+            ) => text.push('\n'),
+            Event::End(TagEnd::TableCell) => text.push(' '),
+            _ => {}
+        }
+    }
+
+    let text = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    MarkdownDocument { text, links }
+}
@@ -0,0 +1,37 @@
+use crate::ingest::markdown::{looks_like_markdown, parse_markdown};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_looks_like_markdown() {
+        assert!(looks_like_markdown(
+            "# Notes\n\nRead [the book](https://doc.rust-lang.org/book/)"
+        ));
+        assert!(looks_like_markdown("---\ntitle: Notes\n---\nJust text"));
+        assert!(!looks_like_markdown(
+            "Rust is a systems programming language."
+        ));
+        assert!(!looks_like_markdown("- just one bullet"));
+    }
+
+    #[test]
+    fn test_parse_markdown() {
+        let markdown = "---\ntitle: Rust notes\ntags: [draft]\n---\n\n# Ownership\n\nRead **the** [Rust book](https://doc.rust-lang.org/book/) and\nthe [reference](https://doc.rust-lang.org/reference/).\n\n- [local](./notes.md)\n- `Box<T>` and [the book](https://doc.rust-lang.org/book/) again\n";
+
+        let document = parse_markdown(markdown);
+
+        assert_eq!(
+            document.text,
+            "Ownership\nRead the Rust book and the reference.\nlocal\nBox<T> and the book again"
+        );
+        assert_eq!(
+            document.links,
+            vec![
+                "https://doc.rust-lang.org/book/".to_string(),
+                "https://doc.rust-lang.org/reference/".to_string(),
+            ]
+        );
+    }
+}
@@ -0,0 +1,374 @@
+pub mod bookmarks;
+pub mod email;
+pub mod imap;
+pub mod import;
+#[cfg(feature = "kafka")]
+pub mod kafka;
+pub mod lock;
+pub mod markdown;
+#[cfg(feature = "mqtt")]
+pub mod mqtt;
+pub mod payload;
+pub mod slack;
+pub mod telegram;
+pub mod webhook;
+
+#[cfg(test)]
+mod bookmarks_test;
+#[cfg(test)]
+mod email_test;
+#[cfg(test)]
+mod import_test;
+#[cfg(test)]
+mod lock_test;
+#[cfg(test)]
+mod markdown_test;
+#[cfg(test)]
+mod payload_test;
+#[cfg(test)]
+mod slack_test;
+#[cfg(test)]
+mod telegram_test;
+#[cfg(test)]
+mod webhook_test;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+use crate::api::{AppState, SNAPSHOT_ATTACHMENT};
+use crate::config::AppConfig;
+use crate::extract::{fetch_and_extract, page_metadata, DocumentKind};
+use crate::ingest::lock::Claim;
+use crate::ingest::markdown::{looks_like_markdown, parse_markdown};
+use crate::jobs::spawn_periodic;
+use crate::shutdown;
+use crate::web::canonicalize_url;
+use crate::{ClassifyResult, Content, Usage};
+
+/// Result of running a piece of content through the classification pipeline
+#[derive(Debug, Clone)]
+pub enum Ingested {
+    /// The content was classified and stored
+    Created(Content),
+    /// Content with the same hash already exists
+    Duplicate(Content),
+}
+
+impl Ingested {
+    pub fn content(&self) -> &Content {
+        match self {
+            Self::Created(content) | Self::Duplicate(content) => content,
+        }
+    }
+}
+
+/// Classify text or a URL and store the result in content and tag storage,
+/// skipping content that was stored before
+pub async fn ingest(state: &AppState, text: String) -> ClassifyResult<Ingested> {
+    ingest_with_tags(state, text, &[]).await
+}
+
+/// Like [`ingest`], adding `extra_tags` (e.g. source metadata) to the classifier's tags
+pub async fn ingest_with_tags(
+    state: &AppState,
+    text: String,
+    extra_tags: &[String],
+) -> ClassifyResult<Ingested> {
+    ingest_with_metadata(state, text, extra_tags, HashMap::new()).await
+}
+
+/// Like [`ingest_with_tags`], storing `metadata` with newly created content.
+/// Duplicates are returned as stored, their metadata is left untouched.
+pub async fn ingest_with_metadata(
+    state: &AppState,
+    text: String,
+    extra_tags: &[String],
+    metadata: HashMap<String, String>,
+) -> ClassifyResult<Ingested> {
+    // Canonicalize URLs so tracking parameters and similar noise don't defeat dedup
+    let text = if Content::looks_like_url(&text) {
+        canonicalize_url(&text)?
+    } else {
+        text
+    };
+
+    // Content stored before the hash settings changed is found by its old hash
+    let mut hashes = Content::lookup_hashes(&text);
+    if let Some(namespace) = &state.namespace {
+        for hash in hashes.iter_mut() {
+            *hash = Content::namespaced_hash(namespace, hash);
+        }
+    }
+    let content_hash = hashes[0].clone();
+
+    let known_missing = state
+        .hash_cache
+        .as_ref()
+        .is_some_and(|cache| cache.is_known_missing(&content_hash));
+
+    if !known_missing {
+        for hash in &hashes {
+            if let Some(existing_content) = state.content_storage.find_by_hash(hash).await? {
+                info!("Found existing content with the same hash");
+                return Ok(Ingested::Duplicate(existing_content));
+            }
+        }
+
+        if let Some(cache) = &state.hash_cache {
+            cache.record_missing(&content_hash);
+        }
+    }
+
+    let lock = match &state.dedup_lock {
+        Some(dedup_lock) => match dedup_lock
+            .claim(&content_hash, || {
+                state.content_storage.find_by_hash(&content_hash)
+            })
+            .await?
+        {
+            Claim::Acquired(lock) => Some(lock),
+            Claim::Stored(existing_content) => {
+                info!("Content with the same hash was stored meanwhile");
+                if let Some(cache) = &state.hash_cache {
+                    cache.invalidate(&content_hash);
+                }
+                return Ok(Ingested::Duplicate(*existing_content));
+            }
+            Claim::Unlocked => None,
+        },
+        None => None,
+    };
+
+    let created = classify_and_store(state, text, extra_tags, metadata, &content_hash).await;
+    if let (Some(dedup_lock), Some(lock)) = (&state.dedup_lock, lock) {
+        dedup_lock.release(lock).await;
+    }
+    created.map(Ingested::Created)
+}
+
+/// Tags the classifier gives stored content now, with the tag policy applied.
+/// URLs are fetched again, Markdown is classified without its markup.
+pub async fn classify_stored(state: &AppState, content: &Content) -> ClassifyResult<Vec<String>> {
+    let tags = if content.is_url() {
+        let document = fetch_and_extract(&state.http_client, &content.content).await?;
+        state.classifier.classify(&document.text).await?
+    } else if looks_like_markdown(&content.content) {
+        let markdown = parse_markdown(&content.content);
+        state.classifier.classify(&markdown.text).await?
+    } else {
+        state.classifier.classify(&content.content).await?
+    };
+
+    crate::usage::record(Usage {
+        classifications: 1,
+        stored_bytes: 0,
+        tokens: 0,
+    });
+
+    Ok(state.ingest.tags.apply(&tags))
+}
+
+/// Classify new content and store it with its tags
+async fn classify_and_store(
+    state: &AppState,
+    text: String,
+    extra_tags: &[String],
+    metadata: HashMap<String, String>,
+    content_hash: &str,
+) -> ClassifyResult<Content> {
+    let mut content = Content::new(text)
+        .with_metadata(metadata)
+        .with_namespace(state.namespace.clone());
+
+    // Markdown is stored as is but classified without front matter and markup
+    let markdown = (!content.is_url() && looks_like_markdown(&content.content))
+        .then(|| parse_markdown(&content.content));
+
+    let mut snapshot = None;
+
+    // Tags and the text they were given for
+    let (tags, classified_text) = if content.is_url() {
+        info!("Detected URL: {}", &content.content);
+        let document = fetch_and_extract(&state.http_client, &content.content).await?;
+        info!("Fetched document of type {}", document.content_type);
+
+        let tags = state.classifier.classify(&document.text).await?;
+
+        content.content_type = Some(document.content_type.clone());
+        if document.kind() == DocumentKind::Html {
+            let page = String::from_utf8_lossy(&document.body).into_owned();
+            let preview = page_metadata(&page, &content.content);
+            content = content.with_page_metadata(preview);
+            content.page_hash = Some(Content::generate_hash(&page));
+            content.last_checked_at = Some(content.created_at);
+            // Only HTML pages are archived, the snapshot is served as text/html
+            if state.archive_snapshots {
+                snapshot = Some(page);
+            }
+        }
+        (tags, document.text)
+    } else if let Some(markdown) = &markdown {
+        info!("Detected Markdown content");
+        content.content_type = Some("text/markdown".to_string());
+        let tags = state.classifier.classify(&markdown.text).await?;
+        (tags, markdown.text.clone())
+    } else {
+        info!("Detected text content");
+        content.content_type = Some("text/plain".to_string());
+        let tags = state.classifier.classify(&content.content).await?;
+        (tags, content.content.clone())
+    };
+
+    // The blocklist applies to the classifier, tags given by the source are kept
+    let mut tags = state.ingest.tags.apply(&tags);
+    tags.extend_from_slice(extra_tags);
+    let tags = state.ingest.tags.normalize_tags(&tags);
+
+    let mut content = content.with_tags(tags.clone());
+
+    // Slugs are short enough to collide now and then
+    while let Some(slug) = &content.slug {
+        if !state.content_storage.slug_exists(slug).await? {
+            break;
+        }
+        content.slug = Some(Content::generate_slug());
+    }
+
+    state
+        .storage_transaction()
+        .store_with_tags(&content, &tags)
+        .await?;
+
+    if let Some(cache) = &state.hash_cache {
+        cache.invalidate(content_hash);
+    }
+
+    let snapshot_len = snapshot.as_ref().map_or(0, String::len);
+    if let Some(page) = snapshot {
+        info!("Archiving page snapshot for content {}", content.id);
+        state
+            .content_storage
+            .store_attachment(
+                &content.id.to_string(),
+                SNAPSHOT_ATTACHMENT,
+                page.as_bytes(),
+            )
+            .await?;
+    }
+
+    if let Some(index) = &state.semantic_index {
+        // Content without an embedding is picked up by the backfill job
+        if let Err(e) = index.index(&content, &classified_text).await {
+            warn!("Failed to embed content {}: {}", content.id, e);
+        }
+    }
+
+    if let Some(tag_trends) = &state.tag_trends {
+        tag_trends
+            .record(state.namespace.as_deref(), &tags, content.created_at)
+            .await;
+    }
+
+    if let Some(heartbeat) = &state.heartbeat {
+        heartbeat.classified();
+    }
+
+    crate::usage::record(Usage {
+        classifications: 1,
+        stored_bytes: (content.content.len() + snapshot_len) as u64,
+        tokens: 0,
+    });
+
+    if let Some(markdown) = markdown.filter(|_| state.ingest.classify_markdown_links) {
+        for url in markdown.links {
+            // Boxed because the linked URL goes through this same pipeline
+            if let Err(e) = Box::pin(ingest(state, url.clone())).await {
+                warn!(
+                    "Failed to classify {} linked from {}: {}",
+                    url, content.id, e
+                );
+            }
+        }
+    }
+
+    Ok(content)
+}
+
+/// Start the ingestion workers enabled in the configuration
+pub fn spawn_ingestion_workers(state: Arc<AppState>, config: &AppConfig) -> Vec<JoinHandle<()>> {
+    let mut handles = Vec::new();
+
+    if let Some(imap) = &config.imap {
+        let imap = imap.clone();
+        let state = state.clone();
+        handles.push(spawn_periodic("imap", imap.poll_interval, move || {
+            imap::poll_mailbox(state.clone(), imap.clone())
+        }));
+    }
+
+    if let Some(telegram) = &config.telegram {
+        let telegram = telegram.clone();
+        let state = state.clone();
+        handles.push(tokio::spawn(async move {
+            tokio::select! {
+                result = telegram::run_bot(state, telegram) => {
+                    if let Err(e) = result {
+                        tracing::error!("Telegram bot stopped: {}", e);
+                    }
+                }
+                // Consumers run until the process stops, they are cancelled then
+                _ = shutdown::requested() => {}
+            }
+        }));
+    }
+
+    if let Some(kafka) = &config.kafka {
+        #[cfg(feature = "kafka")]
+        {
+            let kafka = kafka.clone();
+            let state = state.clone();
+            handles.push(tokio::spawn(async move {
+                tokio::select! {
+                    result = kafka::consume(state, kafka) => {
+                        if let Err(e) = result {
+                            tracing::error!("Kafka consumer stopped: {}", e);
+                        }
+                    }
+                    _ = shutdown::requested() => {}
+                }
+            }));
+        }
+        #[cfg(not(feature = "kafka"))]
+        tracing::warn!(
+            "KAFKA_BROKERS is set to {} but classify was built without the kafka feature",
+            kafka.brokers
+        );
+    }
+
+    if let Some(mqtt) = &config.mqtt {
+        #[cfg(feature = "mqtt")]
+        {
+            let mqtt = mqtt.clone();
+            let state = state.clone();
+            handles.push(tokio::spawn(async move {
+                tokio::select! {
+                    result = mqtt::subscribe(state, mqtt) => {
+                        if let Err(e) = result {
+                            tracing::error!("MQTT subscriber stopped: {}", e);
+                        }
+                    }
+                    _ = shutdown::requested() => {}
+                }
+            }));
+        }
+        #[cfg(not(feature = "mqtt"))]
+        tracing::warn!(
+            "MQTT_HOST is set to {} but classify was built without the mqtt feature",
+            mqtt.host
+        );
+    }
+
+    handles
+}
@@ -0,0 +1,67 @@
+use rumqttc::{AsyncClient, Event, MqttOptions, Packet, QoS};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::api::AppState;
+use crate::config::MqttConfig;
+use crate::ingest::ingest_with_tags;
+use crate::ingest::payload::payload_text;
+use crate::ClassifyResult;
+
+/// Subscribe to the configured topics and classify every message published on them
+pub async fn subscribe(state: Arc<AppState>, config: MqttConfig) -> ClassifyResult<()> {
+    let mut options = MqttOptions::new(&config.client_id, &config.host, config.port);
+    options.set_keep_alive(Duration::from_secs(30));
+
+    if let (Some(username), Some(password)) = (&config.username, &config.password) {
+        options.set_credentials(username, password);
+    }
+
+    let (client, mut event_loop) = AsyncClient::new(options, 16);
+
+    info!(
+        "Connecting to MQTT broker {}:{} for topics {:?}",
+        config.host, config.port, config.topics
+    );
+
+    loop {
+        let event = match event_loop.poll().await {
+            Ok(event) => event,
+            Err(e) => {
+                // The event loop reconnects on the next poll
+                warn!("MQTT connection error: {}", e);
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                continue;
+            }
+        };
+
+        match event {
+            Event::Incoming(Packet::ConnAck(_)) => {
+                // Subscriptions are re-established after every (re)connect
+                for topic in &config.topics {
+                    if let Err(e) = client.subscribe(topic, QoS::AtLeastOnce).await {
+                        warn!("Failed to subscribe to MQTT topic {}: {}", topic, e);
+                    }
+                }
+            }
+            Event::Incoming(Packet::Publish(publish)) => {
+                let Some(text) = payload_text(&publish.payload) else {
+                    warn!("Skipping MQTT message on {} without content", publish.topic);
+                    continue;
+                };
+
+                let tags = vec![format!("topic:{}", publish.topic)];
+                match ingest_with_tags(&state, text, &tags).await {
+                    Ok(ingested) => info!(
+                        "Ingested MQTT message on {} as content {}",
+                        publish.topic,
+                        ingested.content().id
+                    ),
+                    Err(e) => warn!("Failed to ingest MQTT message on {}: {}", publish.topic, e),
+                }
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,15 @@
+use crate::ClassifyRequest;
+
+/// Extract the content to classify from a message payload, which is either a
+/// JSON classify request or plain UTF-8 text
+pub fn payload_text(payload: &[u8]) -> Option<String> {
+    if let Ok(request) = serde_json::from_slice::<ClassifyRequest>(payload) {
+        return Some(request.content);
+    }
+
+    std::str::from_utf8(payload)
+        .ok()
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+        .map(String::from)
+}
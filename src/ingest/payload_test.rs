@@ -0,0 +1,20 @@
+use crate::ingest::payload::payload_text;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payload_text() {
+        assert_eq!(
+            payload_text(br#"{"content": "Rust ownership"}"#),
+            Some("Rust ownership".to_string())
+        );
+        assert_eq!(
+            payload_text(b"  https://example.com/post \n"),
+            Some("https://example.com/post".to_string())
+        );
+        assert_eq!(payload_text(b"   "), None);
+        assert_eq!(payload_text(&[0xff, 0xfe]), None);
+    }
+}
@@ -0,0 +1,186 @@
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use sha2::Sha256;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use crate::api::AppState;
+use crate::config::SlackConfig;
+use crate::ingest::{ingest, Ingested};
+use crate::{ClassifyError, ClassifyResult};
+
+const SLACK_API_URL: &str = "https://slack.com/api";
+/// Requests signed longer ago than this are rejected to prevent replays
+const MAX_REQUEST_AGE_SECS: i64 = 5 * 60;
+
+/// Slash command invocation, sent form encoded
+#[derive(Debug, Clone, Default)]
+pub struct SlashCommand {
+    pub command: String,
+    pub text: String,
+    pub user_id: String,
+    pub channel_id: String,
+    pub response_url: String,
+}
+
+impl SlashCommand {
+    pub fn parse(body: &[u8]) -> Self {
+        let mut command = Self::default();
+        for (key, value) in url::form_urlencoded::parse(body) {
+            let value = value.into_owned();
+            match key.as_ref() {
+                "command" => command.command = value,
+                "text" => command.text = value,
+                "user_id" => command.user_id = value,
+                "channel_id" => command.channel_id = value,
+                "response_url" => command.response_url = value,
+                _ => {}
+            }
+        }
+        command
+    }
+}
+
+/// Payload of the Events API
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SlackEvent {
+    /// Sent once when the request URL is configured in the Slack app
+    UrlVerification {
+        challenge: String,
+    },
+    EventCallback {
+        event: Value,
+    },
+    #[serde(other)]
+    Other,
+}
+
+/// A `link_shared` event, sent when a message contains a link on one of the
+/// app's unfurl domains
+#[derive(Debug, Deserialize)]
+pub struct LinkShared {
+    pub channel: String,
+    pub message_ts: String,
+    pub links: Vec<SharedLink>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SharedLink {
+    pub url: String,
+}
+
+/// Check the `X-Slack-Signature` of a request against the signing secret
+pub fn verify_signature(
+    signing_secret: &str,
+    timestamp: &str,
+    body: &[u8],
+    signature: &str,
+    now: i64,
+) -> bool {
+    let Ok(sent_at) = timestamp.parse::<i64>() else {
+        return false;
+    };
+    if (now - sent_at).abs() > MAX_REQUEST_AGE_SECS {
+        return false;
+    }
+
+    let Some(signature) = signature
+        .strip_prefix("v0=")
+        .and_then(|signature| hex::decode(signature).ok())
+    else {
+        return false;
+    };
+
+    let Ok(mut mac) = Hmac::<Sha256>::new_from_slice(signing_secret.as_bytes()) else {
+        return false;
+    };
+    mac.update(format!("v0:{}:", timestamp).as_bytes());
+    mac.update(body);
+    mac.verify_slice(&signature).is_ok()
+}
+
+/// Message text describing the tags assigned to classified content
+pub fn tags_message(result: &ClassifyResult<Ingested>) -> String {
+    match result {
+        Ok(Ingested::Created(content)) => format!("Tags: {}", content.tags.join(", ")),
+        Ok(Ingested::Duplicate(content)) => {
+            format!("Already stored. Tags: {}", content.tags.join(", "))
+        }
+        Err(e) => format!("Failed to classify: {}", e),
+    }
+}
+
+/// Classify the text of a slash command and post the tags back to the channel
+/// through the command's response URL
+pub async fn run_command(state: Arc<AppState>, command: SlashCommand) {
+    let result = ingest(&state, command.text.trim().to_string()).await;
+    if let Ok(ingested) = &result {
+        info!(
+            "Ingested Slack command from {} as content {}",
+            command.user_id,
+            ingested.content().id
+        );
+    }
+
+    let response = state
+        .http_client
+        .post(&command.response_url)
+        .json(&json!({
+            "response_type": "in_channel",
+            "text": format!("{}\n{}", command.text.trim(), tags_message(&result)),
+        }))
+        .send()
+        .await;
+
+    if let Err(e) = response {
+        warn!("Failed to post Slack command response: {}", e);
+    }
+}
+
+/// Classify the links of a `link_shared` event and unfurl them with their tags
+pub async fn unfurl_links(
+    state: Arc<AppState>,
+    config: Arc<SlackConfig>,
+    event: LinkShared,
+) -> ClassifyResult<()> {
+    let Some(bot_token) = &config.bot_token else {
+        return Err(ClassifyError::IngestError(
+            "SLACK_BOT_TOKEN is required to unfurl links".to_string(),
+        ));
+    };
+
+    let mut unfurls = serde_json::Map::new();
+    for link in event.links {
+        let result = ingest(&state, link.url.clone()).await;
+        unfurls.insert(link.url, json!({ "text": tags_message(&result) }));
+    }
+
+    let response: Value = state
+        .http_client
+        .post(format!("{}/chat.unfurl", SLACK_API_URL))
+        .bearer_auth(bot_token)
+        .json(&json!({
+            "channel": event.channel,
+            "ts": event.message_ts,
+            "unfurls": unfurls,
+        }))
+        .send()
+        .await
+        .map_err(|e| ClassifyError::IngestError(format!("Slack chat.unfurl failed: {}", e)))?
+        .json()
+        .await
+        .map_err(|e| {
+            ClassifyError::IngestError(format!("Invalid Slack chat.unfurl response: {}", e))
+        })?;
+
+    if response["ok"] != Value::Bool(true) {
+        return Err(ClassifyError::IngestError(format!(
+            "Slack chat.unfurl failed: {}",
+            response["error"].as_str().unwrap_or_default()
+        )));
+    }
+
+    Ok(())
+}
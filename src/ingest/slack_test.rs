@@ -0,0 +1,83 @@
+use crate::ingest::slack::{verify_signature, SlackEvent, SlashCommand};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sign(secret: &str, timestamp: &str, body: &[u8]) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+        mac.update(format!("v0:{}:", timestamp).as_bytes());
+        mac.update(body);
+        format!("v0={}", hex::encode(mac.finalize().into_bytes()))
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        let body = b"command=%2Fclassify&text=https%3A%2F%2Fwww.rust-lang.org";
+        let signature = sign("secret", "1700000000", body);
+
+        assert!(verify_signature(
+            "secret",
+            "1700000000",
+            body,
+            &signature,
+            1700000010
+        ));
+        // Wrong secret, tampered body, stale timestamp
+        assert!(!verify_signature(
+            "other",
+            "1700000000",
+            body,
+            &signature,
+            1700000010
+        ));
+        assert!(!verify_signature(
+            "secret",
+            "1700000000",
+            b"command=%2Fclassify&text=tampered",
+            &signature,
+            1700000010
+        ));
+        assert!(!verify_signature(
+            "secret",
+            "1700000000",
+            body,
+            &signature,
+            1700001000
+        ));
+        assert!(!verify_signature(
+            "secret",
+            "1700000000",
+            body,
+            "v0=zz",
+            1700000010
+        ));
+    }
+
+    #[test]
+    fn test_parse_slash_command() {
+        let command = SlashCommand::parse(
+            b"command=%2Fclassify&text=https%3A%2F%2Fwww.rust-lang.org&user_id=U1&channel_id=C1&response_url=https%3A%2F%2Fhooks.slack.com%2Fcommands%2F1",
+        );
+
+        assert_eq!(command.command, "/classify");
+        assert_eq!(command.text, "https://www.rust-lang.org");
+        assert_eq!(command.user_id, "U1");
+        assert_eq!(command.channel_id, "C1");
+        assert_eq!(command.response_url, "https://hooks.slack.com/commands/1");
+    }
+
+    #[test]
+    fn test_parse_events() {
+        let event: SlackEvent =
+            serde_json::from_str(r#"{"type":"url_verification","token":"t","challenge":"abc"}"#)
+                .unwrap();
+        assert!(matches!(event, SlackEvent::UrlVerification { challenge } if challenge == "abc"));
+
+        let event: SlackEvent =
+            serde_json::from_str(r#"{"type":"app_rate_limited","minute_rate_limited":1}"#).unwrap();
+        assert!(matches!(event, SlackEvent::Other));
+    }
+}
@@ -0,0 +1,189 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::api::AppState;
+use crate::config::TelegramConfig;
+use crate::ingest::{ingest, Ingested};
+use crate::{ClassifyError, ClassifyResult};
+
+const TELEGRAM_API_URL: &str = "https://api.telegram.org";
+/// Seconds the server holds a getUpdates request open waiting for messages
+const LONG_POLL_TIMEOUT: u64 = 30;
+
+#[derive(Debug, Deserialize)]
+struct TelegramResponse<T> {
+    ok: bool,
+    result: Option<T>,
+    description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Update {
+    update_id: i64,
+    message: Option<Message>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Message {
+    message_id: i64,
+    chat: Chat,
+    text: Option<String>,
+    caption: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Chat {
+    id: i64,
+}
+
+#[derive(Debug, Serialize)]
+struct SendMessage<'a> {
+    chat_id: i64,
+    text: &'a str,
+    reply_to_message_id: i64,
+}
+
+/// Text the bot replies with after handling a message
+pub fn reply_text(result: &ClassifyResult<Ingested>) -> String {
+    match result {
+        Ok(Ingested::Created(content)) => format!("Tags: {}", content.tags.join(", ")),
+        Ok(Ingested::Duplicate(content)) => {
+            format!("Already stored. Tags: {}", content.tags.join(", "))
+        }
+        Err(e) => format!("Failed to classify: {}", e),
+    }
+}
+
+/// Whether messages from a chat may be classified, all chats are allowed
+/// when no allow list is configured
+pub fn is_allowed(config: &TelegramConfig, chat_id: i64) -> bool {
+    config.allowed_chats.is_empty() || config.allowed_chats.contains(&chat_id)
+}
+
+struct TelegramBot {
+    client: reqwest::Client,
+    base_url: String,
+}
+
+impl TelegramBot {
+    fn new(token: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: format!("{}/bot{}", TELEGRAM_API_URL, token),
+        }
+    }
+
+    async fn get_updates(&self, offset: i64) -> ClassifyResult<Vec<Update>> {
+        let response = self
+            .client
+            .get(format!("{}/getUpdates", self.base_url))
+            .query(&[
+                ("offset", offset.to_string()),
+                ("timeout", LONG_POLL_TIMEOUT.to_string()),
+            ])
+            .timeout(Duration::from_secs(LONG_POLL_TIMEOUT + 10))
+            .send()
+            .await
+            // The error would contain the request URL and with it the bot token
+            .map_err(|e| {
+                ClassifyError::IngestError(format!(
+                    "Telegram getUpdates failed: {}",
+                    e.without_url()
+                ))
+            })?
+            .json::<TelegramResponse<Vec<Update>>>()
+            .await
+            .map_err(|e| {
+                ClassifyError::IngestError(format!(
+                    "Invalid Telegram getUpdates response: {}",
+                    e.without_url()
+                ))
+            })?;
+
+        if !response.ok {
+            return Err(ClassifyError::IngestError(format!(
+                "Telegram getUpdates failed: {}",
+                response.description.unwrap_or_default()
+            )));
+        }
+
+        Ok(response.result.unwrap_or_default())
+    }
+
+    async fn reply(&self, message: &Message, text: &str) -> ClassifyResult<()> {
+        self.client
+            .post(format!("{}/sendMessage", self.base_url))
+            .json(&SendMessage {
+                chat_id: message.chat.id,
+                text,
+                reply_to_message_id: message.message_id,
+            })
+            .send()
+            .await
+            .map_err(|e| {
+                ClassifyError::IngestError(format!(
+                    "Telegram sendMessage failed: {}",
+                    e.without_url()
+                ))
+            })?;
+
+        Ok(())
+    }
+}
+
+/// Long-poll the Telegram Bot API, classifying every message sent or forwarded
+/// to the bot and replying with the resulting tags
+pub async fn run_bot(state: Arc<AppState>, config: TelegramConfig) -> ClassifyResult<()> {
+    let bot = TelegramBot::new(&config.bot_token);
+    let mut offset = 0;
+
+    info!("Telegram bot started");
+
+    loop {
+        let updates = match bot.get_updates(offset).await {
+            Ok(updates) => updates,
+            Err(e) => {
+                warn!("{}", e);
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                continue;
+            }
+        };
+
+        for update in updates {
+            offset = offset.max(update.update_id + 1);
+
+            let Some(message) = update.message else {
+                continue;
+            };
+
+            if !is_allowed(&config, message.chat.id) {
+                warn!("Ignoring Telegram message from chat {}", message.chat.id);
+                continue;
+            }
+
+            let Some(text) = message
+                .text
+                .clone()
+                .or_else(|| message.caption.clone())
+                .map(|text| text.trim().to_string())
+                .filter(|text| !text.is_empty())
+            else {
+                continue;
+            };
+
+            let result = ingest(&state, text).await;
+            if let Ok(ingested) = &result {
+                info!(
+                    "Ingested Telegram message as content {}",
+                    ingested.content().id
+                );
+            }
+
+            if let Err(e) = bot.reply(&message, &reply_text(&result)).await {
+                warn!("{}", e);
+            }
+        }
+    }
+}
@@ -0,0 +1,41 @@
+use crate::config::TelegramConfig;
+use crate::ingest::telegram::{is_allowed, reply_text};
+use crate::ingest::Ingested;
+use crate::{ClassifyError, Content};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reply_text() {
+        let content = Content::new("Rust".to_string())
+            .with_tags(vec!["rust".to_string(), "programming".to_string()]);
+
+        assert_eq!(
+            reply_text(&Ok(Ingested::Created(content.clone()))),
+            "Tags: rust, programming"
+        );
+        assert_eq!(
+            reply_text(&Ok(Ingested::Duplicate(content))),
+            "Already stored. Tags: rust, programming"
+        );
+        assert!(
+            reply_text(&Err(ClassifyError::ClassificationError("down".to_string())))
+                .starts_with("Failed to classify")
+        );
+    }
+
+    #[test]
+    fn test_is_allowed() {
+        let mut config = TelegramConfig {
+            bot_token: "token".to_string(),
+            allowed_chats: Vec::new(),
+        };
+        assert!(is_allowed(&config, 42));
+
+        config.allowed_chats = vec![7];
+        assert!(is_allowed(&config, 7));
+        assert!(!is_allowed(&config, 42));
+    }
+}
@@ -0,0 +1,68 @@
+use serde_json::Value;
+
+/// Resolve a simple JSONPath such as `$.data.items[0].url` against a JSON value
+pub fn json_path<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    let path = path.trim();
+    let path = path.strip_prefix('$').unwrap_or(path);
+
+    let mut current = value;
+    for segment in path.split('.').filter(|segment| !segment.is_empty()) {
+        let (key, indexes) = match segment.find('[') {
+            Some(start) => (&segment[..start], &segment[start..]),
+            None => (segment, ""),
+        };
+
+        if !key.is_empty() {
+            current = current.get(key)?;
+        }
+
+        for index in indexes.split('[').filter(|index| !index.is_empty()) {
+            let index = index.strip_suffix(']')?.parse::<usize>().ok()?;
+            current = current.get(index)?;
+        }
+    }
+
+    Some(current)
+}
+
+/// Render a JSON value as plain text, leaving out nulls
+fn value_text(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => None,
+        Value::String(text) => Some(text.clone()),
+        Value::Array(items) => {
+            let parts: Vec<String> = items.iter().filter_map(value_text).collect();
+            (!parts.is_empty()).then(|| parts.join(", "))
+        }
+        other => Some(other.to_string()),
+    }
+}
+
+/// Extract the content of a webhook payload using a source mapping.
+///
+/// The mapping is either a JSONPath (`$.data.url`) or a template in which
+/// `{{path}}` placeholders are replaced by the values they point at, e.g.
+/// `{{$.title}}\n\n{{$.text}}`.
+pub fn extract_content(payload: &Value, mapping: &str) -> Option<String> {
+    let text = if mapping.contains("{{") {
+        let mut rendered = String::new();
+        let mut rest = mapping;
+
+        while let Some(start) = rest.find("{{") {
+            let end = rest[start..].find("}}")? + start;
+            rendered.push_str(&rest[..start]);
+            if let Some(value) = json_path(payload, &rest[start + 2..end]).and_then(value_text) {
+                rendered.push_str(&value);
+            }
+            rest = &rest[end + 2..];
+        }
+        rendered.push_str(rest);
+
+        rendered.replace("\\n", "\n")
+    } else {
+        json_path(payload, mapping).and_then(value_text)?
+    };
+
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
@@ -0,0 +1,40 @@
+use crate::ingest::webhook::{extract_content, json_path};
+use serde_json::json;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_path() {
+        let payload = json!({"data": {"items": [{"url": "https://example.com"}]}});
+
+        assert_eq!(
+            json_path(&payload, "$.data.items[0].url"),
+            Some(&json!("https://example.com"))
+        );
+        assert_eq!(json_path(&payload, "$.data.items[1].url"), None);
+        assert_eq!(json_path(&payload, "$.missing"), None);
+    }
+
+    #[test]
+    fn test_extract_content_with_path() {
+        let payload = json!({"EntryUrl": "https://example.com/post"});
+
+        assert_eq!(
+            extract_content(&payload, "$.EntryUrl"),
+            Some("https://example.com/post".to_string())
+        );
+        assert_eq!(extract_content(&payload, "$.Missing"), None);
+    }
+
+    #[test]
+    fn test_extract_content_with_template() {
+        let payload = json!({"title": "Ownership", "text": "Borrowing rules", "note": null});
+
+        assert_eq!(
+            extract_content(&payload, "{{$.title}}\\n\\n{{$.text}}{{$.note}}"),
+            Some("Ownership\n\nBorrowing rules".to_string())
+        );
+    }
+}
@@ -0,0 +1,148 @@
+use serde_json::json;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{error, warn};
+
+use crate::api::AppState;
+use crate::config::AlertsConfig;
+use crate::metrics::counter_total;
+use crate::ClassifyResult;
+
+/// Failure rate to watch, as the share of failed operations in a counter of all operations
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlertRule {
+    /// Shown in the notification, e.g. `Classifier`
+    pub name: &'static str,
+    pub errors_metric: &'static str,
+    pub total_metric: &'static str,
+    /// Error rate from 0 to 1 above which the alert fires
+    pub threshold: f64,
+}
+
+impl AlertRule {
+    /// Rules for the thresholds set in the configuration
+    pub fn from_config(config: &AlertsConfig) -> Vec<Self> {
+        let mut rules = Vec::new();
+        if let Some(threshold) = config.classifier_error_rate {
+            rules.push(Self {
+                name: "Classifier",
+                errors_metric: "classifier_errors_total",
+                total_metric: "classifier_requests_total",
+                threshold,
+            });
+        }
+        if let Some(threshold) = config.storage_error_rate {
+            rules.push(Self {
+                name: "Storage",
+                errors_metric: "storage_operation_errors_total",
+                total_metric: "storage_operation_seconds_count",
+                threshold,
+            });
+        }
+        rules
+    }
+}
+
+#[derive(Default)]
+struct RuleState {
+    /// Counter values with the time they were read, oldest first
+    samples: VecDeque<(Instant, f64, f64)>,
+    firing: bool,
+}
+
+/// Compares the failure rates over a sliding window with their thresholds,
+/// notifying when an alert starts firing and when it is resolved
+pub struct Alerter {
+    rules: Vec<(AlertRule, Mutex<RuleState>)>,
+    window: Duration,
+    min_operations: u64,
+}
+
+impl Alerter {
+    pub fn new(rules: Vec<AlertRule>, window: Duration, min_operations: u64) -> Self {
+        Self {
+            rules: rules
+                .into_iter()
+                .map(|rule| (rule, Mutex::new(RuleState::default())))
+                .collect(),
+            window,
+            min_operations,
+        }
+    }
+
+    /// Record the counters read at `now`, returning the notifications to send
+    pub fn evaluate(&self, rendered: &str, now: Instant) -> Vec<String> {
+        let mut notifications = Vec::new();
+
+        for (rule, state) in &self.rules {
+            let mut state = state.lock().unwrap_or_else(|e| e.into_inner());
+            let errors = counter_total(rendered, rule.errors_metric);
+            let total = counter_total(rendered, rule.total_metric);
+
+            state.samples.push_back((now, errors, total));
+            // Keep the newest sample at least a window old as the baseline
+            while state.samples.len() > 1 && now.duration_since(state.samples[1].0) >= self.window {
+                state.samples.pop_front();
+            }
+
+            let (_, base_errors, base_total) = state.samples[0];
+            let (errors, total) = (errors - base_errors, total - base_total);
+            let rate = if total > 0.0 { errors / total } else { 0.0 };
+            let minutes = self.window.as_secs().div_ceil(60);
+
+            if !state.firing && total >= self.min_operations as f64 && rate > rule.threshold {
+                state.firing = true;
+                notifications.push(format!(
+                    ":rotating_light: {} error rate is {:.0}% ({} of {} operations) over the last {} minutes, above the {:.0}% threshold",
+                    rule.name,
+                    rate * 100.0,
+                    errors,
+                    total,
+                    minutes,
+                    rule.threshold * 100.0
+                ));
+            } else if state.firing && rate <= rule.threshold {
+                state.firing = false;
+                notifications.push(format!(
+                    ":white_check_mark: {} error rate is back to {:.0}% over the last {} minutes",
+                    rule.name,
+                    rate * 100.0,
+                    minutes
+                ));
+            }
+        }
+
+        notifications
+    }
+}
+
+/// Post a notification as `{"text": ...}`, the format of Slack incoming webhooks
+async fn notify(client: &reqwest::Client, url: &str, text: &str) {
+    let result = client
+        .post(url)
+        .json(&json!({ "text": text }))
+        .send()
+        .await
+        .and_then(|response| response.error_for_status());
+    if let Err(e) = result {
+        error!("Failed to send alert notification: {}", e);
+    }
+}
+
+/// Read the metrics and notify about alerts that started firing or were resolved
+pub async fn check_alerts(
+    state: Arc<AppState>,
+    alerter: Arc<Alerter>,
+    webhook_url: &str,
+) -> ClassifyResult<()> {
+    let Some(metrics) = &state.metrics else {
+        return Ok(());
+    };
+
+    for notification in alerter.evaluate(&metrics.render(), Instant::now()) {
+        warn!("Alert: {}", notification);
+        notify(&state.http_client, webhook_url, &notification).await;
+    }
+    Ok(())
+}
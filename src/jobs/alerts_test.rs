@@ -0,0 +1,86 @@
+use crate::config::parse_rate;
+use crate::jobs::alerts::{AlertRule, Alerter};
+use crate::metrics::counter_total;
+use std::time::{Duration, Instant};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WINDOW: Duration = Duration::from_secs(300);
+
+    fn alerter() -> Alerter {
+        Alerter::new(
+            vec![AlertRule {
+                name: "Classifier",
+                errors_metric: "classifier_errors_total",
+                total_metric: "classifier_requests_total",
+                threshold: 0.2,
+            }],
+            WINDOW,
+            10,
+        )
+    }
+
+    fn rendered(errors: u64, requests: u64) -> String {
+        format!(
+            "# TYPE classifier_requests_total counter\n\
+             classifier_requests_total{{classifier=\"claude\",operation=\"classify\"}} {}\n\
+             classifier_requests_total{{classifier=\"claude\",operation=\"classify_url\"}} 0\n\
+             # TYPE classifier_errors_total counter\n\
+             classifier_errors_total{{classifier=\"claude\",operation=\"classify\"}} {}\n",
+            requests, errors
+        )
+    }
+
+    #[test]
+    fn test_counter_total_sums_labels() {
+        let rendered = "# TYPE requests counter\nrequests{a=\"1\"} 3\nrequests{a=\"2\"} 4\nrequests_other 10\n";
+        assert_eq!(counter_total(rendered, "requests"), 7.0);
+        assert_eq!(counter_total(rendered, "missing"), 0.0);
+    }
+
+    #[test]
+    fn test_alert_fires_once_and_resolves() {
+        let alerter = alerter();
+        let start = Instant::now();
+        let minute = Duration::from_secs(60);
+
+        assert!(alerter.evaluate(&rendered(0, 100), start).is_empty());
+
+        // 6 of 20 requests failed in the last minute
+        let notifications = alerter.evaluate(&rendered(6, 120), start + minute);
+        assert_eq!(notifications.len(), 1);
+        assert!(notifications[0].contains("Classifier error rate is 30% (6 of 20 operations)"));
+
+        // Still failing, but already notified
+        assert!(alerter
+            .evaluate(&rendered(12, 140), start + minute * 2)
+            .is_empty());
+
+        // The failures have left the window
+        let notifications = alerter.evaluate(&rendered(12, 400), start + minute * 8);
+        assert_eq!(notifications.len(), 1);
+        assert!(notifications[0].contains("back to 0%"));
+    }
+
+    #[test]
+    fn test_too_few_operations_never_fire() {
+        let alerter = alerter();
+        let start = Instant::now();
+
+        alerter.evaluate(&rendered(0, 0), start);
+        assert!(alerter
+            .evaluate(&rendered(5, 5), start + Duration::from_secs(60))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_parse_rate() {
+        assert_eq!(parse_rate("RATE", "0.2").unwrap(), Some(0.2));
+        assert_eq!(parse_rate("RATE", "25%").unwrap(), Some(0.25));
+        assert_eq!(parse_rate("RATE", "off").unwrap(), None);
+        assert!(parse_rate("RATE", "120%").is_err());
+        assert!(parse_rate("RATE", "high").is_err());
+    }
+}
@@ -0,0 +1,76 @@
+use reqwest::StatusCode;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::api::AppState;
+use crate::{ClassifyResult, Content, LinkStatus};
+
+/// Map the HTTP status of a HEAD probe to a link status
+pub fn link_status_for(status: StatusCode) -> LinkStatus {
+    match status {
+        StatusCode::NOT_FOUND | StatusCode::GONE => LinkStatus::Dead,
+        _ => LinkStatus::Alive,
+    }
+}
+
+/// Probe a URL with a HEAD request, returning `None` when the outcome is inconclusive
+async fn probe(client: &reqwest::Client, url: &str, timeout: Duration) -> Option<LinkStatus> {
+    match client.head(url).timeout(timeout).send().await {
+        Ok(response) => Some(link_status_for(response.status())),
+        Err(e) if e.is_timeout() => Some(LinkStatus::Dead),
+        Err(e) => {
+            warn!("Failed to probe {}: {}", url, e);
+            None
+        }
+    }
+}
+
+/// Probe every stored URL and record which ones are dead
+pub async fn check_links(state: Arc<AppState>, timeout: Duration) -> ClassifyResult<()> {
+    let contents = state.content_storage.list().await?;
+    let mut dead = 0;
+
+    for content in contents.into_iter().filter(Content::is_url) {
+        let id = content.id;
+        match check_link(&state, content, timeout).await {
+            Ok(Some(LinkStatus::Dead)) => dead += 1,
+            Ok(_) => {}
+            Err(e) => warn!("Failed to record the link status of content {}: {}", id, e),
+        }
+    }
+
+    info!("Dead-link check finished, {} dead links", dead);
+    Ok(())
+}
+
+/// Probe the URL of `content`, as it was listed, and record its link status.
+/// Probes can take until the timeout, so the content is read again before it
+/// is written: edits made meanwhile are kept and deleted content stays deleted.
+pub async fn check_link(
+    state: &AppState,
+    content: Content,
+    timeout: Duration,
+) -> ClassifyResult<Option<LinkStatus>> {
+    let Some(status) = probe(&state.http_client, &content.content, timeout).await else {
+        return Ok(None);
+    };
+
+    if content.link_status == Some(status) {
+        return Ok(Some(status));
+    }
+
+    let id = content.id.to_string();
+    let Some(mut current) = state.content_storage.get(&id).await? else {
+        info!("Content {} was deleted while its link was checked", id);
+        return Ok(Some(status));
+    };
+
+    if current.link_status != Some(status) {
+        info!("Link status of content {} is now {:?}", id, status);
+        current.link_status = Some(status);
+        state.content_storage.store(&current).await?;
+    }
+
+    Ok(Some(status))
+}
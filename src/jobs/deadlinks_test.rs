@@ -0,0 +1,79 @@
+use crate::api::AppState;
+use crate::classifier::Classifier;
+use crate::jobs::deadlinks::{check_link, link_status_for};
+use crate::storage::content::memory::MemoryContentStorage;
+use crate::storage::tag::memory::MemoryTagStorage;
+use crate::{ClassifyResult, Content, LinkStatus};
+use async_trait::async_trait;
+use axum::{http::StatusCode as AxumStatusCode, routing::head, Router};
+use reqwest::StatusCode;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpListener;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct UnusedClassifier;
+
+    #[async_trait]
+    impl Classifier for UnusedClassifier {
+        async fn classify(&self, _content: &str) -> ClassifyResult<Vec<String>> {
+            unimplemented!()
+        }
+
+        async fn classify_url(&self, _url: &str) -> ClassifyResult<Vec<String>> {
+            unimplemented!()
+        }
+    }
+
+    #[test]
+    fn test_link_status_for() {
+        assert_eq!(link_status_for(StatusCode::NOT_FOUND), LinkStatus::Dead);
+        assert_eq!(link_status_for(StatusCode::GONE), LinkStatus::Dead);
+        assert_eq!(link_status_for(StatusCode::OK), LinkStatus::Alive);
+        assert_eq!(
+            link_status_for(StatusCode::METHOD_NOT_ALLOWED),
+            LinkStatus::Alive
+        );
+    }
+
+    #[tokio::test]
+    async fn test_edits_and_deletes_during_check_are_kept() -> ClassifyResult<()> {
+        let app = Router::new().route("/gone", head(|| async { AxumStatusCode::NOT_FOUND }));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let state = AppState::new(
+            Arc::new(UnusedClassifier),
+            Arc::new(MemoryContentStorage::new()),
+            Arc::new(MemoryTagStorage::new()),
+        );
+        let timeout = Duration::from_secs(5);
+
+        let content = Content::new(format!("http://{}/gone", address));
+        state.content_storage.store(&content).await?;
+
+        // Edited after the check listed the content
+        let mut edited = content.clone().with_tags(vec!["mine".to_string()]);
+        edited.pinned = true;
+        state.content_storage.store(&edited).await?;
+
+        let status = check_link(&state, content.clone(), timeout).await?;
+        assert_eq!(status, Some(LinkStatus::Dead));
+
+        let id = content.id.to_string();
+        let stored = state.content_storage.get(&id).await?.unwrap();
+        assert_eq!(stored.link_status, Some(LinkStatus::Dead));
+        assert_eq!(stored.tags, vec!["mine".to_string()]);
+        assert!(stored.pinned);
+
+        // Deleted after the check listed the content
+        state.content_storage.delete(&id).await?;
+        check_link(&state, content, timeout).await?;
+        assert!(state.content_storage.get(&id).await?.is_none());
+        Ok(())
+    }
+}
@@ -0,0 +1,124 @@
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::info;
+
+use crate::api::AppState;
+use crate::config::{DigestConfig, DigestPeriod};
+use crate::{ClassifyError, ClassifyResult, Content, Digest, DigestItem, DigestTag};
+
+/// Characters of text content shown as its title
+const TITLE_LENGTH: usize = 80;
+
+/// Items listed per tag in the text of a posted digest, the JSON has them all
+const TEXT_ITEMS_PER_TAG: usize = 5;
+
+/// Title of content in a digest: the URL, or the start of the first line of text
+pub fn title(content: &Content) -> String {
+    if content.is_url() {
+        return content.content.clone();
+    }
+
+    let line = content.content.trim().lines().next().unwrap_or_default();
+    match line.char_indices().nth(TITLE_LENGTH) {
+        Some((end, _)) => format!("{}...", &line[..end]),
+        None => line.to_string(),
+    }
+}
+
+/// Group the content classified in the period up to `until` by tag
+pub fn compile(contents: Vec<Content>, period: DigestPeriod, until: DateTime<Utc>) -> Digest {
+    let since = until - chrono::Duration::from_std(period.duration()).unwrap_or_default();
+
+    let mut recent: Vec<Content> = contents
+        .into_iter()
+        .filter(|content| content.created_at >= since && content.created_at < until)
+        .collect();
+    recent.sort_by_key(|content| std::cmp::Reverse(content.created_at));
+
+    let mut by_tag: HashMap<String, Vec<DigestItem>> = HashMap::new();
+    for content in &recent {
+        for tag in &content.tags {
+            by_tag.entry(tag.clone()).or_default().push(DigestItem {
+                id: content.id,
+                title: title(content),
+                created_at: content.created_at,
+            });
+        }
+    }
+
+    let mut tags: Vec<DigestTag> = by_tag
+        .into_iter()
+        .map(|(tag, items)| DigestTag { tag, items })
+        .collect();
+    tags.sort_by(|a, b| b.items.len().cmp(&a.items.len()).then(a.tag.cmp(&b.tag)));
+
+    Digest {
+        period,
+        since,
+        until,
+        count: recent.len(),
+        tags,
+    }
+}
+
+/// The digest as a chat message
+pub fn render_text(digest: &Digest) -> String {
+    let period = match digest.period {
+        DigestPeriod::Day => "day",
+        DigestPeriod::Week => "week",
+    };
+    let mut text = format!(
+        "Digest of the {} up to {}: {} new item{}",
+        period,
+        digest.until.format("%Y-%m-%d"),
+        digest.count,
+        if digest.count == 1 { "" } else { "s" }
+    );
+
+    for tag in &digest.tags {
+        text.push_str(&format!("\n\n*{}* ({})", tag.tag, tag.items.len()));
+        for item in tag.items.iter().take(TEXT_ITEMS_PER_TAG) {
+            text.push_str(&format!("\n• {}", item.title));
+        }
+        if tag.items.len() > TEXT_ITEMS_PER_TAG {
+            text.push_str(&format!(
+                "\n• and {} more",
+                tag.items.len() - TEXT_ITEMS_PER_TAG
+            ));
+        }
+    }
+
+    text
+}
+
+/// Post the digest of the last period to the webhook, skipping periods
+/// without new content
+pub async fn send_digest(state: Arc<AppState>, config: &DigestConfig) -> ClassifyResult<()> {
+    let digest = compile(
+        state.content_storage.list().await?,
+        config.period,
+        Utc::now(),
+    );
+    if digest.count == 0 {
+        info!("No new content, skipping the digest");
+        return Ok(());
+    }
+
+    state
+        .http_client
+        .post(&config.webhook_url)
+        .json(&json!({ "text": render_text(&digest), "digest": digest }))
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| ClassifyError::HttpError(format!("Failed to send digest: {}", e)))?;
+
+    info!(
+        "Sent digest of {} items in {} tags",
+        digest.count,
+        digest.tags.len()
+    );
+    Ok(())
+}
@@ -0,0 +1,77 @@
+use crate::config::DigestPeriod;
+use crate::jobs::digest::{compile, render_text, title};
+use crate::Content;
+use chrono::{Duration, Utc};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn content(text: &str, tags: &[&str], days_ago: i64) -> Content {
+        let mut content =
+            Content::new(text.to_string()).with_tags(tags.iter().map(|t| t.to_string()).collect());
+        content.created_at = Utc::now() - Duration::days(days_ago) - Duration::minutes(1);
+        content
+    }
+
+    #[test]
+    fn test_compile_groups_recent_content_by_tag() {
+        let contents = vec![
+            content("Async Rust in depth", &["rust", "async"], 1),
+            content("https://example.com/borrowing", &["rust"], 2),
+            content("Sourdough starter", &["baking"], 3),
+            content("Old news", &["rust"], 8),
+        ];
+
+        let digest = compile(contents, DigestPeriod::Week, Utc::now());
+
+        assert_eq!(digest.count, 3);
+        let tags: Vec<(&str, usize)> = digest
+            .tags
+            .iter()
+            .map(|tag| (tag.tag.as_str(), tag.items.len()))
+            .collect();
+        assert_eq!(tags, vec![("rust", 2), ("async", 1), ("baking", 1)]);
+        // Newest first
+        assert_eq!(digest.tags[0].items[0].title, "Async Rust in depth");
+        assert_eq!(
+            digest.tags[0].items[1].title,
+            "https://example.com/borrowing"
+        );
+
+        let day = compile(
+            vec![
+                content("Today", &["rust"], 0),
+                content("Yesterday", &["rust"], 1),
+            ],
+            DigestPeriod::Day,
+            Utc::now(),
+        );
+        assert_eq!(day.count, 1);
+    }
+
+    #[test]
+    fn test_title_shortens_text() {
+        let long = format!("{}\nSecond line", "é".repeat(100));
+        assert_eq!(title(&Content::new(long)), format!("{}...", "é".repeat(80)));
+        assert_eq!(
+            title(&Content::new("  First line\nSecond line".to_string())),
+            "First line"
+        );
+    }
+
+    #[test]
+    fn test_render_text_lists_items_per_tag() {
+        let contents = (0..7)
+            .map(|i| content(&format!("Note {}", i), &["notes"], 0))
+            .collect();
+
+        let text = render_text(&compile(contents, DigestPeriod::Day, Utc::now()));
+
+        assert!(text.starts_with("Digest of the day up to "));
+        assert!(text.contains("7 new items"));
+        assert!(text.contains("*notes* (7)"));
+        assert_eq!(text.matches("\n• Note").count(), 5);
+        assert!(text.ends_with("• and 2 more"));
+    }
+}
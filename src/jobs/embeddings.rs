@@ -0,0 +1,43 @@
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use crate::api::AppState;
+use crate::extract::fetch_and_extract;
+use crate::{ClassifyResult, Content};
+
+/// Text to embed for stored content: the page of a URL, the text itself
+/// otherwise. A page that can't be fetched is embedded by its URL and tags.
+async fn source_text(state: &AppState, content: Content) -> String {
+    if !content.is_url() {
+        return content.content;
+    }
+
+    match fetch_and_extract(&state.http_client, &content.content).await {
+        Ok(document) => document.text,
+        Err(e) => {
+            warn!("Embedding {} without its page: {}", content.content, e);
+            content.content
+        }
+    }
+}
+
+/// Embed stored content that has no embedding yet, like content stored before
+/// semantic search was turned on or while the embedding provider was down
+pub async fn backfill_embeddings(state: Arc<AppState>) -> ClassifyResult<()> {
+    let Some(index) = &state.semantic_index else {
+        return Ok(());
+    };
+
+    let contents = state.content_storage.list().await?;
+    let (embedded, dropped) = index
+        .backfill(&contents, |content| source_text(&state, content))
+        .await?;
+
+    if embedded > 0 || dropped > 0 {
+        info!(
+            "Embedded {} items, dropped {} embeddings of deleted content",
+            embedded, dropped
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,82 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+use crate::api::health::check_components;
+use crate::api::AppState;
+use crate::config::HeartbeatConfig;
+use crate::ClassifyResult;
+
+/// Longest a ping may take, the monitor counts a missed ping anyway
+const PING_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Pings an uptime monitor, such as healthchecks.io, which alerts when the
+/// pings stop coming
+pub struct Heartbeat {
+    client: reqwest::Client,
+    url: String,
+    interval: Duration,
+    last_ping: Mutex<Option<Instant>>,
+}
+
+impl Heartbeat {
+    pub fn new(config: &HeartbeatConfig) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url: config.url.clone(),
+            interval: config.interval,
+            last_ping: Mutex::new(None),
+        }
+    }
+
+    /// Reserve a ping unless one was sent within the interval
+    pub fn claim(&self) -> bool {
+        let mut last_ping = self.last_ping.lock().unwrap_or_else(|e| e.into_inner());
+        let due = last_ping.is_none_or(|last| last.elapsed() >= self.interval);
+        if due {
+            *last_ping = Some(Instant::now());
+        }
+        due
+    }
+
+    pub async fn ping(&self) {
+        *self.last_ping.lock().unwrap_or_else(|e| e.into_inner()) = Some(Instant::now());
+
+        match self
+            .client
+            .get(&self.url)
+            .timeout(PING_TIMEOUT)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+        {
+            Ok(_) => debug!("Heartbeat sent"),
+            Err(e) => warn!("Failed to send heartbeat: {}", e),
+        }
+    }
+
+    /// Ping after a successful classification, at most once per interval, so
+    /// a busy server doesn't flood the monitor
+    pub fn classified(self: &Arc<Self>) {
+        if self.claim() {
+            let heartbeat = self.clone();
+            tokio::spawn(async move { heartbeat.ping().await });
+        }
+    }
+}
+
+/// Scheduled ping, sent only while content and tag storage answer. A worker
+/// stuck on a storage connection holds up the check, so the pings stop.
+pub async fn beat(state: Arc<AppState>) -> ClassifyResult<()> {
+    let Some(heartbeat) = &state.heartbeat else {
+        return Ok(());
+    };
+
+    let health = check_components(&state, false).await;
+    if health.healthy {
+        heartbeat.ping().await;
+    } else {
+        warn!("Storage is unhealthy, skipping heartbeat");
+    }
+    Ok(())
+}
@@ -0,0 +1,37 @@
+use crate::config::HeartbeatConfig;
+use crate::jobs::heartbeat::Heartbeat;
+use std::time::Duration;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn heartbeat(interval: Duration) -> Heartbeat {
+        Heartbeat::new(&HeartbeatConfig {
+            url: "http://127.0.0.1:9/ping".to_string(),
+            interval,
+        })
+    }
+
+    #[test]
+    fn test_classification_pings_once_per_interval() {
+        let heartbeat = heartbeat(Duration::from_secs(60));
+
+        assert!(heartbeat.claim());
+        assert!(!heartbeat.claim());
+        assert!(!heartbeat.claim());
+    }
+
+    #[tokio::test]
+    async fn test_scheduled_ping_delays_next_classification_ping() {
+        let heartbeat = heartbeat(Duration::from_secs(60));
+
+        // Nothing listens on the discard port, the failure is only logged
+        heartbeat.ping().await;
+        assert!(!heartbeat.claim());
+
+        let heartbeat = self::heartbeat(Duration::ZERO);
+        heartbeat.ping().await;
+        assert!(heartbeat.claim());
+    }
+}
@@ -0,0 +1,168 @@
+pub mod alerts;
+pub mod deadlinks;
+pub mod digest;
+pub mod embeddings;
+pub mod heartbeat;
+pub mod refetch;
+pub mod retention;
+
+#[cfg(test)]
+mod alerts_test;
+#[cfg(test)]
+mod deadlinks_test;
+#[cfg(test)]
+mod digest_test;
+#[cfg(test)]
+mod heartbeat_test;
+#[cfg(test)]
+mod refetch_test;
+#[cfg(test)]
+mod retention_test;
+
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::{interval_at, Instant};
+use tracing::{error, info};
+
+use crate::api::AppState;
+use crate::config::JobsConfig;
+use crate::shutdown;
+use crate::ClassifyResult;
+
+/// Spawn a task that runs `job` every `period`, starting one period from now
+pub fn spawn_periodic<F, Fut>(name: &'static str, period: Duration, job: F) -> JoinHandle<()>
+where
+    F: Fn() -> Fut + Send + 'static,
+    Fut: Future<Output = ClassifyResult<()>> + Send,
+{
+    info!("Scheduling background job '{}' every {:?}", name, period);
+
+    tokio::spawn(async move {
+        let mut ticker = interval_at(Instant::now() + period, period);
+        loop {
+            // A run that has started is finished before the job stops
+            tokio::select! {
+                _ = ticker.tick() => {}
+                _ = shutdown::requested() => {
+                    info!("Stopping background job '{}'", name);
+                    return;
+                }
+            }
+            info!("Running background job '{}'", name);
+            if let Err(e) = job().await {
+                error!("Background job '{}' failed: {}", name, e);
+            }
+        }
+    })
+}
+
+/// Jobs write to storage, so their runs are skipped in maintenance mode
+fn paused(state: &AppState, name: &str) -> bool {
+    let paused = state.maintenance.is_enabled();
+    if paused {
+        info!("Skipping background job '{}' in maintenance mode", name);
+    }
+    paused
+}
+
+/// Start all background jobs enabled in the configuration
+pub fn spawn_background_jobs(state: Arc<AppState>, config: &JobsConfig) -> Vec<JoinHandle<()>> {
+    let mut handles = Vec::new();
+
+    if let Some(period) = config.refetch_interval {
+        let mode = config.refetch_mode;
+        let state = state.clone();
+        handles.push(spawn_periodic("refetch", period, move || {
+            let state = state.clone();
+            async move {
+                if paused(&state, "refetch") {
+                    return Ok(());
+                }
+                refetch::refetch_urls(state, mode).await
+            }
+        }));
+    }
+
+    if let Some(period) = config.deadlink_interval {
+        let timeout = config.deadlink_timeout;
+        let state = state.clone();
+        handles.push(spawn_periodic("deadlinks", period, move || {
+            let state = state.clone();
+            async move {
+                if paused(&state, "deadlinks") {
+                    return Ok(());
+                }
+                deadlinks::check_links(state, timeout).await
+            }
+        }));
+    }
+
+    if let Some(period) = config.retention_interval {
+        let policy = Arc::new(config.retention.clone());
+        let state = state.clone();
+        handles.push(spawn_periodic("retention", period, move || {
+            let policy = policy.clone();
+            let state = state.clone();
+            async move {
+                if paused(&state, "retention") {
+                    return Ok(());
+                }
+                retention::sweep_expired(state, &policy).await
+            }
+        }));
+    }
+
+    if let Some(alerts) = &config.alerts {
+        let alerter = Arc::new(alerts::Alerter::new(
+            alerts::AlertRule::from_config(alerts),
+            alerts.window,
+            alerts.min_operations,
+        ));
+        let webhook_url = Arc::new(alerts.webhook_url.clone());
+        let state = state.clone();
+        handles.push(spawn_periodic("alerts", alerts.check_interval, move || {
+            let (state, alerter, webhook_url) =
+                (state.clone(), alerter.clone(), webhook_url.clone());
+            async move { alerts::check_alerts(state, alerter, &webhook_url).await }
+        }));
+    }
+
+    // Only reads content, so it isn't paused in maintenance mode
+    if let Some(digest) = &config.digest {
+        let digest = Arc::new(digest.clone());
+        let state = state.clone();
+        handles.push(spawn_periodic(
+            "digest",
+            digest.period.duration(),
+            move || {
+                let (state, digest) = (state.clone(), digest.clone());
+                async move { digest::send_digest(state, &digest).await }
+            },
+        ));
+    }
+
+    if let Some(period) = config.embedding_backfill_interval {
+        let state = state.clone();
+        handles.push(spawn_periodic("embeddings", period, move || {
+            let state = state.clone();
+            async move {
+                if paused(&state, "embeddings") {
+                    return Ok(());
+                }
+                embeddings::backfill_embeddings(state).await
+            }
+        }));
+    }
+
+    // Not paused in maintenance mode, the server is still up
+    if let Some(heartbeat) = &config.heartbeat {
+        let state = state.clone();
+        handles.push(spawn_periodic("heartbeat", heartbeat.interval, move || {
+            heartbeat::beat(state.clone())
+        }));
+    }
+
+    handles
+}
@@ -0,0 +1,114 @@
+use chrono::Utc;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use crate::api::AppState;
+use crate::config::RefetchMode;
+use crate::extract::page_metadata;
+use crate::web::fetch_page;
+use crate::{ClassifyResult, Content};
+
+/// Outcome of comparing a freshly fetched page with the stored hash
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PageChange {
+    /// No hash was stored yet, the fetched page becomes the baseline
+    Baseline,
+    Unchanged,
+    Changed,
+}
+
+/// Compare the stored page hash with the hash of the freshly fetched page
+pub fn detect_change(stored_hash: Option<&str>, fetched_hash: &str) -> PageChange {
+    match stored_hash {
+        None => PageChange::Baseline,
+        Some(hash) if hash == fetched_hash => PageChange::Unchanged,
+        Some(_) => PageChange::Changed,
+    }
+}
+
+/// Re-fetch every stored URL and record or re-classify pages that changed
+pub async fn refetch_urls(state: Arc<AppState>, mode: RefetchMode) -> ClassifyResult<()> {
+    let contents = state.content_storage.list().await?;
+    let mut changed = 0;
+
+    for content in contents.into_iter().filter(Content::is_url) {
+        match refetch_content(&state, content, mode).await {
+            Ok(PageChange::Changed) => changed += 1,
+            Ok(_) => {}
+            Err(e) => warn!("Failed to re-fetch content: {}", e),
+        }
+    }
+
+    info!("Re-fetch finished, {} pages changed", changed);
+    Ok(())
+}
+
+/// Re-fetch the page of `content`, as it was listed, and record the outcome.
+/// Fetching and classifying take a while, so the content is read again before
+/// it is written: edits made meanwhile, like tags, pins or metadata, are kept
+/// and only the fields of the re-fetch are updated.
+pub async fn refetch_content(
+    state: &AppState,
+    content: Content,
+    mode: RefetchMode,
+) -> ClassifyResult<PageChange> {
+    let page = fetch_page(&state.http_client, &content.content).await?;
+    let page_hash = Content::generate_hash(&page);
+    // Compared like with like, the hash settings may have changed since the last fetch
+    let fetched_hash = match content.page_hash.as_deref() {
+        Some(stored_hash) => Content::generate_hash_like(&page, stored_hash),
+        None => page_hash.clone(),
+    };
+    let change = detect_change(content.page_hash.as_deref(), &fetched_hash);
+    let now = Utc::now();
+    let preview = page_metadata(&page, &content.content);
+
+    let tags = if change == PageChange::Changed && mode == RefetchMode::Retag {
+        Some(
+            state
+                .ingest
+                .tags
+                .apply(&state.classifier.classify(&page).await?),
+        )
+    } else {
+        None
+    };
+
+    let id = content.id.to_string();
+    let Some(current) = state.content_storage.get(&id).await? else {
+        info!("Content {} was deleted while its page was re-fetched", id);
+        return Ok(change);
+    };
+
+    // Also fills in the preview of content stored before previews were kept
+    let mut current = current.with_page_metadata(preview);
+    current.last_checked_at = Some(now);
+    current.page_hash = Some(page_hash);
+
+    if change == PageChange::Changed {
+        info!("Page for content {} changed", id);
+        current.last_changed_at = Some(now);
+    }
+
+    if let Some(tags) = tags {
+        if current.updated_at != content.updated_at {
+            info!(
+                "Content {} was edited while its page was re-fetched, keeping its tags",
+                id
+            );
+        } else {
+            // Tags of a tenant's content are indexed within its namespace
+            let tag_storage = state
+                .for_namespace(current.namespace.as_deref())
+                .tag_storage;
+            tag_storage.remove_tags(&id, &current.tags).await?;
+            tag_storage.add_tags(&id, &tags).await?;
+
+            current = current.with_tags(tags);
+        }
+    }
+
+    state.content_storage.store(&current).await?;
+
+    Ok(change)
+}
@@ -0,0 +1,87 @@
+use crate::api::AppState;
+use crate::classifier::Classifier;
+use crate::config::RefetchMode;
+use crate::jobs::refetch::{detect_change, refetch_content, PageChange};
+use crate::storage::content::memory::MemoryContentStorage;
+use crate::storage::tag::memory::MemoryTagStorage;
+use crate::{ClassifyResult, Content};
+use async_trait::async_trait;
+use axum::{routing::get, Router};
+use std::sync::Arc;
+use tokio::net::TcpListener;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct RustClassifier;
+
+    #[async_trait]
+    impl Classifier for RustClassifier {
+        async fn classify(&self, _content: &str) -> ClassifyResult<Vec<String>> {
+            Ok(vec!["rust".to_string()])
+        }
+
+        async fn classify_url(&self, _url: &str) -> ClassifyResult<Vec<String>> {
+            unimplemented!()
+        }
+    }
+
+    #[test]
+    fn test_detect_change() {
+        assert_eq!(detect_change(None, "abc"), PageChange::Baseline);
+        assert_eq!(detect_change(Some("abc"), "abc"), PageChange::Unchanged);
+        assert_eq!(detect_change(Some("abc"), "def"), PageChange::Changed);
+    }
+
+    #[tokio::test]
+    async fn test_edits_made_during_refetch_are_kept() -> ClassifyResult<()> {
+        let app = Router::new().route("/", get(|| async { "<title>Async Rust</title>" }));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let state = AppState::new(
+            Arc::new(RustClassifier),
+            Arc::new(MemoryContentStorage::new()),
+            Arc::new(MemoryTagStorage::new()),
+        );
+
+        let mut content = Content::new(format!("http://{}/", address));
+        content.page_hash = Some("old".to_string());
+        state.content_storage.store(&content).await?;
+
+        // Edited after the re-fetch listed the content
+        let mut edited = content.clone().with_tags(vec!["mine".to_string()]);
+        edited.updated_at = content.updated_at + chrono::Duration::minutes(1);
+        edited.pinned = true;
+        state.content_storage.store(&edited).await?;
+
+        let change = refetch_content(&state, content.clone(), RefetchMode::Retag).await?;
+        assert_eq!(change, PageChange::Changed);
+
+        let stored = state
+            .content_storage
+            .get(&content.id.to_string())
+            .await?
+            .unwrap();
+        assert_eq!(stored.tags, vec!["mine".to_string()]);
+        assert!(stored.pinned);
+        assert_ne!(stored.page_hash.as_deref(), Some("old"));
+        assert!(stored.last_changed_at.is_some());
+        assert_eq!(stored.title.as_deref(), Some("Async Rust"));
+
+        // Without edits the changed page is re-classified
+        let mut unedited = stored.clone();
+        unedited.page_hash = Some("old".to_string());
+        state.content_storage.store(&unedited).await?;
+        refetch_content(&state, unedited, RefetchMode::Retag).await?;
+        let stored = state
+            .content_storage
+            .get(&content.id.to_string())
+            .await?
+            .unwrap();
+        assert_eq!(stored.tags, vec!["rust".to_string()]);
+        Ok(())
+    }
+}
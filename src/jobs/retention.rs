@@ -0,0 +1,52 @@
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use crate::api::AppState;
+use crate::config::RetentionPolicy;
+use crate::{ClassifyResult, Content};
+
+/// Whether content has outlived the retention policy at `now`
+pub fn is_expired(policy: &RetentionPolicy, content: &Content, now: DateTime<Utc>) -> bool {
+    let Some(max_age) = policy.max_age_for(&content.tags) else {
+        return false;
+    };
+
+    match chrono::Duration::from_std(max_age) {
+        Ok(max_age) => content.created_at + max_age <= now,
+        Err(_) => false,
+    }
+}
+
+/// Delete content that has outlived the retention policy, together with its tags
+pub async fn sweep_expired(state: Arc<AppState>, policy: &RetentionPolicy) -> ClassifyResult<()> {
+    let now = Utc::now();
+    let contents = state.content_storage.list().await?;
+    let mut deleted = 0;
+
+    for content in contents
+        .iter()
+        .filter(|content| is_expired(policy, content, now))
+    {
+        let id = content.id.to_string();
+        let tags = state.tag_storage.get_tags(&id).await?;
+
+        match state
+            .storage_transaction()
+            .delete_with_tags(&id, &tags)
+            .await
+        {
+            Ok(_) => {
+                if let Some(index) = &state.semantic_index {
+                    index.remove(&id).await;
+                }
+                info!("Deleted expired content {}", id);
+                deleted += 1;
+            }
+            Err(e) => warn!("Failed to delete expired content {}: {}", id, e),
+        }
+    }
+
+    info!("Retention sweep finished, {} items deleted", deleted);
+    Ok(())
+}
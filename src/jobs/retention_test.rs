@@ -0,0 +1,153 @@
+use crate::classifier::Classifier;
+use crate::config::{parse_tag_days, RetentionPolicy};
+use crate::embedding::index::SemanticIndex;
+use crate::embedding::Embedder;
+use crate::jobs::retention::{is_expired, sweep_expired};
+use crate::storage::vector::memory::MemoryVectorStorage;
+use crate::storage::VectorStorage;
+use crate::{ClassifyResult, Content};
+use chrono::Utc;
+use mockall::mock;
+use std::collections::HashMap;
+use std::time::Duration;
+
+mock! {
+    pub ClassifierMock {}
+
+    #[async_trait::async_trait]
+    impl Classifier for ClassifierMock {
+        async fn classify(&self, content: &str) -> ClassifyResult<Vec<String>>;
+        async fn classify_url(&self, url: &str) -> ClassifyResult<Vec<String>>;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::AppState;
+    use crate::storage::content::memory::MemoryContentStorage;
+    use crate::storage::tag::memory::MemoryTagStorage;
+    use std::sync::Arc;
+
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    /// Embeds every text the same, only whether a vector is kept matters
+    struct ConstantEmbedder;
+
+    #[async_trait::async_trait]
+    impl Embedder for ConstantEmbedder {
+        async fn embed(&self, _text: &str) -> ClassifyResult<Vec<f32>> {
+            Ok(vec![1.0])
+        }
+    }
+
+    fn aged(text: &str, days: i64, tags: &[&str]) -> Content {
+        let mut content =
+            Content::new(text.to_string()).with_tags(tags.iter().map(|t| t.to_string()).collect());
+        content.created_at = Utc::now() - chrono::Duration::days(days);
+        content
+    }
+
+    fn policy() -> RetentionPolicy {
+        RetentionPolicy {
+            max_age: Some(DAY * 365),
+            tag_max_age: HashMap::from([
+                ("news".to_string(), DAY * 90),
+                ("keep".to_string(), DAY * 3650),
+            ]),
+        }
+    }
+
+    #[test]
+    fn test_tag_rules_override_default() {
+        let now = Utc::now();
+
+        assert!(is_expired(
+            &policy(),
+            &aged("old news", 100, &["news"]),
+            now
+        ));
+        assert!(!is_expired(
+            &policy(),
+            &aged("recent news", 10, &["news"]),
+            now
+        ));
+        assert!(!is_expired(&policy(), &aged("note", 100, &["rust"]), now));
+        assert!(is_expired(
+            &policy(),
+            &aged("old note", 400, &["rust"]),
+            now
+        ));
+        // The longest matching rule wins
+        assert!(!is_expired(
+            &policy(),
+            &aged("kept news", 400, &["news", "keep"]),
+            now
+        ));
+    }
+
+    #[test]
+    fn test_without_policy_nothing_expires() {
+        let policy = RetentionPolicy::default();
+
+        assert!(!policy.is_enabled());
+        assert!(!is_expired(
+            &policy,
+            &aged("ancient", 10_000, &["news"]),
+            Utc::now()
+        ));
+    }
+
+    #[test]
+    fn test_parse_tag_days() {
+        let rules = parse_tag_days("news=90, scratch = 7,").unwrap();
+
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules["news"], DAY * 90);
+        assert_eq!(rules["scratch"], DAY * 7);
+        assert!(parse_tag_days("news").is_err());
+        assert!(parse_tag_days("news=soon").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_sweep_deletes_expired_content_tags_and_embeddings() {
+        let vectors = Arc::new(MemoryVectorStorage::new());
+        let state = Arc::new(
+            AppState::new(
+                Arc::new(MockClassifierMock::new()),
+                Arc::new(MemoryContentStorage::new()),
+                Arc::new(MemoryTagStorage::new()),
+            )
+            .with_semantic_index(Some(SemanticIndex::new(
+                Arc::new(ConstantEmbedder),
+                vectors.clone(),
+            ))),
+        );
+
+        let expired = aged("old news", 100, &["news"]);
+        let current = aged("recent news", 10, &["news"]);
+        for content in [&expired, &current] {
+            state
+                .storage_transaction()
+                .store_with_tags(content, &content.tags)
+                .await
+                .unwrap();
+            let index = state.semantic_index.as_ref().unwrap();
+            index.index(content, &content.content).await.unwrap();
+        }
+
+        sweep_expired(state.clone(), &policy()).await.unwrap();
+
+        let remaining = state.content_storage.list().await.unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].id, current.id);
+        assert_eq!(
+            state.tag_storage.find_by_tag("news").await.unwrap(),
+            vec![current.id.to_string()]
+        );
+        assert_eq!(
+            vectors.ids().await.unwrap(),
+            [current.id.to_string()].into()
+        );
+    }
+}
@@ -1,18 +1,65 @@
 #[cfg(test)]
 extern crate mockall;
 
+pub mod activity;
+#[cfg(test)]
+mod activity_test;
 pub mod api;
+pub mod ask;
+#[cfg(test)]
+mod ask_test;
 pub mod classifier;
+pub mod cli;
+#[cfg(test)]
+mod cli_test;
 pub mod config;
+pub mod doctor;
+#[cfg(test)]
+mod doctor_test;
+pub mod embedding;
+pub mod extract;
+pub mod ingest;
+pub mod jobs;
+pub mod logging;
+#[cfg(test)]
+mod logging_test;
+pub mod metrics;
+pub mod queue;
+pub mod registry;
+#[cfg(test)]
+mod registry_test;
+pub mod seed;
+#[cfg(test)]
+mod seed_test;
+pub mod shutdown;
 pub mod storage;
+pub mod tags;
+#[cfg(test)]
+mod tags_test;
+pub mod trends;
+#[cfg(test)]
+mod trends_test;
+pub mod usage;
+#[cfg(test)]
+mod usage_test;
+pub mod web;
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use sha2::{Digest, Sha256};
+use sha2::{Digest as _, Sha256};
+use std::borrow::Cow;
+use std::collections::HashMap;
 use std::fmt;
+use std::sync::OnceLock;
 use thiserror::Error;
+
 use uuid::Uuid;
 
+use crate::config::{DigestPeriod, HashAlgorithm, HashNormalization};
+
+static HASH_ALGORITHM: OnceLock<HashAlgorithm> = OnceLock::new();
+static HASH_NORMALIZATION: OnceLock<HashNormalization> = OnceLock::new();
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Content {
     pub id: Uuid,
@@ -21,8 +68,59 @@ pub struct Content {
     pub tags: Vec<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Hash of the last fetched page body, for URL content
+    #[serde(default)]
+    pub page_hash: Option<String>,
+    /// When the URL was last re-fetched
+    #[serde(default)]
+    pub last_checked_at: Option<DateTime<Utc>>,
+    /// When a re-fetch last detected a change in the page
+    #[serde(default)]
+    pub last_changed_at: Option<DateTime<Utc>>,
+    /// Result of the last dead-link check, for URL content
+    #[serde(default)]
+    pub link_status: Option<LinkStatus>,
+    /// MIME type of the content, or of the fetched document for URL content
+    #[serde(default)]
+    pub content_type: Option<String>,
+    /// Free-form key/value pairs set by integrators, e.g. source IDs or authors
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+    /// Tenant the content belongs to, unset for content stored with the main API key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    /// Who besides its own tenant may see the content
+    #[serde(default)]
+    pub visibility: Visibility,
+    /// Caller that marked the content private, the only one besides the main API key that sees it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    /// Other tenant namespaces the content is shared with
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub shared_with: Vec<String>,
+    /// Marked as important, listed before other content
+    #[serde(default)]
+    pub pinned: bool,
+    /// Short ID that can be used in place of the UUID in `/content/:id` routes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slug: Option<String>,
+    /// Title of the page, for previews of URL content
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Description of the page, for previews of URL content
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Preview image of the page (`og:image`), for URL content
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image_url: Option<String>,
+    /// Name of the site the page is part of (`og:site_name`), for URL content
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub site_name: Option<String>,
 }
 
+/// Length of the slugs of new content
+pub const SLUG_LENGTH: usize = 8;
+
 impl Content {
     pub fn new(content: String) -> Self {
         let now = Utc::now();
@@ -35,26 +133,239 @@ impl Content {
             tags: Vec::new(),
             created_at: now,
             updated_at: now,
+            page_hash: None,
+            last_checked_at: None,
+            last_changed_at: None,
+            link_status: None,
+            content_type: None,
+            metadata: HashMap::new(),
+            namespace: None,
+            visibility: Visibility::default(),
+            owner: None,
+            shared_with: Vec::new(),
+            pinned: false,
+            slug: Some(Self::generate_slug()),
+            title: None,
+            description: None,
+            image_url: None,
+            site_name: None,
         }
     }
 
+    /// A random 8 character slug. Its alphabet leaves out `i`, `l`, `o` and
+    /// `u` so slugs are easy to read out and type.
+    pub fn generate_slug() -> String {
+        const ALPHABET: &[u8] = b"0123456789abcdefghjkmnpqrstvwxyz";
+        // The last 48 bits of a v4 UUID are all random
+        let random = Uuid::new_v4().as_u128();
+        (0..SLUG_LENGTH)
+            .map(|i| ALPHABET[(random >> (5 * i)) as usize & 31] as char)
+            .collect()
+    }
+
     pub fn with_tags(mut self, tags: Vec<String>) -> Self {
         self.tags = tags;
         self.updated_at = Utc::now();
         self
     }
 
+    pub fn with_metadata(mut self, metadata: HashMap<String, String>) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    /// Keep the preview metadata of the fetched page
+    pub fn with_page_metadata(mut self, page: extract::PageMetadata) -> Self {
+        self.title = page.title;
+        self.description = page.description;
+        self.image_url = page.image_url;
+        self.site_name = page.site_name;
+        self
+    }
+
+    /// Move content into a namespace. The namespace becomes part of the content
+    /// hash, so duplicates are only detected within the same namespace.
+    pub fn with_namespace(mut self, namespace: Option<String>) -> Self {
+        if let Some(namespace) = &namespace {
+            self.content_hash = self
+                .content_hash
+                .map(|hash| Self::namespaced_hash(namespace, &hash));
+        }
+        self.namespace = namespace;
+        self
+    }
+
+    /// Content hash of content in a namespace
+    pub fn namespaced_hash(namespace: &str, hash: &str) -> String {
+        format!("{}:{}", namespace, hash)
+    }
+
+    /// Check if every key/value pair of the filter is in the metadata
+    pub fn matches_metadata(&self, filter: &HashMap<String, String>) -> bool {
+        filter
+            .iter()
+            .all(|(key, value)| self.metadata.get(key) == Some(value))
+    }
+
     /// Check if content is a URL
     pub fn is_url(&self) -> bool {
-        self.content.starts_with("http://") || self.content.starts_with("https://")
+        Self::looks_like_url(&self.content)
+    }
+
+    /// Check if a piece of text should be treated as a URL
+    pub fn looks_like_url(text: &str) -> bool {
+        text.starts_with("http://") || text.starts_with("https://")
+    }
+
+    /// Set the hash function used by [`Content::generate_hash`], once at startup.
+    /// Returns false when it was already set.
+    pub fn set_hash_algorithm(algorithm: HashAlgorithm) -> bool {
+        HASH_ALGORITHM.set(algorithm).is_ok()
     }
 
-    /// Generate a SHA-256 hash of the content string
+    /// Set the normalization applied by [`Content::generate_hash`], once at startup.
+    /// Returns false when it was already set.
+    pub fn set_hash_normalization(normalization: HashNormalization) -> bool {
+        HASH_NORMALIZATION.set(normalization).is_ok()
+    }
+
+    /// Generate a hash of the content string with the configured normalization
+    /// and hash function, unnormalized SHA-256 unless set otherwise
     pub fn generate_hash(content: &str) -> String {
-        let mut hasher = Sha256::new();
-        hasher.update(content.as_bytes());
-        format!("{:x}", hasher.finalize())
+        let algorithm = HASH_ALGORITHM.get().copied().unwrap_or_default();
+        let normalization = HASH_NORMALIZATION.get().copied().unwrap_or_default();
+        Self::generate_hash_as(content, algorithm, normalization)
     }
+
+    /// Generate a hash with the given settings, prefixed with their name as
+    /// given by [`Content::hash_prefix`]
+    pub fn generate_hash_as(
+        content: &str,
+        algorithm: HashAlgorithm,
+        normalization: HashNormalization,
+    ) -> String {
+        format!(
+            "{}{}",
+            Self::hash_prefix(algorithm, normalization),
+            Self::generate_hash_with(&Self::normalize(content, normalization), algorithm)
+        )
+    }
+
+    /// Hash content with the settings `hash` was made with, so a stored hash is
+    /// compared like with like after the settings changed. Hashes with an
+    /// unknown prefix are compared with the configured settings.
+    pub fn generate_hash_like(content: &str, hash: &str) -> String {
+        match Self::hash_settings(hash) {
+            Some((algorithm, normalization)) => {
+                Self::generate_hash_as(content, algorithm, normalization)
+            }
+            None => Self::generate_hash(content),
+        }
+    }
+
+    /// Hashes content may have been stored with: with the configured settings
+    /// first and, when those aren't the default, with the default settings
+    /// every hash was made with before they could be changed
+    pub fn lookup_hashes(content: &str) -> Vec<String> {
+        let mut hashes = vec![Self::generate_hash(content)];
+        let default = Self::generate_hash_as(
+            content,
+            HashAlgorithm::default(),
+            HashNormalization::default(),
+        );
+        if hashes[0] != default {
+            hashes.push(default);
+        }
+        hashes
+    }
+
+    /// Prefix naming the hash function and normalization of a hash, e.g.
+    /// `blake3-ws-cf:`, so hashes made with different settings never match.
+    /// Unnormalized SHA-256 hashes, the default, have none.
+    pub fn hash_prefix(algorithm: HashAlgorithm, normalization: HashNormalization) -> String {
+        if algorithm == HashAlgorithm::default() && normalization == HashNormalization::default() {
+            return String::new();
+        }
+
+        let mut prefix = algorithm.name().to_string();
+        if normalization.collapse_whitespace {
+            prefix.push_str("-ws");
+        }
+        if normalization.case_fold {
+            prefix.push_str("-cf");
+        }
+        prefix.push(':');
+        prefix
+    }
+
+    /// Settings a hash was made with, read from its prefix
+    fn hash_settings(hash: &str) -> Option<(HashAlgorithm, HashNormalization)> {
+        let Some((prefix, _)) = hash.split_once(':') else {
+            return Some((HashAlgorithm::default(), HashNormalization::default()));
+        };
+
+        let mut parts = prefix.split('-');
+        let algorithm = parts.next()?.parse().ok()?;
+        let mut normalization = HashNormalization::default();
+        for part in parts {
+            match part {
+                "ws" => normalization.collapse_whitespace = true,
+                "cf" => normalization.case_fold = true,
+                _ => return None,
+            }
+        }
+
+        Some((algorithm, normalization))
+    }
+
+    /// Normalize text before hashing, so that e.g. "Hello " and "hello" can hash the same
+    pub fn normalize(content: &str, normalization: HashNormalization) -> Cow<'_, str> {
+        let mut text = Cow::Borrowed(content);
+
+        if normalization.collapse_whitespace {
+            text = Cow::Owned(text.split_whitespace().collect::<Vec<_>>().join(" "));
+        }
+
+        if normalization.case_fold && !Self::looks_like_url(&text) {
+            text = Cow::Owned(text.to_lowercase());
+        }
+
+        text
+    }
+
+    /// Hex digest of the content string with a given hash function, without
+    /// normalization or prefix
+    pub fn generate_hash_with(content: &str, algorithm: HashAlgorithm) -> String {
+        match algorithm {
+            HashAlgorithm::Sha256 => {
+                let mut hasher = Sha256::new();
+                hasher.update(content.as_bytes());
+                format!("{:x}", hasher.finalize())
+            }
+            HashAlgorithm::Blake3 => blake3::hash(content.as_bytes()).to_hex().to_string(),
+        }
+    }
+}
+
+/// Reachability of URL content as determined by the dead-link checker
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkStatus {
+    Alive,
+    Dead,
+}
+
+/// Who may see content in multi-tenant mode
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Visibility {
+    /// Only the caller that marked it private and the main API key
+    Private,
+    /// All callers of the tenant, and tenants it is shared with
+    #[default]
+    Team,
+    /// All tenants, read-only for the others
+    Public,
 }
 
 impl fmt::Display for Content {
@@ -76,6 +387,59 @@ impl fmt::Display for Content {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ClassifyRequest {
     pub content: String,
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+}
+
+/// Changes to the metadata of stored content, a null value removes the key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetadataPatchRequest {
+    pub metadata: HashMap<String, Option<String>>,
+}
+
+/// Tags replacing the current tags of stored content
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContentTagsRequest {
+    pub tags: Vec<String>,
+}
+
+/// Stored content with the tags of a new classification, and how they changed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReclassifyResponse {
+    pub content: Content,
+    /// Tags the content didn't have before
+    pub added: Vec<String>,
+    /// Tags the classifier no longer gives
+    pub removed: Vec<String>,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+/// Access to stored content, replacing the current visibility and shares
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContentAccessRequest {
+    pub visibility: Visibility,
+    /// Tenant namespaces to share the content with
+    #[serde(default)]
+    pub shared_with: Vec<String>,
+}
+
+/// Tenant namespaces to share all content with a tag with
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagShareRequest {
+    pub namespaces: Vec<String>,
+}
+
+/// Represents a tag share response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagShareResponse {
+    pub tag: String,
+    /// Content items that were shared
+    pub count: usize,
+    /// Whether the operation was successful
+    pub success: bool,
+    /// Any error message
+    pub error: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -107,12 +471,544 @@ pub struct TagsResponse {
     pub tags: Vec<String>,
     /// Total number of tags
     pub count: usize,
+    /// The tags arranged by their levels, when requested with `tree=true`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tree: Option<Vec<TagNode>>,
     /// Whether the operation was successful
     pub success: bool,
     /// Any error message
     pub error: Option<String>,
 }
 
+/// A level of the tag hierarchy with the levels below it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TagNode {
+    /// The last level, `rust` for `lang/rust`
+    pub name: String,
+    /// The full tag
+    pub tag: String,
+    /// Whether content is tagged with it, false for levels that only group other tags
+    pub tagged: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<TagNode>,
+}
+
+/// Represents a tag counts response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagCountsResponse {
+    /// Number of content items per tag
+    pub counts: HashMap<String, usize>,
+    /// Whether the operation was successful
+    pub success: bool,
+    /// Any error message
+    pub error: Option<String>,
+}
+
+/// Represents a request to share content through a signed link
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ShareRequest {
+    /// Lifetime of the link, the configured default when unset
+    pub expires_in_secs: Option<u64>,
+}
+
+/// Represents a share link response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShareResponse {
+    /// Link that gives access to the content without an API key
+    pub url: String,
+    pub expires_at: DateTime<Utc>,
+    /// Whether the operation was successful
+    pub success: bool,
+    /// Any error message
+    pub error: Option<String>,
+}
+
+/// Criteria of content to purge, all given criteria must match
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PurgeRequest {
+    /// Content hash, as returned in `content_hash`
+    pub hash: Option<String>,
+    /// URL pattern where `*` matches any run of characters
+    pub url_pattern: Option<String>,
+    /// Metadata key the content must have
+    pub metadata_key: Option<String>,
+    /// Value the metadata key must have, any value when unset
+    pub metadata_value: Option<String>,
+}
+
+/// Content removed by a purge
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PurgedContent {
+    pub id: String,
+    pub content_hash: Option<String>,
+    /// Tags whose index no longer references the content
+    pub tags: Vec<String>,
+    /// Whether an archived page snapshot was removed
+    pub snapshot: bool,
+}
+
+/// Attestation of what a purge removed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PurgeResponse {
+    pub purged: Vec<PurgedContent>,
+    /// Number of content items removed
+    pub count: usize,
+    pub purged_at: DateTime<Utc>,
+    /// Whether the operation was successful
+    pub success: bool,
+    /// Any error message
+    pub error: Option<String>,
+}
+
+/// Blocked tags removed from stored content
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockedTagsResponse {
+    /// The tags on the blocklist
+    pub blocklist: Vec<String>,
+    /// Number of content items each blocked tag was removed from
+    pub removed: HashMap<String, usize>,
+    /// Whether the operation was successful
+    pub success: bool,
+    /// Any error message
+    pub error: Option<String>,
+}
+
+/// Request to replace tags with another tag on all content
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagMergeRequest {
+    /// The tags to replace
+    pub tags: Vec<String>,
+    /// The tag they are replaced with
+    pub into: String,
+}
+
+/// Result of merging tags
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagMergeResponse {
+    pub into: String,
+    /// Number of content items each tag was replaced on
+    pub merged: HashMap<String, usize>,
+    /// Whether the operation was successful
+    pub success: bool,
+    /// Any error message
+    pub error: Option<String>,
+}
+
+/// Two tags that probably mean the same
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagDuplicate {
+    /// The tag suggested to go, the one with fewer content items
+    pub tag: String,
+    /// The tag suggested to keep
+    pub into: String,
+    /// How the pair was found: `spelling`, `acronym` or `meaning`
+    pub reason: String,
+    /// How alike the tags are, from 0 to 1
+    pub score: f32,
+    /// Body for `POST /admin/tags/merge` that merges the pair
+    pub merge: TagMergeRequest,
+}
+
+/// Represents a report of probably duplicate tags
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagDuplicatesResponse {
+    /// The most alike first
+    pub duplicates: Vec<TagDuplicate>,
+    pub count: usize,
+    /// Whether the operation was successful
+    pub success: bool,
+    /// Any error message
+    pub error: Option<String>,
+}
+
+/// API key created through the admin endpoints. Only a hash of the key is stored.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKey {
+    pub id: Uuid,
+    pub name: String,
+    /// SHA-256 of the key
+    pub key_hash: String,
+    /// Tenant the key belongs to, a key without namespace sees all data
+    pub namespace: Option<String>,
+    pub created_at: DateTime<Utc>,
+    /// When a rotated key stops working
+    pub expires_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    /// Only allowed to read, requests that change data are refused
+    #[serde(default)]
+    pub read_only: bool,
+}
+
+impl ApiKey {
+    /// Hash a key the way it is stored
+    pub fn hash_key(key: &str) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(key.as_bytes());
+        format!("{:x}", hasher.finalize())
+    }
+
+    /// Whether the key can be used at `now`
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.revoked_at.is_none() && self.expires_at.is_none_or(|expires_at| now < expires_at)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateApiKeyRequest {
+    pub name: String,
+    pub namespace: Option<String>,
+    #[serde(default)]
+    pub read_only: bool,
+}
+
+/// Represents an API key response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKeyResponse {
+    pub key: ApiKey,
+    /// The new key, only returned when it is created or rotated
+    pub secret: Option<String>,
+    /// Whether the operation was successful
+    pub success: bool,
+    /// Any error message
+    pub error: Option<String>,
+}
+
+/// Represents an API key list response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKeysResponse {
+    pub keys: Vec<ApiKey>,
+    /// Total number of keys
+    pub count: usize,
+    /// Whether the operation was successful
+    pub success: bool,
+    /// Any error message
+    pub error: Option<String>,
+}
+
+/// Resources used by an account in a month
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Usage {
+    /// Content classified and stored
+    pub classifications: u64,
+    /// Bytes of content and snapshots stored
+    pub stored_bytes: u64,
+    /// Tokens used by the classifier
+    pub tokens: u64,
+}
+
+impl Usage {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn add(&mut self, other: &Usage) {
+        self.classifications += other.classifications;
+        self.stored_bytes += other.stored_bytes;
+        self.tokens += other.tokens;
+    }
+}
+
+/// Usage of one account, with its monthly quota
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountUsage {
+    /// `tenant:<namespace>` for tenants, otherwise the API key, bearer token subject or certificate
+    pub account: String,
+    pub usage: Usage,
+    pub quota: Option<UsageQuota>,
+}
+
+/// Monthly limits of an account, unset fields are unlimited
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct UsageQuota {
+    pub classifications: Option<u64>,
+    pub stored_bytes: Option<u64>,
+    pub tokens: Option<u64>,
+}
+
+impl UsageQuota {
+    /// The first limit that `usage` has reached, if any
+    pub fn exceeded_by(&self, usage: &Usage) -> Option<&'static str> {
+        let reached = |limit: Option<u64>, used: u64| limit.is_some_and(|limit| used >= limit);
+
+        if reached(self.classifications, usage.classifications) {
+            Some("classifications")
+        } else if reached(self.stored_bytes, usage.stored_bytes) {
+            Some("stored bytes")
+        } else if reached(self.tokens, usage.tokens) {
+            Some("tokens")
+        } else {
+            None
+        }
+    }
+}
+
+/// Represents a usage report response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageResponse {
+    /// Month of the report, as `YYYY-MM`
+    pub month: String,
+    pub accounts: Vec<AccountUsage>,
+    /// Whether the operation was successful
+    pub success: bool,
+    /// Any error message
+    pub error: Option<String>,
+}
+
+/// Content classified over a period, grouped by tag
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Digest {
+    pub period: DigestPeriod,
+    pub since: DateTime<Utc>,
+    pub until: DateTime<Utc>,
+    /// Number of content items classified in the period
+    pub count: usize,
+    /// Tags with the most content first. Content with several tags is listed
+    /// under each of them.
+    pub tags: Vec<DigestTag>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DigestTag {
+    pub tag: String,
+    /// Newest first
+    pub items: Vec<DigestItem>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DigestItem {
+    pub id: Uuid,
+    /// The URL, or the start of the text
+    pub title: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Represents a digest response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DigestResponse {
+    pub digest: Digest,
+    /// Whether the operation was successful
+    pub success: bool,
+    /// Any error message
+    pub error: Option<String>,
+}
+
+/// Content found by semantic search, with its similarity to the query
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SemanticSearchHit {
+    pub content: Content,
+    /// Cosine similarity of the content and the query, up to 1
+    pub score: f32,
+}
+
+/// Represents a semantic search response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SemanticSearchResponse {
+    pub query: String,
+    /// Matching content, the most similar first
+    pub items: Vec<SemanticSearchHit>,
+    /// Number of items found
+    pub count: usize,
+    /// Whether the operation was successful
+    pub success: bool,
+    /// Any error message
+    pub error: Option<String>,
+}
+
+/// Represents a request to cluster the embedded content
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ClusterRequest {
+    /// Number of clusters, about the square root of half the items when unset
+    pub clusters: Option<usize>,
+    /// Add the label of each cluster as a tag to its content
+    #[serde(default)]
+    pub apply: bool,
+}
+
+/// Content that is close in meaning, with the label proposed for it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContentCluster {
+    /// Name proposed by the classifier, unset when it failed
+    pub label: Option<String>,
+    pub size: usize,
+    /// Titles of the items closest to the middle of the cluster
+    pub titles: Vec<String>,
+    /// All content in the cluster, the closest to its middle first
+    pub content_ids: Vec<String>,
+}
+
+/// Represents a cluster response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClusterResponse {
+    /// Clusters, the largest first
+    pub clusters: Vec<ContentCluster>,
+    /// Number of content items given the label of their cluster as a tag
+    pub tagged: usize,
+    /// Whether the operation was successful
+    pub success: bool,
+    /// Any error message
+    pub error: Option<String>,
+}
+
+/// Stored content recommended to the caller, with the interest tags it has
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Recommendation {
+    pub content: Content,
+    /// Weight of the caller's interests the content matches, 0 for content
+    /// recommended only for being new
+    pub score: f64,
+    /// Interest tags of the caller the content has
+    pub reasons: Vec<String>,
+}
+
+/// Represents a recommendations response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecommendationsResponse {
+    /// Recommended content, the best match first
+    pub items: Vec<Recommendation>,
+    /// Number of items recommended
+    pub count: usize,
+    /// Whether the operation was successful
+    pub success: bool,
+    /// Any error message
+    pub error: Option<String>,
+}
+
+/// Represents a question about the stored content
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AskRequest {
+    pub question: String,
+    /// Number of stored items given to the LLM as context, 5 when unset
+    pub limit: Option<usize>,
+}
+
+/// Stored content an answer refers to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Citation {
+    /// Number the answer cites the content by, like `[1]`
+    pub number: usize,
+    pub id: Uuid,
+    pub title: String,
+}
+
+/// Represents an answer to a question about the stored content
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AskResponse {
+    pub answer: String,
+    /// Content the answer cites, in the order it is first cited
+    pub citations: Vec<Citation>,
+    /// Number of items given to the LLM as context
+    pub sources: usize,
+    /// Whether the operation was successful
+    pub success: bool,
+    /// Any error message
+    pub error: Option<String>,
+}
+
+/// A tag used more in the recent window than in the baseline before it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagTrend {
+    pub tag: String,
+    /// Items tagged in the recent window
+    pub recent: u64,
+    /// Items tagged in the baseline window
+    pub baseline: u64,
+    /// Daily rate in the recent window over the daily rate in the baseline
+    pub change: f64,
+}
+
+/// Represents a trending tags response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrendingTagsResponse {
+    /// Rising tags, the fastest rising first
+    pub trending: Vec<TagTrend>,
+    pub recent_days: u32,
+    pub baseline_days: u32,
+    /// Whether the operation was successful
+    pub success: bool,
+    /// Any error message
+    pub error: Option<String>,
+}
+
+/// Represents a request to change the log filter
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogLevelRequest {
+    /// Filter in the `RUST_LOG` syntax, e.g. `info,classify::storage=debug`
+    pub filter: String,
+}
+
+/// Represents the log filter in use
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogLevelResponse {
+    pub filter: String,
+    /// Whether the operation was successful
+    pub success: bool,
+    /// Any error message
+    pub error: Option<String>,
+}
+
+/// Represents a request to switch maintenance mode on or off
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceRequest {
+    pub enabled: bool,
+    /// Shown to callers whose changes are refused
+    pub message: Option<String>,
+}
+
+/// Represents the maintenance mode of the server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceResponse {
+    pub enabled: bool,
+    pub message: Option<String>,
+    /// Whether the operation was successful
+    pub success: bool,
+    /// Any error message
+    pub error: Option<String>,
+}
+
+/// Represents the state of the async runtime, to diagnose stalled tasks and
+/// contention on shared connections
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuntimeStatsResponse {
+    /// Worker threads of the runtime
+    pub workers: usize,
+    /// Tasks spawned and not yet completed
+    pub alive_tasks: usize,
+    /// Tasks scheduled but not yet picked up by a worker
+    pub global_queue_depth: usize,
+    pub connections: Vec<ConnectionStats>,
+    /// Whether the operation was successful
+    pub success: bool,
+    /// Any error message
+    pub error: Option<String>,
+}
+
+/// Represents a storage connection shared by several storages. Commands take
+/// turns on it, so a connection that stays in use points at contention.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConnectionStats {
+    pub backend: String,
+    /// Storages holding the connection
+    pub users: usize,
+    /// Whether a command holds the connection right now
+    pub in_use: bool,
+}
+
+/// Represents the status of the service and the components it depends on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthResponse {
+    /// Whether all checked components are healthy
+    pub healthy: bool,
+    pub components: std::collections::BTreeMap<String, ComponentHealth>,
+}
+
+/// Represents the status of one component
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComponentHealth {
+    pub healthy: bool,
+    /// How long the check took
+    pub latency_ms: u64,
+}
+
 /// Application error types
 #[derive(Debug, Error)]
 pub enum ClassifyError {
@@ -139,6 +1035,19 @@ pub enum ClassifyError {
 
     #[error("HTTP error: {0}")]
     HttpError(String),
+
+    #[error("Ingestion error: {0}")]
+    IngestError(String),
+
+    #[error("Extraction error: {0}")]
+    ExtractionError(String),
+
+    #[error("Embedding error: {0}")]
+    EmbeddingError(String),
+
+    /// The provider limits the rate or is overloaded, with how long it asked to wait
+    #[error("Rate limited by the {0} API")]
+    RateLimited(String, Option<std::time::Duration>),
 }
 
 pub type ClassifyResult<T> = Result<T, ClassifyError>;
@@ -164,4 +1073,96 @@ mod tests {
         let direct_hash = Content::generate_hash(text);
         assert_eq!(Some(direct_hash), content1.content_hash);
     }
+
+    #[test]
+    fn test_content_slugs() {
+        let content1 = Content::new("Same text".to_string());
+        let content2 = Content::new("Same text".to_string());
+
+        let slug = content1.slug.clone().unwrap();
+        assert_eq!(slug.len(), SLUG_LENGTH);
+        assert!(slug
+            .chars()
+            .all(|c| c.is_ascii_digit() || c.is_ascii_lowercase()));
+        assert!(Uuid::parse_str(&slug).is_err());
+        assert_ne!(content1.slug, content2.slug);
+    }
+
+    #[test]
+    fn test_content_hash_algorithms() {
+        let text = "Test content for hashing";
+
+        let sha256 = Content::generate_hash_with(text, HashAlgorithm::Sha256);
+        let blake3 = Content::generate_hash_with(text, HashAlgorithm::Blake3);
+
+        assert_eq!(sha256, Content::generate_hash(text));
+        assert_eq!(sha256.len(), 64);
+        assert_eq!(blake3.len(), 64);
+        assert_ne!(sha256, blake3);
+        assert_eq!(
+            blake3,
+            Content::generate_hash_with(text, HashAlgorithm::Blake3)
+        );
+    }
+
+    #[test]
+    fn test_content_normalization() {
+        let none = HashNormalization::default();
+        let whitespace = HashNormalization {
+            collapse_whitespace: true,
+            case_fold: false,
+        };
+        let all = HashNormalization {
+            collapse_whitespace: true,
+            case_fold: true,
+        };
+
+        assert_eq!(
+            Content::normalize(" Hello  \n world ", none),
+            " Hello  \n world "
+        );
+        assert_eq!(
+            Content::normalize(" Hello  \n world ", whitespace),
+            "Hello world"
+        );
+        assert_eq!(
+            Content::normalize("Hello ", all),
+            Content::normalize("hello", all)
+        );
+
+        // URL paths are case sensitive
+        assert_eq!(
+            Content::normalize(" https://example.com/Post ", all),
+            "https://example.com/Post"
+        );
+    }
+
+    #[test]
+    fn test_hashes_name_their_settings() {
+        let text = "Test content for hashing";
+        let none = HashNormalization::default();
+        let all = HashNormalization {
+            collapse_whitespace: true,
+            case_fold: true,
+        };
+
+        // Default hashes are unprefixed, as they were stored before
+        let sha256 = Content::generate_hash_as(text, HashAlgorithm::Sha256, none);
+        assert_eq!(sha256, Content::generate_hash(text));
+        assert_eq!(Content::lookup_hashes(text), vec![sha256.clone()]);
+
+        let blake3 = Content::generate_hash_as(text, HashAlgorithm::Blake3, none);
+        assert!(blake3.starts_with("blake3:"));
+        let normalized = Content::generate_hash_as(" Hello ", HashAlgorithm::Sha256, all);
+        assert!(normalized.starts_with("sha256-ws-cf:"));
+
+        // A stored hash is compared with a hash made the same way
+        assert_eq!(Content::generate_hash_like(text, &sha256), sha256);
+        assert_eq!(Content::generate_hash_like(text, &blake3), blake3);
+        assert_eq!(
+            Content::generate_hash_like("hello", &normalized),
+            normalized
+        );
+        assert_ne!(Content::generate_hash_like("Other", &blake3), blake3);
+    }
 }
@@ -0,0 +1,201 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tracing::Subscriber;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt, reload, EnvFilter, Layer, Registry};
+
+use crate::config::{LogFileConfig, LogFormat, LogRotation, LoggingConfig};
+use crate::{ClassifyError, ClassifyResult};
+
+/// Swaps the filter of the installed subscriber
+static FILTER: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+
+/// Parse a filter in the `RUST_LOG` syntax, e.g. `info,classify::storage=debug`
+pub fn parse_filter(directives: &str) -> ClassifyResult<EnvFilter> {
+    EnvFilter::try_new(directives).map_err(|e| {
+        ClassifyError::ConfigError(format!("Invalid log filter '{}': {}", directives, e))
+    })
+}
+
+fn output_layer<S, W>(format: LogFormat, writer: W, ansi: bool) -> Box<dyn Layer<S> + Send + Sync>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    let layer = fmt::layer().with_writer(writer).with_ansi(ansi);
+    match format {
+        LogFormat::Text => layer.boxed(),
+        LogFormat::Json => layer.json().boxed(),
+    }
+}
+
+/// Install the global tracing subscriber. Log lines include the fields of the
+/// spans they are logged in, such as the request ID. Returns the guard that
+/// flushes the log file when it is dropped, keep it until the program exits.
+pub fn init(config: &LoggingConfig) -> ClassifyResult<Option<WorkerGuard>> {
+    let (filter, handle) = reload::Layer::new(parse_filter(&config.filter)?);
+
+    let mut output = output_layer(config.format, io::stdout, true);
+    let mut guard = None;
+    if let Some(file) = &config.file {
+        let (writer, file_guard) = match file.rotation {
+            LogRotation::Size => tracing_appender::non_blocking(
+                SizeRotatingFile::open(
+                    Path::new(&file.directory).join(&file.name),
+                    file.max_bytes,
+                    file.max_files,
+                )
+                .map_err(|e| log_file_error(file, e))?,
+            ),
+            rotation => {
+                tracing_appender::non_blocking(rolling_appender(file, rotation).map_err(|e| {
+                    ClassifyError::ConfigError(format!(
+                        "Failed to open log file in {}: {}",
+                        file.directory, e
+                    ))
+                })?)
+            }
+        };
+        output = output
+            .and_then(output_layer(config.format, writer, false))
+            .boxed();
+        guard = Some(file_guard);
+    }
+
+    // The filter only applies to the log output, tokio-console needs the
+    // runtime's trace events whatever the log level is
+    let subscriber = tracing_subscriber::registry().with(output.with_filter(filter));
+    #[cfg(feature = "console")]
+    let subscriber = subscriber.with(console_subscriber::spawn());
+
+    subscriber.try_init().map_err(|e| {
+        ClassifyError::ConfigError(format!("Failed to set tracing subscriber: {}", e))
+    })?;
+
+    FILTER.set(handle).ok();
+    Ok(guard)
+}
+
+fn log_file_error(file: &LogFileConfig, e: io::Error) -> ClassifyError {
+    ClassifyError::ConfigError(format!(
+        "Failed to open log file {}/{}: {}",
+        file.directory, file.name, e
+    ))
+}
+
+/// Log files rotated by time, named after the date and hour they were started
+fn rolling_appender(
+    file: &LogFileConfig,
+    rotation: LogRotation,
+) -> Result<RollingFileAppender, tracing_appender::rolling::InitError> {
+    let mut builder = RollingFileAppender::builder()
+        .rotation(match rotation {
+            LogRotation::Hourly => Rotation::HOURLY,
+            LogRotation::Never => Rotation::NEVER,
+            _ => Rotation::DAILY,
+        })
+        .filename_prefix(&file.name);
+    if file.max_files > 0 {
+        builder = builder.max_log_files(file.max_files);
+    }
+    builder.build(&file.directory)
+}
+
+/// Log file that is moved to `<name>.1`, `<name>.2` and so on when it reaches
+/// its maximum size, keeping at most `max_files` of those
+pub struct SizeRotatingFile {
+    path: PathBuf,
+    max_bytes: u64,
+    max_files: usize,
+    file: File,
+    size: u64,
+}
+
+impl SizeRotatingFile {
+    pub fn open(path: PathBuf, max_bytes: u64, max_files: usize) -> io::Result<Self> {
+        if let Some(directory) = path.parent() {
+            std::fs::create_dir_all(directory)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+
+        Ok(Self {
+            path,
+            max_bytes,
+            max_files,
+            file,
+            size,
+        })
+    }
+
+    fn numbered(&self, number: usize) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{}", number));
+        path.into()
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+
+        if self.max_files > 0 {
+            for number in (1..self.max_files).rev() {
+                let from = self.numbered(number);
+                if from.exists() {
+                    std::fs::rename(&from, self.numbered(number + 1))?;
+                }
+            }
+            std::fs::rename(&self.path, self.numbered(1))?;
+        }
+
+        self.file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+impl Write for SizeRotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.size > 0 && self.size + buf.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+
+        let written = self.file.write(buf)?;
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// Filter the installed subscriber currently logs with
+pub fn current_filter() -> ClassifyResult<String> {
+    installed_filter()?
+        .with_current(|filter| filter.to_string())
+        .map_err(|e| ClassifyError::ConfigError(format!("Failed to read log filter: {}", e)))
+}
+
+/// Replace the filter of the installed subscriber, until the next restart
+pub fn set_filter(filter: EnvFilter) -> ClassifyResult<()> {
+    installed_filter()?
+        .reload(filter)
+        .map_err(|e| ClassifyError::ConfigError(format!("Failed to set log filter: {}", e)))
+}
+
+fn installed_filter() -> ClassifyResult<&'static reload::Handle<EnvFilter, Registry>> {
+    FILTER
+        .get()
+        .ok_or_else(|| ClassifyError::ConfigError("Logging is not initialized".to_string()))
+}
@@ -0,0 +1,27 @@
+use crate::logging::SizeRotatingFile;
+use std::io::Write;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_size_rotating_file_keeps_max_files() {
+        let dir = std::env::temp_dir().join(format!("classify-logs-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("classify.log");
+        let mut file = SizeRotatingFile::open(path.clone(), 10, 2).unwrap();
+
+        for line in ["first\n", "second\n", "third\n", "fourth\n"] {
+            file.write_all(line.as_bytes()).unwrap();
+        }
+        file.flush().unwrap();
+
+        let read = |name: &str| std::fs::read_to_string(dir.join(name)).unwrap();
+        assert_eq!(read("classify.log"), "fourth\n");
+        assert_eq!(read("classify.log.1"), "third\n");
+        assert_eq!(read("classify.log.2"), "second\n");
+        assert!(!dir.join("classify.log.3").exists());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,23 +1,82 @@
+use std::num::NonZeroUsize;
 use std::process::exit;
-use tracing::{error, info, Level};
-use tracing_subscriber::FmtSubscriber;
+use std::sync::Arc;
+use tracing::{error, info, warn};
 
+use classify::activity::create_activity_log;
+use classify::api::maintenance::Maintenance;
+use classify::api::rate_limit::create_rate_limiter;
+use classify::api::share::ShareLinks;
 use classify::api::{start_server, AppState};
 use classify::classifier::create_classifier;
-use classify::config::AppConfig;
-use classify::storage::{create_content_storage, create_tag_storage};
+use classify::classifier::instrumented::InstrumentedClassifier;
+use classify::config::secrets::refresh_secrets;
+use classify::config::{AppConfig, LoggingConfig};
+use classify::embedding::create_semantic_index;
+use classify::ingest::lock::create_dedup_lock;
+use classify::ingest::spawn_ingestion_workers;
+use classify::jobs::heartbeat::Heartbeat;
+use classify::jobs::{spawn_background_jobs, spawn_periodic};
+use classify::queue::worker::spawn_workers;
+use classify::queue::{create_job_queue, ProcessMode};
+use classify::shutdown;
+use classify::storage::api_key::ApiKeys;
+use classify::storage::hash_cache::HashLookupCache;
+use classify::storage::instrumented::{InstrumentedContentStorage, InstrumentedTagStorage};
+use classify::storage::{
+    create_api_key_storage, create_content_storage, create_shared_storage, create_tag_storage,
+    create_usage_storage, ContentStorage, TagStorage,
+};
+use classify::trends::create_tag_trends;
+use classify::usage::UsageTracker;
+use classify::Content;
+use futures::future::join_all;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let subscriber = FmtSubscriber::builder()
-        .with_max_level(Level::INFO)
-        .finish();
+    // Logging starts before the rest of the configuration, which logs while loading
+    dotenvy::dotenv().ok();
+    if let Err(e) = classify::config::file::load() {
+        eprintln!("{}", e);
+        exit(1);
+    }
+    let _log_guard =
+        match LoggingConfig::from_env().and_then(|logging| classify::logging::init(&logging)) {
+            Ok(guard) => guard,
+            Err(e) => {
+                eprintln!("Failed to initialize logging: {}", e);
+                exit(1);
+            }
+        };
 
-    tracing::subscriber::set_global_default(subscriber).expect("Failed to set tracing subscriber");
+    // `classify doctor [--dry-run]` checks the setup and exits
+    if std::env::args().nth(1).as_deref() == Some("doctor") {
+        let dry_run = std::env::args().any(|arg| arg == "--dry-run");
+        let report = classify::doctor::run(dry_run).await;
+        println!("{}", report);
+        exit(if report.passed() { 0 } else { 1 });
+    }
+
+    // `classify seed` loads the bundled sample content and exits
+    if std::env::args().nth(1).as_deref() == Some("seed") {
+        match classify::seed::run().await {
+            Ok(report) => {
+                println!("{}", report);
+                exit(0);
+            }
+            Err(e) => {
+                error!("Failed to seed sample content: {}", e);
+                exit(1);
+            }
+        }
+    }
 
-    info!("Starting classify application...");
+    // `classify serve` runs the API only and `classify worker` only classifies
+    // queued jobs, without a subcommand the process does both
+    let mode = ProcessMode::from_command(std::env::args().nth(1).as_deref());
+    info!("Starting classify application ({:?} mode)...", mode);
 
-    let config = match AppConfig::init() {
+    let config = match AppConfig::load().await {
         Ok(config) => config,
         Err(e) => {
             error!("Failed to initialize configuration: {}", e);
@@ -25,33 +84,96 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     };
 
-    let content_storage =
-        match create_content_storage(&config.storage.storage_type, &config.storage).await {
-            Ok(storage) => storage,
-            Err(e) => {
-                error!("Failed to initialize content storage: {}", e);
-                exit(1);
-            }
-        };
+    if let Err(e) = mode.check_queue(config.queue.queue_type) {
+        error!("{}", e);
+        exit(1);
+    }
 
-    info!(
-        "Content storage initialized: {:?}",
-        config.storage.storage_type
-    );
+    if config.api.auth_disabled {
+        warn!("**************************************************************");
+        warn!("AUTH_DISABLED is set: every request is accepted without an API");
+        warn!("key and has full access. Never use this outside development!");
+        warn!("**************************************************************");
+    }
 
-    let tag_storage =
-        match create_tag_storage(&config.tag_storage.tag_storage_type, &config.tag_storage).await {
-            Ok(storage) => storage,
+    let metrics = match classify::metrics::install() {
+        Ok(metrics) => metrics,
+        Err(e) => {
+            error!("Failed to initialize metrics: {}", e);
+            exit(1);
+        }
+    };
+
+    Content::set_hash_algorithm(config.ingest.hash_algorithm);
+    Content::set_hash_normalization(config.ingest.hash_normalization);
+
+    let (content_storage, tag_storage, atomic_storage) = match &config.storage_backend {
+        Some(backend) => match create_shared_storage(backend, config).await {
+            Ok(storage) => {
+                info!("Content and tag storage initialized: {:?}", backend);
+                (
+                    storage.content_storage,
+                    storage.tag_storage,
+                    storage.atomic_storage,
+                )
+            }
             Err(e) => {
-                error!("Failed to initialize tag storage: {}", e);
+                error!("Failed to initialize storage: {}", e);
                 exit(1);
             }
-        };
+        },
+        None => {
+            let content_storage =
+                match create_content_storage(&config.storage.storage_type, &config.storage).await {
+                    Ok(storage) => storage,
+                    Err(e) => {
+                        error!("Failed to initialize content storage: {}", e);
+                        exit(1);
+                    }
+                };
 
-    info!(
-        "Tag storage initialized: {:?}",
-        config.tag_storage.tag_storage_type
-    );
+            info!(
+                "Content storage initialized: {:?}",
+                config.storage.storage_type
+            );
+
+            let tag_storage =
+                match create_tag_storage(&config.tag_storage.tag_storage_type, &config.tag_storage)
+                    .await
+                {
+                    Ok(storage) => storage,
+                    Err(e) => {
+                        error!("Failed to initialize tag storage: {}", e);
+                        exit(1);
+                    }
+                };
+
+            info!(
+                "Tag storage initialized: {:?}",
+                config.tag_storage.tag_storage_type
+            );
+
+            (content_storage, tag_storage, None)
+        }
+    };
+
+    let (content_backend, tag_backend) = match &config.storage_backend {
+        Some(backend) => (format!("{:?}", backend), format!("{:?}", backend)),
+        None => (
+            format!("{:?}", config.storage.storage_type),
+            format!("{:?}", config.tag_storage.tag_storage_type),
+        ),
+    };
+    let content_storage: Arc<dyn ContentStorage> = Arc::new(InstrumentedContentStorage::new(
+        content_storage,
+        &content_backend.to_lowercase(),
+        config.storage.slow_operation_threshold,
+    ));
+    let tag_storage: Arc<dyn TagStorage> = Arc::new(InstrumentedTagStorage::new(
+        tag_storage,
+        &tag_backend.to_lowercase(),
+        config.storage.slow_operation_threshold,
+    ));
 
     let classifier =
         match create_classifier(&config.classifier.classifier_type, &config.classifier).await {
@@ -66,26 +188,181 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         "Classifier initialized: {:?}",
         config.classifier.classifier_type
     );
+    let classifier = Arc::new(InstrumentedClassifier::new(
+        classifier,
+        &format!("{:?}", config.classifier.classifier_type).to_lowercase(),
+    ));
 
-    let app_state = AppState::new(classifier, content_storage, tag_storage);
+    let api_keys = match create_api_key_storage(config).await {
+        Ok(storage) => ApiKeys::new(storage, config.api.key_rotation_grace),
+        Err(e) => {
+            error!("Failed to initialize API key storage: {}", e);
+            exit(1);
+        }
+    };
+
+    info!("API key storage initialized: {:?}", config.api.key_storage);
 
-    let addr = match config.api_addr() {
-        Ok(addr) => addr,
+    let usage = match create_usage_storage(config).await {
+        Ok(storage) => {
+            UsageTracker::new(storage, config.api.quota, config.api.tenant_quotas.clone())
+        }
         Err(e) => {
-            error!("Failed to get API address: {}", e);
+            error!("Failed to initialize usage storage: {}", e);
             exit(1);
         }
     };
 
-    info!(
-        "Starting API server on {}:{}",
-        config.api.host, config.api.port
-    );
+    let rate_limiter = match create_rate_limiter(config).await {
+        Ok(rate_limiter) => rate_limiter,
+        Err(e) => {
+            error!("Failed to initialize rate limiter: {}", e);
+            exit(1);
+        }
+    };
+
+    let dedup_lock = match create_dedup_lock(config).await {
+        Ok(dedup_lock) => dedup_lock,
+        Err(e) => {
+            error!("Failed to initialize dedup lock: {}", e);
+            exit(1);
+        }
+    };
+
+    let semantic_index = match create_semantic_index(config).await {
+        Ok(semantic_index) => semantic_index,
+        Err(e) => {
+            error!("Failed to initialize semantic search: {}", e);
+            exit(1);
+        }
+    };
+
+    let tag_trends = match create_tag_trends(config, content_storage.as_ref()).await {
+        Ok(tag_trends) => tag_trends,
+        Err(e) => {
+            error!("Failed to initialize tag trends: {}", e);
+            exit(1);
+        }
+    };
+
+    let activity_log = match create_activity_log(config).await {
+        Ok(activity_log) => activity_log,
+        Err(e) => {
+            error!("Failed to initialize activity log: {}", e);
+            exit(1);
+        }
+    };
+
+    let queue = match create_job_queue(config).await {
+        Ok(queue) => queue,
+        Err(e) => {
+            error!("Failed to initialize job queue: {}", e);
+            exit(1);
+        }
+    };
+
+    info!("Job queue initialized: {:?}", config.queue.queue_type);
+
+    let app_state = AppState::new(classifier, content_storage, tag_storage)
+        .with_atomic_storage(atomic_storage)
+        .with_hash_cache(
+            NonZeroUsize::new(config.storage.hash_cache_size)
+                .map(|size| HashLookupCache::new(size, config.storage.hash_cache_ttl)),
+        )
+        .with_archive_snapshots(config.storage.archive_snapshots)
+        .with_ingest_config(config.ingest.clone())
+        .with_dedup_lock(dedup_lock)
+        .with_semantic_index(semantic_index)
+        .with_tag_trends(tag_trends)
+        .with_activity_log(activity_log)
+        .with_import_config(config.import.clone())
+        .with_webhooks(config.webhooks.clone())
+        .with_slack(config.slack.clone())
+        .with_api_keys(Some(api_keys))
+        .with_jwt(config.api.jwt.clone())
+        .with_rate_limiter(rate_limiter)
+        .with_auth_lockout(config.api.auth_lockout)
+        .with_access_log(config.api.access_log.clone())
+        .with_trust_forwarded_for(config.api.trust_forwarded_for)
+        .with_read_only(config.api.read_only)
+        .with_maintenance(Maintenance::new(
+            config.api.maintenance,
+            config.api.maintenance_message.clone(),
+        ))
+        .with_usage(Some(usage))
+        .with_metrics(Some(metrics))
+        .with_heartbeat(config.jobs.heartbeat.as_ref().map(Heartbeat::new))
+        .with_queue(Some(queue.clone()))
+        .with_classify_async(mode == ProcessMode::Serve)
+        .with_share_links(config.api.share_link_secret.as_deref().map(|secret| {
+            ShareLinks::new(
+                secret,
+                config.api.share_link_ttl,
+                config.api.share_link_max_ttl,
+                config.api.public_url.clone(),
+            )
+        }));
+
+    let shared_state = Arc::new(app_state.clone());
+    let mut tasks: Vec<_> = config
+        .secrets_refresh
+        .map(|period| spawn_periodic("secrets", period, refresh_secrets))
+        .into_iter()
+        .collect();
+    if mode.runs_workers() {
+        tasks.extend(spawn_background_jobs(shared_state.clone(), &config.jobs));
+        tasks.extend(spawn_ingestion_workers(shared_state.clone(), config));
+        tasks.extend(spawn_workers(shared_state.clone(), queue, &config.queue));
+    }
+
+    let result = if mode.runs_api() {
+        if let Err(e) = shared_state.imports.resume_saved(shared_state.clone()) {
+            error!("Failed to resume saved imports: {}", e);
+        }
+
+        let addr = match config.api_addr() {
+            Ok(addr) => addr,
+            Err(e) => {
+                error!("Failed to get API address: {}", e);
+                exit(1);
+            }
+        };
+
+        info!(
+            "Starting API server on {}:{}",
+            config.api.host, config.api.port
+        );
+
+        tokio::spawn(shutdown::wait_for_signal());
+
+        start_server(
+            app_state,
+            addr,
+            config.api.tls.as_ref(),
+            config.api.shutdown_timeout,
+        )
+        .await
+    } else {
+        shutdown::wait_for_signal().await;
+        Ok(())
+    };
+
+    // Let background jobs and workers finish their current run, then drop the
+    // storage connections along with the state
+    shutdown::trigger();
+    if tokio::time::timeout(config.api.shutdown_timeout, join_all(tasks))
+        .await
+        .is_err()
+    {
+        warn!("Background jobs still running, stopping anyway");
+    }
+    drop(shared_state);
 
-    if let Err(e) = start_server(app_state, addr).await {
+    if let Err(e) = result {
         error!("Server error: {}", e);
         exit(1);
     }
 
+    info!("Shutdown complete");
     Ok(())
 }
@@ -0,0 +1,38 @@
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
+
+use crate::{ClassifyError, ClassifyResult};
+
+/// Histogram buckets in seconds for latencies, from cache hits to slow LLM calls
+const LATENCY_BUCKETS: &[f64] = &[
+    0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0,
+];
+
+/// Install the global recorder that the `metrics` macros report to, returning the
+/// handle that renders them in the Prometheus text format. Without a recorder,
+/// as in tests, recording metrics does nothing.
+pub fn install() -> ClassifyResult<PrometheusHandle> {
+    PrometheusBuilder::new()
+        .set_buckets_for_metric(Matcher::Suffix("_seconds".to_string()), LATENCY_BUCKETS)
+        .and_then(|builder| builder.install_recorder())
+        .map_err(|e| {
+            ClassifyError::ConfigError(format!("Failed to install metrics recorder: {}", e))
+        })
+}
+
+/// Sum of a counter over all its labels, read from metrics rendered in the
+/// Prometheus text format. Zero when the counter wasn't recorded yet.
+pub fn counter_total(rendered: &str, name: &str) -> f64 {
+    rendered
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| {
+            let (series, value) = line.rsplit_once(' ')?;
+            let metric = series.split('{').next()?;
+            if metric == name {
+                value.parse::<f64>().ok()
+            } else {
+                None
+            }
+        })
+        .sum()
+}
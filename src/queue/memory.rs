@@ -0,0 +1,132 @@
+use async_trait::async_trait;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+use uuid::Uuid;
+
+use crate::queue::{ClassificationJob, JobQueue, QueuedJob};
+use crate::ClassifyResult;
+
+/// Jobs kept in the process, lost on a restart. Retries wait for a worker to
+/// look for the next job, dead letters are kept until the process stops.
+pub struct MemoryJobQueue {
+    jobs: Mutex<VecDeque<QueuedJob>>,
+    /// Woken once per pushed job
+    pushed: Notify,
+    /// Failed jobs with the time they are delivered again
+    delayed: Mutex<Vec<(Instant, QueuedJob)>>,
+    dead: Mutex<Vec<(QueuedJob, String)>>,
+    statuses: Mutex<HashMap<Uuid, (ClassificationJob, Instant)>>,
+    status_ttl: Duration,
+}
+
+impl MemoryJobQueue {
+    pub fn new(status_ttl: Duration) -> Self {
+        Self {
+            jobs: Mutex::new(VecDeque::new()),
+            pushed: Notify::new(),
+            delayed: Mutex::new(Vec::new()),
+            dead: Mutex::new(Vec::new()),
+            statuses: Mutex::new(HashMap::new()),
+            status_ttl,
+        }
+    }
+
+    /// Number of jobs waiting for a worker
+    pub fn len(&self) -> usize {
+        self.jobs.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Jobs set aside after running out of attempts, with their last error
+    pub fn dead_letters(&self) -> Vec<(QueuedJob, String)> {
+        self.dead.lock().unwrap().clone()
+    }
+
+    /// Move retries that are due to the queue
+    fn promote_due(&self) {
+        let now = Instant::now();
+        let mut delayed = self.delayed.lock().unwrap();
+        if delayed.is_empty() {
+            return;
+        }
+
+        let mut jobs = self.jobs.lock().unwrap();
+        delayed.retain(|(due, job)| {
+            if *due <= now {
+                jobs.push_back(job.clone());
+            }
+            *due > now
+        });
+    }
+}
+
+#[async_trait]
+impl JobQueue for MemoryJobQueue {
+    async fn push(&self, job: &QueuedJob) -> ClassifyResult<()> {
+        self.jobs.lock().unwrap().push_back(job.clone());
+        self.pushed.notify_one();
+        Ok(())
+    }
+
+    async fn pop(&self, wait: Duration) -> ClassifyResult<Option<QueuedJob>> {
+        let deadline = tokio::time::Instant::now() + wait;
+        loop {
+            self.promote_due();
+            if let Some(job) = self.jobs.lock().unwrap().pop_front() {
+                return Ok(Some(job));
+            }
+            // A push without a waiting worker leaves a permit, so a job pushed
+            // after the check above is still noticed
+            if tokio::time::timeout_at(deadline, self.pushed.notified())
+                .await
+                .is_err()
+            {
+                return Ok(None);
+            }
+        }
+    }
+
+    async fn ack(&self, _job: &QueuedJob) -> ClassifyResult<()> {
+        // Taking a job removed it already
+        Ok(())
+    }
+
+    async fn retry(&self, job: &QueuedJob, delay: Duration) -> ClassifyResult<()> {
+        self.delayed
+            .lock()
+            .unwrap()
+            .push((Instant::now() + delay, job.clone()));
+        Ok(())
+    }
+
+    async fn dead_letter(&self, job: &QueuedJob, error: &str) -> ClassifyResult<()> {
+        self.dead
+            .lock()
+            .unwrap()
+            .push((job.clone(), error.to_string()));
+        Ok(())
+    }
+
+    async fn set_status(&self, job: &ClassificationJob) -> ClassifyResult<()> {
+        let now = Instant::now();
+        let mut statuses = self.statuses.lock().unwrap();
+        statuses.retain(|_, (_, updated)| now.duration_since(*updated) < self.status_ttl);
+        statuses.insert(job.id, (job.clone(), now));
+        Ok(())
+    }
+
+    async fn status(&self, id: &Uuid) -> ClassifyResult<Option<ClassificationJob>> {
+        Ok(self
+            .statuses
+            .lock()
+            .unwrap()
+            .get(id)
+            .filter(|(_, updated)| updated.elapsed() < self.status_ttl)
+            .map(|(job, _)| job.clone()))
+    }
+}
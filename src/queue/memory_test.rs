@@ -0,0 +1,75 @@
+use crate::queue::memory::MemoryJobQueue;
+use crate::queue::{enqueue, JobQueue, JobStatus, QueuedJob};
+use crate::ClassifyResult;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(content: &str) -> QueuedJob {
+        QueuedJob::new(content.to_string(), Vec::new(), HashMap::new(), None)
+    }
+
+    #[tokio::test]
+    async fn test_jobs_are_taken_in_order() -> ClassifyResult<()> {
+        let queue = MemoryJobQueue::new(Duration::from_secs(60));
+        let first = job("first");
+        let second = job("second");
+
+        let status = enqueue(&queue, &first).await?;
+        enqueue(&queue, &second).await?;
+
+        assert_eq!(status.status, JobStatus::Queued);
+        assert_eq!(queue.status(&first.id).await?, Some(status));
+        assert_eq!(queue.len(), 2);
+
+        assert_eq!(queue.pop(Duration::ZERO).await?, Some(first));
+        assert_eq!(queue.pop(Duration::ZERO).await?, Some(second));
+        assert_eq!(queue.pop(Duration::from_millis(10)).await?, None);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_waiting_worker_gets_job_pushed_later() -> ClassifyResult<()> {
+        let queue = Arc::new(MemoryJobQueue::new(Duration::from_secs(60)));
+        let waiting = tokio::spawn({
+            let queue = queue.clone();
+            async move { queue.pop(Duration::from_secs(5)).await }
+        });
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        let job = job("later");
+        queue.push(&job).await?;
+
+        assert_eq!(waiting.await.unwrap()?, Some(job));
+        assert!(queue.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_retry_is_delivered_once_due() -> ClassifyResult<()> {
+        let queue = MemoryJobQueue::new(Duration::from_secs(60));
+        let job = job("retried");
+
+        queue.retry(&job, Duration::from_millis(30)).await?;
+        assert_eq!(queue.pop(Duration::ZERO).await?, None);
+
+        tokio::time::sleep(Duration::from_millis(40)).await;
+        assert_eq!(queue.pop(Duration::ZERO).await?, Some(job));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_status_expires() -> ClassifyResult<()> {
+        let queue = MemoryJobQueue::new(Duration::from_millis(20));
+        let job = job("expiring");
+        enqueue(&queue, &job).await?;
+
+        tokio::time::sleep(Duration::from_millis(40)).await;
+        assert_eq!(queue.status(&job.id).await?, None);
+        Ok(())
+    }
+}
@@ -0,0 +1,219 @@
+pub mod memory;
+#[cfg(feature = "redis")]
+pub mod redis;
+pub mod worker;
+
+#[cfg(test)]
+mod memory_test;
+#[cfg(all(test, feature = "redis"))]
+mod redis_test;
+#[cfg(test)]
+mod worker_test;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::config::{AppConfig, QueueType};
+use crate::{ClassifyError, ClassifyResult};
+
+/// Content waiting to be classified by a worker
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueuedJob {
+    pub id: Uuid,
+    /// Text or URL to classify
+    pub content: String,
+    /// Added to the classifier's tags, e.g. the source of a webhook
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+    /// Tenant the content is stored for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    pub enqueued_at: DateTime<Utc>,
+    /// Number of failed attempts before this delivery
+    #[serde(default)]
+    pub attempts: u32,
+    /// Account the usage of the job is counted towards, as for the request
+    /// that queued it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account: Option<String>,
+    /// Delivery the job was taken as, acknowledged once the job is done
+    #[serde(skip)]
+    pub receipt: Option<String>,
+}
+
+impl QueuedJob {
+    pub fn new(
+        content: String,
+        tags: Vec<String>,
+        metadata: HashMap<String, String>,
+        namespace: Option<String>,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            content,
+            tags,
+            metadata,
+            namespace,
+            enqueued_at: Utc::now(),
+            attempts: 0,
+            account: None,
+            receipt: None,
+        }
+    }
+
+    pub fn with_account(mut self, account: Option<String>) -> Self {
+        self.account = account;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    /// The content was classified and stored
+    Completed,
+    /// Content with the same hash was already stored
+    Duplicate,
+    Failed,
+}
+
+/// Progress of a queued classification
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ClassificationJob {
+    pub id: Uuid,
+    pub status: JobStatus,
+    /// The stored content, once the job is completed or found a duplicate
+    pub content_id: Option<Uuid>,
+    pub error: Option<String>,
+    /// Number of failed attempts, the job is queued again until it runs out
+    #[serde(default)]
+    pub attempts: u32,
+    /// Tenant that queued the job
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    pub enqueued_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+impl ClassificationJob {
+    pub fn queued(job: &QueuedJob) -> Self {
+        Self {
+            id: job.id,
+            status: JobStatus::Queued,
+            content_id: None,
+            error: None,
+            attempts: job.attempts,
+            namespace: job.namespace.clone(),
+            enqueued_at: job.enqueued_at,
+            finished_at: None,
+        }
+    }
+}
+
+/// Classifications waiting for a worker, and the status of recent jobs
+#[async_trait]
+pub trait JobQueue: Send + Sync {
+    /// Add a job to the end of the queue
+    async fn push(&self, job: &QueuedJob) -> ClassifyResult<()>;
+
+    /// Take the next job, waiting up to `wait` for one to arrive. The job is
+    /// delivered again when it isn't acknowledged, retried or set aside.
+    async fn pop(&self, wait: Duration) -> ClassifyResult<Option<QueuedJob>>;
+
+    /// Mark a job taken with [`JobQueue::pop`] as done
+    async fn ack(&self, job: &QueuedJob) -> ClassifyResult<()>;
+
+    /// Deliver a failed job again after `delay`, acknowledging the delivery it
+    /// was taken as
+    async fn retry(&self, job: &QueuedJob, delay: Duration) -> ClassifyResult<()>;
+
+    /// Set a job that keeps failing aside in the dead letters, acknowledging
+    /// the delivery it was taken as
+    async fn dead_letter(&self, job: &QueuedJob, error: &str) -> ClassifyResult<()>;
+
+    /// Record the progress of a job
+    async fn set_status(&self, job: &ClassificationJob) -> ClassifyResult<()>;
+
+    /// Get the progress of a job, `None` once its status has expired
+    async fn status(&self, id: &Uuid) -> ClassifyResult<Option<ClassificationJob>>;
+}
+
+/// Queue a job and return its status. The status is recorded first, so a
+/// worker picking the job up right away doesn't have its progress overwritten.
+pub async fn enqueue(queue: &dyn JobQueue, job: &QueuedJob) -> ClassifyResult<ClassificationJob> {
+    let status = ClassificationJob::queued(job);
+    queue.set_status(&status).await?;
+    queue.push(job).await?;
+    Ok(status)
+}
+
+/// Wait before the next attempt of a job that failed `attempts` times: the
+/// backoff, doubled for every earlier failure
+pub fn retry_delay(backoff: Duration, attempts: u32) -> Duration {
+    backoff.saturating_mul(1 << attempts.saturating_sub(1).min(10))
+}
+
+/// Which parts of the service a process runs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProcessMode {
+    /// The API and the workers in one process
+    All,
+    /// `classify serve`: the API only, classifications are queued for the workers
+    Serve,
+    /// `classify worker`: classifies queued jobs and runs the ingestion workers
+    /// and background jobs, without the API
+    Worker,
+}
+
+impl ProcessMode {
+    /// Mode for the subcommand the binary was started with
+    pub fn from_command(command: Option<&str>) -> Self {
+        match command {
+            Some("serve") => Self::Serve,
+            Some("worker") => Self::Worker,
+            _ => Self::All,
+        }
+    }
+
+    pub fn runs_api(self) -> bool {
+        self != Self::Worker
+    }
+
+    pub fn runs_workers(self) -> bool {
+        self != Self::Serve
+    }
+
+    /// Separate processes only share a queue kept outside of them
+    pub fn check_queue(self, queue_type: QueueType) -> ClassifyResult<()> {
+        if self != Self::All && queue_type == QueueType::Memory {
+            return Err(ClassifyError::ConfigError(
+                "QUEUE_TYPE=memory only reaches a worker in the same process. Set QUEUE_TYPE=redis to run `classify serve` and `classify worker` separately".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Create the job queue configured by `QUEUE_TYPE`
+pub async fn create_job_queue(config: &AppConfig) -> ClassifyResult<Arc<dyn JobQueue>> {
+    match config.queue.queue_type {
+        QueueType::Memory => Ok(Arc::new(memory::MemoryJobQueue::new(
+            config.queue.status_ttl,
+        ))),
+        #[cfg(feature = "redis")]
+        QueueType::Redis => Ok(Arc::new(redis::RedisJobQueue::connect(config).await?)),
+        #[cfg(not(feature = "redis"))]
+        QueueType::Redis => Err(ClassifyError::ConfigError(
+            "The Redis job queue requires building with the redis feature".to_string(),
+        )),
+    }
+}
@@ -0,0 +1,306 @@
+use async_trait::async_trait;
+use redis::streams::{
+    StreamClaimReply, StreamId, StreamMaxlen, StreamReadOptions, StreamReadReply,
+};
+use redis::AsyncCommands;
+use std::time::Duration;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::config::{AppConfig, QueueConfig};
+use crate::queue::{ClassificationJob, JobQueue, QueuedJob};
+use crate::storage::redis::{connect_tag_storage, SharedConnection};
+use crate::{ClassifyError, ClassifyResult};
+
+/// Dead letters kept, older ones are trimmed
+const DEAD_LETTERS_MAX: usize = 10_000;
+
+/// Move retries that are due from the sorted set to the stream, returning how many
+const PROMOTE_SCRIPT: &str = r#"
+    local due = redis.call('ZRANGEBYSCORE', KEYS[1], '-inf', ARGV[1], 'LIMIT', 0, 100)
+    for _, job in ipairs(due) do
+        redis.call('XADD', KEYS[2], '*', 'job', job)
+        redis.call('ZREM', KEYS[1], job)
+    end
+    return #due
+"#;
+
+/// Jobs kept in a Redis stream read by a consumer group, so they survive
+/// restarts and are shared by any number of workers.
+///
+/// A job stays pending in the group until its worker acknowledges it. Jobs a
+/// worker didn't acknowledge within `claim_idle`, because it crashed or was
+/// killed, are claimed by the next worker looking for a job. Retries wait in
+/// a sorted set until they are due, jobs out of attempts go to a dead-letter
+/// stream.
+pub struct RedisJobQueue {
+    connection: SharedConnection,
+    /// Used for the blocking read only, which would hold up every other command
+    blocking: SharedConnection,
+    prefix: String,
+    group: String,
+    consumer: String,
+    claim_idle: Duration,
+    status_ttl: Duration,
+    promote: redis::Script,
+}
+
+impl RedisJobQueue {
+    pub fn with_connections(
+        connection: SharedConnection,
+        blocking: SharedConnection,
+        prefix: Option<&str>,
+        config: &QueueConfig,
+    ) -> Self {
+        crate::storage::connections::register("redis", &connection);
+        crate::storage::connections::register("redis", &blocking);
+        Self {
+            connection,
+            blocking,
+            prefix: prefix.unwrap_or("classify:").to_string(),
+            group: config.consumer_group.clone(),
+            consumer: config.consumer_name.clone(),
+            claim_idle: config.claim_idle,
+            status_ttl: config.status_ttl,
+            promote: redis::Script::new(PROMOTE_SCRIPT),
+        }
+    }
+
+    /// Connect to the Redis server of the tag storage and create the consumer
+    /// group when it doesn't exist yet
+    pub async fn connect(config: &AppConfig) -> ClassifyResult<Self> {
+        let tag_storage = &config.tag_storage;
+        let connection = connect_tag_storage(tag_storage).await?;
+        let blocking = connect_tag_storage(tag_storage).await?;
+
+        let queue = Self::with_connections(
+            connection,
+            blocking,
+            tag_storage.redis_prefix.as_deref(),
+            &config.queue,
+        );
+        queue.create_group().await?;
+        info!(
+            "Reading jobs from {} as {} in group {}",
+            queue.stream_key(),
+            queue.consumer,
+            queue.group
+        );
+        Ok(queue)
+    }
+
+    pub(crate) async fn create_group(&self) -> ClassifyResult<()> {
+        let mut conn = self.connection.lock().await;
+        let created: redis::RedisResult<()> = conn
+            .xgroup_create_mkstream(self.stream_key(), &self.group, "0")
+            .await;
+        match created {
+            Ok(()) => Ok(()),
+            Err(e) if e.code() == Some("BUSYGROUP") => Ok(()),
+            Err(e) => Err(ClassifyError::StorageError(format!(
+                "Failed to create consumer group {}: {}",
+                self.group, e
+            ))),
+        }
+    }
+
+    fn stream_key(&self) -> String {
+        format!("{}jobs", self.prefix)
+    }
+
+    fn retry_key(&self) -> String {
+        format!("{}jobs:retry", self.prefix)
+    }
+
+    fn dead_key(&self) -> String {
+        format!("{}jobs:dead", self.prefix)
+    }
+
+    fn status_key(&self, id: &Uuid) -> String {
+        format!("{}job:{}", self.prefix, id)
+    }
+
+    /// Add a dead letter to the pipeline, trimming the oldest ones
+    fn add_dead_letter(&self, pipe: &mut redis::Pipeline, payload: String, error: String) {
+        pipe.xadd_maxlen(
+            self.dead_key(),
+            StreamMaxlen::Approx(DEAD_LETTERS_MAX),
+            "*",
+            &[
+                ("job", payload),
+                ("error", error),
+                ("failed_at", chrono::Utc::now().to_rfc3339()),
+            ],
+        );
+    }
+
+    /// Acknowledge a delivery and drop it from the stream, in the pipeline of
+    /// the step that finishes it
+    fn finish(&self, pipe: &mut redis::Pipeline, receipt: Option<&str>) {
+        if let Some(receipt) = receipt {
+            pipe.xack(self.stream_key(), &self.group, &[receipt])
+                .xdel(self.stream_key(), &[receipt]);
+        }
+    }
+
+    async fn run(&self, pipe: &redis::Pipeline, action: &str) -> ClassifyResult<()> {
+        let mut conn = self.connection.lock().await;
+        pipe.query_async::<_, ()>(&mut *conn)
+            .await
+            .map_err(|e| ClassifyError::StorageError(format!("Failed to {}: {}", action, e)))
+    }
+
+    /// Read the job of a stream entry. Entries that can't be read are set
+    /// aside, delivering them again would fail the same way.
+    async fn job(&self, entry: StreamId) -> ClassifyResult<Option<QueuedJob>> {
+        let payload: Option<String> = entry.get("job");
+        let error = match payload.as_deref().map(serde_json::from_str::<QueuedJob>) {
+            Some(Ok(mut job)) => {
+                job.receipt = Some(entry.id);
+                return Ok(Some(job));
+            }
+            Some(Err(e)) => format!("Unreadable job: {}", e),
+            None => "Entry without a job".to_string(),
+        };
+
+        warn!("Setting aside job entry {}: {}", entry.id, error);
+        let mut pipe = redis::pipe();
+        pipe.atomic();
+        self.add_dead_letter(&mut pipe, payload.unwrap_or_default(), error);
+        self.finish(&mut pipe, Some(&entry.id));
+        self.run(&pipe, "set job aside").await?;
+        Ok(None)
+    }
+
+    /// Take over a job another worker took but didn't finish in time
+    async fn claim_abandoned(&self) -> ClassifyResult<Option<StreamId>> {
+        let mut conn = self.connection.lock().await;
+        let reply: redis::Value = redis::cmd("XAUTOCLAIM")
+            .arg(self.stream_key())
+            .arg(&self.group)
+            .arg(&self.consumer)
+            .arg(self.claim_idle.as_millis() as u64)
+            .arg("0-0")
+            .arg("COUNT")
+            .arg(1)
+            .query_async(&mut *conn)
+            .await
+            .map_err(|e| ClassifyError::StorageError(format!("Failed to claim jobs: {}", e)))?;
+
+        // The reply is the next cursor, the claimed entries and, from Redis 7,
+        // the ids of entries that were deleted meanwhile
+        let redis::Value::Bulk(items) = reply else {
+            return Ok(None);
+        };
+        let Some(entries) = items.get(1) else {
+            return Ok(None);
+        };
+        let claimed: StreamClaimReply = redis::from_redis_value(entries).map_err(|e| {
+            ClassifyError::StorageError(format!("Failed to read claimed jobs: {}", e))
+        })?;
+        Ok(claimed.ids.into_iter().next())
+    }
+}
+
+#[async_trait]
+impl JobQueue for RedisJobQueue {
+    async fn push(&self, job: &QueuedJob) -> ClassifyResult<()> {
+        let payload = serde_json::to_string(job)?;
+        let mut conn = self.connection.lock().await;
+        conn.xadd::<_, _, _, _, ()>(self.stream_key(), "*", &[("job", payload)])
+            .await
+            .map_err(|e| ClassifyError::StorageError(format!("Failed to queue job: {}", e)))
+    }
+
+    async fn pop(&self, wait: Duration) -> ClassifyResult<Option<QueuedJob>> {
+        {
+            let mut conn = self.connection.lock().await;
+            self.promote
+                .key(self.retry_key())
+                .key(self.stream_key())
+                .arg(chrono::Utc::now().timestamp_millis())
+                .invoke_async::<_, usize>(&mut *conn)
+                .await
+                .map_err(|e| {
+                    ClassifyError::StorageError(format!("Failed to requeue retries: {}", e))
+                })?;
+        }
+
+        if let Some(entry) = self.claim_abandoned().await? {
+            info!("Claimed abandoned job entry {}", entry.id);
+            return self.job(entry).await;
+        }
+
+        let entry = {
+            let mut conn = self.blocking.lock().await;
+            let options = StreamReadOptions::default()
+                .group(&self.group, &self.consumer)
+                .count(1)
+                .block(wait.as_millis().max(1) as usize);
+            let reply: Option<StreamReadReply> = conn
+                .xread_options(&[self.stream_key()], &[">"], &options)
+                .await
+                .map_err(|e| ClassifyError::StorageError(format!("Failed to take job: {}", e)))?;
+
+            reply
+                .and_then(|reply| reply.keys.into_iter().next())
+                .and_then(|key| key.ids.into_iter().next())
+        };
+
+        match entry {
+            Some(entry) => self.job(entry).await,
+            None => Ok(None),
+        }
+    }
+
+    async fn ack(&self, job: &QueuedJob) -> ClassifyResult<()> {
+        let mut pipe = redis::pipe();
+        pipe.atomic();
+        self.finish(&mut pipe, job.receipt.as_deref());
+        self.run(&pipe, "acknowledge job").await
+    }
+
+    async fn retry(&self, job: &QueuedJob, delay: Duration) -> ClassifyResult<()> {
+        let payload = serde_json::to_string(job)?;
+        let due = chrono::Utc::now().timestamp_millis() + delay.as_millis() as i64;
+
+        let mut pipe = redis::pipe();
+        pipe.atomic().zadd(self.retry_key(), payload, due);
+        self.finish(&mut pipe, job.receipt.as_deref());
+        self.run(&pipe, "schedule retry").await
+    }
+
+    async fn dead_letter(&self, job: &QueuedJob, error: &str) -> ClassifyResult<()> {
+        let payload = serde_json::to_string(job)?;
+
+        let mut pipe = redis::pipe();
+        pipe.atomic();
+        self.add_dead_letter(&mut pipe, payload, error.to_string());
+        self.finish(&mut pipe, job.receipt.as_deref());
+        self.run(&pipe, "set job aside").await
+    }
+
+    async fn set_status(&self, job: &ClassificationJob) -> ClassifyResult<()> {
+        let status = serde_json::to_string(job)?;
+        let mut conn = self.connection.lock().await;
+        conn.set_ex::<_, _, ()>(
+            self.status_key(&job.id),
+            status,
+            self.status_ttl.as_secs().max(1) as usize,
+        )
+        .await
+        .map_err(|e| ClassifyError::StorageError(format!("Failed to store job status: {}", e)))
+    }
+
+    async fn status(&self, id: &Uuid) -> ClassifyResult<Option<ClassificationJob>> {
+        let mut conn = self.connection.lock().await;
+        let status: Option<String> = conn
+            .get(self.status_key(id))
+            .await
+            .map_err(|e| ClassifyError::StorageError(format!("Failed to get job status: {}", e)))?;
+
+        status
+            .map(|status| serde_json::from_str(&status).map_err(ClassifyError::from))
+            .transpose()
+    }
+}
@@ -0,0 +1,98 @@
+use crate::config::{QueueConfig, QueueType};
+use crate::queue::redis::RedisJobQueue;
+use crate::queue::{JobQueue, QueuedJob};
+use crate::storage::redis::{connect, RedisConnectOptions};
+use crate::ClassifyResult;
+use std::collections::HashMap;
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    async fn queue(prefix: &str, consumer: &str) -> ClassifyResult<RedisJobQueue> {
+        let redis_url =
+            env::var("TEST_REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string());
+        let options = RedisConnectOptions {
+            password: env::var("TEST_REDIS_PASSWORD").ok(),
+            ..Default::default()
+        };
+        let config = QueueConfig {
+            queue_type: QueueType::Redis,
+            concurrency: 1,
+            status_ttl: Duration::from_secs(60),
+            max_attempts: 3,
+            retry_backoff: Duration::from_secs(1),
+            // Anything not acknowledged can be claimed right away
+            claim_idle: Duration::ZERO,
+            consumer_group: "classify".to_string(),
+            consumer_name: consumer.to_string(),
+        };
+
+        let queue = RedisJobQueue::with_connections(
+            Arc::new(Mutex::new(connect(&redis_url, &options).await?)),
+            Arc::new(Mutex::new(connect(&redis_url, &options).await?)),
+            Some(prefix),
+            &config,
+        );
+        queue.create_group().await?;
+        Ok(queue)
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_redis_stream_queue() -> ClassifyResult<()> {
+        // Requires a Redis server of version 6.2 or later, for XAUTOCLAIM
+        let prefix = format!("test:{}:", Uuid::new_v4());
+        let first = queue(&prefix, "first").await?;
+        let second = queue(&prefix, "second").await?;
+
+        let job = QueuedJob::new("Queued".to_string(), Vec::new(), HashMap::new(), None);
+        first.push(&job).await?;
+
+        // Taken but not acknowledged, as by a worker that crashed
+        let taken = first.pop(Duration::from_millis(100)).await?.unwrap();
+        assert_eq!(taken.id, job.id);
+
+        let claimed = second.pop(Duration::from_millis(100)).await?.unwrap();
+        assert_eq!(claimed.id, job.id);
+
+        let retry = QueuedJob {
+            attempts: 1,
+            ..claimed
+        };
+        second.retry(&retry, Duration::ZERO).await?;
+        let retried = first.pop(Duration::from_millis(100)).await?.unwrap();
+        assert_eq!(retried.attempts, 1);
+
+        first.dead_letter(&retried, "still failing").await?;
+        assert!(second.pop(Duration::from_millis(100)).await?.is_none());
+
+        let redis_url =
+            env::var("TEST_REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string());
+        let options = RedisConnectOptions {
+            password: env::var("TEST_REDIS_PASSWORD").ok(),
+            ..Default::default()
+        };
+        let mut conn = connect(&redis_url, &options).await?;
+        let dead: usize = redis::cmd("XLEN")
+            .arg(format!("{}jobs:dead", prefix))
+            .query_async(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(dead, 1);
+
+        redis::cmd("DEL")
+            .arg(format!("{}jobs", prefix))
+            .arg(format!("{}jobs:retry", prefix))
+            .arg(format!("{}jobs:dead", prefix))
+            .query_async::<_, ()>(&mut conn)
+            .await
+            .unwrap();
+        Ok(())
+    }
+}
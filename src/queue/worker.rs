@@ -0,0 +1,165 @@
+use chrono::Utc;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+
+use crate::api::AppState;
+use crate::config::QueueConfig;
+use crate::ingest::{ingest_with_metadata, Ingested};
+use crate::queue::{retry_delay, ClassificationJob, JobQueue, JobStatus, QueuedJob};
+use crate::shutdown;
+
+/// Longest a worker waits for a job before checking whether the process stops
+const POLL_WAIT: Duration = Duration::from_secs(1);
+
+/// Pause after the queue failed, so a down queue isn't hammered
+const ERROR_BACKOFF: Duration = Duration::from_secs(5);
+
+/// Classify a queued job for its tenant, counting its usage towards the
+/// account that queued it, and return its final status
+pub async fn run_job(state: &AppState, job: QueuedJob) -> ClassificationJob {
+    let mut status = ClassificationJob::queued(&job);
+    let state = state.for_namespace(job.namespace.as_deref());
+
+    // The request that queued the job was answered before its usage was known
+    let ingest = ingest_with_metadata(&state, job.content, &job.tags, job.metadata);
+    let result = match (state.usage.as_deref(), job.account.as_deref()) {
+        (Some(usage), Some(account)) => usage.count(account, ingest).await,
+        _ => ingest.await,
+    };
+
+    match result {
+        Ok(Ingested::Created(content)) => {
+            status.status = JobStatus::Completed;
+            status.content_id = Some(content.id);
+        }
+        Ok(Ingested::Duplicate(content)) => {
+            status.status = JobStatus::Duplicate;
+            status.content_id = Some(content.id);
+        }
+        Err(e) => {
+            warn!(
+                "Queued classification {} failed (attempt {}): {}",
+                job.id,
+                job.attempts + 1,
+                e
+            );
+            status.status = JobStatus::Failed;
+            status.error = Some(e.to_string());
+            status.attempts += 1;
+        }
+    }
+
+    status.finished_at = Some(Utc::now());
+    status
+}
+
+/// Finish the delivery of a job: acknowledge it when it is done, or retry it
+/// with backoff until it runs out of attempts and is set aside. Returns the
+/// status to record.
+pub async fn settle(
+    queue: &dyn JobQueue,
+    job: QueuedJob,
+    mut status: ClassificationJob,
+    config: &QueueConfig,
+) -> ClassificationJob {
+    let settled = match status.status {
+        JobStatus::Failed if status.attempts < config.max_attempts => {
+            let delay = retry_delay(config.retry_backoff, status.attempts);
+            info!(
+                "Retrying job {} in {}s, attempt {} of {}",
+                job.id,
+                delay.as_secs(),
+                status.attempts + 1,
+                config.max_attempts
+            );
+            // Queued again, so the status doesn't look final to clients
+            status.status = JobStatus::Queued;
+            status.finished_at = None;
+            let retry = QueuedJob {
+                attempts: status.attempts,
+                ..job.clone()
+            };
+            queue.retry(&retry, delay).await
+        }
+        JobStatus::Failed => {
+            error!(
+                "Giving up on job {} after {} attempts",
+                job.id, status.attempts
+            );
+            let error = status.error.clone().unwrap_or_default();
+            queue.dead_letter(&job, &error).await
+        }
+        _ => queue.ack(&job).await,
+    };
+
+    // The job is delivered again when its delivery wasn't finished
+    if let Err(e) = settled {
+        warn!("Failed to finish the delivery of job {}: {}", job.id, e);
+    }
+    status
+}
+
+/// Take the next job from the queue, if one arrives in time, and classify it
+async fn work_once(state: &AppState, queue: &dyn JobQueue, config: &QueueConfig) {
+    // Jobs write to storage, they wait in the queue during maintenance
+    if state.maintenance.is_enabled() {
+        tokio::time::sleep(POLL_WAIT).await;
+        return;
+    }
+
+    let job = match queue.pop(POLL_WAIT).await {
+        Ok(Some(job)) => job,
+        Ok(None) => return,
+        Err(e) => {
+            error!("Failed to take a job from the queue: {}", e);
+            tokio::time::sleep(ERROR_BACKOFF).await;
+            return;
+        }
+    };
+
+    info!("Classifying queued job {}", job.id);
+    let mut running = ClassificationJob::queued(&job);
+    running.status = JobStatus::Running;
+    if let Err(e) = queue.set_status(&running).await {
+        warn!("Failed to update the status of job {}: {}", job.id, e);
+    }
+
+    let status = run_job(state, job.clone()).await;
+    let status = settle(queue, job, status, config).await;
+    let label = match status.status {
+        JobStatus::Completed => "completed",
+        JobStatus::Duplicate => "duplicate",
+        JobStatus::Queued => "retried",
+        _ => "failed",
+    };
+    metrics::counter!("queue_jobs_total", "status" => label).increment(1);
+
+    if let Err(e) = queue.set_status(&status).await {
+        warn!("Failed to update the status of job {}: {}", status.id, e);
+    }
+}
+
+/// Start the configured number of workers classifying queued jobs until the
+/// process stops. A job that was taken is finished before its worker stops.
+pub fn spawn_workers(
+    state: Arc<AppState>,
+    queue: Arc<dyn JobQueue>,
+    config: &QueueConfig,
+) -> Vec<JoinHandle<()>> {
+    info!("Starting {} queue workers", config.concurrency);
+
+    (0..config.concurrency)
+        .map(|_| {
+            let state = state.clone();
+            let queue = queue.clone();
+            let config = config.clone();
+            tokio::spawn(async move {
+                while !shutdown::is_shutting_down() {
+                    work_once(&state, queue.as_ref(), &config).await;
+                }
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,189 @@
+use crate::api::AppState;
+use crate::classifier::Classifier;
+use crate::config::QueueConfig;
+use crate::queue::memory::MemoryJobQueue;
+use crate::queue::worker::{run_job, settle};
+use crate::queue::{retry_delay, JobQueue, JobStatus, ProcessMode, QueuedJob};
+use crate::storage::content::memory::MemoryContentStorage;
+use crate::storage::tag::memory::MemoryTagStorage;
+use crate::{ClassifyError, ClassifyResult};
+use mockall::mock;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+mock! {
+    pub ClassifierMock {}
+
+    #[async_trait::async_trait]
+    impl Classifier for ClassifierMock {
+        async fn classify(&self, content: &str) -> ClassifyResult<Vec<String>>;
+        async fn classify_url(&self, url: &str) -> ClassifyResult<Vec<String>>;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::QueueType;
+
+    fn state(classifier: MockClassifierMock) -> AppState {
+        AppState::new(
+            Arc::new(classifier),
+            Arc::new(MemoryContentStorage::new()),
+            Arc::new(MemoryTagStorage::new()),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_job_is_classified_for_its_tenant() -> ClassifyResult<()> {
+        let mut classifier = MockClassifierMock::new();
+        classifier
+            .expect_classify()
+            .times(1)
+            .returning(|_| Ok(vec!["queued".to_string()]));
+        let state = state(classifier);
+
+        let job = QueuedJob::new(
+            "Queued text".to_string(),
+            vec!["source:test".to_string()],
+            HashMap::new(),
+            Some("acme".to_string()),
+        );
+        let status = run_job(&state, job.clone()).await;
+
+        assert_eq!(status.status, JobStatus::Completed);
+        assert_eq!(status.namespace.as_deref(), Some("acme"));
+        assert!(status.finished_at.is_some());
+
+        let acme = state.for_namespace(Some("acme"));
+        let content_id = status.content_id.unwrap().to_string();
+        let stored = acme.content_storage.get(&content_id).await?.unwrap();
+        assert_eq!(stored.tags, vec!["queued", "source:test"]);
+
+        // The same text again is found as a duplicate, without classifying it
+        let status = run_job(&state, job).await;
+        assert_eq!(status.status, JobStatus::Duplicate);
+        assert_eq!(status.content_id.unwrap().to_string(), content_id);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_job_usage_counts_towards_its_account() {
+        use crate::storage::usage::memory::MemoryUsageStorage;
+        use crate::usage::{current_month, UsageTracker};
+
+        let mut classifier = MockClassifierMock::new();
+        classifier
+            .expect_classify()
+            .returning(|_| Ok(vec!["queued".to_string()]));
+        let state = state(classifier).with_usage(Some(UsageTracker::new(
+            Arc::new(MemoryUsageStorage::new()),
+            Default::default(),
+            HashMap::new(),
+        )));
+
+        let job = QueuedJob::new("Queued text".to_string(), Vec::new(), HashMap::new(), None)
+            .with_account(Some("key:1".to_string()));
+        let status = run_job(&state, job).await;
+        assert_eq!(status.status, JobStatus::Completed);
+
+        let usage = state
+            .usage
+            .as_ref()
+            .unwrap()
+            .report(&current_month(), Some("key:1"))
+            .await
+            .unwrap();
+        assert_eq!(usage[0].usage.classifications, 1);
+        assert_eq!(usage[0].usage.stored_bytes, "Queued text".len() as u64);
+    }
+
+    #[tokio::test]
+    async fn test_failed_job_records_the_error() {
+        let mut classifier = MockClassifierMock::new();
+        classifier
+            .expect_classify()
+            .returning(|_| Err(ClassifyError::ClassificationError("down".to_string())));
+        let state = state(classifier);
+
+        let job = QueuedJob::new("Text".to_string(), Vec::new(), HashMap::new(), None);
+        let status = run_job(&state, job).await;
+
+        assert_eq!(status.status, JobStatus::Failed);
+        assert_eq!(status.attempts, 1);
+        assert!(status.error.unwrap().contains("down"));
+        assert!(status.content_id.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_failing_job_is_retried_then_set_aside() -> ClassifyResult<()> {
+        let mut classifier = MockClassifierMock::new();
+        classifier
+            .expect_classify()
+            .times(2)
+            .returning(|_| Err(ClassifyError::ClassificationError("down".to_string())));
+        let state = state(classifier);
+        let queue = MemoryJobQueue::new(Duration::from_secs(60));
+        let config = QueueConfig {
+            queue_type: QueueType::Memory,
+            concurrency: 1,
+            status_ttl: Duration::from_secs(60),
+            max_attempts: 2,
+            retry_backoff: Duration::ZERO,
+            claim_idle: Duration::from_secs(60),
+            consumer_group: "classify".to_string(),
+            consumer_name: "test".to_string(),
+        };
+
+        queue
+            .push(&QueuedJob::new(
+                "Text".to_string(),
+                Vec::new(),
+                HashMap::new(),
+                None,
+            ))
+            .await?;
+
+        let job = queue.pop(Duration::ZERO).await?.unwrap();
+        let status = settle(&queue, job.clone(), run_job(&state, job).await, &config).await;
+        assert_eq!(status.status, JobStatus::Queued);
+        assert_eq!(status.attempts, 1);
+        assert!(queue.dead_letters().is_empty());
+
+        let job = queue.pop(Duration::ZERO).await?.unwrap();
+        assert_eq!(job.attempts, 1);
+        let status = settle(&queue, job.clone(), run_job(&state, job).await, &config).await;
+        assert_eq!(status.status, JobStatus::Failed);
+        assert_eq!(status.attempts, 2);
+
+        let dead = queue.dead_letters();
+        assert_eq!(dead.len(), 1);
+        assert!(dead[0].1.contains("down"));
+        assert_eq!(queue.pop(Duration::ZERO).await?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_retry_delay_doubles() {
+        let backoff = Duration::from_secs(10);
+        assert_eq!(retry_delay(backoff, 1), Duration::from_secs(10));
+        assert_eq!(retry_delay(backoff, 2), Duration::from_secs(20));
+        assert_eq!(retry_delay(backoff, 4), Duration::from_secs(80));
+    }
+
+    #[test]
+    fn test_separate_processes_need_a_shared_queue() {
+        assert_eq!(ProcessMode::from_command(Some("serve")), ProcessMode::Serve);
+        assert_eq!(
+            ProcessMode::from_command(Some("worker")),
+            ProcessMode::Worker
+        );
+        assert_eq!(ProcessMode::from_command(None), ProcessMode::All);
+
+        assert!(ProcessMode::All.check_queue(QueueType::Memory).is_ok());
+        assert!(ProcessMode::Serve.check_queue(QueueType::Memory).is_err());
+        assert!(ProcessMode::Worker.check_queue(QueueType::Memory).is_err());
+        assert!(ProcessMode::Worker.check_queue(QueueType::Redis).is_ok());
+    }
+}
@@ -0,0 +1,121 @@
+use futures::future::BoxFuture;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, OnceLock, RwLock};
+
+use crate::classifier::Classifier;
+use crate::config::{ClassifierConfig, StorageConfig, TagStorageConfig};
+use crate::storage::{ContentStorage, TagStorage};
+use crate::{ClassifyError, ClassifyResult};
+
+type Factory<C, T> = Arc<dyn Fn(C) -> BoxFuture<'static, ClassifyResult<Arc<T>>> + Send + Sync>;
+
+/// Factories of custom backends by name
+struct Registry<C, T: ?Sized> {
+    factories: RwLock<HashMap<String, Factory<C, T>>>,
+}
+
+impl<C, T: ?Sized> Registry<C, T> {
+    fn new() -> Self {
+        Self {
+            factories: RwLock::new(HashMap::new()),
+        }
+    }
+
+    fn register<F, Fut>(&self, name: &str, factory: F)
+    where
+        F: Fn(C) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ClassifyResult<Arc<T>>> + Send + 'static,
+    {
+        let factory: Factory<C, T> = Arc::new(move |config| Box::pin(factory(config)));
+        self.factories
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(name.to_lowercase(), factory);
+    }
+
+    async fn create(&self, kind: &str, name: &str, config: C) -> ClassifyResult<Arc<T>> {
+        // Clone the factory so the lock isn't held across the await
+        let factory = self
+            .factories
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&name.to_lowercase())
+            .cloned()
+            .ok_or_else(|| ClassifyError::ConfigError(format!("Unknown {}: {}", kind, name)))?;
+
+        factory(config).await
+    }
+}
+
+fn content_storages() -> &'static Registry<StorageConfig, dyn ContentStorage> {
+    static REGISTRY: OnceLock<Registry<StorageConfig, dyn ContentStorage>> = OnceLock::new();
+    REGISTRY.get_or_init(Registry::new)
+}
+
+fn tag_storages() -> &'static Registry<TagStorageConfig, dyn TagStorage> {
+    static REGISTRY: OnceLock<Registry<TagStorageConfig, dyn TagStorage>> = OnceLock::new();
+    REGISTRY.get_or_init(Registry::new)
+}
+
+fn classifiers() -> &'static Registry<ClassifierConfig, dyn Classifier> {
+    static REGISTRY: OnceLock<Registry<ClassifierConfig, dyn Classifier>> = OnceLock::new();
+    REGISTRY.get_or_init(Registry::new)
+}
+
+/// Register a content storage under a name, selected with `CONTENT_STORAGE_TYPE=<name>`.
+///
+/// Names are case insensitive. Built-in names such as `redis` always resolve to
+/// the built-in backend, and registering a name twice replaces the first factory.
+pub fn register_content_storage<F, Fut>(name: &str, factory: F)
+where
+    F: Fn(StorageConfig) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = ClassifyResult<Arc<dyn ContentStorage>>> + Send + 'static,
+{
+    content_storages().register(name, factory);
+}
+
+/// Register a tag storage under a name, selected with `TAG_STORAGE_TYPE=<name>`
+pub fn register_tag_storage<F, Fut>(name: &str, factory: F)
+where
+    F: Fn(TagStorageConfig) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = ClassifyResult<Arc<dyn TagStorage>>> + Send + 'static,
+{
+    tag_storages().register(name, factory);
+}
+
+/// Register a classifier under a name, selected with `CLASSIFIER_TYPE=<name>`
+pub fn register_classifier<F, Fut>(name: &str, factory: F)
+where
+    F: Fn(ClassifierConfig) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = ClassifyResult<Arc<dyn Classifier>>> + Send + 'static,
+{
+    classifiers().register(name, factory);
+}
+
+pub(crate) async fn create_content_storage(
+    name: &str,
+    config: &StorageConfig,
+) -> ClassifyResult<Arc<dyn ContentStorage>> {
+    content_storages()
+        .create("content storage type", name, config.clone())
+        .await
+}
+
+pub(crate) async fn create_tag_storage(
+    name: &str,
+    config: &TagStorageConfig,
+) -> ClassifyResult<Arc<dyn TagStorage>> {
+    tag_storages()
+        .create("tag storage type", name, config.clone())
+        .await
+}
+
+pub(crate) async fn create_classifier(
+    name: &str,
+    config: &ClassifierConfig,
+) -> ClassifyResult<Arc<dyn Classifier>> {
+    classifiers()
+        .create("classifier type", name, config.clone())
+        .await
+}
@@ -0,0 +1,72 @@
+use crate::classifier::{create_classifier, Classifier};
+use crate::config::{ClassifierConfig, ClassifierType};
+use crate::registry::register_classifier;
+use crate::{ClassifyError, ClassifyResult};
+use async_trait::async_trait;
+use std::sync::Arc;
+
+struct FixedClassifier;
+
+#[async_trait]
+impl Classifier for FixedClassifier {
+    async fn classify(&self, _content: &str) -> ClassifyResult<Vec<String>> {
+        Ok(vec!["fixed".to_string()])
+    }
+
+    async fn classify_url(&self, _url: &str) -> ClassifyResult<Vec<String>> {
+        Ok(vec!["fixed".to_string()])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn classifier_config(classifier_type: ClassifierType) -> ClassifierConfig {
+        ClassifierConfig {
+            classifier_type,
+            anthropic_api_key: None,
+            openai_api_key: None,
+            openai_model: None,
+            openai_base_url: None,
+            max_prompt_length: 1000,
+            max_tags: 5,
+            system_prompt_template: None,
+            retry: Default::default(),
+            ensemble_classifiers: Vec::new(),
+            ensemble_min_votes: 2,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_registered_classifier_is_resolved_by_name() -> ClassifyResult<()> {
+        register_classifier("fixed", |_config| async {
+            Ok(Arc::new(FixedClassifier) as Arc<dyn Classifier>)
+        });
+
+        let classifier_type: ClassifierType = "Fixed".parse().unwrap();
+        assert_eq!(classifier_type, ClassifierType::Custom("fixed".to_string()));
+
+        let classifier = create_classifier(
+            &classifier_type,
+            &classifier_config(classifier_type.clone()),
+        )
+        .await?;
+        assert_eq!(classifier.classify("anything").await?, vec!["fixed"]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_unregistered_classifier_is_a_config_error() {
+        let classifier_type = ClassifierType::Custom("not-registered".to_string());
+
+        let result = create_classifier(
+            &classifier_type,
+            &classifier_config(classifier_type.clone()),
+        )
+        .await;
+
+        assert!(matches!(result, Err(ClassifyError::ConfigError(_))));
+    }
+}
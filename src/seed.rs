@@ -0,0 +1,133 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use tracing::info;
+
+use crate::api::AppState;
+use crate::classifier::Classifier;
+use crate::config::AppConfig;
+use crate::ingest::{ingest_with_metadata, Ingested};
+use crate::storage::{create_content_storage, create_shared_storage, create_tag_storage};
+use crate::{ClassifyResult, Content};
+
+/// Sample content bundled with the binary
+const SAMPLE_CONTENT: &str = include_str!("../seed/sample.json");
+
+/// Metadata key and value marking seeded content, so it can be found and removed
+pub const SEED_METADATA: (&str, &str) = ("source", "classify-seed");
+
+/// A bundled content item with the tags it is stored with
+#[derive(Debug, Clone, Deserialize)]
+pub struct SampleItem {
+    pub content: String,
+    pub tags: Vec<String>,
+}
+
+/// The bundled sample content
+pub fn sample_items() -> ClassifyResult<Vec<SampleItem>> {
+    Ok(serde_json::from_str(SAMPLE_CONTENT)?)
+}
+
+/// Classifier answering with fixed tags per text, so the sample content is
+/// tagged without calling a provider. Other texts get no tags.
+pub struct StaticClassifier {
+    tags: HashMap<String, Vec<String>>,
+}
+
+impl StaticClassifier {
+    pub fn new(items: &[SampleItem]) -> Self {
+        Self {
+            tags: items
+                .iter()
+                .map(|item| (item.content.clone(), item.tags.clone()))
+                .collect(),
+        }
+    }
+}
+
+#[async_trait]
+impl Classifier for StaticClassifier {
+    async fn classify(&self, content: &str) -> ClassifyResult<Vec<String>> {
+        Ok(self.tags.get(content).cloned().unwrap_or_default())
+    }
+
+    async fn classify_url(&self, url: &str) -> ClassifyResult<Vec<String>> {
+        self.classify(url).await
+    }
+}
+
+/// What `classify seed` stored
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SeedReport {
+    pub created: usize,
+    /// Items stored by an earlier run
+    pub duplicates: usize,
+}
+
+impl fmt::Display for SeedReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Seeded {} sample items, {} were already stored",
+            self.created, self.duplicates
+        )
+    }
+}
+
+/// Store the sample content through the ingestion pipeline of `state`, so it
+/// is deduplicated, normalized and tagged like classified content. Running it
+/// again only finds duplicates.
+pub async fn seed(state: &AppState, items: &[SampleItem]) -> ClassifyResult<SeedReport> {
+    let mut report = SeedReport::default();
+    for item in items {
+        let metadata = HashMap::from([(SEED_METADATA.0.to_string(), SEED_METADATA.1.to_string())]);
+        match ingest_with_metadata(state, item.content.clone(), &[], metadata).await? {
+            Ingested::Created(_) => report.created += 1,
+            Ingested::Duplicate(_) => report.duplicates += 1,
+        }
+    }
+
+    info!(
+        "Seeded {} sample items, {} duplicates",
+        report.created, report.duplicates
+    );
+    Ok(report)
+}
+
+/// Load the sample content into the configured content and tag storage,
+/// tagged by a [`StaticClassifier`]
+pub async fn run() -> ClassifyResult<SeedReport> {
+    let config = AppConfig::load().await?;
+    // Hashes must match the server's for duplicates to be found
+    Content::set_hash_algorithm(config.ingest.hash_algorithm);
+    Content::set_hash_normalization(config.ingest.hash_normalization);
+
+    let (content_storage, tag_storage, atomic_storage) = match &config.storage_backend {
+        Some(backend) => {
+            let storage = create_shared_storage(backend, config).await?;
+            (
+                storage.content_storage,
+                storage.tag_storage,
+                storage.atomic_storage,
+            )
+        }
+        None => (
+            create_content_storage(&config.storage.storage_type, &config.storage).await?,
+            create_tag_storage(&config.tag_storage.tag_storage_type, &config.tag_storage).await?,
+            None,
+        ),
+    };
+
+    let items = sample_items()?;
+    let state = AppState::new(
+        Arc::new(StaticClassifier::new(&items)),
+        content_storage,
+        tag_storage,
+    )
+    .with_atomic_storage(atomic_storage)
+    .with_ingest_config(config.ingest.clone());
+
+    seed(&state, &items).await
+}
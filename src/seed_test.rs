@@ -0,0 +1,61 @@
+use crate::api::AppState;
+use crate::seed::{sample_items, seed, SeedReport, StaticClassifier, SEED_METADATA};
+use crate::storage::content::memory::MemoryContentStorage;
+use crate::storage::tag::memory::MemoryTagStorage;
+use crate::storage::{ContentStorage, TagStorage};
+use std::sync::Arc;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_seed_stores_sample_content_once() {
+        let items = sample_items().unwrap();
+        assert!(!items.is_empty());
+
+        let content_storage = Arc::new(MemoryContentStorage::new());
+        let tag_storage = Arc::new(MemoryTagStorage::new());
+        let state = AppState::new(
+            Arc::new(StaticClassifier::new(&items)),
+            content_storage.clone(),
+            tag_storage.clone(),
+        );
+
+        let report = seed(&state, &items).await.unwrap();
+        assert_eq!(
+            report,
+            SeedReport {
+                created: items.len(),
+                duplicates: 0
+            }
+        );
+
+        // Every item is tagged with its bundled tags, none fell through to the classifier
+        let stored = content_storage.list().await.unwrap();
+        assert_eq!(stored.len(), items.len());
+        for content in &stored {
+            let item = items
+                .iter()
+                .find(|item| item.content == content.content)
+                .unwrap();
+            assert_eq!(content.tags, item.tags);
+            assert_eq!(
+                content.metadata.get(SEED_METADATA.0).map(String::as_str),
+                Some(SEED_METADATA.1)
+            );
+        }
+        assert!(
+            tag_storage
+                .find_by_tag_tree("programming")
+                .await
+                .unwrap()
+                .len()
+                >= 2
+        );
+
+        let again = seed(&state, &items).await.unwrap();
+        assert_eq!(again.created, 0);
+        assert_eq!(again.duplicates, items.len());
+    }
+}
@@ -0,0 +1,61 @@
+use std::sync::OnceLock;
+use tokio::sync::watch;
+use tracing::info;
+
+/// Set once when the process is asked to stop
+static SHUTDOWN: OnceLock<watch::Sender<bool>> = OnceLock::new();
+
+fn sender() -> &'static watch::Sender<bool> {
+    SHUTDOWN.get_or_init(|| watch::channel(false).0)
+}
+
+/// Ask the server and background tasks to stop
+pub fn trigger() {
+    sender().send_replace(true);
+}
+
+/// Whether the process is stopping, readiness fails from then on
+pub fn is_shutting_down() -> bool {
+    *sender().borrow()
+}
+
+/// Resolves once the process is asked to stop
+pub async fn requested() {
+    let mut receiver = sender().subscribe();
+    // The sender is static, so the channel is never closed
+    let _ = receiver.wait_for(|stopping| *stopping).await;
+}
+
+/// Wait for SIGINT or, on Unix, SIGTERM as sent by Kubernetes and systemd, then
+/// start shutting down
+pub async fn wait_for_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!("Failed to listen for Ctrl-C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                tracing::error!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => info!("Received Ctrl-C, shutting down"),
+        _ = terminate => info!("Received SIGTERM, shutting down"),
+        _ = requested() => {}
+    }
+
+    trigger();
+}
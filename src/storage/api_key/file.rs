@@ -0,0 +1,84 @@
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tokio::sync::RwLock;
+
+use crate::storage::ApiKeyStorage;
+use crate::{ApiKey, ClassifyError, ClassifyResult};
+
+/// API key storage in a local JSON file, rewritten on every change
+pub struct FileApiKeyStorage {
+    path: PathBuf,
+    keys: RwLock<HashMap<String, ApiKey>>,
+}
+
+impl FileApiKeyStorage {
+    pub async fn new(path: &str) -> ClassifyResult<Self> {
+        let path = PathBuf::from(path);
+
+        let keys = match tokio::fs::read(&path).await {
+            Ok(data) => serde_json::from_slice::<Vec<ApiKey>>(&data)?
+                .into_iter()
+                .map(|key| (key.id.to_string(), key))
+                .collect(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => HashMap::new(),
+            Err(e) => {
+                return Err(ClassifyError::StorageError(format!(
+                    "Failed to read API keys: {}",
+                    e
+                )))
+            }
+        };
+
+        Ok(Self {
+            path,
+            keys: RwLock::new(keys),
+        })
+    }
+
+    async fn write(&self, keys: &HashMap<String, ApiKey>) -> ClassifyResult<()> {
+        if let Some(dir) = self.path.parent() {
+            tokio::fs::create_dir_all(dir).await.map_err(|e| {
+                ClassifyError::StorageError(format!("Failed to create directory: {}", e))
+            })?;
+        }
+
+        let data = serde_json::to_vec_pretty(&keys.values().collect::<Vec<_>>())?;
+
+        // Write to a temporary file first so a crash can't leave a truncated file
+        let tmp_path = self.path.with_extension("tmp");
+        tokio::fs::write(&tmp_path, data)
+            .await
+            .map_err(|e| ClassifyError::StorageError(format!("Failed to write API keys: {}", e)))?;
+        tokio::fs::rename(&tmp_path, &self.path)
+            .await
+            .map_err(|e| ClassifyError::StorageError(format!("Failed to write API keys: {}", e)))
+    }
+}
+
+#[async_trait]
+impl ApiKeyStorage for FileApiKeyStorage {
+    async fn save(&self, key: &ApiKey) -> ClassifyResult<()> {
+        let mut keys = self.keys.write().await;
+        keys.insert(key.id.to_string(), key.clone());
+        self.write(&keys).await
+    }
+
+    async fn get(&self, id: &str) -> ClassifyResult<Option<ApiKey>> {
+        Ok(self.keys.read().await.get(id).cloned())
+    }
+
+    async fn find_by_hash(&self, key_hash: &str) -> ClassifyResult<Option<ApiKey>> {
+        Ok(self
+            .keys
+            .read()
+            .await
+            .values()
+            .find(|key| key.key_hash == key_hash)
+            .cloned())
+    }
+
+    async fn list(&self) -> ClassifyResult<Vec<ApiKey>> {
+        Ok(self.keys.read().await.values().cloned().collect())
+    }
+}
@@ -0,0 +1,36 @@
+use crate::storage::api_key::file::FileApiKeyStorage;
+use crate::storage::api_key::ApiKeys;
+use crate::ClassifyResult;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_keys_survive_reopening() -> ClassifyResult<()> {
+        let dir = std::env::temp_dir().join(format!("classify-api-keys-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("api_keys.json");
+        let path = path.to_str().unwrap();
+
+        let keys = ApiKeys::new(
+            Arc::new(FileApiKeyStorage::new(path).await?),
+            Duration::from_secs(60),
+        );
+        let (key, secret) = keys.create("ci", None, false).await?;
+
+        let reopened = ApiKeys::new(
+            Arc::new(FileApiKeyStorage::new(path).await?),
+            Duration::from_secs(60),
+        );
+        let authenticated = reopened
+            .authenticate(&secret)
+            .await?
+            .expect("key is stored");
+        assert_eq!(authenticated.id, key.id);
+
+        std::fs::remove_dir_all(dir).ok();
+        Ok(())
+    }
+}
@@ -0,0 +1,47 @@
+use async_trait::async_trait;
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+
+use crate::storage::ApiKeyStorage;
+use crate::{ApiKey, ClassifyResult};
+
+/// In-memory API key storage, for development and tests. Keys are lost on restart.
+#[derive(Default)]
+pub struct MemoryApiKeyStorage {
+    keys: RwLock<HashMap<String, ApiKey>>,
+}
+
+impl MemoryApiKeyStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl ApiKeyStorage for MemoryApiKeyStorage {
+    async fn save(&self, key: &ApiKey) -> ClassifyResult<()> {
+        self.keys
+            .write()
+            .await
+            .insert(key.id.to_string(), key.clone());
+        Ok(())
+    }
+
+    async fn get(&self, id: &str) -> ClassifyResult<Option<ApiKey>> {
+        Ok(self.keys.read().await.get(id).cloned())
+    }
+
+    async fn find_by_hash(&self, key_hash: &str) -> ClassifyResult<Option<ApiKey>> {
+        Ok(self
+            .keys
+            .read()
+            .await
+            .values()
+            .find(|key| key.key_hash == key_hash)
+            .cloned())
+    }
+
+    async fn list(&self) -> ClassifyResult<Vec<ApiKey>> {
+        Ok(self.keys.read().await.values().cloned().collect())
+    }
+}
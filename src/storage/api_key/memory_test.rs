@@ -0,0 +1,95 @@
+use crate::storage::api_key::memory::MemoryApiKeyStorage;
+use crate::storage::api_key::ApiKeys;
+use crate::ClassifyResult;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn api_keys(grace: Duration) -> ApiKeys {
+        ApiKeys::new(Arc::new(MemoryApiKeyStorage::new()), grace)
+    }
+
+    #[tokio::test]
+    async fn test_create_and_authenticate() -> ClassifyResult<()> {
+        let keys = api_keys(Duration::from_secs(60));
+
+        let (key, secret) = keys.create("ci", Some("acme".to_string()), false).await?;
+        assert_ne!(key.key_hash, secret);
+
+        let authenticated = keys.authenticate(&secret).await?.expect("key is active");
+        assert_eq!(authenticated.id, key.id);
+        assert_eq!(authenticated.namespace.as_deref(), Some("acme"));
+
+        assert!(keys.authenticate("not-a-key").await?.is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rotation_keeps_old_key_during_grace_period() -> ClassifyResult<()> {
+        let keys = api_keys(Duration::from_secs(60));
+
+        let (old_key, old_secret) = keys.create("ci", None, false).await?;
+        let (new_key, new_secret) = keys
+            .rotate(&old_key.id.to_string())
+            .await?
+            .expect("key exists");
+
+        assert_ne!(new_key.id, old_key.id);
+        assert_eq!(new_key.name, "ci");
+        assert!(keys.authenticate(&old_secret).await?.is_some());
+        assert!(keys.authenticate(&new_secret).await?.is_some());
+
+        let rotated = keys
+            .list()
+            .await?
+            .into_iter()
+            .find(|key| key.id == old_key.id)
+            .unwrap();
+        assert!(rotated.expires_at.unwrap() > Utc::now());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rotation_without_grace_period() -> ClassifyResult<()> {
+        let keys = api_keys(Duration::ZERO);
+
+        let (old_key, old_secret) = keys.create("ci", None, false).await?;
+        keys.rotate(&old_key.id.to_string()).await?;
+
+        assert!(keys.authenticate(&old_secret).await?.is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rotation_keeps_read_only() -> ClassifyResult<()> {
+        let keys = api_keys(Duration::from_secs(60));
+
+        let (old_key, _) = keys.create("dashboard", None, true).await?;
+        let (new_key, new_secret) = keys
+            .rotate(&old_key.id.to_string())
+            .await?
+            .expect("key exists");
+
+        assert!(new_key.read_only);
+        assert!(keys.authenticate(&new_secret).await?.unwrap().read_only);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_revoked_key_is_rejected() -> ClassifyResult<()> {
+        let keys = api_keys(Duration::from_secs(60));
+
+        let (key, secret) = keys.create("ci", None, false).await?;
+        let revoked = keys.revoke(&key.id.to_string()).await?.expect("key exists");
+
+        assert!(revoked.revoked_at.is_some());
+        assert!(keys.authenticate(&secret).await?.is_none());
+        assert!(keys.rotate(&key.id.to_string()).await.is_err());
+        assert!(keys.revoke("missing").await?.is_none());
+        Ok(())
+    }
+}
@@ -0,0 +1,124 @@
+pub mod file;
+pub mod memory;
+pub mod postgres;
+#[cfg(feature = "redis")]
+pub mod redis;
+
+#[cfg(test)]
+mod file_test;
+#[cfg(test)]
+mod memory_test;
+
+use chrono::Utc;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::storage::ApiKeyStorage;
+use crate::{ApiKey, ClassifyError, ClassifyResult};
+
+/// Creates, rotates, revokes and checks API keys kept in an [`ApiKeyStorage`]
+pub struct ApiKeys {
+    storage: Arc<dyn ApiKeyStorage>,
+    rotation_grace: Duration,
+}
+
+impl ApiKeys {
+    pub fn new(storage: Arc<dyn ApiKeyStorage>, rotation_grace: Duration) -> Self {
+        Self {
+            storage,
+            rotation_grace,
+        }
+    }
+
+    /// Create a key, returning it together with the secret to hand out
+    pub async fn create(
+        &self,
+        name: &str,
+        namespace: Option<String>,
+        read_only: bool,
+    ) -> ClassifyResult<(ApiKey, String)> {
+        let secret = generate_secret();
+        let key = ApiKey {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            key_hash: ApiKey::hash_key(&secret),
+            namespace,
+            created_at: Utc::now(),
+            expires_at: None,
+            revoked_at: None,
+            read_only,
+        };
+
+        self.storage.save(&key).await?;
+        info!("Created API key {} ({})", key.id, key.name);
+
+        Ok((key, secret))
+    }
+
+    /// Replace a key with a new one. The old key keeps working for the grace
+    /// period, so clients can switch over without downtime.
+    pub async fn rotate(&self, id: &str) -> ClassifyResult<Option<(ApiKey, String)>> {
+        let Some(mut old_key) = self.storage.get(id).await? else {
+            return Ok(None);
+        };
+
+        let now = Utc::now();
+        if !old_key.is_active(now) {
+            return Err(ClassifyError::ApiError(format!(
+                "API key {} is no longer active",
+                id
+            )));
+        }
+
+        let (new_key, secret) = self
+            .create(&old_key.name, old_key.namespace.clone(), old_key.read_only)
+            .await?;
+
+        let grace = chrono::Duration::from_std(self.rotation_grace).map_err(|e| {
+            ClassifyError::ConfigError(format!("Invalid rotation grace period: {}", e))
+        })?;
+        old_key.expires_at = Some(
+            old_key
+                .expires_at
+                .map_or(now + grace, |expires_at| expires_at.min(now + grace)),
+        );
+        self.storage.save(&old_key).await?;
+        info!("Rotated API key {} to {}", old_key.id, new_key.id);
+
+        Ok(Some((new_key, secret)))
+    }
+
+    /// Revoke a key immediately
+    pub async fn revoke(&self, id: &str) -> ClassifyResult<Option<ApiKey>> {
+        let Some(mut key) = self.storage.get(id).await? else {
+            return Ok(None);
+        };
+
+        if key.revoked_at.is_none() {
+            key.revoked_at = Some(Utc::now());
+            self.storage.save(&key).await?;
+            info!("Revoked API key {} ({})", key.id, key.name);
+        }
+
+        Ok(Some(key))
+    }
+
+    pub async fn list(&self) -> ClassifyResult<Vec<ApiKey>> {
+        let mut keys = self.storage.list().await?;
+        keys.sort_by_key(|key| key.created_at);
+        Ok(keys)
+    }
+
+    /// Look up the active key matching a secret
+    pub async fn authenticate(&self, secret: &str) -> ClassifyResult<Option<ApiKey>> {
+        let key = self.storage.find_by_hash(&ApiKey::hash_key(secret)).await?;
+        Ok(key.filter(|key| key.is_active(Utc::now())))
+    }
+}
+
+/// Generate a random key of 64 hex characters
+fn generate_secret() -> String {
+    format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
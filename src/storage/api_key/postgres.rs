@@ -0,0 +1,107 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tokio_postgres::Row;
+
+use crate::storage::postgres::{connect, SharedClient};
+use crate::storage::ApiKeyStorage;
+use crate::{ApiKey, ClassifyError, ClassifyResult};
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS api_keys (
+        id TEXT PRIMARY KEY,
+        key_hash TEXT NOT NULL UNIQUE,
+        document JSONB NOT NULL
+    );
+";
+
+/// PostgreSQL-based API key storage
+pub struct PostgresApiKeyStorage {
+    client: SharedClient,
+}
+
+impl PostgresApiKeyStorage {
+    pub async fn new(postgres_url: &str) -> ClassifyResult<Self> {
+        let client = connect(postgres_url).await?;
+        Self::with_client(Arc::new(Mutex::new(client))).await
+    }
+
+    /// Use a client shared with other storages
+    pub async fn with_client(client: SharedClient) -> ClassifyResult<Self> {
+        crate::storage::connections::register("postgres", &client);
+        client
+            .lock()
+            .await
+            .batch_execute(SCHEMA)
+            .await
+            .map_err(|e| {
+                ClassifyError::StorageError(format!("Failed to create API key table: {}", e))
+            })?;
+
+        Ok(Self { client })
+    }
+}
+
+fn storage_error(action: &str) -> impl Fn(tokio_postgres::Error) -> ClassifyError + '_ {
+    move |e| ClassifyError::StorageError(format!("Failed to {}: {}", action, e))
+}
+
+fn key_from_row(row: &Row) -> ClassifyResult<ApiKey> {
+    let document: serde_json::Value = row.get("document");
+    Ok(serde_json::from_value(document)?)
+}
+
+#[async_trait]
+impl ApiKeyStorage for PostgresApiKeyStorage {
+    async fn save(&self, key: &ApiKey) -> ClassifyResult<()> {
+        let document = serde_json::to_value(key)?;
+        let client = self.client.lock().await;
+
+        client
+            .execute(
+                "INSERT INTO api_keys (id, key_hash, document) VALUES ($1, $2, $3)
+                 ON CONFLICT (id) DO UPDATE SET key_hash = EXCLUDED.key_hash, document = EXCLUDED.document",
+                &[&key.id.to_string(), &key.key_hash, &document],
+            )
+            .await
+            .map_err(storage_error("save API key"))?;
+
+        Ok(())
+    }
+
+    async fn get(&self, id: &str) -> ClassifyResult<Option<ApiKey>> {
+        let client = self.client.lock().await;
+
+        let row = client
+            .query_opt("SELECT document FROM api_keys WHERE id = $1", &[&id])
+            .await
+            .map_err(storage_error("get API key"))?;
+
+        row.as_ref().map(key_from_row).transpose()
+    }
+
+    async fn find_by_hash(&self, key_hash: &str) -> ClassifyResult<Option<ApiKey>> {
+        let client = self.client.lock().await;
+
+        let row = client
+            .query_opt(
+                "SELECT document FROM api_keys WHERE key_hash = $1",
+                &[&key_hash],
+            )
+            .await
+            .map_err(storage_error("find API key"))?;
+
+        row.as_ref().map(key_from_row).transpose()
+    }
+
+    async fn list(&self) -> ClassifyResult<Vec<ApiKey>> {
+        let client = self.client.lock().await;
+
+        let rows = client
+            .query("SELECT document FROM api_keys", &[])
+            .await
+            .map_err(storage_error("list API keys"))?;
+
+        rows.iter().map(key_from_row).collect()
+    }
+}
@@ -0,0 +1,122 @@
+use async_trait::async_trait;
+use redis::AsyncCommands;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+use crate::storage::redis::{connect, RedisConnectOptions, SharedConnection};
+use crate::storage::ApiKeyStorage;
+use crate::{ApiKey, ClassifyError, ClassifyResult};
+
+/// Redis-based API key storage, with each key as a JSON string and an index
+/// from key hash to key id
+pub struct RedisApiKeyStorage {
+    connection: SharedConnection,
+    prefix: String,
+}
+
+impl RedisApiKeyStorage {
+    pub async fn new(
+        redis_url: &str,
+        options: &RedisConnectOptions,
+        prefix: Option<&str>,
+    ) -> ClassifyResult<Self> {
+        let connection = connect(redis_url, options).await?;
+        Ok(Self::with_connection(
+            Arc::new(Mutex::new(connection)),
+            prefix,
+        ))
+    }
+
+    /// Use a connection shared with other storages
+    pub fn with_connection(connection: SharedConnection, prefix: Option<&str>) -> Self {
+        crate::storage::connections::register("redis", &connection);
+        Self {
+            connection,
+            prefix: prefix.unwrap_or("classify:").to_string(),
+        }
+    }
+
+    fn get_key_key(&self, id: &str) -> String {
+        format!("{}apikey:{}", self.prefix, id)
+    }
+
+    fn get_hash_key(&self, key_hash: &str) -> String {
+        format!("{}apikey-hash:{}", self.prefix, key_hash)
+    }
+
+    fn get_ids_key(&self) -> String {
+        format!("{}apikeys", self.prefix)
+    }
+}
+
+fn parse_key(json: &str) -> ClassifyResult<ApiKey> {
+    serde_json::from_str(json)
+        .map_err(|e| ClassifyError::StorageError(format!("Failed to parse API key: {}", e)))
+}
+
+#[async_trait]
+impl ApiKeyStorage for RedisApiKeyStorage {
+    async fn save(&self, key: &ApiKey) -> ClassifyResult<()> {
+        let id = key.id.to_string();
+        let json = serde_json::to_string(key)?;
+        let mut conn = self.connection.lock().await;
+
+        redis::pipe()
+            .atomic()
+            .set(self.get_key_key(&id), json)
+            .set(self.get_hash_key(&key.key_hash), &id)
+            .sadd(self.get_ids_key(), &id)
+            .query_async::<_, ()>(&mut *conn)
+            .await
+            .map_err(|e| ClassifyError::StorageError(format!("Failed to save API key: {}", e)))?;
+
+        Ok(())
+    }
+
+    async fn get(&self, id: &str) -> ClassifyResult<Option<ApiKey>> {
+        let mut conn = self.connection.lock().await;
+
+        let json: Option<String> = conn
+            .get(self.get_key_key(id))
+            .await
+            .map_err(|e| ClassifyError::StorageError(format!("Failed to get API key: {}", e)))?;
+
+        json.as_deref().map(parse_key).transpose()
+    }
+
+    async fn find_by_hash(&self, key_hash: &str) -> ClassifyResult<Option<ApiKey>> {
+        let id: Option<String> = {
+            let mut conn = self.connection.lock().await;
+            conn.get(self.get_hash_key(key_hash)).await.map_err(|e| {
+                ClassifyError::StorageError(format!("Failed to find API key: {}", e))
+            })?
+        };
+
+        match id {
+            Some(id) => self.get(&id).await,
+            None => Ok(None),
+        }
+    }
+
+    async fn list(&self) -> ClassifyResult<Vec<ApiKey>> {
+        let mut conn = self.connection.lock().await;
+
+        let ids: Vec<String> = conn
+            .smembers(self.get_ids_key())
+            .await
+            .map_err(|e| ClassifyError::StorageError(format!("Failed to list API keys: {}", e)))?;
+
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let keys: Vec<String> = ids.iter().map(|id| self.get_key_key(id)).collect();
+        let jsons: Vec<Option<String>> = redis::cmd("MGET")
+            .arg(&keys)
+            .query_async(&mut *conn)
+            .await
+            .map_err(|e| ClassifyError::StorageError(format!("Failed to list API keys: {}", e)))?;
+
+        jsons.iter().flatten().map(|json| parse_key(json)).collect()
+    }
+}
@@ -0,0 +1,59 @@
+use std::sync::{Arc, Mutex, Weak};
+
+use crate::ConnectionStats;
+
+/// A connection that is locked for every command, as the Redis connections and
+/// Postgres clients shared between storages are
+trait Lockable: Send + Sync {
+    fn in_use(&self) -> bool;
+}
+
+impl<T: Send> Lockable for tokio::sync::Mutex<T> {
+    fn in_use(&self) -> bool {
+        self.try_lock().is_err()
+    }
+}
+
+struct Registered {
+    backend: &'static str,
+    connection: Weak<dyn Lockable>,
+}
+
+/// Shared connections in use, kept to report their contention at runtime
+static CONNECTIONS: Mutex<Vec<Registered>> = Mutex::new(Vec::new());
+
+/// Track a shared connection. Registering the same connection again, as every
+/// storage sharing it does, has no effect.
+pub fn register<T: Send + 'static>(backend: &'static str, connection: &Arc<tokio::sync::Mutex<T>>) {
+    let connection: Weak<dyn Lockable> = Arc::downgrade(connection) as Weak<dyn Lockable>;
+    let mut connections = CONNECTIONS.lock().unwrap_or_else(|e| e.into_inner());
+
+    connections.retain(|registered| registered.connection.strong_count() > 0);
+    if !connections
+        .iter()
+        .any(|registered| Weak::ptr_eq(&registered.connection, &connection))
+    {
+        connections.push(Registered {
+            backend,
+            connection,
+        });
+    }
+}
+
+/// Stats of the shared connections that are still in use
+pub fn stats() -> Vec<ConnectionStats> {
+    let connections = CONNECTIONS.lock().unwrap_or_else(|e| e.into_inner());
+
+    connections
+        .iter()
+        .filter_map(|registered| {
+            let connection = registered.connection.upgrade()?;
+            Some(ConnectionStats {
+                backend: registered.backend.to_string(),
+                // Not counting the reference taken here
+                users: Arc::strong_count(&connection) - 1,
+                in_use: connection.in_use(),
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,219 @@
+use async_trait::async_trait;
+use aws_sdk_dynamodb::primitives::Blob;
+use aws_sdk_dynamodb::types::{AttributeValue, ReturnValue};
+use aws_sdk_dynamodb::Client;
+
+use crate::storage::dynamodb::{check_table, create_client, string_attribute, Item};
+use crate::storage::ContentStorage;
+use crate::{ClassifyError, ClassifyResult, Content};
+
+/// Global secondary index on `content_hash`, used for duplicate detection
+pub const HASH_INDEX: &str = "content_hash-index";
+
+/// DynamoDB-based content storage.
+///
+/// Content items are keyed by `id` and hold the serialized content in `document`
+/// plus the names of their `attachments`. Attachments are separate items keyed
+/// by `<id>#attachment#<name>`, since an item is limited to 400 KB.
+pub struct DynamoDbContentStorage {
+    client: Client,
+    table: String,
+}
+
+impl DynamoDbContentStorage {
+    pub async fn new(table: &str, region: Option<&str>) -> ClassifyResult<Self> {
+        let client = create_client(region).await?;
+        check_table(&client, table).await?;
+
+        Ok(Self {
+            client,
+            table: table.to_string(),
+        })
+    }
+
+    fn attachment_key(&self, id: &str, name: &str) -> String {
+        format!("{}#attachment#{}", id, name)
+    }
+
+    fn parse_item(&self, item: &Item) -> ClassifyResult<Option<Content>> {
+        match string_attribute(item, "document") {
+            Some(document) => Ok(Some(serde_json::from_str(document)?)),
+            None => Ok(None),
+        }
+    }
+}
+
+fn storage_error<E: std::fmt::Display>(action: &str) -> impl Fn(E) -> ClassifyError + '_ {
+    move |e| ClassifyError::StorageError(format!("Failed to {}: {}", action, e))
+}
+
+#[async_trait]
+impl ContentStorage for DynamoDbContentStorage {
+    async fn store(&self, content: &Content) -> ClassifyResult<()> {
+        let document = serde_json::to_string(content)?;
+
+        // Updated rather than replaced so the list of attachments is kept
+        let mut request = self
+            .client
+            .update_item()
+            .table_name(&self.table)
+            .key("id", AttributeValue::S(content.id.to_string()))
+            .expression_attribute_names("#document", "document")
+            .expression_attribute_values(":document", AttributeValue::S(document));
+
+        request = match &content.content_hash {
+            Some(hash) => request
+                .update_expression("SET #document = :document, content_hash = :hash")
+                .expression_attribute_values(":hash", AttributeValue::S(hash.clone())),
+            None => request.update_expression("SET #document = :document REMOVE content_hash"),
+        };
+
+        request
+            .send()
+            .await
+            .map_err(storage_error("store content"))?;
+
+        Ok(())
+    }
+
+    async fn get(&self, id: &str) -> ClassifyResult<Option<Content>> {
+        let output = self
+            .client
+            .get_item()
+            .table_name(&self.table)
+            .key("id", AttributeValue::S(id.to_string()))
+            .consistent_read(true)
+            .send()
+            .await
+            .map_err(storage_error("get content"))?;
+
+        match output.item() {
+            Some(item) => self.parse_item(item),
+            None => Ok(None),
+        }
+    }
+
+    async fn list(&self) -> ClassifyResult<Vec<Content>> {
+        let mut contents = Vec::new();
+        let mut start_key = None;
+
+        loop {
+            let output = self
+                .client
+                .scan()
+                .table_name(&self.table)
+                .filter_expression("attribute_exists(#document)")
+                .expression_attribute_names("#document", "document")
+                .set_exclusive_start_key(start_key)
+                .send()
+                .await
+                .map_err(storage_error("list content"))?;
+
+            for item in output.items().unwrap_or_default() {
+                if let Some(content) = self.parse_item(item)? {
+                    contents.push(content);
+                }
+            }
+
+            start_key = output.last_evaluated_key().cloned();
+            if start_key.is_none() {
+                break;
+            }
+        }
+
+        Ok(contents)
+    }
+
+    async fn delete(&self, id: &str) -> ClassifyResult<bool> {
+        let output = self
+            .client
+            .delete_item()
+            .table_name(&self.table)
+            .key("id", AttributeValue::S(id.to_string()))
+            .return_values(ReturnValue::AllOld)
+            .send()
+            .await
+            .map_err(storage_error("delete content"))?;
+
+        let Some(item) = output.attributes() else {
+            return Ok(false);
+        };
+
+        let attachments = item
+            .get("attachments")
+            .and_then(|value| value.as_ss().ok())
+            .cloned()
+            .unwrap_or_default();
+
+        for name in attachments {
+            self.client
+                .delete_item()
+                .table_name(&self.table)
+                .key("id", AttributeValue::S(self.attachment_key(id, &name)))
+                .send()
+                .await
+                .map_err(storage_error("delete attachment"))?;
+        }
+
+        Ok(true)
+    }
+
+    async fn find_by_hash(&self, hash: &str) -> ClassifyResult<Option<Content>> {
+        let output = self
+            .client
+            .query()
+            .table_name(&self.table)
+            .index_name(HASH_INDEX)
+            .key_condition_expression("content_hash = :hash")
+            .expression_attribute_values(":hash", AttributeValue::S(hash.to_string()))
+            .limit(1)
+            .send()
+            .await
+            .map_err(storage_error("find content by hash"))?;
+
+        match output.items().unwrap_or_default().first() {
+            Some(item) => self.parse_item(item),
+            None => Ok(None),
+        }
+    }
+
+    async fn store_attachment(&self, id: &str, name: &str, data: &[u8]) -> ClassifyResult<()> {
+        self.client
+            .put_item()
+            .table_name(&self.table)
+            .item("id", AttributeValue::S(self.attachment_key(id, name)))
+            .item("data", AttributeValue::B(Blob::new(data.to_vec())))
+            .send()
+            .await
+            .map_err(storage_error("store attachment"))?;
+
+        self.client
+            .update_item()
+            .table_name(&self.table)
+            .key("id", AttributeValue::S(id.to_string()))
+            .update_expression("ADD attachments :name")
+            .expression_attribute_values(":name", AttributeValue::Ss(vec![name.to_string()]))
+            .send()
+            .await
+            .map_err(storage_error("store attachment"))?;
+
+        Ok(())
+    }
+
+    async fn get_attachment(&self, id: &str, name: &str) -> ClassifyResult<Option<Vec<u8>>> {
+        let output = self
+            .client
+            .get_item()
+            .table_name(&self.table)
+            .key("id", AttributeValue::S(self.attachment_key(id, name)))
+            .send()
+            .await
+            .map_err(storage_error("get attachment"))?;
+
+        Ok(output
+            .item()
+            .and_then(|item| item.get("data"))
+            .and_then(|value| value.as_b().ok())
+            .map(|data| data.as_ref().to_vec()))
+    }
+}
@@ -1,7 +1,7 @@
 use async_trait::async_trait;
 use std::fs;
 use std::path::PathBuf;
-use tokio::fs::{create_dir_all, read_dir, remove_file};
+use tokio::fs::{create_dir_all, read_dir, remove_dir_all, remove_file};
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
 use crate::storage::ContentStorage;
@@ -26,6 +26,10 @@ impl FilesystemContentStorage {
     fn get_file_path(&self, id: &str) -> PathBuf {
         self.base_dir.join(format!("{}.json", id))
     }
+
+    fn get_attachments_dir(&self, id: &str) -> PathBuf {
+        self.base_dir.join(format!("{}.attachments", id))
+    }
 }
 
 #[async_trait]
@@ -115,6 +119,13 @@ impl ContentStorage for FilesystemContentStorage {
             .await
             .map_err(|e| ClassifyError::StorageError(format!("Failed to delete file: {}", e)))?;
 
+        let attachments_dir = self.get_attachments_dir(id);
+        if attachments_dir.exists() {
+            remove_dir_all(&attachments_dir).await.map_err(|e| {
+                ClassifyError::StorageError(format!("Failed to delete attachments: {}", e))
+            })?;
+        }
+
         Ok(true)
     }
 
@@ -129,4 +140,35 @@ impl ContentStorage for FilesystemContentStorage {
 
         Ok(None)
     }
+
+    async fn store_attachment(&self, id: &str, name: &str, data: &[u8]) -> ClassifyResult<()> {
+        let attachments_dir = self.get_attachments_dir(id);
+
+        create_dir_all(&attachments_dir).await.map_err(|e| {
+            ClassifyError::StorageError(format!("Failed to create directory: {}", e))
+        })?;
+
+        let mut file = tokio::fs::File::create(attachments_dir.join(name))
+            .await
+            .map_err(|e| ClassifyError::StorageError(format!("Failed to create file: {}", e)))?;
+
+        file.write_all(data)
+            .await
+            .map_err(|e| ClassifyError::StorageError(format!("Failed to write file: {}", e)))?;
+
+        Ok(())
+    }
+
+    async fn get_attachment(&self, id: &str, name: &str) -> ClassifyResult<Option<Vec<u8>>> {
+        let file_path = self.get_attachments_dir(id).join(name);
+
+        match tokio::fs::read(&file_path).await {
+            Ok(data) => Ok(Some(data)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(ClassifyError::StorageError(format!(
+                "Failed to read attachment: {}",
+                e
+            ))),
+        }
+    }
 }
@@ -0,0 +1,82 @@
+use async_trait::async_trait;
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+
+use crate::storage::ContentStorage;
+use crate::{ClassifyResult, Content};
+
+/// In-memory content storage, for development and tests. Nothing survives a restart.
+#[derive(Default)]
+pub struct MemoryContentStorage {
+    contents: RwLock<HashMap<String, Content>>,
+    attachments: RwLock<HashMap<(String, String), Vec<u8>>>,
+}
+
+impl MemoryContentStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl ContentStorage for MemoryContentStorage {
+    async fn store(&self, content: &Content) -> ClassifyResult<()> {
+        self.contents
+            .write()
+            .await
+            .insert(content.id.to_string(), content.clone());
+        Ok(())
+    }
+
+    async fn get(&self, id: &str) -> ClassifyResult<Option<Content>> {
+        Ok(self.contents.read().await.get(id).cloned())
+    }
+
+    async fn get_many(&self, ids: &[String]) -> ClassifyResult<Vec<Content>> {
+        let contents = self.contents.read().await;
+        Ok(ids
+            .iter()
+            .filter_map(|id| contents.get(id).cloned())
+            .collect())
+    }
+
+    async fn list(&self) -> ClassifyResult<Vec<Content>> {
+        Ok(self.contents.read().await.values().cloned().collect())
+    }
+
+    async fn delete(&self, id: &str) -> ClassifyResult<bool> {
+        let removed = self.contents.write().await.remove(id).is_some();
+        self.attachments
+            .write()
+            .await
+            .retain(|(content_id, _), _| content_id != id);
+        Ok(removed)
+    }
+
+    async fn find_by_hash(&self, hash: &str) -> ClassifyResult<Option<Content>> {
+        Ok(self
+            .contents
+            .read()
+            .await
+            .values()
+            .find(|content| content.content_hash.as_deref() == Some(hash))
+            .cloned())
+    }
+
+    async fn store_attachment(&self, id: &str, name: &str, data: &[u8]) -> ClassifyResult<()> {
+        self.attachments
+            .write()
+            .await
+            .insert((id.to_string(), name.to_string()), data.to_vec());
+        Ok(())
+    }
+
+    async fn get_attachment(&self, id: &str, name: &str) -> ClassifyResult<Option<Vec<u8>>> {
+        Ok(self
+            .attachments
+            .read()
+            .await
+            .get(&(id.to_string(), name.to_string()))
+            .cloned())
+    }
+}
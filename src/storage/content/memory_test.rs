@@ -0,0 +1,53 @@
+use crate::storage::content::memory::MemoryContentStorage;
+use crate::storage::ContentStorage;
+use crate::{ClassifyResult, Content};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_memory_content_storage() -> ClassifyResult<()> {
+        let storage = MemoryContentStorage::new();
+
+        let content = Content::new("In-memory storage test".to_string())
+            .with_tags(vec!["memory".to_string()]);
+        let id = content.id.to_string();
+
+        storage.store(&content).await?;
+        storage
+            .store_attachment(&id, "snapshot.html", b"<html></html>")
+            .await?;
+
+        assert_eq!(storage.get(&id).await?.unwrap().content, content.content);
+        assert_eq!(storage.list().await?.len(), 1);
+        assert_eq!(
+            storage
+                .get_many(&[id.clone(), "missing".to_string()])
+                .await?
+                .len(),
+            1
+        );
+        assert_eq!(
+            storage
+                .find_by_hash(content.content_hash.as_deref().unwrap())
+                .await?
+                .map(|found| found.id),
+            Some(content.id)
+        );
+        assert_eq!(
+            storage.get_attachment(&id, "snapshot.html").await?,
+            Some(b"<html></html>".to_vec())
+        );
+
+        assert!(storage.delete(&id).await?);
+        assert!(!storage.delete(&id).await?);
+        assert!(storage.get(&id).await?.is_none());
+        assert!(storage
+            .get_attachment(&id, "snapshot.html")
+            .await?
+            .is_none());
+
+        Ok(())
+    }
+}
@@ -1,14 +1,30 @@
+#[cfg(feature = "dynamodb")]
+pub mod dynamodb;
 pub mod filesystem;
+pub mod memory;
+pub mod postgres;
+#[cfg(feature = "redis")]
 pub mod redis;
+#[cfg(feature = "s3")]
 pub mod s3;
+pub mod sqlite;
 
 // #[cfg(test)]
 // mod filesystem_test;
 
 #[cfg(test)]
-mod s3_test;
+mod memory_test;
+
+#[cfg(test)]
+mod postgres_test;
 
 #[cfg(test)]
+mod sqlite_test;
+
+#[cfg(all(test, feature = "s3"))]
+mod s3_test;
+
+#[cfg(all(test, feature = "redis"))]
 mod redis_test;
 
 // Other content storage implementations can be added here
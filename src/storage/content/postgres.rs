@@ -0,0 +1,182 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tokio_postgres::Row;
+
+use crate::storage::postgres::{connect, SharedClient};
+use crate::storage::ContentStorage;
+use crate::{ClassifyError, ClassifyResult, Content};
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS contents (
+        id TEXT PRIMARY KEY,
+        content_hash TEXT,
+        document JSONB NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS contents_content_hash_idx ON contents (content_hash);
+    CREATE INDEX IF NOT EXISTS contents_slug_idx ON contents ((document->>'slug'));
+    CREATE TABLE IF NOT EXISTS content_attachments (
+        content_id TEXT NOT NULL REFERENCES contents (id) ON DELETE CASCADE,
+        name TEXT NOT NULL,
+        data BYTEA NOT NULL,
+        PRIMARY KEY (content_id, name)
+    );
+";
+
+/// PostgreSQL-based content storage, keeping each content item as a JSONB document
+pub struct PostgresContentStorage {
+    client: SharedClient,
+}
+
+impl PostgresContentStorage {
+    pub async fn new(postgres_url: &str) -> ClassifyResult<Self> {
+        let client = connect(postgres_url).await?;
+        Self::with_client(Arc::new(Mutex::new(client))).await
+    }
+
+    /// Use a client shared with other storages
+    pub async fn with_client(client: SharedClient) -> ClassifyResult<Self> {
+        crate::storage::connections::register("postgres", &client);
+        client
+            .lock()
+            .await
+            .batch_execute(SCHEMA)
+            .await
+            .map_err(|e| {
+                ClassifyError::StorageError(format!("Failed to create content tables: {}", e))
+            })?;
+
+        Ok(Self { client })
+    }
+}
+
+fn storage_error(action: &str) -> impl Fn(tokio_postgres::Error) -> ClassifyError + '_ {
+    move |e| ClassifyError::StorageError(format!("Failed to {}: {}", action, e))
+}
+
+fn parse_row(row: &Row) -> ClassifyResult<Content> {
+    let document: serde_json::Value = row.get("document");
+    Ok(serde_json::from_value(document)?)
+}
+
+#[async_trait]
+impl ContentStorage for PostgresContentStorage {
+    async fn store(&self, content: &Content) -> ClassifyResult<()> {
+        let document = serde_json::to_value(content)?;
+        let client = self.client.lock().await;
+
+        client
+            .execute(
+                "INSERT INTO contents (id, content_hash, document) VALUES ($1, $2, $3)
+                 ON CONFLICT (id) DO UPDATE
+                 SET content_hash = EXCLUDED.content_hash, document = EXCLUDED.document",
+                &[&content.id.to_string(), &content.content_hash, &document],
+            )
+            .await
+            .map_err(storage_error("store content"))?;
+
+        Ok(())
+    }
+
+    async fn get(&self, id: &str) -> ClassifyResult<Option<Content>> {
+        let client = self.client.lock().await;
+
+        let row = client
+            .query_opt("SELECT document FROM contents WHERE id = $1", &[&id])
+            .await
+            .map_err(storage_error("get content"))?;
+
+        row.as_ref().map(parse_row).transpose()
+    }
+
+    async fn get_many(&self, ids: &[String]) -> ClassifyResult<Vec<Content>> {
+        let client = self.client.lock().await;
+
+        let rows = client
+            .query("SELECT document FROM contents WHERE id = ANY($1)", &[&ids])
+            .await
+            .map_err(storage_error("get content"))?;
+
+        rows.iter().map(parse_row).collect()
+    }
+
+    async fn list(&self) -> ClassifyResult<Vec<Content>> {
+        let client = self.client.lock().await;
+
+        let rows = client
+            .query("SELECT document FROM contents", &[])
+            .await
+            .map_err(storage_error("list content"))?;
+
+        rows.iter().map(parse_row).collect()
+    }
+
+    async fn delete(&self, id: &str) -> ClassifyResult<bool> {
+        let client = self.client.lock().await;
+
+        // Attachments are removed by the foreign key cascade
+        let deleted = client
+            .execute("DELETE FROM contents WHERE id = $1", &[&id])
+            .await
+            .map_err(storage_error("delete content"))?;
+
+        Ok(deleted > 0)
+    }
+
+    async fn find_by_hash(&self, hash: &str) -> ClassifyResult<Option<Content>> {
+        let client = self.client.lock().await;
+
+        let row = client
+            .query_opt(
+                "SELECT document FROM contents WHERE content_hash = $1 LIMIT 1",
+                &[&hash],
+            )
+            .await
+            .map_err(storage_error("find content by hash"))?;
+
+        row.as_ref().map(parse_row).transpose()
+    }
+
+    async fn find_by_slug(&self, slug: &str) -> ClassifyResult<Option<Content>> {
+        let client = self.client.lock().await;
+
+        let row = client
+            .query_opt(
+                "SELECT document FROM contents WHERE document->>'slug' = $1 LIMIT 1",
+                &[&slug],
+            )
+            .await
+            .map_err(storage_error("find content by slug"))?;
+
+        row.as_ref().map(parse_row).transpose()
+    }
+
+    async fn store_attachment(&self, id: &str, name: &str, data: &[u8]) -> ClassifyResult<()> {
+        let client = self.client.lock().await;
+
+        client
+            .execute(
+                "INSERT INTO content_attachments (content_id, name, data) VALUES ($1, $2, $3)
+                 ON CONFLICT (content_id, name) DO UPDATE SET data = EXCLUDED.data",
+                &[&id, &name, &data],
+            )
+            .await
+            .map_err(storage_error("store attachment"))?;
+
+        Ok(())
+    }
+
+    async fn get_attachment(&self, id: &str, name: &str) -> ClassifyResult<Option<Vec<u8>>> {
+        let client = self.client.lock().await;
+
+        let row = client
+            .query_opt(
+                "SELECT data FROM content_attachments WHERE content_id = $1 AND name = $2",
+                &[&id, &name],
+            )
+            .await
+            .map_err(storage_error("get attachment"))?;
+
+        Ok(row.map(|row| row.get("data")))
+    }
+}
@@ -1,36 +1,40 @@
 use async_trait::async_trait;
 use redis::{AsyncCommands, Pipeline};
 use std::sync::Arc;
+use tracing::{debug, error, info, warn};
 
+use crate::storage::redis::{connection_info, RedisConnectOptions, SharedConnection};
 use crate::storage::ContentStorage;
 use crate::{ClassifyError, ClassifyResult, Content};
 
 /// Redis-based content storage
 pub struct RedisContentStorage {
-    connection: Arc<tokio::sync::Mutex<redis::aio::Connection>>,
+    connection: SharedConnection,
     prefix: String,
 }
 
 impl RedisContentStorage {
     pub async fn new(
         redis_url: &str,
-        redis_password: Option<&str>,
+        options: &RedisConnectOptions,
         prefix: Option<&str>,
     ) -> ClassifyResult<Self> {
-        eprintln!("Creating Redis client with URL: {}", redis_url);
-        let client = redis::Client::open(redis_url).map_err(|e| {
-            eprintln!("Failed to create Redis client: {}", e);
+        // The URL may carry the password, so it is not logged
+        debug!("Creating Redis client");
+        let client = redis::Client::open(connection_info(redis_url, options)?).map_err(|e| {
+            error!("Failed to create Redis client: {}", e);
             ClassifyError::StorageError(format!("Failed to create Redis client: {}", e))
         })?;
 
-        eprintln!("Getting async connection...");
+        // The client authenticates while connecting when a password is set
+        debug!("Getting async connection...");
         let mut connection = match client.get_async_connection().await {
             Ok(conn) => {
-                eprintln!("Redis connection established successfully");
+                info!("Redis connection established successfully");
                 conn
             }
             Err(e) => {
-                eprintln!("Failed to connect to Redis: {}", e);
+                error!("Failed to connect to Redis: {}", e);
                 return Err(ClassifyError::StorageError(format!(
                     "Failed to connect to Redis: {}",
                     e
@@ -38,33 +42,15 @@ impl RedisContentStorage {
             }
         };
 
-        if let Some(password) = redis_password {
-            eprintln!("Authenticating to Redis...");
-            match redis::cmd("AUTH")
-                .arg(password)
-                .query_async::<_, ()>(&mut connection)
-                .await
-            {
-                Ok(_) => eprintln!("Redis authentication successful"),
-                Err(e) => {
-                    eprintln!("Failed to authenticate to Redis: {}", e);
-                    return Err(ClassifyError::StorageError(format!(
-                        "Failed to authenticate to Redis: {}",
-                        e
-                    )));
-                }
-            }
-        }
-
         // Test the connection with a PING
-        eprintln!("Testing Redis connection with PING...");
+        debug!("Testing Redis connection with PING...");
         match redis::cmd("PING")
             .query_async::<_, String>(&mut connection)
             .await
         {
-            Ok(response) => eprintln!("Redis PING successful: {}", response),
+            Ok(response) => debug!("Redis PING successful: {}", response),
             Err(e) => {
-                eprintln!("Redis PING failed: {}", e);
+                error!("Redis PING failed: {}", e);
                 return Err(ClassifyError::StorageError(format!(
                     "Redis PING failed: {}",
                     e
@@ -73,7 +59,7 @@ impl RedisContentStorage {
         }
 
         let prefix = prefix.unwrap_or("classify:content:").to_string();
-        eprintln!("Using Redis prefix: {}", prefix);
+        info!("Using Redis prefix: {}", prefix);
 
         Ok(Self {
             connection: Arc::new(tokio::sync::Mutex::new(connection)),
@@ -81,49 +67,79 @@ impl RedisContentStorage {
         })
     }
 
-    fn get_content_key(&self, id: &str) -> String {
-        format!("{}:{}", self.prefix, id)
-    }
-
-    fn get_hash_index_key(&self) -> String {
-        format!("{}hash_index", self.prefix)
+    /// Use a connection shared with other storages
+    pub fn with_connection(connection: SharedConnection, prefix: Option<&str>) -> Self {
+        crate::storage::connections::register("redis", &connection);
+        Self {
+            connection,
+            prefix: prefix.unwrap_or("classify:content:").to_string(),
+        }
     }
-}
 
-#[async_trait]
-impl ContentStorage for RedisContentStorage {
-    async fn store(&self, content: &Content) -> ClassifyResult<()> {
+    /// Add the commands storing `content` to a pipeline, so they can be combined
+    /// with tag writes on the same connection
+    pub(crate) fn queue_store(&self, pipe: &mut Pipeline, content: &Content) -> ClassifyResult<()> {
         let content_key = self.get_content_key(&content.id.to_string());
-        eprintln!("Storing content with key: {}", content_key);
+        debug!("Storing content with key: {}", content_key);
 
         let json = match serde_json::to_string(content) {
             Ok(json) => json,
             Err(e) => {
-                eprintln!("Failed to serialize content: {}", e);
+                error!("Failed to serialize content: {}", e);
                 return Err(ClassifyError::SerializationError(e));
             }
         };
 
-        let mut pipe = Pipeline::new();
         pipe.set(&content_key, &json);
 
         if let Some(hash) = &content.content_hash {
             let hash_index_key = self.get_hash_index_key();
-            eprintln!("Adding hash index: {}={}", hash, content.id);
+            debug!("Adding hash index: {}={}", hash, content.id);
             pipe.hset(&hash_index_key, hash, content.id.to_string());
         }
 
-        eprintln!("Acquiring Redis connection lock...");
+        if let Some(slug) = &content.slug {
+            debug!("Adding slug index: {}={}", slug, content.id);
+            pipe.hset(self.get_slug_index_key(), slug, content.id.to_string());
+        }
+
+        Ok(())
+    }
+
+    fn get_content_key(&self, id: &str) -> String {
+        format!("{}:{}", self.prefix, id)
+    }
+
+    fn get_hash_index_key(&self) -> String {
+        format!("{}hash_index", self.prefix)
+    }
+
+    fn get_slug_index_key(&self) -> String {
+        format!("{}slug_index", self.prefix)
+    }
+
+    fn get_attachments_key(&self, id: &str) -> String {
+        format!("{}attachments:{}", self.prefix, id)
+    }
+}
+
+#[async_trait]
+impl ContentStorage for RedisContentStorage {
+    async fn store(&self, content: &Content) -> ClassifyResult<()> {
+        let mut pipe = Pipeline::new();
+        self.queue_store(&mut pipe, content)?;
+
+        debug!("Acquiring Redis connection lock...");
         let mut conn = self.connection.lock().await;
-        eprintln!("Executing Redis pipeline for content storage...");
+        debug!("Executing Redis pipeline for content storage...");
 
         match pipe.query_async::<_, ()>(&mut *conn).await {
             Ok(_) => {
-                eprintln!("Content stored successfully");
+                debug!("Content stored successfully");
                 Ok(())
             }
             Err(e) => {
-                eprintln!("Failed to store content in Redis: {}", e);
+                error!("Failed to store content in Redis: {}", e);
                 Err(ClassifyError::StorageError(format!(
                     "Failed to store content in Redis: {}",
                     e
@@ -134,19 +150,19 @@ impl ContentStorage for RedisContentStorage {
 
     async fn get(&self, id: &str) -> ClassifyResult<Option<Content>> {
         let content_key = self.get_content_key(id);
-        eprintln!("Getting content with key: {}", content_key);
+        debug!("Getting content with key: {}", content_key);
 
-        eprintln!("Acquiring Redis connection lock...");
+        debug!("Acquiring Redis connection lock...");
         let mut conn = self.connection.lock().await;
-        eprintln!("Executing Redis GET...");
+        debug!("Executing Redis GET...");
 
         let json: Option<String> = match conn.get(&content_key).await {
             Ok(json) => {
-                eprintln!("Content retrieval successful");
+                debug!("Content retrieval successful");
                 json
             }
             Err(e) => {
-                eprintln!("Failed to get content from Redis: {}", e);
+                error!("Failed to get content from Redis: {}", e);
                 return Err(ClassifyError::StorageError(format!(
                     "Failed to get content from Redis: {}",
                     e
@@ -157,39 +173,63 @@ impl ContentStorage for RedisContentStorage {
         match json {
             Some(json_str) => match serde_json::from_str(&json_str) {
                 Ok(content) => {
-                    eprintln!("Content deserialized successfully");
+                    debug!("Content deserialized successfully");
                     Ok(Some(content))
                 }
                 Err(e) => {
-                    eprintln!("Failed to deserialize content: {}", e);
+                    error!("Failed to deserialize content: {}", e);
                     Err(ClassifyError::SerializationError(e))
                 }
             },
             None => {
-                eprintln!("Content not found");
+                debug!("Content not found");
                 Ok(None)
             }
         }
     }
 
+    async fn get_many(&self, ids: &[String]) -> ClassifyResult<Vec<Content>> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let keys: Vec<String> = ids.iter().map(|id| self.get_content_key(id)).collect();
+        let mut conn = self.connection.lock().await;
+
+        // MGET always returns a list, even for a single key
+        let values: Vec<Option<String>> = redis::cmd("MGET")
+            .arg(&keys)
+            .query_async(&mut *conn)
+            .await
+            .map_err(|e| {
+                ClassifyError::StorageError(format!("Failed to get content from Redis: {}", e))
+            })?;
+
+        values
+            .into_iter()
+            .flatten()
+            .map(|json| serde_json::from_str(&json).map_err(ClassifyError::SerializationError))
+            .collect()
+    }
+
     async fn list(&self) -> ClassifyResult<Vec<Content>> {
-        eprintln!("Listing content with prefix pattern: {}:*", self.prefix);
-        eprintln!("Acquiring Redis connection lock...");
+        debug!("Listing content with prefix pattern: {}:*", self.prefix);
+        debug!("Acquiring Redis connection lock...");
         let mut conn = self.connection.lock().await;
         let pattern = format!("{}:*", self.prefix);
 
-        eprintln!("Executing Redis KEYS command with pattern: {}", pattern);
+        debug!("Executing Redis KEYS command with pattern: {}", pattern);
         let keys: Vec<String> = match redis::cmd("KEYS")
             .arg(&pattern)
             .query_async::<_, Vec<String>>(&mut *conn)
             .await
         {
             Ok(keys) => {
-                eprintln!("Found {} keys matching pattern", keys.len());
+                debug!("Found {} keys matching pattern", keys.len());
                 keys
             }
             Err(e) => {
-                eprintln!("Failed to list content keys: {}", e);
+                error!("Failed to list content keys: {}", e);
                 return Err(ClassifyError::StorageError(format!(
                     "Failed to list content keys: {}",
                     e
@@ -198,22 +238,22 @@ impl ContentStorage for RedisContentStorage {
         };
 
         if keys.is_empty() {
-            eprintln!("No keys found, returning empty list");
+            debug!("No keys found, returning empty list");
             return Ok(Vec::new());
         }
 
-        eprintln!("Executing Redis MGET command for {} keys", keys.len());
+        debug!("Executing Redis MGET command for {} keys", keys.len());
         let json_strings: Vec<Option<String>> = match redis::cmd("MGET")
             .arg(&keys)
             .query_async::<_, Vec<Option<String>>>(&mut *conn)
             .await
         {
             Ok(strings) => {
-                eprintln!("MGET successful, retrieved {} values", strings.len());
+                debug!("MGET successful, retrieved {} values", strings.len());
                 strings
             }
             Err(e) => {
-                eprintln!("Failed to get content data: {}", e);
+                error!("Failed to get content data: {}", e);
                 return Err(ClassifyError::StorageError(format!(
                     "Failed to get content data: {}",
                     e
@@ -226,31 +266,31 @@ impl ContentStorage for RedisContentStorage {
             let json_string = json_opt.clone();
             match serde_json::from_str::<Content>(&json_string) {
                 Ok(content) => {
-                    eprintln!("Successfully deserialized content item");
+                    debug!("Successfully deserialized content item");
                     contents.push(content);
                 }
                 Err(e) => {
-                    eprintln!("Error deserializing content: {}", e);
+                    warn!("Skipping content that failed to deserialize: {}", e);
                 }
             }
         }
 
-        eprintln!("Returning {} content items", contents.len());
+        debug!("Returning {} content items", contents.len());
         Ok(contents)
     }
 
     async fn delete(&self, id: &str) -> ClassifyResult<bool> {
         let content_key = self.get_content_key(id);
-        eprintln!("Deleting content with key: {}", content_key);
+        debug!("Deleting content with key: {}", content_key);
 
-        eprintln!("Acquiring Redis connection lock...");
+        debug!("Acquiring Redis connection lock...");
         let mut conn = self.connection.lock().await;
 
-        eprintln!("Getting content before deletion");
+        debug!("Getting content before deletion");
         let json: Option<String> = match conn.get(&content_key).await {
             Ok(json) => json,
             Err(e) => {
-                eprintln!("Failed to get content for deletion: {}", e);
+                error!("Failed to get content for deletion: {}", e);
                 return Err(ClassifyError::StorageError(format!(
                     "Failed to get content for deletion: {}",
                     e
@@ -265,27 +305,32 @@ impl ContentStorage for RedisContentStorage {
                 Ok(content) => {
                     if let Some(hash) = &content.content_hash {
                         let hash_index_key = self.get_hash_index_key();
-                        eprintln!("Removing hash index: {}", hash);
+                        debug!("Removing hash index: {}", hash);
                         pipe.hdel(&hash_index_key, hash);
                     }
+                    if let Some(slug) = &content.slug {
+                        debug!("Removing slug index: {}", slug);
+                        pipe.hdel(self.get_slug_index_key(), slug);
+                    }
                 }
                 Err(e) => {
-                    eprintln!("Failed to deserialize content for deletion: {}", e);
+                    error!("Failed to deserialize content for deletion: {}", e);
                     return Err(ClassifyError::SerializationError(e));
                 }
             }
 
-            eprintln!("Deleting content key: {}", content_key);
+            debug!("Deleting content key: {}", content_key);
             pipe.del(&content_key);
+            pipe.del(self.get_attachments_key(id));
 
-            eprintln!("Executing Redis pipeline for deletion...");
+            debug!("Executing Redis pipeline for deletion...");
             match pipe.query_async::<_, ()>(&mut *conn).await {
                 Ok(_) => {
-                    eprintln!("Content deleted successfully");
+                    debug!("Content deleted successfully");
                     Ok(true)
                 }
                 Err(e) => {
-                    eprintln!("Failed to delete content: {}", e);
+                    error!("Failed to delete content: {}", e);
                     Err(ClassifyError::StorageError(format!(
                         "Failed to delete content: {}",
                         e
@@ -293,36 +338,36 @@ impl ContentStorage for RedisContentStorage {
                 }
             }
         } else {
-            eprintln!("Content not found for deletion");
+            debug!("Content not found for deletion");
             Ok(false)
         }
     }
 
     async fn find_by_hash(&self, hash: &str) -> ClassifyResult<Option<Content>> {
         let hash_index_key = self.get_hash_index_key();
-        eprintln!(
+        debug!(
             "Finding content by hash: {} using index: {}",
             hash, hash_index_key
         );
 
-        eprintln!("Acquiring Redis connection lock...");
+        debug!("Acquiring Redis connection lock...");
         let mut conn = self.connection.lock().await;
 
-        eprintln!("Executing Redis HGET...");
+        debug!("Executing Redis HGET...");
         let content_id: Option<String> = match conn
             .hget::<_, _, Option<String>>(&hash_index_key, hash)
             .await
         {
             Ok(id) => {
                 if id.is_some() {
-                    eprintln!("Content ID found for hash: {:?}", id);
+                    debug!("Content ID found for hash: {:?}", id);
                 } else {
-                    eprintln!("No content found for hash");
+                    debug!("No content found for hash");
                 }
                 id
             }
             Err(e) => {
-                eprintln!("Failed to look up content by hash: {}", e);
+                error!("Failed to look up content by hash: {}", e);
                 return Err(ClassifyError::StorageError(format!(
                     "Failed to look up content by hash: {}",
                     e
@@ -336,13 +381,59 @@ impl ContentStorage for RedisContentStorage {
 
         match content_id {
             Some(id) => {
-                eprintln!("Retrieving content with ID: {}", id);
+                debug!("Retrieving content with ID: {}", id);
                 self.get(&id).await
             }
             None => {
-                eprintln!("No content found for hash: {}", hash);
+                debug!("No content found for hash: {}", hash);
                 Ok(None)
             }
         }
     }
+
+    async fn find_by_slug(&self, slug: &str) -> ClassifyResult<Option<Content>> {
+        debug!("Finding content by slug: {}", slug);
+        let content_id: Option<String> = {
+            let mut conn = self.connection.lock().await;
+            conn.hget(self.get_slug_index_key(), slug)
+                .await
+                .map_err(|e| {
+                    error!("Failed to look up content by slug: {}", e);
+                    ClassifyError::StorageError(format!("Failed to look up content by slug: {}", e))
+                })?
+        };
+
+        match content_id {
+            Some(id) => self.get(&id).await,
+            None => Ok(None),
+        }
+    }
+
+    async fn store_attachment(&self, id: &str, name: &str, data: &[u8]) -> ClassifyResult<()> {
+        let attachments_key = self.get_attachments_key(id);
+        debug!("Storing attachment {} under key: {}", name, attachments_key);
+
+        let mut conn = self.connection.lock().await;
+
+        conn.hset::<_, _, _, ()>(&attachments_key, name, data)
+            .await
+            .map_err(|e| {
+                error!("Failed to store attachment in Redis: {}", e);
+                ClassifyError::StorageError(format!("Failed to store attachment in Redis: {}", e))
+            })
+    }
+
+    async fn get_attachment(&self, id: &str, name: &str) -> ClassifyResult<Option<Vec<u8>>> {
+        let attachments_key = self.get_attachments_key(id);
+        debug!("Getting attachment {} from key: {}", name, attachments_key);
+
+        let mut conn = self.connection.lock().await;
+
+        conn.hget::<_, _, Option<Vec<u8>>>(&attachments_key, name)
+            .await
+            .map_err(|e| {
+                error!("Failed to get attachment from Redis: {}", e);
+                ClassifyError::StorageError(format!("Failed to get attachment from Redis: {}", e))
+            })
+    }
 }
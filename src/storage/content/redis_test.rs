@@ -8,6 +8,7 @@ use tokio::time::timeout;
 mod tests {
     use super::*;
     use crate::storage::content::redis::RedisContentStorage;
+    use crate::storage::redis::RedisConnectOptions;
     use uuid::Uuid;
 
     #[tokio::test]
@@ -71,8 +72,11 @@ mod tests {
         println!("Successfully connected to Redis and verified with PING");
 
         println!("Creating RedisContentStorage instance...");
-        let storage_future =
-            RedisContentStorage::new(&redis_url, redis_password.as_deref(), Some(&prefix));
+        let options = RedisConnectOptions {
+            password: redis_password.clone(),
+            ..Default::default()
+        };
+        let storage_future = RedisContentStorage::new(&redis_url, &options, Some(&prefix));
         let storage = match timeout(op_timeout, storage_future).await {
             Ok(result) => result?,
             Err(_) => {
@@ -2,16 +2,28 @@ use async_trait::async_trait;
 use aws_credential_types::Credentials;
 use aws_sdk_s3::primitives::ByteStream;
 use aws_sdk_s3::{config::Region, Client as S3Client};
+use std::sync::atomic::{AtomicBool, Ordering};
 use tokio::io::AsyncReadExt;
 
 use crate::storage::ContentStorage;
 use crate::{ClassifyError, ClassifyResult, Content};
 
+/// Connection options for S3-compatible stores such as MinIO, Cloudflare R2 or LocalStack
+#[derive(Debug, Clone, Default)]
+pub struct S3ClientOptions {
+    /// Custom endpoint, instead of AWS
+    pub endpoint_url: Option<String>,
+    /// Address buckets as `<endpoint>/<bucket>` instead of `<bucket>.<endpoint>`
+    pub force_path_style: bool,
+}
+
 /// S3-based content storage
 pub struct S3ContentStorage {
     client: S3Client,
     bucket: String,
     prefix: String,
+    /// Set once the hash index is known to cover content stored before it existed
+    hash_index_ready: AtomicBool,
 }
 
 impl S3ContentStorage {
@@ -22,6 +34,7 @@ impl S3ContentStorage {
         profile: Option<&str>,
         access_key: Option<&str>,
         secret_key: Option<&str>,
+        options: S3ClientOptions,
     ) -> ClassifyResult<Self> {
         let region = Region::new(region.to_string());
 
@@ -41,7 +54,13 @@ impl S3ContentStorage {
         }
 
         let aws_config = builder.load().await;
-        let client = S3Client::new(&aws_config);
+
+        let mut s3_config = aws_sdk_s3::config::Builder::from(&aws_config)
+            .force_path_style(options.force_path_style);
+        if let Some(endpoint_url) = &options.endpoint_url {
+            s3_config = s3_config.endpoint_url(endpoint_url);
+        }
+        let client = S3Client::from_conf(s3_config.build());
 
         match client.head_bucket().bucket(bucket).send().await {
             Ok(_) => {}
@@ -61,125 +80,274 @@ impl S3ContentStorage {
             } else {
                 format!("{}/", prefix)
             },
+            hash_index_ready: AtomicBool::new(false),
         })
     }
 
     fn get_object_key(&self, id: &str) -> String {
         format!("{}{}.json", self.prefix, id)
     }
-}
-
-#[async_trait]
-impl ContentStorage for S3ContentStorage {
-    async fn store(&self, content: &Content) -> ClassifyResult<()> {
-        let object_key = self.get_object_key(&content.id.to_string());
-        let json =
-            serde_json::to_string_pretty(content).map_err(ClassifyError::SerializationError)?;
 
-        let _put_object_response = self
-            .client
-            .put_object()
-            .bucket(&self.bucket)
-            .key(&object_key)
-            .body(ByteStream::from(json.into_bytes()))
-            .content_type("application/json")
-            .send()
-            .await
-            .map_err(|e| {
-                ClassifyError::StorageError(format!("Failed to store content in S3: {}", e))
-            })?;
+    fn get_attachments_prefix(&self, id: &str) -> String {
+        format!("{}attachments/{}/", self.prefix, id)
+    }
 
-        Ok(())
+    /// Key of the index object holding the id of the content with a hash
+    fn get_hash_key(&self, hash: &str) -> String {
+        format!("{}hash-index/{}", self.prefix, hash)
     }
 
-    async fn get(&self, id: &str) -> ClassifyResult<Option<Content>> {
-        let object_key = self.get_object_key(id);
+    /// Marker written once all content stored before the hash index existed is
+    /// indexed. Earlier backfills, marked `.complete`, only indexed the first
+    /// 1000 objects, so they are redone.
+    fn get_hash_index_marker(&self) -> String {
+        format!("{}hash-index/.backfilled", self.prefix)
+    }
 
+    async fn get_object_bytes(&self, key: &str, what: &str) -> ClassifyResult<Option<Vec<u8>>> {
         let get_object_output = match self
             .client
             .get_object()
             .bucket(&self.bucket)
-            .key(&object_key)
+            .key(key)
             .send()
             .await
         {
             Ok(output) => output,
             Err(err) => {
-                // For 404 errors, return None
                 if err.to_string().contains("NoSuchKey") {
                     return Ok(None);
                 }
                 return Err(ClassifyError::StorageError(format!(
-                    "Failed to get content from S3: {}",
-                    err
+                    "Failed to get {} from S3: {}",
+                    what, err
                 )));
             }
         };
 
-        let body = get_object_output.body;
         let mut buffer = Vec::new();
-
-        let mut stream = body.into_async_read();
+        let mut stream = get_object_output.body.into_async_read();
         stream.read_to_end(&mut buffer).await.map_err(|e| {
             ClassifyError::StorageError(format!("Failed to read S3 object body: {}", e))
         })?;
 
-        let content = serde_json::from_slice(&buffer).map_err(ClassifyError::SerializationError)?;
+        Ok(Some(buffer))
+    }
 
-        Ok(Some(content))
+    async fn put_hash_index(&self, hash: &str, id: &str) -> ClassifyResult<()> {
+        self.client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(self.get_hash_key(hash))
+            .body(ByteStream::from(id.as_bytes().to_vec()))
+            .content_type("text/plain")
+            .send()
+            .await
+            .map_err(|e| {
+                ClassifyError::StorageError(format!("Failed to update hash index in S3: {}", e))
+            })?;
+
+        Ok(())
     }
 
-    async fn list(&self) -> ClassifyResult<Vec<Content>> {
-        let list_objects_output = self
-            .client
-            .list_objects_v2()
+    async fn delete_hash_index(&self, hash: &str) -> ClassifyResult<()> {
+        self.client
+            .delete_object()
             .bucket(&self.bucket)
-            .prefix(&self.prefix)
+            .key(self.get_hash_key(hash))
             .send()
             .await
             .map_err(|e| {
-                ClassifyError::StorageError(format!("Failed to list objects in S3: {}", e))
+                ClassifyError::StorageError(format!("Failed to update hash index in S3: {}", e))
             })?;
 
-        let mut contents = Vec::new();
+        Ok(())
+    }
 
-        if let Some(objects) = list_objects_output.contents() {
-            for object in objects {
-                if let Some(key) = &object.key {
-                    if key.ends_with(".json") && key.starts_with(&self.prefix) {
-                        let id = key[self.prefix.len()..key.len() - 5].to_string();
-                        if let Some(content) = self.get(&id).await? {
-                            contents.push(content);
-                        }
-                    }
-                }
+    /// Index content stored before the hash index existed. Runs once per bucket
+    /// and prefix, later calls only check the marker.
+    async fn backfill_hash_index(&self) -> ClassifyResult<()> {
+        if self.hash_index_ready.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+
+        let marker = self.get_hash_index_marker();
+        if self
+            .get_object_bytes(&marker, "hash index")
+            .await?
+            .is_some()
+        {
+            self.hash_index_ready.store(true, Ordering::Relaxed);
+            return Ok(());
+        }
+
+        for content in self.list().await? {
+            if let Some(hash) = &content.content_hash {
+                self.put_hash_index(hash, &content.id.to_string()).await?;
             }
         }
 
-        Ok(contents)
+        self.client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(&marker)
+            .body(ByteStream::from_static(b""))
+            .send()
+            .await
+            .map_err(|e| {
+                ClassifyError::StorageError(format!("Failed to update hash index in S3: {}", e))
+            })?;
+
+        self.hash_index_ready.store(true, Ordering::Relaxed);
+        Ok(())
     }
 
-    async fn delete(&self, id: &str) -> ClassifyResult<bool> {
-        let object_key = self.get_object_key(id);
+    /// Keys of the objects under `prefix`, following continuation tokens past
+    /// the 1000 keys a single request returns. Unless `nested`, keys below a
+    /// further `/`, like the hash index and attachments, are left out.
+    async fn list_keys(
+        &self,
+        prefix: &str,
+        nested: bool,
+        what: &str,
+    ) -> ClassifyResult<Vec<String>> {
+        let mut keys = Vec::new();
+        let mut continuation_token = None;
+
+        loop {
+            let mut request = self
+                .client
+                .list_objects_v2()
+                .bucket(&self.bucket)
+                .prefix(prefix)
+                .set_continuation_token(continuation_token);
+            if !nested {
+                request = request.delimiter("/");
+            }
+
+            let output = request.send().await.map_err(|e| {
+                ClassifyError::StorageError(format!("Failed to list {} in S3: {}", what, e))
+            })?;
+
+            if let Some(objects) = output.contents() {
+                keys.extend(objects.iter().filter_map(|object| object.key.clone()));
+            }
+
+            continuation_token = output.next_continuation_token().map(String::from);
+            if continuation_token.is_none() {
+                return Ok(keys);
+            }
+        }
+    }
+
+    async fn delete_attachments(&self, id: &str) -> ClassifyResult<()> {
+        let keys = self
+            .list_keys(&self.get_attachments_prefix(id), true, "attachments")
+            .await?;
+
+        for key in keys {
+            self.client
+                .delete_object()
+                .bucket(&self.bucket)
+                .key(&key)
+                .send()
+                .await
+                .map_err(|e| {
+                    ClassifyError::StorageError(format!(
+                        "Failed to delete attachment from S3: {}",
+                        e
+                    ))
+                })?;
+        }
+
+        Ok(())
+    }
+
+    /// Delete every object under the prefix, including the hash index and
+    /// attachments, so integration tests leave the bucket as they found it
+    #[cfg(test)]
+    pub(crate) async fn delete_prefix(&self) -> ClassifyResult<()> {
+        for key in self.list_keys(&self.prefix, true, "objects").await? {
+            self.client
+                .delete_object()
+                .bucket(&self.bucket)
+                .key(&key)
+                .send()
+                .await
+                .map_err(|e| {
+                    ClassifyError::StorageError(format!("Failed to delete object from S3: {}", e))
+                })?;
+        }
 
-        let head_result = self
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ContentStorage for S3ContentStorage {
+    async fn store(&self, content: &Content) -> ClassifyResult<()> {
+        let object_key = self.get_object_key(&content.id.to_string());
+        let json =
+            serde_json::to_string_pretty(content).map_err(ClassifyError::SerializationError)?;
+
+        let _put_object_response = self
             .client
-            .head_object()
+            .put_object()
             .bucket(&self.bucket)
             .key(&object_key)
+            .body(ByteStream::from(json.into_bytes()))
+            .content_type("application/json")
             .send()
-            .await;
+            .await
+            .map_err(|e| {
+                ClassifyError::StorageError(format!("Failed to store content in S3: {}", e))
+            })?;
+
+        if let Some(hash) = &content.content_hash {
+            self.put_hash_index(hash, &content.id.to_string()).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn get(&self, id: &str) -> ClassifyResult<Option<Content>> {
+        let object_key = self.get_object_key(id);
+
+        let Some(buffer) = self.get_object_bytes(&object_key, "content").await? else {
+            return Ok(None);
+        };
 
-        if let Err(err) = head_result {
-            if err.to_string().contains("NotFound") || err.to_string().contains("404") {
-                return Ok(false);
+        let content = serde_json::from_slice(&buffer).map_err(ClassifyError::SerializationError)?;
+
+        Ok(Some(content))
+    }
+
+    async fn list(&self) -> ClassifyResult<Vec<Content>> {
+        let keys = self.list_keys(&self.prefix, false, "objects").await?;
+        let mut contents = Vec::new();
+
+        for key in keys {
+            if let Some(id) = key
+                .strip_prefix(&self.prefix)
+                .and_then(|name| name.strip_suffix(".json"))
+            {
+                if let Some(content) = self.get(id).await? {
+                    contents.push(content);
+                }
             }
-            return Err(ClassifyError::StorageError(format!(
-                "Failed to check if object exists in S3: {}",
-                err
-            )));
         }
 
+        Ok(contents)
+    }
+
+    async fn delete(&self, id: &str) -> ClassifyResult<bool> {
+        let object_key = self.get_object_key(id);
+
+        // The stored content tells which hash index entry to remove
+        let Some(content) = self.get(id).await? else {
+            return Ok(false);
+        };
+
         self.client
             .delete_object()
             .bucket(&self.bucket)
@@ -190,21 +358,57 @@ impl ContentStorage for S3ContentStorage {
                 ClassifyError::StorageError(format!("Failed to delete object from S3: {}", e))
             })?;
 
+        self.delete_attachments(id).await?;
+
+        if let Some(hash) = &content.content_hash {
+            self.delete_hash_index(hash).await?;
+        }
+
         Ok(true)
     }
 
     async fn find_by_hash(&self, hash: &str) -> ClassifyResult<Option<Content>> {
-        // S3 doesn't provide a native way to query objects by their content
-        // We need to list all objects and check each one
+        // S3 can't query objects by their content, so a small index object per
+        // hash holds the id of the content with that hash
+        self.backfill_hash_index().await?;
 
-        let all_content = self.list().await?;
+        let Some(id) = self
+            .get_object_bytes(&self.get_hash_key(hash), "hash index")
+            .await?
+            .map(|id| String::from_utf8_lossy(&id).into_owned())
+        else {
+            return Ok(None);
+        };
 
-        for content in all_content {
-            if content.content_hash.as_deref() == Some(hash) {
-                return Ok(Some(content));
-            }
+        let content = self.get(&id).await?;
+        if content.is_none() {
+            // Left behind by content that was removed without going through delete
+            self.delete_hash_index(hash).await?;
         }
 
-        Ok(None)
+        Ok(content)
+    }
+
+    async fn store_attachment(&self, id: &str, name: &str, data: &[u8]) -> ClassifyResult<()> {
+        let object_key = format!("{}{}", self.get_attachments_prefix(id), name);
+
+        self.client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(&object_key)
+            .body(ByteStream::from(data.to_vec()))
+            .send()
+            .await
+            .map_err(|e| {
+                ClassifyError::StorageError(format!("Failed to store attachment in S3: {}", e))
+            })?;
+
+        Ok(())
+    }
+
+    async fn get_attachment(&self, id: &str, name: &str) -> ClassifyResult<Option<Vec<u8>>> {
+        let object_key = format!("{}{}", self.get_attachments_prefix(id), name);
+
+        self.get_object_bytes(&object_key, "attachment").await
     }
 }
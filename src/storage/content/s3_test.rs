@@ -1,59 +1,104 @@
-// use crate::storage::ContentStorage;
-// use crate::{ClassifyResult, Content};
-// use std::env;
+use crate::storage::content::s3::{S3ClientOptions, S3ContentStorage};
+use crate::storage::ContentStorage;
+use crate::{ClassifyResult, Content};
+use std::env;
 
 #[cfg(test)]
 mod tests {
-    // use super::*;
-    // use crate::storage::content::s3::S3ContentStorage;
-
-    // #[tokio::test]
-    // #[ignore]
-    // async fn test_s3_storage_integration() -> ClassifyResult<()> {
-    //     // This test requires valid AWS credentials and a test bucket
-    //     // It's marked as 'ignore' so it doesn't run in normal test runs
-
-    //     let bucket = env::var("TEST_S3_BUCKET").expect("TEST_S3_BUCKET must be set for S3 tests");
-    //     let region = env::var("TEST_S3_REGION").expect("TEST_S3_REGION must be set for S3 tests");
-    //     let prefix = format!("test-{}/", uuid::Uuid::new_v4());
-
-    //     let storage = S3ContentStorage::new(
-    //         &bucket, &prefix, &region, None, // use default profile
-    //         None, // no explicit access key
-    //         None, // no explicit secret key
-    //     )
-    //     .await?;
-
-    //     let content = Content::new("S3 storage test content".to_string())
-    //         .with_tags(vec!["test".to_string(), "s3".to_string()]);
-    //     let content_id = content.id.to_string();
-
-    //     storage.store(&content).await?;
-
-    //     let retrieved = storage.get(&content_id).await?;
-    //     assert!(retrieved.is_some());
-    //     let retrieved = retrieved.unwrap();
-    //     assert_eq!(retrieved.id, content.id);
-    //     assert_eq!(retrieved.content, content.content);
-    //     assert_eq!(retrieved.tags, content.tags);
-
-    //     let contents = storage.list().await?;
-    //     assert_eq!(contents.len(), 1);
-
-    //     let hash = content.content_hash.as_ref().unwrap();
-    //     let found = storage.find_by_hash(hash).await?;
-    //     assert!(found.is_some());
-    //     assert_eq!(found.unwrap().id, content.id);
-
-    //     let deleted = storage.delete(&content_id).await?;
-    //     assert!(deleted);
-
-    //     let retrieved = storage.get(&content_id).await?;
-    //     assert!(retrieved.is_none());
-
-    //     let deleted = storage.delete(&content_id).await?;
-    //     assert!(!deleted);
-
-    //     Ok(())
-    // }
+    use super::*;
+
+    /// Storage under a fresh prefix in the bucket from `TEST_S3_BUCKET`
+    async fn test_storage() -> ClassifyResult<S3ContentStorage> {
+        let bucket = env::var("TEST_S3_BUCKET").expect("TEST_S3_BUCKET must be set for S3 tests");
+        let region = env::var("TEST_S3_REGION").unwrap_or_else(|_| "us-east-1".to_string());
+        let prefix = format!("test-{}/", uuid::Uuid::new_v4());
+        let endpoint_url = env::var("TEST_S3_ENDPOINT_URL").ok();
+
+        S3ContentStorage::new(
+            &bucket,
+            &prefix,
+            &region,
+            None, // use default profile
+            env::var("TEST_S3_ACCESS_KEY_ID").ok().as_deref(),
+            env::var("TEST_S3_SECRET_ACCESS_KEY").ok().as_deref(),
+            S3ClientOptions {
+                force_path_style: endpoint_url.is_some(),
+                endpoint_url,
+            },
+        )
+        .await
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_s3_storage_integration() -> ClassifyResult<()> {
+        // This test requires an S3 bucket, either on AWS or in a local
+        // S3-compatible store such as MinIO or LocalStack (TEST_S3_ENDPOINT_URL)
+        // It's marked as 'ignore' so it doesn't run in normal test runs
+
+        let storage = test_storage().await?;
+
+        let content = Content::new("S3 storage test content".to_string())
+            .with_tags(vec!["test".to_string(), "s3".to_string()]);
+        let content_id = content.id.to_string();
+
+        storage.store(&content).await?;
+
+        let retrieved = storage.get(&content_id).await?;
+        assert!(retrieved.is_some());
+        let retrieved = retrieved.unwrap();
+        assert_eq!(retrieved.id, content.id);
+        assert_eq!(retrieved.content, content.content);
+        assert_eq!(retrieved.tags, content.tags);
+
+        let contents = storage.list().await?;
+        assert_eq!(contents.len(), 1);
+
+        let hash = content.content_hash.as_ref().unwrap();
+        let found = storage.find_by_hash(hash).await?;
+        assert!(found.is_some());
+        assert_eq!(found.unwrap().id, content.id);
+
+        let deleted = storage.delete(&content_id).await?;
+        assert!(deleted);
+
+        let retrieved = storage.get(&content_id).await?;
+        assert!(retrieved.is_none());
+        assert!(storage.find_by_hash(hash).await?.is_none());
+
+        let deleted = storage.delete(&content_id).await?;
+        assert!(!deleted);
+
+        storage.delete_prefix().await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_s3_list_beyond_one_page() -> ClassifyResult<()> {
+        // S3 returns at most 1000 keys per request, and the hash index objects
+        // written next to the content must not take up any of them
+        let storage = test_storage().await?;
+
+        for i in 0..1005 {
+            storage
+                .store(&Content::new(format!("S3 paging test content {}", i)))
+                .await?;
+        }
+
+        let listed = storage.list().await?.len();
+
+        let last = Content::new("S3 paging test content 1004".to_string());
+        let hash = last.content_hash.as_ref().unwrap();
+        let found = storage.find_by_hash(hash).await?;
+
+        // Clean up before asserting, so a failure doesn't leave 1005 objects behind
+        storage.delete_prefix().await?;
+
+        assert_eq!(listed, 1005);
+        assert!(found.is_some());
+
+        Ok(())
+    }
 }
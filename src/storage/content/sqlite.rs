@@ -0,0 +1,210 @@
+use async_trait::async_trait;
+use rusqlite::{params, OptionalExtension};
+
+use crate::storage::sqlite::{open, run, SharedConnection};
+use crate::storage::ContentStorage;
+use crate::{ClassifyResult, Content};
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS contents (
+        id TEXT PRIMARY KEY,
+        content_hash TEXT,
+        document TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS contents_content_hash_idx ON contents (content_hash);
+    CREATE INDEX IF NOT EXISTS contents_slug_idx ON contents (json_extract(document, '$.slug'));
+    CREATE TABLE IF NOT EXISTS content_attachments (
+        content_id TEXT NOT NULL REFERENCES contents (id) ON DELETE CASCADE,
+        name TEXT NOT NULL,
+        data BLOB NOT NULL,
+        PRIMARY KEY (content_id, name)
+    );
+";
+
+/// Embedded content storage in a SQLite database file, keeping each content
+/// item as a JSON document like the Postgres storage
+pub struct SqliteContentStorage {
+    connection: SharedConnection,
+}
+
+impl SqliteContentStorage {
+    pub async fn new(path: &str) -> ClassifyResult<Self> {
+        Self::with_connection(open(path)?).await
+    }
+
+    /// Use a connection shared with other storages
+    pub async fn with_connection(connection: SharedConnection) -> ClassifyResult<Self> {
+        crate::storage::connections::register("sqlite", &connection);
+        run(&connection, "create content tables", |connection| {
+            connection.execute_batch(SCHEMA)
+        })
+        .await?;
+
+        Ok(Self { connection })
+    }
+}
+
+fn parse_document(document: &str) -> ClassifyResult<Content> {
+    Ok(serde_json::from_str(document)?)
+}
+
+fn parse_documents(documents: Vec<String>) -> ClassifyResult<Vec<Content>> {
+    documents
+        .iter()
+        .map(|document| parse_document(document))
+        .collect()
+}
+
+#[async_trait]
+impl ContentStorage for SqliteContentStorage {
+    async fn store(&self, content: &Content) -> ClassifyResult<()> {
+        let document = serde_json::to_string(content)?;
+        let id = content.id.to_string();
+        let content_hash = content.content_hash.clone();
+
+        run(&self.connection, "store content", move |connection| {
+            connection.execute(
+                "INSERT INTO contents (id, content_hash, document) VALUES (?1, ?2, ?3)
+                 ON CONFLICT (id) DO UPDATE
+                 SET content_hash = excluded.content_hash, document = excluded.document",
+                params![id, content_hash, document],
+            )
+        })
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get(&self, id: &str) -> ClassifyResult<Option<Content>> {
+        let id = id.to_string();
+
+        let document = run(&self.connection, "get content", move |connection| {
+            connection
+                .query_row("SELECT document FROM contents WHERE id = ?1", [id], |row| {
+                    row.get::<_, String>(0)
+                })
+                .optional()
+        })
+        .await?;
+
+        document.as_deref().map(parse_document).transpose()
+    }
+
+    async fn get_many(&self, ids: &[String]) -> ClassifyResult<Vec<Content>> {
+        // The ids are passed as one JSON array, expanded by `json_each`
+        let ids = serde_json::to_string(ids)?;
+
+        let documents = run(&self.connection, "get content", move |connection| {
+            connection
+                .prepare(
+                    "SELECT document FROM contents
+                     WHERE id IN (SELECT value FROM json_each(?1))",
+                )?
+                .query_map([ids], |row| row.get(0))?
+                .collect()
+        })
+        .await?;
+
+        parse_documents(documents)
+    }
+
+    async fn list(&self) -> ClassifyResult<Vec<Content>> {
+        let documents = run(&self.connection, "list content", |connection| {
+            connection
+                .prepare("SELECT document FROM contents")?
+                .query_map([], |row| row.get(0))?
+                .collect()
+        })
+        .await?;
+
+        parse_documents(documents)
+    }
+
+    async fn delete(&self, id: &str) -> ClassifyResult<bool> {
+        let id = id.to_string();
+
+        // Attachments are removed by the foreign key cascade
+        let deleted = run(&self.connection, "delete content", move |connection| {
+            connection.execute("DELETE FROM contents WHERE id = ?1", [id])
+        })
+        .await?;
+
+        Ok(deleted > 0)
+    }
+
+    async fn find_by_hash(&self, hash: &str) -> ClassifyResult<Option<Content>> {
+        let hash = hash.to_string();
+
+        let document = run(
+            &self.connection,
+            "find content by hash",
+            move |connection| {
+                connection
+                    .query_row(
+                        "SELECT document FROM contents WHERE content_hash = ?1 LIMIT 1",
+                        [hash],
+                        |row| row.get::<_, String>(0),
+                    )
+                    .optional()
+            },
+        )
+        .await?;
+
+        document.as_deref().map(parse_document).transpose()
+    }
+
+    async fn find_by_slug(&self, slug: &str) -> ClassifyResult<Option<Content>> {
+        let slug = slug.to_string();
+
+        let document = run(
+            &self.connection,
+            "find content by slug",
+            move |connection| {
+                connection
+                    .query_row(
+                        "SELECT document FROM contents
+                     WHERE json_extract(document, '$.slug') = ?1 LIMIT 1",
+                        [slug],
+                        |row| row.get::<_, String>(0),
+                    )
+                    .optional()
+            },
+        )
+        .await?;
+
+        document.as_deref().map(parse_document).transpose()
+    }
+
+    async fn store_attachment(&self, id: &str, name: &str, data: &[u8]) -> ClassifyResult<()> {
+        let id = id.to_string();
+        let name = name.to_string();
+        let data = data.to_vec();
+
+        run(&self.connection, "store attachment", move |connection| {
+            connection.execute(
+                "INSERT INTO content_attachments (content_id, name, data) VALUES (?1, ?2, ?3)
+                 ON CONFLICT (content_id, name) DO UPDATE SET data = excluded.data",
+                params![id, name, data],
+            )
+        })
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_attachment(&self, id: &str, name: &str) -> ClassifyResult<Option<Vec<u8>>> {
+        let id = id.to_string();
+        let name = name.to_string();
+
+        run(&self.connection, "get attachment", move |connection| {
+            connection
+                .query_row(
+                    "SELECT data FROM content_attachments WHERE content_id = ?1 AND name = ?2",
+                    [id, name],
+                    |row| row.get(0),
+                )
+                .optional()
+        })
+        .await
+    }
+}
@@ -0,0 +1,75 @@
+use std::fs;
+use uuid::Uuid;
+
+use crate::storage::content::sqlite::SqliteContentStorage;
+use crate::storage::ContentStorage;
+use crate::{ClassifyResult, Content};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_sqlite_content_storage() -> ClassifyResult<()> {
+        let test_dir = format!("./test_data_{}", Uuid::new_v4());
+        let path = format!("{}/classify.db", test_dir);
+
+        let content =
+            Content::new("SQLite storage test".to_string()).with_tags(vec!["sqlite".to_string()]);
+        let id = content.id.to_string();
+        {
+            let storage = SqliteContentStorage::new(&path).await?;
+
+            storage.store(&content).await?;
+            // Storing again replaces the stored item
+            storage.store(&content).await?;
+            storage
+                .store_attachment(&id, "snapshot.html", b"<html></html>")
+                .await?;
+
+            assert_eq!(storage.get(&id).await?.unwrap().content, content.content);
+            assert_eq!(storage.list().await?.len(), 1);
+            assert_eq!(
+                storage
+                    .get_many(&[id.clone(), "missing".to_string()])
+                    .await?
+                    .len(),
+                1
+            );
+            assert_eq!(
+                storage
+                    .find_by_hash(content.content_hash.as_deref().unwrap())
+                    .await?
+                    .map(|found| found.id),
+                Some(content.id)
+            );
+            assert_eq!(
+                storage
+                    .find_by_slug(content.slug.as_deref().unwrap())
+                    .await?
+                    .map(|found| found.id),
+                Some(content.id)
+            );
+            assert_eq!(
+                storage.get_attachment(&id, "snapshot.html").await?,
+                Some(b"<html></html>".to_vec())
+            );
+        }
+
+        // Content survives reopening the database, deleting it removes its attachments
+        let storage = SqliteContentStorage::new(&path).await?;
+        assert_eq!(storage.get(&id).await?.unwrap().tags, content.tags);
+        assert!(storage.delete(&id).await?);
+        assert!(!storage.delete(&id).await?);
+        assert!(storage.get(&id).await?.is_none());
+        assert!(storage
+            .get_attachment(&id, "snapshot.html")
+            .await?
+            .is_none());
+        drop(storage);
+
+        fs::remove_dir_all(&test_dir).unwrap();
+
+        Ok(())
+    }
+}
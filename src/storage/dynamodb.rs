@@ -0,0 +1,39 @@
+use aws_sdk_dynamodb::types::AttributeValue;
+use aws_sdk_dynamodb::{config::Region, Client};
+use std::collections::HashMap;
+
+use crate::{ClassifyError, ClassifyResult};
+
+pub type Item = HashMap<String, AttributeValue>;
+
+/// Create a DynamoDB client using the default AWS credential chain
+/// (environment, profile or the IAM role of the function or task)
+pub async fn create_client(region: Option<&str>) -> ClassifyResult<Client> {
+    let mut builder = aws_config::from_env();
+    if let Some(region) = region {
+        builder = builder.region(Region::new(region.to_string()));
+    }
+
+    Ok(Client::new(&builder.load().await))
+}
+
+/// Check that a table exists and is reachable with the configured credentials
+pub async fn check_table(client: &Client, table: &str) -> ClassifyResult<()> {
+    client
+        .describe_table()
+        .table_name(table)
+        .send()
+        .await
+        .map_err(|e| {
+            ClassifyError::StorageError(format!(
+                "Failed to access DynamoDB table '{}': {}",
+                table, e
+            ))
+        })?;
+
+    Ok(())
+}
+
+pub fn string_attribute<'a>(item: &'a Item, name: &str) -> Option<&'a String> {
+    item.get(name).and_then(|value| value.as_s().ok())
+}
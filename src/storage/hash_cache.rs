@@ -0,0 +1,49 @@
+use lru::LruCache;
+use std::num::NonZeroUsize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Remembers content hashes that were recently looked up and not found, so
+/// repeated duplicate checks for new content skip the storage round trip.
+///
+/// Entries expire after `ttl` to pick up content stored by other instances,
+/// and are removed as soon as content with the hash is stored here.
+pub struct HashLookupCache {
+    misses: Mutex<LruCache<String, Instant>>,
+    ttl: Duration,
+}
+
+impl HashLookupCache {
+    pub fn new(capacity: NonZeroUsize, ttl: Duration) -> Self {
+        Self {
+            misses: Mutex::new(LruCache::new(capacity)),
+            ttl,
+        }
+    }
+
+    /// Whether a recent lookup found no content with this hash
+    pub fn is_known_missing(&self, hash: &str) -> bool {
+        let mut misses = self.misses.lock().unwrap();
+        match misses.get(hash) {
+            Some(checked_at) if checked_at.elapsed() < self.ttl => true,
+            Some(_) => {
+                misses.pop(hash);
+                false
+            }
+            None => false,
+        }
+    }
+
+    /// Record that no content with this hash exists
+    pub fn record_missing(&self, hash: &str) {
+        self.misses
+            .lock()
+            .unwrap()
+            .put(hash.to_string(), Instant::now());
+    }
+
+    /// Forget a hash once content with it is stored
+    pub fn invalidate(&self, hash: &str) {
+        self.misses.lock().unwrap().pop(hash);
+    }
+}
@@ -0,0 +1,46 @@
+use crate::storage::hash_cache::HashLookupCache;
+use std::num::NonZeroUsize;
+use std::time::Duration;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache(capacity: usize, ttl: Duration) -> HashLookupCache {
+        HashLookupCache::new(NonZeroUsize::new(capacity).unwrap(), ttl)
+    }
+
+    #[test]
+    fn test_records_and_invalidates_misses() {
+        let cache = cache(10, Duration::from_secs(60));
+
+        assert!(!cache.is_known_missing("abc"));
+        cache.record_missing("abc");
+        assert!(cache.is_known_missing("abc"));
+
+        cache.invalidate("abc");
+        assert!(!cache.is_known_missing("abc"));
+    }
+
+    #[test]
+    fn test_misses_expire() {
+        let cache = cache(10, Duration::ZERO);
+
+        cache.record_missing("abc");
+        assert!(!cache.is_known_missing("abc"));
+    }
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let cache = cache(2, Duration::from_secs(60));
+
+        cache.record_missing("a");
+        cache.record_missing("b");
+        assert!(cache.is_known_missing("a"));
+        cache.record_missing("c");
+
+        assert!(cache.is_known_missing("a"));
+        assert!(!cache.is_known_missing("b"));
+        assert!(cache.is_known_missing("c"));
+    }
+}
@@ -0,0 +1,226 @@
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+use crate::storage::{ContentStorage, TagStorage};
+use crate::{ClassifyResult, Content};
+
+/// Times storage operations for the `storage_operation_seconds` histogram and
+/// logs the ones slower than the threshold
+#[derive(Debug, Clone)]
+pub struct StorageInstrumentation {
+    /// Content or tag storage
+    storage: &'static str,
+    /// Backend behind the storage, e.g. `redis`
+    backend: String,
+    slow_threshold: Option<Duration>,
+}
+
+impl StorageInstrumentation {
+    pub fn new(storage: &'static str, backend: &str, slow_threshold: Option<Duration>) -> Self {
+        Self {
+            storage,
+            backend: backend.to_string(),
+            slow_threshold,
+        }
+    }
+
+    pub async fn measure<T>(
+        &self,
+        operation: &'static str,
+        future: impl Future<Output = ClassifyResult<T>>,
+    ) -> ClassifyResult<T> {
+        let started = Instant::now();
+        let result = future.await;
+        let elapsed = started.elapsed();
+
+        let labels = [
+            ("storage", self.storage.to_string()),
+            ("backend", self.backend.clone()),
+            ("operation", operation.to_string()),
+        ];
+        metrics::histogram!("storage_operation_seconds", &labels).record(elapsed.as_secs_f64());
+        if result.is_err() {
+            metrics::counter!("storage_operation_errors_total", &labels).increment(1);
+        }
+
+        if self
+            .slow_threshold
+            .is_some_and(|threshold| elapsed >= threshold)
+        {
+            warn!(
+                "Slow {} storage operation: {} on {} took {}ms",
+                self.storage,
+                operation,
+                self.backend,
+                elapsed.as_millis()
+            );
+        }
+
+        result
+    }
+}
+
+/// Content storage that records metrics for every operation
+pub struct InstrumentedContentStorage {
+    inner: Arc<dyn ContentStorage>,
+    instrumentation: StorageInstrumentation,
+}
+
+impl InstrumentedContentStorage {
+    pub fn new(
+        inner: Arc<dyn ContentStorage>,
+        backend: &str,
+        slow_threshold: Option<Duration>,
+    ) -> Self {
+        Self {
+            inner,
+            instrumentation: StorageInstrumentation::new("content", backend, slow_threshold),
+        }
+    }
+}
+
+#[async_trait]
+impl ContentStorage for InstrumentedContentStorage {
+    async fn store(&self, content: &Content) -> ClassifyResult<()> {
+        self.instrumentation
+            .measure("store", self.inner.store(content))
+            .await
+    }
+
+    async fn get(&self, id: &str) -> ClassifyResult<Option<Content>> {
+        self.instrumentation
+            .measure("get", self.inner.get(id))
+            .await
+    }
+
+    async fn list(&self) -> ClassifyResult<Vec<Content>> {
+        self.instrumentation
+            .measure("list", self.inner.list())
+            .await
+    }
+
+    async fn delete(&self, id: &str) -> ClassifyResult<bool> {
+        self.instrumentation
+            .measure("delete", self.inner.delete(id))
+            .await
+    }
+
+    async fn find_by_hash(&self, hash: &str) -> ClassifyResult<Option<Content>> {
+        self.instrumentation
+            .measure("find_by_hash", self.inner.find_by_hash(hash))
+            .await
+    }
+
+    async fn find_by_slug(&self, slug: &str) -> ClassifyResult<Option<Content>> {
+        self.instrumentation
+            .measure("find_by_slug", self.inner.find_by_slug(slug))
+            .await
+    }
+
+    async fn slug_exists(&self, slug: &str) -> ClassifyResult<bool> {
+        self.instrumentation
+            .measure("slug_exists", self.inner.slug_exists(slug))
+            .await
+    }
+
+    async fn store_attachment(&self, id: &str, name: &str, data: &[u8]) -> ClassifyResult<()> {
+        self.instrumentation
+            .measure(
+                "store_attachment",
+                self.inner.store_attachment(id, name, data),
+            )
+            .await
+    }
+
+    async fn get_attachment(&self, id: &str, name: &str) -> ClassifyResult<Option<Vec<u8>>> {
+        self.instrumentation
+            .measure("get_attachment", self.inner.get_attachment(id, name))
+            .await
+    }
+
+    async fn get_many(&self, ids: &[String]) -> ClassifyResult<Vec<Content>> {
+        self.instrumentation
+            .measure("get_many", self.inner.get_many(ids))
+            .await
+    }
+
+    async fn health_check(&self) -> ClassifyResult<()> {
+        self.instrumentation
+            .measure("health_check", self.inner.health_check())
+            .await
+    }
+}
+
+/// Tag storage that records metrics for every operation
+pub struct InstrumentedTagStorage {
+    inner: Arc<dyn TagStorage>,
+    instrumentation: StorageInstrumentation,
+}
+
+impl InstrumentedTagStorage {
+    pub fn new(
+        inner: Arc<dyn TagStorage>,
+        backend: &str,
+        slow_threshold: Option<Duration>,
+    ) -> Self {
+        Self {
+            inner,
+            instrumentation: StorageInstrumentation::new("tag", backend, slow_threshold),
+        }
+    }
+}
+
+#[async_trait]
+impl TagStorage for InstrumentedTagStorage {
+    async fn add_tags(&self, content_id: &str, tags: &[String]) -> ClassifyResult<()> {
+        self.instrumentation
+            .measure("add_tags", self.inner.add_tags(content_id, tags))
+            .await
+    }
+
+    async fn get_tags(&self, content_id: &str) -> ClassifyResult<Vec<String>> {
+        self.instrumentation
+            .measure("get_tags", self.inner.get_tags(content_id))
+            .await
+    }
+
+    async fn list_tags(&self) -> ClassifyResult<Vec<String>> {
+        self.instrumentation
+            .measure("list_tags", self.inner.list_tags())
+            .await
+    }
+
+    async fn find_by_tag(&self, tag: &str) -> ClassifyResult<Vec<String>> {
+        self.instrumentation
+            .measure("find_by_tag", self.inner.find_by_tag(tag))
+            .await
+    }
+
+    async fn remove_tags(&self, content_id: &str, tags: &[String]) -> ClassifyResult<()> {
+        self.instrumentation
+            .measure("remove_tags", self.inner.remove_tags(content_id, tags))
+            .await
+    }
+
+    async fn tag_counts(&self) -> ClassifyResult<HashMap<String, usize>> {
+        self.instrumentation
+            .measure("tag_counts", self.inner.tag_counts())
+            .await
+    }
+
+    async fn find_by_all_tags(&self, tags: &[String]) -> ClassifyResult<Vec<String>> {
+        self.instrumentation
+            .measure("find_by_all_tags", self.inner.find_by_all_tags(tags))
+            .await
+    }
+
+    async fn health_check(&self) -> ClassifyResult<()> {
+        self.instrumentation
+            .measure("health_check", self.inner.health_check())
+            .await
+    }
+}
@@ -0,0 +1,43 @@
+use crate::storage::content::memory::MemoryContentStorage;
+use crate::storage::instrumented::{InstrumentedContentStorage, InstrumentedTagStorage};
+use crate::storage::tag::memory::MemoryTagStorage;
+use crate::storage::{ContentStorage, TagStorage};
+use crate::Content;
+use metrics_exporter_prometheus::PrometheusBuilder;
+use std::sync::Arc;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_operations_are_forwarded_and_timed() {
+        let recorder = PrometheusBuilder::new().build_recorder();
+        let handle = recorder.handle();
+        let _guard = metrics::set_default_local_recorder(&recorder);
+
+        let content_storage =
+            InstrumentedContentStorage::new(Arc::new(MemoryContentStorage::new()), "memory", None);
+        let tag_storage =
+            InstrumentedTagStorage::new(Arc::new(MemoryTagStorage::new()), "memory", None);
+
+        let content = Content::new("Instrumented".to_string());
+        let id = content.id.to_string();
+        content_storage.store(&content).await.unwrap();
+        tag_storage
+            .add_tags(&id, &["rust".to_string()])
+            .await
+            .unwrap();
+
+        assert!(content_storage.get(&id).await.unwrap().is_some());
+        assert_eq!(tag_storage.find_by_tag("rust").await.unwrap(), vec![id]);
+
+        let rendered = handle.render();
+        assert!(rendered.contains(
+            r#"storage_operation_seconds_count{storage="content",backend="memory",operation="get"} 1"#
+        ));
+        assert!(rendered.contains(
+            r#"storage_operation_seconds_count{storage="tag",backend="memory",operation="find_by_tag"} 1"#
+        ));
+    }
+}
@@ -4,6 +4,7 @@ use std::sync::Arc;
 use uuid::Uuid;
 
 use crate::storage::content::filesystem::FilesystemContentStorage;
+#[cfg(feature = "redis")]
 use crate::storage::tag::redis::RedisTagStorage;
 use crate::storage::{ContentStorage, TagStorage};
 use crate::ClassifyResult;
@@ -119,19 +120,25 @@ mod tests {
         assert_eq!(tag1_content_ids.len(), 2);
 
         let mut retrieved_content = Vec::new();
-        for id in tag1_content_ids {
-            if let Some(content) = content_storage.get(&id).await? {
+        for id in &tag1_content_ids {
+            if let Some(content) = content_storage.get(id).await? {
                 retrieved_content.push(content);
             }
         }
 
         assert_eq!(retrieved_content.len(), 2);
 
+        // Batched lookup leaves out ids that aren't stored
+        let mut ids = tag1_content_ids.clone();
+        ids.push(Uuid::new_v4().to_string());
+        assert_eq!(content_storage.get_many(&ids).await?.len(), 2);
+
         cleanup_test_dir(test_dir);
 
         Ok(())
     }
 
+    #[cfg(feature = "redis")]
     #[tokio::test]
     #[ignore]
     async fn test_real_redis_integration() -> ClassifyResult<()> {
@@ -139,7 +146,8 @@ mod tests {
 
         let content_storage = Arc::new(FilesystemContentStorage::new(test_dir.to_str().unwrap())?);
 
-        let tag_storage = Arc::new(RedisTagStorage::new("redis://localhost", None).await?);
+        let tag_storage =
+            Arc::new(RedisTagStorage::new("redis://localhost", &Default::default(), None).await?);
 
         let content = Content::new("Real Redis integration test".to_string());
         let content_id = content.id.to_string();
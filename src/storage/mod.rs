@@ -1,11 +1,40 @@
+pub mod api_key;
+pub mod connections;
 pub mod content;
+#[cfg(feature = "dynamodb")]
+pub mod dynamodb;
+pub mod hash_cache;
+pub mod instrumented;
+pub mod namespace;
+pub mod postgres;
+pub mod purge;
+#[cfg(feature = "redis")]
+pub mod redis;
+pub mod sqlite;
 pub mod tag;
+pub mod transaction;
+pub mod usage;
+pub mod vector;
 
+#[cfg(test)]
+mod hash_cache_test;
+#[cfg(test)]
+mod instrumented_test;
 #[cfg(test)]
 mod integration_test;
+#[cfg(test)]
+mod namespace_test;
+#[cfg(test)]
+mod purge_test;
+#[cfg(all(test, feature = "redis"))]
+mod redis_test;
+#[cfg(test)]
+mod transaction_test;
 
-use crate::{ClassifyError, ClassifyResult, Content};
+use crate::{ApiKey, ClassifyError, ClassifyResult, Content, Usage};
 use async_trait::async_trait;
+use futures::future::try_join_all;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
 /// ContentStorage trait for storing and retrieving content
@@ -16,8 +45,53 @@ pub trait ContentStorage: Send + Sync {
     async fn list(&self) -> ClassifyResult<Vec<Content>>;
     async fn delete(&self, id: &str) -> ClassifyResult<bool>;
     async fn find_by_hash(&self, hash: &str) -> ClassifyResult<Option<Content>>;
+    /// Store a named binary attachment (e.g. a page snapshot) alongside content
+    async fn store_attachment(&self, id: &str, name: &str, data: &[u8]) -> ClassifyResult<()>;
+    /// Retrieve a named attachment, if one was stored
+    async fn get_attachment(&self, id: &str, name: &str) -> ClassifyResult<Option<Vec<u8>>>;
+
+    /// Retrieve several content items at once, leaving out ids that aren't stored.
+    /// The default runs individual lookups concurrently; backends that can fetch
+    /// many items in one request override it.
+    async fn get_many(&self, ids: &[String]) -> ClassifyResult<Vec<Content>> {
+        let mut contents = Vec::with_capacity(ids.len());
+        for chunk in ids.chunks(GET_MANY_CONCURRENCY) {
+            let mut lookups = Vec::with_capacity(chunk.len());
+            for id in chunk {
+                lookups.push(self.get(id));
+            }
+            contents.extend(try_join_all(lookups).await?.into_iter().flatten());
+        }
+
+        Ok(contents)
+    }
+
+    /// Look up content by its slug. The default scans all content; backends
+    /// that keep a slug index override it.
+    async fn find_by_slug(&self, slug: &str) -> ClassifyResult<Option<Content>> {
+        Ok(self
+            .list()
+            .await?
+            .into_iter()
+            .find(|content| content.slug.as_deref() == Some(slug)))
+    }
+
+    /// Whether any content has the slug, including content the storage doesn't
+    /// show to its caller. Used to keep new slugs unique.
+    async fn slug_exists(&self, slug: &str) -> ClassifyResult<bool> {
+        Ok(self.find_by_slug(slug).await?.is_some())
+    }
+
+    /// Check that the storage can be reached. The default looks up content that
+    /// doesn't exist; backends with a cheaper ping override it.
+    async fn health_check(&self) -> ClassifyResult<()> {
+        self.get(&uuid::Uuid::nil().to_string()).await.map(|_| ())
+    }
 }
 
+/// Number of concurrent lookups in the default `get_many`
+const GET_MANY_CONCURRENCY: usize = 16;
+
 /// TagStorage trait for storing and retrieving tags
 #[async_trait]
 pub trait TagStorage: Send + Sync {
@@ -26,6 +100,122 @@ pub trait TagStorage: Send + Sync {
     async fn list_tags(&self) -> ClassifyResult<Vec<String>>;
     async fn find_by_tag(&self, tag: &str) -> ClassifyResult<Vec<String>>;
     async fn remove_tags(&self, content_id: &str, tags: &[String]) -> ClassifyResult<()>;
+
+    /// Number of content items per tag. Backends override this with a cheaper
+    /// count than looking up every tag.
+    async fn tag_counts(&self) -> ClassifyResult<HashMap<String, usize>> {
+        let mut counts = HashMap::new();
+        for tag in self.list_tags().await? {
+            let count = self.find_by_tag(&tag).await?.len();
+            counts.insert(tag, count);
+        }
+        Ok(counts)
+    }
+
+    /// Content with `tag` or a tag below it in the hierarchy, so `lang` also
+    /// finds content tagged `lang/rust`. Backends that can look up tags by
+    /// prefix override it.
+    async fn find_by_tag_tree(&self, tag: &str) -> ClassifyResult<Vec<String>> {
+        let mut ids = self.find_by_tag(tag).await?;
+        for child in self.list_tags().await? {
+            if child == tag || !crate::tags::is_within(&child, tag) {
+                continue;
+            }
+            for id in self.find_by_tag(&child).await? {
+                if !ids.contains(&id) {
+                    ids.push(id);
+                }
+            }
+        }
+        Ok(ids)
+    }
+
+    /// Content with every one of `tags`, each matching the tags below it too.
+    /// The default intersects the lookups of the tags; backends that can
+    /// intersect sets themselves override it.
+    async fn find_by_all_tags(&self, tags: &[String]) -> ClassifyResult<Vec<String>> {
+        let mut ids: Option<HashSet<String>> = None;
+        for tag in tags {
+            let found = self.find_by_tag_tree(tag).await?.into_iter();
+            let matching: HashSet<String> = match ids {
+                Some(ids) => found.filter(|id| ids.contains(id)).collect(),
+                None => found.collect(),
+            };
+            if matching.is_empty() {
+                return Ok(Vec::new());
+            }
+            ids = Some(matching);
+        }
+        Ok(ids.unwrap_or_default().into_iter().collect())
+    }
+
+    /// Check that the storage can be reached, by default with a lookup of the
+    /// tags of content that doesn't exist
+    async fn health_check(&self) -> ClassifyResult<()> {
+        self.get_tags(&uuid::Uuid::nil().to_string())
+            .await
+            .map(|_| ())
+    }
+}
+
+/// Writes content together with its tags in a single atomic operation, for
+/// backends that serve both over one connection
+#[async_trait]
+pub trait AtomicStorage: Send + Sync {
+    async fn store_with_tags(&self, content: &Content, tags: &[String]) -> ClassifyResult<()>;
+}
+
+/// Content and tag storage served by a single backend
+pub struct SharedStorage {
+    pub content_storage: Arc<dyn ContentStorage>,
+    pub tag_storage: Arc<dyn TagStorage>,
+    /// Set when the backend can write content and tags atomically
+    pub atomic_storage: Option<Arc<dyn AtomicStorage>>,
+}
+
+/// ApiKeyStorage trait for API keys created at runtime
+#[async_trait]
+pub trait ApiKeyStorage: Send + Sync {
+    /// Insert or replace a key
+    async fn save(&self, key: &ApiKey) -> ClassifyResult<()>;
+    async fn get(&self, id: &str) -> ClassifyResult<Option<ApiKey>>;
+    async fn find_by_hash(&self, key_hash: &str) -> ClassifyResult<Option<ApiKey>>;
+    async fn list(&self) -> ClassifyResult<Vec<ApiKey>>;
+}
+
+/// UsageStorage trait for monthly usage counters per account
+#[async_trait]
+pub trait UsageStorage: Send + Sync {
+    /// Add to the usage of an account in a month (`YYYY-MM`)
+    async fn add(&self, account: &str, month: &str, usage: &Usage) -> ClassifyResult<()>;
+    async fn get(&self, account: &str, month: &str) -> ClassifyResult<Usage>;
+    async fn list(&self, month: &str) -> ClassifyResult<HashMap<String, Usage>>;
+}
+
+/// VectorStorage trait for the embedding vectors of stored content, by content ID
+#[async_trait]
+pub trait VectorStorage: Send + Sync {
+    /// Insert or replace the vector of content
+    async fn store(&self, id: &str, stored: &vector::StoredVector) -> ClassifyResult<()>;
+    async fn delete(&self, id: &str) -> ClassifyResult<()>;
+
+    /// IDs of all content with a vector
+    async fn ids(&self) -> ClassifyResult<HashSet<String>>;
+
+    /// All vectors by content ID, only of the tenant when `namespace` is set
+    async fn vectors(
+        &self,
+        namespace: Option<&str>,
+    ) -> ClassifyResult<Vec<(String, vector::StoredVector)>>;
+
+    /// Content closest to `vector` by cosine similarity, the most similar first,
+    /// only of the tenant when `namespace` is set
+    async fn nearest(
+        &self,
+        vector: &[f32],
+        namespace: Option<&str>,
+        limit: usize,
+    ) -> ClassifyResult<Vec<(String, f32)>>;
 }
 
 /// Content storage factory
@@ -39,7 +229,57 @@ pub async fn create_content_storage(
                 content::filesystem::FilesystemContentStorage::new(&config.content_storage_path)?;
             Ok(Arc::new(storage))
         }
+        #[cfg(feature = "dynamodb")]
+        crate::config::StorageType::DynamoDb => {
+            let table = config.dynamodb_table.as_deref().ok_or_else(|| {
+                ClassifyError::ConfigError(
+                    "DYNAMODB_CONTENT_TABLE is required for DynamoDB storage".to_string(),
+                )
+            })?;
+
+            let storage = content::dynamodb::DynamoDbContentStorage::new(
+                table,
+                config.dynamodb_region.as_deref(),
+            )
+            .await?;
+            Ok(Arc::new(storage))
+        }
+        #[cfg(not(feature = "dynamodb"))]
+        crate::config::StorageType::DynamoDb => Err(ClassifyError::ConfigError(
+            "DynamoDB storage requires building with the dynamodb feature".to_string(),
+        )),
+        crate::config::StorageType::Memory => {
+            Ok(Arc::new(content::memory::MemoryContentStorage::new()))
+        }
+        crate::config::StorageType::Postgres => {
+            let postgres_url = config.postgres_url.as_deref().ok_or_else(|| {
+                ClassifyError::ConfigError(
+                    "POSTGRES_URL is required for Postgres storage".to_string(),
+                )
+            })?;
+
+            let storage = content::postgres::PostgresContentStorage::new(postgres_url).await?;
+            Ok(Arc::new(storage))
+        }
+        #[cfg(feature = "redis")]
         crate::config::StorageType::Redis => {
+            let options = redis::RedisConnectOptions {
+                username: config.redis_username.clone(),
+                password: config.redis_password.clone(),
+                db: config.redis_db,
+                tls_insecure: config.redis_tls_insecure,
+            };
+
+            if let Some(sentinel) = &config.redis_sentinel {
+                let connection = redis::connect_sentinel(sentinel, &options).await?;
+                return Ok(Arc::new(
+                    content::redis::RedisContentStorage::with_connection(
+                        connection,
+                        config.redis_prefix.as_deref(),
+                    ),
+                ));
+            }
+
             // Get the Redis URL, using the tag storage Redis URL as a fallback
             let redis_url = config.redis_url.as_deref().ok_or_else(|| {
                 ClassifyError::ConfigError(
@@ -50,13 +290,18 @@ pub async fn create_content_storage(
             // Create Redis content storage
             let storage = content::redis::RedisContentStorage::new(
                 redis_url,
-                config.redis_password.as_deref(),
+                &options,
                 config.redis_prefix.as_deref(),
             )
             .await?;
 
             Ok(Arc::new(storage))
         }
+        #[cfg(not(feature = "redis"))]
+        crate::config::StorageType::Redis => Err(ClassifyError::ConfigError(
+            "Redis storage requires building with the redis feature".to_string(),
+        )),
+        #[cfg(feature = "s3")]
         crate::config::StorageType::S3 => {
             // Validate S3 configuration
             let bucket = config.s3_bucket.as_deref().ok_or_else(|| {
@@ -78,11 +323,25 @@ pub async fn create_content_storage(
                 config.s3_profile.as_deref(),
                 config.s3_access_key.as_deref(),
                 config.s3_secret_key.as_deref(),
+                content::s3::S3ClientOptions {
+                    endpoint_url: config.s3_endpoint_url.clone(),
+                    force_path_style: config.s3_force_path_style,
+                },
             )
             .await?;
 
             Ok(Arc::new(storage))
         }
+        #[cfg(not(feature = "s3"))]
+        crate::config::StorageType::S3 => Err(ClassifyError::ConfigError(
+            "S3 storage requires building with the s3 feature".to_string(),
+        )),
+        crate::config::StorageType::Sqlite => Ok(Arc::new(
+            content::sqlite::SqliteContentStorage::new(&config.sqlite_path).await?,
+        )),
+        crate::config::StorageType::Custom(name) => {
+            crate::registry::create_content_storage(name, config).await
+        }
     }
 }
 
@@ -92,13 +351,297 @@ pub async fn create_tag_storage(
     config: &crate::config::TagStorageConfig,
 ) -> ClassifyResult<Arc<dyn TagStorage>> {
     match storage_type {
+        #[cfg(feature = "dynamodb")]
+        crate::config::TagStorageType::DynamoDb => {
+            let table = config.dynamodb_table.as_deref().ok_or_else(|| {
+                ClassifyError::ConfigError(
+                    "DYNAMODB_TAG_TABLE is required for DynamoDB tag storage".to_string(),
+                )
+            })?;
+
+            let storage =
+                tag::dynamodb::DynamoDbTagStorage::new(table, config.dynamodb_region.as_deref())
+                    .await?;
+            Ok(Arc::new(storage))
+        }
+        #[cfg(not(feature = "dynamodb"))]
+        crate::config::TagStorageType::DynamoDb => Err(ClassifyError::ConfigError(
+            "DynamoDB tag storage requires building with the dynamodb feature".to_string(),
+        )),
+        crate::config::TagStorageType::Embedded => Ok(Arc::new(tag::redb::RedbTagStorage::new(
+            &config.tag_storage_path,
+        )?)),
+        crate::config::TagStorageType::Memory => Ok(Arc::new(tag::memory::MemoryTagStorage::new())),
+        #[cfg(feature = "redis")]
         crate::config::TagStorageType::Redis => {
+            let options = redis::RedisConnectOptions {
+                username: config.redis_username.clone(),
+                password: config.redis_password.clone(),
+                db: config.redis_db,
+                tls_insecure: config.redis_tls_insecure,
+            };
+
+            if let Some(sentinel) = &config.redis_sentinel {
+                let connection = redis::connect_sentinel(sentinel, &options).await?;
+                return Ok(Arc::new(tag::redis::RedisTagStorage::with_connection(
+                    connection,
+                    config.redis_prefix.as_deref(),
+                )));
+            }
+
             let storage = tag::redis::RedisTagStorage::new(
                 &config.redis_url,
-                config.redis_password.as_deref(),
+                &options,
+                config.redis_prefix.as_deref(),
+            )
+            .await?;
+            Ok(Arc::new(storage))
+        }
+        #[cfg(not(feature = "redis"))]
+        crate::config::TagStorageType::Redis => Err(ClassifyError::ConfigError(
+            "Redis tag storage requires building with the redis feature".to_string(),
+        )),
+        crate::config::TagStorageType::Postgres => {
+            let postgres_url = config.postgres_url.as_deref().ok_or_else(|| {
+                ClassifyError::ConfigError(
+                    "POSTGRES_URL is required for Postgres tag storage".to_string(),
+                )
+            })?;
+
+            let storage = tag::postgres::PostgresTagStorage::new(postgres_url).await?;
+            Ok(Arc::new(storage))
+        }
+        crate::config::TagStorageType::Sqlite => Ok(Arc::new(
+            tag::sqlite::SqliteTagStorage::new(&config.sqlite_path).await?,
+        )),
+        crate::config::TagStorageType::Custom(name) => {
+            crate::registry::create_tag_storage(name, config).await
+        }
+    }
+}
+
+/// Create content and tag storage served by a single backend over one shared connection
+pub async fn create_shared_storage(
+    backend: &crate::config::StorageBackend,
+    config: &crate::config::AppConfig,
+) -> ClassifyResult<SharedStorage> {
+    match backend {
+        #[cfg(feature = "redis")]
+        crate::config::StorageBackend::Redis => {
+            let options = redis::RedisConnectOptions {
+                username: config.tag_storage.redis_username.clone(),
+                password: config.tag_storage.redis_password.clone(),
+                db: config.tag_storage.redis_db,
+                tls_insecure: config.tag_storage.redis_tls_insecure,
+            };
+            let connection = match &config.tag_storage.redis_sentinel {
+                Some(sentinel) => redis::connect_sentinel(sentinel, &options).await?,
+                None => {
+                    let connection =
+                        redis::connect(&config.tag_storage.redis_url, &options).await?;
+                    Arc::new(tokio::sync::Mutex::new(connection))
+                }
+            };
+
+            let content_storage = Arc::new(content::redis::RedisContentStorage::with_connection(
+                connection.clone(),
+                config.storage.redis_prefix.as_deref(),
+            ));
+            let tag_storage = Arc::new(tag::redis::RedisTagStorage::with_connection(
+                connection.clone(),
+                config.tag_storage.redis_prefix.as_deref(),
+            ));
+            let atomic_storage = redis::RedisAtomicStorage::new(
+                connection,
+                content_storage.clone(),
+                tag_storage.clone(),
+            );
+
+            Ok(SharedStorage {
+                content_storage,
+                tag_storage,
+                atomic_storage: Some(Arc::new(atomic_storage)),
+            })
+        }
+        #[cfg(not(feature = "redis"))]
+        crate::config::StorageBackend::Redis => Err(ClassifyError::ConfigError(
+            "The Redis backend requires building with the redis feature".to_string(),
+        )),
+        crate::config::StorageBackend::Postgres => {
+            let postgres_url = config.tag_storage.postgres_url.as_deref().ok_or_else(|| {
+                ClassifyError::ConfigError(
+                    "POSTGRES_URL is required for the Postgres backend".to_string(),
+                )
+            })?;
+
+            let client = Arc::new(tokio::sync::Mutex::new(
+                postgres::connect(postgres_url).await?,
+            ));
+
+            let content_storage =
+                content::postgres::PostgresContentStorage::with_client(client.clone()).await?;
+            let tag_storage = tag::postgres::PostgresTagStorage::with_client(client).await?;
+
+            Ok(SharedStorage {
+                content_storage: Arc::new(content_storage),
+                tag_storage: Arc::new(tag_storage),
+                atomic_storage: None,
+            })
+        }
+        crate::config::StorageBackend::Memory => Ok(SharedStorage {
+            content_storage: Arc::new(content::memory::MemoryContentStorage::new()),
+            tag_storage: Arc::new(tag::memory::MemoryTagStorage::new()),
+            atomic_storage: None,
+        }),
+    }
+}
+
+/// API key storage factory, reusing the tag storage connection settings
+pub async fn create_api_key_storage(
+    config: &crate::config::AppConfig,
+) -> ClassifyResult<Arc<dyn ApiKeyStorage>> {
+    match config.api.key_storage {
+        crate::config::ApiKeyStorageType::File => Ok(Arc::new(
+            api_key::file::FileApiKeyStorage::new(&config.api.key_storage_path).await?,
+        )),
+        crate::config::ApiKeyStorageType::Memory => {
+            Ok(Arc::new(api_key::memory::MemoryApiKeyStorage::new()))
+        }
+        #[cfg(feature = "redis")]
+        crate::config::ApiKeyStorageType::Redis => {
+            let options = redis::RedisConnectOptions {
+                username: config.tag_storage.redis_username.clone(),
+                password: config.tag_storage.redis_password.clone(),
+                db: config.tag_storage.redis_db,
+                tls_insecure: config.tag_storage.redis_tls_insecure,
+            };
+
+            if let Some(sentinel) = &config.tag_storage.redis_sentinel {
+                let connection = redis::connect_sentinel(sentinel, &options).await?;
+                return Ok(Arc::new(
+                    api_key::redis::RedisApiKeyStorage::with_connection(
+                        connection,
+                        config.tag_storage.redis_prefix.as_deref(),
+                    ),
+                ));
+            }
+
+            let storage = api_key::redis::RedisApiKeyStorage::new(
+                &config.tag_storage.redis_url,
+                &options,
+                config.tag_storage.redis_prefix.as_deref(),
+            )
+            .await?;
+            Ok(Arc::new(storage))
+        }
+        #[cfg(not(feature = "redis"))]
+        crate::config::ApiKeyStorageType::Redis => Err(ClassifyError::ConfigError(
+            "Redis API key storage requires building with the redis feature".to_string(),
+        )),
+        crate::config::ApiKeyStorageType::Postgres => {
+            let postgres_url = config.tag_storage.postgres_url.as_deref().ok_or_else(|| {
+                ClassifyError::ConfigError(
+                    "POSTGRES_URL is required for Postgres API key storage".to_string(),
+                )
+            })?;
+
+            let storage = api_key::postgres::PostgresApiKeyStorage::new(postgres_url).await?;
+            Ok(Arc::new(storage))
+        }
+    }
+}
+
+/// Create the usage storage, kept in the same backend as the API keys
+pub async fn create_usage_storage(
+    config: &crate::config::AppConfig,
+) -> ClassifyResult<Arc<dyn UsageStorage>> {
+    match config.api.key_storage {
+        crate::config::ApiKeyStorageType::File => Ok(Arc::new(
+            usage::file::FileUsageStorage::new(&config.api.usage_storage_path).await?,
+        )),
+        crate::config::ApiKeyStorageType::Memory => {
+            Ok(Arc::new(usage::memory::MemoryUsageStorage::new()))
+        }
+        #[cfg(feature = "redis")]
+        crate::config::ApiKeyStorageType::Redis => {
+            let options = redis::RedisConnectOptions {
+                username: config.tag_storage.redis_username.clone(),
+                password: config.tag_storage.redis_password.clone(),
+                db: config.tag_storage.redis_db,
+                tls_insecure: config.tag_storage.redis_tls_insecure,
+            };
+
+            if let Some(sentinel) = &config.tag_storage.redis_sentinel {
+                let connection = redis::connect_sentinel(sentinel, &options).await?;
+                return Ok(Arc::new(usage::redis::RedisUsageStorage::with_connection(
+                    connection,
+                    config.tag_storage.redis_prefix.as_deref(),
+                )));
+            }
+
+            let storage = usage::redis::RedisUsageStorage::new(
+                &config.tag_storage.redis_url,
+                &options,
+                config.tag_storage.redis_prefix.as_deref(),
             )
             .await?;
             Ok(Arc::new(storage))
-        } // Add more tag storage types as needed
+        }
+        #[cfg(not(feature = "redis"))]
+        crate::config::ApiKeyStorageType::Redis => Err(ClassifyError::ConfigError(
+            "Redis usage storage requires building with the redis feature".to_string(),
+        )),
+        crate::config::ApiKeyStorageType::Postgres => {
+            let postgres_url = config.tag_storage.postgres_url.as_deref().ok_or_else(|| {
+                ClassifyError::ConfigError(
+                    "POSTGRES_URL is required for Postgres usage storage".to_string(),
+                )
+            })?;
+
+            let storage = usage::postgres::PostgresUsageStorage::new(postgres_url).await?;
+            Ok(Arc::new(storage))
+        }
+    }
+}
+
+/// Create the vector storage for embeddings, set by `VECTOR_STORAGE_TYPE`
+pub async fn create_vector_storage(
+    config: &crate::config::AppConfig,
+    embedding: &crate::config::EmbeddingConfig,
+) -> ClassifyResult<Arc<dyn VectorStorage>> {
+    match embedding.storage_type {
+        crate::config::VectorStorageType::Memory => {
+            Ok(Arc::new(vector::memory::MemoryVectorStorage::new()))
+        }
+        #[cfg(feature = "redis")]
+        crate::config::VectorStorageType::Redis => Ok(Arc::new(
+            vector::redis::RedisVectorStorage::with_connection(
+                redis::connect_tag_storage(&config.tag_storage).await?,
+                config.tag_storage.redis_prefix.as_deref(),
+            ),
+        )),
+        #[cfg(not(feature = "redis"))]
+        crate::config::VectorStorageType::Redis => Err(ClassifyError::ConfigError(
+            "Redis vector storage requires building with the redis feature".to_string(),
+        )),
+        crate::config::VectorStorageType::Postgres => {
+            let postgres_url = config.tag_storage.postgres_url.as_deref().ok_or_else(|| {
+                ClassifyError::ConfigError(
+                    "POSTGRES_URL is required for Postgres vector storage".to_string(),
+                )
+            })?;
+
+            let storage = vector::postgres::PostgresVectorStorage::new(postgres_url).await?;
+            Ok(Arc::new(storage))
+        }
+        crate::config::VectorStorageType::Qdrant => {
+            let qdrant = embedding.qdrant.as_ref().ok_or_else(|| {
+                ClassifyError::ConfigError(
+                    "QDRANT_URL is required for Qdrant vector storage".to_string(),
+                )
+            })?;
+
+            Ok(Arc::new(vector::qdrant::QdrantVectorStorage::new(qdrant)))
+        }
     }
 }
@@ -0,0 +1,310 @@
+use async_trait::async_trait;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use crate::storage::{AtomicStorage, ContentStorage, TagStorage};
+use crate::{ClassifyError, ClassifyResult, Content, Visibility};
+
+/// Separates the namespace from the tag in namespaced tag index entries
+const TAG_SEPARATOR: &str = "::";
+
+/// Tag index of public content, searched by all tenants. Not a valid namespace,
+/// so it can't clash with a tenant.
+pub const PUBLIC_NAMESPACE: &str = "*";
+
+/// Content storage that only sees the content of one namespace
+pub struct NamespacedContentStorage {
+    inner: Arc<dyn ContentStorage>,
+    namespace: String,
+}
+
+impl NamespacedContentStorage {
+    pub fn new(inner: Arc<dyn ContentStorage>, namespace: &str) -> Self {
+        Self {
+            inner,
+            namespace: namespace.to_string(),
+        }
+    }
+
+    fn owns(&self, content: &Content) -> bool {
+        content.namespace.as_deref() == Some(self.namespace.as_str())
+    }
+
+    /// Content of the namespace, and content of others that is public or shared with it
+    fn sees(&self, content: &Content) -> bool {
+        let shares = share_namespaces(content);
+        self.owns(content) || shares.contains(PUBLIC_NAMESPACE) || shares.contains(&self.namespace)
+    }
+
+    async fn get_owned(&self, id: &str) -> ClassifyResult<Option<Content>> {
+        Ok(self
+            .inner
+            .get(id)
+            .await?
+            .filter(|content| self.owns(content)))
+    }
+}
+
+#[async_trait]
+impl ContentStorage for NamespacedContentStorage {
+    async fn store(&self, content: &Content) -> ClassifyResult<()> {
+        if !self.owns(content) {
+            return Err(ClassifyError::StorageError(format!(
+                "Content {} is not in namespace {}",
+                content.id, self.namespace
+            )));
+        }
+
+        // Content with the same id in another namespace must not be overwritten
+        if let Some(existing) = self.inner.get(&content.id.to_string()).await? {
+            if !self.owns(&existing) {
+                return Err(ClassifyError::StorageError(format!(
+                    "Content {} is not in namespace {}",
+                    content.id, self.namespace
+                )));
+            }
+        }
+
+        self.inner.store(content).await
+    }
+
+    async fn get(&self, id: &str) -> ClassifyResult<Option<Content>> {
+        Ok(self
+            .inner
+            .get(id)
+            .await?
+            .filter(|content| self.sees(content)))
+    }
+
+    async fn get_many(&self, ids: &[String]) -> ClassifyResult<Vec<Content>> {
+        let mut contents = self.inner.get_many(ids).await?;
+        contents.retain(|content| self.sees(content));
+        Ok(contents)
+    }
+
+    async fn list(&self) -> ClassifyResult<Vec<Content>> {
+        let mut contents = self.inner.list().await?;
+        contents.retain(|content| self.sees(content));
+        Ok(contents)
+    }
+
+    async fn delete(&self, id: &str) -> ClassifyResult<bool> {
+        if self.get_owned(id).await?.is_none() {
+            return Ok(false);
+        }
+        self.inner.delete(id).await
+    }
+
+    async fn find_by_hash(&self, hash: &str) -> ClassifyResult<Option<Content>> {
+        // Hashes of namespaced content include the namespace, see Content::with_namespace
+        Ok(self
+            .inner
+            .find_by_hash(hash)
+            .await?
+            .filter(|content| self.owns(content)))
+    }
+
+    async fn find_by_slug(&self, slug: &str) -> ClassifyResult<Option<Content>> {
+        Ok(self
+            .inner
+            .find_by_slug(slug)
+            .await?
+            .filter(|content| self.sees(content)))
+    }
+
+    async fn slug_exists(&self, slug: &str) -> ClassifyResult<bool> {
+        // Slugs are unique across namespaces, so content of others counts too
+        self.inner.slug_exists(slug).await
+    }
+
+    async fn store_attachment(&self, id: &str, name: &str, data: &[u8]) -> ClassifyResult<()> {
+        if self.get_owned(id).await?.is_none() {
+            return Err(ClassifyError::StorageError(format!(
+                "Content {} is not in namespace {}",
+                id, self.namespace
+            )));
+        }
+        self.inner.store_attachment(id, name, data).await
+    }
+
+    async fn get_attachment(&self, id: &str, name: &str) -> ClassifyResult<Option<Vec<u8>>> {
+        if self.get(id).await?.is_none() {
+            return Ok(None);
+        }
+        self.inner.get_attachment(id, name).await
+    }
+}
+
+/// Tag storage that keeps the tags of one namespace apart by prefixing them
+/// with `<namespace>::` in the underlying storage
+pub struct NamespacedTagStorage {
+    inner: Arc<dyn TagStorage>,
+    prefix: String,
+    public_prefix: String,
+}
+
+impl NamespacedTagStorage {
+    pub fn new(inner: Arc<dyn TagStorage>, namespace: &str) -> Self {
+        Self {
+            inner,
+            prefix: format!("{}{}", namespace, TAG_SEPARATOR),
+            public_prefix: format!("{}{}", PUBLIC_NAMESPACE, TAG_SEPARATOR),
+        }
+    }
+
+    fn scope(&self, tags: &[String]) -> Vec<String> {
+        tags.iter()
+            .map(|tag| format!("{}{}", self.prefix, tag))
+            .collect()
+    }
+
+    fn unscope(&self, tag: &str) -> Option<String> {
+        tag.strip_prefix(&self.prefix).map(str::to_string)
+    }
+}
+
+#[async_trait]
+impl TagStorage for NamespacedTagStorage {
+    async fn add_tags(&self, content_id: &str, tags: &[String]) -> ClassifyResult<()> {
+        self.inner.add_tags(content_id, &self.scope(tags)).await
+    }
+
+    async fn get_tags(&self, content_id: &str) -> ClassifyResult<Vec<String>> {
+        let mut tags = Vec::new();
+        for tag in self.inner.get_tags(content_id).await? {
+            // Public content of other tenants is only indexed under the public prefix
+            let tag = self
+                .unscope(&tag)
+                .or_else(|| tag.strip_prefix(&self.public_prefix).map(str::to_string));
+            if let Some(tag) = tag.filter(|tag| !tags.contains(tag)) {
+                tags.push(tag);
+            }
+        }
+        Ok(tags)
+    }
+
+    async fn list_tags(&self) -> ClassifyResult<Vec<String>> {
+        Ok(self
+            .inner
+            .list_tags()
+            .await?
+            .iter()
+            .filter_map(|tag| self.unscope(tag))
+            .collect())
+    }
+
+    async fn find_by_tag(&self, tag: &str) -> ClassifyResult<Vec<String>> {
+        let mut ids = self
+            .inner
+            .find_by_tag(&format!("{}{}", self.prefix, tag))
+            .await?;
+
+        for id in self
+            .inner
+            .find_by_tag(&format!("{}{}", self.public_prefix, tag))
+            .await?
+        {
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+
+        Ok(ids)
+    }
+
+    async fn remove_tags(&self, content_id: &str, tags: &[String]) -> ClassifyResult<()> {
+        self.inner.remove_tags(content_id, &self.scope(tags)).await
+    }
+
+    async fn tag_counts(&self) -> ClassifyResult<HashMap<String, usize>> {
+        Ok(self
+            .inner
+            .tag_counts()
+            .await?
+            .into_iter()
+            .filter_map(|(tag, count)| Some((self.unscope(&tag)?, count)))
+            .collect())
+    }
+}
+
+/// Atomic writes of namespaced content, with the tags prefixed like [`NamespacedTagStorage`]
+pub struct NamespacedAtomicStorage {
+    inner: Arc<dyn AtomicStorage>,
+    tag_storage: NamespacedTagStorage,
+    namespace: String,
+}
+
+impl NamespacedAtomicStorage {
+    pub fn new(
+        inner: Arc<dyn AtomicStorage>,
+        tag_storage: Arc<dyn TagStorage>,
+        namespace: &str,
+    ) -> Self {
+        Self {
+            inner,
+            tag_storage: NamespacedTagStorage::new(tag_storage, namespace),
+            namespace: namespace.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl AtomicStorage for NamespacedAtomicStorage {
+    async fn store_with_tags(&self, content: &Content, tags: &[String]) -> ClassifyResult<()> {
+        if content.namespace.as_deref() != Some(self.namespace.as_str()) {
+            return Err(ClassifyError::StorageError(format!(
+                "Content {} is not in namespace {}",
+                content.id, self.namespace
+            )));
+        }
+
+        self.inner
+            .store_with_tags(content, &self.tag_storage.scope(tags))
+            .await
+    }
+}
+
+/// Namespaces whose tag index lists content of another tenant: those it is
+/// shared with, and the public index for public content
+pub fn share_namespaces(content: &Content) -> HashSet<String> {
+    let mut namespaces: HashSet<String> = match content.visibility {
+        Visibility::Private => return HashSet::new(),
+        Visibility::Team => HashSet::new(),
+        Visibility::Public => HashSet::from([PUBLIC_NAMESPACE.to_string()]),
+    };
+
+    namespaces.extend(
+        content
+            .shared_with
+            .iter()
+            .filter(|namespace| Some(namespace.as_str()) != content.namespace.as_deref())
+            .cloned(),
+    );
+    namespaces
+}
+
+/// Update the tag indexes of other namespaces after the access to content changed
+/// from `previous`. `tag_storage` is the unscoped tag storage.
+pub async fn update_shares(
+    tag_storage: Arc<dyn TagStorage>,
+    previous: &Content,
+    content: &Content,
+) -> ClassifyResult<()> {
+    let before = share_namespaces(previous);
+    let after = share_namespaces(content);
+    let id = content.id.to_string();
+
+    for namespace in before.difference(&after) {
+        NamespacedTagStorage::new(tag_storage.clone(), namespace)
+            .remove_tags(&id, &previous.tags)
+            .await?;
+    }
+
+    for namespace in after.difference(&before) {
+        NamespacedTagStorage::new(tag_storage.clone(), namespace)
+            .add_tags(&id, &content.tags)
+            .await?;
+    }
+
+    Ok(())
+}
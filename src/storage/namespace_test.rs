@@ -0,0 +1,153 @@
+use crate::classifier::Classifier;
+use crate::ClassifyResult;
+use mockall::mock;
+
+mock! {
+    pub ClassifierMock {}
+
+    #[async_trait::async_trait]
+    impl Classifier for ClassifierMock {
+        async fn classify(&self, content: &str) -> ClassifyResult<Vec<String>>;
+        async fn classify_url(&self, url: &str) -> ClassifyResult<Vec<String>>;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::AppState;
+    use crate::ingest::{ingest, Ingested};
+    use crate::storage::content::memory::MemoryContentStorage;
+    use crate::storage::namespace::update_shares;
+    use crate::storage::tag::memory::MemoryTagStorage;
+    use crate::Visibility;
+    use std::sync::Arc;
+
+    fn root_state() -> AppState {
+        let mut classifier = MockClassifierMock::new();
+        classifier
+            .expect_classify()
+            .returning(|_| Ok(vec!["shared".to_string()]));
+
+        AppState::new(
+            Arc::new(classifier),
+            Arc::new(MemoryContentStorage::new()),
+            Arc::new(MemoryTagStorage::new()),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_tenants_only_see_their_own_content() -> ClassifyResult<()> {
+        let root = root_state();
+        let acme = root.for_namespace(Some("acme"));
+        let globex = root.for_namespace(Some("globex"));
+
+        let Ingested::Created(acme_content) = ingest(&acme, "Same text".to_string()).await? else {
+            panic!("Expected new content for acme");
+        };
+        // The same text in another namespace is not a duplicate
+        let Ingested::Created(globex_content) = ingest(&globex, "Same text".to_string()).await?
+        else {
+            panic!("Expected new content for globex");
+        };
+        assert!(matches!(
+            ingest(&acme, "Same text".to_string()).await?,
+            Ingested::Duplicate(_)
+        ));
+
+        let acme_id = acme_content.id.to_string();
+        let globex_id = globex_content.id.to_string();
+
+        assert_eq!(acme.content_storage.list().await?.len(), 1);
+        assert!(acme.content_storage.get(&globex_id).await?.is_none());
+        assert!(!acme.content_storage.delete(&globex_id).await?);
+        assert!(globex.content_storage.get(&globex_id).await?.is_some());
+
+        assert_eq!(acme.tag_storage.list_tags().await?, vec!["shared"]);
+        assert_eq!(acme.tag_storage.get_tags(&acme_id).await?, vec!["shared"]);
+        assert_eq!(
+            acme.tag_storage.find_by_tag("shared").await?,
+            vec![acme_id.clone()]
+        );
+
+        // The unscoped state sees everything
+        assert_eq!(root.content_storage.list().await?.len(), 2);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_tenant_cannot_overwrite_other_namespace() {
+        let root = root_state();
+        let acme = root.for_namespace(Some("acme"));
+        let globex = root.for_namespace(Some("globex"));
+
+        let Ok(Ingested::Created(content)) = ingest(&acme, "Acme only".to_string()).await else {
+            panic!("Expected new content");
+        };
+
+        let mut hijacked = content.clone();
+        hijacked.namespace = Some("globex".to_string());
+
+        assert!(globex.content_storage.store(&hijacked).await.is_err());
+        assert!(globex.content_storage.store(&content).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_shared_and_public_content_is_visible_to_other_tenants() -> ClassifyResult<()> {
+        let root = root_state();
+        let acme = root.for_namespace(Some("acme"));
+        let globex = root.for_namespace(Some("globex"));
+        let initech = root.for_namespace(Some("initech"));
+
+        let Ingested::Created(content) = ingest(&acme, "Acme report".to_string()).await? else {
+            panic!("Expected new content");
+        };
+        let id = content.id.to_string();
+
+        let mut shared = content.clone();
+        shared.shared_with = vec!["globex".to_string()];
+        acme.content_storage.store(&shared).await?;
+        update_shares(root.tag_storage.clone(), &content, &shared).await?;
+
+        assert!(globex.content_storage.get(&id).await?.is_some());
+        assert_eq!(
+            globex.tag_storage.find_by_tag("shared").await?,
+            vec![id.clone()]
+        );
+        assert!(initech.content_storage.get(&id).await?.is_none());
+        assert!(initech.tag_storage.find_by_tag("shared").await?.is_empty());
+
+        // Shared content stays read-only for the other tenant
+        assert!(!globex.content_storage.delete(&id).await?);
+        assert!(globex.content_storage.store(&shared).await.is_err());
+
+        let mut public = shared.clone();
+        public.visibility = Visibility::Public;
+        public.shared_with.clear();
+        acme.content_storage.store(&public).await?;
+        update_shares(root.tag_storage.clone(), &shared, &public).await?;
+
+        assert!(initech.content_storage.get(&id).await?.is_some());
+        assert_eq!(
+            initech.tag_storage.find_by_tag("shared").await?,
+            vec![id.clone()]
+        );
+        assert_eq!(initech.tag_storage.get_tags(&id).await?, vec!["shared"]);
+        // Public content is not in the tag lists of the other tenants
+        assert!(initech.tag_storage.list_tags().await?.is_empty());
+        // The owner sees its tags once
+        assert_eq!(acme.tag_storage.get_tags(&id).await?, vec!["shared"]);
+
+        let mut private = public.clone();
+        private.visibility = Visibility::Private;
+        acme.content_storage.store(&private).await?;
+        update_shares(root.tag_storage.clone(), &public, &private).await?;
+
+        assert!(globex.content_storage.get(&id).await?.is_none());
+        assert!(initech.tag_storage.find_by_tag("shared").await?.is_empty());
+        assert!(acme.content_storage.get(&id).await?.is_some());
+
+        Ok(())
+    }
+}
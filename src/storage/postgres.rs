@@ -0,0 +1,34 @@
+use postgres_native_tls::MakeTlsConnector;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tokio_native_tls::native_tls;
+use tokio_postgres::Client;
+use tracing::error;
+
+use crate::{ClassifyError, ClassifyResult};
+
+/// A Postgres client shared between storages
+pub type SharedClient = Arc<Mutex<Client>>;
+
+/// Connect to Postgres, driving the connection in a background task
+pub async fn connect(postgres_url: &str) -> ClassifyResult<Client> {
+    let connector = native_tls::TlsConnector::new().map_err(|e| {
+        ClassifyError::StorageError(format!("Failed to create TLS connector: {}", e))
+    })?;
+
+    // TLS is used when the server supports it, unless the URL sets sslmode
+    let (client, connection) =
+        tokio_postgres::connect(postgres_url, MakeTlsConnector::new(connector))
+            .await
+            .map_err(|e| {
+                ClassifyError::StorageError(format!("Failed to connect to Postgres: {}", e))
+            })?;
+
+    tokio::spawn(async move {
+        if let Err(e) = connection.await {
+            error!("Postgres connection error: {}", e);
+        }
+    });
+
+    Ok(client)
+}
@@ -0,0 +1,113 @@
+use chrono::Utc;
+use tracing::{info, warn};
+
+use crate::api::{AppState, SNAPSHOT_ATTACHMENT};
+use crate::{ClassifyError, ClassifyResult, Content, PurgeRequest, PurgeResponse, PurgedContent};
+
+/// Whether content matches every criterion of a purge request
+pub fn matches_purge(request: &PurgeRequest, content: &Content) -> bool {
+    if let Some(hash) = &request.hash {
+        if content.content_hash.as_ref() != Some(hash) {
+            return false;
+        }
+    }
+
+    if let Some(pattern) = &request.url_pattern {
+        if !content.is_url() || !glob_match(pattern, &content.content) {
+            return false;
+        }
+    }
+
+    if let Some(key) = &request.metadata_key {
+        match (content.metadata.get(key), &request.metadata_value) {
+            (None, _) => return false,
+            (Some(value), Some(expected)) if value != expected => return false,
+            _ => {}
+        }
+    }
+
+    true
+}
+
+/// Match text against a pattern where `*` matches any run of characters
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    // split always yields at least one part
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // No wildcard, the pattern must match exactly
+        return rest.is_empty();
+    };
+
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+
+    rest.ends_with(last)
+}
+
+/// Hard-delete all content matching the request together with its tag index
+/// entries and snapshot, returning an attestation of what was removed
+pub async fn purge(state: &AppState, request: &PurgeRequest) -> ClassifyResult<PurgeResponse> {
+    if request.hash.is_none() && request.url_pattern.is_none() && request.metadata_key.is_none() {
+        return Err(ClassifyError::ConfigError(
+            "A purge needs a hash, URL pattern or metadata key".to_string(),
+        ));
+    }
+
+    let contents = state.content_storage.list().await?;
+    let mut purged = Vec::new();
+
+    for content in contents
+        .iter()
+        .filter(|content| matches_purge(request, content))
+    {
+        let id = content.id.to_string();
+        let tags = state.tag_storage.get_tags(&id).await?;
+        let snapshot = state
+            .content_storage
+            .get_attachment(&id, SNAPSHOT_ATTACHMENT)
+            .await?
+            .is_some();
+
+        if !state
+            .storage_transaction()
+            .delete_with_tags(&id, &tags)
+            .await?
+        {
+            warn!("Content {} disappeared before it could be purged", id);
+            continue;
+        }
+
+        // Its embedding would still match searches and recommendations
+        if let Some(index) = &state.semantic_index {
+            index.remove(&id).await;
+        }
+
+        info!("Purged content {}", id);
+        purged.push(PurgedContent {
+            id,
+            content_hash: content.content_hash.clone(),
+            tags,
+            snapshot,
+        });
+    }
+
+    info!("Purge finished, {} items removed", purged.len());
+
+    Ok(PurgeResponse {
+        count: purged.len(),
+        purged,
+        purged_at: Utc::now(),
+        success: true,
+        error: None,
+    })
+}
@@ -0,0 +1,182 @@
+use crate::classifier::Classifier;
+use crate::embedding::index::SemanticIndex;
+use crate::embedding::Embedder;
+use crate::storage::purge::{glob_match, matches_purge, purge};
+use crate::storage::vector::memory::MemoryVectorStorage;
+use crate::storage::VectorStorage;
+use crate::{ClassifyResult, Content, PurgeRequest};
+use mockall::mock;
+use std::collections::HashMap;
+
+mock! {
+    pub ClassifierMock {}
+
+    #[async_trait::async_trait]
+    impl Classifier for ClassifierMock {
+        async fn classify(&self, content: &str) -> ClassifyResult<Vec<String>>;
+        async fn classify_url(&self, url: &str) -> ClassifyResult<Vec<String>>;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{AppState, SNAPSHOT_ATTACHMENT};
+    use crate::storage::content::memory::MemoryContentStorage;
+    use crate::storage::tag::memory::MemoryTagStorage;
+    use std::sync::Arc;
+
+    /// Embeds every text the same, only whether a vector is kept matters
+    struct ConstantEmbedder;
+
+    #[async_trait::async_trait]
+    impl Embedder for ConstantEmbedder {
+        async fn embed(&self, _text: &str) -> ClassifyResult<Vec<f32>> {
+            Ok(vec![1.0])
+        }
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match(
+            "https://example.com/*",
+            "https://example.com/post"
+        ));
+        assert!(glob_match("*example.com*", "https://www.example.com/post"));
+        assert!(glob_match("https://*/post", "https://example.com/post"));
+        assert!(glob_match("exact", "exact"));
+        assert!(!glob_match("exact", "exactly"));
+        assert!(!glob_match(
+            "https://example.com/*",
+            "https://other.com/post"
+        ));
+        assert!(!glob_match("*/a/*/a", "https://x/a/b"));
+    }
+
+    #[test]
+    fn test_criteria_must_all_match() {
+        let content = Content::new("https://example.com/post".to_string())
+            .with_metadata(HashMap::from([("author".to_string(), "alice".to_string())]));
+
+        let by_author = PurgeRequest {
+            metadata_key: Some("author".to_string()),
+            ..Default::default()
+        };
+        assert!(matches_purge(&by_author, &content));
+
+        let by_other_author = PurgeRequest {
+            metadata_value: Some("bob".to_string()),
+            ..by_author.clone()
+        };
+        assert!(!matches_purge(&by_other_author, &content));
+
+        let by_author_and_url = PurgeRequest {
+            url_pattern: Some("https://other.com/*".to_string()),
+            ..by_author
+        };
+        assert!(!matches_purge(&by_author_and_url, &content));
+    }
+
+    #[tokio::test]
+    async fn test_purge_removes_content_tags_and_snapshot() -> ClassifyResult<()> {
+        let state = AppState::new(
+            Arc::new(MockClassifierMock::new()),
+            Arc::new(MemoryContentStorage::new()),
+            Arc::new(MemoryTagStorage::new()),
+        );
+
+        let purged = Content::new("https://example.com/private".to_string())
+            .with_tags(vec!["private".to_string()]);
+        let kept = Content::new("https://other.com/public".to_string())
+            .with_tags(vec!["public".to_string()]);
+
+        for content in [&purged, &kept] {
+            state
+                .storage_transaction()
+                .store_with_tags(content, &content.tags)
+                .await?;
+        }
+        state
+            .content_storage
+            .store_attachment(&purged.id.to_string(), SNAPSHOT_ATTACHMENT, b"<html>")
+            .await?;
+
+        let response = purge(
+            &state,
+            &PurgeRequest {
+                url_pattern: Some("https://example.com/*".to_string()),
+                ..Default::default()
+            },
+        )
+        .await?;
+
+        assert_eq!(response.count, 1);
+        assert_eq!(response.purged[0].id, purged.id.to_string());
+        assert_eq!(response.purged[0].content_hash, purged.content_hash);
+        assert_eq!(response.purged[0].tags, vec!["private".to_string()]);
+        assert!(response.purged[0].snapshot);
+
+        assert!(state
+            .content_storage
+            .get(&purged.id.to_string())
+            .await?
+            .is_none());
+        assert!(state
+            .content_storage
+            .get_attachment(&purged.id.to_string(), SNAPSHOT_ATTACHMENT)
+            .await?
+            .is_none());
+        assert!(state.tag_storage.find_by_tag("private").await?.is_empty());
+        assert!(state
+            .content_storage
+            .get(&kept.id.to_string())
+            .await?
+            .is_some());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_purge_removes_embeddings() -> ClassifyResult<()> {
+        let vectors = Arc::new(MemoryVectorStorage::new());
+        let state = AppState::new(
+            Arc::new(MockClassifierMock::new()),
+            Arc::new(MemoryContentStorage::new()),
+            Arc::new(MemoryTagStorage::new()),
+        )
+        .with_semantic_index(Some(SemanticIndex::new(
+            Arc::new(ConstantEmbedder),
+            vectors.clone(),
+        )));
+
+        let purged = Content::new("https://example.com/private".to_string());
+        let kept = Content::new("https://other.com/public".to_string());
+        for content in [&purged, &kept] {
+            state.content_storage.store(content).await?;
+            let index = state.semantic_index.as_ref().unwrap();
+            index.index(content, &content.content).await?;
+        }
+
+        let request = PurgeRequest {
+            url_pattern: Some("https://example.com/*".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(purge(&state, &request).await?.count, 1);
+
+        let ids = vectors.ids().await?;
+        assert!(!ids.contains(&purged.id.to_string()));
+        assert!(ids.contains(&kept.id.to_string()));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_purge_without_criteria_is_rejected() {
+        let state = AppState::new(
+            Arc::new(MockClassifierMock::new()),
+            Arc::new(MemoryContentStorage::new()),
+            Arc::new(MemoryTagStorage::new()),
+        );
+
+        assert!(purge(&state, &PurgeRequest::default()).await.is_err());
+    }
+}
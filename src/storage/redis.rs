@@ -0,0 +1,237 @@
+use async_trait::async_trait;
+use redis::aio::Connection;
+use redis::{ConnectionAddr, ConnectionInfo, IntoConnectionInfo};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+use crate::config::{RedisSentinelConfig, TagStorageConfig};
+use crate::storage::content::redis::RedisContentStorage;
+use crate::storage::tag::redis::RedisTagStorage;
+use crate::storage::AtomicStorage;
+use crate::{ClassifyError, ClassifyResult, Content};
+
+/// A Redis connection shared between storages
+pub type SharedConnection = Arc<Mutex<Connection>>;
+
+/// Credentials and TLS options for a Redis connection
+#[derive(Debug, Clone, Default)]
+pub struct RedisConnectOptions {
+    /// ACL user, sent as `AUTH username password`
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// Database index, overrides the one in the URL
+    pub db: Option<i64>,
+    /// Skip certificate verification for `rediss://` URLs
+    pub tls_insecure: bool,
+}
+
+/// Parse a Redis URL and apply the options. Credentials given in the URL are
+/// kept unless the options override them.
+pub fn connection_info(
+    redis_url: &str,
+    options: &RedisConnectOptions,
+) -> ClassifyResult<ConnectionInfo> {
+    let mut info = redis_url
+        .into_connection_info()
+        .map_err(|e| ClassifyError::StorageError(format!("Invalid Redis URL: {}", e)))?;
+
+    if options.username.is_some() {
+        info.redis.username = options.username.clone();
+    }
+    if options.password.is_some() {
+        info.redis.password = options.password.clone();
+    }
+    if let Some(db) = options.db {
+        info.redis.db = db;
+    }
+    if let ConnectionAddr::TcpTls { insecure, .. } = &mut info.addr {
+        *insecure |= options.tls_insecure;
+    }
+
+    Ok(info)
+}
+
+/// Connect to Redis, authenticating when a password is set
+pub async fn connect(redis_url: &str, options: &RedisConnectOptions) -> ClassifyResult<Connection> {
+    let client = redis::Client::open(connection_info(redis_url, options)?).map_err(|e| {
+        ClassifyError::StorageError(format!("Failed to create Redis client: {}", e))
+    })?;
+
+    client
+        .get_async_connection()
+        .await
+        .map_err(|e| ClassifyError::StorageError(format!("Failed to connect to Redis: {}", e)))
+}
+
+/// How often the master connection is checked when using Sentinel
+const FAILOVER_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Ask the sentinels for the address of the current master, using the first
+/// sentinel that answers
+async fn sentinel_master_address(sentinel: &RedisSentinelConfig) -> ClassifyResult<String> {
+    for node in &sentinel.nodes {
+        let address = async {
+            let client = redis::Client::open(node.as_str())?;
+            let mut connection = client.get_async_connection().await?;
+            redis::cmd("SENTINEL")
+                .arg("get-master-addr-by-name")
+                .arg(&sentinel.master_name)
+                .query_async::<_, Option<(String, u16)>>(&mut connection)
+                .await
+        }
+        .await;
+
+        match address {
+            Ok(Some((host, port))) => return Ok(format!("redis://{}:{}/", host, port)),
+            Ok(None) => warn!(
+                "Sentinel {} does not know master '{}'",
+                node, sentinel.master_name
+            ),
+            Err(e) => warn!("Failed to query sentinel {}: {}", node, e),
+        }
+    }
+
+    Err(ClassifyError::StorageError(format!(
+        "No sentinel could provide the address of master '{}'",
+        sentinel.master_name
+    )))
+}
+
+async fn connect_master(
+    sentinel: &RedisSentinelConfig,
+    options: &RedisConnectOptions,
+) -> ClassifyResult<Connection> {
+    let mut address = sentinel_master_address(sentinel).await?;
+    // Masters of a TLS sentinel deployment are reached over TLS as well
+    if sentinel
+        .nodes
+        .iter()
+        .any(|node| node.starts_with("rediss://"))
+    {
+        address = address.replacen("redis://", "rediss://", 1);
+    }
+    info!("Connecting to Redis master at {}", address);
+
+    let mut connection = connect(&address, options).await?;
+    if !is_master(&mut connection).await {
+        return Err(ClassifyError::StorageError(format!(
+            "Redis at {} is not a master",
+            address
+        )));
+    }
+
+    Ok(connection)
+}
+
+async fn is_master(connection: &mut Connection) -> bool {
+    match redis::cmd("ROLE")
+        .query_async::<_, redis::Value>(connection)
+        .await
+    {
+        Ok(redis::Value::Bulk(values)) => {
+            matches!(values.first(), Some(redis::Value::Data(role)) if role == b"master")
+        }
+        _ => false,
+    }
+}
+
+/// Connect to the master of a Sentinel deployment. The connection is checked
+/// in the background and replaced by one to the new master after a failover.
+pub async fn connect_sentinel(
+    sentinel: &RedisSentinelConfig,
+    options: &RedisConnectOptions,
+) -> ClassifyResult<SharedConnection> {
+    let connection = Arc::new(Mutex::new(connect_master(sentinel, options).await?));
+
+    let watched = Arc::downgrade(&connection);
+    let sentinel = sentinel.clone();
+    let options = options.clone();
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(FAILOVER_CHECK_INTERVAL);
+        interval.tick().await;
+
+        loop {
+            interval.tick().await;
+
+            // Stop once the storages using the connection are gone
+            let Some(connection) = watched.upgrade() else {
+                break;
+            };
+
+            let mut connection = connection.lock().await;
+            if is_master(&mut connection).await {
+                continue;
+            }
+
+            warn!("Lost the Redis master, asking the sentinels for the current one");
+            match connect_master(&sentinel, &options).await {
+                Ok(master) => *connection = master,
+                Err(e) => warn!("Failed to reconnect to the Redis master: {}", e),
+            }
+        }
+    });
+
+    Ok(connection)
+}
+
+/// Open a connection to the Redis server of the tag storage, for the state
+/// kept next to it such as rate limits and the job queue
+pub async fn connect_tag_storage(
+    tag_storage: &TagStorageConfig,
+) -> ClassifyResult<SharedConnection> {
+    let options = RedisConnectOptions {
+        username: tag_storage.redis_username.clone(),
+        password: tag_storage.redis_password.clone(),
+        db: tag_storage.redis_db,
+        tls_insecure: tag_storage.redis_tls_insecure,
+    };
+    match &tag_storage.redis_sentinel {
+        Some(sentinel) => connect_sentinel(sentinel, &options).await,
+        None => Ok(Arc::new(Mutex::new(
+            connect(&tag_storage.redis_url, &options).await?,
+        ))),
+    }
+}
+
+/// Writes content and its tags in one MULTI/EXEC transaction when both storages
+/// share a Redis connection, so a crash can't leave content without its tag index
+pub struct RedisAtomicStorage {
+    connection: SharedConnection,
+    content_storage: Arc<RedisContentStorage>,
+    tag_storage: Arc<RedisTagStorage>,
+}
+
+impl RedisAtomicStorage {
+    pub fn new(
+        connection: SharedConnection,
+        content_storage: Arc<RedisContentStorage>,
+        tag_storage: Arc<RedisTagStorage>,
+    ) -> Self {
+        Self {
+            connection,
+            content_storage,
+            tag_storage,
+        }
+    }
+}
+
+#[async_trait]
+impl AtomicStorage for RedisAtomicStorage {
+    async fn store_with_tags(&self, content: &Content, tags: &[String]) -> ClassifyResult<()> {
+        let mut pipe = redis::pipe();
+        pipe.atomic();
+        self.content_storage.queue_store(&mut pipe, content)?;
+        self.tag_storage
+            .queue_add_tags(&mut pipe, &content.id.to_string(), tags);
+
+        let mut connection = self.connection.lock().await;
+        pipe.query_async::<_, ()>(&mut *connection)
+            .await
+            .map_err(|e| {
+                ClassifyError::StorageError(format!("Failed to store content with tags: {}", e))
+            })
+    }
+}
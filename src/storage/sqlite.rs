@@ -0,0 +1,61 @@
+use rusqlite::Connection;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+use crate::{ClassifyError, ClassifyResult};
+
+/// A SQLite connection shared between storages
+pub type SharedConnection = Arc<Mutex<Connection>>;
+
+/// Name of the database file in `CONTENT_STORAGE_PATH`
+pub const DATABASE_FILE: &str = "classify.db";
+
+/// How long a write waits for another connection to the same file to finish
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Open the database file, creating it and its directory when missing. The
+/// file is opened in WAL mode, so the content and tag storage can each keep
+/// a connection to it.
+pub fn open(path: &str) -> ClassifyResult<SharedConnection> {
+    if let Some(parent) = Path::new(path).parent() {
+        std::fs::create_dir_all(parent).map_err(|e| {
+            ClassifyError::StorageError(format!("Failed to create directory: {}", e))
+        })?;
+    }
+
+    let connection = Connection::open(path).map_err(storage_error("open SQLite database"))?;
+    connection
+        .busy_timeout(BUSY_TIMEOUT)
+        .map_err(storage_error("open SQLite database"))?;
+    connection
+        .pragma_update(None, "journal_mode", "WAL")
+        .map_err(storage_error("open SQLite database"))?;
+    connection
+        .pragma_update(None, "foreign_keys", "ON")
+        .map_err(storage_error("open SQLite database"))?;
+
+    Ok(Arc::new(Mutex::new(connection)))
+}
+
+pub fn storage_error(action: &str) -> impl Fn(rusqlite::Error) -> ClassifyError + '_ {
+    move |e| ClassifyError::StorageError(format!("Failed to {}: {}", action, e))
+}
+
+/// Run a blocking database operation off the async runtime
+pub async fn run<T, F>(
+    connection: &SharedConnection,
+    action: &'static str,
+    operation: F,
+) -> ClassifyResult<T>
+where
+    T: Send + 'static,
+    F: FnOnce(&mut Connection) -> rusqlite::Result<T> + Send + 'static,
+{
+    let connection = connection.clone();
+    tokio::task::spawn_blocking(move || operation(&mut connection.blocking_lock()))
+        .await
+        .map_err(|e| ClassifyError::StorageError(format!("Failed to {}: {}", action, e)))?
+        .map_err(storage_error(action))
+}
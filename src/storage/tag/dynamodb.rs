@@ -0,0 +1,170 @@
+use async_trait::async_trait;
+use aws_sdk_dynamodb::types::AttributeValue;
+use aws_sdk_dynamodb::Client;
+use std::collections::HashSet;
+
+use crate::storage::dynamodb::{check_table, create_client, string_attribute, Item};
+use crate::storage::TagStorage;
+use crate::{ClassifyError, ClassifyResult};
+
+/// DynamoDB-based tag storage using an adjacency list.
+///
+/// Every tag assignment is stored twice in a table keyed by `pk` and `sk`:
+/// `content#<id>` / `tag#<tag>` and `tag#<tag>` / `content#<id>`, so both
+/// the tags of content and the content of a tag are a single query.
+pub struct DynamoDbTagStorage {
+    client: Client,
+    table: String,
+}
+
+impl DynamoDbTagStorage {
+    pub async fn new(table: &str, region: Option<&str>) -> ClassifyResult<Self> {
+        let client = create_client(region).await?;
+        check_table(&client, table).await?;
+
+        Ok(Self {
+            client,
+            table: table.to_string(),
+        })
+    }
+
+    fn content_key(&self, content_id: &str) -> String {
+        format!("content#{}", content_id)
+    }
+
+    fn tag_key(&self, tag: &str) -> String {
+        format!("tag#{}", tag)
+    }
+
+    /// Sort keys of all items in a partition, with `prefix` stripped
+    async fn query_partition(&self, pk: String, prefix: &str) -> ClassifyResult<Vec<String>> {
+        let mut values = Vec::new();
+        let mut start_key = None;
+
+        loop {
+            let output = self
+                .client
+                .query()
+                .table_name(&self.table)
+                .key_condition_expression("pk = :pk")
+                .expression_attribute_values(":pk", AttributeValue::S(pk.clone()))
+                .set_exclusive_start_key(start_key)
+                .send()
+                .await
+                .map_err(|e| ClassifyError::StorageError(format!("Failed to query tags: {}", e)))?;
+
+            values.extend(
+                output
+                    .items()
+                    .unwrap_or_default()
+                    .iter()
+                    .filter_map(|item| string_attribute(item, "sk"))
+                    .filter_map(|sk| sk.strip_prefix(prefix))
+                    .map(String::from),
+            );
+
+            start_key = output.last_evaluated_key().cloned();
+            if start_key.is_none() {
+                break;
+            }
+        }
+
+        Ok(values)
+    }
+
+    async fn put_edge(&self, pk: String, sk: String) -> ClassifyResult<()> {
+        self.client
+            .put_item()
+            .table_name(&self.table)
+            .item("pk", AttributeValue::S(pk))
+            .item("sk", AttributeValue::S(sk))
+            .send()
+            .await
+            .map_err(|e| ClassifyError::StorageError(format!("Failed to add tags: {}", e)))?;
+
+        Ok(())
+    }
+
+    async fn delete_edge(&self, pk: String, sk: String) -> ClassifyResult<()> {
+        self.client
+            .delete_item()
+            .table_name(&self.table)
+            .key("pk", AttributeValue::S(pk))
+            .key("sk", AttributeValue::S(sk))
+            .send()
+            .await
+            .map_err(|e| ClassifyError::StorageError(format!("Failed to remove tags: {}", e)))?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl TagStorage for DynamoDbTagStorage {
+    async fn add_tags(&self, content_id: &str, tags: &[String]) -> ClassifyResult<()> {
+        for tag in tags {
+            self.put_edge(self.content_key(content_id), self.tag_key(tag))
+                .await?;
+            self.put_edge(self.tag_key(tag), self.content_key(content_id))
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn get_tags(&self, content_id: &str) -> ClassifyResult<Vec<String>> {
+        self.query_partition(self.content_key(content_id), "tag#")
+            .await
+    }
+
+    async fn list_tags(&self) -> ClassifyResult<Vec<String>> {
+        let mut tags = HashSet::new();
+        let mut start_key: Option<Item> = None;
+
+        loop {
+            let output = self
+                .client
+                .scan()
+                .table_name(&self.table)
+                .filter_expression("begins_with(pk, :prefix)")
+                .expression_attribute_values(":prefix", AttributeValue::S("tag#".to_string()))
+                .projection_expression("pk")
+                .set_exclusive_start_key(start_key)
+                .send()
+                .await
+                .map_err(|e| ClassifyError::StorageError(format!("Failed to list tags: {}", e)))?;
+
+            tags.extend(
+                output
+                    .items()
+                    .unwrap_or_default()
+                    .iter()
+                    .filter_map(|item| string_attribute(item, "pk"))
+                    .filter_map(|pk| pk.strip_prefix("tag#"))
+                    .map(String::from),
+            );
+
+            start_key = output.last_evaluated_key().cloned();
+            if start_key.is_none() {
+                break;
+            }
+        }
+
+        Ok(tags.into_iter().collect())
+    }
+
+    async fn find_by_tag(&self, tag: &str) -> ClassifyResult<Vec<String>> {
+        self.query_partition(self.tag_key(tag), "content#").await
+    }
+
+    async fn remove_tags(&self, content_id: &str, tags: &[String]) -> ClassifyResult<()> {
+        for tag in tags {
+            self.delete_edge(self.content_key(content_id), self.tag_key(tag))
+                .await?;
+            self.delete_edge(self.tag_key(tag), self.content_key(content_id))
+                .await?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,107 @@
+use async_trait::async_trait;
+use std::collections::{HashMap, HashSet};
+use tokio::sync::RwLock;
+
+use crate::storage::TagStorage;
+use crate::ClassifyResult;
+
+#[derive(Default)]
+struct TagIndex {
+    content_tags: HashMap<String, HashSet<String>>,
+    tag_contents: HashMap<String, HashSet<String>>,
+}
+
+/// In-memory tag storage, for development and tests. Nothing survives a restart.
+#[derive(Default)]
+pub struct MemoryTagStorage {
+    index: RwLock<TagIndex>,
+}
+
+impl MemoryTagStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl TagStorage for MemoryTagStorage {
+    async fn add_tags(&self, content_id: &str, tags: &[String]) -> ClassifyResult<()> {
+        let mut index = self.index.write().await;
+
+        for tag in tags {
+            index
+                .content_tags
+                .entry(content_id.to_string())
+                .or_default()
+                .insert(tag.clone());
+            index
+                .tag_contents
+                .entry(tag.clone())
+                .or_default()
+                .insert(content_id.to_string());
+        }
+
+        Ok(())
+    }
+
+    async fn get_tags(&self, content_id: &str) -> ClassifyResult<Vec<String>> {
+        let index = self.index.read().await;
+        Ok(index
+            .content_tags
+            .get(content_id)
+            .map(|tags| tags.iter().cloned().collect())
+            .unwrap_or_default())
+    }
+
+    async fn list_tags(&self) -> ClassifyResult<Vec<String>> {
+        Ok(self
+            .index
+            .read()
+            .await
+            .tag_contents
+            .keys()
+            .cloned()
+            .collect())
+    }
+
+    async fn find_by_tag(&self, tag: &str) -> ClassifyResult<Vec<String>> {
+        let index = self.index.read().await;
+        Ok(index
+            .tag_contents
+            .get(tag)
+            .map(|ids| ids.iter().cloned().collect())
+            .unwrap_or_default())
+    }
+
+    async fn tag_counts(&self) -> ClassifyResult<HashMap<String, usize>> {
+        let index = self.index.read().await;
+        Ok(index
+            .tag_contents
+            .iter()
+            .map(|(tag, ids)| (tag.clone(), ids.len()))
+            .collect())
+    }
+
+    async fn remove_tags(&self, content_id: &str, tags: &[String]) -> ClassifyResult<()> {
+        let mut index = self.index.write().await;
+
+        for tag in tags {
+            if let Some(content_tags) = index.content_tags.get_mut(content_id) {
+                content_tags.remove(tag);
+                if content_tags.is_empty() {
+                    index.content_tags.remove(content_id);
+                }
+            }
+
+            // Like Redis sets, a tag without content disappears
+            if let Some(tag_contents) = index.tag_contents.get_mut(tag) {
+                tag_contents.remove(content_id);
+                if tag_contents.is_empty() {
+                    index.tag_contents.remove(tag);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
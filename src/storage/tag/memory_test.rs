@@ -0,0 +1,48 @@
+use crate::storage::tag::memory::MemoryTagStorage;
+use crate::storage::TagStorage;
+use crate::ClassifyResult;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_memory_tag_storage() -> ClassifyResult<()> {
+        let storage = MemoryTagStorage::new();
+        let tags = vec!["rust".to_string(), "programming".to_string()];
+
+        storage.add_tags("content-1", &tags).await?;
+        storage.add_tags("content-2", &tags[..1]).await?;
+
+        let mut content_tags = storage.get_tags("content-1").await?;
+        content_tags.sort();
+        assert_eq!(content_tags, vec!["programming", "rust"]);
+
+        let mut rust_content = storage.find_by_tag("rust").await?;
+        rust_content.sort();
+        assert_eq!(rust_content, vec!["content-1", "content-2"]);
+
+        assert_eq!(storage.find_by_all_tags(&tags).await?, vec!["content-1"]);
+        storage
+            .add_tags("content-3", &["rust/async".to_string()])
+            .await?;
+        let mut all_rust = storage.find_by_all_tags(&tags[..1]).await?;
+        all_rust.sort();
+        assert_eq!(all_rust, vec!["content-1", "content-2", "content-3"]);
+        storage
+            .remove_tags("content-3", &["rust/async".to_string()])
+            .await?;
+
+        let counts = storage.tag_counts().await?;
+        assert_eq!(counts["rust"], 2);
+        assert_eq!(counts["programming"], 1);
+
+        storage.remove_tags("content-1", &tags).await?;
+
+        assert!(storage.get_tags("content-1").await?.is_empty());
+        assert_eq!(storage.find_by_tag("rust").await?, vec!["content-2"]);
+        assert_eq!(storage.list_tags().await?, vec!["rust"]);
+
+        Ok(())
+    }
+}
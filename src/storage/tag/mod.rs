@@ -1,6 +1,21 @@
+#[cfg(feature = "dynamodb")]
+pub mod dynamodb;
+pub mod memory;
+pub mod postgres;
+pub mod redb;
+#[cfg(feature = "redis")]
 pub mod redis;
+pub mod sqlite;
 
+#[cfg(test)]
+mod memory_test;
+#[cfg(test)]
+mod postgres_test;
+#[cfg(test)]
+mod redb_test;
 #[cfg(test)]
 mod redis_test;
+#[cfg(test)]
+mod sqlite_test;
 
 // Other tag storage implementations can be added here
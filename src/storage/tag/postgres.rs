@@ -0,0 +1,190 @@
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+use crate::storage::postgres::{connect, SharedClient};
+use crate::storage::TagStorage;
+use crate::{ClassifyError, ClassifyResult};
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS tags (
+        id BIGSERIAL PRIMARY KEY,
+        name TEXT NOT NULL UNIQUE
+    );
+    CREATE TABLE IF NOT EXISTS content_tags (
+        content_id TEXT NOT NULL,
+        tag_id BIGINT NOT NULL REFERENCES tags (id) ON DELETE CASCADE,
+        PRIMARY KEY (content_id, tag_id)
+    );
+    CREATE INDEX IF NOT EXISTS content_tags_tag_id_idx ON content_tags (tag_id);
+";
+
+/// PostgreSQL-based tag storage, with tags linked to content through a
+/// `content_tags` join table
+pub struct PostgresTagStorage {
+    client: SharedClient,
+}
+
+impl PostgresTagStorage {
+    pub async fn new(postgres_url: &str) -> ClassifyResult<Self> {
+        let client = connect(postgres_url).await?;
+        Self::with_client(Arc::new(Mutex::new(client))).await
+    }
+
+    /// Use a client shared with other storages
+    pub async fn with_client(client: SharedClient) -> ClassifyResult<Self> {
+        crate::storage::connections::register("postgres", &client);
+        client
+            .lock()
+            .await
+            .batch_execute(SCHEMA)
+            .await
+            .map_err(|e| {
+                ClassifyError::StorageError(format!("Failed to create tag tables: {}", e))
+            })?;
+
+        Ok(Self { client })
+    }
+}
+
+fn storage_error(action: &str) -> impl Fn(tokio_postgres::Error) -> ClassifyError + '_ {
+    move |e| ClassifyError::StorageError(format!("Failed to {}: {}", action, e))
+}
+
+#[async_trait]
+impl TagStorage for PostgresTagStorage {
+    async fn add_tags(&self, content_id: &str, tags: &[String]) -> ClassifyResult<()> {
+        let mut client = self.client.lock().await;
+        let transaction = client
+            .transaction()
+            .await
+            .map_err(storage_error("add tags"))?;
+
+        transaction
+            .execute(
+                "INSERT INTO tags (name) SELECT unnest($1::text[]) ON CONFLICT (name) DO NOTHING",
+                &[&tags],
+            )
+            .await
+            .map_err(storage_error("add tags"))?;
+
+        transaction
+            .execute(
+                "INSERT INTO content_tags (content_id, tag_id)
+                 SELECT $1, id FROM tags WHERE name = ANY($2)
+                 ON CONFLICT DO NOTHING",
+                &[&content_id, &tags],
+            )
+            .await
+            .map_err(storage_error("add tags"))?;
+
+        transaction
+            .commit()
+            .await
+            .map_err(storage_error("add tags"))
+    }
+
+    async fn get_tags(&self, content_id: &str) -> ClassifyResult<Vec<String>> {
+        let client = self.client.lock().await;
+
+        let rows = client
+            .query(
+                "SELECT t.name FROM tags t
+                 JOIN content_tags ct ON ct.tag_id = t.id
+                 WHERE ct.content_id = $1",
+                &[&content_id],
+            )
+            .await
+            .map_err(storage_error("get tags"))?;
+
+        Ok(rows.iter().map(|row| row.get(0)).collect())
+    }
+
+    async fn list_tags(&self) -> ClassifyResult<Vec<String>> {
+        let client = self.client.lock().await;
+
+        // Tags no longer linked to any content are left out, like in Redis
+        let rows = client
+            .query(
+                "SELECT t.name FROM tags t
+                 WHERE EXISTS (SELECT 1 FROM content_tags ct WHERE ct.tag_id = t.id)",
+                &[],
+            )
+            .await
+            .map_err(storage_error("list tags"))?;
+
+        Ok(rows.iter().map(|row| row.get(0)).collect())
+    }
+
+    async fn find_by_tag(&self, tag: &str) -> ClassifyResult<Vec<String>> {
+        let client = self.client.lock().await;
+
+        let rows = client
+            .query(
+                "SELECT ct.content_id FROM content_tags ct
+                 JOIN tags t ON t.id = ct.tag_id
+                 WHERE t.name = $1",
+                &[&tag],
+            )
+            .await
+            .map_err(storage_error("find by tag"))?;
+
+        Ok(rows.iter().map(|row| row.get(0)).collect())
+    }
+
+    async fn find_by_all_tags(&self, tags: &[String]) -> ClassifyResult<Vec<String>> {
+        let client = self.client.lock().await;
+
+        // Content matching as many of the queried tags as there are, counting
+        // a tag below a queried tag as that tag
+        let rows = client
+            .query(
+                "SELECT ct.content_id FROM content_tags ct
+                 JOIN tags t ON t.id = ct.tag_id
+                 JOIN unnest($1::text[]) WITH ORDINALITY AS q (name, n)
+                   ON t.name = q.name OR starts_with(t.name, q.name || '/')
+                 GROUP BY ct.content_id
+                 HAVING COUNT(DISTINCT q.n) = $2",
+                &[&tags, &(tags.len() as i64)],
+            )
+            .await
+            .map_err(storage_error("find by tags"))?;
+
+        Ok(rows.iter().map(|row| row.get(0)).collect())
+    }
+
+    async fn tag_counts(&self) -> ClassifyResult<HashMap<String, usize>> {
+        let client = self.client.lock().await;
+
+        let rows = client
+            .query(
+                "SELECT t.name, COUNT(*) FROM tags t
+                 JOIN content_tags ct ON ct.tag_id = t.id
+                 GROUP BY t.name",
+                &[],
+            )
+            .await
+            .map_err(storage_error("count tags"))?;
+
+        Ok(rows
+            .iter()
+            .map(|row| (row.get(0), row.get::<_, i64>(1) as usize))
+            .collect())
+    }
+
+    async fn remove_tags(&self, content_id: &str, tags: &[String]) -> ClassifyResult<()> {
+        let client = self.client.lock().await;
+
+        client
+            .execute(
+                "DELETE FROM content_tags ct USING tags t
+                 WHERE ct.tag_id = t.id AND ct.content_id = $1 AND t.name = ANY($2)",
+                &[&content_id, &tags],
+            )
+            .await
+            .map_err(storage_error("remove tags"))?;
+
+        Ok(())
+    }
+}
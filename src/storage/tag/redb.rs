@@ -0,0 +1,177 @@
+use async_trait::async_trait;
+use redb::{Database, MultimapTableDefinition, ReadableMultimapTable};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::storage::TagStorage;
+use crate::{ClassifyError, ClassifyResult};
+
+/// Tags per content id
+const CONTENT_TAGS: MultimapTableDefinition<&str, &str> =
+    MultimapTableDefinition::new("content_tags");
+/// Content ids per tag
+const TAG_CONTENTS: MultimapTableDefinition<&str, &str> =
+    MultimapTableDefinition::new("tag_contents");
+
+/// Embedded tag storage in a single redb database file, using multimap tables
+/// for the same set semantics as the Redis storage
+pub struct RedbTagStorage {
+    db: Arc<Database>,
+}
+
+impl RedbTagStorage {
+    pub fn new(path: &str) -> ClassifyResult<Self> {
+        if let Some(parent) = Path::new(path).parent() {
+            std::fs::create_dir_all(parent).map_err(|e| {
+                ClassifyError::StorageError(format!("Failed to create directory: {}", e))
+            })?;
+        }
+
+        let db = Database::create(path).map_err(|e| {
+            ClassifyError::StorageError(format!("Failed to open tag database: {}", e))
+        })?;
+
+        // Create the tables up front so read transactions never miss them
+        let txn = db.begin_write().map_err(db_error)?;
+        txn.open_multimap_table(CONTENT_TAGS).map_err(db_error)?;
+        txn.open_multimap_table(TAG_CONTENTS).map_err(db_error)?;
+        txn.commit().map_err(db_error)?;
+
+        Ok(Self { db: Arc::new(db) })
+    }
+
+    /// Run a blocking database operation off the async runtime
+    async fn run<T, F>(&self, action: &'static str, operation: F) -> ClassifyResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Database) -> ClassifyResult<T> + Send + 'static,
+    {
+        let db = self.db.clone();
+        tokio::task::spawn_blocking(move || operation(&db))
+            .await
+            .map_err(|e| ClassifyError::StorageError(format!("Failed to {}: {}", action, e)))?
+    }
+}
+
+fn db_error(e: impl Into<redb::Error>) -> ClassifyError {
+    ClassifyError::StorageError(format!("Tag database error: {}", e.into()))
+}
+
+fn values(
+    table: &impl ReadableMultimapTable<&'static str, &'static str>,
+    key: &str,
+) -> ClassifyResult<Vec<String>> {
+    table
+        .get(key)
+        .map_err(db_error)?
+        .map(|value| Ok(value.map_err(db_error)?.value().to_string()))
+        .collect()
+}
+
+#[async_trait]
+impl TagStorage for RedbTagStorage {
+    async fn add_tags(&self, content_id: &str, tags: &[String]) -> ClassifyResult<()> {
+        let content_id = content_id.to_string();
+        let tags = tags.to_vec();
+
+        self.run("add tags", move |db| {
+            let txn = db.begin_write().map_err(db_error)?;
+            {
+                let mut content_tags = txn.open_multimap_table(CONTENT_TAGS).map_err(db_error)?;
+                let mut tag_contents = txn.open_multimap_table(TAG_CONTENTS).map_err(db_error)?;
+                for tag in &tags {
+                    content_tags
+                        .insert(content_id.as_str(), tag.as_str())
+                        .map_err(db_error)?;
+                    tag_contents
+                        .insert(tag.as_str(), content_id.as_str())
+                        .map_err(db_error)?;
+                }
+            }
+            txn.commit().map_err(db_error)?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn get_tags(&self, content_id: &str) -> ClassifyResult<Vec<String>> {
+        let content_id = content_id.to_string();
+
+        self.run("get tags", move |db| {
+            let txn = db.begin_read().map_err(db_error)?;
+            values(
+                &txn.open_multimap_table(CONTENT_TAGS).map_err(db_error)?,
+                &content_id,
+            )
+        })
+        .await
+    }
+
+    async fn list_tags(&self) -> ClassifyResult<Vec<String>> {
+        self.run("list tags", |db| {
+            let txn = db.begin_read().map_err(db_error)?;
+            let table = txn.open_multimap_table(TAG_CONTENTS).map_err(db_error)?;
+
+            let mut tags = HashSet::new();
+            for entry in table.iter().map_err(db_error)? {
+                let (tag, _) = entry.map_err(db_error)?;
+                tags.insert(tag.value().to_string());
+            }
+            Ok(tags.into_iter().collect())
+        })
+        .await
+    }
+
+    async fn tag_counts(&self) -> ClassifyResult<HashMap<String, usize>> {
+        self.run("count tags", |db| {
+            let txn = db.begin_read().map_err(db_error)?;
+            let table = txn.open_multimap_table(TAG_CONTENTS).map_err(db_error)?;
+
+            let mut counts = HashMap::new();
+            for entry in table.iter().map_err(db_error)? {
+                let (tag, ids) = entry.map_err(db_error)?;
+                counts.insert(tag.value().to_string(), ids.len() as usize);
+            }
+            Ok(counts)
+        })
+        .await
+    }
+
+    async fn find_by_tag(&self, tag: &str) -> ClassifyResult<Vec<String>> {
+        let tag = tag.to_string();
+
+        self.run("find by tag", move |db| {
+            let txn = db.begin_read().map_err(db_error)?;
+            values(
+                &txn.open_multimap_table(TAG_CONTENTS).map_err(db_error)?,
+                &tag,
+            )
+        })
+        .await
+    }
+
+    async fn remove_tags(&self, content_id: &str, tags: &[String]) -> ClassifyResult<()> {
+        let content_id = content_id.to_string();
+        let tags = tags.to_vec();
+
+        self.run("remove tags", move |db| {
+            let txn = db.begin_write().map_err(db_error)?;
+            {
+                let mut content_tags = txn.open_multimap_table(CONTENT_TAGS).map_err(db_error)?;
+                let mut tag_contents = txn.open_multimap_table(TAG_CONTENTS).map_err(db_error)?;
+                for tag in &tags {
+                    content_tags
+                        .remove(content_id.as_str(), tag.as_str())
+                        .map_err(db_error)?;
+                    tag_contents
+                        .remove(tag.as_str(), content_id.as_str())
+                        .map_err(db_error)?;
+                }
+            }
+            txn.commit().map_err(db_error)?;
+            Ok(())
+        })
+        .await
+    }
+}
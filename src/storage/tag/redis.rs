@@ -1,51 +1,66 @@
 use async_trait::async_trait;
-use redis::AsyncCommands;
-use std::collections::HashSet;
+use redis::{AsyncCommands, Pipeline};
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
+use tokio::sync::Mutex;
+use uuid::Uuid;
 
+use crate::storage::redis::{connect, RedisConnectOptions, SharedConnection};
 use crate::storage::TagStorage;
+use crate::tags::is_within;
 use crate::{ClassifyError, ClassifyResult};
 
 /// Redis-based tag storage
 pub struct RedisTagStorage {
-    connection: Arc<tokio::sync::Mutex<redis::aio::Connection>>,
+    connection: SharedConnection,
+    prefix: String,
 }
 
 impl RedisTagStorage {
-    pub async fn new(redis_url: &str, redis_password: Option<&str>) -> ClassifyResult<Self> {
-        let client = redis::Client::open(redis_url).map_err(|e| {
-            ClassifyError::StorageError(format!("Failed to create Redis client: {}", e))
-        })?;
-
-        let mut connection = client.get_async_connection().await.map_err(|e| {
-            ClassifyError::StorageError(format!("Failed to connect to Redis: {}", e))
-        })?;
-
-        if let Some(password) = redis_password {
-            redis::cmd("AUTH")
-                .arg(password)
-                .query_async::<_, ()>(&mut connection)
-                .await
-                .map_err(|e| {
-                    ClassifyError::StorageError(format!("Failed to authenticate to Redis: {}", e))
-                })?;
+    pub async fn new(
+        redis_url: &str,
+        options: &RedisConnectOptions,
+        prefix: Option<&str>,
+    ) -> ClassifyResult<Self> {
+        let connection = connect(redis_url, options).await?;
+        Ok(Self::with_connection(
+            Arc::new(Mutex::new(connection)),
+            prefix,
+        ))
+    }
+
+    /// Use a connection shared with other storages
+    pub fn with_connection(connection: SharedConnection, prefix: Option<&str>) -> Self {
+        crate::storage::connections::register("redis", &connection);
+        Self {
+            connection,
+            prefix: prefix.unwrap_or("classify:").to_string(),
         }
+    }
 
-        Ok(Self {
-            connection: Arc::new(tokio::sync::Mutex::new(connection)),
-        })
+    /// Add the commands indexing `tags` for a content id to a pipeline, so they
+    /// can be combined with content writes on the same connection
+    pub(crate) fn queue_add_tags(&self, pipe: &mut Pipeline, content_id: &str, tags: &[String]) {
+        let content_tags_key = self.get_content_tags_key(content_id);
+
+        for tag in tags {
+            pipe.sadd(&content_tags_key, tag);
+
+            let tag_contents_key = self.get_tag_contents_key(tag);
+            pipe.sadd(&tag_contents_key, content_id);
+        }
     }
 
     fn get_content_tags_key(&self, content_id: &str) -> String {
-        format!("classify:content:{}:tags", content_id)
+        format!("{}content:{}:tags", self.prefix, content_id)
     }
 
     fn get_tag_contents_key(&self, tag: &str) -> String {
-        format!("classify:tag:{}:contents", tag)
+        format!("{}tag:{}:contents", self.prefix, tag)
     }
 
     fn get_all_tag_contents_pattern(&self) -> String {
-        "classify:tag:*:contents".to_string()
+        format!("{}tag:*:contents", self.prefix)
     }
 }
 
@@ -53,16 +68,9 @@ impl RedisTagStorage {
 impl TagStorage for RedisTagStorage {
     async fn add_tags(&self, content_id: &str, tags: &[String]) -> ClassifyResult<()> {
         let mut conn = self.connection.lock().await;
-        let content_tags_key = self.get_content_tags_key(content_id);
 
         let mut pipe = redis::pipe();
-
-        for tag in tags {
-            pipe.sadd(&content_tags_key, tag);
-
-            let tag_contents_key = self.get_tag_contents_key(tag);
-            pipe.sadd(&tag_contents_key, content_id);
-        }
+        self.queue_add_tags(&mut pipe, content_id, tags);
 
         pipe.query_async::<_, ()>(&mut *conn)
             .await
@@ -93,10 +101,11 @@ impl TagStorage for RedisTagStorage {
             .await
             .map_err(|e| ClassifyError::StorageError(format!("Failed to list tag keys: {}", e)))?;
 
+        let tag_prefix = format!("{}tag:", self.prefix);
         let mut tags = HashSet::new();
         for key in tag_keys {
             if let Some(tag) = key
-                .strip_prefix("classify:tag:")
+                .strip_prefix(tag_prefix.as_str())
                 .and_then(|s| s.strip_suffix(":contents"))
             {
                 tags.insert(tag.to_string());
@@ -106,6 +115,39 @@ impl TagStorage for RedisTagStorage {
         Ok(tags.into_iter().collect())
     }
 
+    async fn tag_counts(&self) -> ClassifyResult<HashMap<String, usize>> {
+        let mut conn = self.connection.lock().await;
+        let pattern = self.get_all_tag_contents_pattern();
+
+        let tag_keys: Vec<String> = redis::cmd("KEYS")
+            .arg(&pattern)
+            .query_async(&mut *conn)
+            .await
+            .map_err(|e| ClassifyError::StorageError(format!("Failed to list tag keys: {}", e)))?;
+
+        let mut pipe = redis::pipe();
+        for key in &tag_keys {
+            pipe.scard(key);
+        }
+
+        let counts: Vec<usize> = pipe
+            .query_async(&mut *conn)
+            .await
+            .map_err(|e| ClassifyError::StorageError(format!("Failed to count tags: {}", e)))?;
+
+        let tag_prefix = format!("{}tag:", self.prefix);
+        Ok(tag_keys
+            .iter()
+            .zip(counts)
+            .filter_map(|(key, count)| {
+                let tag = key
+                    .strip_prefix(tag_prefix.as_str())?
+                    .strip_suffix(":contents")?;
+                Some((tag.to_string(), count))
+            })
+            .collect())
+    }
+
     async fn find_by_tag(&self, tag: &str) -> ClassifyResult<Vec<String>> {
         let mut conn = self.connection.lock().await;
         let tag_contents_key = self.get_tag_contents_key(tag);
@@ -118,6 +160,51 @@ impl TagStorage for RedisTagStorage {
         Ok(content_ids)
     }
 
+    async fn find_by_all_tags(&self, tags: &[String]) -> ClassifyResult<Vec<String>> {
+        if tags.is_empty() {
+            return Ok(Vec::new());
+        }
+        let known = self.list_tags().await?;
+
+        // A tag with tags below it is first merged with those into a temporary set
+        let mut pipe = redis::pipe();
+        pipe.atomic();
+        let mut keys = Vec::with_capacity(tags.len());
+        let mut temporary = Vec::new();
+        for tag in tags {
+            let children: Vec<String> = known
+                .iter()
+                .filter(|known| *known != tag && is_within(known, tag))
+                .map(|child| self.get_tag_contents_key(child))
+                .collect();
+            if children.is_empty() {
+                keys.push(self.get_tag_contents_key(tag));
+                continue;
+            }
+
+            let key = format!("{}tmp:{}", self.prefix, Uuid::new_v4());
+            pipe.cmd("SUNIONSTORE")
+                .arg(&key)
+                .arg(self.get_tag_contents_key(tag))
+                .arg(&children)
+                .ignore();
+            keys.push(key.clone());
+            temporary.push(key);
+        }
+        pipe.sinter(&keys);
+        if !temporary.is_empty() {
+            pipe.del(&temporary).ignore();
+        }
+
+        let mut conn = self.connection.lock().await;
+        let (ids,): (Vec<String>,) = pipe
+            .query_async(&mut *conn)
+            .await
+            .map_err(|e| ClassifyError::StorageError(format!("Failed to find by tags: {}", e)))?;
+
+        Ok(ids)
+    }
+
     async fn remove_tags(&self, content_id: &str, tags: &[String]) -> ClassifyResult<()> {
         let mut conn = self.connection.lock().await;
         let content_tags_key = self.get_content_tags_key(content_id);
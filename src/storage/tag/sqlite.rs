@@ -0,0 +1,171 @@
+use async_trait::async_trait;
+use std::collections::HashMap;
+
+use crate::storage::sqlite::{open, run, SharedConnection};
+use crate::storage::TagStorage;
+use crate::ClassifyResult;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS content_tags (
+        content_id TEXT NOT NULL,
+        tag TEXT NOT NULL,
+        PRIMARY KEY (content_id, tag)
+    );
+    CREATE INDEX IF NOT EXISTS content_tags_tag_idx ON content_tags (tag);
+";
+
+/// Tags of `?1` and the tags below it in the hierarchy
+const TAG_TREE: &str = "(tag = ?1 OR substr(tag, 1, length(?1) + 1) = ?1 || '/')";
+
+/// Embedded tag storage in a SQLite database file, with a row per tag of
+/// each content item
+pub struct SqliteTagStorage {
+    connection: SharedConnection,
+}
+
+impl SqliteTagStorage {
+    pub async fn new(path: &str) -> ClassifyResult<Self> {
+        Self::with_connection(open(path)?).await
+    }
+
+    /// Use a connection shared with other storages
+    pub async fn with_connection(connection: SharedConnection) -> ClassifyResult<Self> {
+        crate::storage::connections::register("sqlite", &connection);
+        run(&connection, "create tag tables", |connection| {
+            connection.execute_batch(SCHEMA)
+        })
+        .await?;
+
+        Ok(Self { connection })
+    }
+
+    /// Run a query with a single parameter, returning the first column of every row
+    async fn strings(
+        &self,
+        action: &'static str,
+        query: String,
+        parameter: String,
+    ) -> ClassifyResult<Vec<String>> {
+        run(&self.connection, action, move |connection| {
+            connection
+                .prepare(&query)?
+                .query_map([parameter], |row| row.get(0))?
+                .collect()
+        })
+        .await
+    }
+}
+
+#[async_trait]
+impl TagStorage for SqliteTagStorage {
+    async fn add_tags(&self, content_id: &str, tags: &[String]) -> ClassifyResult<()> {
+        let content_id = content_id.to_string();
+        let tags = tags.to_vec();
+
+        run(&self.connection, "add tags", move |connection| {
+            let transaction = connection.transaction()?;
+            {
+                let mut insert = transaction.prepare(
+                    "INSERT INTO content_tags (content_id, tag) VALUES (?1, ?2)
+                     ON CONFLICT DO NOTHING",
+                )?;
+                for tag in &tags {
+                    insert.execute([&content_id, tag])?;
+                }
+            }
+            transaction.commit()
+        })
+        .await
+    }
+
+    async fn get_tags(&self, content_id: &str) -> ClassifyResult<Vec<String>> {
+        self.strings(
+            "get tags",
+            "SELECT tag FROM content_tags WHERE content_id = ?1".to_string(),
+            content_id.to_string(),
+        )
+        .await
+    }
+
+    async fn list_tags(&self) -> ClassifyResult<Vec<String>> {
+        run(&self.connection, "list tags", |connection| {
+            connection
+                .prepare("SELECT DISTINCT tag FROM content_tags")?
+                .query_map([], |row| row.get(0))?
+                .collect()
+        })
+        .await
+    }
+
+    async fn tag_counts(&self) -> ClassifyResult<HashMap<String, usize>> {
+        run(&self.connection, "count tags", |connection| {
+            connection
+                .prepare("SELECT tag, COUNT(*) FROM content_tags GROUP BY tag")?
+                .query_map([], |row| Ok((row.get(0)?, row.get::<_, i64>(1)? as usize)))?
+                .collect()
+        })
+        .await
+    }
+
+    async fn find_by_tag(&self, tag: &str) -> ClassifyResult<Vec<String>> {
+        self.strings(
+            "find by tag",
+            "SELECT content_id FROM content_tags WHERE tag = ?1".to_string(),
+            tag.to_string(),
+        )
+        .await
+    }
+
+    async fn find_by_tag_tree(&self, tag: &str) -> ClassifyResult<Vec<String>> {
+        self.strings(
+            "find by tag",
+            format!(
+                "SELECT DISTINCT content_id FROM content_tags WHERE {}",
+                TAG_TREE
+            ),
+            tag.to_string(),
+        )
+        .await
+    }
+
+    async fn find_by_all_tags(&self, tags: &[String]) -> ClassifyResult<Vec<String>> {
+        let count = tags.len() as i64;
+        // The tags are passed as one JSON array, `key` numbers them
+        let tags = serde_json::to_string(tags)?;
+
+        // Content matching as many of the queried tags as there are, counting
+        // a tag below a queried tag as that tag
+        run(&self.connection, "find by tags", move |connection| {
+            connection
+                .prepare(
+                    "SELECT ct.content_id FROM content_tags ct
+                     JOIN json_each(?1) q
+                       ON ct.tag = q.value
+                       OR substr(ct.tag, 1, length(q.value) + 1) = q.value || '/'
+                     GROUP BY ct.content_id
+                     HAVING COUNT(DISTINCT q.key) = ?2",
+                )?
+                .query_map(rusqlite::params![tags, count], |row| row.get(0))?
+                .collect()
+        })
+        .await
+    }
+
+    async fn remove_tags(&self, content_id: &str, tags: &[String]) -> ClassifyResult<()> {
+        let content_id = content_id.to_string();
+        let tags = tags.to_vec();
+
+        run(&self.connection, "remove tags", move |connection| {
+            let transaction = connection.transaction()?;
+            {
+                let mut delete = transaction
+                    .prepare("DELETE FROM content_tags WHERE content_id = ?1 AND tag = ?2")?;
+                for tag in &tags {
+                    delete.execute([&content_id, tag])?;
+                }
+            }
+            transaction.commit()
+        })
+        .await
+    }
+}
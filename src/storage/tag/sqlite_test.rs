@@ -0,0 +1,72 @@
+use std::fs;
+use uuid::Uuid;
+
+use crate::storage::tag::sqlite::SqliteTagStorage;
+use crate::storage::TagStorage;
+use crate::ClassifyResult;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_sqlite_tag_storage() -> ClassifyResult<()> {
+        let test_dir = format!("./test_data_{}", Uuid::new_v4());
+        let path = format!("{}/classify.db", test_dir);
+
+        {
+            let storage = SqliteTagStorage::new(&path).await?;
+            let tags = vec!["rust".to_string(), "programming".to_string()];
+
+            storage.add_tags("content-1", &tags).await?;
+            storage.add_tags("content-2", &tags[..1]).await?;
+            // Adding a tag twice keeps a single entry
+            storage.add_tags("content-2", &tags[..1]).await?;
+            storage
+                .add_tags("content-3", &["rust/async".to_string()])
+                .await?;
+            // Only a whole tag name counts as a parent
+            storage
+                .add_tags("content-4", &["rustacean".to_string()])
+                .await?;
+
+            let mut content_tags = storage.get_tags("content-1").await?;
+            content_tags.sort();
+            assert_eq!(content_tags, vec!["programming", "rust"]);
+
+            let mut rust_content = storage.find_by_tag("rust").await?;
+            rust_content.sort();
+            assert_eq!(rust_content, vec!["content-1", "content-2"]);
+
+            let mut rust_tree = storage.find_by_tag_tree("rust").await?;
+            rust_tree.sort();
+            assert_eq!(rust_tree, vec!["content-1", "content-2", "content-3"]);
+
+            assert_eq!(storage.find_by_all_tags(&tags).await?, vec!["content-1"]);
+
+            let counts = storage.tag_counts().await?;
+            assert_eq!(counts["rust"], 2);
+            assert_eq!(counts["programming"], 1);
+
+            storage.remove_tags("content-1", &tags).await?;
+
+            assert!(storage.get_tags("content-1").await?.is_empty());
+            assert_eq!(storage.find_by_tag("rust").await?, vec!["content-2"]);
+            storage
+                .remove_tags("content-3", &["rust/async".to_string()])
+                .await?;
+            storage
+                .remove_tags("content-4", &["rustacean".to_string()])
+                .await?;
+        }
+
+        // Tags survive reopening the database
+        let storage = SqliteTagStorage::new(&path).await?;
+        assert_eq!(storage.list_tags().await?, vec!["rust"]);
+        drop(storage);
+
+        fs::remove_dir_all(&test_dir).unwrap();
+
+        Ok(())
+    }
+}
@@ -0,0 +1,85 @@
+use tracing::{error, warn};
+
+use crate::storage::{AtomicStorage, ContentStorage, TagStorage};
+use crate::{ClassifyResult, Content};
+
+/// Coordinates writes that span content and tag storage.
+///
+/// Backends that can write both atomically are used as is. Otherwise the
+/// writes run one after the other and a failed step undoes the steps before
+/// it, so content is never left without its tags or the other way around.
+pub struct StorageTransaction<'a> {
+    content_storage: &'a dyn ContentStorage,
+    tag_storage: &'a dyn TagStorage,
+    atomic_storage: Option<&'a dyn AtomicStorage>,
+}
+
+impl<'a> StorageTransaction<'a> {
+    pub fn new(content_storage: &'a dyn ContentStorage, tag_storage: &'a dyn TagStorage) -> Self {
+        Self {
+            content_storage,
+            tag_storage,
+            atomic_storage: None,
+        }
+    }
+
+    pub fn with_atomic_storage(mut self, atomic_storage: Option<&'a dyn AtomicStorage>) -> Self {
+        self.atomic_storage = atomic_storage;
+        self
+    }
+
+    /// Store content and index its tags, deleting the content again when the
+    /// tags can't be indexed
+    pub async fn store_with_tags(&self, content: &Content, tags: &[String]) -> ClassifyResult<()> {
+        if let Some(atomic_storage) = self.atomic_storage {
+            return atomic_storage.store_with_tags(content, tags).await;
+        }
+
+        let id = content.id.to_string();
+        self.content_storage.store(content).await?;
+
+        if let Err(e) = self.tag_storage.add_tags(&id, tags).await {
+            warn!("Failed to index tags of content {}, removing it: {}", id, e);
+            // Tags that did get written would point at missing content
+            self.compensate("remove tags", self.tag_storage.remove_tags(&id, tags))
+                .await;
+            self.compensate("delete content", self.content_storage.delete(&id))
+                .await;
+            return Err(e);
+        }
+
+        Ok(())
+    }
+
+    /// Remove the tags of content and delete it, restoring the tags when the
+    /// content can't be deleted. Returns false when there was no content to delete.
+    pub async fn delete_with_tags(&self, id: &str, tags: &[String]) -> ClassifyResult<bool> {
+        self.tag_storage.remove_tags(id, tags).await?;
+
+        let result = match self.content_storage.delete(id).await {
+            Ok(true) => return Ok(true),
+            Ok(false) => Ok(false),
+            Err(e) => Err(e),
+        };
+
+        warn!("Failed to delete content {}, restoring its tags", id);
+        self.compensate("restore tags", self.tag_storage.add_tags(id, tags))
+            .await;
+        result
+    }
+
+    /// Run a compensating step. Its failure is logged but doesn't replace the
+    /// error that caused the rollback.
+    async fn compensate<T>(
+        &self,
+        action: &str,
+        step: impl std::future::Future<Output = ClassifyResult<T>>,
+    ) {
+        if let Err(e) = step.await {
+            error!(
+                "Failed to {} while rolling back, storage may be inconsistent: {}",
+                action, e
+            );
+        }
+    }
+}
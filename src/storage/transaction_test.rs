@@ -0,0 +1,141 @@
+use async_trait::async_trait;
+
+use crate::storage::content::memory::MemoryContentStorage;
+use crate::storage::tag::memory::MemoryTagStorage;
+use crate::storage::transaction::StorageTransaction;
+use crate::storage::{ContentStorage, TagStorage};
+use crate::{ClassifyError, ClassifyResult, Content};
+
+/// Tag storage that fails every write, delegating reads to memory
+struct FailingTagStorage {
+    inner: MemoryTagStorage,
+}
+
+#[async_trait]
+impl TagStorage for FailingTagStorage {
+    async fn add_tags(&self, _content_id: &str, _tags: &[String]) -> ClassifyResult<()> {
+        Err(ClassifyError::StorageError("add_tags failed".to_string()))
+    }
+
+    async fn get_tags(&self, content_id: &str) -> ClassifyResult<Vec<String>> {
+        self.inner.get_tags(content_id).await
+    }
+
+    async fn list_tags(&self) -> ClassifyResult<Vec<String>> {
+        self.inner.list_tags().await
+    }
+
+    async fn find_by_tag(&self, tag: &str) -> ClassifyResult<Vec<String>> {
+        self.inner.find_by_tag(tag).await
+    }
+
+    async fn remove_tags(&self, content_id: &str, tags: &[String]) -> ClassifyResult<()> {
+        self.inner.remove_tags(content_id, tags).await
+    }
+}
+
+/// Content storage whose deletes always fail
+struct UndeletableContentStorage {
+    inner: MemoryContentStorage,
+}
+
+#[async_trait]
+impl ContentStorage for UndeletableContentStorage {
+    async fn store(&self, content: &Content) -> ClassifyResult<()> {
+        self.inner.store(content).await
+    }
+
+    async fn get(&self, id: &str) -> ClassifyResult<Option<Content>> {
+        self.inner.get(id).await
+    }
+
+    async fn list(&self) -> ClassifyResult<Vec<Content>> {
+        self.inner.list().await
+    }
+
+    async fn delete(&self, _id: &str) -> ClassifyResult<bool> {
+        Err(ClassifyError::StorageError("delete failed".to_string()))
+    }
+
+    async fn find_by_hash(&self, hash: &str) -> ClassifyResult<Option<Content>> {
+        self.inner.find_by_hash(hash).await
+    }
+
+    async fn store_attachment(&self, id: &str, name: &str, data: &[u8]) -> ClassifyResult<()> {
+        self.inner.store_attachment(id, name, data).await
+    }
+
+    async fn get_attachment(&self, id: &str, name: &str) -> ClassifyResult<Option<Vec<u8>>> {
+        self.inner.get_attachment(id, name).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags() -> Vec<String> {
+        vec!["rust".to_string(), "saga".to_string()]
+    }
+
+    #[tokio::test]
+    async fn test_store_and_delete_with_tags() -> ClassifyResult<()> {
+        let content_storage = MemoryContentStorage::new();
+        let tag_storage = MemoryTagStorage::new();
+        let transaction = StorageTransaction::new(&content_storage, &tag_storage);
+
+        let content = Content::new("Both or neither".to_string());
+        let id = content.id.to_string();
+        transaction.store_with_tags(&content, &tags()).await?;
+
+        assert!(content_storage.get(&id).await?.is_some());
+        assert_eq!(tag_storage.find_by_tag("saga").await?, vec![id.clone()]);
+
+        assert!(transaction.delete_with_tags(&id, &tags()).await?);
+        assert!(content_storage.get(&id).await?.is_none());
+        assert!(tag_storage.find_by_tag("saga").await?.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_failed_tag_write_removes_stored_content() {
+        let content_storage = MemoryContentStorage::new();
+        let tag_storage = FailingTagStorage {
+            inner: MemoryTagStorage::new(),
+        };
+        let transaction = StorageTransaction::new(&content_storage, &tag_storage);
+
+        let content = Content::new("Never indexed".to_string());
+        let result = transaction.store_with_tags(&content, &tags()).await;
+
+        assert!(result.is_err());
+        assert!(content_storage
+            .get(&content.id.to_string())
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_failed_delete_restores_tags() -> ClassifyResult<()> {
+        let content_storage = UndeletableContentStorage {
+            inner: MemoryContentStorage::new(),
+        };
+        let tag_storage = MemoryTagStorage::new();
+        let transaction = StorageTransaction::new(&content_storage, &tag_storage);
+
+        let content = Content::new("Stays put".to_string());
+        let id = content.id.to_string();
+        transaction.store_with_tags(&content, &tags()).await?;
+
+        assert!(transaction.delete_with_tags(&id, &tags()).await.is_err());
+        assert!(content_storage.get(&id).await?.is_some());
+
+        let mut restored = tag_storage.get_tags(&id).await?;
+        restored.sort();
+        assert_eq!(restored, tags());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,91 @@
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tokio::sync::RwLock;
+
+use crate::storage::UsageStorage;
+use crate::{ClassifyError, ClassifyResult, Usage};
+
+type Months = HashMap<String, HashMap<String, Usage>>;
+
+/// Usage storage in a local JSON file, rewritten on every change
+pub struct FileUsageStorage {
+    path: PathBuf,
+    months: RwLock<Months>,
+}
+
+impl FileUsageStorage {
+    pub async fn new(path: &str) -> ClassifyResult<Self> {
+        let path = PathBuf::from(path);
+
+        let months = match tokio::fs::read(&path).await {
+            Ok(data) => serde_json::from_slice(&data)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => HashMap::new(),
+            Err(e) => {
+                return Err(ClassifyError::StorageError(format!(
+                    "Failed to read usage: {}",
+                    e
+                )))
+            }
+        };
+
+        Ok(Self {
+            path,
+            months: RwLock::new(months),
+        })
+    }
+
+    async fn write(&self, months: &Months) -> ClassifyResult<()> {
+        if let Some(dir) = self.path.parent() {
+            tokio::fs::create_dir_all(dir).await.map_err(|e| {
+                ClassifyError::StorageError(format!("Failed to create directory: {}", e))
+            })?;
+        }
+
+        let data = serde_json::to_vec_pretty(months)?;
+
+        // Write to a temporary file first so a crash can't leave a truncated file
+        let tmp_path = self.path.with_extension("tmp");
+        tokio::fs::write(&tmp_path, data)
+            .await
+            .map_err(|e| ClassifyError::StorageError(format!("Failed to write usage: {}", e)))?;
+        tokio::fs::rename(&tmp_path, &self.path)
+            .await
+            .map_err(|e| ClassifyError::StorageError(format!("Failed to write usage: {}", e)))
+    }
+}
+
+#[async_trait]
+impl UsageStorage for FileUsageStorage {
+    async fn add(&self, account: &str, month: &str, usage: &Usage) -> ClassifyResult<()> {
+        let mut months = self.months.write().await;
+        months
+            .entry(month.to_string())
+            .or_default()
+            .entry(account.to_string())
+            .or_default()
+            .add(usage);
+        self.write(&months).await
+    }
+
+    async fn get(&self, account: &str, month: &str) -> ClassifyResult<Usage> {
+        Ok(self
+            .months
+            .read()
+            .await
+            .get(month)
+            .and_then(|accounts| accounts.get(account))
+            .copied()
+            .unwrap_or_default())
+    }
+
+    async fn list(&self, month: &str) -> ClassifyResult<HashMap<String, Usage>> {
+        Ok(self
+            .months
+            .read()
+            .await
+            .get(month)
+            .cloned()
+            .unwrap_or_default())
+    }
+}
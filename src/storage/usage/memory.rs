@@ -0,0 +1,55 @@
+use async_trait::async_trait;
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+
+use crate::storage::UsageStorage;
+use crate::{ClassifyResult, Usage};
+
+/// In-memory usage storage, for development and tests. Counters are lost on restart.
+#[derive(Default)]
+pub struct MemoryUsageStorage {
+    /// Usage per account per month
+    months: RwLock<HashMap<String, HashMap<String, Usage>>>,
+}
+
+impl MemoryUsageStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl UsageStorage for MemoryUsageStorage {
+    async fn add(&self, account: &str, month: &str, usage: &Usage) -> ClassifyResult<()> {
+        self.months
+            .write()
+            .await
+            .entry(month.to_string())
+            .or_default()
+            .entry(account.to_string())
+            .or_default()
+            .add(usage);
+        Ok(())
+    }
+
+    async fn get(&self, account: &str, month: &str) -> ClassifyResult<Usage> {
+        Ok(self
+            .months
+            .read()
+            .await
+            .get(month)
+            .and_then(|accounts| accounts.get(account))
+            .copied()
+            .unwrap_or_default())
+    }
+
+    async fn list(&self, month: &str) -> ClassifyResult<HashMap<String, Usage>> {
+        Ok(self
+            .months
+            .read()
+            .await
+            .get(month)
+            .cloned()
+            .unwrap_or_default())
+    }
+}
@@ -0,0 +1,53 @@
+use crate::storage::usage::memory::MemoryUsageStorage;
+use crate::storage::UsageStorage;
+use crate::{ClassifyResult, Usage};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_add_accumulates_per_month() -> ClassifyResult<()> {
+        let storage = MemoryUsageStorage::new();
+        let usage = Usage {
+            classifications: 1,
+            stored_bytes: 100,
+            tokens: 50,
+        };
+
+        storage.add("tenant:acme", "2024-05", &usage).await?;
+        storage.add("tenant:acme", "2024-05", &usage).await?;
+        storage.add("tenant:acme", "2024-06", &usage).await?;
+
+        let may = storage.get("tenant:acme", "2024-05").await?;
+        assert_eq!(may.classifications, 2);
+        assert_eq!(may.stored_bytes, 200);
+        assert_eq!(may.tokens, 100);
+
+        assert_eq!(
+            storage.get("tenant:acme", "2024-06").await?.classifications,
+            1
+        );
+        assert!(storage.get("tenant:other", "2024-05").await?.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_list_returns_accounts_of_month() -> ClassifyResult<()> {
+        let storage = MemoryUsageStorage::new();
+        let usage = Usage {
+            classifications: 1,
+            ..Usage::default()
+        };
+
+        storage.add("tenant:acme", "2024-05", &usage).await?;
+        storage.add("key:1", "2024-05", &usage).await?;
+        storage.add("key:2", "2024-06", &usage).await?;
+
+        let accounts = storage.list("2024-05").await?;
+        assert_eq!(accounts.len(), 2);
+        assert!(accounts.contains_key("tenant:acme"));
+        assert!(accounts.contains_key("key:1"));
+        Ok(())
+    }
+}
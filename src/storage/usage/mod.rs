@@ -0,0 +1,8 @@
+pub mod file;
+pub mod memory;
+pub mod postgres;
+#[cfg(feature = "redis")]
+pub mod redis;
+
+#[cfg(test)]
+mod memory_test;
@@ -0,0 +1,120 @@
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tokio_postgres::Row;
+
+use crate::storage::postgres::{connect, SharedClient};
+use crate::storage::UsageStorage;
+use crate::{ClassifyError, ClassifyResult, Usage};
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS usage (
+        month TEXT NOT NULL,
+        account TEXT NOT NULL,
+        classifications BIGINT NOT NULL DEFAULT 0,
+        stored_bytes BIGINT NOT NULL DEFAULT 0,
+        tokens BIGINT NOT NULL DEFAULT 0,
+        PRIMARY KEY (month, account)
+    );
+";
+
+/// PostgreSQL-based usage storage
+pub struct PostgresUsageStorage {
+    client: SharedClient,
+}
+
+impl PostgresUsageStorage {
+    pub async fn new(postgres_url: &str) -> ClassifyResult<Self> {
+        let client = connect(postgres_url).await?;
+        Self::with_client(Arc::new(Mutex::new(client))).await
+    }
+
+    /// Use a client shared with other storages
+    pub async fn with_client(client: SharedClient) -> ClassifyResult<Self> {
+        crate::storage::connections::register("postgres", &client);
+        client
+            .lock()
+            .await
+            .batch_execute(SCHEMA)
+            .await
+            .map_err(|e| {
+                ClassifyError::StorageError(format!("Failed to create usage table: {}", e))
+            })?;
+
+        Ok(Self { client })
+    }
+}
+
+fn storage_error(action: &str) -> impl Fn(tokio_postgres::Error) -> ClassifyError + '_ {
+    move |e| ClassifyError::StorageError(format!("Failed to {}: {}", action, e))
+}
+
+fn usage_from_row(row: &Row) -> Usage {
+    Usage {
+        classifications: row.get::<_, i64>("classifications") as u64,
+        stored_bytes: row.get::<_, i64>("stored_bytes") as u64,
+        tokens: row.get::<_, i64>("tokens") as u64,
+    }
+}
+
+#[async_trait]
+impl UsageStorage for PostgresUsageStorage {
+    async fn add(&self, account: &str, month: &str, usage: &Usage) -> ClassifyResult<()> {
+        let client = self.client.lock().await;
+
+        client
+            .execute(
+                "INSERT INTO usage (month, account, classifications, stored_bytes, tokens)
+                 VALUES ($1, $2, $3, $4, $5)
+                 ON CONFLICT (month, account) DO UPDATE SET
+                    classifications = usage.classifications + EXCLUDED.classifications,
+                    stored_bytes = usage.stored_bytes + EXCLUDED.stored_bytes,
+                    tokens = usage.tokens + EXCLUDED.tokens",
+                &[
+                    &month,
+                    &account,
+                    &(usage.classifications as i64),
+                    &(usage.stored_bytes as i64),
+                    &(usage.tokens as i64),
+                ],
+            )
+            .await
+            .map_err(storage_error("update usage"))?;
+
+        Ok(())
+    }
+
+    async fn get(&self, account: &str, month: &str) -> ClassifyResult<Usage> {
+        let client = self.client.lock().await;
+
+        let row = client
+            .query_opt(
+                "SELECT classifications, stored_bytes, tokens FROM usage
+                 WHERE month = $1 AND account = $2",
+                &[&month, &account],
+            )
+            .await
+            .map_err(storage_error("get usage"))?;
+
+        Ok(row.as_ref().map(usage_from_row).unwrap_or_default())
+    }
+
+    async fn list(&self, month: &str) -> ClassifyResult<HashMap<String, Usage>> {
+        let client = self.client.lock().await;
+
+        let rows = client
+            .query(
+                "SELECT account, classifications, stored_bytes, tokens FROM usage
+                 WHERE month = $1",
+                &[&month],
+            )
+            .await
+            .map_err(storage_error("list usage"))?;
+
+        Ok(rows
+            .iter()
+            .map(|row| (row.get("account"), usage_from_row(row)))
+            .collect())
+    }
+}
@@ -0,0 +1,102 @@
+use async_trait::async_trait;
+use redis::AsyncCommands;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+use crate::storage::redis::{connect, RedisConnectOptions, SharedConnection};
+use crate::storage::UsageStorage;
+use crate::{ClassifyError, ClassifyResult, Usage};
+
+/// Redis-based usage storage, with a hash of counters per account and month
+pub struct RedisUsageStorage {
+    connection: SharedConnection,
+    prefix: String,
+}
+
+impl RedisUsageStorage {
+    pub async fn new(
+        redis_url: &str,
+        options: &RedisConnectOptions,
+        prefix: Option<&str>,
+    ) -> ClassifyResult<Self> {
+        let connection = connect(redis_url, options).await?;
+        Ok(Self::with_connection(
+            Arc::new(Mutex::new(connection)),
+            prefix,
+        ))
+    }
+
+    /// Use a connection shared with other storages
+    pub fn with_connection(connection: SharedConnection, prefix: Option<&str>) -> Self {
+        crate::storage::connections::register("redis", &connection);
+        Self {
+            connection,
+            prefix: prefix.unwrap_or("classify:").to_string(),
+        }
+    }
+
+    fn get_usage_key(&self, account: &str, month: &str) -> String {
+        format!("{}usage:{}:{}", self.prefix, month, account)
+    }
+
+    fn get_accounts_key(&self, month: &str) -> String {
+        format!("{}usage-accounts:{}", self.prefix, month)
+    }
+}
+
+fn usage_from_hash(hash: &HashMap<String, u64>) -> Usage {
+    Usage {
+        classifications: hash.get("classifications").copied().unwrap_or(0),
+        stored_bytes: hash.get("stored_bytes").copied().unwrap_or(0),
+        tokens: hash.get("tokens").copied().unwrap_or(0),
+    }
+}
+
+#[async_trait]
+impl UsageStorage for RedisUsageStorage {
+    async fn add(&self, account: &str, month: &str, usage: &Usage) -> ClassifyResult<()> {
+        let key = self.get_usage_key(account, month);
+        let mut conn = self.connection.lock().await;
+
+        redis::pipe()
+            .atomic()
+            .hincr(&key, "classifications", usage.classifications)
+            .hincr(&key, "stored_bytes", usage.stored_bytes)
+            .hincr(&key, "tokens", usage.tokens)
+            .sadd(self.get_accounts_key(month), account)
+            .query_async::<_, ()>(&mut *conn)
+            .await
+            .map_err(|e| ClassifyError::StorageError(format!("Failed to update usage: {}", e)))?;
+
+        Ok(())
+    }
+
+    async fn get(&self, account: &str, month: &str) -> ClassifyResult<Usage> {
+        let mut conn = self.connection.lock().await;
+
+        let hash: HashMap<String, u64> = conn
+            .hgetall(self.get_usage_key(account, month))
+            .await
+            .map_err(|e| ClassifyError::StorageError(format!("Failed to get usage: {}", e)))?;
+
+        Ok(usage_from_hash(&hash))
+    }
+
+    async fn list(&self, month: &str) -> ClassifyResult<HashMap<String, Usage>> {
+        let accounts: Vec<String> = {
+            let mut conn = self.connection.lock().await;
+            conn.smembers(self.get_accounts_key(month))
+                .await
+                .map_err(|e| ClassifyError::StorageError(format!("Failed to list usage: {}", e)))?
+        };
+
+        let mut usage = HashMap::new();
+        for account in accounts {
+            let account_usage = self.get(&account, month).await?;
+            usage.insert(account, account_usage);
+        }
+
+        Ok(usage)
+    }
+}
@@ -0,0 +1,63 @@
+use async_trait::async_trait;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+use crate::storage::vector::{in_namespace, rank, StoredVector};
+use crate::storage::VectorStorage;
+use crate::ClassifyResult;
+
+/// Vectors kept in memory, searched by comparing the query with each of them
+#[derive(Default)]
+pub struct MemoryVectorStorage {
+    vectors: Mutex<HashMap<String, StoredVector>>,
+}
+
+impl MemoryVectorStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl VectorStorage for MemoryVectorStorage {
+    async fn store(&self, id: &str, stored: &StoredVector) -> ClassifyResult<()> {
+        self.vectors
+            .lock()
+            .unwrap()
+            .insert(id.to_string(), stored.clone());
+        Ok(())
+    }
+
+    async fn delete(&self, id: &str) -> ClassifyResult<()> {
+        self.vectors.lock().unwrap().remove(id);
+        Ok(())
+    }
+
+    async fn ids(&self) -> ClassifyResult<HashSet<String>> {
+        Ok(self.vectors.lock().unwrap().keys().cloned().collect())
+    }
+
+    async fn vectors(
+        &self,
+        namespace: Option<&str>,
+    ) -> ClassifyResult<Vec<(String, StoredVector)>> {
+        Ok(self
+            .vectors
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, stored)| in_namespace(stored, namespace))
+            .map(|(id, stored)| (id.clone(), stored.clone()))
+            .collect())
+    }
+
+    async fn nearest(
+        &self,
+        vector: &[f32],
+        namespace: Option<&str>,
+        limit: usize,
+    ) -> ClassifyResult<Vec<(String, f32)>> {
+        let vectors = self.vectors.lock().unwrap();
+        Ok(rank(vectors.iter(), vector, namespace, limit))
+    }
+}
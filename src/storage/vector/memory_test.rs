@@ -0,0 +1,58 @@
+use crate::storage::vector::memory::MemoryVectorStorage;
+use crate::storage::vector::{cosine_similarity, StoredVector};
+use crate::storage::VectorStorage;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn embedding(namespace: Option<&str>, vector: &[f32]) -> StoredVector {
+        StoredVector {
+            namespace: namespace.map(String::from),
+            vector: vector.to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_cosine_similarity() {
+        assert!((cosine_similarity(&[1.0, 0.0], &[2.0, 0.0]) - 1.0).abs() < 1e-6);
+        assert!(cosine_similarity(&[1.0, 0.0], &[0.0, 3.0]).abs() < 1e-6);
+        assert!((cosine_similarity(&[1.0, 0.0], &[-1.0, 0.0]) + 1.0).abs() < 1e-6);
+
+        // Vectors that can't be compared aren't similar
+        assert_eq!(cosine_similarity(&[1.0, 0.0], &[1.0, 0.0, 0.0]), 0.0);
+        assert_eq!(cosine_similarity(&[0.0, 0.0], &[1.0, 0.0]), 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_memory_store_returns_nearest_first() {
+        let store = MemoryVectorStorage::new();
+        store
+            .store("east", &embedding(None, &[1.0, 0.1]))
+            .await
+            .unwrap();
+        store
+            .store("north", &embedding(None, &[0.0, 1.0]))
+            .await
+            .unwrap();
+        store
+            .store("north-east", &embedding(Some("acme"), &[1.0, 1.0]))
+            .await
+            .unwrap();
+
+        let nearest = store.nearest(&[1.0, 0.0], None, 2).await.unwrap();
+        let ids: Vec<&str> = nearest.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(ids, vec!["east", "north-east"]);
+        assert!(nearest[0].1 > nearest[1].1);
+
+        // A tenant only finds its own content
+        let nearest = store.nearest(&[1.0, 0.0], Some("acme"), 10).await.unwrap();
+        assert_eq!(nearest.len(), 1);
+        assert_eq!(nearest[0].0, "north-east");
+
+        store.delete("east").await.unwrap();
+        let ids = store.ids().await.unwrap();
+        assert_eq!(ids.len(), 2);
+        assert!(!ids.contains("east"));
+    }
+}
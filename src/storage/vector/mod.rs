@@ -0,0 +1,64 @@
+pub mod memory;
+pub mod postgres;
+pub mod qdrant;
+#[cfg(feature = "redis")]
+pub mod redis;
+
+#[cfg(test)]
+mod memory_test;
+#[cfg(test)]
+mod postgres_test;
+#[cfg(test)]
+mod qdrant_test;
+#[cfg(all(test, feature = "redis"))]
+mod redis_test;
+
+use serde::{Deserialize, Serialize};
+
+/// The embedding vector of a content item, with the tenant it belongs to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StoredVector {
+    pub namespace: Option<String>,
+    pub vector: Vec<f32>,
+}
+
+/// Cosine similarity of two vectors, 0 when they differ in length or one is zero
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+
+    let (mut dot, mut norm_a, mut norm_b) = (0.0, 0.0, 0.0);
+    for (x, y) in a.iter().zip(b) {
+        dot += x * y;
+        norm_a += x * x;
+        norm_b += y * y;
+    }
+
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a.sqrt() * norm_b.sqrt())
+}
+
+fn in_namespace(stored: &StoredVector, namespace: Option<&str>) -> bool {
+    namespace.is_none() || stored.namespace.as_deref() == namespace
+}
+
+/// Score every vector against `vector` and keep the best `limit`, for
+/// backends that can't search themselves
+fn rank<'a>(
+    vectors: impl Iterator<Item = (&'a String, &'a StoredVector)>,
+    vector: &[f32],
+    namespace: Option<&str>,
+    limit: usize,
+) -> Vec<(String, f32)> {
+    let mut scored: Vec<(String, f32)> = vectors
+        .filter(|(_, stored)| in_namespace(stored, namespace))
+        .map(|(id, stored)| (id.clone(), cosine_similarity(vector, &stored.vector)))
+        .collect();
+
+    scored.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    scored.truncate(limit);
+    scored
+}
@@ -0,0 +1,146 @@
+use async_trait::async_trait;
+use std::collections::HashSet;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+use crate::storage::postgres::{connect, SharedClient};
+use crate::storage::vector::StoredVector;
+use crate::storage::VectorStorage;
+use crate::{ClassifyError, ClassifyResult};
+
+// Without a dimension on the column any embedding model fits, searches scan
+// the table instead of using an index
+const SCHEMA: &str = "
+    CREATE EXTENSION IF NOT EXISTS vector;
+    CREATE TABLE IF NOT EXISTS embeddings (
+        id TEXT PRIMARY KEY,
+        namespace TEXT,
+        embedding vector NOT NULL
+    );
+";
+
+/// Vectors kept in Postgres with the pgvector extension, searched by Postgres
+pub struct PostgresVectorStorage {
+    client: SharedClient,
+}
+
+impl PostgresVectorStorage {
+    pub async fn new(postgres_url: &str) -> ClassifyResult<Self> {
+        let client = connect(postgres_url).await?;
+        Self::with_client(Arc::new(Mutex::new(client))).await
+    }
+
+    /// Use a client shared with other storages
+    pub async fn with_client(client: SharedClient) -> ClassifyResult<Self> {
+        crate::storage::connections::register("postgres", &client);
+        client
+            .lock()
+            .await
+            .batch_execute(SCHEMA)
+            .await
+            .map_err(|e| {
+                ClassifyError::StorageError(format!("Failed to create embeddings table: {}", e))
+            })?;
+
+        Ok(Self { client })
+    }
+}
+
+fn storage_error(action: &str) -> impl Fn(tokio_postgres::Error) -> ClassifyError + '_ {
+    move |e| ClassifyError::StorageError(format!("Failed to {}: {}", action, e))
+}
+
+/// A vector in the text form of pgvector, `[1,2,3]`
+fn to_literal(vector: &[f32]) -> String {
+    let values: Vec<String> = vector.iter().map(|x| x.to_string()).collect();
+    format!("[{}]", values.join(","))
+}
+
+#[async_trait]
+impl VectorStorage for PostgresVectorStorage {
+    async fn store(&self, id: &str, stored: &StoredVector) -> ClassifyResult<()> {
+        let client = self.client.lock().await;
+        client
+            .execute(
+                "INSERT INTO embeddings (id, namespace, embedding)
+                 VALUES ($1, $2, $3::text::vector)
+                 ON CONFLICT (id) DO UPDATE SET
+                    namespace = EXCLUDED.namespace,
+                    embedding = EXCLUDED.embedding",
+                &[&id, &stored.namespace, &to_literal(&stored.vector)],
+            )
+            .await
+            .map_err(storage_error("store vector"))?;
+        Ok(())
+    }
+
+    async fn delete(&self, id: &str) -> ClassifyResult<()> {
+        let client = self.client.lock().await;
+        client
+            .execute("DELETE FROM embeddings WHERE id = $1", &[&id])
+            .await
+            .map_err(storage_error("delete vector"))?;
+        Ok(())
+    }
+
+    async fn ids(&self) -> ClassifyResult<HashSet<String>> {
+        let client = self.client.lock().await;
+        let rows = client
+            .query("SELECT id FROM embeddings", &[])
+            .await
+            .map_err(storage_error("list vectors"))?;
+        Ok(rows.iter().map(|row| row.get("id")).collect())
+    }
+
+    async fn vectors(
+        &self,
+        namespace: Option<&str>,
+    ) -> ClassifyResult<Vec<(String, StoredVector)>> {
+        let client = self.client.lock().await;
+        let rows = client
+            .query(
+                "SELECT id, namespace, embedding::text AS embedding FROM embeddings
+                 WHERE $1::text IS NULL OR namespace = $1",
+                &[&namespace],
+            )
+            .await
+            .map_err(storage_error("read vectors"))?;
+
+        rows.iter()
+            .map(|row| {
+                let vector: Vec<f32> = serde_json::from_str(row.get("embedding"))?;
+                Ok((
+                    row.get("id"),
+                    StoredVector {
+                        namespace: row.get("namespace"),
+                        vector,
+                    },
+                ))
+            })
+            .collect()
+    }
+
+    async fn nearest(
+        &self,
+        vector: &[f32],
+        namespace: Option<&str>,
+        limit: usize,
+    ) -> ClassifyResult<Vec<(String, f32)>> {
+        let client = self.client.lock().await;
+        let rows = client
+            .query(
+                "SELECT id, 1 - (embedding <=> $1::text::vector) AS score FROM embeddings
+                 WHERE $2::text IS NULL OR namespace = $2
+                 ORDER BY embedding <=> $1::text::vector
+                 LIMIT $3",
+                &[&to_literal(vector), &namespace, &(limit as i64)],
+            )
+            .await
+            .map_err(storage_error("search vectors"))?;
+
+        Ok(rows
+            .iter()
+            .map(|row| (row.get("id"), row.get::<_, f64>("score") as f32))
+            .collect())
+    }
+}
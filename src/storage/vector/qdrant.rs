@@ -0,0 +1,274 @@
+use async_trait::async_trait;
+use reqwest::{Client, RequestBuilder, StatusCode};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::config::QdrantConfig;
+use crate::storage::vector::StoredVector;
+use crate::storage::VectorStorage;
+use crate::{ClassifyError, ClassifyResult};
+
+/// Points fetched per scroll request
+const PAGE_SIZE: usize = 1_000;
+
+/// Vectors kept in a Qdrant collection, searched by Qdrant. The collection is
+/// created with the dimension of the first vector stored.
+pub struct QdrantVectorStorage {
+    client: Client,
+    url: String,
+    api_key: Option<String>,
+    collection: String,
+    /// Whether the collection is known to exist
+    ready: AtomicBool,
+}
+
+#[derive(Debug, Deserialize)]
+struct QdrantResponse<T> {
+    result: T,
+}
+
+#[derive(Debug, Deserialize)]
+struct ScrollResult {
+    points: Vec<Point>,
+    next_page_offset: Option<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Point {
+    id: Value,
+    #[serde(default)]
+    payload: Option<Value>,
+    #[serde(default)]
+    vector: Option<Vec<f32>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ScoredPoint {
+    id: Value,
+    score: f32,
+}
+
+/// Point IDs are UUIDs, as content IDs are
+fn point_id(id: &Value) -> String {
+    match id {
+        Value::String(id) => id.clone(),
+        id => id.to_string(),
+    }
+}
+
+fn namespace_filter(namespace: Option<&str>) -> Value {
+    match namespace {
+        Some(namespace) => {
+            json!({ "must": [{ "key": "namespace", "match": { "value": namespace } }] })
+        }
+        None => Value::Null,
+    }
+}
+
+impl QdrantVectorStorage {
+    pub fn new(config: &QdrantConfig) -> Self {
+        Self {
+            client: Client::new(),
+            url: config.url.trim_end_matches('/').to_string(),
+            api_key: config.api_key.clone(),
+            collection: config.collection.clone(),
+            ready: AtomicBool::new(false),
+        }
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> RequestBuilder {
+        let request = self.client.request(
+            method,
+            format!("{}/collections/{}{}", self.url, self.collection, path),
+        );
+        match &self.api_key {
+            Some(api_key) => request.header("api-key", api_key),
+            None => request,
+        }
+    }
+
+    /// Send a request, `None` when the collection doesn't exist yet
+    async fn send<T: for<'de> Deserialize<'de>>(
+        &self,
+        request: RequestBuilder,
+        action: &str,
+    ) -> ClassifyResult<Option<T>> {
+        let response = request
+            .send()
+            .await
+            .map_err(|e| ClassifyError::StorageError(format!("Failed to {}: {}", action, e)))?;
+
+        if response.status() == StatusCode::NOT_FOUND {
+            self.ready.store(false, Ordering::Relaxed);
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(ClassifyError::StorageError(format!(
+                "Failed to {}: Qdrant returned {}: {}",
+                action, status, body
+            )));
+        }
+
+        let body: QdrantResponse<T> = response
+            .json()
+            .await
+            .map_err(|e| ClassifyError::StorageError(format!("Failed to {}: {}", action, e)))?;
+        Ok(Some(body.result))
+    }
+
+    /// Create the collection for vectors of `dimension` when it doesn't exist
+    async fn ensure_collection(&self, dimension: usize) -> ClassifyResult<()> {
+        if self.ready.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+
+        let exists: Option<Value> = self
+            .send(self.request(reqwest::Method::GET, ""), "look up collection")
+            .await?;
+        if exists.is_none() {
+            self.send::<Value>(
+                self.request(reqwest::Method::PUT, "").json(&json!({
+                    "vectors": { "size": dimension, "distance": "Cosine" }
+                })),
+                "create collection",
+            )
+            .await?;
+        }
+
+        self.ready.store(true, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// All points, with their vectors when `with_vector` is set
+    async fn scroll(
+        &self,
+        namespace: Option<&str>,
+        with_vector: bool,
+    ) -> ClassifyResult<Vec<Point>> {
+        let mut points = Vec::new();
+        let mut offset = Value::Null;
+        loop {
+            let page: Option<ScrollResult> = self
+                .send(
+                    self.request(reqwest::Method::POST, "/points/scroll")
+                        .json(&json!({
+                            "limit": PAGE_SIZE,
+                            "offset": offset,
+                            "filter": namespace_filter(namespace),
+                            "with_payload": true,
+                            "with_vector": with_vector,
+                        })),
+                    "list vectors",
+                )
+                .await?;
+            let Some(page) = page else {
+                return Ok(points);
+            };
+
+            points.extend(page.points);
+            match page.next_page_offset {
+                Some(next) if !next.is_null() => offset = next,
+                _ => return Ok(points),
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl VectorStorage for QdrantVectorStorage {
+    async fn store(&self, id: &str, stored: &StoredVector) -> ClassifyResult<()> {
+        self.ensure_collection(stored.vector.len()).await?;
+        self.send::<Value>(
+            self.request(reqwest::Method::PUT, "/points?wait=true")
+                .json(&json!({
+                    "points": [{
+                        "id": id,
+                        "vector": stored.vector,
+                        "payload": { "namespace": stored.namespace },
+                    }]
+                })),
+            "store vector",
+        )
+        .await?
+        .ok_or_else(|| {
+            ClassifyError::StorageError(format!(
+                "Failed to store vector: collection {} was removed",
+                self.collection
+            ))
+        })?;
+        Ok(())
+    }
+
+    async fn delete(&self, id: &str) -> ClassifyResult<()> {
+        self.send::<Value>(
+            self.request(reqwest::Method::POST, "/points/delete?wait=true")
+                .json(&json!({ "points": [id] })),
+            "delete vector",
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn ids(&self) -> ClassifyResult<HashSet<String>> {
+        Ok(self
+            .scroll(None, false)
+            .await?
+            .iter()
+            .map(|point| point_id(&point.id))
+            .collect())
+    }
+
+    async fn vectors(
+        &self,
+        namespace: Option<&str>,
+    ) -> ClassifyResult<Vec<(String, StoredVector)>> {
+        Ok(self
+            .scroll(namespace, true)
+            .await?
+            .into_iter()
+            .filter_map(|point| {
+                let namespace = point
+                    .payload
+                    .as_ref()
+                    .and_then(|payload| payload["namespace"].as_str())
+                    .map(String::from);
+                Some((
+                    point_id(&point.id),
+                    StoredVector {
+                        namespace,
+                        vector: point.vector?,
+                    },
+                ))
+            })
+            .collect())
+    }
+
+    async fn nearest(
+        &self,
+        vector: &[f32],
+        namespace: Option<&str>,
+        limit: usize,
+    ) -> ClassifyResult<Vec<(String, f32)>> {
+        let scored: Option<Vec<ScoredPoint>> = self
+            .send(
+                self.request(reqwest::Method::POST, "/points/search")
+                    .json(&json!({
+                        "vector": vector,
+                        "limit": limit,
+                        "filter": namespace_filter(namespace),
+                    })),
+                "search vectors",
+            )
+            .await?;
+
+        Ok(scored
+            .unwrap_or_default()
+            .into_iter()
+            .map(|point| (point_id(&point.id), point.score))
+            .collect())
+    }
+}
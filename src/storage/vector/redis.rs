@@ -0,0 +1,161 @@
+use async_trait::async_trait;
+use serde_json::json;
+use std::collections::HashSet;
+
+use crate::storage::redis::SharedConnection;
+use crate::storage::vector::{in_namespace, StoredVector};
+use crate::storage::VectorStorage;
+use crate::{ClassifyError, ClassifyResult};
+
+/// Vectors kept in a Redis vector set, searched by Redis itself. Vector sets
+/// were added in Redis 8.
+pub struct RedisVectorStorage {
+    connection: SharedConnection,
+    key: String,
+}
+
+impl RedisVectorStorage {
+    pub fn with_connection(connection: SharedConnection, prefix: Option<&str>) -> Self {
+        crate::storage::connections::register("redis", &connection);
+        Self {
+            connection,
+            key: format!("{}vectors", prefix.unwrap_or("classify:")),
+        }
+    }
+}
+
+fn storage_error(action: &str) -> impl Fn(redis::RedisError) -> ClassifyError + '_ {
+    move |e| ClassifyError::StorageError(format!("Failed to {}: {}", action, e))
+}
+
+/// Append `VALUES <dim> <x>...`, the vector as text
+fn values(cmd: &mut redis::Cmd, vector: &[f32]) {
+    cmd.arg("VALUES").arg(vector.len());
+    for x in vector {
+        cmd.arg(*x);
+    }
+}
+
+#[async_trait]
+impl VectorStorage for RedisVectorStorage {
+    async fn store(&self, id: &str, stored: &StoredVector) -> ClassifyResult<()> {
+        let mut cmd = redis::cmd("VADD");
+        cmd.arg(&self.key);
+        values(&mut cmd, &stored.vector);
+        // Unquantized, so clustering gets the vectors back as they were stored
+        cmd.arg(id)
+            .arg("NOQUANT")
+            .arg("SETATTR")
+            .arg(json!({ "namespace": stored.namespace }).to_string());
+
+        let mut conn = self.connection.lock().await;
+        cmd.query_async::<_, ()>(&mut *conn)
+            .await
+            .map_err(storage_error("store vector"))
+    }
+
+    async fn delete(&self, id: &str) -> ClassifyResult<()> {
+        let mut conn = self.connection.lock().await;
+        redis::cmd("VREM")
+            .arg(&self.key)
+            .arg(id)
+            .query_async::<_, ()>(&mut *conn)
+            .await
+            .map_err(storage_error("delete vector"))
+    }
+
+    async fn ids(&self) -> ClassifyResult<HashSet<String>> {
+        let mut conn = self.connection.lock().await;
+        let count: usize = redis::cmd("VCARD")
+            .arg(&self.key)
+            .query_async(&mut *conn)
+            .await
+            .map_err(storage_error("count vectors"))?;
+        if count == 0 {
+            return Ok(HashSet::new());
+        }
+
+        // A positive count returns distinct elements, all of them when it's the size of the set
+        redis::cmd("VRANDMEMBER")
+            .arg(&self.key)
+            .arg(count)
+            .query_async(&mut *conn)
+            .await
+            .map_err(storage_error("list vectors"))
+    }
+
+    async fn vectors(
+        &self,
+        namespace: Option<&str>,
+    ) -> ClassifyResult<Vec<(String, StoredVector)>> {
+        let ids: Vec<String> = self.ids().await?.into_iter().collect();
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut pipe = redis::pipe();
+        for id in &ids {
+            pipe.cmd("VEMB").arg(&self.key).arg(id);
+            pipe.cmd("VGETATTR").arg(&self.key).arg(id);
+        }
+        let replies: Vec<(Option<Vec<f32>>, Option<String>)> = {
+            let mut conn = self.connection.lock().await;
+            let flat: Vec<redis::Value> = pipe
+                .query_async(&mut *conn)
+                .await
+                .map_err(storage_error("read vectors"))?;
+            flat.chunks(2)
+                .map(|pair| {
+                    Ok((
+                        redis::from_redis_value(&pair[0])?,
+                        redis::from_redis_value(&pair[1])?,
+                    ))
+                })
+                .collect::<Result<_, redis::RedisError>>()
+                .map_err(storage_error("read vectors"))?
+        };
+
+        Ok(ids
+            .into_iter()
+            .zip(replies)
+            .filter_map(|(id, (vector, attributes))| {
+                // Elements removed between listing and reading have no vector
+                let vector = vector?;
+                let namespace = attributes
+                    .and_then(|json| serde_json::from_str::<serde_json::Value>(&json).ok())
+                    .and_then(|attributes| attributes["namespace"].as_str().map(String::from));
+                Some((id, StoredVector { namespace, vector }))
+            })
+            .filter(|(_, stored)| in_namespace(stored, namespace))
+            .collect())
+    }
+
+    async fn nearest(
+        &self,
+        vector: &[f32],
+        namespace: Option<&str>,
+        limit: usize,
+    ) -> ClassifyResult<Vec<(String, f32)>> {
+        let mut cmd = redis::cmd("VSIM");
+        cmd.arg(&self.key);
+        values(&mut cmd, vector);
+        cmd.arg("WITHSCORES").arg("COUNT").arg(limit);
+        if let Some(namespace) = namespace {
+            cmd.arg("FILTER")
+                .arg(format!(".namespace == {}", json!(namespace)));
+        }
+
+        let scored: Vec<(String, f32)> = {
+            let mut conn = self.connection.lock().await;
+            cmd.query_async(&mut *conn)
+                .await
+                .map_err(storage_error("search vectors"))?
+        };
+
+        // Redis scores from 0 for opposite to 1 for identical vectors
+        Ok(scored
+            .into_iter()
+            .map(|(id, score)| (id, score * 2.0 - 1.0))
+            .collect())
+    }
+}
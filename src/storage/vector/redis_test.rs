@@ -0,0 +1,59 @@
+use std::env;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::storage::redis::{connect, RedisConnectOptions};
+use crate::storage::vector::redis::RedisVectorStorage;
+use crate::storage::vector::StoredVector;
+use crate::storage::VectorStorage;
+use crate::ClassifyResult;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_redis_vector_storage_integration() -> ClassifyResult<()> {
+        // This test requires Redis 8 or later
+        let redis_url =
+            env::var("TEST_REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string());
+        let connection = connect(&redis_url, &RedisConnectOptions::default()).await?;
+        let prefix = format!("test:{}:", Uuid::new_v4());
+        let storage =
+            RedisVectorStorage::with_connection(Arc::new(Mutex::new(connection)), Some(&prefix));
+
+        let namespace = format!("test-{}", Uuid::new_v4());
+        let east = Uuid::new_v4().to_string();
+        let north = Uuid::new_v4().to_string();
+        for (id, vector) in [(&east, vec![1.0, 0.1, 0.0]), (&north, vec![0.0, 1.0, 0.0])] {
+            storage
+                .store(
+                    id,
+                    &StoredVector {
+                        namespace: Some(namespace.clone()),
+                        vector,
+                    },
+                )
+                .await?;
+        }
+
+        let nearest = storage
+            .nearest(&[1.0, 0.0, 0.0], Some(&namespace), 2)
+            .await?;
+        assert_eq!(nearest.len(), 2);
+        assert_eq!(nearest[0].0, east);
+        assert!(nearest[0].1 > 0.9);
+
+        let vectors = storage.vectors(Some(&namespace)).await?;
+        assert_eq!(vectors.len(), 2);
+        assert!(storage.ids().await?.contains(&north));
+
+        storage.delete(&east).await?;
+        storage.delete(&north).await?;
+        assert!(storage.vectors(Some(&namespace)).await?.is_empty());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,434 @@
+use std::collections::{BTreeMap, HashMap};
+use tracing::info;
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::api::AppState;
+use crate::config::{TagPolicy, TagWordSeparator};
+use crate::embedding::index::SemanticIndex;
+use crate::storage::vector::cosine_similarity;
+use crate::{ClassifyResult, TagDuplicate, TagMergeRequest, TagNode};
+
+/// Separator between the levels of a hierarchical tag, as in `lang/rust`
+pub const TAG_SEPARATOR: char = '/';
+
+/// Write a hierarchical tag with `/` between its levels, `>` is accepted too:
+/// `topic > ai > ml` becomes `topic/ai/ml`. Empty levels are dropped.
+pub fn normalize_tag_path(tag: &str) -> String {
+    tag.split([TAG_SEPARATOR, '>'])
+        .map(str::trim)
+        .filter(|level| !level.is_empty())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Embeddings of tags at least this alike are reported as duplicates
+const SEMANTIC_DUPLICATE_SIMILARITY: f32 = 0.85;
+
+/// Words that end in `s` without being plural
+const NOT_PLURAL: &[&str] = &[
+    "analysis",
+    "aws",
+    "business",
+    "chaos",
+    "kubernetes",
+    "news",
+    "postgres",
+    "redis",
+    "series",
+    "species",
+    "status",
+    "windows",
+];
+
+/// The singular of an English plural, by the common rules. Short words and
+/// words ending in `ss`, `us` or `is` are left alone.
+pub fn singularize(word: &str) -> String {
+    let ends_with = |end: &str| {
+        word.len() >= end.len()
+            && word.is_char_boundary(word.len() - end.len())
+            && word[word.len() - end.len()..].eq_ignore_ascii_case(end)
+    };
+    if word.chars().count() <= 3
+        || NOT_PLURAL.contains(&word.to_lowercase().as_str())
+        || ["ss", "us", "is"].into_iter().any(ends_with)
+    {
+        return word.to_string();
+    }
+
+    if ends_with("ies") {
+        format!("{}y", &word[..word.len() - 3])
+    } else if ["sses", "shes", "ches", "xes", "zes"]
+        .into_iter()
+        .any(ends_with)
+    {
+        word[..word.len() - 2].to_string()
+    } else if ends_with("s") {
+        word[..word.len() - 1].to_string()
+    } else {
+        word.to_string()
+    }
+}
+
+/// Drop accents and similar marks, `café` becomes `cafe`
+fn fold_unicode(text: &str) -> String {
+    text.nfkd().filter(|c| !is_combining_mark(*c)).collect()
+}
+
+impl TagPolicy {
+    /// A tag in the form it is stored in: its levels separated by `/`, and
+    /// each level cased, separated, singularized and folded as configured
+    pub fn normalize_tag(&self, tag: &str) -> String {
+        normalize_tag_path(tag)
+            .split(TAG_SEPARATOR)
+            .map(|level| self.normalize_level(level))
+            .filter(|level| !level.is_empty())
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    fn normalize_level(&self, level: &str) -> String {
+        let mut level = level.to_string();
+        if self.fold_unicode {
+            level = fold_unicode(&level);
+        }
+        if self.lowercase {
+            level = level.to_lowercase();
+        }
+
+        let separator = match self.word_separator {
+            TagWordSeparator::Keep => None,
+            TagWordSeparator::Hyphen => Some("-"),
+            TagWordSeparator::Space => Some(" "),
+            TagWordSeparator::Underscore => Some("_"),
+        };
+        let mut words: Vec<String> = match separator {
+            Some(_) => level
+                .split(|c: char| c.is_whitespace() || c == '-' || c == '_')
+                .filter(|word| !word.is_empty())
+                .map(String::from)
+                .collect(),
+            None => vec![level],
+        };
+
+        // Only the last word is a plural in tags like `neural-networks`
+        if self.singularize {
+            if let Some(last) = words.last_mut() {
+                *last = singularize(last);
+            }
+        }
+
+        words.join(separator.unwrap_or_default())
+    }
+
+    /// Tags normalized by the policy, empty and repeated tags dropped, in
+    /// their original order
+    pub fn normalize_tags<S: AsRef<str>>(&self, tags: &[S]) -> Vec<String> {
+        let mut normalized: Vec<String> = Vec::with_capacity(tags.len());
+        for tag in tags {
+            let tag = self.normalize_tag(tag.as_ref());
+            if !tag.is_empty() && !normalized.contains(&tag) {
+                normalized.push(tag);
+            }
+        }
+        normalized
+    }
+
+    /// Whether a normalized tag is on the blocklist
+    pub fn is_blocked(&self, tag: &str) -> bool {
+        self.blocklist
+            .iter()
+            .any(|blocked| self.normalize_tag(blocked).to_lowercase() == tag.to_lowercase())
+    }
+
+    /// Tags of the classifier as they are stored: normalized, without blocked tags
+    pub fn apply<S: AsRef<str>>(&self, tags: &[S]) -> Vec<String> {
+        let mut tags = self.normalize_tags(tags);
+        tags.retain(|tag| !self.is_blocked(tag));
+        tags
+    }
+}
+
+/// Remove the tags on the blocklist from stored content, for content classified
+/// before they were blocked. Returns the number of items changed per tag.
+pub async fn strip_blocked_tags(state: &AppState) -> ClassifyResult<HashMap<String, usize>> {
+    let blocked: Vec<String> = state
+        .tag_storage
+        .list_tags()
+        .await?
+        .into_iter()
+        .filter(|tag| state.ingest.tags.is_blocked(tag))
+        .collect();
+    replace_tags(state, &blocked, None).await
+}
+
+/// Replace `tags` with `into` on all content that has them, e.g. `databases`
+/// with `database`. Returns the number of items changed per tag.
+pub async fn merge_tags(
+    state: &AppState,
+    tags: &[String],
+    into: &str,
+) -> ClassifyResult<HashMap<String, usize>> {
+    let tags: Vec<String> = tags.iter().filter(|tag| *tag != into).cloned().collect();
+    replace_tags(state, &tags, Some(into)).await
+}
+
+/// Remove `tags` from stored content, in both content and tag storage, adding
+/// `into` in their place when set
+async fn replace_tags(
+    state: &AppState,
+    tags: &[String],
+    into: Option<&str>,
+) -> ClassifyResult<HashMap<String, usize>> {
+    // The replaced tags of each content item, so every item is stored once
+    let mut tags_by_content: HashMap<String, Vec<String>> = HashMap::new();
+    let mut replaced = HashMap::new();
+    for tag in tags {
+        let ids = state.tag_storage.find_by_tag(tag).await?;
+        if ids.is_empty() {
+            continue;
+        }
+        replaced.insert(tag.clone(), ids.len());
+        for id in ids {
+            tags_by_content.entry(id).or_default().push(tag.clone());
+        }
+    }
+
+    for (id, old) in tags_by_content {
+        state.tag_storage.remove_tags(&id, &old).await?;
+        if let Some(into) = into {
+            state.tag_storage.add_tags(&id, &[into.to_string()]).await?;
+        }
+
+        if let Some(content) = state.content_storage.get(&id).await? {
+            let mut tags: Vec<String> = Vec::with_capacity(content.tags.len());
+            for tag in &content.tags {
+                let tag = match into {
+                    Some(into) if old.contains(tag) => into.to_string(),
+                    None if old.contains(tag) => continue,
+                    _ => tag.clone(),
+                };
+                if !tags.contains(&tag) {
+                    tags.push(tag);
+                }
+            }
+            state
+                .content_storage
+                .store(&content.with_tags(tags))
+                .await?;
+        }
+
+        match into {
+            Some(into) => info!("Merged tags {:?} into {} on content {}", old, into, id),
+            None => info!("Removed tags {:?} from content {}", old, id),
+        }
+    }
+
+    Ok(replaced)
+}
+
+/// Number of single-character edits that turn `a` into `b`
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+fn words(tag: &str) -> Vec<&str> {
+    tag.split(|c: char| c.is_whitespace() || c == '-' || c == '_')
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+/// Why two tags look like the same tag, with a similarity from 0 to 1
+fn spelling_match(a: &str, b: &str) -> Option<(&'static str, f32)> {
+    // The same words in another case, separated or in the plural
+    let key = |tag: &str| {
+        let mut words: Vec<String> = words(tag).iter().map(|w| w.to_lowercase()).collect();
+        if let Some(last) = words.last_mut() {
+            *last = singularize(last);
+        }
+        words.concat()
+    };
+    let (key_a, key_b) = (key(a), key(b));
+    if key_a == key_b {
+        return Some(("spelling", 1.0));
+    }
+
+    // An abbreviation of the other tag's words, `ai` for `artificial-intelligence`
+    let (short, long) = if a.len() < b.len() { (a, b) } else { (b, a) };
+    let long_words = words(long);
+    if long_words.len() > 1 && short.len() == long_words.len() {
+        let initials: String = long_words
+            .iter()
+            .filter_map(|word| word.chars().next())
+            .collect();
+        if initials.eq_ignore_ascii_case(short) {
+            return Some(("acronym", 0.9));
+        }
+    }
+
+    // A typo, only for tags long enough that an edit doesn't change the word
+    let length = key_a.chars().count().max(key_b.chars().count());
+    let allowed = match length {
+        0..=4 => 0,
+        5..=9 => 1,
+        _ => 2,
+    };
+    let distance = edit_distance(&key_a, &key_b);
+    (distance <= allowed).then(|| ("spelling", 1.0 - distance as f32 / length as f32))
+}
+
+/// Tags whose spelling suggests they are the same tag. Each pair suggests
+/// merging the tag with fewer content items into the other.
+pub fn spelling_duplicates(counts: &HashMap<String, usize>) -> Vec<TagDuplicate> {
+    let mut tags: Vec<&String> = counts.keys().collect();
+    tags.sort();
+
+    let mut duplicates = Vec::new();
+    for (i, a) in tags.iter().enumerate() {
+        for b in &tags[i + 1..] {
+            // Levels of a hierarchy aren't duplicates of each other
+            if is_within(a, b) || is_within(b, a) {
+                continue;
+            }
+            if let Some((reason, score)) = spelling_match(a, b) {
+                duplicates.push(duplicate(a, b, counts, reason, score));
+            }
+        }
+    }
+    duplicates
+}
+
+/// Tags whose embeddings are at least `min_similarity` alike
+pub async fn semantic_duplicates(
+    index: &SemanticIndex,
+    counts: &HashMap<String, usize>,
+    min_similarity: f32,
+) -> ClassifyResult<Vec<TagDuplicate>> {
+    let mut tags: Vec<&String> = counts.keys().collect();
+    tags.sort();
+
+    let mut vectors = Vec::with_capacity(tags.len());
+    for tag in &tags {
+        // Hierarchical tags read better as words, `lang rust`
+        vectors.push(index.embed(&tag.replace(TAG_SEPARATOR, " ")).await?);
+    }
+
+    let mut duplicates = Vec::new();
+    for i in 0..tags.len() {
+        for j in i + 1..tags.len() {
+            let score = cosine_similarity(&vectors[i], &vectors[j]);
+            if score >= min_similarity {
+                duplicates.push(duplicate(tags[i], tags[j], counts, "meaning", score));
+            }
+        }
+    }
+    Ok(duplicates)
+}
+
+fn duplicate(
+    a: &str,
+    b: &str,
+    counts: &HashMap<String, usize>,
+    reason: &str,
+    score: f32,
+) -> TagDuplicate {
+    let count = |tag: &str| counts.get(tag).copied().unwrap_or_default();
+    // The more used tag is kept, the shorter one when both are used as much
+    let (tag, into) = match count(a).cmp(&count(b)) {
+        std::cmp::Ordering::Less => (a, b),
+        std::cmp::Ordering::Greater => (b, a),
+        std::cmp::Ordering::Equal if a.len() < b.len() => (b, a),
+        std::cmp::Ordering::Equal => (a, b),
+    };
+
+    TagDuplicate {
+        tag: tag.to_string(),
+        into: into.to_string(),
+        reason: reason.to_string(),
+        score,
+        merge: TagMergeRequest {
+            tags: vec![tag.to_string()],
+            into: into.to_string(),
+        },
+    }
+}
+
+/// Probable duplicates among the stored tags, by spelling and, with
+/// `semantic`, by the meaning of their embeddings. The most alike first.
+pub async fn find_duplicates(
+    state: &AppState,
+    semantic: bool,
+) -> ClassifyResult<Vec<TagDuplicate>> {
+    let counts = state.tag_storage.tag_counts().await?;
+    let mut duplicates = spelling_duplicates(&counts);
+
+    if let Some(index) = state.semantic_index.as_ref().filter(|_| semantic) {
+        for found in semantic_duplicates(index, &counts, SEMANTIC_DUPLICATE_SIMILARITY).await? {
+            let known = duplicates.iter().any(|known| {
+                (known.tag == found.tag && known.into == found.into)
+                    || (known.tag == found.into && known.into == found.tag)
+            });
+            if !known {
+                duplicates.push(found);
+            }
+        }
+    }
+
+    duplicates.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.tag.cmp(&b.tag)));
+    Ok(duplicates)
+}
+
+/// Whether `tag` is `parent` or one of the tags below it
+pub fn is_within(tag: &str, parent: &str) -> bool {
+    tag.strip_prefix(parent)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with(TAG_SEPARATOR))
+}
+
+#[derive(Default)]
+struct Branch {
+    tagged: bool,
+    children: BTreeMap<String, Branch>,
+}
+
+/// Arrange tags by their levels, sorted by name. Levels that aren't tags
+/// themselves, like `lang` for `lang/rust`, are included to hold their children.
+pub fn tag_tree(tags: &[String]) -> Vec<TagNode> {
+    let mut root = Branch::default();
+    for tag in tags {
+        let mut branch = &mut root;
+        for level in tag.split(TAG_SEPARATOR) {
+            branch = branch.children.entry(level.to_string()).or_default();
+        }
+        branch.tagged = true;
+    }
+
+    nodes(root.children, None)
+}
+
+fn nodes(children: BTreeMap<String, Branch>, parent: Option<&str>) -> Vec<TagNode> {
+    children
+        .into_iter()
+        .map(|(name, branch)| {
+            let tag = match parent {
+                Some(parent) => format!("{}{}{}", parent, TAG_SEPARATOR, name),
+                None => name.clone(),
+            };
+            TagNode {
+                children: nodes(branch.children, Some(&tag)),
+                tagged: branch.tagged,
+                name,
+                tag,
+            }
+        })
+        .collect()
+}
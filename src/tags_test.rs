@@ -0,0 +1,156 @@
+use crate::config::{TagPolicy, TagWordSeparator};
+use crate::tags::{
+    edit_distance, is_within, normalize_tag_path, singularize, spelling_duplicates, tag_tree,
+};
+use std::collections::HashMap;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tag_paths_are_normalized() {
+        assert_eq!(normalize_tag_path("lang/rust"), "lang/rust");
+        assert_eq!(normalize_tag_path("topic > ai > ml"), "topic/ai/ml");
+        assert_eq!(normalize_tag_path("/lang//rust/"), "lang/rust");
+        assert_eq!(normalize_tag_path(" > "), "");
+
+        assert_eq!(
+            TagPolicy::default().normalize_tags(&["rust", "lang>rust", " rust ", "", "lang/rust"]),
+            vec!["rust", "lang/rust"]
+        );
+    }
+
+    #[test]
+    fn test_policy_normalizes_words() {
+        let policy = TagPolicy {
+            lowercase: true,
+            word_separator: TagWordSeparator::Hyphen,
+            singularize: true,
+            fold_unicode: true,
+            ..TagPolicy::default()
+        };
+
+        assert_eq!(
+            policy.normalize_tags(&["Machine Learning", "machine-learning", "machine_learning"]),
+            vec!["machine-learning"]
+        );
+        assert_eq!(policy.normalize_tag("Neural Networks"), "neural-network");
+        assert_eq!(policy.normalize_tag("Cuisine > Crêpes"), "cuisine/crepe");
+        assert_eq!(policy.normalize_tag("news"), "news");
+
+        // Without a policy tags keep their words as written
+        assert_eq!(
+            TagPolicy::default().normalize_tag("Machine Learning"),
+            "Machine Learning"
+        );
+    }
+
+    #[test]
+    fn test_singularize() {
+        assert_eq!(singularize("databases"), "database");
+        assert_eq!(singularize("categories"), "category");
+        assert_eq!(singularize("boxes"), "box");
+        assert_eq!(singularize("classes"), "class");
+        assert_eq!(singularize("Recipes"), "Recipe");
+
+        for word in ["css", "aws", "class", "status", "analysis", "rust", "ios"] {
+            assert_eq!(singularize(word), word);
+        }
+    }
+
+    #[test]
+    fn test_policy_drops_blocked_tags() {
+        let policy = TagPolicy {
+            blocklist: vec!["misc".to_string(), "topic/other".to_string()],
+            ..TagPolicy::default()
+        };
+
+        assert_eq!(
+            policy.apply(&["rust", "MISC", "topic > other", "topic/ai"]),
+            vec!["rust", "topic/ai"]
+        );
+        assert!(TagPolicy::default()
+            .apply(&["misc"])
+            .contains(&"misc".to_string()));
+    }
+
+    #[test]
+    fn test_is_within_matches_whole_levels() {
+        assert!(is_within("lang", "lang"));
+        assert!(is_within("lang/rust", "lang"));
+        assert!(is_within("lang/rust/async", "lang"));
+        assert!(!is_within("language", "lang"));
+        assert!(!is_within("lang", "lang/rust"));
+    }
+
+    #[test]
+    fn test_tag_tree_groups_levels() {
+        let tags: Vec<String> = ["web", "lang/rust", "lang/go", "lang/rust/async"]
+            .iter()
+            .map(|tag| tag.to_string())
+            .collect();
+
+        let tree = tag_tree(&tags);
+        assert_eq!(tree.len(), 2);
+
+        let lang = &tree[0];
+        assert_eq!((lang.name.as_str(), lang.tagged), ("lang", false));
+        assert_eq!(lang.children.len(), 2);
+        let rust = &lang.children[1];
+        assert_eq!((rust.tag.as_str(), rust.tagged), ("lang/rust", true));
+        assert_eq!(rust.children[0].tag, "lang/rust/async");
+
+        assert_eq!(tree[1].tag, "web");
+        assert!(tree[1].children.is_empty());
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("kubernetes", "kubernetes"), 0);
+        assert_eq!(edit_distance("kubernetes", "kubernets"), 1);
+        assert_eq!(edit_distance("rust", "rest"), 1);
+        assert_eq!(edit_distance("", "go"), 2);
+    }
+
+    #[test]
+    fn test_spelling_duplicates() {
+        let counts: HashMap<String, usize> = [
+            ("database", 5),
+            ("databases", 2),
+            ("ai", 7),
+            ("artificial-intelligence", 1),
+            ("Machine Learning", 1),
+            ("machine-learning", 3),
+            ("kubernets", 1),
+            ("kubernetes", 4),
+            ("rust", 3),
+            ("rest", 2),
+            ("lang", 1),
+            ("lang/rust", 2),
+        ]
+        .into_iter()
+        .map(|(tag, count)| (tag.to_string(), count))
+        .collect();
+
+        let mut found: Vec<(String, String, String)> = spelling_duplicates(&counts)
+            .into_iter()
+            .map(|duplicate| (duplicate.tag, duplicate.into, duplicate.reason))
+            .collect();
+        found.sort();
+
+        let expected = [
+            ("Machine Learning", "machine-learning", "spelling"),
+            ("artificial-intelligence", "ai", "acronym"),
+            ("databases", "database", "spelling"),
+            ("kubernets", "kubernetes", "spelling"),
+        ];
+        assert_eq!(
+            found,
+            expected
+                .iter()
+                .map(|(tag, into, reason)| (tag.to_string(), into.to_string(), reason.to_string()))
+                .collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,324 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Days, NaiveDate, Utc};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tracing::{info, warn};
+
+use crate::config::{AppConfig, TrendStoreType, TrendsConfig};
+use crate::storage::ContentStorage;
+use crate::{ClassifyError, ClassifyResult, TagTrend};
+
+/// Counters of all content, next to those of each tenant
+const ALL_SCOPE: &str = "*";
+
+/// Daily counters of the tags given to classified content, per scope: all
+/// content or the content of a tenant
+#[async_trait]
+pub trait TrendStore: Send + Sync {
+    /// Count one more item with each of `tags` on `day`
+    async fn increment(&self, scope: &str, day: NaiveDate, tags: &[String]) -> ClassifyResult<()>;
+
+    /// Counts per tag on each of `days`, in the same order
+    async fn counts(
+        &self,
+        scope: &str,
+        days: &[NaiveDate],
+    ) -> ClassifyResult<Vec<HashMap<String, u64>>>;
+}
+
+/// Counters kept in memory, rebuilt from stored content at startup
+pub struct MemoryTrendStore {
+    days: Mutex<HashMap<(String, NaiveDate), HashMap<String, u64>>>,
+    retention_days: u32,
+}
+
+impl MemoryTrendStore {
+    pub fn new(retention_days: u32) -> Self {
+        Self {
+            days: Mutex::new(HashMap::new()),
+            retention_days,
+        }
+    }
+}
+
+#[async_trait]
+impl TrendStore for MemoryTrendStore {
+    async fn increment(&self, scope: &str, day: NaiveDate, tags: &[String]) -> ClassifyResult<()> {
+        let oldest = Utc::now().date_naive() - Days::new(self.retention_days.into());
+        let mut days = self.days.lock().unwrap();
+        days.retain(|(_, counted), _| *counted > oldest);
+
+        if day > oldest {
+            let counts = days.entry((scope.to_string(), day)).or_default();
+            for tag in tags {
+                *counts.entry(tag.clone()).or_default() += 1;
+            }
+        }
+        Ok(())
+    }
+
+    async fn counts(
+        &self,
+        scope: &str,
+        days: &[NaiveDate],
+    ) -> ClassifyResult<Vec<HashMap<String, u64>>> {
+        let counted = self.days.lock().unwrap();
+        Ok(days
+            .iter()
+            .map(|day| {
+                counted
+                    .get(&(scope.to_string(), *day))
+                    .cloned()
+                    .unwrap_or_default()
+            })
+            .collect())
+    }
+}
+
+/// Counters kept in Redis as a hash per scope and day, expiring after the
+/// retention period
+#[cfg(feature = "redis")]
+pub struct RedisTrendStore {
+    connection: crate::storage::redis::SharedConnection,
+    prefix: String,
+    retention_days: u32,
+}
+
+#[cfg(feature = "redis")]
+impl RedisTrendStore {
+    pub fn with_connection(
+        connection: crate::storage::redis::SharedConnection,
+        prefix: Option<&str>,
+        retention_days: u32,
+    ) -> Self {
+        crate::storage::connections::register("redis", &connection);
+        Self {
+            connection,
+            prefix: prefix.unwrap_or("classify:").to_string(),
+            retention_days,
+        }
+    }
+
+    fn day_key(&self, scope: &str, day: NaiveDate) -> String {
+        format!("{}trend:{}:{}", self.prefix, scope, day.format("%Y-%m-%d"))
+    }
+}
+
+#[cfg(feature = "redis")]
+#[async_trait]
+impl TrendStore for RedisTrendStore {
+    async fn increment(&self, scope: &str, day: NaiveDate, tags: &[String]) -> ClassifyResult<()> {
+        let key = self.day_key(scope, day);
+        let mut pipe = redis::pipe();
+        for tag in tags {
+            pipe.cmd("HINCRBY").arg(&key).arg(tag).arg(1).ignore();
+        }
+        pipe.cmd("EXPIRE")
+            .arg(&key)
+            .arg((u64::from(self.retention_days) + 1) * 24 * 3600)
+            .ignore();
+
+        let mut conn = self.connection.lock().await;
+        pipe.query_async::<_, ()>(&mut *conn)
+            .await
+            .map_err(|e| ClassifyError::StorageError(format!("Failed to count tags: {}", e)))
+    }
+
+    async fn counts(
+        &self,
+        scope: &str,
+        days: &[NaiveDate],
+    ) -> ClassifyResult<Vec<HashMap<String, u64>>> {
+        let mut pipe = redis::pipe();
+        for day in days {
+            pipe.cmd("HGETALL").arg(self.day_key(scope, *day));
+        }
+
+        let mut conn = self.connection.lock().await;
+        pipe.query_async(&mut *conn)
+            .await
+            .map_err(|e| ClassifyError::StorageError(format!("Failed to read tag counts: {}", e)))
+    }
+}
+
+/// Days that are compared: the recent window up to today and the baseline
+/// window right before it
+#[derive(Debug, Clone, Copy)]
+pub struct TrendWindow {
+    pub recent_days: u32,
+    pub baseline_days: u32,
+    /// Items a tag needs in the recent window to be trending
+    pub min_count: u64,
+}
+
+/// Tags used more in the recent window than in the baseline, the fastest rising
+/// first. A tag missing from the baseline counts as used once there, so new
+/// tags rank high without dividing by zero.
+pub fn rank(
+    recent: &HashMap<String, u64>,
+    baseline: &HashMap<String, u64>,
+    window: &TrendWindow,
+) -> Vec<TagTrend> {
+    let recent_days = f64::from(window.recent_days.max(1));
+    let baseline_days = f64::from(window.baseline_days.max(1));
+
+    let mut trends: Vec<TagTrend> = recent
+        .iter()
+        .filter(|(_, count)| **count >= window.min_count.max(1))
+        .map(|(tag, count)| {
+            let before = baseline.get(tag).copied().unwrap_or_default();
+            let recent_rate = *count as f64 / recent_days;
+            let baseline_rate = before.max(1) as f64 / baseline_days;
+            TagTrend {
+                tag: tag.clone(),
+                recent: *count,
+                baseline: before,
+                change: recent_rate / baseline_rate,
+            }
+        })
+        .filter(|trend| trend.change > 1.0)
+        .collect();
+
+    trends.sort_by(|a, b| {
+        b.change
+            .total_cmp(&a.change)
+            .then(b.recent.cmp(&a.recent))
+            .then(a.tag.cmp(&b.tag))
+    });
+    trends
+}
+
+/// Per-tag daily counters of classified content, compared over time to find
+/// rising topics
+pub struct TagTrends {
+    store: Arc<dyn TrendStore>,
+    retention_days: u32,
+}
+
+impl TagTrends {
+    pub fn new(store: Arc<dyn TrendStore>, retention_days: u32) -> Self {
+        Self {
+            store,
+            retention_days,
+        }
+    }
+
+    /// Days the counters are kept, the recent and baseline windows together
+    /// can't be longer
+    pub fn retention_days(&self) -> u32 {
+        self.retention_days
+    }
+
+    /// Count the tags of content classified at `at`, for all content and for
+    /// its tenant. Failures are logged, they don't fail the classification.
+    pub async fn record(&self, namespace: Option<&str>, tags: &[String], at: DateTime<Utc>) {
+        if tags.is_empty() {
+            return;
+        }
+
+        let day = at.date_naive();
+        for scope in std::iter::once(ALL_SCOPE).chain(namespace) {
+            if let Err(e) = self.store.increment(scope, day, tags).await {
+                warn!("Failed to count tags for trends: {}", e);
+            }
+        }
+    }
+
+    /// Trending tags of a tenant, or of all content without a namespace
+    pub async fn trending(
+        &self,
+        namespace: Option<&str>,
+        window: &TrendWindow,
+        today: NaiveDate,
+    ) -> ClassifyResult<Vec<TagTrend>> {
+        let recent_days = window.recent_days.max(1);
+        let total_days = recent_days + window.baseline_days.max(1);
+        let days: Vec<NaiveDate> = (0..total_days)
+            .map(|ago| today - Days::new(ago.into()))
+            .collect();
+
+        let counts = self
+            .store
+            .counts(namespace.unwrap_or(ALL_SCOPE), &days)
+            .await?;
+
+        let mut recent: HashMap<String, u64> = HashMap::new();
+        let mut baseline: HashMap<String, u64> = HashMap::new();
+        for (ago, day_counts) in counts.into_iter().enumerate() {
+            let window_counts = if ago < recent_days as usize {
+                &mut recent
+            } else {
+                &mut baseline
+            };
+            for (tag, count) in day_counts {
+                *window_counts.entry(tag).or_default() += count;
+            }
+        }
+
+        Ok(rank(&recent, &baseline, window))
+    }
+}
+
+/// Create the tag trend counters configured by `TREND_STORE`. Counters in
+/// memory start from the content already stored.
+pub async fn create_tag_trends(
+    config: &AppConfig,
+    content_storage: &dyn ContentStorage,
+) -> ClassifyResult<Option<TagTrends>> {
+    let Some(trends) = &config.ingest.trends else {
+        return Ok(None);
+    };
+
+    let tag_trends = match trends.store {
+        TrendStoreType::Memory => {
+            let tag_trends = TagTrends::new(
+                Arc::new(MemoryTrendStore::new(trends.retention_days)),
+                trends.retention_days,
+            );
+            seed(&tag_trends, trends, content_storage).await?;
+            tag_trends
+        }
+        #[cfg(feature = "redis")]
+        TrendStoreType::Redis => TagTrends::new(
+            Arc::new(RedisTrendStore::with_connection(
+                crate::storage::redis::connect_tag_storage(&config.tag_storage).await?,
+                config.tag_storage.redis_prefix.as_deref(),
+                trends.retention_days,
+            )),
+            trends.retention_days,
+        ),
+        #[cfg(not(feature = "redis"))]
+        TrendStoreType::Redis => {
+            return Err(ClassifyError::ConfigError(
+                "The Redis trend store requires building with the redis feature".to_string(),
+            ))
+        }
+    };
+
+    Ok(Some(tag_trends))
+}
+
+/// Count the content stored within the retention period
+async fn seed(
+    tag_trends: &TagTrends,
+    config: &TrendsConfig,
+    content_storage: &dyn ContentStorage,
+) -> ClassifyResult<()> {
+    let since = Utc::now() - chrono::Duration::days(config.retention_days.into());
+    let mut seeded = 0;
+    for content in content_storage.list().await? {
+        if content.created_at > since {
+            tag_trends
+                .record(
+                    content.namespace.as_deref(),
+                    &content.tags,
+                    content.created_at,
+                )
+                .await;
+            seeded += 1;
+        }
+    }
+
+    info!("Counted the tags of {} items for trends", seeded);
+    Ok(())
+}
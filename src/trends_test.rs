@@ -0,0 +1,102 @@
+use crate::trends::{rank, MemoryTrendStore, TagTrends, TrendStore, TrendWindow};
+use chrono::{Days, NaiveDate, TimeZone, Utc};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counts(entries: &[(&str, u64)]) -> HashMap<String, u64> {
+        entries
+            .iter()
+            .map(|(tag, count)| (tag.to_string(), *count))
+            .collect()
+    }
+
+    fn tags(tags: &[&str]) -> Vec<String> {
+        tags.iter().map(|tag| tag.to_string()).collect()
+    }
+
+    const WINDOW: TrendWindow = TrendWindow {
+        recent_days: 7,
+        baseline_days: 28,
+        min_count: 2,
+    };
+
+    #[test]
+    fn test_rank_orders_by_rising_rate() {
+        let recent = counts(&[("rust", 7), ("python", 4), ("go", 3), ("java", 1)]);
+        let baseline = counts(&[("rust", 28), ("python", 4), ("java", 0)]);
+
+        let trends = rank(&recent, &baseline, &WINDOW);
+        let ranked: Vec<&str> = trends.iter().map(|trend| trend.tag.as_str()).collect();
+
+        // rust kept its pace of one a day, java is below the minimum count
+        assert_eq!(ranked, vec!["go", "python"]);
+        assert_eq!(trends[0].baseline, 0);
+        assert!((trends[0].change - 12.0).abs() < 1e-9);
+        assert_eq!(trends[1].recent, 4);
+        assert!((trends[1].change - 4.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_rank_without_recent_content_is_empty() {
+        let trends = rank(&HashMap::new(), &counts(&[("rust", 10)]), &WINDOW);
+        assert!(trends.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_trending_splits_recent_and_baseline_days() {
+        let store = Arc::new(MemoryTrendStore::new(90));
+        let trends = TagTrends::new(store.clone(), 90);
+        let today = Utc::now().date_naive();
+        let at = |ago: u64| {
+            let day: NaiveDate = today - Days::new(ago);
+            Utc.from_utc_datetime(&day.and_hms_opt(12, 0, 0).unwrap())
+        };
+
+        trends.record(None, &tags(&["rust", "ai"]), at(0)).await;
+        trends.record(None, &tags(&["ai"]), at(3)).await;
+        trends.record(Some("acme"), &tags(&["ai"]), at(6)).await;
+        trends.record(None, &tags(&["rust"]), at(10)).await;
+        trends.record(None, &tags(&["rust"]), at(20)).await;
+
+        let window = TrendWindow {
+            recent_days: 7,
+            baseline_days: 28,
+            min_count: 1,
+        };
+        let all = trends.trending(None, &window, today).await.unwrap();
+        let ranked: Vec<(&str, u64, u64)> = all
+            .iter()
+            .map(|trend| (trend.tag.as_str(), trend.recent, trend.baseline))
+            .collect();
+        assert_eq!(ranked, vec![("ai", 3, 0), ("rust", 1, 2)]);
+        assert!((all[1].change - 2.0).abs() < 1e-9);
+
+        // A tenant only sees its own counts
+        let tenant = trends.trending(Some("acme"), &window, today).await.unwrap();
+        assert_eq!(tenant.len(), 1);
+        assert_eq!(tenant[0].recent, 1);
+        assert!(trends
+            .trending(Some("other"), &window, today)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_memory_store_drops_days_past_retention() {
+        let store = MemoryTrendStore::new(30);
+        let today = Utc::now().date_naive();
+        let old = today - Days::new(40);
+
+        store.increment("*", old, &tags(&["rust"])).await.unwrap();
+        store.increment("*", today, &tags(&["rust"])).await.unwrap();
+
+        let counted = store.counts("*", &[old, today]).await.unwrap();
+        assert!(counted[0].is_empty());
+        assert_eq!(counted[1].get("rust"), Some(&1));
+    }
+}
@@ -0,0 +1,152 @@
+use chrono::{DateTime, Datelike, TimeZone, Utc};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::warn;
+
+use crate::storage::UsageStorage;
+use crate::{AccountUsage, ClassifyResult, Usage, UsageQuota};
+
+tokio::task_local! {
+    /// Usage recorded while handling the current request
+    static RECORDED: Arc<Mutex<Usage>>;
+}
+
+/// Count usage towards the request being handled. Does nothing outside of
+/// [`track`], e.g. for background jobs.
+pub fn record(usage: Usage) {
+    let _ = RECORDED.try_with(|recorded| {
+        recorded
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .add(&usage)
+    });
+}
+
+/// Run `future`, returning its output with the usage it recorded
+pub async fn track<F: Future>(future: F) -> (F::Output, Usage) {
+    let recorded = Arc::new(Mutex::new(Usage::default()));
+    let output = RECORDED.scope(recorded.clone(), future).await;
+    let usage = *recorded.lock().unwrap_or_else(|e| e.into_inner());
+    (output, usage)
+}
+
+/// Month that usage is counted in, as `YYYY-MM`
+pub fn month_of(time: DateTime<Utc>) -> String {
+    time.format("%Y-%m").to_string()
+}
+
+pub fn current_month() -> String {
+    month_of(Utc::now())
+}
+
+/// Time until the monthly counters start over
+pub fn until_next_month(now: DateTime<Utc>) -> Duration {
+    let (year, month) = match now.month() {
+        12 => (now.year() + 1, 1),
+        month => (now.year(), month + 1),
+    };
+
+    Utc.with_ymd_and_hms(year, month, 1, 0, 0, 0)
+        .single()
+        .and_then(|next| (next - now).to_std().ok())
+        .unwrap_or_default()
+}
+
+/// Counts the usage of each account and checks it against the monthly quotas
+pub struct UsageTracker {
+    storage: Arc<dyn UsageStorage>,
+    quota: UsageQuota,
+    tenant_quotas: HashMap<String, UsageQuota>,
+}
+
+impl UsageTracker {
+    pub fn new(
+        storage: Arc<dyn UsageStorage>,
+        quota: UsageQuota,
+        tenant_quotas: HashMap<String, UsageQuota>,
+    ) -> Self {
+        Self {
+            storage,
+            quota,
+            tenant_quotas,
+        }
+    }
+
+    /// Quota of an account, a tenant's own limits replace those of the default quota
+    pub fn quota_for(&self, namespace: Option<&str>) -> UsageQuota {
+        let Some(tenant) = namespace.and_then(|namespace| self.tenant_quotas.get(namespace)) else {
+            return self.quota;
+        };
+
+        UsageQuota {
+            classifications: tenant.classifications.or(self.quota.classifications),
+            stored_bytes: tenant.stored_bytes.or(self.quota.stored_bytes),
+            tokens: tenant.tokens.or(self.quota.tokens),
+        }
+    }
+
+    /// The limit of the quota that the account has reached this month, if any
+    pub async fn exceeded(
+        &self,
+        account: &str,
+        namespace: Option<&str>,
+    ) -> ClassifyResult<Option<&'static str>> {
+        let quota = self.quota_for(namespace);
+        if quota == UsageQuota::default() {
+            return Ok(None);
+        }
+
+        let usage = self.storage.get(account, &current_month()).await?;
+        Ok(quota.exceeded_by(&usage))
+    }
+
+    /// Run `future` outside of the request that asked for it, like a URL of an
+    /// import, counting the usage it records towards `account`
+    pub async fn count<F: Future>(&self, account: &str, future: F) -> F::Output {
+        let (output, usage) = track(future).await;
+        if let Err(e) = self.add(account, &usage).await {
+            warn!("Failed to record usage for {}: {}", account, e);
+        }
+        output
+    }
+
+    pub async fn add(&self, account: &str, usage: &Usage) -> ClassifyResult<()> {
+        if usage.is_empty() {
+            return Ok(());
+        }
+
+        self.storage.add(account, &current_month(), usage).await
+    }
+
+    /// Usage of the accounts in a month, of all accounts without a filter
+    pub async fn report(
+        &self,
+        month: &str,
+        account: Option<&str>,
+    ) -> ClassifyResult<Vec<AccountUsage>> {
+        let mut usage = match account {
+            Some(account) => {
+                HashMap::from([(account.to_string(), self.storage.get(account, month).await?)])
+            }
+            None => self.storage.list(month).await?,
+        };
+
+        let mut accounts: Vec<AccountUsage> = usage
+            .drain()
+            .map(|(account, usage)| {
+                let namespace = account.strip_prefix("tenant:");
+                let quota = (account != "admin").then(|| self.quota_for(namespace));
+                AccountUsage {
+                    account,
+                    usage,
+                    quota,
+                }
+            })
+            .collect();
+        accounts.sort_by(|a, b| a.account.cmp(&b.account));
+
+        Ok(accounts)
+    }
+}
@@ -0,0 +1,123 @@
+use crate::storage::usage::memory::MemoryUsageStorage;
+use crate::usage::{month_of, record, track, until_next_month, UsageTracker};
+use crate::{ClassifyResult, Usage, UsageQuota};
+use chrono::{TimeZone, Utc};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracker() -> UsageTracker {
+        let quota = UsageQuota {
+            classifications: Some(2),
+            stored_bytes: None,
+            tokens: Some(1000),
+        };
+        let tenant_quotas = HashMap::from([(
+            "acme".to_string(),
+            UsageQuota {
+                classifications: Some(10),
+                ..UsageQuota::default()
+            },
+        )]);
+        UsageTracker::new(Arc::new(MemoryUsageStorage::new()), quota, tenant_quotas)
+    }
+
+    #[tokio::test]
+    async fn test_track_collects_recorded_usage() {
+        let ((), usage) = track(async {
+            record(Usage {
+                classifications: 1,
+                stored_bytes: 10,
+                tokens: 0,
+            });
+            record(Usage {
+                tokens: 42,
+                ..Usage::default()
+            });
+        })
+        .await;
+
+        assert_eq!(usage.classifications, 1);
+        assert_eq!(usage.stored_bytes, 10);
+        assert_eq!(usage.tokens, 42);
+
+        // Outside of a tracked request usage is not counted anywhere
+        record(Usage {
+            tokens: 1,
+            ..Usage::default()
+        });
+    }
+
+    #[test]
+    fn test_tenant_quota_falls_back_to_default_limits() {
+        let tracker = tracker();
+
+        let quota = tracker.quota_for(Some("acme"));
+        assert_eq!(quota.classifications, Some(10));
+        assert_eq!(quota.tokens, Some(1000));
+
+        assert_eq!(tracker.quota_for(None).classifications, Some(2));
+        assert_eq!(tracker.quota_for(Some("other")).classifications, Some(2));
+    }
+
+    #[tokio::test]
+    async fn test_exceeded_after_quota_is_used() -> ClassifyResult<()> {
+        let tracker = tracker();
+        let classification = Usage {
+            classifications: 1,
+            ..Usage::default()
+        };
+
+        tracker.add("key:1", &classification).await?;
+        assert_eq!(tracker.exceeded("key:1", None).await?, None);
+
+        tracker.add("key:1", &classification).await?;
+        assert_eq!(
+            tracker.exceeded("key:1", None).await?,
+            Some("classifications")
+        );
+
+        // Tenants with a higher limit of their own are not affected
+        tracker.add("tenant:acme", &classification).await?;
+        tracker.add("tenant:acme", &classification).await?;
+        assert_eq!(tracker.exceeded("tenant:acme", Some("acme")).await?, None);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_report_includes_quota_except_for_admin() -> ClassifyResult<()> {
+        let tracker = tracker();
+        let usage = Usage {
+            tokens: 5,
+            ..Usage::default()
+        };
+        tracker.add("admin", &usage).await?;
+        tracker.add("tenant:acme", &usage).await?;
+
+        let month = crate::usage::current_month();
+        let accounts = tracker.report(&month, None).await?;
+        assert_eq!(accounts.len(), 2);
+        assert_eq!(accounts[0].account, "admin");
+        assert!(accounts[0].quota.is_none());
+        assert_eq!(
+            accounts[1].quota.and_then(|quota| quota.classifications),
+            Some(10)
+        );
+
+        let own = tracker.report(&month, Some("key:unknown")).await?;
+        assert_eq!(own.len(), 1);
+        assert!(own[0].usage.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_months() {
+        let time = Utc.with_ymd_and_hms(2024, 12, 31, 23, 0, 0).unwrap();
+        assert_eq!(month_of(time), "2024-12");
+        assert_eq!(until_next_month(time), Duration::from_secs(3600));
+    }
+}
@@ -0,0 +1,48 @@
+use url::Url;
+
+use crate::{ClassifyError, ClassifyResult};
+
+/// Query parameters that only carry tracking information
+const TRACKING_PARAMS: &[&str] = &["fbclid", "gclid", "dclid", "msclkid", "mc_cid", "mc_eid"];
+
+fn is_tracking_param(name: &str) -> bool {
+    let name = name.to_lowercase();
+    name.starts_with("utm_") || TRACKING_PARAMS.contains(&name.as_str())
+}
+
+/// Canonicalize a URL so that trivially different links to the same page
+/// produce the same content hash.
+///
+/// Strips the fragment and tracking parameters, lowercases the host and
+/// removes a trailing slash from non-root paths.
+pub fn canonicalize_url(input: &str) -> ClassifyResult<String> {
+    let mut url = Url::parse(input.trim())
+        .map_err(|e| ClassifyError::UrlError(format!("Invalid URL: {}", e)))?;
+
+    url.set_fragment(None);
+
+    if let Some(host) = url.host_str() {
+        let host = host.to_lowercase();
+        url.set_host(Some(&host))
+            .map_err(|e| ClassifyError::UrlError(format!("Invalid URL host: {}", e)))?;
+    }
+
+    let params: Vec<(String, String)> = url
+        .query_pairs()
+        .filter(|(name, _)| !is_tracking_param(name))
+        .map(|(name, value)| (name.into_owned(), value.into_owned()))
+        .collect();
+
+    if params.is_empty() {
+        url.set_query(None);
+    } else {
+        url.query_pairs_mut().clear().extend_pairs(params);
+    }
+
+    let path = url.path().to_string();
+    if path.len() > 1 && path.ends_with('/') {
+        url.set_path(path.trim_end_matches('/'));
+    }
+
+    Ok(url.to_string())
+}
@@ -0,0 +1,41 @@
+use crate::web::canonical::canonicalize_url;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strips_tracking_params_and_fragment() {
+        let canonical =
+            canonicalize_url("https://example.com/post?utm_source=x&utm_medium=y#comments")
+                .unwrap();
+        assert_eq!(canonical, "https://example.com/post");
+
+        let canonical = canonicalize_url("https://example.com/post?id=42&fbclid=abc").unwrap();
+        assert_eq!(canonical, "https://example.com/post?id=42");
+    }
+
+    #[test]
+    fn test_normalizes_host_and_trailing_slash() {
+        let canonical = canonicalize_url("https://EXAMPLE.com/docs/").unwrap();
+        assert_eq!(canonical, "https://example.com/docs");
+
+        let root = canonicalize_url("https://example.com").unwrap();
+        assert_eq!(root, "https://example.com/");
+    }
+
+    #[test]
+    fn test_equivalent_urls_share_hash() {
+        let a = canonicalize_url("https://example.com/post?utm_source=x").unwrap();
+        let b = canonicalize_url("https://Example.com/post/").unwrap();
+        assert_eq!(
+            crate::Content::generate_hash(&a),
+            crate::Content::generate_hash(&b)
+        );
+    }
+
+    #[test]
+    fn test_invalid_url() {
+        assert!(canonicalize_url("http://").is_err());
+    }
+}
@@ -0,0 +1,64 @@
+use reqwest::header::CONTENT_TYPE;
+use url::Url;
+
+use crate::{ClassifyError, ClassifyResult};
+
+/// Raw body of a fetched URL and the content type the server declared for it
+#[derive(Debug, Clone)]
+pub struct FetchedDocument {
+    pub body: Vec<u8>,
+    pub content_type: Option<String>,
+}
+
+async fn get(client: &reqwest::Client, url: &str) -> ClassifyResult<reqwest::Response> {
+    let url =
+        Url::parse(url).map_err(|e| ClassifyError::UrlError(format!("Invalid URL: {}", e)))?;
+
+    let response = client
+        .get(url.as_str())
+        .send()
+        .await
+        .map_err(|e| ClassifyError::HttpError(format!("Failed to fetch URL: {}", e)))?;
+
+    if !response.status().is_success() {
+        return Err(ClassifyError::HttpError(format!(
+            "Failed to fetch URL: HTTP status {}",
+            response.status()
+        )));
+    }
+
+    Ok(response)
+}
+
+/// Fetch a web page and return its body as text
+pub async fn fetch_page(client: &reqwest::Client, url: &str) -> ClassifyResult<String> {
+    get(client, url)
+        .await?
+        .text()
+        .await
+        .map_err(|e| ClassifyError::HttpError(format!("Failed to read response body: {}", e)))
+}
+
+/// Fetch a URL and return its raw body, which need not be text
+pub async fn fetch_document(
+    client: &reqwest::Client,
+    url: &str,
+) -> ClassifyResult<FetchedDocument> {
+    let response = get(client, url).await?;
+
+    let content_type = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(String::from);
+
+    let body = response
+        .bytes()
+        .await
+        .map_err(|e| ClassifyError::HttpError(format!("Failed to read response body: {}", e)))?;
+
+    Ok(FetchedDocument {
+        body: body.to_vec(),
+        content_type,
+    })
+}
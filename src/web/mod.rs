@@ -0,0 +1,11 @@
+pub mod canonical;
+pub mod fetch;
+pub mod sitemap;
+
+#[cfg(test)]
+mod canonical_test;
+#[cfg(test)]
+mod sitemap_test;
+
+pub use canonical::canonicalize_url;
+pub use fetch::{fetch_document, fetch_page, FetchedDocument};
@@ -0,0 +1,91 @@
+use regex::Regex;
+use std::collections::{HashSet, VecDeque};
+use std::sync::OnceLock;
+use tracing::{info, warn};
+
+use crate::web::fetch_page;
+use crate::ClassifyResult;
+
+/// Locations listed in a sitemap document
+#[derive(Debug, Default, PartialEq)]
+pub struct Sitemap {
+    /// Page URLs from `<url>` entries
+    pub pages: Vec<String>,
+    /// Nested sitemaps from a `<sitemapindex>`
+    pub sitemaps: Vec<String>,
+}
+
+fn unescape_xml(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Parse a sitemap or sitemap index document
+pub fn parse_sitemap(xml: &str) -> Sitemap {
+    static ENTRY: OnceLock<Regex> = OnceLock::new();
+    let entry = ENTRY.get_or_init(|| {
+        Regex::new(r"(?s)<(url|sitemap)\b[^>]*>.*?<loc>\s*(?:<!\[CDATA\[)?(.*?)(?:\]\]>)?\s*</loc>")
+            .unwrap()
+    });
+
+    let mut sitemap = Sitemap::default();
+    for captures in entry.captures_iter(xml) {
+        let location = unescape_xml(captures[2].trim());
+        if captures[1].eq_ignore_ascii_case("sitemap") {
+            sitemap.sitemaps.push(location);
+        } else {
+            sitemap.pages.push(location);
+        }
+    }
+
+    sitemap
+}
+
+/// Fetch a sitemap, following nested sitemap indexes, and return up to
+/// `max_pages` unique page URLs
+pub async fn fetch_sitemap_pages(
+    client: &reqwest::Client,
+    url: &str,
+    max_pages: usize,
+) -> ClassifyResult<Vec<String>> {
+    let mut queue = VecDeque::from([url.to_string()]);
+    let mut visited = HashSet::new();
+    let mut seen = HashSet::new();
+    let mut pages = Vec::new();
+
+    while let Some(sitemap_url) = queue.pop_front() {
+        if pages.len() >= max_pages || !visited.insert(sitemap_url.clone()) {
+            continue;
+        }
+
+        // Only the sitemap that was asked for must be reachable
+        let xml = match fetch_page(client, &sitemap_url).await {
+            Ok(xml) => xml,
+            Err(e) if sitemap_url != url => {
+                warn!("Skipping nested sitemap {}: {}", sitemap_url, e);
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+
+        let sitemap = parse_sitemap(&xml);
+        info!(
+            "Sitemap {} lists {} pages and {} nested sitemaps",
+            sitemap_url,
+            sitemap.pages.len(),
+            sitemap.sitemaps.len()
+        );
+
+        queue.extend(sitemap.sitemaps);
+        for page in sitemap.pages {
+            if pages.len() < max_pages && seen.insert(page.clone()) {
+                pages.push(page);
+            }
+        }
+    }
+
+    Ok(pages)
+}
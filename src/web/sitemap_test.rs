@@ -0,0 +1,49 @@
+use crate::web::sitemap::parse_sitemap;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_urlset() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+  <url><loc>https://docs.example.com/</loc><lastmod>2024-01-01</lastmod></url>
+  <url>
+    <loc>
+      https://docs.example.com/guide?a=1&amp;b=2
+    </loc>
+  </url>
+</urlset>"#;
+
+        let sitemap = parse_sitemap(xml);
+
+        assert_eq!(
+            sitemap.pages,
+            vec![
+                "https://docs.example.com/",
+                "https://docs.example.com/guide?a=1&b=2"
+            ]
+        );
+        assert!(sitemap.sitemaps.is_empty());
+    }
+
+    #[test]
+    fn test_parse_sitemap_index() {
+        let xml = r#"<sitemapindex xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+  <sitemap><loc><![CDATA[https://example.com/sitemap-docs.xml]]></loc></sitemap>
+  <sitemap><loc>https://example.com/sitemap-blog.xml</loc></sitemap>
+</sitemapindex>"#;
+
+        let sitemap = parse_sitemap(xml);
+
+        assert!(sitemap.pages.is_empty());
+        assert_eq!(
+            sitemap.sitemaps,
+            vec![
+                "https://example.com/sitemap-docs.xml",
+                "https://example.com/sitemap-blog.xml"
+            ]
+        );
+    }
+}
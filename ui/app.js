@@ -0,0 +1,205 @@
+// Single-page UI for the classify API. Everything goes through the same
+// endpoints as other clients, authenticated with the key entered in the header.
+"use strict";
+
+const KEY_STORAGE = "classify.apiKey";
+
+const $ = (id) => document.getElementById(id);
+
+let allContent = [];
+let selected = null;
+
+function setStatus(message, isError = false) {
+  $("status").textContent = message;
+  $("status").className = isError ? "error" : "";
+}
+
+async function api(method, path, body) {
+  const headers = {};
+  const key = localStorage.getItem(KEY_STORAGE);
+  if (key) {
+    headers["X-Api-Key"] = key;
+  }
+  if (body !== undefined) {
+    headers["Content-Type"] = "application/json";
+  }
+
+  const response = await fetch(path, {
+    method,
+    headers,
+    body: body === undefined ? undefined : JSON.stringify(body),
+  });
+  const text = await response.text();
+  let data = text;
+  try {
+    data = JSON.parse(text);
+  } catch (e) {
+    // Content text is returned as plain text
+  }
+
+  if (!response.ok) {
+    const error = (data && data.error) || text || response.statusText;
+    throw new Error(`${error} (${response.status})`);
+  }
+  return data;
+}
+
+function renderTagCloud(counts) {
+  const cloud = $("tag-cloud");
+  cloud.replaceChildren();
+
+  const entries = Object.entries(counts).sort(([a], [b]) => a.localeCompare(b));
+  const max = Math.max(1, ...entries.map(([, count]) => count));
+  for (const [tag, count] of entries) {
+    const link = document.createElement("a");
+    link.textContent = tag;
+    link.title = `${count} item${count === 1 ? "" : "s"}`;
+    link.style.fontSize = `${0.8 + (count / max) * 0.9}rem`;
+    link.addEventListener("click", () => showTag(tag));
+    cloud.append(link);
+  }
+  if (entries.length === 0) {
+    cloud.textContent = "No tags yet";
+  }
+}
+
+function renderContent(title, items) {
+  $("results-title").textContent = `${title} (${items.length})`;
+  const list = $("content-list");
+  list.replaceChildren();
+
+  for (const item of items) {
+    const entry = document.createElement("li");
+    // Pages are listed by their title, the URL shows on hover
+    entry.textContent = item.title || item.content;
+    entry.title = item.content;
+    entry.classList.toggle("selected", selected !== null && selected.id === item.id);
+
+    const tags = document.createElement("div");
+    tags.className = "tags";
+    tags.textContent = item.tags.join(", ");
+    entry.append(tags);
+
+    entry.addEventListener("click", () => showPreview(item));
+    list.append(entry);
+  }
+}
+
+async function loadTags() {
+  const response = await api("GET", "/tags/counts");
+  renderTagCloud(response.counts);
+}
+
+async function loadContent() {
+  const response = await api("GET", "/content");
+  allContent = response.items;
+  renderContent("Recent content", allContent);
+}
+
+async function refresh() {
+  try {
+    await Promise.all([loadTags(), loadContent()]);
+    setStatus("");
+  } catch (e) {
+    setStatus(e.message, true);
+  }
+}
+
+async function showTag(tag) {
+  try {
+    const response = await api("GET", `/query?tags=${encodeURIComponent(tag)}`);
+    renderContent(`Tagged ${tag}`, response.items);
+  } catch (e) {
+    setStatus(e.message, true);
+  }
+}
+
+function search(query) {
+  const terms = query.toLowerCase().split(/\s+/).filter(Boolean);
+  if (terms.length === 0) {
+    renderContent("Recent content", allContent);
+    return;
+  }
+
+  const matches = allContent.filter((item) => {
+    const text = `${item.content} ${item.tags.join(" ")}`.toLowerCase();
+    return terms.every((term) => text.includes(term));
+  });
+  renderContent(`Matching "${query}"`, matches);
+}
+
+async function showPreview(item) {
+  selected = item;
+  $("preview").hidden = false;
+  $("preview-meta").textContent =
+    `${item.site_name ? `${item.site_name}, ` : ""}${item.content_type || "unknown type"}, classified ${new Date(item.created_at).toLocaleString()}`;
+  $("preview-tags").value = item.tags.join(", ");
+  $("preview-text").textContent = "Loading...";
+  document.querySelectorAll("#content-list li").forEach((entry) => {
+    entry.classList.toggle("selected", entry.title === item.content);
+  });
+
+  try {
+    $("preview-text").textContent = await api("GET", `/content/${item.id}`);
+  } catch (e) {
+    $("preview-text").textContent = "";
+    setStatus(e.message, true);
+  }
+}
+
+async function saveTags(event) {
+  event.preventDefault();
+  if (selected === null) {
+    return;
+  }
+
+  const tags = $("preview-tags").value.split(",").map((tag) => tag.trim()).filter(Boolean);
+  try {
+    const response = await api("PUT", `/content/${selected.id}/tags`, { tags });
+    selected = response.content;
+    $("preview-tags").value = selected.tags.join(", ");
+    setStatus("Tags saved");
+    await Promise.all([loadTags(), loadContent()]);
+  } catch (e) {
+    setStatus(e.message, true);
+  }
+}
+
+async function classify(event) {
+  event.preventDefault();
+  const content = $("classify-input").value.trim();
+  if (content === "") {
+    return;
+  }
+
+  setStatus("Classifying...");
+  try {
+    const response = await api("POST", "/classify", { content });
+    $("classify-input").value = "";
+    if (response.content) {
+      setStatus(`Classified as ${response.content.tags.join(", ")}`);
+      await Promise.all([loadTags(), loadContent()]);
+      showPreview(response.content);
+    } else {
+      setStatus(`Queued as job ${response.id}`);
+    }
+  } catch (e) {
+    setStatus(e.message, true);
+  }
+}
+
+$("key-form").addEventListener("submit", (event) => {
+  event.preventDefault();
+  localStorage.setItem(KEY_STORAGE, $("api-key").value.trim());
+  refresh();
+});
+$("search-form").addEventListener("submit", (event) => {
+  event.preventDefault();
+  search($("search").value);
+});
+$("search").addEventListener("input", () => search($("search").value));
+$("tags-form").addEventListener("submit", saveTags);
+$("classify-form").addEventListener("submit", classify);
+
+$("api-key").value = localStorage.getItem(KEY_STORAGE) || "";
+refresh();